@@ -1,11 +1,18 @@
 use anyhow::{anyhow, bail, Context, Result};
 use bkt_common::archive::{self, detect_archive_type, ArchiveType};
-use bkt_common::checksum::sha256_hex;
-use bkt_common::http::download;
-use bkt_common::manifest::{InstallConfig, UpstreamManifest};
-use std::path::Path;
+use bkt_common::checksum;
+use bkt_common::http::{download_with_retry, RetryPolicy};
+use bkt_common::manifest::{InstallConfig, Upstream, UpstreamManifest};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-pub fn run(name: &str, manifest_path: &Path) -> Result<()> {
+pub fn run(
+    name: &str,
+    manifest_path: &Path,
+    keys_dir: &Path,
+    skip_signature: bool,
+    retry: &RetryPolicy,
+) -> Result<()> {
     let manifest =
         UpstreamManifest::load_from(manifest_path).context("failed to load upstream manifest")?;
 
@@ -28,17 +35,30 @@ pub fn run(name: &str, manifest_path: &Path) -> Result<()> {
         .ok_or_else(|| anyhow!("no pinned.url for '{}' — run bkt upstream pin first", name))?;
 
     eprintln!("Downloading {} from {}", name, url);
-    let data = download(url).with_context(|| format!("failed to download {}", name))?;
-
-    eprintln!("Verifying SHA256...");
-    let actual = sha256_hex(&data);
-    if actual != upstream.pinned.sha256 {
-        bail!(
-            "SHA256 mismatch for {}: expected {}, got {}",
-            name,
-            upstream.pinned.sha256,
-            actual
-        );
+    let urls = download_urls(url, upstream);
+    // A mirror serving the wrong bytes is skipped like one that 404s
+    let expected = upstream.pinned.expected_checksum();
+    let data = download_with_retry(&urls, retry, |data| checksum::verify(data, expected))
+        .with_context(|| format!("failed to download {}", name))?;
+    eprintln!(
+        "Verified {}",
+        upstream.pinned.algorithm().name().to_uppercase()
+    );
+
+    if let Some(signature_url) = upstream.resolved_signature_url() {
+        if skip_signature {
+            eprintln!("warning: skipping GPG signature verification for {}", name);
+        } else {
+            let key = read_key(upstream, keys_dir)?;
+            eprintln!("Downloading signature from {}", signature_url);
+            let signature = download_with_retry(&[signature_url], retry, |_| Ok(()))
+                .with_context(|| format!("failed to download signature for {}", name))?;
+
+            eprintln!("Verifying GPG signature...");
+            verify_signature(name, &data, &signature, &key)?;
+        }
+    } else if upstream.gpg_key.is_some() {
+        bail!("upstream '{}' has a gpg_key but no signature_url", name);
     }
 
     match upstream.install.as_ref().unwrap() {
@@ -60,6 +80,13 @@ pub fn run(name: &str, manifest_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// The pinned URL followed by the upstream's mirrors.
+fn download_urls(url: &str, upstream: &Upstream) -> Vec<String> {
+    std::iter::once(url.to_string())
+        .chain(upstream.resolved_mirrors())
+        .collect()
+}
+
 fn install_binary(data: &[u8], url: &str, install_path: &str) -> Result<()> {
     let path = Path::new(install_path);
     let parent = path.parent().unwrap_or_else(|| Path::new("."));
@@ -90,6 +117,7 @@ fn install_binary(data: &[u8], url: &str, install_path: &str) -> Result<()> {
             }
         }
         ArchiveType::TarXz => bail!("tar.xz not expected for binary install"),
+        ArchiveType::TarZst => bail!("tar.zst not expected for binary install"),
         ArchiveType::Raw => {
             std::fs::write(path, data)?;
         }
@@ -106,6 +134,7 @@ fn install_archive(data: &[u8], url: &str, extract_to: &str, strip_components: u
     match archive_type {
         ArchiveType::TarGz => archive::extract_tar_gz(data, target, strip_components)?,
         ArchiveType::TarXz => archive::extract_tar_xz(data, target, strip_components)?,
+        ArchiveType::TarZst => archive::extract_tar_zst(data, target, strip_components)?,
         ArchiveType::Zip => {
             if strip_components != 0 {
                 bail!(
@@ -119,3 +148,190 @@ fn install_archive(data: &[u8], url: &str, extract_to: &str, strip_components: u
     }
     Ok(())
 }
+
+/// The armored public key for an upstream, read from `keys_dir` when the
+/// manifest references a repo path rather than embedding the key.
+fn read_key(upstream: &Upstream, keys_dir: &Path) -> Result<String> {
+    let key = upstream.gpg_key.as_ref().ok_or_else(|| {
+        anyhow!(
+            "upstream '{}' has a signature_url but no gpg_key",
+            upstream.name
+        )
+    })?;
+
+    match upstream.gpg_key_path() {
+        Some(path) => {
+            let path = key_path(keys_dir, path);
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read GPG key {}", path.display()))
+        }
+        None => Ok(key.clone()),
+    }
+}
+
+fn key_path(keys_dir: &Path, key: &str) -> PathBuf {
+    keys_dir.join(key.trim_start_matches("./"))
+}
+
+/// Verify a detached signature against a keyring holding only `key`.
+fn verify_signature(name: &str, data: &[u8], signature: &[u8], key: &str) -> Result<()> {
+    let keyring = EphemeralKeyring::new(name)?;
+    let key_file = keyring.write("key.asc", key.as_bytes())?;
+    let data_file = keyring.write("artifact", data)?;
+    let signature_file = keyring.write("artifact.sig", signature)?;
+
+    keyring
+        .gpg(&["--import".as_ref(), key_file.as_os_str()])
+        .with_context(|| format!("failed to import GPG key for {}", name))?;
+    keyring
+        .gpg(&[
+            "--verify".as_ref(),
+            signature_file.as_os_str(),
+            data_file.as_os_str(),
+        ])
+        .with_context(|| format!("GPG signature verification failed for {}", name))?;
+
+    Ok(())
+}
+
+/// A throwaway GNUPGHOME, removed on drop, so verification never trusts or
+/// pollutes the builder's own keyring.
+struct EphemeralKeyring {
+    home: PathBuf,
+}
+
+impl EphemeralKeyring {
+    fn new(name: &str) -> Result<Self> {
+        let home =
+            std::env::temp_dir().join(format!("bkt-build-gpg-{}-{}", name, std::process::id()));
+        if home.exists() {
+            std::fs::remove_dir_all(&home)?;
+        }
+        std::fs::create_dir_all(&home)
+            .with_context(|| format!("failed to create {}", home.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&home, std::fs::Permissions::from_mode(0o700))?;
+        }
+
+        Ok(Self { home })
+    }
+
+    fn write(&self, file: &str, contents: &[u8]) -> Result<PathBuf> {
+        let path = self.home.join(file);
+        std::fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    fn gpg(&self, args: &[&std::ffi::OsStr]) -> Result<()> {
+        let output = Command::new("gpg")
+            .arg("--batch")
+            .arg("--no-tty")
+            .arg("--homedir")
+            .arg(&self.home)
+            .args(args)
+            .output()
+            .context("could not execute gpg")?;
+
+        if !output.status.success() {
+            bail!(
+                "gpg exited with status {}:\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EphemeralKeyring {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.home);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bkt_common::manifest::{PinnedVersion, UpstreamSource};
+
+    fn signed_upstream(gpg_key: Option<&str>) -> Upstream {
+        Upstream {
+            name: "keyd".to_string(),
+            description: None,
+            source: UpstreamSource::Url {
+                url: "https://example.com/keyd-{version}.tar.gz".to_string(),
+            },
+            pinned: PinnedVersion {
+                version: "2.5.0".to_string(),
+                commit: None,
+                url: None,
+                sha256: "abc123".to_string(),
+                checksum: None,
+                gpg_verified: false,
+                pinned_at: chrono::Utc::now(),
+            },
+            install: None,
+            signature_url: Some("https://example.com/keyd-{version}.tar.gz.asc".to_string()),
+            gpg_key: gpg_key.map(str::to_string),
+            arches: Vec::new(),
+            mirrors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_download_urls_put_mirrors_after_pinned_url() {
+        let mut upstream = signed_upstream(None);
+        upstream.mirrors = vec!["https://mirror.example.com/keyd-{version}.tar.gz".to_string()];
+        assert_eq!(
+            download_urls("https://example.com/keyd-2.5.0.tar.gz", &upstream),
+            vec![
+                "https://example.com/keyd-2.5.0.tar.gz",
+                "https://mirror.example.com/keyd-2.5.0.tar.gz",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_url_substitutes_version() {
+        let upstream = signed_upstream(Some("upstream/keys/keyd.asc"));
+        assert_eq!(
+            upstream.resolved_signature_url().as_deref(),
+            Some("https://example.com/keyd-2.5.0.tar.gz.asc")
+        );
+    }
+
+    #[test]
+    fn test_read_key_inline() {
+        let armored = "-----BEGIN PGP PUBLIC KEY BLOCK-----\n\nmQINBF...\n";
+        let upstream = signed_upstream(Some(armored));
+        assert_eq!(upstream.gpg_key_path(), None);
+        assert_eq!(
+            read_key(&upstream, Path::new("/nonexistent")).unwrap(),
+            armored
+        );
+    }
+
+    #[test]
+    fn test_read_key_missing() {
+        let upstream = signed_upstream(None);
+        let err = read_key(&upstream, Path::new("/tmp")).unwrap_err();
+        assert!(err.to_string().contains("no gpg_key"));
+    }
+
+    #[test]
+    fn test_key_path_resolves_under_keys_dir() {
+        let keys_dir = Path::new("/tmp/upstream-keys");
+        assert_eq!(
+            key_path(keys_dir, "upstream/keys/keyd.asc"),
+            Path::new("/tmp/upstream-keys/upstream/keys/keyd.asc")
+        );
+        assert_eq!(
+            key_path(keys_dir, "./upstream/keys/keyd.asc"),
+            Path::new("/tmp/upstream-keys/upstream/keys/keyd.asc")
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
+use bkt_common::http::RetryPolicy;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
 mod fetch;
 mod repos;
+mod rpm_deps;
 mod vendor_artifacts;
 
 #[derive(Parser)]
@@ -12,6 +14,10 @@ mod vendor_artifacts;
     about = "Build-time helper for bootc Containerfile"
 )]
 struct Cli {
+    /// Attempts per URL for downloads that fail transiently (timeouts, 5xx)
+    #[arg(long, global = true, default_value_t = 3)]
+    attempts: u32,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -25,6 +31,12 @@ enum Commands {
         /// Path to upstream manifest
         #[arg(long, default_value = "/tmp/upstream-manifest.json")]
         manifest: PathBuf,
+        /// Directory that repo-relative `gpg_key` paths resolve against
+        #[arg(long, default_value = "/tmp/upstream-keys")]
+        keys_dir: PathBuf,
+        /// Skip GPG signature verification (debugging only)
+        #[arg(long)]
+        skip_signature: bool,
     },
     /// Import GPG keys and write .repo files from external repos manifest
     SetupRepos {
@@ -64,15 +76,21 @@ enum Commands {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let retry = RetryPolicy::with_attempts(cli.attempts);
     match cli.command {
-        Commands::Fetch { name, manifest } => fetch::run(&name, &manifest),
-        Commands::SetupRepos { manifest } => repos::setup_repos(&manifest),
-        Commands::DownloadRpms { repo, manifest } => repos::download_rpms(&repo, &manifest),
+        Commands::Fetch {
+            name,
+            manifest,
+            keys_dir,
+            skip_signature,
+        } => fetch::run(&name, &manifest, &keys_dir, skip_signature, &retry),
+        Commands::SetupRepos { manifest } => repos::setup_repos(&manifest, &retry),
+        Commands::DownloadRpms { repo, manifest } => repos::download_rpms(&repo, &manifest, &retry),
         Commands::ResolveVendorArtifacts { manifest, output } => {
             vendor_artifacts::resolve(&manifest, &output)
         }
         Commands::InstallVendorArtifact { name, resolved } => {
-            vendor_artifacts::install(&name, &resolved)
+            vendor_artifacts::install(&name, &resolved, &retry)
         }
     }
 }
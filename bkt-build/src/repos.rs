@@ -1,9 +1,19 @@
 use anyhow::{anyhow, bail, Context, Result};
-use bkt_common::manifest::ExternalReposManifest;
+use bkt_common::gpg;
+use bkt_common::http::{self, RetryPolicy};
+use bkt_common::manifest::{ExternalRepo, ExternalReposManifest};
+
+use crate::rpm_deps;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn setup_repos(manifest_path: &Path) -> Result<()> {
+/// Where verified repo signing keys are written.
+const KEY_DIR: &str = "/etc/pki/rpm-gpg";
+
+/// dnf errors that no retry will fix.
+const PERMANENT_DNF_ERRORS: &[&str] = &["No match for argument", "No package"];
+
+pub fn setup_repos(manifest_path: &Path, retry: &RetryPolicy) -> Result<()> {
     let manifest = ExternalReposManifest::load_from(manifest_path)
         .context("failed to load external repos manifest")?;
 
@@ -16,25 +26,35 @@ pub fn setup_repos(manifest_path: &Path) -> Result<()> {
     }
 
     std::fs::create_dir_all("/etc/yum.repos.d").context("failed to create /etc/yum.repos.d")?;
+    std::fs::create_dir_all(KEY_DIR).with_context(|| format!("failed to create {}", KEY_DIR))?;
 
     for repo in &manifest.repos {
         validate_repo_line_value("display_name", &repo.display_name)?;
         validate_repo_line_value("baseurl", &repo.baseurl)?;
+        for mirror in &repo.mirrors {
+            validate_repo_line_value("mirrors", mirror)?;
+        }
         validate_repo_line_value("gpg_key", &repo.gpg_key)?;
+        let repo_file = repo_file_path(&repo.name)?;
 
+        // Import the key we checked, and point dnf at that copy rather than
+        // the URL, so nothing fetches an unchecked key later
+        let key = fetch_key(repo, retry)?;
+        let key_file = key_file_path(&repo.name);
+        std::fs::write(&key_file, &key)
+            .with_context(|| format!("failed to write {}", key_file.display()))?;
         run_command(
             "rpm",
-            vec!["--import".to_string(), repo.gpg_key.clone()],
+            vec!["--import".to_string(), key_file.display().to_string()],
             &format!("failed to import GPG key for repo '{}'", repo.name),
         )?;
 
-        let repo_file = repo_file_path(&repo.name)?;
         let content = format!(
-            "[{name}]\nname={display_name}\nbaseurl={baseurl}\nenabled=1\ngpgcheck=1\nrepo_gpgcheck=0\ngpgkey={gpg_key}\n",
+            "[{name}]\nname={display_name}\nbaseurl={baseurl}\nenabled=1\ngpgcheck=1\nrepo_gpgcheck=0\ngpgkey=file://{key_file}\n",
             name = repo.name,
             display_name = repo.display_name,
-            baseurl = repo.baseurl,
-            gpg_key = repo.gpg_key
+            baseurl = baseurls(repo),
+            key_file = key_file.display()
         );
         std::fs::write(&repo_file, content)
             .with_context(|| format!("failed to write {}", repo_file.display()))?;
@@ -44,7 +64,7 @@ pub fn setup_repos(manifest_path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn download_rpms(repo_name: &str, manifest_path: &Path) -> Result<()> {
+pub fn download_rpms(repo_name: &str, manifest_path: &Path, retry: &RetryPolicy) -> Result<()> {
     let manifest = ExternalReposManifest::load_from(manifest_path)
         .context("failed to load external repos manifest")?;
 
@@ -70,27 +90,158 @@ pub fn download_rpms(repo_name: &str, manifest_path: &Path) -> Result<()> {
         other => other,
     };
 
-    let mut args = vec![
-        "download".to_string(),
-        "--destdir".to_string(),
-        "/rpms".to_string(),
+    let repo_args = vec![
         "--disablerepo=*".to_string(),
         format!("--enablerepo={}", repo.name),
         format!("--arch={}", dnf_arch),
         "--arch=noarch".to_string(),
     ];
+
+    let mut args = vec![
+        "download".to_string(),
+        "--destdir".to_string(),
+        "/rpms".to_string(),
+    ];
+    args.extend(repo_args.iter().cloned());
     args.extend(repo.packages.iter().cloned());
 
-    run_command(
+    run_command_with_retry(
         "dnf",
-        args,
+        args.clone(),
         &format!("failed to download RPMs for repo '{}'", repo.name),
+        retry,
+    )?;
+
+    if !repo.resolve_deps {
+        return Ok(());
+    }
+
+    let mut query = vec![
+        "repoquery".to_string(),
+        "--latest-limit=1".to_string(),
+        format!("--queryformat={}", rpm_deps::REPOQUERY_FORMAT),
+    ];
+    query.extend(repo_args);
+    let metadata = run_command(
+        "dnf",
+        query,
+        &format!("failed to query metadata for repo '{}'", repo.name),
+    )?;
+
+    let extras = rpm_deps::resolve_dependencies(
+        &repo.packages,
+        &rpm_deps::parse_repoquery(&metadata),
+        base_provides,
+    );
+    if extras.is_empty() {
+        eprintln!("No extra dependencies from repo '{}'", repo.name);
+        return Ok(());
+    }
+    eprintln!(
+        "Pulling in dependencies from repo '{}': {}",
+        repo.name,
+        extras.join(", ")
+    );
+
+    args.truncate(args.len() - repo.packages.len());
+    args.extend(extras);
+    run_command_with_retry(
+        "dnf",
+        args,
+        &format!("failed to download dependencies for repo '{}'", repo.name),
+        retry,
     )?;
 
     Ok(())
 }
 
-fn run_command(program: &str, args: Vec<String>, error_context: &str) -> Result<()> {
+/// Download a repo's signing key and check it against the pinned
+/// fingerprint, if there is one.
+fn fetch_key(repo: &ExternalRepo, retry: &RetryPolicy) -> Result<Vec<u8>> {
+    let key = match repo.gpg_key.strip_prefix("file://") {
+        Some(path) => {
+            std::fs::read(path).with_context(|| format!("failed to read GPG key {}", path))?
+        }
+        None => http::download_with_retry(std::slice::from_ref(&repo.gpg_key), retry, |data| {
+            gpg::fingerprints(data).map(|_| ())
+        })
+        .with_context(|| format!("failed to download GPG key for repo '{}'", repo.name))?,
+    };
+
+    match &repo.gpg_fingerprint {
+        Some(pinned) => {
+            gpg::verify(&key, pinned).with_context(|| {
+                format!(
+                    "GPG key for repo '{}' from {} does not match its pinned fingerprint; \
+                     if the vendor rotated its key, check the new key and re-run \
+                     `bkt external-repo pin-key {}`",
+                    repo.name, repo.gpg_key, repo.name
+                )
+            })?;
+            eprintln!("Verified GPG key for repo '{}'", repo.name);
+        }
+        None => eprintln!(
+            "warning: repo '{}' has no gpg_fingerprint; trusting whatever {} serves. \
+             Pin it with `bkt external-repo pin-key {}`",
+            repo.name, repo.gpg_key, repo.name
+        ),
+    }
+    Ok(key)
+}
+
+/// Whether something installed in the base image provides `capability`.
+fn base_provides(capability: &str) -> bool {
+    Command::new("rpm")
+        .args(["-q", "--whatprovides", capability])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// The `baseurl` value for a repo: its base URL, then its mirrors, which dnf
+/// tries in order.
+fn baseurls(repo: &ExternalRepo) -> String {
+    std::iter::once(repo.baseurl.as_str())
+        .chain(repo.mirrors.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Run a networked `program`, retrying with backoff unless it fails for a
+/// reason retrying won't fix.
+fn run_command_with_retry(
+    program: &str,
+    args: Vec<String>,
+    error_context: &str,
+    retry: &RetryPolicy,
+) -> Result<String> {
+    let mut backoff = retry.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        let error = match run_command(program, args.clone(), error_context) {
+            Ok(stdout) => return Ok(stdout),
+            Err(e) => e,
+        };
+
+        let message = format!("{:#}", error);
+        let permanent = PERMANENT_DNF_ERRORS.iter().any(|p| message.contains(p));
+        if permanent || attempt >= retry.attempts {
+            return Err(error);
+        }
+        eprintln!(
+            "  attempt {}/{} of {} failed; retrying in {}s...",
+            attempt,
+            retry.attempts,
+            program,
+            backoff.as_secs()
+        );
+        std::thread::sleep(backoff);
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Run `program`, returning its stdout.
+fn run_command(program: &str, args: Vec<String>, error_context: &str) -> Result<String> {
     let output = Command::new(program)
         .args(args.iter().map(String::as_str))
         .output()
@@ -111,7 +262,7 @@ fn run_command(program: &str, args: Vec<String>, error_context: &str) -> Result<
         );
     }
 
-    Ok(())
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 fn validate_repo_line_value(field: &str, value: &str) -> Result<()> {
@@ -162,3 +313,8 @@ fn repo_file_path(name: &str) -> Result<PathBuf> {
 
     Ok(Path::new("/etc/yum.repos.d").join(format!("{}.repo", name)))
 }
+
+/// Where a repo's verified key goes; `name` is checked by `repo_file_path`.
+fn key_file_path(name: &str) -> PathBuf {
+    Path::new(KEY_DIR).join(format!("RPM-GPG-KEY-{}", name))
+}
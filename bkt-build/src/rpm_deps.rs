@@ -0,0 +1,214 @@
+//! Dependency resolution for RPMs downloaded from a single external repo.
+//!
+//! The install stages extract RPMs with `rpm -i --nodeps`, so a dependency
+//! that lives in the same external repo must be downloaded alongside the
+//! named packages or the install ends up half-working. Dependencies the base
+//! image already satisfies are left alone.
+
+use std::collections::{BTreeSet, HashMap};
+
+/// `dnf repoquery --queryformat` that [`parse_repoquery`] understands.
+///
+/// List tags expand to one capability per line, so each section is
+/// introduced by a marker line.
+pub const REPOQUERY_FORMAT: &str =
+    "@@package %{name}\n@@provides\n%{provides}\n@@requires\n%{requires}\n";
+
+/// A package in the repo's metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RepoPackage {
+    pub name: String,
+    pub provides: Vec<String>,
+    pub requires: Vec<String>,
+}
+
+/// Parse `dnf repoquery` output produced with [`REPOQUERY_FORMAT`].
+pub fn parse_repoquery(output: &str) -> Vec<RepoPackage> {
+    enum Section {
+        Provides,
+        Requires,
+    }
+
+    let mut packages: Vec<RepoPackage> = Vec::new();
+    let mut section = None;
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(name) = line.strip_prefix("@@package ") {
+            packages.push(RepoPackage {
+                name: name.trim().to_string(),
+                ..Default::default()
+            });
+            section = None;
+        } else if line == "@@provides" {
+            section = Some(Section::Provides);
+        } else if line == "@@requires" {
+            section = Some(Section::Requires);
+        } else if let (Some(package), Some(section)) = (packages.last_mut(), &section) {
+            match section {
+                Section::Provides => package.provides.push(line.to_string()),
+                Section::Requires => package.requires.push(line.to_string()),
+            }
+        }
+    }
+    packages
+}
+
+/// The capability name of a provide/require, without its version
+/// constraint (`libfoo.so.1()(64bit)`, `foo >= 1.2` -> `foo`).
+pub fn capability_name(entry: &str) -> &str {
+    entry.split_whitespace().next().unwrap_or("")
+}
+
+/// Requirements that never name a package: rpm's own feature flags and
+/// rich (boolean) dependencies, which we don't try to evaluate.
+fn is_ignored_requirement(capability: &str) -> bool {
+    capability.starts_with("rpmlib(") || capability.starts_with('(')
+}
+
+/// Packages from `repo` that `named` transitively require.
+///
+/// A requirement is skipped when `base_provides` says the base image already
+/// satisfies it, and when nothing in the repo provides it (it is then a
+/// distro dependency). Returns the extra package names, sorted, excluding
+/// `named` themselves.
+pub fn resolve_dependencies(
+    named: &[String],
+    repo: &[RepoPackage],
+    mut base_provides: impl FnMut(&str) -> bool,
+) -> Vec<String> {
+    // capability -> providing package; a package always provides its name
+    let mut providers: HashMap<&str, &RepoPackage> = HashMap::new();
+    for package in repo {
+        providers.entry(package.name.as_str()).or_insert(package);
+        for provide in &package.provides {
+            providers.entry(capability_name(provide)).or_insert(package);
+        }
+    }
+    let by_name: HashMap<&str, &RepoPackage> = repo.iter().map(|p| (p.name.as_str(), p)).collect();
+
+    let mut selected: BTreeSet<&str> = named.iter().map(String::as_str).collect();
+    let mut queue: Vec<&RepoPackage> = named
+        .iter()
+        .filter_map(|name| by_name.get(name.as_str()).copied())
+        .collect();
+    let mut checked: HashMap<&str, bool> = HashMap::new();
+    let mut extras = BTreeSet::new();
+
+    while let Some(package) = queue.pop() {
+        for require in &package.requires {
+            let capability = capability_name(require);
+            if capability.is_empty() || is_ignored_requirement(capability) {
+                continue;
+            }
+            let Some(provider) = providers.get(capability) else {
+                continue;
+            };
+            if selected.contains(provider.name.as_str()) {
+                continue;
+            }
+            let in_base = *checked
+                .entry(capability)
+                .or_insert_with(|| base_provides(capability));
+            if in_base {
+                continue;
+            }
+            selected.insert(&provider.name);
+            extras.insert(provider.name.clone());
+            queue.push(provider);
+        }
+    }
+
+    extras.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed `dnf repoquery` output for a 1Password-like repo.
+    const FIXTURE: &str = "\
+@@package 1password
+@@provides
+1password = 8.10.0-1
+application(1password.desktop)
+@@requires
+/bin/sh
+1password-helper >= 8.10
+libgtk-3.so.0()(64bit)
+rpmlib(CompressedFileNames) <= 3.0.4-1
+@@package 1password-helper
+@@provides
+1password-helper = 8.10.0-1
+libop.so.1()(64bit)
+@@requires
+libop-crypto.so.2()(64bit)
+libc.so.6()(64bit)
+@@package 1password-crypto
+@@provides
+libop-crypto.so.2()(64bit)
+@@requires
+@@package 1password-cli
+@@provides
+1password-cli = 2.30.0-1
+@@requires
+@@package unrelated
+@@provides
+unrelated = 1.0
+@@requires
+";
+
+    #[test]
+    fn parses_repoquery_sections() {
+        let packages = parse_repoquery(FIXTURE);
+        assert_eq!(packages.len(), 5);
+        assert_eq!(packages[0].name, "1password");
+        assert_eq!(
+            packages[0].provides,
+            vec!["1password = 8.10.0-1", "application(1password.desktop)"]
+        );
+        assert_eq!(packages[1].requires.len(), 2);
+        assert!(packages[2].requires.is_empty());
+    }
+
+    #[test]
+    fn capability_name_drops_version_constraint() {
+        assert_eq!(
+            capability_name("1password-helper >= 8.10"),
+            "1password-helper"
+        );
+        assert_eq!(
+            capability_name("libgtk-3.so.0()(64bit)"),
+            "libgtk-3.so.0()(64bit)"
+        );
+    }
+
+    #[test]
+    fn resolves_transitive_deps_from_the_same_repo() {
+        let repo = parse_repoquery(FIXTURE);
+        let extras = resolve_dependencies(&["1password".to_string()], &repo, |_| false);
+        assert_eq!(extras, vec!["1password-crypto", "1password-helper"]);
+    }
+
+    #[test]
+    fn skips_deps_the_base_image_provides() {
+        let repo = parse_repoquery(FIXTURE);
+        let mut asked = Vec::new();
+        let extras = resolve_dependencies(&["1password".to_string()], &repo, |cap| {
+            asked.push(cap.to_string());
+            cap == "libop-crypto.so.2()(64bit)"
+        });
+        assert_eq!(extras, vec!["1password-helper"]);
+        // Only capabilities the repo can provide are checked against the base
+        assert_eq!(
+            asked,
+            vec!["1password-helper", "libop-crypto.so.2()(64bit)"]
+        );
+    }
+
+    #[test]
+    fn named_packages_are_never_extras() {
+        let repo = parse_repoquery(FIXTURE);
+        let named = ["1password".to_string(), "1password-helper".to_string()];
+        let extras = resolve_dependencies(&named, &repo, |_| false);
+        assert_eq!(extras, vec!["1password-crypto"]);
+    }
+}
@@ -4,8 +4,8 @@
 //! resolved RPM artifacts during container builds.
 
 use anyhow::{anyhow, bail, Context, Result};
-use bkt_common::checksum::sha256_hex;
-use bkt_common::http;
+use bkt_common::checksum;
+use bkt_common::http::{self, RetryPolicy};
 use bkt_common::manifest::{
     ResolvedVendorArtifact, ResolvedVendorArtifactsManifest, VendorArtifactsManifest,
     VendorResponseMap, VendorSource,
@@ -189,7 +189,7 @@ fn expand_template(
 ///
 /// Reads the resolved manifest, downloads the artifact, verifies its
 /// checksum, and installs it.
-pub fn install(name: &str, resolved_path: &Path) -> Result<()> {
+pub fn install(name: &str, resolved_path: &Path, retry: &RetryPolicy) -> Result<()> {
     let manifest =
         ResolvedVendorArtifactsManifest::load_from(resolved_path).with_context(|| {
             format!(
@@ -203,54 +203,22 @@ pub fn install(name: &str, resolved_path: &Path) -> Result<()> {
         .ok_or_else(|| anyhow!("artifact '{}' not found in resolved manifest", name))?;
 
     match artifact.kind.as_str() {
-        "rpm" => install_rpm(artifact)?,
+        "rpm" => install_rpm(artifact, retry)?,
         other => bail!("unsupported artifact kind '{}' for '{}'", other, name),
     }
 
     Ok(())
 }
 
-/// Download a large artifact with retries on transient network errors.
-fn download_with_retries(url: &str, name: &str) -> Result<Vec<u8>> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 5;
-
-    for attempt in 1..=MAX_RETRIES {
-        match bkt_common::http::download(url) {
-            Ok(data) => return Ok(data),
-            Err(e) if attempt < MAX_RETRIES => {
-                eprintln!(
-                    "  download attempt {}/{} for {} failed: {}",
-                    attempt, MAX_RETRIES, name, e
-                );
-                eprintln!("  retrying in {}s...", RETRY_DELAY_SECS);
-                std::thread::sleep(std::time::Duration::from_secs(RETRY_DELAY_SECS));
-            }
-            Err(e) => {
-                return Err(e)
-                    .with_context(|| format!("failed to download {} after {} attempts", name, MAX_RETRIES));
-            }
-        }
-    }
-    unreachable!()
-}
-
 /// Download and install an RPM artifact.
-fn install_rpm(artifact: &ResolvedVendorArtifact) -> Result<()> {
+fn install_rpm(artifact: &ResolvedVendorArtifact, retry: &RetryPolicy) -> Result<()> {
     eprintln!("Downloading {} v{} ...", artifact.name, artifact.version);
 
-    let data = download_with_retries(&artifact.url, &artifact.name)?;
-
-    eprintln!("Verifying SHA256...");
-    let actual = sha256_hex(&data);
-    if actual != artifact.sha256 {
-        bail!(
-            "SHA256 mismatch for {}: expected {}, got {}",
-            artifact.name,
-            artifact.sha256,
-            actual
-        );
-    }
+    let data = http::download_with_retry(std::slice::from_ref(&artifact.url), retry, |data| {
+        checksum::verify(data, &artifact.sha256)
+    })
+    .with_context(|| format!("failed to download {}", artifact.name))?;
+    eprintln!("Verified SHA256");
 
     // Validate artifact name to prevent path traversal
     if !artifact
@@ -11,6 +11,7 @@ use zip::ZipArchive;
 pub enum ArchiveType {
     TarGz,
     TarXz,
+    TarZst,
     Zip,
     Raw,
 }
@@ -21,6 +22,8 @@ pub fn detect_archive_type(name: &str) -> ArchiveType {
         ArchiveType::TarGz
     } else if lower.ends_with(".tar.xz") || lower.ends_with(".txz") {
         ArchiveType::TarXz
+    } else if lower.ends_with(".tar.zst") || lower.ends_with(".tzst") {
+        ArchiveType::TarZst
     } else if lower.ends_with(".zip") {
         ArchiveType::Zip
     } else {
@@ -28,6 +31,30 @@ pub fn detect_archive_type(name: &str) -> ArchiveType {
     }
 }
 
+/// Detect the archive type from the name, falling back to the leading magic
+/// bytes when the name has no archive extension.
+///
+/// Release assets are sometimes published without one (`tool-linux-amd64`
+/// that is really a tarball), so a name alone isn't enough to call it raw.
+pub fn sniff_archive_type(name: &str, data: &[u8]) -> ArchiveType {
+    match detect_archive_type(name) {
+        ArchiveType::Raw => {
+            if data.starts_with(b"\x1f\x8b") {
+                ArchiveType::TarGz
+            } else if data.starts_with(b"\xfd7zXZ\x00") {
+                ArchiveType::TarXz
+            } else if data.starts_with(b"\x28\xb5\x2f\xfd") {
+                ArchiveType::TarZst
+            } else if data.starts_with(b"PK\x03\x04") {
+                ArchiveType::Zip
+            } else {
+                ArchiveType::Raw
+            }
+        }
+        detected => detected,
+    }
+}
+
 pub fn extract_tar_gz_binary(
     data: &[u8],
     target_dir: &Path,
@@ -68,6 +95,19 @@ pub fn extract_tar_xz(
     Ok(())
 }
 
+pub fn extract_tar_zst(
+    data: &[u8],
+    target_dir: &Path,
+    strip_components: u32,
+) -> Result<(), CommonError> {
+    fs::create_dir_all(target_dir)?;
+    let decoder = zstd::stream::read::Decoder::new(Cursor::new(data))
+        .map_err(|err| CommonError::Archive(err.to_string()))?;
+    let mut archive = Archive::new(decoder);
+    extract_tar_entries(&mut archive, target_dir, strip_components)?;
+    Ok(())
+}
+
 pub fn extract_zip_binary(
     data: &[u8],
     target_dir: &Path,
@@ -98,6 +138,7 @@ pub fn extract_zip_binary(
         }
         let mut outfile = fs::File::create(&out_path)?;
         std::io::copy(&mut file, &mut outfile)?;
+        set_zip_mode(&out_path, file.unix_mode())?;
         extracted.push(out_path);
     }
 
@@ -129,6 +170,7 @@ pub fn extract_zip(data: &[u8], target_dir: &Path) -> Result<(), CommonError> {
         }
         let mut outfile = fs::File::create(&out_path)?;
         std::io::copy(&mut file, &mut outfile)?;
+        set_zip_mode(&out_path, file.unix_mode())?;
     }
 
     Ok(())
@@ -157,6 +199,20 @@ pub fn set_executable(path: &Path) -> Result<(), CommonError> {
     Ok(())
 }
 
+/// Apply the permissions recorded in a zip entry, if the archive kept them,
+/// so executables come out executable like they do from a tarball.
+fn set_zip_mode(path: &Path, mode: Option<u32>) -> Result<(), CommonError> {
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode((mode & 0o777) | 0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+
+    Ok(())
+}
+
 fn extract_tar_entries<R: std::io::Read>(
     archive: &mut Archive<R>,
     target_dir: &Path,
@@ -1,5 +1,10 @@
-use sha2::{Digest, Sha256};
+use crate::error::CommonError;
+use sha2::{Digest, Sha256, Sha512};
 use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
 
 pub fn sha256_hex(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
@@ -7,8 +12,205 @@ pub fn sha256_hex(bytes: &[u8]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// A digest algorithm checksums can use, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Algorithm {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl Algorithm {
+    /// The name used as a digest prefix (`sha512:...`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Parse an algorithm name, as a prefix or a BSD-style tag (`SHA512`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sha256" | "sha-256" => Some(Algorithm::Sha256),
+            "sha512" | "sha-512" => Some(Algorithm::Sha512),
+            "blake3" | "b3" => Some(Algorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// The algorithm of an unprefixed hex digest, going by its length.
+    ///
+    /// 64 digits is taken as SHA-256, which is what unprefixed digests have
+    /// always meant here; BLAKE3 digests are the same length, so they need
+    /// the `blake3:` prefix.
+    pub fn from_digest_len(len: usize) -> Option<Self> {
+        match len {
+            64 => Some(Algorithm::Sha256),
+            128 => Some(Algorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Length of this algorithm's hex digests.
+    pub fn digest_len(self) -> usize {
+        match self {
+            Algorithm::Sha512 => 128,
+            Algorithm::Sha256 | Algorithm::Blake3 => 64,
+        }
+    }
+
+    /// Hex digest of `bytes`.
+    pub fn digest_hex(self, bytes: &[u8]) -> String {
+        match self {
+            Algorithm::Sha256 => sha256_hex(bytes),
+            Algorithm::Sha512 => format!("{:x}", Sha512::digest(bytes)),
+            Algorithm::Blake3 => blake3::hash(bytes).to_hex().to_string(),
+        }
+    }
+
+    /// Hex digest of everything `reader` yields.
+    pub fn digest_reader(self, reader: &mut impl Read) -> io::Result<String> {
+        Ok(match self {
+            Algorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                io::copy(reader, &mut hasher)?;
+                format!("{:x}", hasher.finalize())
+            }
+            Algorithm::Sha512 => {
+                let mut hasher = Sha512::new();
+                io::copy(reader, &mut hasher)?;
+                format!("{:x}", hasher.finalize())
+            }
+            Algorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                io::copy(reader, &mut hasher)?;
+                hasher.finalize().to_hex().to_string()
+            }
+        })
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An expected digest and the algorithm that made it.
+///
+/// Parsed from `sha256:<hex>`, `sha512:<hex>` or `blake3:<hex>`, or from a
+/// bare hex digest whose length gives the algorithm. Displays bare for
+/// SHA-256, the format manifests have always used, and prefixed otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: Algorithm,
+    /// Lowercase hex digest
+    pub digest: String,
+}
+
+impl Checksum {
+    /// The checksum of `bytes` under `algorithm`.
+    pub fn of(algorithm: Algorithm, bytes: &[u8]) -> Self {
+        Self {
+            algorithm,
+            digest: algorithm.digest_hex(bytes),
+        }
+    }
+
+    /// Check `bytes` against this checksum.
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), CommonError> {
+        self.verify_digest(self.algorithm.digest_hex(bytes))
+    }
+
+    /// Check an already-computed digest (same algorithm) against this one.
+    pub fn verify_digest(&self, actual: String) -> Result<(), CommonError> {
+        if actual.eq_ignore_ascii_case(&self.digest) {
+            Ok(())
+        } else {
+            Err(CommonError::ChecksumMismatch {
+                expected: self.to_string(),
+                actual: Checksum {
+                    algorithm: self.algorithm,
+                    digest: actual.to_lowercase(),
+                }
+                .to_string(),
+            })
+        }
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = CommonError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let invalid = || CommonError::InvalidChecksum(value.to_string());
+        let (algorithm, digest) = match value.split_once(':') {
+            Some((name, digest)) => (Algorithm::from_name(name).ok_or_else(invalid)?, digest),
+            None => (
+                Algorithm::from_digest_len(value.len()).ok_or_else(invalid)?,
+                value,
+            ),
+        };
+        if digest.len() != algorithm.digest_len() || !digest.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            algorithm,
+            digest: digest.to_lowercase(),
+        })
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.algorithm {
+            Algorithm::Sha256 => f.write_str(&self.digest),
+            algorithm => write!(f, "{}:{}", algorithm, self.digest),
+        }
+    }
+}
+
+/// Check `bytes` against an expected checksum in any supported form
+/// (see [`Checksum`]).
+pub fn verify(bytes: &[u8], expected: &str) -> Result<(), CommonError> {
+    expected.parse::<Checksum>()?.verify(bytes)
+}
+
+/// Check `bytes` against an expected hex SHA-256 (case-insensitive).
+pub fn verify_sha256(bytes: &[u8], expected: &str) -> Result<(), CommonError> {
+    Checksum {
+        algorithm: Algorithm::Sha256,
+        digest: expected.trim().to_lowercase(),
+    }
+    .verify(bytes)
+}
+
+/// The checksum listed for `asset_name` in a checksum file, matching on the
+/// full name or, failing that, its file name.
+pub fn find_checksum(content: &str, asset_name: &str) -> Option<String> {
+    let checksums = parse_checksum_file(content);
+    checksums
+        .get(asset_name)
+        .or_else(|| {
+            let name = Path::new(asset_name).file_name()?.to_str()?;
+            checksums.get(name)
+        })
+        .cloned()
+}
+
+/// Parse a checksum file (`sha256sum`/`sha512sum` output, BSD-style
+/// `SHA512 (file) = hex` lines, or a mix) into file name to checksum, each
+/// in [`Checksum`]'s display form.
+///
+/// When a file is listed under several algorithms the strongest wins. A
+/// `hex  file` digest in no supported algorithm is kept as-is (lowercased),
+/// so checking against it reports a mismatch rather than a missing entry.
 pub fn parse_checksum_file(content: &str) -> HashMap<String, String> {
-    let mut checksums = HashMap::new();
+    let mut checksums: HashMap<String, (Option<Algorithm>, String)> = HashMap::new();
 
     for line in content.lines() {
         let line = line.trim();
@@ -16,36 +218,176 @@ pub fn parse_checksum_file(content: &str) -> HashMap<String, String> {
             continue;
         }
 
-        if let Some((algo, rest)) = line.split_once('(') {
-            if algo.trim().eq_ignore_ascii_case("sha256") {
-                if let Some((file_part, hash_part)) = rest.split_once(')') {
-                    if let Some((_, hash)) = hash_part.split_once('=') {
-                        let filename = file_part.trim().trim_start_matches("./");
-                        let hash = hash.trim();
-                        if !filename.is_empty() && !hash.is_empty() {
-                            checksums.insert(filename.to_string(), hash.to_lowercase());
-                            continue;
-                        }
-                    }
-                }
+        let entry = if is_bsd_line(line) {
+            parse_bsd_line(line)
+        } else {
+            parse_gnu_line(line)
+        };
+        let Some((filename, algorithm, value)) = entry else {
+            continue;
+        };
+        let filename = filename.trim_start_matches("./");
+        if filename.is_empty() {
+            continue;
+        }
+        match checksums.get(filename) {
+            Some((existing, _)) if *existing >= algorithm => {}
+            _ => {
+                checksums.insert(filename.to_string(), (algorithm, value));
             }
         }
+    }
 
-        let mut parts = line.split_whitespace();
-        let hash = match parts.next() {
-            Some(value) => value,
-            None => continue,
-        };
-        let filename = match parts.next() {
-            Some(value) => value,
-            None => continue,
-        };
+    checksums
+        .into_iter()
+        .map(|(name, (_, value))| (name, value))
+        .collect()
+}
+
+fn is_bsd_line(line: &str) -> bool {
+    line.split_once(" (")
+        .is_some_and(|(_, rest)| rest.contains(") ="))
+}
+
+/// `SHA256 (file) = hex`; lines naming other algorithms are skipped.
+fn parse_bsd_line(line: &str) -> Option<(&str, Option<Algorithm>, String)> {
+    let (algo, rest) = line.split_once('(')?;
+    let algorithm = Algorithm::from_name(algo)?;
+    let (file_part, hash_part) = rest.rsplit_once(')')?;
+    let (_, hash) = hash_part.split_once('=')?;
+    let checksum: Checksum = format!("{}:{}", algorithm, hash.trim()).parse().ok()?;
+    Some((file_part.trim(), Some(algorithm), checksum.to_string()))
+}
+
+/// `hex  file` or `hex *file`
+fn parse_gnu_line(line: &str) -> Option<(&str, Option<Algorithm>, String)> {
+    let mut parts = line.split_whitespace();
+    let hash = parts.next()?;
+    let filename = parts.next()?.trim_start_matches('*');
+    Some(match hash.parse::<Checksum>() {
+        Ok(checksum) => (filename, Some(checksum.algorithm), checksum.to_string()),
+        Err(_) => (filename, None, hash.to_lowercase()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_checksum_matches_name_or_file_name() {
+        let hash = sha256_hex(b"hello");
+        let content = format!("{hash}  cargo-binstall.tgz\n");
+
+        assert_eq!(
+            find_checksum(&content, "cargo-binstall.tgz"),
+            Some(hash.clone())
+        );
+        assert_eq!(
+            find_checksum(&content, "dist/cargo-binstall.tgz"),
+            Some(hash)
+        );
+        assert_eq!(find_checksum(&content, "other.tgz"), None);
+    }
+
+    #[test]
+    fn verify_sha256_reports_mismatch() {
+        let hash = sha256_hex(b"hello");
+        verify_sha256(b"hello", &hash.to_uppercase()).expect("checksum ok");
+
+        let err = verify_sha256(b"hello", "deadbeef").expect_err("checksum error");
+        assert!(matches!(err, CommonError::ChecksumMismatch { .. }));
+    }
+
+    #[test]
+    fn checksum_detects_algorithm_from_prefix_or_length() {
+        let sha256 = sha256_hex(b"hello");
+        let sha512 = Algorithm::Sha512.digest_hex(b"hello");
+        let blake3 = Algorithm::Blake3.digest_hex(b"hello");
+
+        let parsed: Checksum = sha256.parse().unwrap();
+        assert_eq!(parsed.algorithm, Algorithm::Sha256);
+        assert_eq!(parsed.to_string(), sha256);
+        let parsed: Checksum = sha512.to_uppercase().parse().unwrap();
+        assert_eq!(parsed.algorithm, Algorithm::Sha512);
+        assert_eq!(parsed.to_string(), format!("sha512:{sha512}"));
+        let parsed: Checksum = format!("BLAKE3:{blake3}").parse().unwrap();
+        assert_eq!(parsed.algorithm, Algorithm::Blake3);
 
-        let filename = filename.trim_start_matches('*').trim_start_matches("./");
-        if !filename.is_empty() {
-            checksums.insert(filename.to_string(), hash.to_lowercase());
+        verify(b"hello", &sha256).expect("sha256");
+        verify(b"hello", &format!("sha256:{sha256}")).expect("prefixed sha256");
+        verify(b"hello", &sha512).expect("sha512");
+        verify(b"hello", &format!("blake3:{blake3}")).expect("blake3");
+
+        // A bare 64-digit digest means SHA-256, so BLAKE3 needs its prefix
+        assert!(matches!(
+            verify(b"hello", &blake3),
+            Err(CommonError::ChecksumMismatch { .. })
+        ));
+        for bad in [
+            "deadbeef",
+            "md5:abc",
+            &format!("sha512:{sha256}"),
+            &"z".repeat(64),
+        ] {
+            assert!(
+                matches!(verify(b"hello", bad), Err(CommonError::InvalidChecksum(_))),
+                "{bad}"
+            );
         }
     }
 
-    checksums
+    #[test]
+    fn checksum_mismatch_names_the_algorithm() {
+        let expected = format!("sha512:{}", "0".repeat(128));
+        let err = verify(b"hello", &expected).expect_err("mismatch");
+        let CommonError::ChecksumMismatch {
+            expected: e,
+            actual,
+        } = err
+        else {
+            panic!("expected ChecksumMismatch, got {err:?}");
+        };
+        assert_eq!(e, expected);
+        assert_eq!(
+            actual,
+            format!("sha512:{}", Algorithm::Sha512.digest_hex(b"hello"))
+        );
+    }
+
+    #[test]
+    fn parse_checksum_file_handles_mixed_algorithms() {
+        let sha256 = sha256_hex(b"a");
+        let sha512 = Algorithm::Sha512.digest_hex(b"a");
+        let blake3 = Algorithm::Blake3.digest_hex(b"b");
+        let content = format!(
+            "{sha256}  a.tgz\n\
+             SHA512 (a.tgz) = {sha512}\n\
+             BLAKE3 (b.zip) = {blake3}\n\
+             {sha256} *./c.bin\n\
+             0cc175b9c0f1b6a831c399e269772661  md5-only.txt\n\
+             MD5 (d.bin) = 0cc175b9c0f1b6a831c399e269772661\n"
+        );
+
+        let map = parse_checksum_file(&content);
+        assert_eq!(map["a.tgz"], format!("sha512:{sha512}"));
+        assert_eq!(map["b.zip"], format!("blake3:{blake3}"));
+        assert_eq!(map["c.bin"], sha256);
+        assert!(!map.contains_key("d.bin"));
+        assert_eq!(map["md5-only.txt"], "0cc175b9c0f1b6a831c399e269772661");
+        assert!(matches!(
+            verify(b"a", &map["md5-only.txt"]),
+            Err(CommonError::InvalidChecksum(_))
+        ));
+
+        verify(b"a", &find_checksum(&content, "dist/a.tgz").unwrap()).expect("sha512 entry");
+    }
+
+    #[test]
+    fn digest_reader_matches_digest_hex() {
+        for algorithm in [Algorithm::Sha256, Algorithm::Sha512, Algorithm::Blake3] {
+            let streamed = algorithm.digest_reader(&mut &b"hello"[..]).unwrap();
+            assert_eq!(streamed, algorithm.digest_hex(b"hello"));
+        }
+    }
 }
@@ -8,10 +8,39 @@ pub enum CommonError {
     Archive(String),
     #[error("checksum mismatch: expected {expected}, got {actual}")]
     ChecksumMismatch { expected: String, actual: String },
+    /// Not a digest in a supported algorithm (see `checksum::Checksum`).
+    #[error("invalid checksum: {0}")]
+    InvalidChecksum(String),
     #[error("http error: {0}")]
     Http(String),
+    /// The server answered with an error status.
+    #[error("http error: {url} returned status {status}")]
+    HttpStatus { url: String, status: u16 },
+    /// The connection failed or timed out; trying again may succeed.
+    #[error("network error: {0}")]
+    Network(String),
     #[error("manifest error: {0}")]
     Manifest(String),
     #[error("json error: {0}")]
     Json(#[from] serde_json::Error),
+    /// The data isn't an OpenPGP public key this crate can read.
+    #[error("gpg key error: {0}")]
+    GpgKey(String),
+    /// A key doesn't match the fingerprint pinned for it.
+    #[error("gpg key fingerprint mismatch: pinned {expected}, got {actual}")]
+    FingerprintMismatch { expected: String, actual: String },
+}
+
+impl CommonError {
+    /// Whether the same request might succeed if tried again: timeouts,
+    /// dropped connections and server-side (5xx, 408, 429) errors.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CommonError::Network(_) => true,
+            CommonError::HttpStatus { status, .. } => {
+                matches!(status, 408 | 429) || (500..600).contains(status)
+            }
+            _ => false,
+        }
+    }
 }
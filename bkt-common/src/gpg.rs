@@ -0,0 +1,309 @@
+//! OpenPGP key fingerprints, for pinning repo signing keys.
+//!
+//! Just enough of RFC 4880 / RFC 9580 to find the primary public key
+//! packets in an armored or binary key file and hash them the way
+//! `gpg --fingerprint` does: SHA-1 for v4 keys, SHA-256 for v5 and v6.
+
+use crate::error::CommonError;
+use base64::Engine;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+const BEGIN: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+const END: &str = "-----END PGP PUBLIC KEY BLOCK-----";
+
+/// Packet tag of a primary public key.
+const PUBLIC_KEY_TAG: u8 = 6;
+
+/// Fingerprints (upper-case hex) of the primary keys in `key`, which may be
+/// ASCII-armored or binary. Subkeys are not included.
+pub fn fingerprints(key: &[u8]) -> Result<Vec<String>, CommonError> {
+    let binary = match std::str::from_utf8(key) {
+        Ok(text) if text.contains(BEGIN) => dearmor(text)?,
+        _ => key.to_vec(),
+    };
+
+    let mut fingerprints = Vec::new();
+    for (tag, body) in packets(&binary)? {
+        if tag == PUBLIC_KEY_TAG {
+            let fingerprint = fingerprint(body)?;
+            if !fingerprints.contains(&fingerprint) {
+                fingerprints.push(fingerprint);
+            }
+        }
+    }
+    if fingerprints.is_empty() {
+        return Err(CommonError::GpgKey("no public key found".to_string()));
+    }
+    Ok(fingerprints)
+}
+
+/// Check that `key` holds exactly the key `pinned`, so a key added
+/// alongside it can't slip through.
+pub fn verify(key: &[u8], pinned: &str) -> Result<(), CommonError> {
+    let expected = normalize(pinned);
+    let actual = fingerprints(key)?;
+    if actual != [expected.as_str()] {
+        return Err(CommonError::FingerprintMismatch {
+            expected,
+            actual: actual.join(", "),
+        });
+    }
+    Ok(())
+}
+
+/// A fingerprint as `fingerprints` reports it: upper-case, without the
+/// spaces `gpg` groups it with.
+pub fn normalize(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Decode every public key block in armored `text`.
+fn dearmor(text: &str) -> Result<Vec<u8>, CommonError> {
+    let mut binary = Vec::new();
+    let mut lines = text.lines().map(str::trim);
+
+    while lines.any(|line| line == BEGIN) {
+        // Armor headers (`Version: ...`) run up to the first blank line
+        let mut body = String::new();
+        let mut in_headers = true;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line == END {
+                closed = true;
+                break;
+            }
+            if in_headers {
+                if line.is_empty() {
+                    in_headers = false;
+                } else if !line.contains(':') {
+                    // No headers at all: this is already body
+                    in_headers = false;
+                    body.push_str(line);
+                }
+                continue;
+            }
+            // `=XXXX` is the CRC, not key material
+            if !line.starts_with('=') {
+                body.push_str(line);
+            }
+        }
+        if !closed {
+            return Err(CommonError::GpgKey(
+                "unterminated public key block".to_string(),
+            ));
+        }
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&body)
+            .map_err(|e| CommonError::GpgKey(format!("invalid armor: {}", e)))?;
+        binary.extend(decoded);
+    }
+    Ok(binary)
+}
+
+/// Split binary OpenPGP data into `(tag, body)` packets.
+fn packets(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>, CommonError> {
+    let truncated = || CommonError::GpgKey("truncated packet".to_string());
+    let mut packets = Vec::new();
+
+    while let Some((&header, rest)) = data.split_first() {
+        if header & 0x80 == 0 {
+            return Err(CommonError::GpgKey(format!(
+                "invalid packet header 0x{:02x}",
+                header
+            )));
+        }
+
+        let (tag, len, rest) = if header & 0x40 != 0 {
+            // New format: the length's first octet says how it's encoded
+            let tag = header & 0x3f;
+            let (&first, rest) = rest.split_first().ok_or_else(truncated)?;
+            match first {
+                0..=191 => (tag, first as usize, rest),
+                192..=223 => {
+                    let (&second, rest) = rest.split_first().ok_or_else(truncated)?;
+                    let len = ((first as usize - 192) << 8) + second as usize + 192;
+                    (tag, len, rest)
+                }
+                255 => {
+                    let bytes = rest.get(..4).ok_or_else(truncated)?;
+                    let len = u32::from_be_bytes(bytes.try_into().unwrap()) as usize;
+                    (tag, len, &rest[4..])
+                }
+                _ => {
+                    return Err(CommonError::GpgKey(
+                        "partial body lengths are not valid in key files".to_string(),
+                    ))
+                }
+            }
+        } else {
+            // Old format: the low two bits give the length's size
+            let tag = (header >> 2) & 0x0f;
+            match header & 0x03 {
+                0 => {
+                    let (&len, rest) = rest.split_first().ok_or_else(truncated)?;
+                    (tag, len as usize, rest)
+                }
+                1 => {
+                    let bytes = rest.get(..2).ok_or_else(truncated)?;
+                    let len = u16::from_be_bytes(bytes.try_into().unwrap()) as usize;
+                    (tag, len, &rest[2..])
+                }
+                2 => {
+                    let bytes = rest.get(..4).ok_or_else(truncated)?;
+                    let len = u32::from_be_bytes(bytes.try_into().unwrap()) as usize;
+                    (tag, len, &rest[4..])
+                }
+                // Indeterminate: the packet runs to the end of the data
+                _ => (tag, rest.len(), rest),
+            }
+        };
+
+        let body = rest.get(..len).ok_or_else(truncated)?;
+        packets.push((tag, body));
+        data = &rest[len..];
+    }
+    Ok(packets)
+}
+
+/// Fingerprint of a public key packet body.
+fn fingerprint(body: &[u8]) -> Result<String, CommonError> {
+    let digest = match body.first() {
+        Some(4) => {
+            let len = u16::try_from(body.len())
+                .map_err(|_| CommonError::GpgKey("v4 key packet too long".to_string()))?;
+            let mut hasher = Sha1::new();
+            hasher.update([0x99]);
+            hasher.update(len.to_be_bytes());
+            hasher.update(body);
+            hasher.finalize().to_vec()
+        }
+        Some(version @ (5 | 6)) => {
+            let prefix = if *version == 5 { 0x9a } else { 0x9b };
+            let mut hasher = Sha256::new();
+            hasher.update([prefix]);
+            hasher.update((body.len() as u32).to_be_bytes());
+            hasher.update(body);
+            hasher.finalize().to_vec()
+        }
+        Some(version) => {
+            return Err(CommonError::GpgKey(format!(
+                "unsupported key version {}",
+                version
+            )))
+        }
+        None => return Err(CommonError::GpgKey("empty key packet".to_string())),
+    };
+    Ok(digest.iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A v4 Ed25519 primary key packet body (version, creation time,
+    /// algorithm 22, curve OID, 263-bit point).
+    fn key_body(seed: u8) -> Vec<u8> {
+        let mut body = vec![4, 0x5f, 0x00, 0x00, 0x00, 22, 9];
+        body.extend([0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01]);
+        body.extend([0x01, 0x07, 0x40]);
+        body.extend([seed; 32]);
+        body
+    }
+
+    /// A new-format packet.
+    fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0xc0 | tag, body.len() as u8];
+        packet.extend(body);
+        packet
+    }
+
+    fn expected(body: &[u8]) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update([0x99]);
+        hasher.update((body.len() as u16).to_be_bytes());
+        hasher.update(body);
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect()
+    }
+
+    fn armor(binary: &[u8]) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(binary);
+        let wrapped: Vec<&str> = encoded
+            .as_bytes()
+            .chunks(64)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect();
+        format!(
+            "{BEGIN}\nVersion: test\n\n{}\n=AAAA\n{END}\n",
+            wrapped.join("\n")
+        )
+    }
+
+    #[test]
+    fn fingerprints_primary_keys_not_subkeys() {
+        let primary = key_body(1);
+        let subkey = key_body(2);
+        let mut binary = packet(PUBLIC_KEY_TAG, &primary);
+        binary.extend(packet(13, b"Repo Signing <repo@example.com>"));
+        binary.extend(packet(14, &subkey));
+
+        assert_eq!(fingerprints(&binary).unwrap(), [expected(&primary)]);
+        assert_eq!(
+            fingerprints(armor(&binary).as_bytes()).unwrap(),
+            [expected(&primary)]
+        );
+    }
+
+    #[test]
+    fn old_format_packets_parse() {
+        let primary = key_body(3);
+        // Old format, tag 6, two-octet length
+        let mut binary = vec![0x99];
+        binary.extend((primary.len() as u16).to_be_bytes());
+        binary.extend(&primary);
+        assert_eq!(fingerprints(&binary).unwrap(), [expected(&primary)]);
+    }
+
+    #[test]
+    fn verify_requires_exactly_the_pinned_key() {
+        let primary = key_body(1);
+        let key = armor(&packet(PUBLIC_KEY_TAG, &primary));
+        let pinned = expected(&primary);
+
+        assert!(verify(key.as_bytes(), &pinned).is_ok());
+        // gpg prints fingerprints grouped and callers may paste lower-case
+        let grouped = pinned
+            .as_bytes()
+            .chunks(4)
+            .map(|c| std::str::from_utf8(c).unwrap().to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert!(verify(key.as_bytes(), &grouped).is_ok());
+
+        let other = armor(&packet(PUBLIC_KEY_TAG, &key_body(9)));
+        assert!(matches!(
+            verify(other.as_bytes(), &pinned),
+            Err(CommonError::FingerprintMismatch { .. })
+        ));
+
+        // An extra key served alongside the pinned one is rejected too
+        let mut both = packet(PUBLIC_KEY_TAG, &primary);
+        both.extend(packet(PUBLIC_KEY_TAG, &key_body(9)));
+        assert!(verify(&both, &pinned).is_err());
+    }
+
+    #[test]
+    fn rejects_things_that_are_not_keys() {
+        assert!(fingerprints(b"<html>404</html>").is_err());
+        assert!(fingerprints(format!("{BEGIN}\n\nAAAA\n").as_bytes()).is_err());
+        assert!(fingerprints(&[0xc6, 0x40, 4]).is_err());
+    }
+}
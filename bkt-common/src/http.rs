@@ -1,6 +1,39 @@
+use crate::checksum::{Algorithm, Checksum};
 use crate::error::CommonError;
 use serde::de::DeserializeOwned;
-use std::io::Read;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often to try a download before giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts per URL, including the first.
+    pub attempts: u32,
+    /// Wait before the second attempt; doubles after each failure.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default backoff with `attempts` tries per URL.
+    pub fn with_attempts(attempts: u32) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            ..Self::default()
+        }
+    }
+}
 
 /// Download raw bytes from a URL.
 pub fn download(url: &str) -> Result<Vec<u8>, CommonError> {
@@ -8,23 +41,365 @@ pub fn download(url: &str) -> Result<Vec<u8>, CommonError> {
 }
 
 /// Download raw bytes from a URL with custom headers.
-pub fn download_with_headers(
+pub fn download_with_headers(url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, CommonError> {
+    download_with_progress(url, headers, |_, _| {})
+}
+
+/// Download raw bytes from a URL, reporting progress as it arrives.
+///
+/// `on_progress` is called with the bytes received so far and the total
+/// size, when the server sent a usable `Content-Length`.
+pub fn download_with_progress(
     url: &str,
     headers: &[(&str, &str)],
+    mut on_progress: impl FnMut(u64, Option<u64>),
 ) -> Result<Vec<u8>, CommonError> {
     let mut request = ureq::get(url);
     for &(key, value) in headers {
         request = request.header(key, value);
     }
-    let mut bytes = Vec::new();
-    request
-        .call()
-        .map_err(|e| CommonError::Http(e.to_string()))?
+    let mut response = request.call().map_err(|e| request_error(url, e))?;
+    let total = response.body().content_length();
+    let mut reader = response.body_mut().as_reader();
+
+    let mut bytes = Vec::with_capacity(total.unwrap_or(0).min(64 << 20) as usize);
+    let mut chunk = [0u8; 64 * 1024];
+    on_progress(0, total);
+    loop {
+        // A body cut short is a dropped connection
+        let read = reader
+            .read(&mut chunk)
+            .map_err(|e| CommonError::Network(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        on_progress(bytes.len() as u64, total);
+    }
+    Ok(bytes)
+}
+
+/// Classify a ureq error so callers can tell transient failures apart.
+fn request_error(url: &str, error: ureq::Error) -> CommonError {
+    match error {
+        ureq::Error::StatusCode(status) => CommonError::HttpStatus {
+            url: url.to_string(),
+            status,
+        },
+        ureq::Error::Io(_)
+        | ureq::Error::Timeout(_)
+        | ureq::Error::ConnectionFailed
+        | ureq::Error::HostNotFound
+        | ureq::Error::Protocol(_) => CommonError::Network(error.to_string()),
+        other => CommonError::Http(other.to_string()),
+    }
+}
+
+/// Download the first of `urls` (a primary URL followed by mirrors) that
+/// succeeds and passes `check`.
+///
+/// Retryable failures are retried on the same URL with exponential backoff;
+/// permanent ones (e.g. a 404, or `check` rejecting a checksum mismatch) move
+/// on to the next URL. Attempts are logged to stderr. Returns the last error
+/// if every URL fails.
+pub fn download_with_retry(
+    urls: &[String],
+    policy: &RetryPolicy,
+    check: impl Fn(&[u8]) -> Result<(), CommonError>,
+) -> Result<Vec<u8>, CommonError> {
+    let mut last_error = CommonError::Http("no download URLs given".to_string());
+
+    for (index, url) in urls.iter().enumerate() {
+        if index > 0 {
+            eprintln!("  trying mirror {}", url);
+        }
+
+        let mut backoff = policy.initial_backoff;
+        for attempt in 1..=policy.attempts {
+            let error = match download(url) {
+                Ok(data) => match check(&data) {
+                    Ok(()) => return Ok(data),
+                    Err(e) => e,
+                },
+                Err(e) => e,
+            };
+
+            let retry = error.is_retryable() && attempt < policy.attempts;
+            eprintln!(
+                "  attempt {}/{} for {} failed: {}",
+                attempt, policy.attempts, url, error
+            );
+            last_error = error;
+            if !retry {
+                break;
+            }
+            eprintln!("  retrying in {}s...", backoff.as_secs());
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Hosts that get `GITHUB_TOKEN` as a bearer token: the API, release
+/// download URLs, and the storage those redirect to.
+const GITHUB_HOSTS: &[&str] = &[
+    "api.github.com",
+    "github.com",
+    "objects.githubusercontent.com",
+];
+
+/// Per-download settings for [`Downloader::download`].
+#[derive(Default)]
+pub struct DownloadOptions<'a> {
+    /// Expected checksum of the complete file, in any form
+    /// [`Checksum`](crate::checksum::Checksum) parses (bare SHA-256 or
+    /// SHA-512 hex, or `sha512:`/`blake3:` prefixed).
+    pub checksum: Option<&'a str>,
+    /// Extra request headers.
+    pub headers: &'a [(&'a str, &'a str)],
+    /// Called with the bytes on disk so far (including a resumed prefix)
+    /// and the total size, when the server said.
+    pub progress: Option<&'a dyn Fn(u64, Option<u64>)>,
+}
+
+/// A finished download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadResult {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Hex SHA-256 of the file.
+    pub sha256: String,
+    /// Bytes kept from an earlier, interrupted download of the same file.
+    pub resumed_from: u64,
+}
+
+/// Downloads files to disk with retries, resume and checksum verification.
+///
+/// Bytes land in `<dest>.part` first. A retry, or a later run after an
+/// interrupted one, continues from the partial file with an HTTP range
+/// request when the server supports it, and starts over when it doesn't.
+/// The file only moves to `dest` once it's complete and its checksum
+/// matches; a mismatch deletes it.
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    retry: RetryPolicy,
+    user_agent: String,
+    github_token: Option<String>,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Downloader {
+    /// A downloader with the default retry policy, using `GITHUB_TOKEN`
+    /// from the environment for GitHub hosts.
+    pub fn new() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            user_agent: "bkt".to_string(),
+            github_token: std::env::var("GITHUB_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty()),
+        }
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn github_token(mut self, token: Option<String>) -> Self {
+        self.github_token = token;
+        self
+    }
+
+    /// Download `url` to `dest`.
+    ///
+    /// Retryable failures are retried with exponential backoff, resuming
+    /// from what already arrived. A checksum mismatch isn't retried.
+    pub fn download(
+        &self,
+        url: &str,
+        dest: &Path,
+        options: &DownloadOptions,
+    ) -> Result<DownloadResult, CommonError> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = partial_path(dest);
+        let resumed_from = fs::metadata(&partial).map(|meta| meta.len()).unwrap_or(0);
+
+        let mut backoff = self.retry.initial_backoff;
+        let mut attempt = 1;
+        while let Err(error) = self.fetch_to(url, &partial, options) {
+            if !error.is_retryable() || attempt >= self.retry.attempts {
+                return Err(error);
+            }
+            eprintln!(
+                "  attempt {}/{} for {} failed: {}",
+                attempt, self.retry.attempts, url, error
+            );
+            std::thread::sleep(backoff);
+            backoff *= 2;
+            attempt += 1;
+        }
+
+        let (size, sha256) = hash_file(&partial)?;
+        if let Some(expected) = options.checksum {
+            let verified = expected.parse::<Checksum>().and_then(|expected| {
+                let actual = match expected.algorithm {
+                    Algorithm::Sha256 => sha256.clone(),
+                    algorithm => algorithm.digest_reader(&mut File::open(&partial)?)?,
+                };
+                expected.verify_digest(actual)
+            });
+            if let Err(e) = verified {
+                fs::remove_file(&partial)?;
+                return Err(e);
+            }
+        }
+
+        fs::rename(&partial, dest)?;
+        Ok(DownloadResult {
+            path: dest.to_path_buf(),
+            size,
+            sha256,
+            resumed_from,
+        })
+    }
+
+    /// One request for `url`, appending to `partial` when the server
+    /// honours a range request for the bytes already there.
+    fn fetch_to(
+        &self,
+        url: &str,
+        partial: &Path,
+        options: &DownloadOptions,
+    ) -> Result<(), CommonError> {
+        let offset = fs::metadata(partial).map(|meta| meta.len()).unwrap_or(0);
+
+        let mut request = ureq::get(url).header("User-Agent", &self.user_agent);
+        if let Some(token) = &self.github_token {
+            if is_github_host(url) {
+                request = request.header("Authorization", &format!("Bearer {token}"));
+            }
+        }
+        for &(key, value) in options.headers {
+            request = request.header(key, value);
+        }
+        if offset > 0 {
+            request = request.header("Range", &format!("bytes={offset}-"));
+        }
+
+        let mut response = match request.call() {
+            // The partial file is already whole (or stale): start over
+            Err(ureq::Error::StatusCode(416)) if offset > 0 => {
+                fs::remove_file(partial)?;
+                return self.fetch_to(url, partial, options);
+            }
+            result => result.map_err(|e| request_error(url, e))?,
+        };
+
+        let resuming = offset > 0 && response.status().as_u16() == 206;
+        let (mut file, start) = if resuming {
+            (OpenOptions::new().append(true).open(partial)?, offset)
+        } else {
+            (File::create(partial)?, 0)
+        };
+        let total = response.body().content_length().map(|len| len + start);
+        let mut reader = response.body_mut().as_reader();
+
+        let mut written = start;
+        let mut chunk = [0u8; 64 * 1024];
+        if let Some(progress) = options.progress {
+            progress(written, total);
+        }
+        loop {
+            // A body cut short is a dropped connection; what arrived stays
+            // in the partial file for the next attempt
+            let read = reader
+                .read(&mut chunk)
+                .map_err(|e| CommonError::Network(e.to_string()))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&chunk[..read])?;
+            written += read as u64;
+            if let Some(progress) = options.progress {
+                progress(written, total);
+            }
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Where a download to `dest` keeps its bytes until it's complete.
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+fn is_github_host(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    GITHUB_HOSTS
+        .iter()
+        .any(|github| host.eq_ignore_ascii_case(github))
+}
+
+fn hash_file(path: &Path) -> Result<(u64, String), CommonError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher)?;
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// The outcome of [`download_if_modified`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revalidated {
+    /// The server confirmed the cached copy is current (304).
+    NotModified,
+    /// A fresh body, with the ETag to revalidate it next time.
+    Modified { body: Vec<u8>, etag: Option<String> },
+}
+
+/// Download `url` unless it still matches `etag` from an earlier download.
+pub fn download_if_modified(url: &str, etag: Option<&str>) -> Result<Revalidated, CommonError> {
+    let mut request = ureq::get(url);
+    if let Some(etag) = etag {
+        request = request.header("If-None-Match", etag);
+    }
+    let mut response = request.call().map_err(|e| request_error(url, e))?;
+    if response.status().as_u16() == 304 {
+        return Ok(Revalidated::NotModified);
+    }
+
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let mut body = Vec::new();
+    response
         .body_mut()
         .as_reader()
-        .read_to_end(&mut bytes)
-        .map_err(|e| CommonError::Http(e.to_string()))?;
-    Ok(bytes)
+        .read_to_end(&mut body)
+        .map_err(|e| CommonError::Network(e.to_string()))?;
+    Ok(Revalidated::Modified { body, etag })
 }
 
 /// Download and deserialize JSON from a URL with custom headers.
@@ -49,3 +424,228 @@ pub fn download_text_with_headers(
     let bytes = download_with_headers(url, headers)?;
     String::from_utf8(bytes).map_err(|e| CommonError::Http(format!("invalid UTF-8: {e}")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::sha256_hex;
+    use mockito::{Matcher, Server};
+
+    const BODY: &[u8] = b"0123456789abcdef";
+
+    fn downloader() -> Downloader {
+        Downloader::new().github_token(None).retry(RetryPolicy {
+            attempts: 2,
+            initial_backoff: Duration::from_millis(1),
+        })
+    }
+
+    #[test]
+    fn downloads_and_verifies_checksum() {
+        let mut server = Server::new();
+        server.mock("GET", "/tool").with_body(BODY).create();
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("tool");
+        let expected = sha256_hex(BODY);
+
+        let seen = std::cell::Cell::new(0);
+        let progress = |done: u64, _total: Option<u64>| seen.set(done);
+        let result = downloader()
+            .download(
+                &format!("{}/tool", server.url()),
+                &dest,
+                &DownloadOptions {
+                    checksum: Some(&expected),
+                    progress: Some(&progress),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+        assert_eq!(result.size, BODY.len() as u64);
+        assert_eq!(result.sha256, expected);
+        assert_eq!(result.resumed_from, 0);
+        assert_eq!(seen.get(), BODY.len() as u64);
+    }
+
+    #[test]
+    fn verifies_prefixed_checksums_in_other_algorithms() {
+        let mut server = Server::new();
+        server.mock("GET", "/tool").with_body(BODY).create();
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("{}/tool", server.url());
+
+        for algorithm in [Algorithm::Sha512, Algorithm::Blake3] {
+            let dest = temp.path().join(algorithm.name());
+            let expected = Checksum::of(algorithm, BODY).to_string();
+            let result = downloader()
+                .download(
+                    &url,
+                    &dest,
+                    &DownloadOptions {
+                        checksum: Some(&expected),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            assert_eq!(result.sha256, sha256_hex(BODY));
+        }
+
+        let dest = temp.path().join("bad");
+        let err = downloader()
+            .download(
+                &url,
+                &dest,
+                &DownloadOptions {
+                    checksum: Some(&format!("blake3:{}", "0".repeat(64))),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(err, CommonError::ChecksumMismatch { .. }));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn resumes_partial_download_with_range_request() {
+        let mut server = Server::new();
+        let ranged = server
+            .mock("GET", "/tool")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_body(&BODY[6..])
+            .create();
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("tool");
+        fs::write(partial_path(&dest), &BODY[..6]).unwrap();
+
+        let result = downloader()
+            .download(
+                &format!("{}/tool", server.url()),
+                &dest,
+                &DownloadOptions {
+                    checksum: Some(&sha256_hex(BODY)),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        ranged.assert();
+        assert_eq!(result.resumed_from, 6);
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+        assert!(!partial_path(&dest).exists());
+    }
+
+    #[test]
+    fn starts_over_when_server_ignores_range() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/tool")
+            .match_header("range", Matcher::Any)
+            .with_status(200)
+            .with_body(BODY)
+            .create();
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("tool");
+        fs::write(partial_path(&dest), b"stale!").unwrap();
+
+        downloader()
+            .download(
+                &format!("{}/tool", server.url()),
+                &dest,
+                &DownloadOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+    }
+
+    #[test]
+    fn checksum_mismatch_discards_download() {
+        let mut server = Server::new();
+        server.mock("GET", "/tool").with_body(BODY).create();
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("tool");
+
+        let err = downloader()
+            .download(
+                &format!("{}/tool", server.url()),
+                &dest,
+                &DownloadOptions {
+                    checksum: Some(&"0".repeat(64)),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+
+        assert!(matches!(err, CommonError::ChecksumMismatch { .. }));
+        assert!(!dest.exists());
+        assert!(!partial_path(&dest).exists());
+    }
+
+    #[test]
+    fn permanent_errors_are_not_retried() {
+        let mut server = Server::new();
+        let missing = server
+            .mock("GET", "/tool")
+            .with_status(404)
+            .expect(1)
+            .create();
+        let temp = tempfile::tempdir().unwrap();
+
+        let err = downloader()
+            .download(
+                &format!("{}/tool", server.url()),
+                &temp.path().join("tool"),
+                &DownloadOptions::default(),
+            )
+            .unwrap_err();
+
+        missing.assert();
+        assert!(matches!(err, CommonError::HttpStatus { status: 404, .. }));
+    }
+
+    #[test]
+    fn conditional_download_revalidates_with_etag() {
+        let mut server = Server::new();
+        let fresh = server
+            .mock("GET", "/index")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_header("etag", "\"v1\"")
+            .with_body(BODY)
+            .create();
+        let cached = server
+            .mock("GET", "/index")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .create();
+        let url = format!("{}/index", server.url());
+
+        assert_eq!(
+            download_if_modified(&url, None).unwrap(),
+            Revalidated::Modified {
+                body: BODY.to_vec(),
+                etag: Some("\"v1\"".to_string()),
+            }
+        );
+        assert_eq!(
+            download_if_modified(&url, Some("\"v1\"")).unwrap(),
+            Revalidated::NotModified
+        );
+        fresh.assert();
+        cached.assert();
+    }
+
+    #[test]
+    fn github_token_only_goes_to_github_hosts() {
+        assert!(is_github_host("https://api.github.com/repos/a/b"));
+        assert!(is_github_host(
+            "https://objects.githubusercontent.com/github-production-release-asset/1"
+        ));
+        assert!(is_github_host(
+            "https://github.com:443/a/b/releases/download/v1/x"
+        ));
+        assert!(!is_github_host("https://github.com.evil.example/x"));
+        assert!(!is_github_host("https://nodejs.org/dist/index.json"));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod archive;
 pub mod checksum;
 pub mod error;
+pub mod gpg;
 #[cfg(feature = "http")]
 pub mod http;
 pub mod manifest;
@@ -1,3 +1,4 @@
+use crate::checksum::{Algorithm, Checksum};
 use crate::error::CommonError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -57,9 +58,16 @@ pub struct PinnedVersion {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
 
-    /// SHA256 checksum of the downloaded asset
+    /// SHA256 checksum of the downloaded asset (superseded by `checksum`,
+    /// still honoured when that is unset)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub sha256: String,
 
+    /// Checksum of the downloaded asset: bare SHA-256/SHA-512 hex, or
+    /// prefixed (`sha512:<hex>`, `blake3:<hex>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+
     /// Whether GPG signature was verified
     #[serde(default)]
     pub gpg_verified: bool,
@@ -68,6 +76,50 @@ pub struct PinnedVersion {
     pub pinned_at: DateTime<Utc>,
 }
 
+impl PinnedVersion {
+    /// The checksum the asset must match: `checksum` when set, else `sha256`.
+    pub fn expected_checksum(&self) -> &str {
+        self.checksum.as_deref().unwrap_or(&self.sha256)
+    }
+
+    /// The algorithm of the expected checksum, defaulting to SHA-256.
+    pub fn algorithm(&self) -> Algorithm {
+        self.expected_checksum()
+            .parse::<Checksum>()
+            .map(|c| c.algorithm)
+            .unwrap_or(Algorithm::Sha256)
+    }
+
+    /// Record a checksum. SHA-256 goes in `sha256`, where older tools look
+    /// for it; other algorithms go in `checksum`.
+    pub fn set_checksum(&mut self, checksum: &Checksum) {
+        if checksum.algorithm == Algorithm::Sha256 {
+            self.sha256 = checksum.digest.clone();
+            self.checksum = None;
+        } else {
+            self.sha256.clear();
+            self.checksum = Some(checksum.to_string());
+        }
+    }
+
+    /// Replace the checksum with an all-zero placeholder in the same
+    /// algorithm, marking it as needing a relock.
+    pub fn reset_checksum(&mut self) {
+        let algorithm = self.algorithm();
+        self.set_checksum(&Checksum {
+            algorithm,
+            digest: "0".repeat(algorithm.digest_len()),
+        });
+    }
+
+    /// Whether the checksum is missing or an all-zero placeholder.
+    pub fn has_placeholder_checksum(&self) -> bool {
+        let expected = self.expected_checksum();
+        let digest = expected.rsplit_once(':').map_or(expected, |(_, d)| d);
+        digest.chars().all(|c| c == '0')
+    }
+}
+
 /// How to install an upstream dependency.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -126,6 +178,26 @@ pub struct Upstream {
     /// Installation configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub install: Option<InstallConfig>,
+
+    /// URL of a detached GPG signature for the pinned asset.
+    /// Use {version} placeholder for version substitution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_url: Option<String>,
+
+    /// Key that signs `signature_url`: an inline ASCII-armored public key,
+    /// or a path to one relative to the repository root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpg_key: Option<String>,
+
+    /// Architectures this upstream is available for (e.g., `["x86_64"]`).
+    /// Empty means all architectures.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arches: Vec<String>,
+
+    /// Alternative URLs for the pinned asset, tried in order when
+    /// `pinned.url` fails. Use {version} placeholder for version substitution.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
 }
 
 /// The upstream manifest (upstream/manifest.json).
@@ -148,7 +220,17 @@ pub struct ExternalRepo {
     pub display_name: String,
     pub baseurl: String,
     pub gpg_key: String,
+    /// Fingerprint of the signing key at `gpg_key`; the build fails if the
+    /// key served there doesn't match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpg_fingerprint: Option<String>,
     pub packages: Vec<String>,
+    /// Also download the packages' dependencies that this repo provides.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resolve_deps: bool,
+    /// Alternative base URLs, tried in order after `baseurl`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
 }
 
 /// External repositories manifest (manifests/external-repos.json).
@@ -161,6 +243,33 @@ pub struct ExternalReposManifest {
     pub repos: Vec<ExternalRepo>,
 }
 
+/// Header that starts an ASCII-armored public key.
+const ARMORED_KEY_HEADER: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+
+impl Upstream {
+    /// The signature URL with `{version}` substituted, if one is configured.
+    pub fn resolved_signature_url(&self) -> Option<String> {
+        self.signature_url
+            .as_ref()
+            .map(|url| url.replace("{version}", &self.pinned.version))
+    }
+
+    /// Mirror URLs with `{version}` substituted.
+    pub fn resolved_mirrors(&self) -> Vec<String> {
+        self.mirrors
+            .iter()
+            .map(|url| url.replace("{version}", &self.pinned.version))
+            .collect()
+    }
+
+    /// The repository-relative key path, when `gpg_key` is not inline.
+    pub fn gpg_key_path(&self) -> Option<&str> {
+        self.gpg_key
+            .as_deref()
+            .filter(|key| !key.trim_start().starts_with(ARMORED_KEY_HEADER))
+    }
+}
+
 impl UpstreamManifest {
     /// Load a manifest from a specific path.
     pub fn load_from(path: &Path) -> Result<Self, CommonError> {
@@ -388,3 +497,50 @@ impl ResolvedVendorArtifactsManifest {
         self.artifacts.iter().find(|a| a.name == name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pinned(json: &str) -> PinnedVersion {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn pinned_checksum_supersedes_legacy_sha256() {
+        let sha256 = "a".repeat(64);
+        let legacy = pinned(&format!(
+            r#"{{"version": "v1", "sha256": "{sha256}", "pinned_at": "2026-01-01T00:00:00Z"}}"#
+        ));
+        assert_eq!(legacy.expected_checksum(), sha256);
+        assert_eq!(legacy.algorithm(), Algorithm::Sha256);
+
+        let blake3 = format!("blake3:{}", "b".repeat(64));
+        let generic = pinned(&format!(
+            r#"{{"version": "v1", "sha256": "{sha256}", "checksum": "{blake3}", "pinned_at": "2026-01-01T00:00:00Z"}}"#
+        ));
+        assert_eq!(generic.expected_checksum(), blake3);
+        assert_eq!(generic.algorithm(), Algorithm::Blake3);
+    }
+
+    #[test]
+    fn set_checksum_keeps_sha256_in_legacy_field() {
+        let mut pin = pinned(r#"{"version": "v1", "pinned_at": "2026-01-01T00:00:00Z"}"#);
+        assert!(pin.has_placeholder_checksum());
+
+        pin.set_checksum(&Checksum::of(Algorithm::Sha512, b"x"));
+        let json = serde_json::to_value(&pin).unwrap();
+        assert!(json.get("sha256").is_none());
+        assert!(json["checksum"].as_str().unwrap().starts_with("sha512:"));
+
+        pin.reset_checksum();
+        assert!(pin.has_placeholder_checksum());
+        assert_eq!(pin.algorithm(), Algorithm::Sha512);
+
+        pin.set_checksum(&Checksum::of(Algorithm::Sha256, b"x"));
+        let json = serde_json::to_value(&pin).unwrap();
+        assert_eq!(json["sha256"], crate::checksum::sha256_hex(b"x"));
+        assert!(json.get("checksum").is_none());
+        assert!(!pin.has_placeholder_checksum());
+    }
+}
@@ -9,6 +9,7 @@ use clap::{Parser, Subcommand};
 use crate::commands;
 use crate::context;
 use crate::context::ExecutionContext;
+use crate::output::OutputFormat;
 
 #[derive(Debug, Parser)]
 #[command(name = "bkt")]
@@ -35,10 +36,41 @@ pub struct Cli {
     #[arg(long, short = 'n', global = true)]
     pub dry_run: bool,
 
+    /// Output format (json prints dry-run plans and execution reports as JSON)
+    #[arg(long, value_enum, global = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Only print errors, warnings, and requested data
+    #[arg(long, short = 'q', global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print more detail and enable debug logging (repeat for trace logging)
+    #[arg(long, short = 'v', global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
     /// Skip preflight checks for PR workflow
     #[arg(long, global = true)]
     pub skip_preflight: bool,
 
+    /// Don't sync the repo checkout with upstream before creating a PR
+    #[arg(long, global = true)]
+    pub no_sync: bool,
+
+    /// Stage the PR for this change instead of opening it
+    ///
+    /// Staged changes accumulate until `bkt pr submit` opens one PR for
+    /// all of them.
+    #[arg(long, global = true)]
+    pub stage: bool,
+
+    /// Work without the network
+    ///
+    /// Network-backed validation is skipped with a warning, and PRs are
+    /// deferred until `bkt pr flush`. Without this flag, bkt probes the
+    /// network (briefly) the first time a command needs it.
+    #[arg(long, global = true)]
+    pub offline: bool,
+
     /// Don't auto-delegate to host/toolbox (for debugging)
     #[arg(long, global = true, hide = true)]
     pub no_delegate: bool,
@@ -121,6 +153,9 @@ pub enum Commands {
     /// Repository information
     Repo(commands::repo::RepoArgs),
 
+    /// Review and submit PR changes staged with --stage or deferred offline
+    Pr(commands::pr::PrArgs),
+
     /// Generate JSON schemas for manifest types
     Schema(commands::schema::SchemaArgs),
 
@@ -136,12 +171,22 @@ pub enum Commands {
     /// Manage upstream dependencies (themes, icons, fonts, tools)
     Upstream(commands::upstream::UpstreamArgs),
 
+    /// Restore the previous version of a recently changed manifest
+    Undo(commands::undo::UndoArgs),
+
+    /// Manage external RPM repos (signing key pins)
+    #[command(name = "external-repo")]
+    ExternalRepo(commands::external_repo::ExternalRepoArgs),
+
     /// Manage distribution changelog and version history
     Changelog(commands::changelog::ChangelogArgs),
 
     /// Check for configuration drift between manifests and system
     Drift(commands::drift::DriftArgs),
 
+    /// Compare the running system with the manifests at a git ref
+    Diff(commands::diff::DiffArgs),
+
     /// Track what the upstream Bazzite image provides (for drift detection)
     Base(commands::base::BaseArgs),
 
@@ -208,10 +253,14 @@ impl Commands {
 
             // Either: pure utilities or work on repo/user files only
             Commands::Drift(_) => CommandTarget::Either,
+            Commands::Diff(_) => CommandTarget::Either,
             Commands::Repo(_) => CommandTarget::Either,
+            Commands::Pr(_) => CommandTarget::Either,
             Commands::Schema(_) => CommandTarget::Either,
             Commands::Completions(_) => CommandTarget::Either,
             Commands::Upstream(_) => CommandTarget::Either,
+            Commands::ExternalRepo(_) => CommandTarget::Either,
+            Commands::Undo(_) => CommandTarget::Either,
             Commands::Changelog(_) => CommandTarget::Either,
             Commands::Skel(_) => CommandTarget::Either,
             Commands::BuildInfo(_) => CommandTarget::Either,
@@ -3,11 +3,13 @@
 //! Provides passwordless access to bootc operations via polkit + pkexec.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::context::HostExec;
 use anyhow::{Context, Result, bail};
 use clap::Subcommand;
 use is_terminal::IsTerminal;
 use owo_colors::OwoColorize;
 
+use crate::manifest::system_config::{SystemConfigManifest, UpgradePolicy};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 
@@ -17,7 +19,8 @@ pub enum BootcAction {
     /// Show current deployment status (passwordless, read-only)
     ///
     /// Displays information about current and staged deployments,
-    /// image references, and update availability.
+    /// image references, and update availability, followed by the
+    /// deployment indices used by `pin`/`unpin` and which are pinned.
     Status,
 
     /// Upgrade to the latest image (requires --confirm or --yes)
@@ -65,6 +68,84 @@ pub enum BootcAction {
         #[arg(long, short = 'y')]
         yes: bool,
     },
+
+    /// Pin a deployment so it is never garbage collected (requires --confirm or --yes)
+    ///
+    /// Keeps a known-good deployment around across future upgrades.
+    /// The index is the deployment index shown by `bkt admin bootc status`.
+    Pin {
+        /// Deployment index (see `bkt admin bootc status`)
+        index: usize,
+
+        /// Confirm the pin operation (required for safety)
+        #[arg(long)]
+        confirm: bool,
+
+        /// Skip confirmation prompt and proceed (implies --confirm, for automation)
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Unpin a previously pinned deployment (requires --confirm or --yes)
+    ///
+    /// The deployment becomes eligible for garbage collection again.
+    Unpin {
+        /// Deployment index (see `bkt admin bootc status`)
+        index: usize,
+
+        /// Confirm the unpin operation (required for safety)
+        #[arg(long)]
+        confirm: bool,
+
+        /// Skip confirmation prompt and proceed (implies --confirm, for automation)
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Manage the unattended upgrade window (image-time configuration)
+    ///
+    /// The policy in system-config.json becomes a bootc-auto-upgrade.timer
+    /// in the next image build.
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+}
+
+/// Upgrade window operations.
+#[derive(Debug, Subcommand)]
+pub enum ScheduleAction {
+    /// Show the upgrade policy
+    Show,
+
+    /// Set the upgrade policy
+    Set {
+        /// When to upgrade, in systemd OnCalendar syntax (e.g. "Sun *-*-* 03:00")
+        schedule: String,
+
+        /// Reboot into the new deployment once one is staged
+        #[arg(long)]
+        auto_reboot: bool,
+
+        /// Only reboot when the system is idle (with --auto-reboot)
+        #[arg(long)]
+        only_if_idle: bool,
+    },
+
+    /// Remove the upgrade policy
+    Clear,
+}
+
+/// A deployment as reported by `bootc status --json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Deployment {
+    /// ostree deployment index (`None` for a staged deployment)
+    index: Option<usize>,
+    /// "staged", "booted", "rollback" or "other"
+    role: &'static str,
+    image: Option<String>,
+    version: Option<String>,
+    pinned: bool,
 }
 
 /// Execute a bootc action.
@@ -80,6 +161,17 @@ pub fn run(action: BootcAction, plan: &ExecutionPlan) -> Result<()> {
             yes,
         } => handle_switch(plan, &image, confirm, yes, runner),
         BootcAction::Rollback { confirm, yes } => handle_rollback(plan, confirm, yes, runner),
+        BootcAction::Pin {
+            index,
+            confirm,
+            yes,
+        } => handle_pin(plan, index, false, confirm, yes, runner),
+        BootcAction::Unpin {
+            index,
+            confirm,
+            yes,
+        } => handle_pin(plan, index, true, confirm, yes, runner),
+        BootcAction::Schedule { action } => handle_schedule(plan, action),
     }
 }
 
@@ -93,7 +185,111 @@ fn handle_status(plan: &ExecutionPlan, runner: &dyn CommandRunner) -> Result<()>
         return Ok(());
     }
 
-    exec_bootc("status", &[], runner)
+    exec_bootc("status", &[], runner)?;
+
+    // Pin state isn't part of the human-readable output; show it separately.
+    // Failure here shouldn't hide the status that was already printed.
+    match query_deployments(runner) {
+        Ok(deployments) => print_deployments(&deployments),
+        Err(e) => Output::warning(format!("Could not determine pinned deployments: {:#}", e)),
+    }
+
+    Ok(())
+}
+
+/// Query deployments (with pin state) from `bootc status --json`.
+fn query_deployments(runner: &dyn CommandRunner) -> Result<Vec<Deployment>> {
+    let output = HostExec::new(runner)
+        .run_on_host("pkexec", &["bootc", "status", "--json"])
+        .context("Failed to execute pkexec bootc status --json")?;
+
+    if !output.status.success() {
+        bail!(
+            "bootc status --json failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("Failed to parse bootc status JSON")?;
+    Ok(parse_deployments(&json))
+}
+
+/// Extract deployments from `bootc status --json` output.
+///
+/// Indices follow ostree's deployment order (what `ostree admin pin`
+/// expects): the default deployment first, then the other, then any
+/// additional (e.g. pinned) deployments. A staged deployment has no
+/// index until it is finalized at shutdown.
+fn parse_deployments(json: &serde_json::Value) -> Vec<Deployment> {
+    let status = &json["status"];
+
+    let entry = |role: &'static str, value: &serde_json::Value| -> Option<Deployment> {
+        if value.is_null() {
+            return None;
+        }
+        let image = &value["image"];
+        Some(Deployment {
+            index: None,
+            role,
+            image: image["image"]["image"].as_str().map(str::to_string),
+            version: image["version"].as_str().map(str::to_string),
+            pinned: value["pinned"].as_bool().unwrap_or(false),
+        })
+    };
+
+    let booted = entry("booted", &status["booted"]);
+    let rollback = entry("rollback", &status["rollback"]);
+    let rollback_queued = status["rollbackQueued"].as_bool().unwrap_or(false);
+
+    let ordered = if rollback_queued {
+        [rollback, booted]
+    } else {
+        [booted, rollback]
+    };
+    let others = status["otherDeployments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|value| entry("other", value));
+
+    let mut deployments: Vec<Deployment> = entry("staged", &status["staged"]).into_iter().collect();
+    for (index, mut deployment) in ordered.into_iter().flatten().chain(others).enumerate() {
+        deployment.index = Some(index);
+        deployments.push(deployment);
+    }
+    deployments
+}
+
+/// Print the deployment table with pin state.
+fn print_deployments(deployments: &[Deployment]) {
+    if deployments.is_empty() {
+        return;
+    }
+
+    println!();
+    Output::subheader("Deployments:");
+    println!("  {:<6} {:<9} {:<7} IMAGE", "INDEX", "ROLE", "PINNED");
+    for deployment in deployments {
+        let index = deployment
+            .index
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let pinned = if deployment.pinned {
+            "yes".green().to_string()
+        } else {
+            "no".dimmed().to_string()
+        };
+        let image = match (&deployment.image, &deployment.version) {
+            (Some(image), Some(version)) => format!("{} ({})", image, version),
+            (Some(image), None) => image.clone(),
+            (None, _) => "unknown".to_string(),
+        };
+        println!(
+            "  {:<6} {:<9} {:<7} {}",
+            index, deployment.role, pinned, image
+        );
+    }
 }
 
 /// Handle `bkt admin bootc upgrade`.
@@ -183,6 +379,140 @@ fn handle_rollback(
     exec_bootc("rollback", &[], runner)
 }
 
+/// Handle `bkt admin bootc pin` / `bkt admin bootc unpin`.
+///
+/// bootc has no pin command of its own, so this goes through
+/// `ostree admin pin`, which operates on the same deployments.
+fn handle_pin(
+    plan: &ExecutionPlan,
+    index: usize,
+    unpin: bool,
+    confirm: bool,
+    yes: bool,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    let operation = if unpin { "unpin" } else { "pin" };
+
+    // --yes implies --confirm (for non-interactive automation)
+    let confirmed = confirm || yes;
+    require_confirmation(operation, confirmed)?;
+
+    let msg = if unpin {
+        format!(
+            "This will allow deployment {} to be garbage collected.",
+            index
+        )
+    } else {
+        format!(
+            "This will keep deployment {} across future upgrades.",
+            index
+        )
+    };
+    if !yes && !plan.dry_run && !prompt_continue(&msg)? {
+        Output::info("Cancelled.");
+        return Ok(());
+    }
+
+    let args = pin_args(index, unpin);
+    if plan.dry_run {
+        Output::dry_run(format!("Would execute: pkexec {}", args.join(" ")));
+        return Ok(());
+    }
+
+    let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+    let status = runner
+        .run_status("pkexec", &argv, &CommandOptions::default())
+        .context("Failed to execute pkexec ostree")?;
+
+    if !status.success() {
+        let code = status
+            .code()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        bail!("ostree admin {} failed with exit code {}", operation, code);
+    }
+
+    let done = if unpin { "unpinned" } else { "pinned" };
+    Output::success(format!("Deployment {} {}", index, done));
+    Ok(())
+}
+
+/// Handle `bkt admin bootc schedule`.
+fn handle_schedule(plan: &ExecutionPlan, action: ScheduleAction) -> Result<()> {
+    let mut manifest = SystemConfigManifest::load()?;
+
+    match action {
+        ScheduleAction::Show => {
+            print_upgrade_policy(manifest.upgrade_policy.as_ref());
+            return Ok(());
+        }
+        ScheduleAction::Set {
+            schedule,
+            auto_reboot,
+            only_if_idle,
+        } => {
+            let policy = UpgradePolicy {
+                schedule,
+                auto_reboot,
+                only_if_idle,
+            };
+            policy.validate()?;
+            if only_if_idle && !auto_reboot {
+                Output::warning("--only-if-idle has no effect without --auto-reboot");
+            }
+            if plan.dry_run {
+                Output::dry_run(format!("Would set upgrade schedule: {}", policy.schedule));
+                return Ok(());
+            }
+            Output::success(format!("Upgrade schedule set: {}", policy.schedule));
+            manifest.upgrade_policy = Some(policy);
+        }
+        ScheduleAction::Clear => {
+            if manifest.upgrade_policy.is_none() {
+                Output::info("No upgrade schedule configured.");
+                return Ok(());
+            }
+            if plan.dry_run {
+                Output::dry_run("Would remove the upgrade schedule");
+                return Ok(());
+            }
+            manifest.upgrade_policy = None;
+            Output::success("Upgrade schedule removed");
+        }
+    }
+
+    manifest.save()?;
+    Output::hint("The schedule takes effect after the next image build.");
+    Ok(())
+}
+
+/// Print the upgrade policy.
+fn print_upgrade_policy(policy: Option<&UpgradePolicy>) {
+    Output::subheader("Upgrade Schedule (Manifest)");
+    let Some(policy) = policy else {
+        Output::info("No upgrade schedule configured.");
+        return;
+    };
+
+    let reboot = match (policy.auto_reboot, policy.only_if_idle) {
+        (false, _) => "no",
+        (true, false) => "yes",
+        (true, true) => "when idle",
+    };
+    Output::kv("Schedule", &policy.schedule);
+    Output::kv("Auto-reboot", reboot);
+}
+
+/// Arguments (after `pkexec`) for pinning or unpinning a deployment.
+fn pin_args(index: usize, unpin: bool) -> Vec<String> {
+    let mut args = vec!["ostree".to_string(), "admin".to_string(), "pin".to_string()];
+    if unpin {
+        args.push("--unpin".to_string());
+    }
+    args.push(index.to_string());
+    args
+}
+
 /// Require the --confirm or --yes flag for mutating operations.
 fn require_confirmation(operation: &str, confirmed: bool) -> Result<()> {
     if confirmed {
@@ -205,6 +535,16 @@ fn require_confirmation(operation: &str, confirmed: bool) -> Result<()> {
             "Does not affect the running system",
             "Can be re-upgraded with 'bkt admin bootc upgrade'",
         ],
+        "pin" => vec![
+            "Keeps the deployment from being garbage collected",
+            "Does not affect the running system or boot order",
+            "Can be undone with 'bkt admin bootc unpin'",
+        ],
+        "unpin" => vec![
+            "Lets the deployment be garbage collected on a future upgrade",
+            "Does not affect the running system or boot order",
+            "Can be undone with 'bkt admin bootc pin'",
+        ],
         _ => vec![],
     };
 
@@ -345,4 +685,72 @@ mod tests {
         assert!(upgrade_err.contains("--confirm"));
         assert!(rollback_err.contains("--confirm"));
     }
+
+    #[test]
+    fn test_pin_args() {
+        assert_eq!(pin_args(1, false), vec!["ostree", "admin", "pin", "1"]);
+        assert_eq!(
+            pin_args(2, true),
+            vec!["ostree", "admin", "pin", "--unpin", "2"]
+        );
+    }
+
+    fn sample_status(rollback_queued: bool) -> serde_json::Value {
+        serde_json::json!({
+            "apiVersion": "org.containers.bootc/v1",
+            "kind": "BootcHost",
+            "status": {
+                "staged": {
+                    "image": { "image": { "image": "ghcr.io/test/image:latest", "transport": "registry" }, "version": "43.3" },
+                    "pinned": false
+                },
+                "booted": {
+                    "image": { "image": { "image": "ghcr.io/test/image:latest", "transport": "registry" }, "version": "43.2" },
+                    "pinned": false
+                },
+                "rollback": {
+                    "image": { "image": { "image": "ghcr.io/test/image:latest", "transport": "registry" }, "version": "43.1" },
+                    "pinned": true
+                },
+                "otherDeployments": [
+                    { "image": null, "pinned": true }
+                ],
+                "rollbackQueued": rollback_queued
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_deployments_reports_pins_and_indices() {
+        let deployments = parse_deployments(&sample_status(false));
+        let summary: Vec<_> = deployments
+            .iter()
+            .map(|d| (d.index, d.role, d.pinned))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (None, "staged", false),
+                (Some(0), "booted", false),
+                (Some(1), "rollback", true),
+                (Some(2), "other", true),
+            ]
+        );
+        assert_eq!(deployments[2].version.as_deref(), Some("43.1"));
+        assert_eq!(deployments[3].image, None);
+    }
+
+    #[test]
+    fn test_parse_deployments_rollback_queued_puts_rollback_first() {
+        let deployments = parse_deployments(&sample_status(true));
+        let rollback = deployments.iter().find(|d| d.role == "rollback").unwrap();
+        let booted = deployments.iter().find(|d| d.role == "booted").unwrap();
+        assert_eq!(rollback.index, Some(0));
+        assert_eq!(booted.index, Some(1));
+    }
+
+    #[test]
+    fn test_parse_deployments_handles_missing_status() {
+        assert!(parse_deployments(&serde_json::json!({})).is_empty());
+    }
 }
@@ -2,12 +2,24 @@
 //!
 //! Manages the host command daemon for fast cross-boundary execution.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::daemon::{self, DaemonServer};
+use crate::daemon::{self, AccessPolicy, DaemonServer};
+use crate::dbus::SystemdManager;
+use crate::manifest::DaemonAllowlist;
+use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 
+/// Socket unit installed by `bkt admin daemon install`.
+const SOCKET_UNIT: &str = "bkt-hostd.socket";
+/// Service unit started by [`SOCKET_UNIT`].
+const SERVICE_UNIT: &str = "bkt-hostd.service";
+/// The always-running daemon unit shipped in the image.
+const LEGACY_UNIT: &str = "bkt-daemon.service";
+
 /// Daemon operations available via `bkt admin daemon`.
 #[derive(Debug, Subcommand)]
 pub enum DaemonAction {
@@ -16,8 +28,30 @@ pub enum DaemonAction {
     /// Starts the host command daemon, listening for requests from
     /// distrobox containers. Press Ctrl+C to stop.
     ///
-    /// The daemon listens on $XDG_RUNTIME_DIR/bkt/host.sock.
-    Run,
+    /// The daemon listens on $XDG_RUNTIME_DIR/bkt/host.sock and only runs
+    /// programs listed in manifests/daemon-allowlist.json.
+    Run {
+        /// Run any program, ignoring the allowlist (connections must still
+        /// come from the same user)
+        #[arg(long)]
+        allow_all: bool,
+
+        /// Exit after this many seconds without a connection
+        #[arg(long, value_name = "SECS")]
+        idle_exit: Option<u64>,
+    },
+
+    /// Install socket-activated user units for the daemon
+    ///
+    /// Writes bkt-hostd.socket and bkt-hostd.service to
+    /// ~/.config/systemd/user/ and enables the socket. systemd then starts
+    /// the daemon on the first connection, and the daemon exits again after
+    /// --idle-exit seconds without one.
+    Install {
+        /// Seconds of inactivity before the daemon exits
+        #[arg(long, value_name = "SECS", default_value_t = 600)]
+        idle_exit: u64,
+    },
 
     /// Show daemon status
     ///
@@ -29,6 +63,10 @@ pub enum DaemonAction {
     /// Connects to the running daemon and executes the given command.
     /// This is useful for testing the daemon protocol.
     Test {
+        /// Stream output over the socket instead of passing stdout/stderr
+        #[arg(long)]
+        stream: bool,
+
         /// Command and arguments to execute
         #[arg(trailing_var_arg = true, required = true)]
         command: Vec<String>,
@@ -36,35 +74,178 @@ pub enum DaemonAction {
 }
 
 /// Execute a daemon subcommand.
-pub fn run(action: DaemonAction, _plan: &ExecutionPlan) -> Result<()> {
+pub fn run(action: DaemonAction, plan: &ExecutionPlan) -> Result<()> {
     match action {
-        DaemonAction::Run => run_foreground(),
+        DaemonAction::Run {
+            allow_all,
+            idle_exit,
+        } => run_foreground(allow_all, idle_exit.map(Duration::from_secs)),
+        DaemonAction::Install { idle_exit } => install_units(idle_exit, plan),
         DaemonAction::Status => show_status(),
-        DaemonAction::Test { command } => test_execute(command),
+        DaemonAction::Test { stream, command } => test_execute(command, stream),
     }
 }
 
 /// Run the daemon in foreground mode.
-fn run_foreground() -> Result<()> {
+fn run_foreground(allow_all: bool, idle_exit: Option<Duration>) -> Result<()> {
     let socket_path = daemon::socket_path()?;
 
+    let policy = if allow_all {
+        AccessPolicy::allow_all()
+    } else {
+        let allowlist = DaemonAllowlist::load_repo().unwrap_or_else(|e| {
+            Output::warning(format!("Using default daemon allowlist: {}", e));
+            DaemonAllowlist::default()
+        });
+        AccessPolicy::new(&allowlist)
+    };
+
     eprintln!("Starting bkt host daemon...");
     eprintln!("Socket: {}", socket_path.display());
+    match &policy.allowed {
+        Some(allowed) => eprintln!(
+            "Allowed: {}",
+            allowed.iter().cloned().collect::<Vec<_>>().join(", ")
+        ),
+        None => eprintln!("Allowed: any program (--allow-all)"),
+    }
+    if let Some(idle) = idle_exit {
+        eprintln!("Idle exit: {}s", idle.as_secs());
+    }
     eprintln!("Press Ctrl+C to stop.\n");
 
-    let server = DaemonServer::bind(&socket_path)?;
+    let server = DaemonServer::listen(&socket_path)?
+        .with_policy(policy)
+        .with_idle_exit(idle_exit);
     server.run()?;
 
     Ok(())
 }
 
+/// Directory for the user's own systemd units.
+fn user_unit_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("Cannot determine $HOME")?;
+    Ok(PathBuf::from(home).join(".config/systemd/user"))
+}
+
+/// Contents of `bkt-hostd.socket`.
+fn socket_unit() -> String {
+    "\
+[Unit]
+Description=bkt host command daemon socket
+Documentation=man:bkt(1)
+
+[Socket]
+ListenStream=%t/bkt/host.sock
+SocketMode=0600
+DirectoryMode=0700
+
+[Install]
+WantedBy=sockets.target
+"
+    .to_string()
+}
+
+/// Contents of `bkt-hostd.service`, running `bkt` at `exe`.
+fn service_unit(exe: &Path, idle_exit: u64) -> String {
+    format!(
+        "\
+[Unit]
+Description=bkt host command daemon
+Documentation=man:bkt(1)
+Requires={SOCKET_UNIT}
+After={SOCKET_UNIT}
+
+[Service]
+Type=simple
+ExecStart={} admin daemon run --idle-exit {idle_exit}
+",
+        exe.display()
+    )
+}
+
+/// Write and enable the socket-activated daemon units.
+fn install_units(idle_exit: u64, plan: &ExecutionPlan) -> Result<()> {
+    let unit_dir = user_unit_dir()?;
+    let exe = std::env::current_exe().context("Cannot determine path to bkt")?;
+    let units = [
+        (SOCKET_UNIT, socket_unit()),
+        (SERVICE_UNIT, service_unit(&exe, idle_exit)),
+    ];
+
+    if plan.dry_run {
+        for (name, _) in &units {
+            Output::dry_run(format!("Write {}", unit_dir.join(name).display()));
+        }
+        Output::dry_run(format!(
+            "systemctl --user stop {} (if running)",
+            LEGACY_UNIT
+        ));
+        Output::dry_run(format!("systemctl --user enable --now {}", SOCKET_UNIT));
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&unit_dir)
+        .with_context(|| format!("Failed to create {}", unit_dir.display()))?;
+    for (name, content) in &units {
+        let path = unit_dir.join(name);
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Output::success(format!("Wrote {}", path.display()));
+    }
+
+    let systemd = SystemdManager::user()?;
+    systemd.daemon_reload()?;
+
+    // The always-on daemon would hold the socket path; socket activation
+    // replaces it
+    if systemd.status(LEGACY_UNIT).is_ok_and(|s| s.is_active()) {
+        systemd.stop(LEGACY_UNIT)?;
+        Output::info(format!("Stopped {}", LEGACY_UNIT));
+    }
+    if systemd
+        .get_unit_file_state(LEGACY_UNIT)
+        .is_ok_and(|state| state == "enabled")
+    {
+        systemd.mask(LEGACY_UNIT)?;
+        Output::info(format!("Masked {}", LEGACY_UNIT));
+    }
+
+    systemd.enable(SOCKET_UNIT)?;
+    systemd.start(SOCKET_UNIT)?;
+    Output::success(format!(
+        "Enabled {} (daemon starts on first connection)",
+        SOCKET_UNIT
+    ));
+
+    Ok(())
+}
+
 /// Test executing a command via the daemon.
-fn test_execute(command: Vec<String>) -> Result<()> {
-    use crate::daemon::DaemonClient;
+fn test_execute(command: Vec<String>, stream: bool) -> Result<()> {
+    use crate::daemon::{DaemonClient, OutputStream};
+    use std::io::Write;
 
     let socket_path = daemon::socket_path()?;
     let client = DaemonClient::new(&socket_path);
-    let exit_code = client.execute_current(&command)?;
+    let exit_code = if stream {
+        let envp: Vec<String> = std::env::vars()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        let cwd = std::env::current_dir()?;
+        client.execute_streaming(&command, &envp, &cwd, |stream, data| {
+            // Best effort: a closed stdout shouldn't abort the command
+            let _ = match stream {
+                OutputStream::Stdout => {
+                    let mut stdout = std::io::stdout().lock();
+                    stdout.write_all(data).and_then(|_| stdout.flush())
+                }
+                OutputStream::Stderr => std::io::stderr().write_all(data),
+            };
+        })?
+    } else {
+        client.execute_current(&command)?
+    };
 
     std::process::exit(exit_code);
 }
@@ -86,15 +267,38 @@ fn show_status() -> Result<()> {
                 println!("\nThe socket file exists but the daemon is not responding.");
                 println!("You may need to remove the stale socket and restart:");
                 println!("  rm {}", socket_path.display());
-                println!("  bkt admin daemon run");
+                println!("  bkt admin daemon install");
             }
         }
     } else {
         println!("Daemon Status: not running");
         println!("  Socket: {} (not found)", socket_path.display());
-        println!("\nTo start the daemon:");
+        println!("\nTo start the daemon on demand:");
+        println!("  bkt admin daemon install");
+        println!("\nOr run it in the foreground:");
         println!("  bkt admin daemon run");
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_unit_listens_on_daemon_socket() {
+        let unit = socket_unit();
+        assert!(unit.contains("ListenStream=%t/bkt/host.sock\n"));
+        assert!(unit.contains("WantedBy=sockets.target\n"));
+    }
+
+    #[test]
+    fn service_unit_runs_with_idle_exit() {
+        let unit = service_unit(Path::new("/usr/bin/bkt"), 300);
+        assert!(unit.contains("Requires=bkt-hostd.socket\n"));
+        assert!(unit.contains("ExecStart=/usr/bin/bkt admin daemon run --idle-exit 300\n"));
+        // Started by the socket, never on its own
+        assert!(!unit.contains("[Install]"));
+    }
+}
@@ -1,11 +1,22 @@
 //! Kargs subcommand implementation for `bkt admin kargs`.
 //!
 //! Manages persistent kernel arguments in the `system-config.json` manifest.
+//!
+//! Besides individual arguments, `bkt admin kargs preset` applies built-in
+//! bundles of them ("quiet boot", "zswap tuning"). The manifest records which
+//! arguments each applied preset added, so removing a preset drops only
+//! those and leaves anything that was added by hand.
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use clap::Subcommand;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
 
-use crate::manifest::system_config::{KargsConfig, SystemConfigManifest};
+use crate::containerfile::{
+    ContainerfileEditor, Section, generate_kernel_arguments, is_placeholder_content,
+};
+use crate::manifest::system_config::{KargsConfig, PresetKargs, SystemConfigManifest};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 
@@ -28,26 +39,282 @@ pub enum KargsAction {
 
     /// List managed kernel arguments
     List,
+
+    /// Compare manifest kargs against the running kernel command line
+    Diff {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Apply or remove built-in bundles of kernel arguments
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
+    },
+}
+
+/// Kernel argument preset operations.
+#[derive(Debug, Subcommand)]
+pub enum PresetAction {
+    /// List the built-in presets
+    List,
+
+    /// Add a preset's arguments to the manifest
+    Apply {
+        /// Preset name (see `bkt admin kargs preset list`)
+        name: String,
+    },
+
+    /// Drop the arguments a preset added
+    Remove {
+        /// Preset name
+        name: String,
+    },
+}
+
+/// A named bundle of kernel arguments.
+#[derive(Debug)]
+struct KargsPreset {
+    name: &'static str,
+    description: &'static str,
+    append: &'static [&'static str],
+    remove: &'static [&'static str],
+}
+
+/// The built-in presets.
+const PRESETS: &[KargsPreset] = &[
+    KargsPreset {
+        name: "amd-pstate",
+        description: "AMD P-State driver in active (EPP) mode, for the performance profile",
+        append: &["amd_pstate=active"],
+        remove: &[],
+    },
+    KargsPreset {
+        name: "iommu-passthrough",
+        description: "IOMMU on in passthrough mode, for VFIO device assignment",
+        append: &["amd_iommu=on", "intel_iommu=on", "iommu=pt"],
+        remove: &[],
+    },
+    KargsPreset {
+        name: "quiet-boot",
+        description: "Graphical boot with kernel and udev messages hidden",
+        append: &["quiet", "rhgb", "loglevel=3", "rd.udev.log_level=3"],
+        remove: &[],
+    },
+    KargsPreset {
+        name: "verbose-boot",
+        description: "Show kernel messages while booting",
+        append: &["loglevel=7"],
+        remove: &["quiet", "rhgb"],
+    },
+    KargsPreset {
+        name: "zswap",
+        description: "Compressed swap cache using zstd and zsmalloc",
+        append: &[
+            "zswap.enabled=1",
+            "zswap.compressor=zstd",
+            "zswap.zpool=zsmalloc",
+            "zswap.max_pool_percent=25",
+        ],
+        remove: &[],
+    },
+];
+
+fn find_preset(name: &str) -> Result<&'static KargsPreset> {
+    PRESETS.iter().find(|p| p.name == name).with_context(|| {
+        let names: Vec<&str> = PRESETS.iter().map(|p| p.name).collect();
+        format!("Unknown preset '{}'. Available: {}", name, names.join(", "))
+    })
+}
+
+/// A preset argument at odds with one from an already-applied preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PresetConflict {
+    /// The argument from the preset being applied
+    arg: String,
+    /// The applied preset it conflicts with
+    other: String,
+    /// The other preset's argument
+    other_arg: String,
+}
+
+impl std::fmt::Display for PresetConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} vs {} (from {})",
+            self.arg, self.other_arg, self.other
+        )
+    }
+}
+
+/// Where the running kernel's command line is read from.
+const PROC_CMDLINE: &str = "/proc/cmdline";
+
+/// Manifest kargs compared against a kernel command line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KargsDiff {
+    /// In `append` but not on the command line
+    pub missing: Vec<String>,
+    /// In `remove` but still on the command line
+    pub unremoved: Vec<String>,
+    /// On the command line but not mentioned by the manifest
+    pub untracked: Vec<String>,
+}
+
+impl KargsDiff {
+    /// Diff the manifest against the running kernel.
+    pub fn running(config: &KargsConfig) -> Result<Self> {
+        Ok(Self::compute(config, &running_cmdline()?))
+    }
+
+    /// Diff the manifest against parsed command line tokens.
+    ///
+    /// `append` entries must match a token exactly. A `remove` entry without
+    /// a value matches the key with any value, mirroring `--delete-karg`.
+    pub fn compute(config: &KargsConfig, tokens: &[String]) -> Self {
+        let removes = |token: &str| config.remove.iter().any(|arg| karg_matches(arg, token));
+
+        let missing = config
+            .append
+            .iter()
+            .filter(|arg| !tokens.contains(arg))
+            .cloned()
+            .collect();
+        let unremoved = tokens.iter().filter(|t| removes(t)).cloned().collect();
+        let untracked = tokens
+            .iter()
+            .filter(|t| !config.append.contains(t) && !removes(t))
+            .cloned()
+            .collect();
+
+        Self {
+            missing,
+            unremoved,
+            untracked,
+        }
+    }
+
+    /// True when the running kernel doesn't match the manifest.
+    pub fn has_drift(&self) -> bool {
+        !self.missing.is_empty() || !self.unremoved.is_empty()
+    }
+}
+
+/// The running kernel's command line, as tokens.
+pub fn running_cmdline() -> Result<Vec<String>> {
+    let cmdline = fs::read_to_string(PROC_CMDLINE)
+        .with_context(|| format!("Failed to read {}", PROC_CMDLINE))?;
+    Ok(parse_cmdline(&cmdline))
+}
+
+/// Split a kernel command line into `key[=value]` tokens.
+///
+/// Whitespace inside double quotes (`foo="a b"`) doesn't split; the quotes
+/// are kept so tokens compare equal to how they'd be written in a karg.
+pub fn parse_cmdline(cmdline: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in cmdline.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Whether manifest karg `arg` matches command line `token`.
+fn karg_matches(arg: &str, token: &str) -> bool {
+    if arg.contains('=') {
+        arg == token
+    } else {
+        token.split('=').next() == Some(arg)
+    }
 }
 
 impl KargsAction {
-    pub fn execute(self, _plan: &ExecutionPlan) -> Result<()> {
+    pub fn execute(self, plan: &ExecutionPlan) -> Result<()> {
         let mut manifest = SystemConfigManifest::load()?;
-        let mut kargs = manifest.kargs.unwrap_or_default();
+        let mut kargs = manifest.kargs.take().unwrap_or_default();
 
-        match self {
-            KargsAction::Append { args } => Self::apply_append(&mut kargs, args),
-            KargsAction::Remove { args } => Self::apply_remove(&mut kargs, args),
+        let (action, name) = match self {
+            KargsAction::Append { args } => {
+                let name = args.join(" ");
+                Self::apply_append(&mut kargs, args);
+                ("append", name)
+            }
+            KargsAction::Remove { args } => {
+                let name = args.join(" ");
+                Self::apply_remove(&mut kargs, args);
+                ("remove", name)
+            }
             KargsAction::List => {
                 Self::list(&kargs);
                 return Ok(());
             }
-        }
+            KargsAction::Diff { json } => {
+                let diff = KargsDiff::running(&kargs)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                } else {
+                    Self::show_diff(&diff);
+                }
+                return Ok(());
+            }
+            KargsAction::Preset {
+                action: PresetAction::List,
+            } => {
+                Self::list_presets(&kargs);
+                return Ok(());
+            }
+            KargsAction::Preset {
+                action: PresetAction::Apply { name },
+            } => {
+                let preset = find_preset(&name)?;
+                if kargs.presets.contains_key(preset.name) {
+                    Output::info(format!("Preset already applied: {}", preset.name));
+                    return Ok(());
+                }
+                let conflicts = preset_conflicts(&kargs, preset);
+                if !conflicts.is_empty() {
+                    let list: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+                    bail!(
+                        "Preset '{}' conflicts with applied presets: {}",
+                        preset.name,
+                        list.join(", ")
+                    );
+                }
+                Self::apply_preset(&mut kargs, preset);
+                ("preset-apply", name)
+            }
+            KargsAction::Preset {
+                action: PresetAction::Remove { name },
+            } => {
+                if !Self::remove_preset(&mut kargs, &name) {
+                    Output::warning(format!("Preset not applied: {}", name));
+                    return Ok(());
+                }
+                ("preset-remove", name)
+            }
+        };
 
         manifest.kargs = Some(kargs);
-        manifest.save()?;
-
-        Ok(())
+        save_manifest(&manifest, plan, action, &name)
     }
 
     fn apply_append(config: &mut KargsConfig, args: Vec<String>) {
@@ -62,6 +329,11 @@ impl KargsAction {
             if let Some(pos) = config.remove.iter().position(|x| x == &arg) {
                 config.remove.remove(pos);
             }
+            // Added by hand now, so removing a preset mustn't drop it
+            for preset in config.presets.values_mut() {
+                preset.append.retain(|x| x != &arg);
+                preset.remove.retain(|x| x != &arg);
+            }
         }
     }
 
@@ -77,6 +349,92 @@ impl KargsAction {
             if let Some(pos) = config.append.iter().position(|x| x == &arg) {
                 config.append.remove(pos);
             }
+            for preset in config.presets.values_mut() {
+                preset.append.retain(|x| x != &arg);
+                preset.remove.retain(|x| x != &arg);
+            }
+        }
+    }
+
+    /// Merge a preset's arguments into the manifest, recording the ones it
+    /// added (or shares with another applied preset) under its name.
+    fn apply_preset(config: &mut KargsConfig, preset: &KargsPreset) {
+        let mut added = PresetKargs::default();
+
+        for arg in preset.append {
+            let arg = arg.to_string();
+            let shared = config.presets.values().any(|p| p.append.contains(&arg));
+            if !config.append.contains(&arg) {
+                config.append.push(arg.clone());
+                config.remove.retain(|x| x != &arg);
+                Output::success(format!("Added karg: {}", arg));
+                added.append.push(arg);
+            } else if shared {
+                added.append.push(arg);
+            } else {
+                Output::info(format!("Karg already exists: {}", arg));
+            }
+        }
+
+        for arg in preset.remove {
+            let arg = arg.to_string();
+            let shared = config.presets.values().any(|p| p.remove.contains(&arg));
+            if !config.remove.contains(&arg) {
+                config.remove.push(arg.clone());
+                config.append.retain(|x| x != &arg);
+                Output::success(format!("Arranged removal of karg: {}", arg));
+                added.remove.push(arg);
+            } else if shared {
+                added.remove.push(arg);
+            } else {
+                Output::info(format!("Karg removal already arranged: {}", arg));
+            }
+        }
+
+        config.presets.insert(preset.name.to_string(), added);
+    }
+
+    /// Drop the arguments a preset added, except those another applied
+    /// preset also added. Returns false if the preset wasn't applied.
+    fn remove_preset(config: &mut KargsConfig, name: &str) -> bool {
+        let Some(added) = config.presets.remove(name) else {
+            return false;
+        };
+
+        for arg in &added.append {
+            if config.presets.values().any(|p| p.append.contains(arg)) {
+                Output::info(format!("Keeping karg used by another preset: {}", arg));
+            } else {
+                config.append.retain(|x| x != arg);
+                Output::success(format!("Removed karg: {}", arg));
+            }
+        }
+        for arg in &added.remove {
+            if config.presets.values().any(|p| p.remove.contains(arg)) {
+                Output::info(format!("Keeping removal used by another preset: {}", arg));
+            } else {
+                config.remove.retain(|x| x != arg);
+                Output::success(format!("No longer removing karg: {}", arg));
+            }
+        }
+        true
+    }
+
+    fn list_presets(config: &KargsConfig) {
+        Output::subheader("Kernel Argument Presets");
+        for preset in PRESETS {
+            let applied = if config.presets.contains_key(preset.name) {
+                " (applied)"
+            } else {
+                ""
+            };
+            Output::kv(preset.name, format!("{}{}", preset.description, applied));
+            for arg in preset.append {
+                Output::list_item(format!("+{}", arg));
+            }
+            for arg in preset.remove {
+                Output::list_item(format!("-{}", arg));
+            }
         }
     }
 
@@ -98,6 +456,153 @@ impl KargsAction {
             }
         }
     }
+
+    fn show_diff(diff: &KargsDiff) {
+        Output::subheader("Kernel Arguments (Manifest vs Running)");
+
+        if !diff.missing.is_empty() {
+            Output::warning(format!(
+                "Missing from running kernel: {}",
+                diff.missing.len()
+            ));
+            for arg in &diff.missing {
+                Output::list_item(arg);
+            }
+        }
+        if !diff.unremoved.is_empty() {
+            Output::warning(format!(
+                "Present but marked for removal: {}",
+                diff.unremoved.len()
+            ));
+            for arg in &diff.unremoved {
+                Output::list_item(arg);
+            }
+        }
+        if !diff.has_drift() {
+            Output::success("Running kernel matches the manifest.");
+        }
+
+        if !diff.untracked.is_empty() {
+            Output::blank();
+            Output::info(format!("Untracked: {}", diff.untracked.len()));
+            for arg in &diff.untracked {
+                Output::list_item(arg);
+            }
+        }
+        if diff.has_drift() {
+            Output::hint("Kernel arguments take effect after the next image build and reboot.");
+        }
+    }
+}
+
+/// The key of a `key[=value]` karg.
+fn karg_key(arg: &str) -> &str {
+    arg.split('=').next().unwrap_or(arg)
+}
+
+/// Arguments of `preset` that contradict a preset already in the manifest:
+/// the same key with a different value, or one appending what the other
+/// removes.
+fn preset_conflicts(config: &KargsConfig, preset: &KargsPreset) -> Vec<PresetConflict> {
+    let mut conflicts = Vec::new();
+    for (other, recorded) in &config.presets {
+        if other == preset.name {
+            continue;
+        }
+        // Judge by the preset's full definition; the record omits arguments
+        // that were already in the manifest when it was applied
+        let (append, remove): (Vec<&str>, Vec<&str>) = match find_preset(other) {
+            Ok(other) => (other.append.to_vec(), other.remove.to_vec()),
+            Err(_) => (
+                recorded.append.iter().map(String::as_str).collect(),
+                recorded.remove.iter().map(String::as_str).collect(),
+            ),
+        };
+        let mut conflict = |arg: &str, other_arg: &str| {
+            conflicts.push(PresetConflict {
+                arg: arg.to_string(),
+                other: other.clone(),
+                other_arg: other_arg.to_string(),
+            })
+        };
+
+        for arg in preset.append {
+            for theirs in &append {
+                if karg_key(arg) == karg_key(theirs) && arg != theirs {
+                    conflict(arg, theirs);
+                }
+            }
+            for theirs in &remove {
+                if karg_matches(theirs, arg) {
+                    conflict(arg, &format!("remove {}", theirs));
+                }
+            }
+        }
+        for arg in preset.remove {
+            for theirs in &append {
+                if karg_matches(arg, theirs) {
+                    conflict(&format!("remove {}", arg), theirs);
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Save the manifest and the Containerfile section generated from it, and
+/// open a PR for the change when the plan calls for one.
+fn save_manifest(
+    manifest: &SystemConfigManifest,
+    plan: &ExecutionPlan,
+    action: &str,
+    name: &str,
+) -> Result<()> {
+    if plan.should_update_manifest() {
+        manifest.save()?;
+        sync_containerfile_section(manifest)?;
+    } else if plan.dry_run {
+        Output::dry_run(format!(
+            "Would update {}",
+            SystemConfigManifest::PROJECT_PATH
+        ));
+    }
+
+    if plan.should_create_pr() {
+        // Sync Containerfile before creating PR so both files are committed together
+        sync_containerfile_section(manifest)?;
+        let manifest_content = serde_json::to_string_pretty(manifest)?;
+        plan.maybe_create_pr(
+            "kargs",
+            action,
+            name,
+            "system-config.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Regenerate the Containerfile's KERNEL_ARGUMENTS section, adding it once
+/// there are kargs to set.
+fn sync_containerfile_section(manifest: &SystemConfigManifest) -> Result<bool> {
+    let containerfile_path = Path::new("Containerfile");
+    if !containerfile_path.exists() {
+        return Ok(false);
+    }
+
+    let mut editor = ContainerfileEditor::load(containerfile_path)?;
+    let new_content = generate_kernel_arguments(manifest);
+    if !editor.has_section(Section::KernelArguments) && is_placeholder_content(&new_content) {
+        return Ok(false);
+    }
+    if editor.upsert_section(Section::KernelArguments, new_content) {
+        Output::success("Added Containerfile KERNEL_ARGUMENTS section");
+    } else {
+        Output::success("Synced Containerfile KERNEL_ARGUMENTS section");
+    }
+    editor.write()?;
+    Ok(true)
 }
 
 #[cfg(test)]
@@ -125,4 +630,143 @@ mod tests {
         assert!(config.remove.contains(&"quiet".to_string()));
         assert!(!config.append.contains(&"quiet".to_string()));
     }
+
+    #[test]
+    fn test_parse_cmdline_keeps_quoted_values_together() {
+        let tokens = parse_cmdline("BOOT_IMAGE=/vmlinuz root=UUID=abc  quiet foo=\"a b\"\n");
+        assert_eq!(
+            tokens,
+            vec![
+                "BOOT_IMAGE=/vmlinuz",
+                "root=UUID=abc",
+                "quiet",
+                "foo=\"a b\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_missing_unremoved_and_untracked() {
+        let config = KargsConfig {
+            append: vec!["quiet".to_string(), "mitigations=off".to_string()],
+            remove: vec!["rhgb".to_string(), "nomodeset".to_string()],
+            ..Default::default()
+        };
+        let tokens = parse_cmdline("root=UUID=abc quiet rhgb=1 mitigations=auto");
+
+        let diff = KargsDiff::compute(&config, &tokens);
+
+        assert_eq!(diff.missing, vec!["mitigations=off"]);
+        assert_eq!(diff.unremoved, vec!["rhgb=1"]);
+        assert_eq!(diff.untracked, vec!["root=UUID=abc", "mitigations=auto"]);
+        assert!(diff.has_drift());
+    }
+
+    #[test]
+    fn test_remove_with_value_matches_exactly() {
+        let config = KargsConfig {
+            append: Vec::new(),
+            remove: vec!["console=ttyS0".to_string()],
+            ..Default::default()
+        };
+        let tokens = parse_cmdline("console=tty0");
+
+        let diff = KargsDiff::compute(&config, &tokens);
+
+        assert!(!diff.has_drift());
+        assert_eq!(diff.untracked, vec!["console=tty0"]);
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_presets_are_well_formed() {
+        for (i, preset) in PRESETS.iter().enumerate() {
+            assert!(
+                PRESETS[i + 1..].iter().all(|p| p.name != preset.name),
+                "duplicate preset {}",
+                preset.name
+            );
+            assert!(!preset.append.is_empty() || !preset.remove.is_empty());
+        }
+        assert!(find_preset("nope").is_err());
+    }
+
+    #[test]
+    fn test_preset_remove_keeps_manual_and_shared_args() {
+        let mut config = KargsConfig {
+            append: strings(&["quiet"]),
+            ..Default::default()
+        };
+        let shared = KargsPreset {
+            name: "shared",
+            description: "",
+            append: &["rhgb"],
+            remove: &[],
+        };
+
+        KargsAction::apply_preset(&mut config, find_preset("quiet-boot").unwrap());
+        KargsAction::apply_preset(&mut config, &shared);
+        assert_eq!(
+            config.append,
+            strings(&["quiet", "rhgb", "loglevel=3", "rd.udev.log_level=3"])
+        );
+        // quiet was there first, so the preset didn't add it
+        assert_eq!(
+            config.presets["quiet-boot"].append,
+            strings(&["rhgb", "loglevel=3", "rd.udev.log_level=3"])
+        );
+        assert_eq!(config.presets["shared"].append, strings(&["rhgb"]));
+
+        // A manual append takes the arg over from the preset
+        KargsAction::apply_append(&mut config, strings(&["loglevel=3"]));
+
+        assert!(KargsAction::remove_preset(&mut config, "quiet-boot"));
+        assert_eq!(config.append, strings(&["quiet", "rhgb", "loglevel=3"]));
+        assert!(!KargsAction::remove_preset(&mut config, "quiet-boot"));
+
+        assert!(KargsAction::remove_preset(&mut config, "shared"));
+        assert_eq!(config.append, strings(&["quiet", "loglevel=3"]));
+        assert!(config.presets.is_empty());
+    }
+
+    #[test]
+    fn test_preset_apply_moves_args_between_lists() {
+        let mut config = KargsConfig {
+            append: strings(&["quiet"]),
+            ..Default::default()
+        };
+
+        KargsAction::apply_preset(&mut config, find_preset("verbose-boot").unwrap());
+        assert_eq!(config.append, strings(&["loglevel=7"]));
+        assert_eq!(config.remove, strings(&["quiet", "rhgb"]));
+
+        assert!(KargsAction::remove_preset(&mut config, "verbose-boot"));
+        assert!(config.append.is_empty());
+        assert!(config.remove.is_empty());
+    }
+
+    #[test]
+    fn test_preset_conflicts() {
+        let mut config = KargsConfig::default();
+        KargsAction::apply_preset(&mut config, find_preset("quiet-boot").unwrap());
+
+        let verbose = find_preset("verbose-boot").unwrap();
+        let conflicts: Vec<String> = preset_conflicts(&config, verbose)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                "loglevel=7 vs loglevel=3 (from quiet-boot)",
+                "remove quiet vs quiet (from quiet-boot)",
+                "remove rhgb vs rhgb (from quiet-boot)",
+            ]
+        );
+
+        assert!(preset_conflicts(&config, find_preset("zswap").unwrap()).is_empty());
+    }
 }
@@ -20,11 +20,14 @@
 //! # Bootc operations (via pkexec)
 //! bkt admin bootc status
 //! bkt admin bootc upgrade --confirm
+//! bkt admin bootc pin 1 --confirm
+//! bkt admin bootc schedule set "Sun *-*-* 03:00" --auto-reboot --only-if-idle
 //!
 //! # Systemctl operations (via D-Bus)
 //! bkt admin systemctl status docker
 //! bkt admin systemctl restart docker --confirm
 //! bkt admin systemctl enable docker --confirm
+//! bkt admin systemctl restart 'podman-*' caddy --confirm
 //! ```
 //!
 //! See [RFC-0009](../../../docs/rfcs/0009-privileged-operations.md) for design details.
@@ -42,7 +45,7 @@ use crate::pipeline::ExecutionPlan;
 
 pub use bootc::BootcAction;
 pub use daemon::DaemonAction;
-pub use kargs::KargsAction;
+pub use kargs::{KargsAction, KargsDiff, running_cmdline};
 pub use systemctl::SystemctlAction;
 pub use systemd::SystemdAction;
 
@@ -56,7 +59,7 @@ pub struct AdminArgs {
 /// Subcommands for privileged administration.
 #[derive(Debug, Subcommand)]
 pub enum AdminAction {
-    /// Manage bootc images (upgrade, switch, rollback, status)
+    /// Manage bootc images (upgrade, switch, rollback, pin, status)
     ///
     /// Provides passwordless access to bootc commands for wheel group members.
     /// Read-only operations (status) execute immediately; mutations require --confirm.
@@ -2,13 +2,16 @@
 //!
 //! Provides passwordless access to systemd service control via D-Bus.
 //! Uses polkit for authorization - wheel group members get passwordless access.
+//! With `--user`, actions target the user's own systemd instance over the
+//! session bus instead, which needs no polkit authorization.
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use clap::Subcommand;
 use is_terminal::IsTerminal;
 use owo_colors::OwoColorize;
 
-use crate::dbus::SystemdManager;
+use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::dbus::{SystemdManager, UnitScope};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 
@@ -19,71 +22,148 @@ pub enum SystemctlAction {
     ///
     /// Displays the current state, whether it's enabled, and a description.
     /// This is a read-only operation (no --confirm required).
+    /// Several units or a glob print one line per unit.
     Status {
-        /// Unit name (e.g., docker, docker.service)
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
         ///
         /// If no suffix is provided, .service is assumed.
+        #[arg(required = true)]
+        units: Vec<String>,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
+    },
+
+    /// Show recent journal entries for a unit
+    ///
+    /// Runs `journalctl` on the host. This is a read-only operation
+    /// (no --confirm required). With --follow, press Ctrl-C to stop.
+    Logs {
+        /// Unit name (e.g., docker, docker.service)
         unit: String,
+
+        /// Number of journal lines to show
+        #[arg(long, default_value_t = 50)]
+        lines: u32,
+
+        /// Keep printing new entries as they are written
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Only show entries newer than this (e.g., "1 hour ago", "today")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Start a unit
     ///
-    /// Requires --confirm flag for safety.
+    /// Requires --confirm flag for safety. Several units are confirmed
+    /// once as a batch.
     Start {
-        /// Unit name (e.g., docker, docker.service)
-        unit: String,
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
+        #[arg(required = true)]
+        units: Vec<String>,
 
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Allow a batch of more than 20 units
+        #[arg(long)]
+        yes_really: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Stop a unit
     ///
     /// Requires --confirm flag for safety.
     Stop {
-        /// Unit name (e.g., docker, docker.service)
-        unit: String,
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
+        #[arg(required = true)]
+        units: Vec<String>,
 
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Allow a batch of more than 20 units
+        #[arg(long)]
+        yes_really: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Restart a unit
     ///
     /// Requires --confirm flag for safety.
     Restart {
-        /// Unit name (e.g., docker, docker.service)
-        unit: String,
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
+        #[arg(required = true)]
+        units: Vec<String>,
 
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Allow a batch of more than 20 units
+        #[arg(long)]
+        yes_really: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Enable a unit to start at boot
     ///
     /// Requires --confirm flag for safety.
     Enable {
-        /// Unit name (e.g., docker, docker.service)
-        unit: String,
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
+        #[arg(required = true)]
+        units: Vec<String>,
 
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Allow a batch of more than 20 units
+        #[arg(long)]
+        yes_really: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Disable a unit from starting at boot
     ///
     /// Requires --confirm flag for safety.
     Disable {
-        /// Unit name (e.g., docker, docker.service)
-        unit: String,
+        /// Unit names or glob patterns (e.g., docker, 'podman-*')
+        #[arg(required = true)]
+        units: Vec<String>,
 
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Allow a batch of more than 20 units
+        #[arg(long)]
+        yes_really: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 
     /// Reload systemd daemon configuration
@@ -94,61 +174,166 @@ pub enum SystemctlAction {
         /// Confirm this operation
         #[arg(long)]
         confirm: bool,
+
+        /// Manage a user unit (systemctl --user) instead of a system unit
+        #[arg(long)]
+        user: bool,
     },
 }
 
 /// Execute a systemctl subcommand.
 pub fn run(action: SystemctlAction, plan: &ExecutionPlan) -> Result<()> {
+    let scope = |user| UnitScope::from_user_flag(user);
     match action {
-        SystemctlAction::Status { unit } => status(&unit, plan),
-        SystemctlAction::Start { unit, confirm } => start(&unit, confirm, plan),
-        SystemctlAction::Stop { unit, confirm } => stop(&unit, confirm, plan),
-        SystemctlAction::Restart { unit, confirm } => restart(&unit, confirm, plan),
-        SystemctlAction::Enable { unit, confirm } => enable(&unit, confirm, plan),
-        SystemctlAction::Disable { unit, confirm } => disable(&unit, confirm, plan),
-        SystemctlAction::DaemonReload { confirm } => daemon_reload(confirm, plan),
+        SystemctlAction::Status { units, user } => status(&units, scope(user), plan),
+        SystemctlAction::Logs {
+            unit,
+            lines,
+            follow,
+            since,
+            user,
+        } => logs(&unit, lines, follow, since.as_deref(), scope(user), plan),
+        SystemctlAction::Start {
+            units,
+            confirm,
+            yes_really,
+            user,
+        } => unit_op(
+            UnitOp::Start,
+            &units,
+            confirm,
+            yes_really,
+            scope(user),
+            plan,
+        ),
+        SystemctlAction::Stop {
+            units,
+            confirm,
+            yes_really,
+            user,
+        } => unit_op(UnitOp::Stop, &units, confirm, yes_really, scope(user), plan),
+        SystemctlAction::Restart {
+            units,
+            confirm,
+            yes_really,
+            user,
+        } => unit_op(
+            UnitOp::Restart,
+            &units,
+            confirm,
+            yes_really,
+            scope(user),
+            plan,
+        ),
+        SystemctlAction::Enable {
+            units,
+            confirm,
+            yes_really,
+            user,
+        } => unit_op(
+            UnitOp::Enable,
+            &units,
+            confirm,
+            yes_really,
+            scope(user),
+            plan,
+        ),
+        SystemctlAction::Disable {
+            units,
+            confirm,
+            yes_really,
+            user,
+        } => unit_op(
+            UnitOp::Disable,
+            &units,
+            confirm,
+            yes_really,
+            scope(user),
+            plan,
+        ),
+        SystemctlAction::DaemonReload { confirm, user } => {
+            daemon_reload(confirm, scope(user), plan)
+        }
     }
 }
 
-/// Show status of a unit.
-fn status(unit: &str, plan: &ExecutionPlan) -> Result<()> {
-    if plan.dry_run {
-        Output::dry_run(format!("Would show status of: {}", unit));
-        return Ok(());
+/// A unit name for messages, marked when it's a user unit.
+fn scoped(unit: &str, scope: UnitScope) -> String {
+    match scope {
+        UnitScope::System => unit.to_string(),
+        UnitScope::User => format!("{} (user)", unit),
     }
+}
 
-    let manager = SystemdManager::new()?;
-    let status = manager.status(unit)?;
+/// Show status of units: in full for a single unit, one line each for
+/// several units or a glob.
+fn status(patterns: &[String], scope: UnitScope, plan: &ExecutionPlan) -> Result<()> {
+    if let [unit] = patterns
+        && !is_glob(unit)
+    {
+        return status_one(unit, scope, plan);
+    }
 
-    // Format output similar to systemctl status
-    let use_color = std::io::stdout().is_terminal();
+    if plan.dry_run {
+        Output::dry_run(format!(
+            "Would show status of: {}",
+            scoped(&patterns.join(" "), scope)
+        ));
+        return Ok(());
+    }
 
-    let active_color = if use_color {
-        match status.active_state.as_str() {
-            "active" => status.active_state.green().to_string(),
-            "inactive" => status.active_state.dimmed().to_string(),
-            "failed" => status.active_state.red().to_string(),
-            _ => status.active_state.yellow().to_string(),
-        }
+    let manager = SystemdManager::connect(scope)?;
+    let available = if patterns.iter().any(|p| is_glob(p)) {
+        manager.list_units()?
     } else {
-        status.active_state.clone()
+        Vec::new()
     };
+    let units = expand_units(patterns, &available)?;
+    let width = units.iter().map(String::len).max().unwrap_or(0);
+    let use_color = std::io::stdout().is_terminal();
 
-    let enabled_color = if use_color {
-        match status.unit_file_state.as_str() {
-            "enabled" | "static" => status.unit_file_state.green().to_string(),
-            "disabled" => status.unit_file_state.dimmed().to_string(),
-            "masked" => status.unit_file_state.red().to_string(),
-            _ => status.unit_file_state.yellow().to_string(),
+    for unit in &units {
+        match manager.status(unit) {
+            Ok(status) => println!(
+                "● {:<width$}  {} ({})  {}  {}",
+                status.name,
+                color_active(&status.active_state, use_color),
+                status.sub_state,
+                color_enabled(&status.unit_file_state, use_color),
+                status.description.dimmed()
+            ),
+            Err(e) => println!("● {:<width$}  {:#}", unit, e),
         }
-    } else {
-        status.unit_file_state.clone()
-    };
+    }
+
+    Ok(())
+}
+
+/// Show status of a single unit.
+fn status_one(unit: &str, scope: UnitScope, plan: &ExecutionPlan) -> Result<()> {
+    if plan.dry_run {
+        Output::dry_run(format!("Would show status of: {}", scoped(unit, scope)));
+        return Ok(());
+    }
+
+    let manager = SystemdManager::connect(scope)?;
+    let status = manager.status(unit)?;
+
+    // Format output similar to systemctl status
+    let use_color = std::io::stdout().is_terminal();
 
     println!("● {}", status.name.bold());
+    println!("      Scope: {}", manager.scope().label());
     println!("     Loaded: {}", status.load_state);
-    println!("     Active: {} ({})", active_color, status.sub_state);
-    println!("    Enabled: {}", enabled_color);
+    println!(
+        "     Active: {} ({})",
+        color_active(&status.active_state, use_color),
+        status.sub_state
+    );
+    println!(
+        "    Enabled: {}",
+        color_enabled(&status.unit_file_state, use_color)
+    );
     if !status.description.is_empty() {
         println!("       Desc: {}", status.description);
     }
@@ -156,115 +341,444 @@ fn status(unit: &str, plan: &ExecutionPlan) -> Result<()> {
     Ok(())
 }
 
-/// Start a unit.
-fn start(unit: &str, confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "start", Some(unit))?;
+fn color_active(state: &str, use_color: bool) -> String {
+    if !use_color {
+        return state.to_string();
+    }
+    match state {
+        "active" => state.green().to_string(),
+        "inactive" => state.dimmed().to_string(),
+        "failed" => state.red().to_string(),
+        _ => state.yellow().to_string(),
+    }
+}
+
+fn color_enabled(state: &str, use_color: bool) -> String {
+    if !use_color {
+        return state.to_string();
+    }
+    match state {
+        "enabled" | "static" => state.green().to_string(),
+        "disabled" => state.dimmed().to_string(),
+        "masked" => state.red().to_string(),
+        _ => state.yellow().to_string(),
+    }
+}
+
+/// Show journal entries for a unit.
+fn logs(
+    unit: &str,
+    lines: u32,
+    follow: bool,
+    since: Option<&str>,
+    scope: UnitScope,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    let unit = SystemdManager::normalize_unit_name(unit);
+    let args = journalctl_args(&unit, lines, follow, since, scope);
 
     if plan.dry_run {
-        Output::dry_run(format!("Would start: {}", unit));
+        Output::dry_run(format!("Would execute: journalctl {}", args.join(" ")));
         return Ok(());
     }
 
-    Output::info(format!("Starting {}...", unit.cyan()));
-    let manager = SystemdManager::new()?;
-    manager.start(unit)?;
-    Output::success(format!("Started {}", unit));
+    if follow {
+        Output::info(format!(
+            "Following journal for {} (Ctrl-C to stop)",
+            scoped(&unit, scope).cyan()
+        ));
+        // The terminal delivers Ctrl-C to journalctl too; ignore it here so
+        // we outlive the child and report a clean exit instead of dying mid-line.
+        ctrlc::set_handler(|| {}).context("Failed to set Ctrl-C handler")?;
+    } else {
+        Output::info(format!("Journal for {}", scoped(&unit, scope).cyan()));
+    }
+
+    exec_journalctl(&args, follow, plan.runner())
+}
 
-    Ok(())
+/// Build the `journalctl` argument list for [`logs`].
+fn journalctl_args(
+    unit: &str,
+    lines: u32,
+    follow: bool,
+    since: Option<&str>,
+    scope: UnitScope,
+) -> Vec<String> {
+    let mut args = vec!["--no-pager".to_string()];
+    if let Some(flag) = scope.flag() {
+        args.push(flag.to_string());
+    }
+    args.extend([
+        "-u".to_string(),
+        unit.to_string(),
+        "-n".to_string(),
+        lines.to_string(),
+    ]);
+    if let Some(since) = since {
+        args.push("--since".to_string());
+        args.push(since.to_string());
+    }
+    if follow {
+        args.push("-f".to_string());
+    }
+    args
 }
 
-/// Stop a unit.
-fn stop(unit: &str, confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "stop", Some(unit))?;
+/// Run `journalctl`, treating an interrupted follow as success.
+fn exec_journalctl(args: &[String], follow: bool, runner: &dyn CommandRunner) -> Result<()> {
+    let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+    let status = runner
+        .run_status("journalctl", &argv, &CommandOptions::default())
+        .context("Failed to execute journalctl")?;
 
-    if plan.dry_run {
-        Output::dry_run(format!("Would stop: {}", unit));
+    if status.success() || (follow && interrupted(&status)) {
         return Ok(());
     }
 
-    Output::info(format!("Stopping {}...", unit.cyan()));
-    let manager = SystemdManager::new()?;
-    manager.stop(unit)?;
-    Output::success(format!("Stopped {}", unit));
+    let code = status
+        .code()
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    bail!("journalctl failed with exit code {}", code);
+}
 
-    Ok(())
+/// Whether the child was stopped by Ctrl-C.
+fn interrupted(status: &std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+
+    status.signal() == Some(libc::SIGINT) || status.code() == Some(130)
 }
 
-/// Restart a unit.
-fn restart(unit: &str, confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "restart", Some(unit))?;
+/// Batches larger than this need `--yes-really`, so a stray `*` can't
+/// restart half the system.
+const BULK_UNIT_LIMIT: usize = 20;
+
+/// A mutating operation that can be applied to several units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnitOp {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+}
 
-    if plan.dry_run {
-        Output::dry_run(format!("Would restart: {}", unit));
-        return Ok(());
+impl UnitOp {
+    fn name(self) -> &'static str {
+        match self {
+            UnitOp::Start => "start",
+            UnitOp::Stop => "stop",
+            UnitOp::Restart => "restart",
+            UnitOp::Enable => "enable",
+            UnitOp::Disable => "disable",
+        }
+    }
+
+    fn progress(self) -> &'static str {
+        match self {
+            UnitOp::Start => "Starting",
+            UnitOp::Stop => "Stopping",
+            UnitOp::Restart => "Restarting",
+            UnitOp::Enable => "Enabling",
+            UnitOp::Disable => "Disabling",
+        }
     }
 
-    Output::info(format!("Restarting {}...", unit.cyan()));
-    let manager = SystemdManager::new()?;
-    manager.restart(unit)?;
-    Output::success(format!("Restarted {}", unit));
+    fn done(self) -> &'static str {
+        match self {
+            UnitOp::Start => "Started",
+            UnitOp::Stop => "Stopped",
+            UnitOp::Restart => "Restarted",
+            UnitOp::Enable => "Enabled",
+            UnitOp::Disable => "Disabled",
+        }
+    }
 
-    Ok(())
+    /// Units a glob can match: enable and disable act on unit files, which
+    /// needn't be loaded; the rest on loaded units.
+    fn candidates(self, manager: &SystemdManager) -> Result<Vec<String>> {
+        match self {
+            UnitOp::Enable | UnitOp::Disable => manager.list_unit_files(),
+            _ => manager.list_units(),
+        }
+    }
+
+    /// Apply to one unit. Returns false when there was nothing to do.
+    fn apply(self, manager: &SystemdManager, unit: &str) -> Result<bool> {
+        match self {
+            UnitOp::Start => manager.start(unit).map(|()| true),
+            UnitOp::Stop => manager.stop(unit).map(|()| true),
+            UnitOp::Restart => manager.restart(unit).map(|()| true),
+            UnitOp::Enable => manager.enable(unit),
+            UnitOp::Disable => manager.disable(unit).map(|()| true),
+        }
+    }
 }
 
-/// Enable a unit to start at boot.
-fn enable(unit: &str, confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "enable", Some(unit))?;
+/// Start, stop, restart, enable or disable the units named by `patterns`.
+fn unit_op(
+    op: UnitOp,
+    patterns: &[String],
+    confirm: bool,
+    yes_really: bool,
+    scope: UnitScope,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    if let [unit] = patterns
+        && !is_glob(unit)
+    {
+        return single_unit_op(op, unit, confirm, scope, plan);
+    }
+
+    // Globs are expanded against what's on the system, even for a dry run
+    let mut manager = None;
+    let available = if patterns.iter().any(|p| is_glob(p)) {
+        let connected = SystemdManager::connect(scope)?;
+        let available = op.candidates(&connected)?;
+        manager = Some(connected);
+        available
+    } else {
+        Vec::new()
+    };
+    let units = expand_units(patterns, &available)?;
+    check_batch_size(units.len(), yes_really)?;
+
+    Output::info(format!("{} {} unit(s):", op.progress(), units.len()));
+    for unit in &units {
+        Output::list_item(scoped(unit, scope));
+    }
+    let args = batch_args(patterns, yes_really);
+    require_confirmation(confirm, op.name(), Some(&args), scope)?;
 
     if plan.dry_run {
-        Output::dry_run(format!("Would enable: {}", unit));
+        for unit in &units {
+            Output::dry_run(format!("Would {}: {}", op.name(), scoped(unit, scope)));
+        }
         return Ok(());
     }
 
-    Output::info(format!("Enabling {}...", unit.cyan()));
-    let manager = SystemdManager::new()?;
-    let changes_made = manager.enable(unit)?;
+    let manager = match manager {
+        Some(manager) => manager,
+        None => SystemdManager::connect(scope)?,
+    };
+    let results: Vec<(&str, Result<bool>)> = units
+        .iter()
+        .map(|unit| (unit.as_str(), op.apply(&manager, unit)))
+        .collect();
 
-    if changes_made {
-        Output::success(format!("Enabled {}", unit));
-    } else {
-        Output::info(format!("{} was already enabled", unit));
+    let width = units.iter().map(String::len).max().unwrap_or(0);
+    let use_color = std::io::stdout().is_terminal();
+    println!();
+    println!("  {:<width$}  RESULT", "UNIT");
+    for (unit, result) in &results {
+        let outcome = match result {
+            Ok(true) => op.done().to_lowercase(),
+            Ok(false) => format!("already {}", op.done().to_lowercase()),
+            Err(e) => format!("failed: {:#}", e),
+        };
+        let outcome = match (use_color, result) {
+            (false, _) => outcome,
+            (true, Ok(_)) => outcome.green().to_string(),
+            (true, Err(_)) => outcome.red().to_string(),
+        };
+        println!("  {:<width$}  {}", unit, outcome);
+    }
+    println!();
+
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    if failed > 0 {
+        bail!(
+            "Failed to {} {} of {} unit(s)",
+            op.name(),
+            failed,
+            units.len()
+        );
     }
+    Output::success(format!("{} {} unit(s)", op.done(), units.len()));
 
     Ok(())
 }
 
-/// Disable a unit from starting at boot.
-fn disable(unit: &str, confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "disable", Some(unit))?;
+/// Apply an operation to one explicitly named unit.
+fn single_unit_op(
+    op: UnitOp,
+    unit: &str,
+    confirm: bool,
+    scope: UnitScope,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    require_confirmation(confirm, op.name(), Some(unit), scope)?;
+    let unit_label = scoped(unit, scope);
 
     if plan.dry_run {
-        Output::dry_run(format!("Would disable: {}", unit));
+        Output::dry_run(format!("Would {}: {}", op.name(), unit_label));
         return Ok(());
     }
 
-    Output::info(format!("Disabling {}...", unit.cyan()));
-    let manager = SystemdManager::new()?;
-    manager.disable(unit)?;
-    Output::success(format!("Disabled {}", unit));
+    Output::info(format!("{} {}...", op.progress(), unit_label.cyan()));
+    let manager = SystemdManager::connect(scope)?;
+    if op.apply(&manager, unit)? {
+        Output::success(format!("{} {}", op.done(), unit_label));
+    } else {
+        Output::info(format!(
+            "{} was already {}",
+            unit_label,
+            op.done().to_lowercase()
+        ));
+    }
+
+    Ok(())
+}
 
+/// Refuse batches over [`BULK_UNIT_LIMIT`] without `--yes-really`.
+fn check_batch_size(count: usize, yes_really: bool) -> Result<()> {
+    if count > BULK_UNIT_LIMIT && !yes_really {
+        bail!(
+            "{} units matched, more than {} at once.\n\n\
+             Narrow the pattern, or add {} if that's really intended.",
+            count,
+            BULK_UNIT_LIMIT,
+            "--yes-really".cyan()
+        );
+    }
     Ok(())
 }
 
+/// The unit arguments as they'd be typed again, globs quoted.
+fn batch_args(patterns: &[String], yes_really: bool) -> String {
+    let mut args: Vec<String> = patterns
+        .iter()
+        .map(|p| match is_glob(p) {
+            true => format!("'{}'", p),
+            false => p.clone(),
+        })
+        .collect();
+    if yes_really {
+        args.push("--yes-really".to_string());
+    }
+    args.join(" ")
+}
+
+/// Whether a unit argument is a glob pattern.
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Resolve unit arguments to unit names, in order and without duplicates.
+///
+/// Plain names are normalized (`docker` → `docker.service`); globs are
+/// matched against `available` as-is, like `systemctl` does. A glob that
+/// matches nothing is an error rather than a silent no-op.
+fn expand_units(patterns: &[String], available: &[String]) -> Result<Vec<String>> {
+    let mut units: Vec<String> = Vec::new();
+    for pattern in patterns {
+        if !is_glob(pattern) {
+            let unit = SystemdManager::normalize_unit_name(pattern);
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+            continue;
+        }
+
+        let mut matched: Vec<&String> = available
+            .iter()
+            .filter(|name| glob_match(pattern, name))
+            .collect();
+        if matched.is_empty() {
+            bail!("No units match '{}'", pattern);
+        }
+        matched.sort();
+        for unit in matched {
+            if !units.contains(unit) {
+                units.push(unit.clone());
+            }
+        }
+    }
+    Ok(units)
+}
+
+/// Shell-style glob matching: `*`, `?` and `[...]` classes (with `!` or `^`
+/// negation and `a-z` ranges).
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    glob_match_at(&pattern, &name)
+}
+
+fn glob_match_at(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| glob_match_at(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && glob_match_at(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(close) = pattern.iter().skip(2).position(|&c| c == ']') else {
+                // No closing bracket: a literal '['
+                return name.first() == Some(&'[') && glob_match_at(&pattern[1..], &name[1..]);
+            };
+            let class = &pattern[1..close + 2];
+            let Some(&c) = name.first() else {
+                return false;
+            };
+            class_matches(class, c) && glob_match_at(&pattern[close + 3..], &name[1..])
+        }
+        Some(&literal) => {
+            name.first() == Some(&literal) && glob_match_at(&pattern[1..], &name[1..])
+        }
+    }
+}
+
+/// Whether `c` is in a bracket class (the part between `[` and `]`).
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!' | '^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
 /// Reload systemd daemon configuration.
-fn daemon_reload(confirm: bool, plan: &ExecutionPlan) -> Result<()> {
-    require_confirmation(confirm, "daemon-reload", None)?;
+fn daemon_reload(confirm: bool, scope: UnitScope, plan: &ExecutionPlan) -> Result<()> {
+    require_confirmation(confirm, "daemon-reload", None, scope)?;
 
     if plan.dry_run {
-        Output::dry_run("Would reload systemd daemon configuration");
+        Output::dry_run(format!(
+            "Would reload {} systemd daemon configuration",
+            scope.label()
+        ));
         return Ok(());
     }
 
-    Output::info("Reloading systemd daemon configuration...");
-    let manager = SystemdManager::new()?;
+    Output::info(format!(
+        "Reloading {} systemd daemon configuration...",
+        scope.label()
+    ));
+    let manager = SystemdManager::connect(scope)?;
     manager.daemon_reload()?;
-    Output::success("Reloaded systemd daemon");
+    Output::success(format!("Reloaded {} systemd daemon", scope.label()));
 
     Ok(())
 }
 
-/// Require --confirm flag for mutating operations.
-fn require_confirmation(confirm: bool, operation: &str, unit: Option<&str>) -> Result<()> {
+/// Require --confirm flag for mutating operations. `unit` is the unit
+/// argument(s) as they'd be typed in the suggested command.
+fn require_confirmation(
+    confirm: bool,
+    operation: &str,
+    unit: Option<&str>,
+    scope: UnitScope,
+) -> Result<()> {
     if confirm {
         return Ok(());
     }
@@ -272,7 +786,14 @@ fn require_confirmation(confirm: bool, operation: &str, unit: Option<&str>) -> R
     // Interactive mode: prompt for confirmation if we have a TTY
     if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
         let message = match unit {
-            Some(unit) => format!("This will {} {}. Continue?", operation, unit.cyan()),
+            Some(unit) if unit.contains(' ') || is_glob(unit) => {
+                format!("This will {} the units above. Continue?", operation)
+            }
+            Some(unit) => format!(
+                "This will {} {}. Continue?",
+                operation,
+                scoped(unit, scope).cyan()
+            ),
             None => format!("This will {}. Continue?", operation.cyan()),
         };
         if prompt_continue(&message)? {
@@ -282,23 +803,28 @@ fn require_confirmation(confirm: bool, operation: &str, unit: Option<&str>) -> R
     }
 
     // Non-interactive: require --confirm
-    match unit {
-        Some(unit) => bail!(
-            "This operation requires confirmation.\n\n\
-             Add {} to proceed:\n  \
-             bkt admin systemctl {} {} --confirm",
-            "--confirm".cyan(),
-            operation,
-            unit
-        ),
-        None => bail!(
-            "This operation requires confirmation.\n\n\
-             Add {} to proceed:\n  \
-             bkt admin systemctl {} --confirm",
-            "--confirm".cyan(),
-            operation
-        ),
+    bail!(
+        "This operation requires confirmation.\n\n\
+         Add {} to proceed:\n  \
+         {}",
+        "--confirm".cyan(),
+        confirm_command(operation, unit, scope)
+    )
+}
+
+/// The command to re-run with `--confirm`.
+fn confirm_command(operation: &str, unit: Option<&str>, scope: UnitScope) -> String {
+    let mut command = format!("bkt admin systemctl {}", operation);
+    if let Some(unit) = unit {
+        command.push(' ');
+        command.push_str(unit);
     }
+    if let Some(flag) = scope.flag() {
+        command.push(' ');
+        command.push_str(flag);
+    }
+    command.push_str(" --confirm");
+    command
 }
 
 /// Prompt user for confirmation.
@@ -323,28 +849,168 @@ mod tests {
     fn test_systemctl_action_variants() {
         // Just verify the enum compiles and has expected variants
         let _ = SystemctlAction::Status {
+            units: vec!["docker".to_string(), "podman-*".to_string()],
+            user: false,
+        };
+        let _ = SystemctlAction::Logs {
             unit: "docker".to_string(),
+            lines: 50,
+            follow: false,
+            since: None,
+            user: true,
         };
         let _ = SystemctlAction::Start {
-            unit: "docker".to_string(),
+            units: vec!["docker".to_string()],
             confirm: true,
+            yes_really: false,
+            user: false,
         };
         let _ = SystemctlAction::Stop {
-            unit: "docker".to_string(),
+            units: vec!["docker".to_string()],
             confirm: false,
+            yes_really: false,
+            user: false,
         };
         let _ = SystemctlAction::Restart {
-            unit: "docker".to_string(),
+            units: vec!["docker".to_string()],
             confirm: true,
+            yes_really: false,
+            user: false,
         };
         let _ = SystemctlAction::Enable {
-            unit: "docker".to_string(),
+            units: vec!["docker".to_string()],
             confirm: true,
+            yes_really: false,
+            user: false,
         };
         let _ = SystemctlAction::Disable {
-            unit: "docker".to_string(),
+            units: vec!["docker".to_string()],
+            confirm: true,
+            yes_really: false,
+            user: false,
+        };
+        let _ = SystemctlAction::DaemonReload {
             confirm: true,
+            user: true,
         };
-        let _ = SystemctlAction::DaemonReload { confirm: true };
+    }
+
+    #[test]
+    fn test_journalctl_args_defaults() {
+        assert_eq!(
+            journalctl_args("docker.service", 50, false, None, UnitScope::System),
+            vec!["--no-pager", "-u", "docker.service", "-n", "50"]
+        );
+    }
+
+    #[test]
+    fn test_journalctl_args_follow_since() {
+        assert_eq!(
+            journalctl_args(
+                "sshd.service",
+                10,
+                true,
+                Some("1 hour ago"),
+                UnitScope::System
+            ),
+            vec![
+                "--no-pager",
+                "-u",
+                "sshd.service",
+                "-n",
+                "10",
+                "--since",
+                "1 hour ago",
+                "-f"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_journalctl_args_user_scope() {
+        assert_eq!(
+            journalctl_args("pipewire.service", 20, false, None, UnitScope::User),
+            vec!["--no-pager", "--user", "-u", "pipewire.service", "-n", "20"]
+        );
+    }
+
+    #[test]
+    fn test_confirm_command_keeps_scope() {
+        assert_eq!(
+            confirm_command("restart", Some("docker"), UnitScope::System),
+            "bkt admin systemctl restart docker --confirm"
+        );
+        assert_eq!(
+            confirm_command("daemon-reload", None, UnitScope::User),
+            "bkt admin systemctl daemon-reload --user --confirm"
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("podman-*", "podman-caddy.service"));
+        assert!(!glob_match("podman-*", "caddy.service"));
+        assert!(glob_match("*.timer", "fstrim.timer"));
+        assert!(!glob_match("*.timer", "fstrim.service"));
+        assert!(glob_match("getty@tty?.service", "getty@tty1.service"));
+        assert!(glob_match("sshd[-.]*", "sshd.service"));
+        assert!(glob_match("[a-c]*", "caddy.service"));
+        assert!(!glob_match("[!a-c]*", "caddy.service"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("x[y", "x[y"));
+    }
+
+    #[test]
+    fn test_expand_units_resolves_globs_in_order() {
+        let available: Vec<String> = [
+            "podman-web.service",
+            "caddy.service",
+            "podman-db.service",
+            "sshd.service",
+        ]
+        .map(String::from)
+        .to_vec();
+        let patterns = ["podman-*", "caddy", "podman-db.service"].map(String::from);
+        assert_eq!(
+            expand_units(&patterns, &available).unwrap(),
+            vec!["podman-db.service", "podman-web.service", "caddy.service"]
+        );
+
+        // Plain names needn't be in the list
+        let plain = ["docker".to_string()];
+        assert_eq!(expand_units(&plain, &[]).unwrap(), vec!["docker.service"]);
+
+        let missing = ["nginx-*".to_string()];
+        let err = expand_units(&missing, &available).unwrap_err();
+        assert!(err.to_string().contains("No units match 'nginx-*'"));
+    }
+
+    #[test]
+    fn test_large_batches_need_yes_really() {
+        assert!(check_batch_size(BULK_UNIT_LIMIT, false).is_ok());
+        assert!(check_batch_size(BULK_UNIT_LIMIT + 1, false).is_err());
+        assert!(check_batch_size(BULK_UNIT_LIMIT + 1, true).is_ok());
+        assert_eq!(
+            confirm_command(
+                "restart",
+                Some(&batch_args(
+                    &["podman-*".to_string(), "caddy".to_string()],
+                    true
+                )),
+                UnitScope::User
+            ),
+            "bkt admin systemctl restart 'podman-*' caddy --yes-really --user --confirm"
+        );
+    }
+
+    #[test]
+    fn test_interrupted_follow_is_clean_exit() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert!(interrupted(&std::process::ExitStatus::from_raw(
+            libc::SIGINT
+        )));
+        assert!(interrupted(&std::process::ExitStatus::from_raw(130 << 8)));
+        assert!(!interrupted(&std::process::ExitStatus::from_raw(1 << 8)));
     }
 }
@@ -5,10 +5,16 @@
 //! Note: This is distinct from `bkt admin systemctl` which performs immediate
 //! runtime operations. This command manages the *image* configuration.
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use clap::Subcommand;
+use std::fs;
 
-use crate::manifest::system_config::{SystemConfigManifest, SystemdConfig};
+use crate::command_runner::CommandOptions;
+use crate::dbus::SystemdManager;
+use crate::manifest::parsers::systemd as unit_parser;
+use crate::manifest::system_config::{
+    LOCAL_UNIT_DIR, SystemConfigManifest, SystemdConfig, SystemdDropin,
+};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 
@@ -36,12 +42,62 @@ pub enum SystemdAction {
         units: Vec<String>,
     },
 
+    /// Manage drop-in overrides for a unit
+    ///
+    /// Drop-ins are installed into /usr/lib/systemd/system/<unit>.d/ at
+    /// image build time. With --confirm they're also written to
+    /// /etc/systemd/system/<unit>.d/ so they apply now.
+    Dropin {
+        #[command(subcommand)]
+        action: DropinAction,
+    },
+
     /// List managed systemd units
     List,
 }
 
+/// Drop-in operations.
+#[derive(Debug, Subcommand)]
+pub enum DropinAction {
+    /// Set properties in a unit's drop-in, creating it if needed
+    Set {
+        /// Unit to override (e.g. docker.service)
+        unit: String,
+
+        /// Property to set, as Key=Value (repeatable)
+        #[arg(long = "property", value_name = "KEY=VALUE", required = true)]
+        properties: Vec<String>,
+
+        /// Section the properties belong to (default: from the unit type)
+        #[arg(long)]
+        section: Option<String>,
+
+        /// Drop-in file name
+        #[arg(long, default_value = "override.conf")]
+        file: String,
+
+        /// Also write the drop-in locally and reload systemd
+        #[arg(long)]
+        confirm: bool,
+    },
+
+    /// Remove a unit's drop-in
+    Remove {
+        /// Unit the drop-in belongs to
+        unit: String,
+
+        /// Drop-in file name
+        #[arg(long, default_value = "override.conf")]
+        file: String,
+
+        /// Also remove the local drop-in and reload systemd
+        #[arg(long)]
+        confirm: bool,
+    },
+}
+
 impl SystemdAction {
-    pub fn execute(self, _plan: &ExecutionPlan) -> Result<()> {
+    pub fn execute(self, plan: &ExecutionPlan) -> Result<()> {
         let mut manifest = SystemConfigManifest::load()?;
         let mut systemd = manifest.systemd.unwrap_or_default();
 
@@ -49,6 +105,12 @@ impl SystemdAction {
             SystemdAction::Enable { units } => Self::apply_enable(&mut systemd, units),
             SystemdAction::Disable { units } => Self::apply_disable(&mut systemd, units),
             SystemdAction::Mask { units } => Self::apply_mask(&mut systemd, units),
+            SystemdAction::Dropin { action } => {
+                action.execute(&mut systemd, plan)?;
+                if plan.dry_run {
+                    return Ok(());
+                }
+            }
             SystemdAction::List => {
                 Self::list(&systemd);
                 return Ok(());
@@ -135,9 +197,161 @@ impl SystemdAction {
                 Output::list_item(unit);
             }
         }
+        if !config.dropins.is_empty() {
+            Output::kv("Drop-ins", "");
+            for (unit, dropins) in &config.dropins {
+                for dropin in dropins {
+                    match &dropin.source {
+                        Some(source) => Output::list_item(format!(
+                            "{}.d/{} (from {})",
+                            unit, dropin.filename, source
+                        )),
+                        None => Output::list_item(format!("{}.d/{}", unit, dropin.filename)),
+                    }
+                }
+            }
+        }
     }
 }
 
+impl DropinAction {
+    fn execute(self, config: &mut SystemdConfig, plan: &ExecutionPlan) -> Result<()> {
+        match self {
+            DropinAction::Set {
+                unit,
+                properties,
+                section,
+                file,
+                confirm,
+            } => {
+                let section = section.unwrap_or_else(|| default_section(&unit).to_string());
+                let dropin = config.dropin_mut(&unit, &file);
+                set_properties(dropin, &section, &properties)?;
+                let content = dropin.content.clone().unwrap_or_default();
+                let local_path = dropin.path(LOCAL_UNIT_DIR, &unit);
+
+                if plan.dry_run {
+                    Output::dry_run(format!("Would set {}.d/{}:", unit, file));
+                    for line in content.lines() {
+                        Output::list_item(line);
+                    }
+                    if confirm {
+                        Output::dry_run(format!("Would write {}", local_path));
+                    }
+                    return Ok(());
+                }
+                Output::success(format!("Updated drop-in {}.d/{}", unit, file));
+
+                if confirm {
+                    write_local(plan, &local_path, &content)?;
+                } else {
+                    Output::hint("Add --confirm to also apply it to the running system");
+                }
+            }
+            DropinAction::Remove {
+                unit,
+                file,
+                confirm,
+            } => {
+                let local_path = format!("{}/{}.d/{}", LOCAL_UNIT_DIR, unit, file);
+                if plan.dry_run {
+                    Output::dry_run(format!("Would remove drop-in {}.d/{}", unit, file));
+                    if confirm {
+                        Output::dry_run(format!("Would remove {}", local_path));
+                    }
+                    return Ok(());
+                }
+                if config.remove_dropin(&unit, &file) {
+                    Output::success(format!("Removed drop-in {}.d/{}", unit, file));
+                } else {
+                    Output::info(format!("No drop-in {}.d/{} in manifest", unit, file));
+                }
+                if confirm {
+                    run_privileged(plan, "/usr/bin/rm", &["-f", &local_path])?;
+                    reload_daemon()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The section a bare property belongs to, from the unit's type.
+fn default_section(unit: &str) -> &'static str {
+    match unit.rsplit_once('.').map(|(_, kind)| kind) {
+        Some("service") => "Service",
+        Some("socket") => "Socket",
+        Some("timer") => "Timer",
+        Some("mount") => "Mount",
+        Some("automount") => "Automount",
+        Some("swap") => "Swap",
+        Some("path") => "Path",
+        Some("slice") => "Slice",
+        _ => "Unit",
+    }
+}
+
+/// Patch `Key=Value` properties into an inline drop-in.
+fn set_properties(dropin: &mut SystemdDropin, section: &str, properties: &[String]) -> Result<()> {
+    if let Some(source) = &dropin.source {
+        bail!(
+            "Drop-in {} is read from {}; edit that file instead",
+            dropin.filename,
+            source
+        );
+    }
+
+    let mut parsed = unit_parser::parse(dropin.content.as_deref().unwrap_or_default());
+    let props = parsed.sections.entry(section.to_string()).or_default();
+    for property in properties {
+        let Some((key, value)) = property.split_once('=') else {
+            bail!("Invalid property '{}': expected Key=Value", property);
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("Invalid property '{}': empty key", property);
+        }
+        props.insert(key.to_string(), value.trim().to_string());
+    }
+    dropin.content = Some(unit_parser::render(&parsed));
+    Ok(())
+}
+
+/// Install `content` at `path` as root, then reload systemd.
+fn write_local(plan: &ExecutionPlan, path: &str, content: &str) -> Result<()> {
+    let staged = std::env::temp_dir().join(format!("bkt-dropin-{}", std::process::id()));
+    fs::write(&staged, content).with_context(|| format!("Failed to write {}", staged.display()))?;
+    let staged_str = staged.to_string_lossy();
+    let result = run_privileged(
+        plan,
+        "/usr/bin/install",
+        &["-D", "-m", "0644", &staged_str, path],
+    );
+    let _ = fs::remove_file(&staged);
+    result?;
+    Output::success(format!("Wrote {}", path));
+    reload_daemon()
+}
+
+fn run_privileged(plan: &ExecutionPlan, program: &str, args: &[&str]) -> Result<()> {
+    let mut argv = vec![program];
+    argv.extend(args);
+    let status = plan
+        .runner()
+        .run_status("pkexec", &argv, &CommandOptions::default())
+        .with_context(|| format!("Failed to execute pkexec {}", program))?;
+    if !status.success() {
+        bail!("{} {} failed", program, args.join(" "));
+    }
+    Ok(())
+}
+
+fn reload_daemon() -> Result<()> {
+    SystemdManager::new()?.daemon_reload()?;
+    Output::success("Reloaded systemd daemon");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +369,33 @@ mod tests {
         assert!(!config.mask.contains(&"foo.service".to_string()));
     }
 
+    #[test]
+    fn test_dropin_set_patches_properties() {
+        let mut config = SystemdConfig::default();
+        let dropin = config.dropin_mut("docker.service", "override.conf");
+        set_properties(
+            dropin,
+            default_section("docker.service"),
+            &["MemoryMax=8G".to_string()],
+        )
+        .unwrap();
+        set_properties(
+            dropin,
+            "Service",
+            &["MemoryMax=4G".to_string(), "CPUQuota=50%".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            dropin.content.as_deref(),
+            Some("[Service]\nCPUQuota=50%\nMemoryMax=4G\n")
+        );
+        assert!(set_properties(dropin, "Service", &["MemoryMax".to_string()]).is_err());
+
+        dropin.source = Some("system/docker/override.conf".to_string());
+        assert!(set_properties(dropin, "Service", &["A=b".to_string()]).is_err());
+    }
+
     #[test]
     fn test_disable_removes_enable() {
         let mut config = SystemdConfig::default();
@@ -3,12 +3,14 @@
 //! This module provides commands for managing AppImages through GearLever.
 //! The manifest format is simplified and backend-agnostic.
 
-use crate::command_runner::CommandRunner;
+use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::capture::CapturePlan;
 use crate::manifest::{AppImageApp, AppImageAppsManifest, GearLeverNativeManifest};
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
 use crate::pr::ensure_repo;
 use anyhow::{Context, Result, bail};
@@ -26,17 +28,21 @@ pub struct AppImageArgs {
 pub enum AppImageAction {
     /// Add an AppImage from GitHub releases
     Add {
-        /// GitHub repository in "github:owner/repo" format
+        /// GitHub repository in "github:owner/repo" format, or a release
+        /// asset URL (https://github.com/owner/repo/releases/download/...)
         repo: String,
-        /// Asset filename pattern (glob supported)
+        /// Asset filename pattern (glob supported; defaults to the URL's file name)
         #[arg(short, long)]
-        asset: String,
+        asset: Option<String>,
         /// Human-readable name (defaults to repo name)
         #[arg(short, long)]
         name: Option<String>,
         /// Include prereleases/nightlies
         #[arg(long)]
         prereleases: bool,
+        /// Download the release asset once and record its checksum and size
+        #[arg(long)]
+        pin_checksum: bool,
     },
     /// Remove an AppImage from the manifest
     Remove {
@@ -56,8 +62,8 @@ pub enum AppImageAction {
     /// List all AppImages in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Sync: update GearLever config from manifest
     Sync {
@@ -71,6 +77,8 @@ pub enum AppImageAction {
         #[arg(long)]
         apply: bool,
     },
+    /// Re-download pinned AppImages and check they still match their checksums
+    Verify,
 }
 
 /// Parse a "github:owner/repo" string into "owner/repo".
@@ -91,6 +99,47 @@ fn parse_github_repo(input: &str) -> Result<String> {
     Ok(repo.to_string())
 }
 
+/// Split a GitHub release asset URL into ("owner/repo", asset file name).
+///
+/// Accepts `https://github.com/owner/repo/releases/download/<tag>/<file>`.
+fn parse_release_url(url: &str) -> Option<(String, String)> {
+    let path = url.strip_prefix("https://github.com/")?;
+    let parts: Vec<&str> = path.split('/').collect();
+    match parts.as_slice() {
+        [owner, repo, "releases", "download", tag, file]
+            if [owner, repo, tag, file].iter().all(|part| !part.is_empty()) =>
+        {
+            Some((format!("{}/{}", owner, repo), file.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Download `url` with curl.
+fn download(url: &str, runner: &dyn CommandRunner) -> Result<Vec<u8>> {
+    let output = runner
+        .run_output("curl", &["-fsSL", url], &CommandOptions::default())
+        .context("Failed to download")?;
+
+    if !output.status.success() {
+        bail!(
+            "Download failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+/// Download a pinned app's release asset and check it against its checksum.
+fn verify_pinned(app: &AppImageApp, runner: &dyn CommandRunner) -> Result<()> {
+    let Some(url) = &app.url else {
+        return Ok(());
+    };
+    let bytes = download(url, runner).with_context(|| format!("Failed to download {}", url))?;
+    app.verify_download(&bytes)
+}
+
 /// Infer app name from repo (e.g., "OrcaSlicer/OrcaSlicer" -> "OrcaSlicer").
 fn infer_name_from_repo(repo: &str) -> String {
     repo.split('/').next_back().unwrap_or(repo).to_string()
@@ -109,8 +158,22 @@ pub fn run(args: AppImageArgs, plan: &ExecutionPlan) -> Result<()> {
             asset,
             name,
             prereleases,
+            pin_checksum,
         } => {
-            let repo = parse_github_repo(&repo)?;
+            let release = parse_release_url(&repo);
+            if pin_checksum && release.is_none() {
+                bail!(
+                    "--pin-checksum needs the release asset URL to download\n\n\
+                     Example: bkt appimage add --pin-checksum \
+                     https://github.com/owner/repo/releases/download/v1.0/App.AppImage"
+                );
+            }
+            let url = release.is_some().then(|| repo.clone());
+            let (repo, asset) = match (release, asset) {
+                (Some((repo, file)), asset) => (repo, asset.unwrap_or(file)),
+                (None, Some(asset)) => (parse_github_repo(&repo)?, asset),
+                (None, None) => bail!("--asset is required unless a release asset URL is given"),
+            };
             let name = name.unwrap_or_else(|| infer_name_from_repo(&repo));
             let manifests_dir = get_manifest_path(plan.runner())?;
 
@@ -121,14 +184,30 @@ pub fn run(args: AppImageArgs, plan: &ExecutionPlan) -> Result<()> {
                 Output::warning(format!("AppImage '{}' already in manifest, updating", name));
             }
 
-            let app = AppImageApp {
-                name: name.clone(),
-                repo: repo.clone(),
-                asset,
+            let mut app = AppImageApp {
                 prereleases,
-                disabled: false,
+                ..AppImageApp::new(name.clone(), repo.clone(), asset)
             };
 
+            if let Some(url) = url.as_ref().filter(|_| pin_checksum && plan.dry_run) {
+                Output::dry_run(format!("Would download {} and pin its checksum", url));
+            } else if let Some(url) = url.filter(|_| pin_checksum) {
+                let spinner = Output::spinner(format!("Downloading {}...", url));
+                let bytes = download(&url, plan.runner());
+                spinner.finish_clear();
+                app.pin(&url, &bytes?);
+                Output::info(format!(
+                    "Pinned sha256 {} ({} bytes)",
+                    app.sha256.as_deref().unwrap_or_default(),
+                    app.size.unwrap_or_default()
+                ));
+            } else {
+                Output::warning(format!(
+                    "No checksum pinned for '{}'; use --pin-checksum with a release asset URL",
+                    name
+                ));
+            }
+
             if plan.should_update_manifest() {
                 manifest.upsert(app.clone());
                 manifest.save_to_dir(&manifests_dir)?;
@@ -220,7 +299,7 @@ pub fn run(args: AppImageArgs, plan: &ExecutionPlan) -> Result<()> {
                 return Ok(());
             }
 
-            if format == "json" {
+            if format == OutputFormat::Json {
                 println!("{}", serde_json::to_string_pretty(&manifest.apps)?);
             } else {
                 println!(
@@ -254,52 +333,116 @@ pub fn run(args: AppImageArgs, plan: &ExecutionPlan) -> Result<()> {
             let plan_ctx = PlanContext::new(std::env::current_dir()?, plan.clone());
             let sync_plan = cmd.plan(&plan_ctx)?;
 
-            if sync_plan.is_empty() {
+            if sync_plan.is_empty() && !plan.json_output() {
                 Output::success("GearLever config is in sync with manifest");
                 return Ok(());
             }
 
-            print!("{}", sync_plan.describe());
+            let summary = sync_plan.describe().with_subsystem("appimage");
+            print_summary(&summary, plan, plan.dry_run)?;
 
             if plan.dry_run {
-                Output::info("Run without --dry-run to apply these changes.");
+                if !plan.json_output() {
+                    Output::info("Run without --dry-run to apply these changes.");
+                }
                 return Ok(());
             }
 
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = sync_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("appimage"), plan)?;
+        }
+        AppImageAction::Verify => {
+            let manifests_dir = get_manifest_path(plan.runner())?;
+            let manifest = AppImageAppsManifest::load_from_dir(&manifests_dir)?;
+            verify_all(&manifest, plan.runner())?;
         }
         AppImageAction::Capture { apply } => {
             let cmd = AppImageCaptureCommand;
             let plan_ctx = PlanContext::new(std::env::current_dir()?, plan.clone());
             let capture_plan = cmd.plan(&plan_ctx)?;
 
-            if capture_plan.is_empty() {
+            if capture_plan.is_empty() && !plan.json_output() {
                 Output::success("No new AppImages to capture");
                 return Ok(());
             }
 
-            print!("{}", capture_plan.describe());
+            let summary = capture_plan.describe().with_subsystem("appimage");
+            print_summary(&summary, plan, plan.dry_run || !apply)?;
 
             if !apply {
-                Output::info("Run with --apply to add these to the manifest.");
+                if !plan.json_output() {
+                    Output::info("Run with --apply to add these to the manifest.");
+                }
                 return Ok(());
             }
 
             if plan.dry_run {
-                Output::info("Run without --dry-run to apply these changes.");
+                if !plan.json_output() {
+                    Output::info("Run without --dry-run to apply these changes.");
+                }
                 return Ok(());
             }
 
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = capture_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("appimage"), plan)?;
         }
     }
     Ok(())
 }
 
+/// Re-download every pinned app and check it against its checksum.
+fn verify_all(manifest: &AppImageAppsManifest, runner: &dyn CommandRunner) -> Result<()> {
+    if manifest.apps.is_empty() {
+        Output::info("No AppImages in manifest");
+        return Ok(());
+    }
+
+    Output::header("VERIFYING APPIMAGES");
+    let mut verified = 0;
+    let mut drifted = Vec::new();
+    let mut skipped = 0;
+
+    for app in &manifest.apps {
+        if !app.is_pinned() {
+            Output::warning(format!("{}: no pinned checksum, skipping", app.name));
+            skipped += 1;
+            continue;
+        }
+
+        let spinner = Output::spinner(format!("Downloading {}...", app.name));
+        match verify_pinned(app, runner) {
+            Ok(()) => {
+                spinner.finish_success(format!("{}: checksum matches", app.name));
+                verified += 1;
+            }
+            Err(e) => {
+                spinner.finish_error(format!("{:#}", e));
+                drifted.push(app.name.clone());
+            }
+        }
+    }
+
+    Output::blank();
+    Output::info(format!(
+        "Verified: {}, Drifted: {}, Skipped: {}",
+        verified,
+        drifted.len(),
+        skipped
+    ));
+
+    if !drifted.is_empty() {
+        bail!(
+            "Upstream changed for {} AppImage(s): {}",
+            drifted.len(),
+            drifted.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Plan-based AppImage Sync Implementation
 // ============================================================================
@@ -423,6 +566,19 @@ impl Plan for AppImageSyncPlan {
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
 
+        // Check pinned downloads before touching GearLever's config, so a
+        // replaced upstream file stops the whole sync.
+        for item in &self.to_sync {
+            if item.app.is_pinned() {
+                verify_pinned(&item.app, ctx.execution_plan().runner())?;
+            } else {
+                Output::warning(format!(
+                    "AppImage '{}' has no pinned checksum; its download isn't verified",
+                    item.app.name
+                ));
+            }
+        }
+
         // Load current GearLever state
         let mut gearlever = GearLeverNativeManifest::load()?;
 
@@ -558,6 +714,13 @@ impl Plan for AppImageCapturePlan {
     }
 }
 
+impl CapturePlan for AppImageCapturePlan {
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.to_capture
+            .retain(|item| keep(&format!("appimage:{}", item.app.name)));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -576,6 +739,28 @@ mod tests {
         assert!(parse_github_repo("owner/").is_err());
     }
 
+    #[test]
+    fn test_parse_release_url() {
+        assert_eq!(
+            parse_release_url(
+                "https://github.com/OrcaSlicer/OrcaSlicer/releases/download/v2.1.0/Orca.AppImage"
+            ),
+            Some((
+                "OrcaSlicer/OrcaSlicer".to_string(),
+                "Orca.AppImage".to_string()
+            ))
+        );
+        assert_eq!(parse_release_url("github:owner/repo"), None);
+        assert_eq!(
+            parse_release_url("https://github.com/owner/repo/releases/latest"),
+            None
+        );
+        assert_eq!(
+            parse_release_url("https://example.com/owner/repo/releases/download/v1/a.AppImage"),
+            None
+        );
+    }
+
     #[test]
     fn test_infer_name_from_repo() {
         assert_eq!(infer_name_from_repo("OrcaSlicer/OrcaSlicer"), "OrcaSlicer");
@@ -2,13 +2,20 @@
 //!
 //! The `bkt apply` command composes multiple sync plans into one and executes them.
 //! This is the "manifest → system" direction of bidirectional sync.
+//!
+//! Each subsystem's plan executes on its own. A subsystem that errors out stops
+//! the apply unless `--continue-on-error` is given; either way a per-subsystem
+//! summary is printed and the exit code is non-zero if anything failed.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Args;
 
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
-use crate::plan::{CompositePlan, ExecuteContext, OperationProgress, Plan, PlanContext, Plannable};
+use crate::plan::{
+    CompositePlan, ExecuteContext, OperationProgress, Plan, PlanContext, Plannable, SubsystemTally,
+    print_report, print_summary,
+};
 
 use super::appimage::{AppImageSyncCommand, AppImageSyncPlan};
 use super::distrobox::{DistroboxSyncCommand, DistroboxSyncPlan};
@@ -64,6 +71,10 @@ pub struct ApplyArgs {
     /// Prune unmanaged AppImages (default is to keep them)
     #[arg(long)]
     pub prune_appimages: bool,
+
+    /// Keep going when a subsystem fails to plan or apply
+    #[arg(long)]
+    pub continue_on_error: bool,
 }
 
 /// Command to apply all manifests to the system.
@@ -75,6 +86,9 @@ pub struct ApplyCommand {
 
     /// Whether to prune unmanaged AppImages.
     pub prune_appimages: bool,
+
+    /// Whether a failing subsystem is recorded rather than aborting.
+    pub continue_on_error: bool,
 }
 
 impl ApplyCommand {
@@ -83,7 +97,24 @@ impl ApplyCommand {
             include: args.only.clone(),
             exclude: args.exclude.clone().unwrap_or_default(),
             prune_appimages: args.prune_appimages,
+            continue_on_error: args.continue_on_error,
+        }
+    }
+
+    /// Add a subsystem's plan, or with `--continue-on-error` its planning
+    /// failure, to `composite`.
+    fn add_plan<P: Plan + 'static>(
+        &self,
+        composite: &mut CompositePlan,
+        subsystem: Subsystem,
+        plan: Result<P>,
+    ) -> Result<()> {
+        match plan {
+            Ok(plan) => composite.add_for(subsystem.to_string(), plan),
+            Err(e) if self.continue_on_error => composite.add_failed(subsystem.to_string(), &e),
+            Err(e) => return Err(e),
         }
+        Ok(())
     }
 
     fn should_include(&self, subsystem: Subsystem) -> bool {
@@ -108,41 +139,44 @@ impl Plannable for ApplyCommand {
 
         // Shim sync
         if self.should_include(Subsystem::Shim) {
-            let shim_plan: ShimSyncPlan = ShimSyncCommand.plan(ctx)?;
-            composite.add(shim_plan);
+            let shim_plan: Result<ShimSyncPlan> = ShimSyncCommand {
+                keep_unmanaged: false,
+            }
+            .plan(ctx);
+            self.add_plan(&mut composite, Subsystem::Shim, shim_plan)?;
         }
 
         // Distrobox sync
         if self.should_include(Subsystem::Distrobox) {
-            let distrobox_plan: DistroboxSyncPlan = DistroboxSyncCommand.plan(ctx)?;
-            composite.add(distrobox_plan);
+            let distrobox_plan: Result<DistroboxSyncPlan> = DistroboxSyncCommand.plan(ctx);
+            self.add_plan(&mut composite, Subsystem::Distrobox, distrobox_plan)?;
         }
 
         // GSettings apply
         if self.should_include(Subsystem::Gsetting) {
-            let gsetting_plan: GsettingApplyPlan = GsettingApplyCommand.plan(ctx)?;
-            composite.add(gsetting_plan);
+            let gsetting_plan: Result<GsettingApplyPlan> = GsettingApplyCommand.plan(ctx);
+            self.add_plan(&mut composite, Subsystem::Gsetting, gsetting_plan)?;
         }
 
         // Extension sync
         if self.should_include(Subsystem::Extension) {
-            let extension_plan: ExtensionSyncPlan = ExtensionSyncCommand.plan(ctx)?;
-            composite.add(extension_plan);
+            let extension_plan: Result<ExtensionSyncPlan> = ExtensionSyncCommand.plan(ctx);
+            self.add_plan(&mut composite, Subsystem::Extension, extension_plan)?;
         }
 
         // Flatpak sync
         if self.should_include(Subsystem::Flatpak) {
-            let flatpak_plan: FlatpakSyncPlan = FlatpakSyncCommand.plan(ctx)?;
-            composite.add(flatpak_plan);
+            let flatpak_plan: Result<FlatpakSyncPlan> = FlatpakSyncCommand.plan(ctx);
+            self.add_plan(&mut composite, Subsystem::Flatpak, flatpak_plan)?;
         }
 
         // AppImage sync via GearLever
         if self.should_include(Subsystem::AppImage) {
-            let appimage_plan: AppImageSyncPlan = AppImageSyncCommand {
+            let appimage_plan: Result<AppImageSyncPlan> = AppImageSyncCommand {
                 keep_unmanaged: !self.prune_appimages,
             }
-            .plan(ctx)?;
-            composite.add(appimage_plan);
+            .plan(ctx);
+            self.add_plan(&mut composite, Subsystem::AppImage, appimage_plan)?;
         }
 
         Ok(composite)
@@ -157,20 +191,27 @@ pub fn run(args: ApplyArgs, exec_plan: &ExecutionPlan) -> Result<()> {
 
     let plan = cmd.plan(&plan_ctx)?;
 
-    if plan.is_empty() {
+    let json = exec_plan.json_output();
+    if plan.is_empty() && !json {
         Output::success("Nothing to apply. System is in sync with manifests.");
         return Ok(());
     }
 
     // Always show the plan
     let summary = plan.describe();
-    print!("{}", summary);
+    print_summary(&summary, exec_plan, exec_plan.dry_run)?;
 
     if exec_plan.dry_run {
-        Output::info("Run without --dry-run to apply these changes.");
+        if !json {
+            Output::info("Run without --dry-run to apply these changes.");
+        }
         return Ok(());
     }
 
+    if json && !args.confirm {
+        bail!("--format json cannot prompt for confirmation; pass --confirm");
+    }
+
     if !args.confirm {
         let confirmed = cliclack::confirm("Apply these changes?")
             .initial_value(false)
@@ -182,25 +223,68 @@ pub fn run(args: ApplyArgs, exec_plan: &ExecutionPlan) -> Result<()> {
         }
     }
 
-    // Print hint that we're executing
-    Output::info("Applying changes...");
-    println!();
-
     // Execute the plan with progress tracking
     let total_ops = summary.action_count();
     let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
     exec_ctx.set_total_ops(total_ops);
-    exec_ctx.set_progress_callback(print_progress);
+    if !json {
+        // Print hint that we're executing
+        Output::info("Applying changes...");
+        println!();
+        exec_ctx.set_progress_callback(print_progress);
+    }
 
-    let report = plan.execute(&mut exec_ctx)?;
+    let (report, skipped) = plan.execute_each(&mut exec_ctx, cmd.continue_on_error);
 
     // Print final summary (only failures, since progress showed successes)
-    println!();
-    print!("{}", report);
+    if !json {
+        println!();
+    }
+    print_report(&report, exec_plan)?;
+    if !json && report.has_failures() {
+        print_subsystem_summary(&report.subsystem_tallies(), &skipped);
+    }
+
+    if report.has_failures() {
+        let failed: Vec<String> = report
+            .subsystem_tallies()
+            .into_iter()
+            .filter(|t| t.failed > 0)
+            .map(|t| t.subsystem)
+            .collect();
+        bail!("Apply failed for: {}", failed.join(", "));
+    }
 
     Ok(())
 }
 
+/// Print a subsystem / succeeded / failed table, with the first error of
+/// each failing subsystem, and which subsystems never ran.
+fn print_subsystem_summary(tallies: &[SubsystemTally], skipped: &[String]) {
+    println!();
+    Output::subheader("Summary by subsystem");
+    println!(
+        "  {:<12} {:>9} {:>7}  First error",
+        "Subsystem", "Succeeded", "Failed"
+    );
+    for tally in tallies {
+        println!(
+            "  {:<12} {:>9} {:>7}  {}",
+            tally.subsystem,
+            tally.succeeded,
+            tally.failed,
+            tally.first_error.as_deref().unwrap_or("")
+        );
+    }
+    for subsystem in skipped {
+        println!("  {:<12} {:>9} {:>7}  (skipped)", subsystem, "-", "-");
+    }
+
+    if !skipped.is_empty() {
+        Output::hint("Re-run with --continue-on-error to apply the remaining subsystems.");
+    }
+}
+
 /// Print progress for a single operation.
 pub(crate) fn print_progress(progress: &OperationProgress) {
     use owo_colors::OwoColorize;
@@ -235,6 +319,7 @@ mod tests {
             include: None,
             exclude: vec![],
             prune_appimages: false,
+            continue_on_error: false,
         };
 
         assert!(cmd.should_include(Subsystem::Shim));
@@ -250,6 +335,7 @@ mod tests {
             include: Some(vec![Subsystem::Shim, Subsystem::Flatpak]),
             exclude: vec![],
             prune_appimages: false,
+            continue_on_error: false,
         };
 
         assert!(cmd.should_include(Subsystem::Shim));
@@ -264,6 +350,7 @@ mod tests {
             include: None,
             exclude: vec![Subsystem::Extension, Subsystem::Flatpak],
             prune_appimages: false,
+            continue_on_error: false,
         };
 
         assert!(cmd.should_include(Subsystem::Shim));
@@ -279,6 +366,7 @@ mod tests {
             include: Some(vec![Subsystem::Shim, Subsystem::Extension]),
             exclude: vec![Subsystem::Extension],
             prune_appimages: false,
+            continue_on_error: false,
         };
 
         assert!(cmd.should_include(Subsystem::Shim));
@@ -301,11 +389,13 @@ mod tests {
             exclude: Some(vec![Subsystem::Flatpak]),
             confirm: true,
             prune_appimages: true,
+            continue_on_error: true,
         };
 
         let cmd = ApplyCommand::from_args(&args);
         assert_eq!(cmd.include, Some(vec![Subsystem::Shim]));
         assert_eq!(cmd.exclude, vec![Subsystem::Flatpak]);
         assert!(cmd.prune_appimages);
+        assert!(cmd.continue_on_error);
     }
 }
@@ -13,6 +13,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::manifest::FlatpakRemote;
 use crate::output::Output;
 
 /// System manifest directory (baked into the image by the Containerfile)
@@ -232,6 +233,11 @@ fn apply_flatpak_remotes(bootstrap_dir: &Path) -> Result<()> {
             continue;
         }
 
+        // Priority, subset and filter, as remote-add/remote-modify flags
+        let option_flags = serde_json::from_value::<FlatpakRemote>(remote.clone())
+            .map(|r| r.option_flags())
+            .unwrap_or_default();
+
         // Add remote for both system and user scopes — apps may use either
         for add_scope in &["system", "user"] {
             Output::info(format!(
@@ -246,6 +252,7 @@ fn apply_flatpak_remotes(bootstrap_dir: &Path) -> Result<()> {
                 args.push("--no-gpg-verify");
             }
 
+            args.extend(option_flags.iter().map(String::as_str));
             args.push(name);
             args.push(url);
 
@@ -255,6 +262,21 @@ fn apply_flatpak_remotes(bootstrap_dir: &Path) -> Result<()> {
                     "Failed to add flatpak remote {} ({}); continuing",
                     name, add_scope
                 ));
+                continue;
+            }
+
+            // --if-not-exists leaves an existing remote alone; bring its
+            // settings in line without re-adding it
+            if !option_flags.is_empty() {
+                let mut args = vec!["remote-modify"];
+                args.extend(option_flags.iter().map(String::as_str));
+                args.push(name);
+                if !run_flatpak(add_scope, &args)?.success() {
+                    Output::info(format!(
+                        "Failed to update flatpak remote {} ({}); continuing",
+                        name, add_scope
+                    ));
+                }
             }
         }
     }
@@ -6,26 +6,32 @@
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use clap::{Args, Subcommand};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::containerfile::{Section, split_managed};
 use crate::manifest::base_image;
 use crate::manifest::build_info::{
-    AppImageDiff, BuildInfo, BuildMetadata, ExtensionDiff, FlatpakAppDiff, FlatpakRemoteDiff,
-    GSettingDiff, ManifestDiffs, ShimDiff, SystemConfigDiffs, SystemConfigEntry,
-    SystemConfigModified, UpstreamChanges, convert_diff_result,
+    AppImageDiff, BaseImageChange, BuildInfo, BuildMetadata, ContainerfileDiffs,
+    ContainerfileSectionDiff, ExtensionDiff, FlatpakAppDiff, FlatpakRemoteDiff, GSettingDiff,
+    ManifestDiffs, ShimDiff, SystemConfigDiffs, SystemConfigEntry, SystemConfigModified,
+    ToolChange, UpstreamChanges, convert_diff_result,
 };
 use crate::manifest::diff::{DiffResult, diff_collections, diff_string_sets};
-use crate::manifest::parsers::{ConfigFileType, compute_semantic_diff};
+use crate::manifest::parsers::{ConfigFileType, LineSummary, compute_semantic_diff};
+use crate::manifest::system_config::{IMAGE_UNIT_DIR, SystemConfigManifest};
+use crate::manifest::upstream::{self, UpstreamManifest};
 use crate::manifest::{
     AppImageApp, AppImageAppsManifest, ExtensionItem, FlatpakApp, FlatpakAppsManifest,
     FlatpakRemote, FlatpakRemotesManifest, GSetting, GSettingsManifest, GnomeExtensionsManifest,
-    Shim, ShimsManifest,
+    HostBinariesManifest, Shim, ShimsManifest,
 };
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::repo::find_repo_path;
+use crate::repo::git::{get_file_at_commit, is_shallow_clone, resolve_commit};
 
 #[derive(Debug, Args)]
 pub struct BuildInfoArgs {
@@ -50,7 +56,7 @@ pub enum BuildInfoAction {
         output: Option<PathBuf>,
     },
 
-    /// Render build info JSON to Markdown
+    /// Render build info JSON to Markdown or HTML
     Render {
         /// Path to build-info.json
         input: PathBuf,
@@ -58,6 +64,10 @@ pub enum BuildInfoAction {
         /// Output file (default: stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Document format: markdown or html
+        #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
     },
 
     /// Generate a short summary for OCI annotation (max 512 chars)
@@ -74,7 +84,11 @@ pub enum BuildInfoAction {
 pub fn run(args: BuildInfoArgs, runner: &dyn CommandRunner) -> Result<()> {
     match args.action {
         BuildInfoAction::Generate { from, to, output } => generate(from, to, output, runner),
-        BuildInfoAction::Render { input, output } => render(input, output),
+        BuildInfoAction::Render {
+            input,
+            output,
+            format,
+        } => render(input, output, format),
         BuildInfoAction::Summary { input, max_length } => summary(input, max_length),
     }
 }
@@ -120,6 +134,12 @@ fn generate(
     // Generate system config diffs
     let system_config = diff_system_config(&repo_path, &from_commit, &to_commit, runner)?;
 
+    // Generate Containerfile diffs
+    let old_containerfile = get_file_at_commit(&repo_path, &from_commit, "Containerfile", runner)?;
+    let new_containerfile = get_file_at_commit(&repo_path, &to_commit, "Containerfile", runner)?;
+    let containerfile =
+        diff_containerfile(old_containerfile.as_deref(), new_containerfile.as_deref());
+
     // Generate upstream changes (base image diff)
     let upstream = diff_upstream_changes(&repo_path, &from_commit, &to_commit, runner)?;
 
@@ -138,6 +158,11 @@ fn generate(
         build_info.system_config = Some(system_config);
     }
 
+    // Add Containerfile changes if there are any
+    if !containerfile.is_empty() {
+        build_info.containerfile = Some(containerfile);
+    }
+
     // Add upstream changes if present
     if let Some(upstream_changes) = upstream {
         build_info.upstream = Some(upstream_changes);
@@ -150,7 +175,7 @@ fn generate(
     write_output(&json, output)?;
 
     if build_info.is_empty() {
-        Output::info("No manifest changes detected.");
+        Output::info("No changes detected.");
     } else {
         Output::success("Build info generated.");
     }
@@ -158,63 +183,8 @@ fn generate(
     Ok(())
 }
 
-fn is_shallow_clone(repo_path: &Path) -> Result<bool> {
-    let shallow_file = repo_path.join(".git/shallow");
-    Ok(shallow_file.exists())
-}
-
-fn resolve_commit(
-    repo_path: &PathBuf,
-    ref_spec: &str,
-    runner: &dyn CommandRunner,
-) -> Result<String> {
-    let output = runner
-        .run_output(
-            "git",
-            &["rev-parse", ref_spec],
-            &CommandOptions::with_cwd(repo_path),
-        )
-        .context("Failed to run git rev-parse")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("Failed to resolve commit '{}': {}", ref_spec, stderr.trim());
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-}
-
-fn get_file_at_commit(
-    repo_path: &PathBuf,
-    commit: &str,
-    file_path: &str,
-    runner: &dyn CommandRunner,
-) -> Result<Option<String>> {
-    let git_path = format!("{}:{}", commit, file_path);
-    let output = runner
-        .run_output(
-            "git",
-            &["show", &git_path],
-            &CommandOptions::with_cwd(repo_path),
-        )
-        .context("Failed to run git show")?;
-
-    if !output.status.success() {
-        // File might not exist at this commit
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("does not exist") || stderr.contains("not found") {
-            return Ok(None);
-        }
-        // If it's a different error, still return None but log it
-        tracing::debug!("git show failed for {}: {}", git_path, stderr.trim());
-        return Ok(None);
-    }
-
-    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
-}
-
 fn diff_all_manifests(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -289,7 +259,7 @@ fn diff_all_manifests(
 // ============================================================================
 
 fn diff_flatpak_apps(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -310,7 +280,7 @@ fn diff_flatpak_apps(
 }
 
 fn diff_flatpak_remotes(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -335,7 +305,7 @@ fn diff_flatpak_remotes(
 }
 
 fn diff_extensions(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -360,7 +330,7 @@ fn diff_extensions(
 }
 
 fn diff_gsettings(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -376,7 +346,7 @@ fn diff_gsettings(
 }
 
 fn diff_shims(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -393,7 +363,7 @@ fn diff_shims(
 }
 
 fn diff_appimages(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -430,7 +400,7 @@ struct StringListManifest {
 }
 
 fn diff_string_manifest(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     manifest_path: &str,
@@ -468,7 +438,7 @@ fn diff_string_manifest(
 const SYSTEM_CONFIG_DIRS: &[&str] = &["system/", "systemd/", "skel/"];
 
 fn diff_system_config(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
@@ -499,7 +469,7 @@ fn diff_system_config(
                 let old_content = get_file_at_commit(repo_path, from_commit, &path, runner)?;
                 let new_content = get_file_at_commit(repo_path, to_commit, &path, runner)?;
 
-                let file_type = ConfigFileType::from_path(&path);
+                let file_type = ConfigFileType::detect(&path, new_content.as_deref());
                 let semantic_diff = match (&old_content, &new_content) {
                     (Some(old), Some(new)) => {
                         Some(compute_semantic_diff(file_type, Some(old), Some(new)))
@@ -521,12 +491,61 @@ fn diff_system_config(
         }
     }
 
+    // Drop-ins live in system-config.json, not as files under the scanned dirs
+    let old_dropins = dropins_at_commit(repo_path, from_commit, runner)?;
+    let new_dropins = dropins_at_commit(repo_path, to_commit, runner)?;
+    for (path, new) in &new_dropins {
+        match old_dropins.get(path) {
+            None => diffs.added.push(SystemConfigEntry { path: path.clone() }),
+            Some(old) if old != new => diffs.modified.push(SystemConfigModified {
+                path: path.clone(),
+                semantic_diff: Some(compute_semantic_diff(
+                    ConfigFileType::Systemd,
+                    Some(old),
+                    Some(new),
+                )),
+                diff: None,
+            }),
+            Some(_) => {}
+        }
+    }
+    for path in old_dropins.keys() {
+        if !new_dropins.contains_key(path) {
+            diffs.removed.push(SystemConfigEntry { path: path.clone() });
+        }
+    }
+
     Ok(diffs)
 }
 
+/// Systemd drop-in contents at a commit, keyed by their path in the image.
+fn dropins_at_commit(
+    repo_path: &Path,
+    commit: &str,
+    runner: &dyn CommandRunner,
+) -> Result<BTreeMap<String, String>> {
+    let content = get_file_at_commit(repo_path, commit, "manifests/system-config.json", runner)?;
+    let manifest: SystemConfigManifest = parse_or_default(content)?;
+
+    let mut files = BTreeMap::new();
+    for (unit, dropins) in manifest.systemd.iter().flat_map(|s| &s.dropins) {
+        for dropin in dropins {
+            let content = match &dropin.source {
+                Some(source) => get_file_at_commit(repo_path, commit, source, runner)?,
+                None => dropin.content.clone(),
+            };
+            files.insert(
+                dropin.path(IMAGE_UNIT_DIR, unit),
+                content.unwrap_or_default(),
+            );
+        }
+    }
+    Ok(files)
+}
+
 /// Get list of changed files in specified directories between two commits.
 fn get_changed_files(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     dirs: &[&str],
@@ -565,6 +584,89 @@ fn get_changed_files(
     Ok(results)
 }
 
+// ============================================================================
+// Containerfile diffing
+// ============================================================================
+
+/// Diff two versions of the Containerfile, section by section.
+///
+/// Managed sections report the items they gained or lost; everything else
+/// is only summarized by line count. A file whose markers don't parse is
+/// treated as entirely unmanaged.
+fn diff_containerfile(old: Option<&str>, new: Option<&str>) -> ContainerfileDiffs {
+    let split = |content: Option<&str>| {
+        let content = content.unwrap_or_default();
+        split_managed(content).unwrap_or_else(|e| {
+            tracing::warn!("Cannot parse Containerfile sections: {}", e);
+            (Vec::new(), content.lines().map(str::to_string).collect())
+        })
+    };
+    let (old_blocks, old_unmanaged) = split(old);
+    let (new_blocks, new_unmanaged) = split(new);
+
+    let content = |blocks: &[crate::containerfile::ManagedBlock], section: Section| {
+        blocks
+            .iter()
+            .find(|b| b.section == section)
+            .map(|b| b.content.clone())
+            .unwrap_or_default()
+    };
+
+    let mut diffs = ContainerfileDiffs::default();
+    for section in Section::ORDERED {
+        let old_content = content(&old_blocks, section);
+        let new_content = content(&new_blocks, section);
+        if old_content == new_content {
+            continue;
+        }
+
+        let old_items = section.items(&old_content);
+        let new_items = section.items(&new_content);
+        let mut items = diff_string_sets(&old_items, &new_items);
+        items.added.sort();
+        items.removed.sort();
+        diffs.sections.push(ContainerfileSectionDiff {
+            section: section.marker_name().to_string(),
+            added: items.added,
+            removed: items.removed,
+            lines: changed_lines(&old_content, &new_content),
+        });
+    }
+
+    let unmanaged = changed_lines(&old_unmanaged, &new_unmanaged);
+    if unmanaged.added > 0 || unmanaged.removed > 0 {
+        diffs.unmanaged = Some(unmanaged);
+    }
+
+    diffs
+}
+
+/// Count lines only in `new` (added) and only in `old` (removed).
+///
+/// Lines are compared as a multiset, so an edited line counts as one of
+/// each, and moving a line doesn't count at all.
+fn changed_lines(old: &[String], new: &[String]) -> LineSummary {
+    let mut counts: BTreeMap<&str, isize> = BTreeMap::new();
+    for line in old {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in new {
+        *counts.entry(line).or_default() += 1;
+    }
+    LineSummary {
+        added: counts
+            .values()
+            .filter(|&&n| n > 0)
+            .map(|&n| n as usize)
+            .sum(),
+        removed: counts
+            .values()
+            .filter(|&&n| n < 0)
+            .map(|&n| -n as usize)
+            .sum(),
+    }
+}
+
 // ============================================================================
 // Upstream changes (base image diffing)
 // ============================================================================
@@ -575,56 +677,150 @@ const BASE_IMAGE_NAME: &str = "ghcr.io/ublue-os/bazzite";
 /// Path to the digest file tracking the base image.
 const BASE_IMAGE_DIGEST_FILE: &str = "upstream/bazzite-stable.digest";
 
-/// Diff upstream changes between two commits.
+/// Diff upstream changes between two commits: the base image and the
+/// pinned tool versions.
 fn diff_upstream_changes(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     from_commit: &str,
     to_commit: &str,
     runner: &dyn CommandRunner,
 ) -> Result<Option<UpstreamChanges>> {
+    let base_image = diff_base_image(repo_path, from_commit, to_commit, runner)?;
+    let tools = diff_tools(repo_path, from_commit, to_commit, runner)?;
+
+    if base_image.is_none() && tools.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(UpstreamChanges { base_image, tools }))
+}
+
+/// The base image change between two commits, if its digest moved.
+fn diff_base_image(
+    repo_path: &Path,
+    from_commit: &str,
+    to_commit: &str,
+    runner: &dyn CommandRunner,
+) -> Result<Option<BaseImageChange>> {
     // Check if the digest file changed
     let old_digest = get_base_image_digest_at_commit(repo_path, from_commit, runner)?;
     let new_digest = get_base_image_digest_at_commit(repo_path, to_commit, runner)?;
 
     // If either digest is missing or they're the same, no change
-    match (&old_digest, &new_digest) {
-        (Some(old), Some(new)) if old != new => {
-            tracing::info!("Base image changed: {} → {}", &old[..16], &new[..16]);
-
-            // Try to compute package diffs
-            match base_image::diff_base_image(repo_path, BASE_IMAGE_NAME, old, new, runner) {
-                Ok(base_image_change) => Ok(Some(UpstreamChanges {
-                    base_image: Some(base_image_change),
-                    tools: None,
-                })),
-                Err(e) => {
-                    // Log the error but don't fail the build
-                    tracing::warn!("Failed to compute base image package diff: {}", e);
-                    Output::warning(format!(
-                        "Could not compute package diff (requires podman on host): {}",
-                        e
-                    ));
+    let (Some(old), Some(new)) = (&old_digest, &new_digest) else {
+        return Ok(None);
+    };
+    if old == new {
+        return Ok(None);
+    }
+    tracing::info!("Base image changed: {} → {}", &old[..16], &new[..16]);
+
+    // Try to compute package diffs
+    match base_image::diff_base_image(repo_path, BASE_IMAGE_NAME, old, new, runner) {
+        Ok(base_image_change) => Ok(Some(base_image_change)),
+        Err(e) => {
+            // Log the error but don't fail the build
+            tracing::warn!("Failed to compute base image package diff: {}", e);
+            Output::warning(format!(
+                "Could not compute package diff (requires podman on host): {}",
+                e
+            ));
 
-                    // Still report the digest change even without package details
-                    Ok(Some(UpstreamChanges {
-                        base_image: Some(crate::manifest::build_info::BaseImageChange {
-                            name: BASE_IMAGE_NAME.to_string(),
-                            previous_digest: old.clone(),
-                            current_digest: new.clone(),
-                            packages: None,
-                        }),
-                        tools: None,
-                    }))
-                }
-            }
-        }
-        _ => {
-            // No change or missing digests
-            Ok(None)
+            // Still report the digest change even without package details
+            Ok(Some(BaseImageChange {
+                name: BASE_IMAGE_NAME.to_string(),
+                previous_digest: old.clone(),
+                current_digest: new.clone(),
+                packages: None,
+            }))
         }
     }
 }
 
+/// Tool version changes between two commits, from the upstream manifest
+/// and the fetchbin host-binaries manifest.
+fn diff_tools(
+    repo_path: &Path,
+    from_commit: &str,
+    to_commit: &str,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<ToolChange>> {
+    let host_binaries = format!("manifests/{}", HostBinariesManifest::FILENAME);
+    let old_upstream: UpstreamManifest = parse_or_default(get_file_at_commit(
+        repo_path,
+        from_commit,
+        upstream::MANIFEST_PATH,
+        runner,
+    )?)?;
+    let new_upstream: UpstreamManifest = parse_or_default(get_file_at_commit(
+        repo_path,
+        to_commit,
+        upstream::MANIFEST_PATH,
+        runner,
+    )?)?;
+    let old_binaries: HostBinariesManifest = parse_or_default(get_file_at_commit(
+        repo_path,
+        from_commit,
+        &host_binaries,
+        runner,
+    )?)?;
+    let new_binaries: HostBinariesManifest = parse_or_default(get_file_at_commit(
+        repo_path,
+        to_commit,
+        &host_binaries,
+        runner,
+    )?)?;
+
+    let upstream_versions = |manifest: &UpstreamManifest| {
+        manifest
+            .upstreams
+            .iter()
+            .map(|u| (u.name.clone(), u.pinned.version.clone()))
+            .collect()
+    };
+    // An unpinned host binary tracks the latest release
+    let binary_versions = |manifest: &HostBinariesManifest| {
+        manifest
+            .binaries
+            .iter()
+            .map(|b| {
+                let version = b.version.clone().unwrap_or_else(|| "latest".to_string());
+                (b.name.clone(), version)
+            })
+            .collect()
+    };
+
+    let mut changes = tool_changes(
+        "upstream",
+        upstream_versions(&old_upstream),
+        upstream_versions(&new_upstream),
+    );
+    changes.extend(tool_changes(
+        "fetchbin",
+        binary_versions(&old_binaries),
+        binary_versions(&new_binaries),
+    ));
+    Ok(changes)
+}
+
+/// Changes between two name → version maps, sorted by name.
+fn tool_changes(
+    source: &str,
+    old: BTreeMap<String, String>,
+    new: BTreeMap<String, String>,
+) -> Vec<ToolChange> {
+    let names: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| old.get(*name) != new.get(*name))
+        .map(|name| ToolChange {
+            name: name.clone(),
+            previous_version: old.get(name).cloned(),
+            current_version: new.get(name).cloned(),
+            source: source.to_string(),
+        })
+        .collect()
+}
+
 /// Parse the digest from a digest file (handles comment lines).
 fn parse_digest_file(content: &str) -> Option<String> {
     for line in content.lines() {
@@ -642,7 +838,7 @@ fn parse_digest_file(content: &str) -> Option<String> {
 
 /// Get the base image digest at a specific commit.
 fn get_base_image_digest_at_commit(
-    repo_path: &PathBuf,
+    repo_path: &Path,
     commit: &str,
     runner: &dyn CommandRunner,
 ) -> Result<Option<String>> {
@@ -675,16 +871,22 @@ fn write_output(content: &str, output: Option<PathBuf>) -> Result<()> {
 // Render implementation
 // ============================================================================
 
-fn render(input: PathBuf, output: Option<PathBuf>) -> Result<()> {
+fn render(input: PathBuf, output: Option<PathBuf>, format: OutputFormat) -> Result<()> {
     let content = fs::read_to_string(&input)
         .with_context(|| format!("Failed to read {}", input.display()))?;
 
     let build_info: BuildInfo = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse build info from {}", input.display()))?;
 
-    let markdown = render_to_markdown(&build_info);
+    let rendered = match format {
+        OutputFormat::Markdown => render_to_markdown(&build_info),
+        OutputFormat::Html => render_to_html(&build_info),
+        OutputFormat::Table | OutputFormat::Json => {
+            bail!("build-info render supports --format markdown or html")
+        }
+    };
 
-    write_output(&markdown, output)?;
+    write_output(&rendered, output)?;
 
     Ok(())
 }
@@ -707,9 +909,10 @@ fn summary(input: PathBuf, max_length: usize) -> Result<()> {
 /// Priority order (per RFC-0013):
 /// 1. Kernel updates (always show - security critical)
 /// 2. Security-relevant packages
-/// 3. Flatpak changes
-/// 4. Extension changes
-/// 5. Config changes (lowest priority)
+/// 3. Tool version bumps
+/// 4. Flatpak changes
+/// 5. Extension changes
+/// 6. Config changes (lowest priority)
 fn generate_summary(info: &BuildInfo, max_length: usize) -> String {
     let mut parts: Vec<String> = Vec::new();
     let mut total_changes = 0;
@@ -805,6 +1008,28 @@ fn generate_summary(info: &BuildInfo, max_length: usize) -> String {
         }
     }
 
+    // Containerfile (lowest priority: most of it mirrors manifest changes)
+    if let Some(containerfile) = &info.containerfile
+        && !containerfile.is_empty()
+    {
+        let (added, removed) = containerfile
+            .sections
+            .iter()
+            .map(|s| &s.lines)
+            .chain(&containerfile.unmanaged)
+            .fold((0, 0), |(a, r), l| (a + l.added, r + l.removed));
+        total_changes += containerfile.sections.len() + containerfile.unmanaged.iter().count();
+        parts.push(format!("🐳Containerfile +{}/-{} lines", added, removed));
+    }
+
+    // Tool bumps, ahead of everything but the base image
+    if let Some(upstream) = &info.upstream {
+        total_changes += upstream.tools.len();
+        for (i, tool) in upstream.tools.iter().enumerate() {
+            parts.insert(i, format!("🛠{} {}", tool.name, tool.describe()));
+        }
+    }
+
     // Upstream changes (base image)
     if let Some(upstream) = &info.upstream
         && let Some(base) = &upstream.base_image
@@ -866,12 +1091,14 @@ fn render_to_markdown(info: &BuildInfo) -> String {
     }
     md.push('\n');
 
-    if info.manifests.is_empty() {
-        md.push_str("*No manifest changes detected.*\n");
+    if info.is_empty() {
+        md.push_str("*No changes detected.*\n");
         return md;
     }
 
-    md.push_str("## Manifest Changes\n\n");
+    if !info.manifests.is_empty() {
+        md.push_str("## Manifest Changes\n\n");
+    }
 
     // Flatpak apps
     if let Some(diff) = &info.manifests.flatpak_apps
@@ -945,6 +1172,14 @@ fn render_to_markdown(info: &BuildInfo) -> String {
         render_system_config_diff(&mut md, config);
     }
 
+    // Containerfile
+    if let Some(containerfile) = &info.containerfile
+        && !containerfile.is_empty()
+    {
+        md.push_str("## Containerfile Changes\n\n");
+        render_containerfile_diff(&mut md, containerfile);
+    }
+
     // Upstream changes (base image)
     if let Some(upstream) = &info.upstream {
         md.push_str("## Upstream Changes\n\n");
@@ -977,8 +1212,8 @@ fn render_flatpak_apps_diff(md: &mut String, diff: &DiffResult<FlatpakAppDiff>)
 }
 
 fn render_flatpak_remotes_diff(md: &mut String, diff: &DiffResult<FlatpakRemoteDiff>) {
-    md.push_str("| Change | Remote | URL |\n");
-    md.push_str("|--------|--------|-----|\n");
+    md.push_str("| Change | Remote | Details |\n");
+    md.push_str("|--------|--------|---------|\n");
 
     for remote in &diff.added {
         md.push_str(&format!(
@@ -991,13 +1226,56 @@ fn render_flatpak_remotes_diff(md: &mut String, diff: &DiffResult<FlatpakRemoteD
     }
     for change in &diff.changed {
         md.push_str(&format!(
-            "| 🔄 Changed | `{}` | {} → {} |\n",
-            change.to.name, change.from.url, change.to.url
+            "| 🔄 Changed | `{}` | {} |\n",
+            change.to.name,
+            remote_changes(&change.from, &change.to)
         ));
     }
     md.push('\n');
 }
 
+/// Describe which settings of a remote changed, e.g. `priority 1 → 10`.
+fn remote_changes(from: &FlatpakRemoteDiff, to: &FlatpakRemoteDiff) -> String {
+    fn show<T: std::fmt::Display>(value: &Option<T>) -> String {
+        value
+            .as_ref()
+            .map_or_else(|| "unset".to_string(), |v| v.to_string())
+    }
+
+    let mut changes = Vec::new();
+    if from.url != to.url {
+        changes.push(format!("{} → {}", from.url, to.url));
+    }
+    if from.scope != to.scope {
+        changes.push(format!("scope {} → {}", show(&from.scope), show(&to.scope)));
+    }
+    if from.priority != to.priority {
+        changes.push(format!(
+            "priority {} → {}",
+            show(&from.priority),
+            show(&to.priority)
+        ));
+    }
+    if from.subset != to.subset {
+        changes.push(format!(
+            "subset {} → {}",
+            show(&from.subset),
+            show(&to.subset)
+        ));
+    }
+    if from.filter_path != to.filter_path {
+        changes.push(format!(
+            "filter {} → {}",
+            show(&from.filter_path),
+            show(&to.filter_path)
+        ));
+    }
+    if changes.is_empty() {
+        return "settings changed".to_string();
+    }
+    changes.join(", ")
+}
+
 fn render_string_diff(md: &mut String, diff: &DiffResult<String>) {
     for item in &diff.added {
         md.push_str(&format!("- ➕ `{}`\n", item));
@@ -1141,6 +1419,9 @@ fn render_system_config_diff(md: &mut String, config: &SystemConfigDiffs) {
                 Some(SemanticDiff::KeyValue(diff)) => {
                     render_keyvalue_diff(md, diff);
                 }
+                Some(SemanticDiff::Xml(diff)) => {
+                    render_xml_diff(md, diff);
+                }
                 Some(SemanticDiff::LineSummary(diff)) => {
                     render_line_summary(md, diff);
                 }
@@ -1235,6 +1516,27 @@ fn render_keyvalue_diff(md: &mut String, diff: &crate::manifest::parsers::KeyVal
     md.push('\n');
 }
 
+fn render_xml_diff(md: &mut String, diff: &crate::manifest::parsers::XmlDiff) {
+    if diff.is_empty() {
+        md.push_str("*No changes*\n\n");
+        return;
+    }
+
+    md.push_str("| Path | Change |\n");
+    md.push_str("|------|--------|\n");
+
+    for change in diff.changes() {
+        let change_display = match (&change.from, &change.to) {
+            (None, Some(new)) => format!("➕ Added: `{}`", new),
+            (Some(old), None) => format!("➖ Removed: `{}`", old),
+            (Some(old), Some(new)) => format!("`{}` → `{}`", old, new),
+            (None, None) => "—".to_string(),
+        };
+        md.push_str(&format!("| `{}` | {} |\n", change.property, change_display));
+    }
+    md.push('\n');
+}
+
 fn render_line_summary(md: &mut String, diff: &crate::manifest::parsers::LineSummary) {
     md.push_str(&format!(
         "*{} lines added, {} lines removed*\n\n",
@@ -1242,6 +1544,27 @@ fn render_line_summary(md: &mut String, diff: &crate::manifest::parsers::LineSum
     ));
 }
 
+fn render_containerfile_diff(md: &mut String, diff: &ContainerfileDiffs) {
+    for section in &diff.sections {
+        md.push_str(&format!("### `{}`\n\n", section.section));
+        for item in &section.added {
+            md.push_str(&format!("- ➕ `{}`\n", item));
+        }
+        for item in &section.removed {
+            md.push_str(&format!("- ➖ `{}`\n", item));
+        }
+        if !section.added.is_empty() || !section.removed.is_empty() {
+            md.push('\n');
+        }
+        render_line_summary(md, &section.lines);
+    }
+
+    if let Some(lines) = &diff.unmanaged {
+        md.push_str("### Outside managed sections\n\n");
+        render_line_summary(md, lines);
+    }
+}
+
 fn render_upstream_diff(md: &mut String, upstream: &UpstreamChanges) {
     // Base image changes
     if let Some(base) = &upstream.base_image {
@@ -1321,4 +1644,769 @@ fn render_upstream_diff(md: &mut String, upstream: &UpstreamChanges) {
             md.push_str("*Package diff not available*\n\n");
         }
     }
+
+    if !upstream.tools.is_empty() {
+        md.push_str("### Tools\n\n");
+        for tool in &upstream.tools {
+            md.push_str(&format!(
+                "- `{}` ({}): {}\n",
+                tool.name,
+                tool.source,
+                tool.describe()
+            ));
+        }
+        md.push('\n');
+    }
+}
+
+// ============================================================================
+// HTML rendering
+// ============================================================================
+
+/// Styles for the standalone HTML page; inline so the page has no assets.
+const HTML_STYLE: &str = "\
+body{font:15px/1.5 system-ui,sans-serif;margin:0;color:#1f2328;background:#fff}
+main{max-width:960px;margin:0 auto;padding:1.5rem}
+h1,h2{border-bottom:1px solid #d0d7de;padding-bottom:.3rem}
+dl.meta{display:grid;grid-template-columns:max-content 1fr;gap:.2rem 1rem}
+dl.meta dt{font-weight:600}
+dl.meta dd{margin:0}
+details{border:1px solid #d0d7de;border-radius:6px;margin:.75rem 0;padding:.5rem .75rem}
+summary{cursor:pointer;font-weight:600}
+summary .count{color:#59636e;font-weight:400;margin-left:.5rem}
+table{border-collapse:collapse;width:100%;margin:.5rem 0}
+th,td{border:1px solid #d0d7de;padding:.25rem .5rem;text-align:left;vertical-align:top}
+th{background:#f6f8fa}
+code{font:13px ui-monospace,monospace;word-break:break-all}
+tr.added td:first-child{color:#1a7f37}
+tr.removed td:first-child{color:#cf222e}
+tr.changed td:first-child{color:#9a6700}
+p.note{color:#59636e;font-style:italic}
+";
+
+/// Render build info as a standalone HTML document.
+///
+/// Every manifest diff, modified config file, Containerfile section and the
+/// base image get a collapsible section. All strings from the build info
+/// are escaped.
+fn render_to_html(info: &BuildInfo) -> String {
+    let commit = short(&info.build.commit, 8);
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!(
+        "<title>Build Info {}</title>\n",
+        escape_html(commit)
+    ));
+    html.push_str(&format!("<style>\n{}</style>\n", HTML_STYLE));
+    html.push_str("</head>\n<body>\n<main>\n<h1>Build Info</h1>\n");
+
+    html.push_str("<dl class=\"meta\">\n");
+    html.push_str(&format!("<dt>Commit</dt><dd>{}</dd>\n", code(commit)));
+    html.push_str(&format!(
+        "<dt>Timestamp</dt><dd>{}</dd>\n",
+        escape_html(&info.build.timestamp.to_string())
+    ));
+    if let Some(prev) = &info.build.previous_commit {
+        html.push_str(&format!(
+            "<dt>Previous</dt><dd>{}</dd>\n",
+            code(short(prev, 8))
+        ));
+    }
+    html.push_str("</dl>\n");
+
+    if info.is_empty() {
+        html.push_str("<p class=\"note\">No changes detected.</p>\n");
+    } else {
+        render_html_changes(&mut html, info);
+    }
+
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+fn render_html_changes(html: &mut String, info: &BuildInfo) {
+    let manifests = &info.manifests;
+    if !manifests.is_empty() {
+        html.push_str("<h2>Manifest Changes</h2>\n");
+    }
+
+    if let Some(diff) = &manifests.flatpak_apps {
+        let rows = diff_rows(
+            diff,
+            |app| {
+                vec![
+                    code(&app.id),
+                    format!("Remote: {}", escape_html(&app.remote)),
+                ]
+            },
+            |app| vec![code(&app.id), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.id),
+                    escape_html(&format!("{} → {}", change.from.remote, change.to.remote)),
+                ]
+            },
+        );
+        html_table_section(html, "Flatpak Apps", &["Change", "App", "Details"], &rows);
+    }
+
+    if let Some(diff) = &manifests.flatpak_remotes {
+        let rows = diff_rows(
+            diff,
+            |remote| vec![code(&remote.name), escape_html(&remote.url)],
+            |remote| vec![code(&remote.name), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.name),
+                    escape_html(&remote_changes(&change.from, &change.to)),
+                ]
+            },
+        );
+        html_table_section(
+            html,
+            "Flatpak Remotes",
+            &["Change", "Remote", "Details"],
+            &rows,
+        );
+    }
+
+    for (title, diff) in [
+        ("System Packages", &manifests.system_packages),
+        ("Toolbox Packages", &manifests.toolbox_packages),
+    ] {
+        if let Some(diff) = diff {
+            let rows = diff_rows(
+                diff,
+                |package| vec![code(package)],
+                |package| vec![code(package)],
+                |change| vec![code(&change.to)],
+            );
+            html_table_section(html, title, &["Change", "Package"], &rows);
+        }
+    }
+
+    if let Some(diff) = &manifests.gnome_extensions {
+        let state = |enabled: bool| if enabled { "enabled" } else { "disabled" };
+        let rows = diff_rows(
+            diff,
+            |ext| vec![code(&ext.id), state(ext.enabled).to_string()],
+            |ext| vec![code(&ext.id), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.id),
+                    format!(
+                        "{} → {}",
+                        state(change.from.enabled),
+                        state(change.to.enabled)
+                    ),
+                ]
+            },
+        );
+        html_table_section(
+            html,
+            "GNOME Extensions",
+            &["Change", "Extension", "State"],
+            &rows,
+        );
+    }
+
+    if let Some(diff) = &manifests.gsettings {
+        let rows = diff_rows(
+            diff,
+            |s| vec![code(&s.schema), code(&s.key), code(&s.value)],
+            |s| vec![code(&s.schema), code(&s.key), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.schema),
+                    code(&change.to.key),
+                    format!("{} → {}", code(&change.from.value), code(&change.to.value)),
+                ]
+            },
+        );
+        html_table_section(
+            html,
+            "GSettings",
+            &["Change", "Schema", "Key", "Value"],
+            &rows,
+        );
+    }
+
+    if let Some(diff) = &manifests.host_shims {
+        let host = |shim: &ShimDiff| shim.host.clone().unwrap_or_else(|| shim.name.clone());
+        let rows = diff_rows(
+            diff,
+            |shim| vec![code(&shim.name), code(&host(shim))],
+            |shim| vec![code(&shim.name), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.name),
+                    format!(
+                        "{} → {}",
+                        code(&host(&change.from)),
+                        code(&host(&change.to))
+                    ),
+                ]
+            },
+        );
+        html_table_section(
+            html,
+            "Host Shims",
+            &["Change", "Shim", "Host Command"],
+            &rows,
+        );
+    }
+
+    if let Some(diff) = &manifests.appimage_apps {
+        let rows = diff_rows(
+            diff,
+            |app| vec![code(&app.name), escape_html(&app.repo)],
+            |app| vec![code(&app.name), String::new()],
+            |change| {
+                vec![
+                    code(&change.to.name),
+                    escape_html(&format!("{} → {}", change.from.repo, change.to.repo)),
+                ]
+            },
+        );
+        html_table_section(
+            html,
+            "AppImage Apps",
+            &["Change", "App", "Repository"],
+            &rows,
+        );
+    }
+
+    if let Some(config) = &info.system_config
+        && !config.is_empty()
+    {
+        html.push_str("<h2>System Config Changes</h2>\n");
+        render_system_config_html(html, config);
+    }
+
+    if let Some(containerfile) = &info.containerfile
+        && !containerfile.is_empty()
+    {
+        html.push_str("<h2>Containerfile Changes</h2>\n");
+        render_containerfile_html(html, containerfile);
+    }
+
+    if let Some(upstream) = &info.upstream {
+        html.push_str("<h2>Upstream Changes</h2>\n");
+        if let Some(base) = &upstream.base_image {
+            render_base_image_html(html, base);
+        }
+        if !upstream.tools.is_empty() {
+            render_tools_html(html, &upstream.tools);
+        }
+    }
+}
+
+/// A table row: its change class (`added`, `removed`, `changed` or none)
+/// and its cells, already escaped.
+type HtmlRow = (&'static str, Vec<String>);
+
+/// Table rows for a manifest diff, with a leading change column.
+fn diff_rows<T>(
+    diff: &DiffResult<T>,
+    added: impl Fn(&T) -> Vec<String>,
+    removed: impl Fn(&T) -> Vec<String>,
+    changed: impl Fn(&crate::manifest::diff::ChangedItem<T>) -> Vec<String>,
+) -> Vec<HtmlRow> {
+    let row = |class: &'static str, label: &str, cells: Vec<String>| {
+        let mut row = vec![label.to_string()];
+        row.extend(cells);
+        (class, row)
+    };
+    diff.added
+        .iter()
+        .map(|item| row("added", "Added", added(item)))
+        .chain(
+            diff.removed
+                .iter()
+                .map(|item| row("removed", "Removed", removed(item))),
+        )
+        .chain(
+            diff.changed
+                .iter()
+                .map(|item| row("changed", "Changed", changed(item))),
+        )
+        .collect()
+}
+
+/// A collapsible section holding one table; nothing when `rows` is empty.
+fn html_table_section(html: &mut String, title: &str, headers: &[&str], rows: &[HtmlRow]) {
+    if rows.is_empty() {
+        return;
+    }
+    html_details_open(html, &escape_html(title), rows.len());
+    html_table(html, headers, rows);
+    html.push_str("</details>\n");
+}
+
+/// Open a `<details>` section; `title` is already escaped.
+fn html_details_open(html: &mut String, title: &str, changes: usize) {
+    html.push_str(&format!(
+        "<details open>\n<summary>{}<span class=\"count\">{} change{}</span></summary>\n",
+        title,
+        changes,
+        if changes == 1 { "" } else { "s" }
+    ));
+}
+
+fn html_table(html: &mut String, headers: &[&str], rows: &[HtmlRow]) {
+    html.push_str("<table>\n<thead><tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape_html(header)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for (class, cells) in rows {
+        if class.is_empty() {
+            html.push_str("<tr>");
+        } else {
+            html.push_str(&format!("<tr class=\"{}\">", class));
+        }
+        for cell in cells {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+fn render_system_config_html(html: &mut String, config: &SystemConfigDiffs) {
+    use crate::manifest::parsers::SemanticDiff;
+
+    let files: Vec<HtmlRow> = config
+        .added
+        .iter()
+        .map(|entry| ("added", vec!["Added".to_string(), code(&entry.path)]))
+        .chain(
+            config
+                .removed
+                .iter()
+                .map(|entry| ("removed", vec!["Removed".to_string(), code(&entry.path)])),
+        )
+        .collect();
+    html_table_section(html, "Added and Removed Files", &["Change", "Path"], &files);
+
+    for entry in &config.modified {
+        // Semantic diffs become before/after tables keyed by section and key
+        let rows: Vec<HtmlRow> = match &entry.semantic_diff {
+            Some(SemanticDiff::Keyd(diff)) => diff
+                .sections
+                .iter()
+                .flat_map(|(section, bindings)| {
+                    bindings
+                        .iter()
+                        .map(move |b| before_after_row(Some(section), &b.key, &b.from, &b.to))
+                })
+                .collect(),
+            Some(SemanticDiff::Systemd(diff)) => diff
+                .sections
+                .iter()
+                .flat_map(|(section, props)| {
+                    props
+                        .iter()
+                        .map(move |p| before_after_row(Some(section), &p.property, &p.from, &p.to))
+                })
+                .collect(),
+            Some(SemanticDiff::KeyValue(diff)) => diff
+                .sections
+                .iter()
+                .flat_map(|(section, props)| {
+                    let section = if section.is_empty() {
+                        "(root)"
+                    } else {
+                        section
+                    };
+                    props
+                        .iter()
+                        .map(move |p| before_after_row(Some(section), &p.property, &p.from, &p.to))
+                })
+                .collect(),
+            Some(SemanticDiff::Xml(diff)) => diff
+                .changes()
+                .map(|p| before_after_row(None, &p.property, &p.from, &p.to))
+                .collect(),
+            Some(SemanticDiff::LineSummary(_)) | None => Vec::new(),
+        };
+
+        let changes = match &entry.semantic_diff {
+            Some(SemanticDiff::LineSummary(lines)) => lines.added + lines.removed,
+            _ => rows.len(),
+        };
+        html_details_open(html, &code(&entry.path), changes);
+        match &entry.semantic_diff {
+            Some(SemanticDiff::LineSummary(lines)) => html_line_summary(html, lines),
+            None => html.push_str("<p class=\"note\">No semantic diff available</p>\n"),
+            Some(SemanticDiff::Xml(_)) if !rows.is_empty() => {
+                html_table(html, &["Change", "Path", "Before", "After"], &rows);
+            }
+            Some(_) if !rows.is_empty() => {
+                html_table(
+                    html,
+                    &["Change", "Section", "Key", "Before", "After"],
+                    &rows,
+                );
+            }
+            Some(_) => html.push_str("<p class=\"note\">No changes</p>\n"),
+        }
+        html.push_str("</details>\n");
+    }
+}
+
+/// A before/after row for a semantic config change.
+fn before_after_row(
+    section: Option<&str>,
+    key: &str,
+    from: &Option<String>,
+    to: &Option<String>,
+) -> HtmlRow {
+    let (class, label) = match (from, to) {
+        (None, Some(_)) => ("added", "Added"),
+        (Some(_), None) => ("removed", "Removed"),
+        _ => ("changed", "Changed"),
+    };
+    let value = |v: &Option<String>| v.as_deref().map(code).unwrap_or_default();
+    let mut cells = vec![label.to_string()];
+    if let Some(section) = section {
+        cells.push(code(&format!("[{}]", section)));
+    }
+    cells.extend([code(key), value(from), value(to)]);
+    (class, cells)
+}
+
+fn html_line_summary(html: &mut String, lines: &LineSummary) {
+    html.push_str(&format!(
+        "<p class=\"note\">{} lines added, {} lines removed</p>\n",
+        lines.added, lines.removed
+    ));
+}
+
+fn render_containerfile_html(html: &mut String, diff: &ContainerfileDiffs) {
+    for section in &diff.sections {
+        let rows: Vec<HtmlRow> = section
+            .added
+            .iter()
+            .map(|item| ("added", vec!["Added".to_string(), code(item)]))
+            .chain(
+                section
+                    .removed
+                    .iter()
+                    .map(|item| ("removed", vec!["Removed".to_string(), code(item)])),
+            )
+            .collect();
+        html_details_open(
+            html,
+            &code(&section.section),
+            section.lines.added + section.lines.removed,
+        );
+        if !rows.is_empty() {
+            html_table(html, &["Change", "Item"], &rows);
+        }
+        html_line_summary(html, &section.lines);
+        html.push_str("</details>\n");
+    }
+
+    if let Some(lines) = &diff.unmanaged {
+        html_details_open(
+            html,
+            "Outside managed sections",
+            lines.added + lines.removed,
+        );
+        html_line_summary(html, lines);
+        html.push_str("</details>\n");
+    }
+}
+
+fn render_base_image_html(html: &mut String, base: &BaseImageChange) {
+    let changes = base
+        .packages
+        .as_ref()
+        .map_or(0, |p| p.added.len() + p.removed.len() + p.updated.len());
+    html_details_open(html, "Base Image", changes);
+    html.push_str("<dl class=\"meta\">\n");
+    html.push_str(&format!("<dt>Image</dt><dd>{}</dd>\n", code(&base.name)));
+    html.push_str(&format!(
+        "<dt>Digest</dt><dd>{} → {}</dd>\n",
+        code(&base.previous_digest),
+        code(&base.current_digest)
+    ));
+    html.push_str("</dl>\n");
+
+    match &base.packages {
+        Some(packages) => {
+            let rows: Vec<HtmlRow> = packages
+                .updated
+                .iter()
+                .map(|u| {
+                    (
+                        "changed",
+                        vec![
+                            "Updated".to_string(),
+                            code(&u.name),
+                            escape_html(&u.from),
+                            escape_html(&u.to),
+                        ],
+                    )
+                })
+                .chain(packages.added.iter().map(|name| {
+                    (
+                        "added",
+                        vec![
+                            "Added".to_string(),
+                            code(name),
+                            String::new(),
+                            String::new(),
+                        ],
+                    )
+                }))
+                .chain(packages.removed.iter().map(|name| {
+                    (
+                        "removed",
+                        vec![
+                            "Removed".to_string(),
+                            code(name),
+                            String::new(),
+                            String::new(),
+                        ],
+                    )
+                }))
+                .collect();
+            if !rows.is_empty() {
+                html_table(html, &["Change", "Package", "From", "To"], &rows);
+            }
+        }
+        None => html.push_str("<p class=\"note\">Package diff not available</p>\n"),
+    }
+    html.push_str("</details>\n");
+}
+
+fn render_tools_html(html: &mut String, tools: &[ToolChange]) {
+    let rows: Vec<HtmlRow> = tools
+        .iter()
+        .map(|tool| {
+            let (class, label, version) = match (&tool.previous_version, &tool.current_version) {
+                (None, Some(version)) => ("added", "Added", version.clone()),
+                (Some(version), None) => ("removed", "Removed", version.clone()),
+                _ => ("changed", "Updated", tool.describe()),
+            };
+            (
+                class,
+                vec![
+                    label.to_string(),
+                    code(&tool.name),
+                    escape_html(&tool.source),
+                    escape_html(&version),
+                ],
+            )
+        })
+        .collect();
+    html_table_section(
+        html,
+        "Tools",
+        &["Change", "Tool", "Source", "Version"],
+        &rows,
+    );
+}
+
+/// `value` escaped inside `<code>`.
+fn code(value: &str) -> String {
+    format!("<code>{}</code>", escape_html(value))
+}
+
+/// Escape text for HTML element content and double-quoted attributes.
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The first `len` bytes of a commit or digest (all of it when shorter).
+fn short(value: &str, len: usize) -> &str {
+    value.get(..len).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "\
+FROM fedora:41
+ARG CACHE_EPOCH=1
+# === COPR_REPOS (managed by bkt) ===
+# No COPR repositories configured
+# === END COPR_REPOS ===
+# === SYSTEM_PACKAGES (managed by bkt) ===
+RUN dnf install -y \\
+    htop \\
+    vim \\
+    && dnf clean all
+# === END SYSTEM_PACKAGES ===
+";
+
+    const NEW: &str = "\
+FROM fedora:41
+ARG CACHE_EPOCH=2
+# === COPR_REPOS (managed by bkt) ===
+RUN set -eu; \\
+    dnf copr enable -y atim/starship
+# === END COPR_REPOS ===
+# === SYSTEM_PACKAGES (managed by bkt) ===
+RUN dnf install -y \\
+    htop \\
+    starship \\
+    && dnf clean all
+# === END SYSTEM_PACKAGES ===
+";
+
+    #[test]
+    fn test_diff_containerfile_reports_sections_and_unmanaged_lines() {
+        let diff = diff_containerfile(Some(OLD), Some(NEW));
+
+        let sections: Vec<_> = diff.sections.iter().map(|s| s.section.as_str()).collect();
+        assert_eq!(sections, vec!["COPR_REPOS", "SYSTEM_PACKAGES"]);
+        assert_eq!(diff.sections[0].added, vec!["atim/starship"]);
+        assert_eq!(diff.sections[1].added, vec!["starship"]);
+        assert_eq!(diff.sections[1].removed, vec!["vim"]);
+
+        let unmanaged = diff.unmanaged.as_ref().unwrap();
+        assert_eq!((unmanaged.added, unmanaged.removed), (1, 1));
+
+        assert!(diff_containerfile(Some(NEW), Some(NEW)).is_empty());
+    }
+
+    #[test]
+    fn test_containerfile_only_build_is_reported() {
+        let mut info = BuildInfo::new(
+            BuildMetadata {
+                commit: "0123456789abcdef".to_string(),
+                timestamp: Utc::now(),
+                previous_commit: None,
+            },
+            ManifestDiffs::default(),
+        );
+        info.containerfile = Some(diff_containerfile(Some(OLD), Some(NEW)));
+        assert!(!info.is_empty());
+
+        let md = render_to_markdown(&info);
+        assert!(md.contains("## Containerfile Changes"), "{md}");
+        assert!(md.contains("- ➕ `starship`"), "{md}");
+        assert!(md.contains("### Outside managed sections"), "{md}");
+        assert!(!md.contains("## Manifest Changes"), "{md}");
+
+        let summary = generate_summary(&info, 512);
+        assert!(summary.starts_with("🐳Containerfile +"), "{summary}");
+        assert!(generate_summary(&info, 10).len() <= 10);
+    }
+
+    #[test]
+    fn test_tool_changes_cover_bumps_and_new_and_removed_tools() {
+        let versions = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let changes = tool_changes(
+            "upstream",
+            versions(&[("keyd", "v2.4.3"), ("getnf", "v0.3.0"), ("old", "1.0")]),
+            versions(&[
+                ("keyd", "v2.5.0"),
+                ("getnf", "v0.3.0"),
+                ("starship", "1.20"),
+            ]),
+        );
+        let described: Vec<String> = changes
+            .iter()
+            .map(|c| format!("{} {}", c.name, c.describe()))
+            .collect();
+        assert_eq!(
+            described,
+            ["keyd v2.4.3 → v2.5.0", "old removed", "starship new"]
+        );
+
+        let mut info = BuildInfo::new(
+            BuildMetadata {
+                commit: "0123456789abcdef".to_string(),
+                timestamp: Utc::now(),
+                previous_commit: None,
+            },
+            ManifestDiffs::default(),
+        );
+        info.manifests.system_packages = Some(DiffResult {
+            added: vec!["htop".to_string()],
+            removed: vec![],
+            changed: vec![],
+        });
+        info.upstream = Some(UpstreamChanges {
+            base_image: None,
+            tools: changes,
+        });
+
+        let md = render_to_markdown(&info);
+        assert!(md.contains("### Tools"), "{md}");
+        assert!(md.contains("- `keyd` (upstream): v2.4.3 → v2.5.0"), "{md}");
+        assert!(md.contains("- `starship` (upstream): new"), "{md}");
+
+        let summary = generate_summary(&info, 512);
+        assert!(
+            summary.starts_with("🛠keyd v2.4.3 → v2.5.0 | 🛠old removed | 🛠starship new | ➕1 pkg"),
+            "{summary}"
+        );
+    }
+
+    #[test]
+    fn test_render_html_matches_golden_file() {
+        // Regenerate with BKT_UPDATE_GOLDEN=1 after an intended format change
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let input = fs::read_to_string(dir.join("build-info.json")).unwrap();
+        let info: BuildInfo = serde_json::from_str(&input).unwrap();
+        let html = render_to_html(&info);
+
+        let golden = dir.join("build-info.html");
+        if std::env::var_os("BKT_UPDATE_GOLDEN").is_some() {
+            fs::write(&golden, &html).unwrap();
+        }
+        assert_eq!(html, fs::read_to_string(&golden).unwrap());
+    }
+
+    #[test]
+    fn test_render_html_escapes_build_info_strings() {
+        assert_eq!(
+            escape_html(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+
+        let mut info = BuildInfo::new(
+            BuildMetadata {
+                commit: "<b>commit</b>".to_string(),
+                timestamp: Utc::now(),
+                previous_commit: None,
+            },
+            ManifestDiffs::default(),
+        );
+        info.manifests.system_packages = Some(DiffResult {
+            added: vec!["<img src=x onerror=alert(1)>".to_string()],
+            removed: vec![],
+            changed: vec![],
+        });
+        let html = render_to_html(&info);
+        assert!(!html.contains("<img"), "{html}");
+        assert!(!html.contains("<b>"), "{html}");
+        assert!(
+            html.contains("&lt;img src=x onerror=alert(1)&gt;"),
+            "{html}"
+        );
+    }
 }
@@ -2,13 +2,23 @@
 //!
 //! The `bkt capture` command composes multiple capture plans into one and executes them.
 //! This is the "system → manifest" direction of bidirectional sync.
+//!
+//! Items listed in `~/.config/bootc/capture-ignore.json` are never captured;
+//! `--interactive` asks about each item and can add to that list.
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use clap::Args;
+use is_terminal::IsTerminal;
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
 
+use crate::manifest::CaptureIgnore;
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
-use crate::plan::{CompositePlan, ExecuteContext, Plan, PlanContext, Plannable};
+use crate::plan::{
+    CompositePlan, DynPlan, ExecuteContext, ExecutionReport, Operation, Plan, PlanContext,
+    PlanSummary, Plannable, Verb, print_report, print_summary,
+};
 
 use super::appimage::{AppImageCaptureCommand, AppImageCapturePlan};
 use super::distrobox::{DistroboxCaptureCommand, DistroboxCapturePlan};
@@ -61,6 +71,35 @@ pub struct CaptureArgs {
     /// Apply the plan immediately
     #[arg(long)]
     pub apply: bool,
+
+    /// Ask about each item (add, skip, or always ignore), then capture the
+    /// ones added
+    #[arg(long, short = 'i')]
+    pub interactive: bool,
+}
+
+/// A capture plan whose items can be dropped before it runs.
+///
+/// Items are named by the target of the operation the plan describes them
+/// with (e.g. `flatpak:org.gnome.Boxes`), which is also what
+/// `capture-ignore.json` lists.
+pub trait CapturePlan: DynPlan {
+    /// Keep only the items whose target `keep` accepts.
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool);
+}
+
+impl Plan for Box<dyn CapturePlan> {
+    fn describe(&self) -> PlanSummary {
+        <dyn CapturePlan as DynPlan>::describe_dyn(&**self)
+    }
+
+    fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
+        <dyn CapturePlan as DynPlan>::execute_dyn(self, ctx)
+    }
+
+    fn is_empty(&self) -> bool {
+        <dyn CapturePlan as DynPlan>::is_empty_dyn(&**self)
+    }
 }
 
 /// Command to capture system state to manifests.
@@ -93,48 +132,69 @@ impl CaptureCommand {
     }
 }
 
-impl Plannable for CaptureCommand {
-    type Plan = CompositePlan;
-
-    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
-        let mut composite = CompositePlan::new("Capture");
+impl CaptureCommand {
+    /// Plan each included subsystem, leaving out the items `ignore` lists.
+    fn subsystem_plans(
+        &self,
+        ctx: &PlanContext,
+        ignore: &CaptureIgnore,
+    ) -> Result<Vec<(CaptureSubsystem, Box<dyn CapturePlan>)>> {
+        let mut plans: Vec<(CaptureSubsystem, Box<dyn CapturePlan>)> = Vec::new();
 
         // Extension capture
         if self.should_include(CaptureSubsystem::Extension) {
             let extension_plan: ExtensionCapturePlan = ExtensionCaptureCommand.plan(ctx)?;
-            composite.add(extension_plan);
+            plans.push((CaptureSubsystem::Extension, Box::new(extension_plan)));
         }
 
         // Distrobox capture
         if self.should_include(CaptureSubsystem::Distrobox) {
             let distrobox_plan: DistroboxCapturePlan = DistroboxCaptureCommand.plan(ctx)?;
-            composite.add(distrobox_plan);
+            plans.push((CaptureSubsystem::Distrobox, Box::new(distrobox_plan)));
         }
 
         // Flatpak capture
         if self.should_include(CaptureSubsystem::Flatpak) {
             let flatpak_plan: FlatpakCapturePlan = FlatpakCaptureCommand.plan(ctx)?;
-            composite.add(flatpak_plan);
+            plans.push((CaptureSubsystem::Flatpak, Box::new(flatpak_plan)));
         }
 
         // System capture (rpm-ostree layered packages)
         if self.should_include(CaptureSubsystem::System) {
-            let system_plan: SystemCapturePlan = SystemCaptureCommand.plan(ctx)?;
-            composite.add(system_plan);
+            let system_plan: SystemCapturePlan = SystemCaptureCommand::default().plan(ctx)?;
+            plans.push((CaptureSubsystem::System, Box::new(system_plan)));
         }
 
         // AppImage capture (via GearLever)
         if self.should_include(CaptureSubsystem::AppImage) {
             let appimage_plan: AppImageCapturePlan = AppImageCaptureCommand.plan(ctx)?;
-            composite.add(appimage_plan);
+            plans.push((CaptureSubsystem::AppImage, Box::new(appimage_plan)));
         }
 
         // Homebrew capture
         if self.should_include(CaptureSubsystem::Homebrew) {
             let homebrew_plan: HomebrewCapturePlan = HomebrewCaptureCommand.plan(ctx)?;
-            composite.add(homebrew_plan);
+            plans.push((CaptureSubsystem::Homebrew, Box::new(homebrew_plan)));
+        }
+
+        for (subsystem, plan) in &mut plans {
+            let name = subsystem.to_string();
+            plan.retain_targets(&|target| !ignore.is_ignored(&name, target));
         }
 
+        Ok(plans)
+    }
+}
+
+impl Plannable for CaptureCommand {
+    type Plan = CompositePlan;
+
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
+        let ignore = CaptureIgnore::load_user()?;
+        let mut composite = CompositePlan::new("Capture");
+        for (subsystem, plan) in self.subsystem_plans(ctx, &ignore)? {
+            composite.add_for(subsystem.to_string(), plan);
+        }
         Ok(composite)
     }
 }
@@ -146,18 +206,22 @@ pub fn run(args: CaptureArgs, exec_plan: &ExecutionPlan) -> Result<()> {
     let cwd = std::env::current_dir()?;
     let plan_ctx = PlanContext::new(cwd, exec_plan.clone());
 
+    if args.interactive {
+        return run_interactive(&cmd, &plan_ctx, exec_plan);
+    }
+
     let plan = cmd.plan(&plan_ctx)?;
 
-    if plan.is_empty() {
+    if plan.is_empty() && !exec_plan.json_output() {
         Output::success("Nothing to capture. All system state is already in manifests.");
         return Ok(());
     }
 
     // Always show the plan
-    print!("{}", plan.describe());
+    print_summary(&plan.describe(), exec_plan, exec_plan.dry_run || !apply)?;
 
     if exec_plan.dry_run || !apply {
-        if !apply {
+        if !apply && !exec_plan.json_output() {
             Output::hint("Use --apply to execute this plan.");
         }
         return Ok(());
@@ -166,11 +230,175 @@ pub fn run(args: CaptureArgs, exec_plan: &ExecutionPlan) -> Result<()> {
     // Execute the plan
     let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
     let report = plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report, exec_plan)?;
 
     Ok(())
 }
 
+/// Walk through each subsystem's items, then capture the ones added.
+fn run_interactive(
+    cmd: &CaptureCommand,
+    ctx: &PlanContext,
+    exec_plan: &ExecutionPlan,
+) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("--interactive needs a terminal; use --apply to capture everything");
+    }
+
+    let mut ignore = CaptureIgnore::load_user()?;
+    let mut composite = CompositePlan::new("Capture");
+    let mut newly_ignored = 0;
+    let mut input = std::io::stdin().lock();
+
+    for (subsystem, mut plan) in cmd.subsystem_plans(ctx, &ignore)? {
+        let name = subsystem.to_string();
+        let summary = plan.describe();
+        let ops: Vec<Operation> = summary
+            .operations
+            .into_iter()
+            .filter(|op| op.verb != Verb::Skip)
+            .collect();
+        if ops.is_empty() {
+            continue;
+        }
+
+        Output::header(format!("{} ({} to capture)", name, ops.len()));
+        for warning in &summary.warnings {
+            Output::warning(warning.to_string());
+        }
+        let triage = triage(&ops, &mut input)?;
+
+        for target in &triage.ignored {
+            newly_ignored += usize::from(ignore.ignore(&name, target.clone()));
+        }
+        plan.retain_targets(&|target| triage.added.contains(target));
+        composite.add_for(name, plan);
+
+        if triage.quit {
+            break;
+        }
+    }
+
+    if newly_ignored > 0 {
+        if exec_plan.dry_run {
+            Output::dry_run(format!(
+                "Would add {} item(s) to {}",
+                newly_ignored,
+                CaptureIgnore::FILE_NAME
+            ));
+        } else {
+            ignore.save_user()?;
+            Output::info(format!(
+                "Added {} item(s) to {}",
+                newly_ignored,
+                CaptureIgnore::user_path()?.display()
+            ));
+        }
+    }
+
+    if composite.is_empty() {
+        Output::info("Nothing selected to capture.");
+        return Ok(());
+    }
+
+    print_summary(&composite.describe(), exec_plan, exec_plan.dry_run)?;
+    if exec_plan.dry_run {
+        return Ok(());
+    }
+
+    let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
+    let report = composite.execute(&mut exec_ctx)?;
+    print_report(&report, exec_plan)?;
+
+    Ok(())
+}
+
+/// An answer to the per-item prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Answer {
+    /// Capture this item.
+    Add,
+    /// Leave it out this time.
+    Skip,
+    /// Leave it out and add it to the ignore list.
+    Ignore,
+    /// Capture this and every remaining item of the subsystem.
+    AddRest,
+    /// Leave out everything not yet answered.
+    Quit,
+}
+
+impl Answer {
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => Some(Answer::Add),
+            "" | "n" | "no" => Some(Answer::Skip),
+            "i" | "ignore" => Some(Answer::Ignore),
+            "a" | "all" => Some(Answer::AddRest),
+            "q" | "quit" => Some(Answer::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// What was decided for one subsystem's items.
+#[derive(Debug, Default)]
+struct Triage {
+    /// Targets to capture.
+    added: BTreeSet<String>,
+    /// Targets to always ignore.
+    ignored: Vec<String>,
+    /// Whether the user quit.
+    quit: bool,
+}
+
+/// Ask about each operation, reading answers line by line from `input`.
+/// End of input counts as quitting.
+fn triage(ops: &[Operation], input: &mut impl BufRead) -> Result<Triage> {
+    let mut triage = Triage::default();
+    let mut add_rest = false;
+
+    for op in ops {
+        if add_rest {
+            triage.added.insert(op.target.clone());
+            continue;
+        }
+
+        let answer = loop {
+            print!("  {}  [y/N/i/a/q] ", op);
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                break Answer::Quit;
+            }
+            match Answer::parse(&line) {
+                Some(answer) => break answer,
+                None => {
+                    Output::hint("y = add, n = skip, i = always ignore, a = add the rest, q = quit")
+                }
+            }
+        };
+
+        match answer {
+            Answer::Add => {
+                triage.added.insert(op.target.clone());
+            }
+            Answer::Skip => {}
+            Answer::Ignore => triage.ignored.push(op.target.clone()),
+            Answer::AddRest => {
+                triage.added.insert(op.target.clone());
+                add_rest = true;
+            }
+            Answer::Quit => {
+                triage.quit = true;
+                break;
+            }
+        }
+    }
+
+    Ok(triage)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,10 +467,56 @@ mod tests {
             only: Some(vec![CaptureSubsystem::Extension]),
             exclude: Some(vec![CaptureSubsystem::Flatpak]),
             apply: false,
+            interactive: false,
         };
 
         let cmd = CaptureCommand::from_args(&args);
         assert_eq!(cmd.include, Some(vec![CaptureSubsystem::Extension]));
         assert_eq!(cmd.exclude, vec![CaptureSubsystem::Flatpak]);
     }
+
+    fn ops(targets: &[&str]) -> Vec<Operation> {
+        targets
+            .iter()
+            .map(|t| Operation::new(Verb::Capture, *t))
+            .collect()
+    }
+
+    #[test]
+    fn test_triage_answers() {
+        let ops = ops(&[
+            "flatpak:org.gnome.Boxes",
+            "flatpak:org.example.Experiment",
+            "flatpak:org.example.Once",
+            "flatpak:org.mozilla.firefox",
+            "flatpak:org.gnome.Calculator",
+        ]);
+        // An unknown answer is asked again
+        let mut input = std::io::Cursor::new("y\nwhat\ni\n\na\n");
+
+        let answered = triage(&ops, &mut input).unwrap();
+        assert_eq!(
+            answered.added.into_iter().collect::<Vec<_>>(),
+            [
+                "flatpak:org.gnome.Boxes",
+                "flatpak:org.gnome.Calculator",
+                "flatpak:org.mozilla.firefox"
+            ]
+        );
+        assert_eq!(answered.ignored, ["flatpak:org.example.Experiment"]);
+        assert!(!answered.quit);
+    }
+
+    #[test]
+    fn test_triage_quit_and_end_of_input() {
+        let ops = ops(&["package:htop", "package:tmux", "package:git"]);
+
+        let quit = triage(&ops, &mut std::io::Cursor::new("y\nq\ny\n")).unwrap();
+        assert_eq!(quit.added.len(), 1);
+        assert!(quit.quit);
+
+        let eof = triage(&ops, &mut std::io::Cursor::new("")).unwrap();
+        assert!(eof.added.is_empty());
+        assert!(eof.quit);
+    }
 }
@@ -1,14 +1,21 @@
 //! Shell completion generation.
 //!
-//! Generate completion scripts for various shells.
+//! Generate completion scripts for various shells. The bash, zsh, and fish
+//! scripts are augmented to complete manifest entries (flatpak IDs, system
+//! packages, extension UUIDs) by calling `bkt __complete <source>`.
+//!
+//! `__complete` is handled in `main` before clap parses anything: it runs on
+//! every <TAB>, and clap_complete would otherwise offer it (hidden or not) as
+//! a subcommand.
 
 use anyhow::Result;
 use clap::{CommandFactory, Parser, ValueEnum};
 use clap_complete::Generator;
 use clap_complete_nushell::Nushell;
-use std::io;
+use std::io::{self, Write};
 
 use crate::cli::Cli;
+use crate::manifest::{FlatpakAppsManifest, GnomeExtensionsManifest, SystemPackagesManifest};
 
 /// Supported shell types for completion generation.
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -30,33 +37,269 @@ pub struct CompletionsArgs {
     pub shell: Shell,
 }
 
-/// Generate completions for the given shell and write to stdout.
-fn print_completions<G: Generator>(generator: G, cmd: &mut clap::Command) {
-    clap_complete::generate(
-        generator,
-        cmd,
-        cmd.get_name().to_string(),
-        &mut io::stdout(),
+/// Name of the completion helper pseudo-subcommand.
+pub const HELPER: &str = "__complete";
+
+/// Manifest entries `bkt __complete` can list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionSource {
+    /// Flatpak app IDs
+    FlatpakIds,
+    /// System (DNF) packages
+    DnfPackages,
+    /// GNOME extension UUIDs
+    ExtensionUuids,
+}
+
+/// A positional argument completed from a manifest.
+struct DynamicArg {
+    /// Top-level command and its aliases
+    command: &'static [&'static str],
+    /// Subcommands whose positional arguments are completed
+    subcommands: &'static [&'static str],
+    source: CompletionSource,
+}
+
+const DYNAMIC_ARGS: &[DynamicArg] = &[
+    DynamicArg {
+        command: &["flatpak", "fp"],
+        subcommands: &["remove"],
+        source: CompletionSource::FlatpakIds,
+    },
+    DynamicArg {
+        command: &["system", "sys"],
+        subcommands: &["remove"],
+        source: CompletionSource::DnfPackages,
+    },
+    DynamicArg {
+        command: &["extension", "ext"],
+        subcommands: &["remove", "enable", "disable"],
+        source: CompletionSource::ExtensionUuids,
+    },
+];
+
+/// Global options that take a value, so their value isn't a command word.
+const VALUE_OPTIONS: &str = "--context|--format";
+
+impl CompletionSource {
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Candidates from the merged manifests. Reads local files only.
+    fn candidates(self) -> Result<Vec<String>> {
+        let mut candidates: Vec<String> = match self {
+            CompletionSource::FlatpakIds => FlatpakAppsManifest::load_effective()?
+                .apps
+                .into_iter()
+                .map(|app| app.id)
+                .collect(),
+            CompletionSource::DnfPackages => SystemPackagesManifest::load_repo()?.packages,
+            CompletionSource::ExtensionUuids => GnomeExtensionsManifest::load_effective()?
+                .extensions
+                .iter()
+                .map(|ext| ext.id().to_string())
+                .collect(),
+        };
+        candidates.sort();
+        candidates.dedup();
+        Ok(candidates)
+    }
+}
+
+/// `case` arms mapping "command subcommand" to a completion source.
+fn case_arms(indent: &str) -> String {
+    let mut arms = String::new();
+    for arg in DYNAMIC_ARGS {
+        let patterns: Vec<String> = arg
+            .command
+            .iter()
+            .flat_map(|cmd| {
+                arg.subcommands
+                    .iter()
+                    .map(move |sub| format!("\"{cmd} {sub}\""))
+            })
+            .collect();
+        arms.push_str(&format!(
+            "{indent}{}) kind={} ;;\n",
+            patterns.join("|"),
+            arg.source.name()
+        ));
+    }
+    arms
+}
+
+/// Rename clap's `_bkt` and put a dispatcher in front of it that completes
+/// manifest entries and otherwise falls through.
+fn augment_bash(script: &str) -> String {
+    let mut script = script.replacen("_bkt() {", "_bkt_static() {", 1);
+    script.push_str(&format!(
+        r#"
+_bkt() {{
+    local i word kind="" words=()
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        word="${{COMP_WORDS[i]}}"
+        case "$word" in
+            {VALUE_OPTIONS}) ((i++)) ;;
+            -*) ;;
+            *) words+=("$word") ;;
+        esac
+    done
+    case "${{words[0]}} ${{words[1]}}" in
+{arms}    esac
+    if [[ -n "$kind" && "${{COMP_WORDS[COMP_CWORD]}}" != -* ]]; then
+        COMPREPLY=( $(compgen -W "$(bkt __complete "$kind" 2>/dev/null)" -- "${{COMP_WORDS[COMP_CWORD]}}") )
+        return 0
+    fi
+    _bkt_static "$@"
+}}
+"#,
+        arms = case_arms("        ")
+    ));
+    script
+}
+
+/// Same as [`augment_bash`]; the dispatcher must be defined before the
+/// trailing `compdef`/autoload call, so it goes ahead of it.
+fn augment_zsh(script: &str) -> String {
+    let script = script.replacen("_bkt() {", "_bkt_static() {", 1);
+    let dispatcher = format!(
+        r#"_bkt() {{
+    local i word kind=""
+    local -a cmd_words candidates
+    for ((i = 2; i < CURRENT; i++)); do
+        word=${{words[i]}}
+        case $word in
+            {VALUE_OPTIONS}) ((i++)) ;;
+            -*) ;;
+            *) cmd_words+=("$word") ;;
+        esac
+    done
+    case "${{cmd_words[1]}} ${{cmd_words[2]}}" in
+{arms}    esac
+    if [[ -n $kind && ${{words[CURRENT]}} != -* ]]; then
+        candidates=(${{(f)"$(bkt __complete $kind 2>/dev/null)"}})
+        compadd -a candidates
+        return
+    fi
+    _bkt_static "$@"
+}}
+
+"#,
+        arms = case_arms("        ")
     );
+    match script.rfind("if [ \"$funcstack[1]\"") {
+        Some(at) => format!("{}{}{}", &script[..at], dispatcher, &script[at..]),
+        None => script + "\n" + &dispatcher,
+    }
+}
+
+/// Fish completions are additive, so just append conditions for the
+/// manifest-backed arguments.
+fn augment_fish(script: &str) -> String {
+    let mut script = script.to_string();
+    script.push('\n');
+    for arg in DYNAMIC_ARGS {
+        script.push_str(&format!(
+            "complete -c bkt -n \"__fish_seen_subcommand_from {}; and __fish_seen_subcommand_from {}\" -f -a \"(bkt __complete {} 2>/dev/null)\"\n",
+            arg.command.join(" "),
+            arg.subcommands.join(" "),
+            arg.source.name()
+        ));
+    }
+    script
+}
+
+/// Generate completions for the given shell.
+fn generate<G: Generator>(generator: G, cmd: &mut clap::Command) -> String {
+    let mut buf = Vec::new();
+    clap_complete::generate(generator, cmd, cmd.get_name().to_string(), &mut buf);
+    String::from_utf8_lossy(&buf).into_owned()
 }
 
 pub fn run(args: CompletionsArgs) -> Result<()> {
     let mut cmd = Cli::command();
 
-    match args.shell {
-        Shell::Bash => {
-            print_completions(clap_complete::Shell::Bash, &mut cmd);
-        }
-        Shell::Zsh => {
-            print_completions(clap_complete::Shell::Zsh, &mut cmd);
-        }
-        Shell::Fish => {
-            print_completions(clap_complete::Shell::Fish, &mut cmd);
-        }
-        Shell::Nushell => {
-            print_completions(Nushell, &mut cmd);
+    let script = match args.shell {
+        Shell::Bash => augment_bash(&generate(clap_complete::Shell::Bash, &mut cmd)),
+        Shell::Zsh => augment_zsh(&generate(clap_complete::Shell::Zsh, &mut cmd)),
+        Shell::Fish => augment_fish(&generate(clap_complete::Shell::Fish, &mut cmd)),
+        Shell::Nushell => generate(Nushell, &mut cmd),
+    };
+    io::stdout().write_all(script.as_bytes())?;
+
+    Ok(())
+}
+
+/// `bkt __complete <source>`: print candidates one per line.
+///
+/// `args` are the arguments after `__complete`. Called from completion
+/// scripts, so it never fails: an unknown source or a missing repo or
+/// manifest just yields no candidates.
+pub fn complete(args: &[String]) {
+    let candidates = args
+        .first()
+        .and_then(|source| CompletionSource::from_str(source, false).ok())
+        .and_then(|source| source.candidates().ok())
+        .unwrap_or_default();
+
+    let mut stdout = io::stdout().lock();
+    for candidate in candidates {
+        if writeln!(stdout, "{}", candidate).is_err() {
+            break;
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_names_match_helper_arguments() {
+        assert_eq!(CompletionSource::FlatpakIds.name(), "flatpak-ids");
+        assert_eq!(
+            CompletionSource::from_str("extension-uuids", false),
+            Ok(CompletionSource::ExtensionUuids)
+        );
+    }
 
-    Ok(())
+    #[test]
+    fn test_case_arms_cover_aliases() {
+        let arms = case_arms("");
+        assert!(arms.contains("\"flatpak remove\"|\"fp remove\") kind=flatpak-ids ;;"));
+        assert!(arms.contains("\"sys remove\") kind=dnf-packages ;;"));
+        assert!(arms.contains("\"ext disable\") kind=extension-uuids ;;"));
+    }
+
+    #[test]
+    fn test_augment_bash_wraps_generated_function() {
+        let generated = "_bkt() {\n    :\n}\n\ncomplete -F _bkt -o bashdefault -o default bkt\n";
+        let script = augment_bash(generated);
+        assert!(script.starts_with("_bkt_static() {"));
+        assert!(script.contains("complete -F _bkt -o bashdefault"));
+        assert!(script.contains("bkt __complete \"$kind\""));
+        assert!(script.trim_end().ends_with("_bkt_static \"$@\"\n}"));
+    }
+
+    #[test]
+    fn test_augment_zsh_defines_dispatcher_before_registration() {
+        let generated = "#compdef bkt\n\n_bkt() {\n    :\n}\n\nif [ \"$funcstack[1]\" = \"_bkt\" ]; then\n    _bkt \"$@\"\nelse\n    compdef _bkt bkt\nfi\n";
+        let script = augment_zsh(generated);
+        let dispatcher = script.find("\n_bkt() {").unwrap();
+        let registration = script.find("if [ \"$funcstack[1]\"").unwrap();
+        assert!(script.contains("_bkt_static() {"));
+        assert!(dispatcher < registration);
+        assert!(script.contains("compdef _bkt bkt"));
+    }
+
+    #[test]
+    fn test_augment_fish_appends_conditions() {
+        let script = augment_fish("complete -c bkt -f\n");
+        assert!(script.contains(
+            "complete -c bkt -n \"__fish_seen_subcommand_from extension ext; and __fish_seen_subcommand_from remove enable disable\" -f -a \"(bkt __complete extension-uuids 2>/dev/null)\""
+        ));
+    }
 }
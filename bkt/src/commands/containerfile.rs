@@ -9,16 +9,17 @@
 //! - Execution phase: Apply updates to the Containerfile
 
 use crate::containerfile::{
-    ContainerfileEditor, ContainerfileGeneratorInput, Section, generate_copr_repos,
-    generate_full_containerfile, generate_kernel_arguments, generate_system_packages,
-    generate_systemd_units,
+    ContainerfileEditor, ContainerfileGeneratorInput, ContainerfilePart, SPLIT_DIR, Section,
+    generate_containerfile_parts, generate_copr_repos, generate_full_containerfile,
+    generate_kernel_arguments, generate_system_packages, generate_systemd_units,
+    is_placeholder_content,
 };
 use crate::manifest::image_config::ImageConfigManifest;
 use crate::manifest::system_config::SystemConfigManifest;
 use crate::manifest::upstream::ManifestRepo as UpstreamManifestRepo;
 use crate::manifest::{
-    ExternalReposManifest, ShimsManifest, SystemPackagesManifest, UpstreamManifest,
-    VendorArtifactsManifest,
+    ExternalRepoHashes, ExternalReposManifest, GSettingsManifest, ShimsManifest,
+    SystemPackagesManifest, UpstreamManifest, VendorArtifactsManifest,
 };
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
@@ -26,9 +27,14 @@ use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, PlanWarning,
     Plannable, Verb,
 };
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Parallel repo checks for `bump-cache`, as rpmcheck defaults to.
+pub(crate) const BUMP_CACHE_CONCURRENCY: usize = 4;
+pub(crate) const BUMP_CACHE_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Args)]
 pub struct ContainerfileArgs {
@@ -43,7 +49,26 @@ pub enum ContainerfileAction {
     /// Check for drift between manifests and Containerfile (dry-run)
     Check,
     /// Generate the full Containerfile from manifests
-    Generate,
+    Generate {
+        /// Cache dnf downloads across builds with BuildKit cache mounts
+        /// (for local rebuilds; CI output should not use this)
+        #[arg(long)]
+        cache_mounts: bool,
+        /// Also write per-concern part files to Containerfile.d/ (kept up
+        /// to date by later generates once the directory exists)
+        #[arg(long)]
+        split: bool,
+        /// Generate only `Containerfile.<variant>` for a variant in
+        /// image-config.json
+        #[arg(long, conflicts_with = "split")]
+        variant: Option<String>,
+        /// Also generate `Containerfile.<variant>` for every variant
+        #[arg(long, conflicts_with = "variant")]
+        all_variants: bool,
+    },
+    /// Re-check external repos and record their package hashes as the
+    /// dl-* stages' cache-busting ARG defaults, then regenerate
+    BumpCache,
 }
 
 // ============================================================================
@@ -60,8 +85,7 @@ pub struct SectionUpdate {
     pub section: Section,
     /// The new content to write.
     pub new_content: Vec<String>,
-    /// The old content (retained for future diff display).
-    #[allow(dead_code)]
+    /// The old content, or `None` if the section will be created.
     pub old_content: Option<Vec<String>>,
     /// Whether this represents a drift from current state.
     pub is_drift: bool,
@@ -128,8 +152,14 @@ impl Plannable for ContainerfileSyncCommand {
         check_section(
             &editor,
             Section::SystemPackages,
-            generate_system_packages(&manifest.packages, has_external_rpms),
-            true,
+            generate_system_packages(
+                &manifest.packages,
+                &manifest.groups,
+                &manifest.pins,
+                &manifest.arches,
+                has_external_rpms,
+                false,
+            ),
             &mut section_updates,
             &mut warnings,
         );
@@ -138,7 +168,6 @@ impl Plannable for ContainerfileSyncCommand {
             &editor,
             Section::KernelArguments,
             generate_kernel_arguments(&system_config),
-            true,
             &mut section_updates,
             &mut warnings,
         );
@@ -147,7 +176,6 @@ impl Plannable for ContainerfileSyncCommand {
             &editor,
             Section::SystemdUnits,
             generate_systemd_units(&system_config),
-            true,
             &mut section_updates,
             &mut warnings,
         );
@@ -161,8 +189,7 @@ impl Plannable for ContainerfileSyncCommand {
         check_section(
             &editor,
             Section::CoprRepos,
-            generate_copr_repos(&repo_names),
-            true,
+            generate_copr_repos(&repo_names, false),
             &mut section_updates,
             &mut warnings,
         );
@@ -176,11 +203,13 @@ impl Plannable for ContainerfileSyncCommand {
 }
 
 /// Helper to check a section and add updates/warnings as needed.
+///
+/// Missing sections are planned for creation once their manifest has
+/// something to build; until then they are only reported.
 fn check_section(
     editor: &ContainerfileEditor,
     section: Section,
     new_content: Vec<String>,
-    warn_if_missing: bool,
     updates: &mut Vec<SectionUpdate>,
     warnings: &mut Vec<SectionWarning>,
 ) {
@@ -197,7 +226,14 @@ fn check_section(
             old_content,
             is_drift,
         });
-    } else if warn_if_missing {
+    } else if !is_placeholder_content(&new_content) {
+        updates.push(SectionUpdate {
+            section,
+            new_content,
+            old_content: None,
+            is_drift: true,
+        });
+    } else {
         warnings.push(SectionWarning {
             section,
             message: format!(
@@ -221,7 +257,12 @@ impl Plan for ContainerfileSyncPlan {
 
         // Add operations for each section
         for update in &self.section_updates {
-            if update.is_drift {
+            if update.old_content.is_none() {
+                summary.add_operation(Operation::new(
+                    Verb::Create,
+                    format!("section:{}", update.section.marker_name()),
+                ));
+            } else if update.is_drift {
                 summary.add_operation(Operation::new(
                     Verb::Update,
                     format!("section:{}", update.section.marker_name()),
@@ -271,10 +312,11 @@ impl Plan for ContainerfileSyncPlan {
         // Apply all updates
         for update in &self.section_updates {
             if update.is_drift {
-                editor.update_section(update.section, update.new_content.clone());
+                let created = editor.upsert_section(update.section, update.new_content.clone());
+                let verb = if created { Verb::Create } else { Verb::Update };
                 report.record_success_and_notify(
                     ctx,
-                    Verb::Update,
+                    verb,
                     format!("section:{}", update.section.marker_name()),
                 );
                 any_updates = true;
@@ -361,16 +403,37 @@ pub fn run(args: ContainerfileArgs, plan: &ExecutionPlan) -> Result<()> {
         }
         ContainerfileAction::Check => {
             let input = load_generator_input()?;
-            let generated = generate_full_containerfile(&input);
+            let parts = generate_containerfile_parts(&input);
+            let generated: String = parts.iter().map(|part| part.content.as_str()).collect();
 
             let path = Path::new("Containerfile");
             let current = std::fs::read_to_string(path).context("Failed to read Containerfile")?;
 
-            if generated == current {
+            let mut stale_parts = if uses_split_layout(Path::new(".")) {
+                stale_split_parts(Path::new("."), &parts)?
+            } else {
+                Vec::new()
+            };
+            stale_parts.extend(stale_variant_files(Path::new("."), &input)?);
+
+            if generated == current && stale_parts.is_empty() {
                 Output::success("Containerfile is in sync with manifests.");
                 return Ok(());
             }
 
+            if generated == current {
+                Output::error("Generated files have drifted from manifests.");
+                for part in &stale_parts {
+                    Output::list_item(part.display().to_string());
+                }
+                if input.image_config.variants.is_empty() {
+                    Output::info("Run `bkt containerfile generate` to regenerate.");
+                } else {
+                    Output::info("Run `bkt containerfile generate --all-variants` to regenerate.");
+                }
+                std::process::exit(1);
+            }
+
             Output::error("Containerfile has drifted from manifests.");
             Output::info("Run `bkt containerfile generate` to regenerate.");
 
@@ -388,17 +451,253 @@ pub fn run(args: ContainerfileArgs, plan: &ExecutionPlan) -> Result<()> {
             ));
             std::process::exit(1);
         }
-        ContainerfileAction::Generate => {
-            let input = load_generator_input()?;
-            let generated = generate_full_containerfile(&input);
+        ContainerfileAction::Generate {
+            cache_mounts,
+            split,
+            variant,
+            all_variants,
+        } => {
+            let mut input = load_generator_input()?;
+            input.cache_mounts = cache_mounts;
+            let root = Path::new(".");
+
+            if let Some(name) = variant {
+                let path = write_variant_containerfile(root, &input, &name)?;
+                Output::success(format!("{} generated from manifests", path.display()));
+                return Ok(());
+            }
 
-            let path = Path::new("Containerfile");
-            std::fs::write(path, &generated).context("Failed to write Containerfile")?;
+            let split = split || uses_split_layout(root);
+            write_containerfile(root, &input, split)?;
+
+            if split {
+                Output::success(format!(
+                    "Containerfile and {} generated from manifests",
+                    SPLIT_DIR
+                ));
+            } else {
+                Output::success("Containerfile generated from manifests");
+            }
 
-            Output::success("Containerfile generated from manifests");
+            if all_variants {
+                for name in input.image_config.variants.keys() {
+                    let path = write_variant_containerfile(root, &input, name)?;
+                    Output::success(format!("{} generated from manifests", path.display()));
+                }
+            }
             Ok(())
         }
+        ContainerfileAction::BumpCache => bump_cache(plan),
+    }
+}
+
+/// Re-hash every external repo's tracked packages, record changed hashes in
+/// external-repo-hashes.json, and regenerate the Containerfile with them.
+fn bump_cache(plan: &ExecutionPlan) -> Result<()> {
+    let repo_path = crate::repo::find_repo_path()?;
+    let mut input = load_generator_input()?;
+    if input.external_repos.repos.is_empty() {
+        Output::info("No external repos to check.");
+        return Ok(());
+    }
+
+    let manifest = input.external_repos.to_rpmcheck();
+
+    let spinner = Output::spinner(format!(
+        "Checking {} external repos...",
+        manifest.repos.len()
+    ));
+    let results = rpmcheck::repodata::repo_hashes(
+        &manifest,
+        None,
+        BUMP_CACHE_CONCURRENCY,
+        BUMP_CACHE_TIMEOUT,
+    )?;
+    spinner.finish_clear();
+
+    let hashes_path = repo_path.join(ExternalRepoHashes::PROJECT_PATH);
+    let current = ExternalRepoHashes::load(&hashes_path)?;
+    let mut updated = ExternalRepoHashes::default();
+    let mut failed = Vec::new();
+
+    for (name, result) in results {
+        let previous = current.repos.get(&name);
+        match result {
+            Ok(hash) => {
+                if previous != Some(&hash) {
+                    Output::list_item(format!(
+                        "{}: {} -> {}",
+                        name,
+                        previous.map_or("0", |h| short_hash(h)),
+                        short_hash(&hash)
+                    ));
+                }
+                updated.repos.insert(name, hash);
+            }
+            Err(e) => {
+                Output::warning(format!("{}: {:#}", name, e));
+                // Keep what was recorded rather than busting the cache
+                if let Some(previous) = previous {
+                    updated.repos.insert(name.clone(), previous.clone());
+                }
+                failed.push(name);
+            }
+        }
+    }
+
+    if updated == current {
+        Output::success("External repo hashes are up to date.");
+    } else if plan.dry_run {
+        Output::dry_run(format!(
+            "Would update {} and regenerate Containerfile",
+            ExternalRepoHashes::PROJECT_PATH
+        ));
+    } else {
+        updated.save(&hashes_path)?;
+        input.cache_epochs = updated.repos;
+        let root = Path::new(".");
+        write_containerfile(root, &input, uses_split_layout(root))?;
+        Output::success(format!(
+            "Updated {} and regenerated Containerfile",
+            ExternalRepoHashes::PROJECT_PATH
+        ));
+    }
+
+    if !failed.is_empty() {
+        bail!("Could not check: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// First 12 characters of a hash, for display.
+fn short_hash(hash: &str) -> &str {
+    hash.get(..12).unwrap_or(hash)
+}
+
+// ============================================================================
+// Split Layout
+// ============================================================================
+
+/// Whether the repo at `root` keeps a split Containerfile in `Containerfile.d/`.
+pub fn uses_split_layout(root: &Path) -> bool {
+    root.join(SPLIT_DIR).is_dir()
+}
+
+/// Write the generated Containerfile under `root`, and its parts to
+/// `Containerfile.d/` when `split`.
+///
+/// The top-level Containerfile is always the parts concatenated in order,
+/// so builds and section edits keep working on one file; the parts are what
+/// reviewers read. Returns the paths written or removed, relative to `root`.
+pub fn write_containerfile(
+    root: &Path,
+    input: &ContainerfileGeneratorInput,
+    split: bool,
+) -> Result<Vec<PathBuf>> {
+    let parts = generate_containerfile_parts(input);
+    let full: String = parts.iter().map(|part| part.content.as_str()).collect();
+    std::fs::write(root.join("Containerfile"), full).context("Failed to write Containerfile")?;
+
+    let mut written = vec![PathBuf::from("Containerfile")];
+    if split {
+        written.extend(write_split_parts(root, &parts)?);
+    }
+    Ok(written)
+}
+
+/// Write `Containerfile.<name>` for image variant `name` under `root`.
+/// Returns the path written, relative to `root`.
+pub fn write_variant_containerfile(
+    root: &Path,
+    input: &ContainerfileGeneratorInput,
+    name: &str,
+) -> Result<PathBuf> {
+    let content = generate_full_containerfile(&input.for_variant(name)?);
+    let path = PathBuf::from(format!("Containerfile.{}", name));
+    std::fs::write(root.join(&path), content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Variant Containerfiles under `root` that exist but don't match manifests.
+/// Variants that were never generated aren't checked.
+fn stale_variant_files(root: &Path, input: &ContainerfileGeneratorInput) -> Result<Vec<PathBuf>> {
+    let mut stale = Vec::new();
+    for name in input.image_config.variants.keys() {
+        let path = PathBuf::from(format!("Containerfile.{}", name));
+        let Ok(current) = std::fs::read_to_string(root.join(&path)) else {
+            continue;
+        };
+        if current != generate_full_containerfile(&input.for_variant(name)?) {
+            stale.push(path);
+        }
+    }
+    Ok(stale)
+}
+
+/// Regenerate `Containerfile.d/` from manifests if the repo at `root` uses
+/// the split layout. Returns the paths written or removed, relative to `root`.
+pub fn refresh_split_parts(root: &Path) -> Result<Vec<PathBuf>> {
+    if !uses_split_layout(root) {
+        return Ok(Vec::new());
+    }
+    let input = load_generator_input()?;
+    write_split_parts(root, &generate_containerfile_parts(&input))
+}
+
+/// Write `parts` to `Containerfile.d/`, removing part files no longer generated.
+fn write_split_parts(root: &Path, parts: &[ContainerfilePart]) -> Result<Vec<PathBuf>> {
+    let dir = root.join(SPLIT_DIR);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut written = Vec::new();
+    for name in existing_part_names(&dir)? {
+        if !parts.iter().any(|part| part.name == name) {
+            std::fs::remove_file(dir.join(&name))
+                .with_context(|| format!("Failed to remove {}/{}", SPLIT_DIR, name))?;
+            written.push(Path::new(SPLIT_DIR).join(name));
+        }
+    }
+    for part in parts {
+        std::fs::write(dir.join(part.name), &part.content)
+            .with_context(|| format!("Failed to write {}/{}", SPLIT_DIR, part.name))?;
+        written.push(Path::new(SPLIT_DIR).join(part.name));
+    }
+    Ok(written)
+}
+
+/// Part files in `Containerfile.d/` under `root` that don't match `parts`.
+fn stale_split_parts(root: &Path, parts: &[ContainerfilePart]) -> Result<Vec<PathBuf>> {
+    let dir = root.join(SPLIT_DIR);
+    let mut stale = Vec::new();
+
+    for name in existing_part_names(&dir)? {
+        if !parts.iter().any(|part| part.name == name) {
+            stale.push(Path::new(SPLIT_DIR).join(name));
+        }
+    }
+    for part in parts {
+        let current = std::fs::read_to_string(dir.join(part.name)).unwrap_or_default();
+        if current != part.content {
+            stale.push(Path::new(SPLIT_DIR).join(part.name));
+        }
+    }
+    Ok(stale)
+}
+
+/// Names of the files in a `Containerfile.d/` directory.
+fn existing_part_names(dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
     }
+    names.sort();
+    Ok(names)
 }
 
 // ============================================================================
@@ -444,6 +743,7 @@ fn load_generator_input() -> Result<ContainerfileGeneratorInput> {
     let system_config = SystemConfigManifest::load()?;
     let image_config = ImageConfigManifest::load()?;
     let shims_manifest = ShimsManifest::load_repo()?;
+    let gsettings = GSettingsManifest::load_repo()?;
 
     let has_external_rpms = !external_repos.repos.is_empty();
 
@@ -469,11 +769,93 @@ fn load_generator_input() -> Result<ContainerfileGeneratorInput> {
         external_repos,
         upstreams,
         packages: system_packages.packages,
+        groups: system_packages.groups,
+        pins: system_packages.pins,
+        package_arches: system_packages.arches,
         copr_repos,
         system_config,
         image_config,
+        gsettings,
         shims: shims_manifest.shims,
         has_external_rpms,
         vendor_artifacts,
+        cache_mounts: false,
+        cache_epochs: ExternalRepoHashes::load(&repo_path.join(ExternalRepoHashes::PROJECT_PATH))?
+            .repos,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(name: &'static str, content: &str) -> ContainerfilePart {
+        ContainerfilePart {
+            name,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_split_parts_replaces_stale_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let parts_dir = dir.path().join(SPLIT_DIR);
+        std::fs::create_dir_all(&parts_dir).unwrap();
+        std::fs::write(parts_dir.join("40-vendor"), "FROM base AS vendor-old\n").unwrap();
+
+        let parts = vec![
+            part("10-base", "FROM scratch AS tools\n"),
+            part("90-image", "FROM base AS image\n"),
+        ];
+        let written = write_split_parts(dir.path(), &parts).unwrap();
+
+        assert_eq!(
+            written,
+            vec![
+                PathBuf::from("Containerfile.d/40-vendor"),
+                PathBuf::from("Containerfile.d/10-base"),
+                PathBuf::from("Containerfile.d/90-image"),
+            ]
+        );
+        assert_eq!(
+            existing_part_names(&parts_dir).unwrap(),
+            vec!["10-base", "90-image"]
+        );
+        assert!(stale_split_parts(dir.path(), &parts).unwrap().is_empty());
+
+        // Concatenating the part files in name order gives the Containerfile
+        let stitched: String = existing_part_names(&parts_dir)
+            .unwrap()
+            .iter()
+            .map(|name| std::fs::read_to_string(parts_dir.join(name)).unwrap())
+            .collect();
+        assert_eq!(stitched, "FROM scratch AS tools\nFROM base AS image\n");
+    }
+
+    #[test]
+    fn test_stale_split_parts_reports_changed_missing_and_extra() {
+        let dir = tempfile::tempdir().unwrap();
+        let parts_dir = dir.path().join(SPLIT_DIR);
+        std::fs::create_dir_all(&parts_dir).unwrap();
+        std::fs::write(parts_dir.join("10-base"), "FROM scratch AS tools\n").unwrap();
+        std::fs::write(parts_dir.join("30-install"), "old\n").unwrap();
+        std::fs::write(parts_dir.join("99-extra"), "extra\n").unwrap();
+
+        let parts = vec![
+            part("10-base", "FROM scratch AS tools\n"),
+            part("30-install", "new\n"),
+            part("90-image", "FROM base AS image\n"),
+        ];
+
+        assert_eq!(
+            stale_split_parts(dir.path(), &parts).unwrap(),
+            vec![
+                PathBuf::from("Containerfile.d/99-extra"),
+                PathBuf::from("Containerfile.d/30-install"),
+                PathBuf::from("Containerfile.d/90-image"),
+            ]
+        );
+        assert!(uses_split_layout(dir.path()));
+        assert!(!uses_split_layout(&dir.path().join("missing")));
+    }
+}
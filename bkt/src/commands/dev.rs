@@ -9,7 +9,8 @@
 //! - `install` — Install now (dnf) + record in manifest
 //! - `remove` — Remove now + update manifest
 //! - `list` — Show what's in the manifest
-//! - `sync` — Install all packages from manifest
+//! - `sync` — Install all packages and groups from manifest
+//! - `group add` / `group remove` — Install or remove a package group now
 //! - `capture` — Capture installed packages to manifest
 //!
 //! # Examples
@@ -29,17 +30,24 @@
 //! ```
 
 use crate::command_runner::{CommandOptions, CommandRunner};
-use crate::context::is_in_toolbox;
-use crate::manifest::{CoprRepo, ToolboxPackagesManifest};
-use crate::output::Output;
+use crate::commands::fetchbin::{load_fetchbin_manifest, source_label};
+use crate::context::{CommandDomain, is_in_toolbox};
+use crate::manifest::{
+    CoprRepo, HostBinarySource, ToolboxBinariesManifest, ToolboxBinary, ToolboxPackagesManifest,
+    group_id, group_spec,
+};
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
 use crate::validation::validate_dnf_package;
 use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::process::Command;
 
 #[derive(Debug, Args)]
@@ -74,8 +82,8 @@ pub enum DevAction {
     /// List managed packages from manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Sync: install all packages from manifest
     Sync,
@@ -90,6 +98,11 @@ pub enum DevAction {
         #[command(subcommand)]
         action: CoprAction,
     },
+    /// Manage package groups in toolbox
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
     /// Enter the development toolbox
     Enter {
         /// Toolbox name (default: bootc-dev)
@@ -124,6 +137,30 @@ pub enum CoprAction {
     List,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum GroupAction {
+    /// Install package groups in the toolbox
+    ///
+    /// Runs `dnf group install` and updates the manifest.
+    Add {
+        /// Group IDs (e.g., development-tools or @development-tools)
+        #[arg(required = true)]
+        groups: Vec<String>,
+        /// Only update manifest, skip dnf execution
+        #[arg(long)]
+        manifest_only: bool,
+    },
+    /// Remove package groups from the toolbox
+    Remove {
+        /// Group IDs
+        #[arg(required = true)]
+        groups: Vec<String>,
+        /// Only update manifest, skip dnf execution
+        #[arg(long)]
+        manifest_only: bool,
+    },
+}
+
 /// Run the dev command.
 pub fn run(args: DevArgs, plan: &ExecutionPlan) -> Result<()> {
     let runner = plan.runner();
@@ -142,6 +179,7 @@ pub fn run(args: DevArgs, plan: &ExecutionPlan) -> Result<()> {
         DevAction::Sync => handle_sync(plan),
         DevAction::Capture { apply } => handle_capture(apply, plan),
         DevAction::Copr { action } => handle_copr(action, plan, runner),
+        DevAction::Group { action } => handle_group(action, plan, runner),
         DevAction::Enter { name } => handle_enter(name, runner),
         DevAction::Status => handle_status(plan, runner),
         DevAction::Diff => handle_diff(plan, runner),
@@ -164,7 +202,7 @@ fn handle_install(
     }
 
     // Validate that packages exist in repositories
-    if !force {
+    if !force && !plan.skip_offline("package validation") {
         for pkg in &packages {
             validate_dnf_package(runner, pkg)?;
         }
@@ -304,14 +342,122 @@ fn remove_via_dnf(packages: &[String], runner: &dyn CommandRunner) -> Result<()>
     Ok(())
 }
 
+// =============================================================================
+// Group Commands
+// =============================================================================
+
+fn handle_group(
+    action: GroupAction,
+    plan: &ExecutionPlan,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    let (groups, manifest_only, adding) = match action {
+        GroupAction::Add {
+            groups,
+            manifest_only,
+        } => (groups, manifest_only, true),
+        GroupAction::Remove {
+            groups,
+            manifest_only,
+        } => (groups, manifest_only, false),
+    };
+    let specs: Vec<String> = groups.iter().map(|g| group_spec(g)).collect();
+
+    let mut manifest = ToolboxPackagesManifest::load_repo()?;
+
+    for spec in &specs {
+        let in_manifest = manifest.find_group(spec);
+        match (adding, in_manifest, plan.dry_run) {
+            (true, true, _) => Output::info(format!("Already in manifest: {}", spec)),
+            (false, false, _) => Output::warning(format!("Group not found in manifest: {}", spec)),
+            (true, false, false) => {
+                manifest.add_group(spec);
+                Output::success(format!("Added to toolbox manifest: {}", spec));
+            }
+            (false, true, false) => {
+                manifest.remove_group(spec);
+                Output::success(format!("Removed from manifest: {}", spec));
+            }
+            (true, false, true) => Output::dry_run(format!("Would add to manifest: {}", spec)),
+            (false, true, true) => Output::dry_run(format!("Would remove from manifest: {}", spec)),
+        }
+    }
+
+    if !plan.dry_run {
+        manifest.save_repo()?;
+    }
+
+    let subcommand = if adding { "install" } else { "remove" };
+    if !manifest_only && !plan.dry_run {
+        run_dnf_group(subcommand, &specs, runner)?;
+    } else if !manifest_only && plan.dry_run {
+        let ids: Vec<&str> = specs.iter().map(|s| group_id(s)).collect();
+        Output::dry_run(format!(
+            "Would run: dnf group {} -y {}",
+            subcommand,
+            ids.join(" ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run `dnf group install|remove` for the given groups.
+fn run_dnf_group(subcommand: &str, groups: &[String], runner: &dyn CommandRunner) -> Result<()> {
+    let mut args = vec!["group", subcommand, "-y"];
+    args.extend(groups.iter().map(|g| group_id(g)));
+
+    Output::running(format!("dnf {}", args.join(" ")));
+
+    let status = runner
+        .run_status("dnf", &args, &CommandOptions::default())
+        .context("Failed to run dnf")?;
+
+    if !status.success() {
+        bail!("dnf group {} failed", subcommand);
+    }
+
+    if subcommand == "install" {
+        Output::success("Groups installed in toolbox");
+    } else {
+        Output::success("Groups removed from toolbox");
+    }
+    Ok(())
+}
+
+/// IDs of the groups dnf reports as installed.
+fn installed_groups(runner: &dyn CommandRunner) -> HashSet<String> {
+    runner
+        .run_output(
+            "dnf",
+            &["group", "list", "--installed"],
+            &CommandOptions::default(),
+        )
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_installed_groups(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Parse `dnf group list --installed` (dnf5): an `ID Name Installed` table.
+fn parse_installed_groups(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("ID"))
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
 // =============================================================================
 // List Command
 // =============================================================================
 
-fn handle_list(format: String, runner: &dyn CommandRunner) -> Result<()> {
+fn handle_list(format: OutputFormat, runner: &dyn CommandRunner) -> Result<()> {
     let manifest = ToolboxPackagesManifest::load_repo()?;
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&manifest)?);
         return Ok(());
     }
@@ -384,13 +530,14 @@ fn handle_sync(plan: &ExecutionPlan) -> Result<()> {
 
     let sync_plan = DevSyncCommand.plan(&plan_ctx)?;
 
-    if sync_plan.is_empty() {
+    if sync_plan.is_empty() && !plan.json_output() {
         Output::success("All manifest packages are already installed.");
         return Ok(());
     }
 
     // Always show the plan
-    print!("{}", sync_plan.describe());
+    let summary = sync_plan.describe().with_subsystem("dev");
+    print_summary(&summary, plan, plan.dry_run)?;
 
     if plan.dry_run {
         return Ok(());
@@ -399,7 +546,7 @@ fn handle_sync(plan: &ExecutionPlan) -> Result<()> {
     // Execute the plan
     let mut exec_ctx = ExecuteContext::new(plan.clone());
     let report = sync_plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report.with_subsystem("dev"), plan)?;
 
     Ok(())
 }
@@ -413,16 +560,17 @@ fn handle_capture(apply: bool, plan: &ExecutionPlan) -> Result<()> {
 
     let capture_plan = DevCaptureCommand.plan(&plan_ctx)?;
 
-    if capture_plan.is_empty() {
+    if capture_plan.is_empty() && !plan.json_output() {
         Output::success("All installed packages are already in the manifest.");
         return Ok(());
     }
 
     // Always show the plan
-    print!("{}", capture_plan.describe());
+    let summary = capture_plan.describe().with_subsystem("dev");
+    print_summary(&summary, plan, plan.dry_run || !apply)?;
 
     if plan.dry_run || !apply {
-        if !apply {
+        if !apply && !plan.json_output() {
             Output::hint("Use --apply to execute this plan.");
         }
         return Ok(());
@@ -431,7 +579,7 @@ fn handle_capture(apply: bool, plan: &ExecutionPlan) -> Result<()> {
     // Execute the plan
     let mut exec_ctx = ExecuteContext::new(plan.clone());
     let report = capture_plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report.with_subsystem("dev"), plan)?;
 
     Ok(())
 }
@@ -809,9 +957,11 @@ pub struct DevSyncCommand;
 
 /// Plan for syncing toolbox packages.
 pub struct DevSyncPlan {
+    /// Package groups to install (as `@group`).
+    pub groups_to_install: Vec<String>,
     /// Packages to install.
     pub to_install: Vec<String>,
-    /// Packages already installed.
+    /// Packages and groups already installed.
     pub already_installed: usize,
 }
 
@@ -823,9 +973,22 @@ impl Plannable for DevSyncCommand {
 
         let manifest = ToolboxPackagesManifest::load_repo()?;
 
+        let mut groups_to_install = Vec::new();
         let mut to_install = Vec::new();
         let mut already_installed = 0;
 
+        if !manifest.groups.is_empty() {
+            let installed = installed_groups(runner);
+            for group in &manifest.groups {
+                if installed.contains(group_id(group)) {
+                    already_installed += 1;
+                } else {
+                    groups_to_install.push(group_spec(group));
+                }
+            }
+            groups_to_install.sort();
+        }
+
         for pkg in manifest.packages {
             if is_package_installed(&pkg, runner) {
                 already_installed += 1;
@@ -835,6 +998,7 @@ impl Plannable for DevSyncCommand {
         }
 
         Ok(DevSyncPlan {
+            groups_to_install,
             to_install,
             already_installed,
         })
@@ -845,10 +1009,14 @@ impl Plan for DevSyncPlan {
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!(
             "Dev Sync: {} to install, {} already installed (via dnf)",
-            self.to_install.len(),
+            self.groups_to_install.len() + self.to_install.len(),
             self.already_installed,
         ));
 
+        // Groups first: they usually pull in most of what's listed below
+        for group in &self.groups_to_install {
+            summary.add_operation(Operation::new(Verb::Install, format!("group:{}", group)));
+        }
         for pkg in &self.to_install {
             summary.add_operation(Operation::new(Verb::Install, format!("package:{}", pkg)));
         }
@@ -859,14 +1027,35 @@ impl Plan for DevSyncPlan {
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
 
-        if self.to_install.is_empty() {
+        if self.is_empty() {
             return Ok(report);
         }
 
         let runner = ctx.execution_plan().runner();
 
-        // Install all packages in one batch for efficiency
-        let result = install_via_dnf(&self.to_install, runner);
+        // Groups first, then all packages in one batch for efficiency
+        let group_result = if self.groups_to_install.is_empty() {
+            None
+        } else {
+            Some(run_dnf_group("install", &self.groups_to_install, runner))
+        };
+        let result = if self.to_install.is_empty() {
+            Ok(())
+        } else {
+            install_via_dnf(&self.to_install, runner)
+        };
+
+        if let Some(group_result) = group_result {
+            for group in &self.groups_to_install {
+                let target = format!("group:{}", group);
+                match &group_result {
+                    Ok(()) => report.record_success_and_notify(ctx, Verb::Install, target),
+                    Err(e) => {
+                        report.record_failure_and_notify(ctx, Verb::Install, target, e.to_string())
+                    }
+                }
+            }
+        }
 
         match result {
             Ok(()) => {
@@ -894,7 +1083,7 @@ impl Plan for DevSyncPlan {
     }
 
     fn is_empty(&self) -> bool {
-        self.to_install.is_empty()
+        self.groups_to_install.is_empty() && self.to_install.is_empty()
     }
 }
 
@@ -1016,6 +1205,397 @@ fn get_user_installed_packages(runner: &dyn CommandRunner) -> Vec<String> {
         .collect()
 }
 
+// ============================================================================
+// Plan-based Toolbox Binaries Sync/Capture
+// ============================================================================
+
+/// A crate reported by `cargo install --list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CargoInstall {
+    pub crate_name: String,
+    pub version: String,
+    /// Non-registry source (git URL or local path), if any
+    pub source: Option<String>,
+    pub bins: Vec<String>,
+}
+
+/// Parse the output of `cargo install --list`.
+///
+/// ```text
+/// cargo-nextest v0.9.72:
+///     cargo-nextest
+/// mytool v0.1.0 (/home/me/src/mytool):
+///     mytool
+/// ```
+pub fn parse_cargo_install_list(output: &str) -> Vec<CargoInstall> {
+    let mut installs: Vec<CargoInstall> = Vec::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            if let Some(install) = installs.last_mut() {
+                install.bins.push(line.trim().to_string());
+            }
+            continue;
+        }
+
+        let header = line.trim_end().trim_end_matches(':');
+        let (name_version, source) = match header.split_once(" (") {
+            Some((left, right)) => (left, Some(right.trim_end_matches(')').to_string())),
+            None => (header, None),
+        };
+        let Some((crate_name, version)) = name_version.split_once(' ') else {
+            continue;
+        };
+
+        installs.push(CargoInstall {
+            crate_name: crate_name.to_string(),
+            version: version.trim_start_matches('v').to_string(),
+            source,
+            bins: Vec::new(),
+        });
+    }
+
+    installs
+}
+
+/// Registry crates whose binaries are all still present in the cargo bin dir.
+///
+/// Crates installed from a git URL or local path can't be reinstalled by
+/// name, so they are left out.
+fn cargo_binaries_present(
+    installs: &[CargoInstall],
+    present: &HashSet<String>,
+) -> Vec<ToolboxBinary> {
+    installs
+        .iter()
+        .filter(|i| i.source.is_none())
+        .filter(|i| !i.bins.is_empty() && i.bins.iter().all(|b| present.contains(b)))
+        .map(|i| ToolboxBinary::cargo(&i.crate_name, Some(i.version.clone())))
+        .collect()
+}
+
+/// `$CARGO_HOME/bin`, defaulting to `~/.cargo/bin`.
+fn cargo_bin_dir() -> PathBuf {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cargo")))
+        .unwrap_or_else(|| PathBuf::from(".cargo"))
+        .join("bin")
+}
+
+fn list_cargo_bin_dir() -> HashSet<String> {
+    std::fs::read_dir(cargo_bin_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().to_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Cargo-installed binaries in the toolbox, by scanning the cargo bin dir
+/// and `cargo install --list`.
+pub fn installed_cargo_binaries(runner: &dyn CommandRunner) -> Vec<ToolboxBinary> {
+    let output = runner.run_output("cargo", &["install", "--list"], &CommandOptions::default());
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    let installs = parse_cargo_install_list(&String::from_utf8_lossy(&output.stdout));
+    cargo_binaries_present(&installs, &list_cargo_bin_dir())
+}
+
+/// Installed version of each manifest entry that is present in the toolbox.
+///
+/// Cargo entries come from `cargo install --list`; other sources are looked
+/// up in the fetchbin manifest.
+fn installed_toolbox_binaries(
+    manifest: &ToolboxBinariesManifest,
+    runner: &dyn CommandRunner,
+) -> HashMap<String, String> {
+    let mut installed: HashMap<String, String> = installed_cargo_binaries(runner)
+        .into_iter()
+        .map(|b| (b.name, b.version.unwrap_or_default()))
+        .collect();
+
+    let fetchbin_manifest = load_fetchbin_manifest().unwrap_or_default();
+    for entry in manifest
+        .binaries
+        .iter()
+        .filter(|b| b.crate_name().is_none())
+    {
+        if let Some(binary) = fetchbin_manifest.binaries.get(&entry.name) {
+            installed.insert(entry.name.clone(), binary.version().to_string());
+        }
+    }
+
+    installed
+}
+
+/// Drift keys for toolbox binaries: manifest names vs. installed names.
+pub fn toolbox_binaries_drift_keys(
+    manifest: &ToolboxBinariesManifest,
+    runner: &dyn CommandRunner,
+) -> (Vec<String>, Vec<String>) {
+    let expected = manifest.binaries.iter().map(|b| b.name.clone()).collect();
+    let actual = installed_toolbox_binaries(manifest, runner)
+        .into_keys()
+        .collect();
+    (expected, actual)
+}
+
+fn toolbox_version_matches(entry: &ToolboxBinary, installed: &str) -> bool {
+    match &entry.version {
+        None => true,
+        Some(expected) => expected.trim_start_matches('v') == installed.trim_start_matches('v'),
+    }
+}
+
+/// Install one toolbox binary: cargo crates via cargo-binstall, everything
+/// else via fetchbin.
+fn install_toolbox_binary(entry: &ToolboxBinary, runner: &dyn CommandRunner) -> Result<()> {
+    let (program, args) = toolbox_install_command(entry);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let status = runner
+        .run_status(program, &args, &CommandOptions::default())
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    if !status.success() {
+        bail!("{} {} failed", program, args.join(" "));
+    }
+    Ok(())
+}
+
+fn toolbox_install_command(entry: &ToolboxBinary) -> (&'static str, Vec<String>) {
+    let versioned = |spec: String| match &entry.version {
+        Some(version) => format!("{}@{}", spec, version),
+        None => spec,
+    };
+
+    match &entry.source {
+        HostBinarySource::Cargo { crate_name } => (
+            "cargo",
+            vec![
+                "binstall".to_string(),
+                "--no-confirm".to_string(),
+                versioned(crate_name.clone()),
+            ],
+        ),
+        source => {
+            let mut args = vec!["install".to_string(), versioned(source_label(source))];
+            if let HostBinarySource::Github {
+                asset_pattern: Some(pattern),
+                ..
+            }
+            | HostBinarySource::Gitlab {
+                asset_pattern: Some(pattern),
+                ..
+            } = source
+            {
+                args.push("--asset".to_string());
+                args.push(pattern.clone());
+            }
+            if let HostBinarySource::Github {
+                include_prerelease: true,
+                ..
+            } = source
+            {
+                args.push("--include-prerelease".to_string());
+            }
+            ("fetchbin", args)
+        }
+    }
+}
+
+/// Command to sync toolbox binaries from manifest.
+pub struct ToolboxBinariesSyncCommand;
+
+/// Plan for syncing toolbox binaries.
+pub struct ToolboxBinariesSyncPlan {
+    pub to_install: Vec<ToolboxBinary>,
+    pub to_update: Vec<ToolboxBinary>,
+    pub already_installed: usize,
+}
+
+impl Plannable for ToolboxBinariesSyncCommand {
+    type Plan = ToolboxBinariesSyncPlan;
+
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
+        ctx.execution_plan()
+            .validate_domain(CommandDomain::ToolboxBinaries)?;
+        let runner = ctx.execution_plan().runner();
+
+        let manifest = ToolboxBinariesManifest::load_repo()?;
+        let installed = installed_toolbox_binaries(&manifest, runner);
+
+        let mut to_install = Vec::new();
+        let mut to_update = Vec::new();
+        let mut already_installed = 0;
+
+        for entry in manifest.binaries {
+            match installed.get(&entry.name) {
+                None => to_install.push(entry),
+                Some(version) if toolbox_version_matches(&entry, version) => {
+                    already_installed += 1;
+                }
+                Some(_) => to_update.push(entry),
+            }
+        }
+
+        Ok(ToolboxBinariesSyncPlan {
+            to_install,
+            to_update,
+            already_installed,
+        })
+    }
+}
+
+impl Plan for ToolboxBinariesSyncPlan {
+    fn describe(&self) -> PlanSummary {
+        let mut summary = PlanSummary::new(format!(
+            "Toolbox Binaries Sync: {} to install, {} to update, {} already installed",
+            self.to_install.len(),
+            self.to_update.len(),
+            self.already_installed
+        ));
+
+        for entry in &self.to_install {
+            summary.add_operation(Operation::with_details(
+                Verb::Install,
+                format!("toolbox-bin:{}", entry.name),
+                source_label(&entry.source),
+            ));
+        }
+
+        for entry in &self.to_update {
+            summary.add_operation(Operation::with_details(
+                Verb::Update,
+                format!("toolbox-bin:{}", entry.name),
+                entry.version.clone().unwrap_or_default(),
+            ));
+        }
+
+        summary
+    }
+
+    fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
+        let mut report = ExecutionReport::new();
+
+        let work = self
+            .to_install
+            .into_iter()
+            .map(|e| (Verb::Install, e))
+            .chain(self.to_update.into_iter().map(|e| (Verb::Update, e)));
+
+        for (verb, entry) in work {
+            let result = {
+                let runner = ctx.execution_plan().runner();
+                install_toolbox_binary(&entry, runner)
+            };
+            let target = format!("toolbox-bin:{}", entry.name);
+
+            match result {
+                Ok(()) => report.record_success_and_notify(ctx, verb, target),
+                Err(e) => report.record_failure_and_notify(ctx, verb, target, e.to_string()),
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.to_install.is_empty() && self.to_update.is_empty()
+    }
+}
+
+/// Command to capture cargo-installed toolbox binaries not in manifest.
+pub struct ToolboxBinariesCaptureCommand;
+
+/// Plan for capturing toolbox binaries.
+pub struct ToolboxBinariesCapturePlan {
+    pub to_capture: Vec<ToolboxBinary>,
+    pub already_in_manifest: usize,
+}
+
+impl Plannable for ToolboxBinariesCaptureCommand {
+    type Plan = ToolboxBinariesCapturePlan;
+
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
+        let runner = ctx.execution_plan().runner();
+        let manifest = ToolboxBinariesManifest::load_repo()?;
+
+        let mut to_capture = Vec::new();
+        let mut already_in_manifest = 0;
+
+        for binary in installed_cargo_binaries(runner) {
+            if manifest.find(&binary.name).is_some() {
+                already_in_manifest += 1;
+            } else {
+                to_capture.push(binary);
+            }
+        }
+
+        to_capture.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(ToolboxBinariesCapturePlan {
+            to_capture,
+            already_in_manifest,
+        })
+    }
+}
+
+impl Plan for ToolboxBinariesCapturePlan {
+    fn describe(&self) -> PlanSummary {
+        let mut summary = PlanSummary::new(format!(
+            "Toolbox Binaries Capture: {} to add, {} already in manifest",
+            self.to_capture.len(),
+            self.already_in_manifest
+        ));
+
+        for entry in &self.to_capture {
+            summary.add_operation(Operation::with_details(
+                Verb::Capture,
+                format!("toolbox-bin:{}", entry.name),
+                entry.version.clone().unwrap_or_default(),
+            ));
+        }
+
+        summary
+    }
+
+    fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
+        let mut report = ExecutionReport::new();
+
+        if self.to_capture.is_empty() {
+            return Ok(report);
+        }
+
+        let mut manifest = ToolboxBinariesManifest::load_repo()?;
+
+        for entry in self.to_capture {
+            let target = format!("toolbox-bin:{}", entry.name);
+            manifest.upsert(entry);
+            report.record_success_and_notify(ctx, Verb::Capture, target);
+        }
+
+        manifest.save_repo()?;
+
+        Ok(report)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.to_capture.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1028,6 +1608,125 @@ mod tests {
         let _ = is_in_toolbox();
     }
 
+    #[test]
+    fn test_parse_cargo_install_list() {
+        let output = "\
+bat v0.24.0:
+    bat
+cargo-nextest v0.9.72:
+    cargo-nextest
+mytool v0.1.0 (/home/me/src/mytool):
+    mytool
+wasm-bindgen-cli v0.2.92:
+    wasm-bindgen
+    wasm2es6js
+";
+        let installs = parse_cargo_install_list(output);
+
+        assert_eq!(installs.len(), 4);
+        assert_eq!(installs[0].crate_name, "bat");
+        assert_eq!(installs[0].version, "0.24.0");
+        assert_eq!(installs[0].bins, vec!["bat"]);
+        assert_eq!(installs[2].source.as_deref(), Some("/home/me/src/mytool"));
+        assert_eq!(installs[3].bins, vec!["wasm-bindgen", "wasm2es6js"]);
+    }
+
+    #[test]
+    fn test_cargo_binaries_present_skips_missing_and_path_installs() {
+        let installs = parse_cargo_install_list(
+            "bat v0.24.0:\n    bat\n\
+             gone v1.0.0:\n    gone\n\
+             mytool v0.1.0 (/home/me/src/mytool):\n    mytool\n",
+        );
+        let present: HashSet<String> = ["bat", "mytool", "rustup"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            cargo_binaries_present(&installs, &present),
+            vec![ToolboxBinary::cargo("bat", Some("0.24.0".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_parse_installed_groups() {
+        let output = "\
+Updating and loading repositories:
+Repositories loaded.
+ID                   Name                 Installed
+c-development        C Development Tools        yes
+development-tools    Development Tools          yes
+";
+        let groups = parse_installed_groups(output);
+
+        assert_eq!(groups.len(), 2);
+        assert!(groups.contains("c-development"));
+        assert!(groups.contains("development-tools"));
+        assert!(parse_installed_groups("No groups to list.\n").is_empty());
+    }
+
+    #[test]
+    fn test_toolbox_install_command() {
+        let cargo = ToolboxBinary::cargo("cargo-nextest", Some("0.9.72".to_string()));
+        assert_eq!(
+            toolbox_install_command(&cargo),
+            (
+                "cargo",
+                vec![
+                    "binstall".to_string(),
+                    "--no-confirm".to_string(),
+                    "cargo-nextest@0.9.72".to_string()
+                ]
+            )
+        );
+
+        let github = ToolboxBinary {
+            name: "just".to_string(),
+            source: HostBinarySource::Github {
+                repo: "casey/just".to_string(),
+                asset_pattern: Some("*linux-musl*".to_string()),
+                include_prerelease: false,
+            },
+            version: None,
+        };
+        assert_eq!(
+            toolbox_install_command(&github),
+            (
+                "fetchbin",
+                vec![
+                    "install".to_string(),
+                    "github:casey/just".to_string(),
+                    "--asset".to_string(),
+                    "*linux-musl*".to_string()
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_toolbox_version_matches() {
+        let unpinned = ToolboxBinary::cargo("bat", None);
+        let pinned = ToolboxBinary::cargo("bat", Some("v0.24.0".to_string()));
+        assert!(toolbox_version_matches(&unpinned, "0.23.0"));
+        assert!(toolbox_version_matches(&pinned, "0.24.0"));
+        assert!(!toolbox_version_matches(&pinned, "0.23.0"));
+    }
+
+    #[test]
+    fn test_toolbox_binaries_sync_refuses_host_context() {
+        let plan = crate::pipeline::ExecutionPlanBuilder::new()
+            .context(crate::context::ExecutionContext::Host)
+            .build();
+        let ctx = PlanContext::new(PathBuf::from("."), plan);
+
+        let err = match ToolboxBinariesSyncCommand.plan(&ctx) {
+            Ok(_) => panic!("sync should be refused on the host"),
+            Err(err) => err,
+        };
+        assert!(err.to_string().contains("Invalid context"));
+    }
+
     #[test]
     fn test_is_package_installed() {
         // bash should be installed on any Linux system
@@ -0,0 +1,262 @@
+//! Diff command implementation.
+//!
+//! `bkt diff` compares the running system with the manifests as they were
+//! at a git ref: "repo vs reality" at any point in history. `bkt drift` does
+//! the same against the checkout, and `bkt build-info` compares two commits'
+//! manifests with each other.
+//!
+//! Each drift-capable subsystem's manifests are read out of git into a
+//! scratch directory and its usual drift check runs against that copy.
+//!
+//! ```bash
+//! bkt diff                          # against HEAD
+//! bkt diff --ref HEAD~20            # against an older commit
+//! bkt diff --ref 'main@{2 weeks ago}' --subsystem flatpak,system
+//! bkt diff --ref v1.4.0 --json
+//! ```
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::command_runner::CommandRunner;
+use crate::output::Output;
+use crate::pipeline::ExecutionPlan;
+use crate::repo::find_repo_path;
+use crate::repo::git::{get_file_at_commit, resolve_commit};
+use crate::subsystem::{DriftReport, Subsystem, SubsystemContext, SubsystemRegistry};
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Git ref whose manifests to compare against (commit, tag, branch, ...)
+    #[arg(long = "ref", value_name = "COMMIT-ISH", default_value = "HEAD")]
+    pub git_ref: String,
+
+    /// Only check these subsystems (comma-separated)
+    #[arg(long, short = 's', value_delimiter = ',')]
+    pub subsystem: Vec<String>,
+
+    /// Print the reports as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The full `--json` output.
+#[derive(Debug, Serialize)]
+struct DiffOutput {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    commit: String,
+    subsystems: Vec<SubsystemDiff>,
+}
+
+/// One subsystem's drift against the manifests at the ref.
+#[derive(Debug, Serialize)]
+struct SubsystemDiff {
+    id: &'static str,
+    name: &'static str,
+    /// Whether any of the subsystem's manifests existed at the ref
+    tracked: bool,
+    #[serde(flatten)]
+    report: Option<DriftReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn run(args: DiffArgs, plan: &ExecutionPlan) -> Result<()> {
+    let registry = SubsystemRegistry::builtin();
+    for id in &args.subsystem {
+        if !registry.is_valid_driftable(id) {
+            bail!(
+                "Unknown subsystem '{}'. Valid: {}",
+                id,
+                registry.driftable_ids().join(", ")
+            );
+        }
+    }
+    // Drift that isn't read from a repo manifest (skel) has nothing to
+    // compare at another ref
+    let subsystems: Vec<&dyn Subsystem> = registry
+        .driftable()
+        .into_iter()
+        .filter(|s| !s.manifest_files().is_empty())
+        .filter(|s| args.subsystem.is_empty() || args.subsystem.iter().any(|id| id == s.id()))
+        .collect();
+
+    let runner = plan.runner();
+    let repo_path = find_repo_path()?;
+    let commit = resolve_commit(&repo_path, &args.git_ref, runner)?;
+
+    let snapshot = std::env::temp_dir().join(format!("bkt-diff-{}", std::process::id()));
+    fs::create_dir_all(&snapshot).context("Failed to create temp directory")?;
+    // Clean up on scope exit via a guard
+    struct TmpGuard(PathBuf);
+    impl Drop for TmpGuard {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+    let _tmpguard = TmpGuard(snapshot.clone());
+    let ctx =
+        SubsystemContext::with_repo_root(repo_path.clone()).with_manifest_root(snapshot.clone());
+
+    let mut diffs = Vec::new();
+    for subsystem in subsystems {
+        let tracked = materialize_manifests(
+            &repo_path,
+            &commit,
+            subsystem.manifest_files(),
+            &snapshot,
+            runner,
+        )?;
+        let (report, error) = if !tracked {
+            (None, None)
+        } else {
+            match subsystem.drift(&ctx) {
+                Ok(report) => (report, None),
+                Err(e) => (None, Some(format!("{:#}", e))),
+            }
+        };
+        diffs.push(SubsystemDiff {
+            id: subsystem.id(),
+            name: subsystem.name(),
+            tracked,
+            report,
+            error,
+        });
+    }
+
+    if args.json {
+        let output = DiffOutput {
+            git_ref: args.git_ref,
+            commit,
+            subsystems: diffs,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        Output::header(format!(
+            "System vs manifests at {} ({})",
+            &commit[..12.min(commit.len())],
+            args.git_ref
+        ));
+        print_diffs(&diffs);
+    }
+
+    Ok(())
+}
+
+/// Write the manifests in `files` as of `commit` under `dest`, at the same
+/// relative paths. Returns whether any of them existed then.
+fn materialize_manifests(
+    repo_path: &Path,
+    commit: &str,
+    files: &[&str],
+    dest: &Path,
+    runner: &dyn CommandRunner,
+) -> Result<bool> {
+    let mut any = false;
+    for file in files {
+        let Some(content) = get_file_at_commit(repo_path, commit, file, runner)? else {
+            continue;
+        };
+        let path = dest.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        any = true;
+    }
+    Ok(any)
+}
+
+fn print_diffs(diffs: &[SubsystemDiff]) {
+    for diff in diffs {
+        match (&diff.report, &diff.error) {
+            (_, Some(error)) => {
+                Output::warning(format!("{}: unavailable ({})", diff.name, error));
+            }
+            _ if !diff.tracked => {
+                Output::info(format!("{}: not tracked then", diff.name));
+            }
+            (Some(report), None) if report.has_drift() => {
+                Output::subheader(format!(
+                    "{}: {} missing, {} extra",
+                    diff.name,
+                    report.missing.len(),
+                    report.extra.len()
+                ));
+                for item in &report.missing {
+                    println!("  {} {}", "-".red(), item);
+                }
+                for item in &report.extra {
+                    println!("  {} {}", "+".yellow(), item);
+                }
+            }
+            (Some(_), None) => Output::success(format!("{}: matches", diff.name)),
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_runner::RealCommandRunner;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn materializes_manifests_as_of_the_commit() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir_all(repo.path().join("manifests")).unwrap();
+        let shims = repo.path().join("manifests/host-shims.json");
+        fs::write(&shims, r#"{"shims": [{"name": "podman"}]}"#).unwrap();
+        git(repo.path(), &["init", "-q"]);
+        git(repo.path(), &["add", "-A"]);
+        git(repo.path(), &["commit", "-q", "-m", "shims"]);
+        fs::write(&shims, r#"{"shims": []}"#).unwrap();
+
+        let commit = resolve_commit(repo.path(), "HEAD", &RealCommandRunner).unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let files = ["manifests/host-shims.json", "manifests/homebrew.json"];
+
+        assert!(
+            materialize_manifests(
+                repo.path(),
+                &commit,
+                &files,
+                dest.path(),
+                &RealCommandRunner
+            )
+            .unwrap()
+        );
+        // The committed version, not the working tree's
+        let written = fs::read_to_string(dest.path().join(files[0])).unwrap();
+        assert!(written.contains("podman"));
+        assert!(!dest.path().join(files[1]).exists());
+
+        assert!(
+            !materialize_manifests(
+                repo.path(),
+                &commit,
+                &files[1..],
+                dest.path(),
+                &RealCommandRunner
+            )
+            .unwrap()
+        );
+    }
+}
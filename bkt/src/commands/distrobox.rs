@@ -2,17 +2,19 @@
 
 use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::command_runner::{CommandOptions, CommandRunner};
-use crate::context::{CommandDomain, run_command};
+use crate::commands::capture::CapturePlan;
+use crate::context::{CommandDomain, Environment, REAL_ENV, run_command};
 use crate::manifest::{DistroboxBins, DistroboxContainer, DistroboxManifest};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
 use crate::repo::find_repo_path;
 
@@ -51,30 +53,37 @@ pub fn run(args: DistroboxArgs, plan: &ExecutionPlan) -> Result<()> {
             let cmd = DistroboxSyncCommand;
             let plan = cmd.plan(&plan_ctx)?;
 
-            if plan.is_empty() {
+            let exec_plan = plan_ctx.execution_plan();
+            let json = exec_plan.json_output();
+            if plan.is_empty() && !json {
                 Output::success("Nothing to apply. Distrobox is in sync with manifest.");
                 return Ok(());
             }
 
-            let summary = plan.describe();
-            print!("{}", summary);
+            let summary = plan.describe().with_subsystem("distrobox");
+            print_summary(&summary, exec_plan, plan_ctx.is_dry_run())?;
 
             if plan_ctx.is_dry_run() {
-                Output::info("Run without --dry-run to apply these changes.");
+                if !json {
+                    Output::info("Run without --dry-run to apply these changes.");
+                }
                 return Ok(());
             }
 
-            Output::info("Applying distrobox changes...");
-            println!();
-
             let total_ops = summary.action_count();
-            let mut exec_ctx = ExecuteContext::new(plan_ctx.execution_plan().clone());
+            let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
             exec_ctx.set_total_ops(total_ops);
-            exec_ctx.set_progress_callback(super::apply::print_progress);
+            if !json {
+                Output::info("Applying distrobox changes...");
+                println!();
+                exec_ctx.set_progress_callback(super::apply::print_progress);
+            }
 
             let report = plan.execute(&mut exec_ctx)?;
-            println!();
-            print!("{}", report);
+            if !json {
+                println!();
+            }
+            print_report(&report.with_subsystem("distrobox"), exec_plan)?;
             Ok(())
         }
         DistroboxAction::Capture {
@@ -121,22 +130,25 @@ pub fn run(args: DistroboxArgs, plan: &ExecutionPlan) -> Result<()> {
             let cmd = DistroboxCaptureCommand;
             let plan = cmd.plan(&plan_ctx)?;
 
-            if plan.is_empty() {
+            let exec_plan = plan_ctx.execution_plan();
+            if plan.is_empty() && !exec_plan.json_output() {
                 Output::success("Nothing to capture. Distrobox manifest is in sync.");
                 return Ok(());
             }
 
-            let summary = plan.describe();
-            print!("{}", summary);
+            let summary = plan.describe().with_subsystem("distrobox");
+            print_summary(&summary, exec_plan, plan_ctx.is_dry_run())?;
 
             if plan_ctx.is_dry_run() {
-                Output::info("Run without --dry-run to capture these changes.");
+                if !exec_plan.json_output() {
+                    Output::info("Run without --dry-run to capture these changes.");
+                }
                 return Ok(());
             }
 
-            let mut exec_ctx = ExecuteContext::new(plan_ctx.execution_plan().clone());
+            let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
             let report = plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("distrobox"), exec_plan)?;
             Ok(())
         }
     }
@@ -162,6 +174,7 @@ struct ContainerApplyPlan {
     bins_also: Vec<String>,
     bins_exclude: Vec<String>,
     bins_to: String,
+    apps: Vec<String>,
 }
 
 impl Plannable for DistroboxSyncCommand {
@@ -200,6 +213,7 @@ impl Plannable for DistroboxSyncCommand {
                     .to
                     .clone()
                     .unwrap_or_else(|| "~/.local/bin".to_string()),
+                apps: container.exported_apps.clone(),
             });
         }
 
@@ -239,6 +253,13 @@ impl Plan for DistroboxSyncPlan {
                     format!("distrobox-export:{}", bin),
                 ));
             }
+
+            for app in &container.apps {
+                summary.add_operation(Operation::new(
+                    Verb::Create,
+                    format!("distrobox-export-app:{}", app),
+                ));
+            }
         }
 
         summary
@@ -311,6 +332,19 @@ impl Plan for DistroboxSyncPlan {
                     format!("distrobox-export:{}", bin),
                 );
             }
+
+            for app in &container.apps {
+                Output::info(format!(
+                    "Exporting app '{}' from '{}'.",
+                    app, container.name
+                ));
+                run_export_app(&container.name, app)?;
+                report.record_success_and_notify(
+                    ctx,
+                    Verb::Create,
+                    format!("distrobox-export-app:{}", app),
+                );
+            }
         }
 
         Ok(report)
@@ -334,20 +368,7 @@ fn run_assemble(container: &str, ini_path: &Path) -> Result<()> {
     ];
     let output = run_command("distrobox", &args)?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = stderr.trim();
-        let stdout = stdout.trim();
-        let mut detail = String::new();
-        if !stderr.is_empty() {
-            detail.push_str(stderr);
-        }
-        if !stdout.is_empty() {
-            if !detail.is_empty() {
-                detail.push('\n');
-            }
-            detail.push_str(stdout);
-        }
+        let detail = failure_detail(&output);
         if detail.is_empty() {
             bail!("distrobox assemble failed for {}", container);
         }
@@ -400,20 +421,7 @@ fn run_export(container: &str, bin: &str, export_path: &str) -> Result<()> {
     ];
     let output = run_command("distrobox", &args)?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = stderr.trim();
-        let stdout = stdout.trim();
-        let mut detail = String::new();
-        if !stderr.is_empty() {
-            detail.push_str(stderr);
-        }
-        if !stdout.is_empty() {
-            if !detail.is_empty() {
-                detail.push('\n');
-            }
-            detail.push_str(stdout);
-        }
+        let detail = failure_detail(&output);
         if detail.is_empty() {
             bail!("distrobox-export failed for {}", bin);
         }
@@ -422,24 +430,44 @@ fn run_export(container: &str, bin: &str, export_path: &str) -> Result<()> {
     Ok(())
 }
 
+fn run_export_app(container: &str, app: &str) -> Result<()> {
+    // Already exported (by assemble or an earlier apply)
+    if let Some(apps_dir) = applications_dir()
+        && scan_exported_apps(container, &apps_dir)
+            .iter()
+            .any(|exported| exported == app)
+    {
+        return Ok(());
+    }
+
+    let args = vec!["enter", container, "--", "distrobox-export", "--app", app];
+    let output = run_command("distrobox", &args)?;
+    if !output.status.success() {
+        let detail = failure_detail(&output);
+        if detail.is_empty() {
+            bail!("distrobox-export failed for app {}", app);
+        }
+        bail!("distrobox-export failed for app {}: {}", app, detail);
+    }
+    Ok(())
+}
+
+/// A failed command's stderr and stdout, for error messages.
+fn failure_detail(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    [stderr.trim(), stdout.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn list_bins_in_dir(container: &str, dir: &str, original_dir: &str) -> Result<Vec<String>> {
     let check_args = vec!["enter", container, "--", "test", "-d", dir];
     let output = run_command("distrobox", &check_args)?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = stderr.trim();
-        let stdout = stdout.trim();
-        let mut detail = String::new();
-        if !stderr.is_empty() {
-            detail.push_str(stderr);
-        }
-        if !stdout.is_empty() {
-            if !detail.is_empty() {
-                detail.push('\n');
-            }
-            detail.push_str(stdout);
-        }
+        let detail = failure_detail(&output);
         bail!(
             "Distrobox container '{}' is missing bins.from directory '{}': {}",
             container,
@@ -471,20 +499,7 @@ fn list_bins_in_dir(container: &str, dir: &str, original_dir: &str) -> Result<Ve
     ];
     let output = run_command("distrobox", &find_args)?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = stderr.trim();
-        let stdout = stdout.trim();
-        let mut detail = String::new();
-        if !stderr.is_empty() {
-            detail.push_str(stderr);
-        }
-        if !stdout.is_empty() {
-            if !detail.is_empty() {
-                detail.push('\n');
-            }
-            detail.push_str(stdout);
-        }
+        let detail = failure_detail(&output);
         bail!(
             "Failed to list bins for distrobox container '{}' in '{}': {}",
             container,
@@ -676,6 +691,10 @@ pub struct DistroboxCaptureCommand;
 
 pub struct DistroboxCapturePlan {
     manifest: DistroboxManifest,
+    /// The manifest as it was, for containers left out of the capture.
+    existing: BTreeMap<String, DistroboxContainer>,
+    /// Containers left out; they keep their existing entry, if any.
+    left_out: BTreeSet<String>,
     manifest_dir: PathBuf,
 }
 
@@ -690,6 +709,8 @@ impl Plannable for DistroboxCaptureCommand {
                     schema: Some("../schemas/distrobox.schema.json".to_string()),
                     containers: BTreeMap::new(),
                 },
+                existing: BTreeMap::new(),
+                left_out: BTreeSet::new(),
                 manifest_dir: ctx.manifest_dir().clone(),
             });
         }
@@ -712,6 +733,7 @@ impl Plannable for DistroboxCaptureCommand {
                     container.bins.to = existing.bins.to.clone();
                 }
             }
+            merge_exports(container, &scan_exports(name, container));
         }
 
         for (name, container) in &manifest.containers {
@@ -719,16 +741,28 @@ impl Plannable for DistroboxCaptureCommand {
         }
         Ok(DistroboxCapturePlan {
             manifest,
+            existing: existing_manifest.containers,
+            left_out: BTreeSet::new(),
             manifest_dir: ctx.manifest_dir().clone(),
         })
     }
 }
 
+impl DistroboxCapturePlan {
+    /// Names of the containers being captured.
+    fn captured(&self) -> impl Iterator<Item = &String> {
+        self.manifest
+            .containers
+            .keys()
+            .filter(|name| !self.left_out.contains(*name))
+    }
+}
+
 impl Plan for DistroboxCapturePlan {
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new("Distrobox Capture");
 
-        for name in self.manifest.containers.keys() {
+        for name in self.captured() {
             summary.add_operation(Operation::new(Verb::Capture, format!("distrobox:{}", name)));
         }
 
@@ -739,7 +773,7 @@ impl Plan for DistroboxCapturePlan {
         let mut report = ExecutionReport::new();
         self.manifest.save_to_dir(&self.manifest_dir)?;
 
-        for name in self.manifest.containers.keys() {
+        for name in self.captured() {
             report.record_success(Verb::Capture, format!("distrobox:{}", name));
         }
 
@@ -747,10 +781,228 @@ impl Plan for DistroboxCapturePlan {
     }
 
     fn is_empty(&self) -> bool {
-        self.manifest.containers.is_empty()
+        self.captured().next().is_none()
+    }
+}
+
+impl CapturePlan for DistroboxCapturePlan {
+    /// The whole manifest is rewritten, so a container left out keeps the
+    /// entry it already had rather than disappearing from it.
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        let dropped: Vec<String> = self
+            .captured()
+            .filter(|name| !keep(&format!("distrobox:{}", name)))
+            .cloned()
+            .collect();
+        for name in dropped {
+            match self.existing.get(&name) {
+                Some(existing) => {
+                    self.manifest
+                        .containers
+                        .insert(name.clone(), existing.clone());
+                }
+                None => {
+                    self.manifest.containers.remove(&name);
+                }
+            }
+            self.left_out.insert(name);
+        }
     }
 }
 
+// ============================================================================
+// Exports (apps and bins a container has put on the host)
+// ============================================================================
+
+/// What a container has exported onto the host.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContainerExports {
+    /// App names, as passed to `distrobox-export --app`
+    pub apps: Vec<String>,
+    /// Binaries, as their `~`-collapsed path inside the container
+    pub bins: Vec<String>,
+}
+
+/// Where exported desktop entries go (`~/.local/share/applications`).
+fn applications_dir() -> Option<PathBuf> {
+    REAL_ENV.data_dir().map(|dir| dir.join("applications"))
+}
+
+/// Scan the host for `container`'s exported apps and the bins exported
+/// into its `bins.to` directory.
+pub fn scan_exports(name: &str, container: &DistroboxContainer) -> ContainerExports {
+    let bins_dir = expand_home(container.bins.to.as_deref().unwrap_or("~/.local/bin"));
+    ContainerExports {
+        apps: applications_dir()
+            .map(|dir| scan_exported_apps(name, &dir))
+            .unwrap_or_default(),
+        bins: scan_exported_bins(name, Path::new(&bins_dir)),
+    }
+}
+
+/// Apps exported from `container` into `apps_dir`.
+///
+/// `distrobox-export --app` writes `<container>-<app>.desktop` entries that
+/// launch through `distrobox-enter -n <container>`.
+fn scan_exported_apps(container: &str, apps_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(apps_dir) else {
+        return Vec::new();
+    };
+    let mut apps: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let contents = fs::read_to_string(entry.path()).ok()?;
+            exported_app_name(container, &file_name, &contents)
+        })
+        .collect();
+    apps.sort();
+    apps.dedup();
+    apps
+}
+
+fn exported_app_name(container: &str, file_name: &str, contents: &str) -> Option<String> {
+    let app = file_name
+        .strip_suffix(".desktop")?
+        .strip_prefix(&format!("{}-", container))?;
+    let launches_container = contents.lines().any(|line| {
+        line.starts_with("Exec=") && line.contains("distrobox") && {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            tokens
+                .windows(2)
+                .any(|pair| matches!(pair[0], "-n" | "--name") && pair[1] == container)
+        }
+    });
+    (!app.is_empty() && launches_container).then(|| app.to_string())
+}
+
+/// Binaries exported from `container` as shims in `bins_dir`.
+fn scan_exported_bins(container: &str, bins_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(bins_dir) else {
+        return Vec::new();
+    };
+    let mut bins: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let contents = fs::read_to_string(entry.path()).ok()?;
+            let (name, target) = parse_bin_shim(&contents)?;
+            (name == container).then(|| collapse_home(&target))
+        })
+        .collect();
+    bins.sort();
+    bins
+}
+
+/// The container and in-container binary a `distrobox-export --bin` shim
+/// runs.
+fn parse_bin_shim(contents: &str) -> Option<(String, String)> {
+    if !contents.contains("distrobox_binary") {
+        return None;
+    }
+    let name = contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# name:"))?
+        .trim()
+        .to_string();
+    let exec = contents
+        .lines()
+        .find(|line| line.contains("distrobox-enter"))?;
+    let tokens = shlex::split(exec.trim())?;
+    let target = tokens
+        .iter()
+        .skip_while(|token| *token != "--")
+        .nth(1)?
+        .clone();
+    Some((name, target))
+}
+
+/// Whether `bin` comes from one of the container's `bins.from` directories
+/// (and so is exported without being listed).
+fn bin_from_dir(container: &DistroboxContainer, bin: &str) -> bool {
+    let bin = expand_home(bin);
+    container
+        .bins
+        .from
+        .iter()
+        .any(|dir| Path::new(&bin).starts_with(expand_home(dir)))
+}
+
+/// Add exports found on the host that the container doesn't declare yet.
+fn merge_exports(container: &mut DistroboxContainer, exports: &ContainerExports) {
+    for app in &exports.apps {
+        if !container.exported_apps.contains(app) {
+            container.exported_apps.push(app.clone());
+        }
+    }
+
+    let declared: Vec<String> = container
+        .bins
+        .also
+        .iter()
+        .map(|bin| collapse_home(&expand_home(bin)))
+        .collect();
+    for bin in &exports.bins {
+        let name = Path::new(bin)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(bin);
+        if declared.contains(bin)
+            || bin_from_dir(container, bin)
+            || container.bins.is_excluded(name)
+        {
+            continue;
+        }
+        container.bins.also.push(bin.clone());
+    }
+}
+
+/// Drift keys for declared and exported apps and bins.
+///
+/// Apps compare as `<container>:app:<name>` and listed bins as
+/// `<container>:bin:<path>`; bins exported from a `bins.from` directory
+/// aren't listed anywhere, so they're left out of both sides.
+pub fn distrobox_export_drift_keys(
+    manifest: &DistroboxManifest,
+    scan: impl Fn(&str, &DistroboxContainer) -> ContainerExports,
+) -> (Vec<String>, Vec<String>) {
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+
+    for (name, container) in &manifest.containers {
+        expected.extend(
+            container
+                .exported_apps
+                .iter()
+                .map(|app| format!("{}:app:{}", name, app)),
+        );
+        expected.extend(
+            container
+                .bins
+                .also
+                .iter()
+                .map(|bin| format!("{}:bin:{}", name, collapse_home(&expand_home(bin)))),
+        );
+
+        let exports = scan(name, container);
+        actual.extend(
+            exports
+                .apps
+                .iter()
+                .map(|app| format!("{}:app:{}", name, app)),
+        );
+        actual.extend(
+            exports
+                .bins
+                .iter()
+                .filter(|bin| !bin_from_dir(container, bin))
+                .map(|bin| format!("{}:bin:{}", name, bin)),
+        );
+    }
+
+    (expected, actual)
+}
+
 // ============================================================================
 // INI rendering/parsing helpers
 // ============================================================================
@@ -1113,3 +1365,120 @@ fn collapse_home(value: &str) -> String {
     }
     value.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIM: &str = r#"#!/bin/sh
+# distrobox_binary
+# name: dev
+if [ -z "${CONTAINER_ID}" ]; then
+	exec "/usr/bin/distrobox-enter"  -n dev  --  '/usr/local/bin/nu'  "$@"
+elif [ -n "${CONTAINER_ID}" ] && [ "${CONTAINER_ID}" != "dev" ]; then
+	exec distrobox-host-exec '/home/me/.local/bin/nu' "$@"
+else
+	exec '/usr/local/bin/nu' "$@"
+fi
+"#;
+
+    const DESKTOP: &str = "[Desktop Entry]\nName=Code (on dev)\n\
+        Exec=/usr/bin/distrobox-enter -n dev -- code %F\nType=Application\n";
+
+    fn container(json: &str) -> DistroboxContainer {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parses_bin_shims() {
+        assert_eq!(
+            parse_bin_shim(SHIM),
+            Some(("dev".to_string(), "/usr/local/bin/nu".to_string()))
+        );
+        assert_eq!(parse_bin_shim("#!/bin/sh\nexec nu \"$@\"\n"), None);
+    }
+
+    #[test]
+    fn recognizes_apps_exported_from_the_container() {
+        assert_eq!(
+            exported_app_name("dev", "dev-code.desktop", DESKTOP),
+            Some("code".to_string())
+        );
+        // Another box's export, or a desktop file that just shares the prefix
+        assert_eq!(
+            exported_app_name("dev", "dev-code.desktop", "Exec=code"),
+            None
+        );
+        assert_eq!(
+            exported_app_name("devbox", "dev-code.desktop", DESKTOP),
+            None
+        );
+    }
+
+    #[test]
+    fn scans_exports_from_host_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let apps = temp.path().join("applications");
+        let bins = temp.path().join("bin");
+        fs::create_dir_all(&apps).unwrap();
+        fs::create_dir_all(&bins).unwrap();
+        fs::write(apps.join("dev-code.desktop"), DESKTOP).unwrap();
+        fs::write(apps.join("org.gnome.Nautilus.desktop"), "Exec=nautilus").unwrap();
+        fs::write(bins.join("nu"), SHIM).unwrap();
+        fs::write(bins.join("other"), SHIM.replace("dev", "other")).unwrap();
+
+        assert_eq!(scan_exported_apps("dev", &apps), ["code"]);
+        assert_eq!(scan_exported_bins("dev", &bins), ["/usr/local/bin/nu"]);
+        assert!(scan_exported_bins("dev", &temp.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn merge_skips_declared_from_dir_and_excluded_bins() {
+        let mut declared = container(
+            r#"{ "image": "fedora", "exported_apps": ["code"],
+                 "bins": { "from": ["/opt/tools/bin"], "also": ["/usr/local/bin/nu"],
+                           "exclude": ["bkt"] } }"#,
+        );
+        let exports = ContainerExports {
+            apps: vec!["code".to_string(), "firefox".to_string()],
+            bins: vec![
+                "/opt/tools/bin/rg".to_string(),
+                "/usr/bin/bkt".to_string(),
+                "/usr/bin/htop".to_string(),
+                "/usr/local/bin/nu".to_string(),
+            ],
+        };
+
+        merge_exports(&mut declared, &exports);
+        assert_eq!(declared.exported_apps, ["code", "firefox"]);
+        assert_eq!(declared.bins.also, ["/usr/local/bin/nu", "/usr/bin/htop"]);
+        declared.validate("dev").unwrap();
+    }
+
+    #[test]
+    fn export_drift_compares_declared_and_exported() {
+        let manifest = DistroboxManifest {
+            schema: None,
+            containers: BTreeMap::from([(
+                "dev".to_string(),
+                container(
+                    r#"{ "image": "fedora", "exported_apps": ["code"],
+                         "bins": { "from": ["/opt/tools/bin"], "also": ["/usr/local/bin/nu"] } }"#,
+                ),
+            )]),
+        };
+        let (mut expected, mut actual) =
+            distrobox_export_drift_keys(&manifest, |_, _| ContainerExports {
+                apps: vec!["firefox".to_string()],
+                bins: vec![
+                    "/opt/tools/bin/rg".to_string(),
+                    "/usr/local/bin/nu".to_string(),
+                ],
+            });
+        expected.sort();
+        actual.sort();
+
+        assert_eq!(expected, ["dev:app:code", "dev:bin:/usr/local/bin/nu"]);
+        assert_eq!(actual, ["dev:app:firefox", "dev:bin:/usr/local/bin/nu"]);
+    }
+}
@@ -3,118 +3,279 @@
 //! Runs pre-flight checks and reports system readiness.
 
 use crate::command_runner::RealCommandRunner;
-use crate::daemon;
-use crate::manifest::DistroboxManifest;
-use crate::output::Output;
-use crate::pr::run_preflight_checks;
+use crate::commands::shim::ShimSyncCommand;
+use crate::daemon::{self, DaemonClient};
+use crate::manifest::{DistroboxManifest, ShimsManifest};
+use crate::output::{Output, OutputFormat};
+use crate::pipeline::ExecutionPlan;
+use crate::plan::{ExecuteContext, Plan, PlanContext, Plannable, print_report};
+use crate::pr::{PreflightResult, run_preflight_checks};
 use crate::repo::find_repo_path;
+use crate::subsystem::get_installed_shims;
 use anyhow::{Context, Result};
 use clap::Args;
+use serde::Serialize;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Args)]
 pub struct DoctorArgs {
-    /// Output format (table, json)
-    #[arg(short, long, default_value = "table")]
-    format: String,
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "table")]
+    format: OutputFormat,
 
     /// Attempt to automatically fix known issues
     #[arg(long)]
     fix: bool,
+
+    /// Run only the check with this ID and exit non-zero unless it passes
+    /// (e.g., daemon, repo-json, gh-auth)
+    #[arg(long, value_name = "ID")]
+    check: Option<String>,
+}
+
+/// IDs of the PR preflight checks, in the order `run_preflight_checks` reports them.
+const PREFLIGHT_CHECK_IDS: &[&str] = &[
+    "gh-cli",
+    "gh-auth",
+    "git",
+    "git-user-name",
+    "git-user-email",
+    "repo-json",
+];
+
+type CheckFn = fn() -> PreflightResult;
+
+/// Environment readiness checks (not specific to PR workflows), in report order.
+const ENVIRONMENT_CHECKS: &[(&str, CheckFn)] = &[
+    ("distrobox-path", check_distrobox_shims_path),
+    ("distrobox-wrappers", check_distrobox_wrappers),
+    ("cargo-exports", check_cargo_bin_exports),
+    ("cargo-resolution", || {
+        check_devtools_resolve_to_distrobox("cargo")
+    }),
+    ("node-resolution", || {
+        check_devtools_resolve_to_distrobox("node")
+    }),
+    ("pnpm-resolution", || {
+        check_devtools_resolve_to_distrobox("pnpm")
+    }),
+    ("daemon", check_daemon_status),
+    ("polkit", check_polkit_rules),
+    ("shims", check_shim_integrity),
+];
+
+/// Checks whose failures are reported as warnings rather than failures.
+///
+/// The daemon only speeds up container-to-host delegation, and without the
+/// polkit rule `bkt admin` still works but prompts for a password.
+const ADVISORY_CHECK_IDS: &[&str] = &["daemon", "polkit"];
+
+/// Where the image installs the polkit rule, and where the repo keeps it.
+const POLKIT_RULES_PATH: &str = "/etc/polkit-1/rules.d/50-bkt-admin.rules";
+const POLKIT_RULES_REPO_PATH: &str = "system/polkit-1/rules.d/50-bkt-admin.rules";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A single check in the doctor report.
+#[derive(Debug, Serialize)]
+pub struct DoctorCheck {
+    /// Stable identifier, usable with `--check`
+    pub id: String,
+    /// Human-readable check name
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// How to fix the problem, if the check did not pass
+    pub remediation: Option<String>,
+}
+
+impl DoctorCheck {
+    fn from_result(id: &str, result: PreflightResult) -> Self {
+        let status = if result.passed {
+            CheckStatus::Pass
+        } else if ADVISORY_CHECK_IDS.contains(&id) {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Fail
+        };
+
+        Self {
+            id: id.to_string(),
+            name: result.name,
+            status,
+            message: result.message,
+            remediation: result.fix_hint,
+        }
+    }
+}
+
+/// The `--format json` report.
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    /// True when no check failed (warnings are allowed)
+    pub ok: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    fn new(checks: Vec<DoctorCheck>) -> Self {
+        let ok = checks.iter().all(|c| c.status != CheckStatus::Fail);
+        Self { ok, checks }
+    }
 }
 
 pub fn run(args: DoctorArgs) -> Result<()> {
-    if args.fix && args.format == "json" {
+    if args.fix && args.format == OutputFormat::Json {
         anyhow::bail!("--fix cannot be used with --format json");
     }
+    if args.fix && args.check.is_some() {
+        anyhow::bail!("--fix cannot be used with --check");
+    }
+
+    if let Some(id) = &args.check
+        && !all_check_ids().any(|known| known == id)
+    {
+        anyhow::bail!(
+            "Unknown check '{}'. Available checks: {}",
+            id,
+            all_check_ids().collect::<Vec<_>>().join(", ")
+        );
+    }
 
     let runner = RealCommandRunner;
-    let mut results = collect_results(&runner)?;
+    let only = args.check.as_deref();
+    let mut checks = collect_checks(&runner, only)?;
 
     if args.fix {
-        apply_known_fixes(&results)?;
-        results = collect_results(&runner)?;
+        apply_known_fixes(&checks)?;
+        checks = collect_checks(&runner, only)?;
     }
 
-    if args.format == "json" {
-        let json_results: Vec<_> = results
-            .iter()
-            .map(|r| {
-                serde_json::json!({
-                    "name": r.name,
-                    "passed": r.passed,
-                    "message": r.message,
-                    "fix_hint": r.fix_hint,
-                })
-            })
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&json_results)?);
-        return Ok(());
+    let report = DoctorReport::new(checks);
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_table(&report, only.is_none());
     }
 
-    Output::header("bkt doctor - checking system readiness");
-    Output::blank();
+    // A single requested check gates the exit code
+    if only.is_some() && report.checks.iter().any(|c| c.status != CheckStatus::Pass) {
+        std::process::exit(1);
+    }
 
-    let mut all_passed = true;
-    for result in &results {
-        if result.passed {
-            Output::success(format!("{}: {}", result.name, result.message));
-        } else {
-            Output::error(format!("{}: {}", result.name, result.message));
-            if let Some(hint) = &result.fix_hint {
-                Output::hint(hint);
-            }
-            all_passed = false;
+    Ok(())
+}
+
+fn print_table(report: &DoctorReport, show_summary: bool) {
+    if show_summary {
+        Output::header("bkt doctor - checking system readiness");
+        Output::blank();
+    }
+
+    for check in &report.checks {
+        match check.status {
+            CheckStatus::Pass => Output::success(format!("{}: {}", check.name, check.message)),
+            CheckStatus::Warn => Output::warning(format!("{}: {}", check.name, check.message)),
+            CheckStatus::Fail => Output::error(format!("{}: {}", check.name, check.message)),
+        }
+        if check.status != CheckStatus::Pass
+            && let Some(hint) = &check.remediation
+        {
+            Output::hint(hint);
         }
     }
 
+    if !show_summary {
+        return;
+    }
+
     Output::blank();
-    if all_passed {
+    if report.ok {
         Output::success("All checks passed! Ready to use bkt --pr workflows.");
     } else {
         Output::error("Some checks failed. Fix the issues above to enable --pr workflows.");
     }
+}
 
-    Ok(())
+fn all_check_ids() -> impl Iterator<Item = &'static str> {
+    PREFLIGHT_CHECK_IDS
+        .iter()
+        .copied()
+        .chain(ENVIRONMENT_CHECKS.iter().map(|(id, _)| *id))
 }
 
-fn collect_results(runner: &RealCommandRunner) -> Result<Vec<crate::pr::PreflightResult>> {
-    let mut results = run_preflight_checks(runner)?;
+/// Run all checks, or only the one with ID `only`.
+fn collect_checks(runner: &RealCommandRunner, only: Option<&str>) -> Result<Vec<DoctorCheck>> {
+    let selected = |id: &str| only.is_none_or(|o| o == id);
+    let mut checks = Vec::new();
+
+    // Skip the preflight batch entirely when gating on an environment check;
+    // it shells out to gh and git.
+    if PREFLIGHT_CHECK_IDS.iter().any(|id| selected(id)) {
+        let results = run_preflight_checks(runner)?;
+        debug_assert_eq!(results.len(), PREFLIGHT_CHECK_IDS.len());
+        for (id, result) in PREFLIGHT_CHECK_IDS.iter().zip(results) {
+            if selected(id) {
+                checks.push(DoctorCheck::from_result(id, result));
+            }
+        }
+    }
 
-    // Additional environment readiness checks (not specific to PR workflows).
-    results.push(check_distrobox_shims_path());
-    results.push(check_distrobox_wrappers());
-    results.push(check_cargo_bin_exports());
-    results.push(check_devtools_resolve_to_distrobox("cargo"));
-    results.push(check_devtools_resolve_to_distrobox("node"));
-    results.push(check_devtools_resolve_to_distrobox("pnpm"));
-    results.push(check_daemon_status());
+    for (id, check) in ENVIRONMENT_CHECKS {
+        if selected(id) {
+            checks.push(DoctorCheck::from_result(id, check()));
+        }
+    }
 
-    Ok(results)
+    Ok(checks)
 }
 
-fn apply_known_fixes(results: &[crate::pr::PreflightResult]) -> Result<()> {
+fn apply_known_fixes(checks: &[DoctorCheck]) -> Result<()> {
     let mut has_wrapper_issue = false;
     let mut has_cargo_export_issue = false;
+    let mut has_stale_socket = false;
+    let mut has_shim_issue = false;
 
-    for result in results {
-        if result.passed {
+    for check in checks {
+        if check.status == CheckStatus::Pass {
             continue;
         }
 
-        if result.name == "distrobox wrappers" {
+        if check.id == "distrobox-wrappers" {
             has_wrapper_issue = true;
         }
-        if result.name == "cargo bin exports" {
+        if check.id == "cargo-exports" {
             has_cargo_export_issue = true;
         }
+        if check.id == "daemon" && daemon_socket_is_stale() {
+            has_stale_socket = true;
+        }
+        if check.id == "shims" {
+            has_shim_issue = true;
+        }
     }
 
-    if !has_wrapper_issue && !has_cargo_export_issue {
+    if !has_wrapper_issue && !has_cargo_export_issue && !has_stale_socket && !has_shim_issue {
         Output::info("No auto-fixable doctor issues detected.");
         return Ok(());
     }
 
+    if has_stale_socket {
+        remove_stale_socket()?;
+    }
+
+    if has_shim_issue {
+        resync_shims()?;
+    }
+
     if has_wrapper_issue {
         remove_non_wrapper_files()?;
     }
@@ -136,6 +297,26 @@ fn apply_known_fixes(results: &[crate::pr::PreflightResult]) -> Result<()> {
     Ok(())
 }
 
+fn remove_stale_socket() -> Result<()> {
+    let path = daemon::socket_path()?;
+    Output::step(format!("Removing stale daemon socket {}", path.display()));
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+fn resync_shims() -> Result<()> {
+    Output::info("Re-syncing toolbox shims from the manifest...");
+    let plan = ExecutionPlan::default();
+    let cwd = std::env::current_dir()?;
+    let sync_plan = ShimSyncCommand {
+        keep_unmanaged: false,
+    }
+    .plan(&PlanContext::new(cwd, plan.clone()))?;
+
+    let mut exec_ctx = ExecuteContext::new(plan.clone());
+    let report = sync_plan.execute(&mut exec_ctx)?;
+    print_report(&report.with_subsystem("shim"), &plan)
+}
+
 fn remove_non_wrapper_files() -> Result<()> {
     let Some(home) = home_dir() else {
         anyhow::bail!("Cannot determine $HOME");
@@ -202,8 +383,8 @@ fn home_dir() -> Option<PathBuf> {
         .or_else(|| std::env::var("HOME").ok().map(PathBuf::from))
 }
 
-fn pass(name: &str, message: &str) -> crate::pr::PreflightResult {
-    crate::pr::PreflightResult {
+fn pass(name: &str, message: &str) -> PreflightResult {
+    PreflightResult {
         name: name.to_string(),
         passed: true,
         message: message.to_string(),
@@ -211,8 +392,8 @@ fn pass(name: &str, message: &str) -> crate::pr::PreflightResult {
     }
 }
 
-fn fail(name: &str, message: &str, fix_hint: &str) -> crate::pr::PreflightResult {
-    crate::pr::PreflightResult {
+fn fail(name: &str, message: &str, fix_hint: &str) -> PreflightResult {
+    PreflightResult {
         name: name.to_string(),
         passed: false,
         message: message.to_string(),
@@ -246,7 +427,7 @@ fn index_of_path(path: &[PathBuf], needle: &Path) -> Option<usize> {
     path.iter().position(|p| p == needle)
 }
 
-fn check_distrobox_shims_path() -> crate::pr::PreflightResult {
+fn check_distrobox_shims_path() -> PreflightResult {
     let Some(home) = home_dir() else {
         return fail(
             "PATH (distrobox shims)",
@@ -303,7 +484,7 @@ fn check_distrobox_shims_path() -> crate::pr::PreflightResult {
     )
 }
 
-fn check_devtools_resolve_to_distrobox(cmd: &str) -> crate::pr::PreflightResult {
+fn check_devtools_resolve_to_distrobox(cmd: &str) -> PreflightResult {
     let Some(home) = home_dir() else {
         return fail(
             &format!("{} resolution", cmd),
@@ -353,7 +534,7 @@ fn check_devtools_resolve_to_distrobox(cmd: &str) -> crate::pr::PreflightResult
 }
 
 /// Check that all files in ~/.local/bin/distrobox are valid wrapper scripts.
-fn check_distrobox_wrappers() -> crate::pr::PreflightResult {
+fn check_distrobox_wrappers() -> PreflightResult {
     let Some(home) = home_dir() else {
         return fail(
             "distrobox wrappers",
@@ -439,7 +620,7 @@ fn check_distrobox_wrappers() -> crate::pr::PreflightResult {
 /// When you run `cargo install --path <crate>` (via the distrobox cargo shim),
 /// the binary lands in ~/.cargo/bin but isn't accessible on the host until
 /// `bkt distrobox apply` creates a shim for it.
-fn check_cargo_bin_exports() -> crate::pr::PreflightResult {
+fn check_cargo_bin_exports() -> PreflightResult {
     let missing_shims = match list_missing_cargo_shims() {
         Ok(missing) => missing,
         Err(e) => {
@@ -557,21 +738,264 @@ fn list_missing_cargo_shims() -> Result<Vec<String>> {
     Ok(missing_shims)
 }
 
-/// Check if the bkt daemon is running and connectable.
-fn check_daemon_status() -> crate::pr::PreflightResult {
+/// The socket file is present but nothing is listening on it.
+fn daemon_socket_is_stale() -> bool {
+    daemon::daemon_socket_exists() && !daemon::daemon_available()
+}
+
+/// Check if the bkt daemon is running, connectable, and the same version as us.
+fn check_daemon_status() -> PreflightResult {
     if daemon::daemon_available() {
-        pass("bkt daemon", "Daemon is running and connectable")
+        let version = daemon::socket_path().and_then(|path| DaemonClient::new(&path).version());
+        match version {
+            Ok(Some(v)) if v == daemon::DAEMON_VERSION => pass(
+                "bkt daemon",
+                &format!("Daemon {} is running and connectable", v),
+            ),
+            Ok(Some(v)) => fail(
+                "bkt daemon",
+                &format!(
+                    "Daemon version mismatch: daemon is {}, bkt is {}",
+                    v,
+                    daemon::DAEMON_VERSION
+                ),
+                "Restart the daemon: systemctl --user restart bkt-daemon.service",
+            ),
+            Ok(None) => fail(
+                "bkt daemon",
+                &format!(
+                    "Daemon predates version reporting (older than bkt {})",
+                    daemon::DAEMON_VERSION
+                ),
+                "Restart the daemon: systemctl --user restart bkt-daemon.service",
+            ),
+            Err(e) => fail(
+                "bkt daemon",
+                &format!("Daemon did not answer a version request: {}", e),
+                "Restart the daemon: systemctl --user restart bkt-daemon.service",
+            ),
+        }
     } else if daemon::daemon_socket_exists() {
         fail(
             "bkt daemon",
-            "Daemon socket exists but is not connectable (stale?)",
-            "Restart the daemon: systemctl --user restart bkt-daemon.service",
+            "Daemon socket exists but is not connectable (stale)",
+            "Run `bkt doctor --fix` to remove it, then restart the daemon: systemctl --user restart bkt-daemon.service",
         )
     } else {
         fail(
             "bkt daemon",
             "Daemon is not running (~30x slower container→host delegation)",
-            "Start the daemon: systemctl --user enable --now bkt-daemon.service",
+            "Start the daemon on demand: bkt admin daemon install",
         )
     }
 }
+
+/// Check that the polkit rule is installed as shipped and applies to us.
+fn check_polkit_rules() -> PreflightResult {
+    let installed = match std::fs::read_to_string(POLKIT_RULES_PATH) {
+        Ok(content) => content,
+        Err(e) => {
+            return fail(
+                "polkit rules",
+                &format!("Cannot read {}: {}", POLKIT_RULES_PATH, e),
+                "The rule ships with the image; rebuild or run `bootc upgrade`",
+            );
+        }
+    };
+
+    if let Ok(repo) = find_repo_path()
+        && let Ok(expected) = std::fs::read_to_string(repo.join(POLKIT_RULES_REPO_PATH))
+        && expected != installed
+    {
+        return fail(
+            "polkit rules",
+            &format!(
+                "{} differs from {}",
+                POLKIT_RULES_PATH, POLKIT_RULES_REPO_PATH
+            ),
+            "The installed rule is from an older image; run `bootc upgrade` and reboot",
+        );
+    }
+
+    match current_user_in_group("wheel") {
+        Ok(true) => pass(
+            "polkit rules",
+            "Rule installed and current user is in wheel",
+        ),
+        Ok(false) => fail(
+            "polkit rules",
+            "Current user is not in the wheel group; the rule does not apply",
+            "Add yourself to wheel: sudo usermod -aG wheel $USER (then log in again)",
+        ),
+        Err(e) => fail("polkit rules", &e.to_string(), ""),
+    }
+}
+
+fn current_user_in_group(name: &str) -> Result<bool> {
+    use nix::unistd::{Group, getegid, getgroups};
+
+    let Some(group) = Group::from_name(name).context("Failed to look up group")? else {
+        return Ok(false);
+    };
+    let groups = getgroups().context("Failed to read supplementary groups")?;
+    Ok(getegid() == group.gid || groups.contains(&group.gid))
+}
+
+/// Check that every manifest shim is installed and every installed shim is sound.
+fn check_shim_integrity() -> PreflightResult {
+    let manifest = match ShimsManifest::load_repo() {
+        Ok(m) => m,
+        Err(_) => {
+            return pass(
+                "toolbox shims",
+                "No shims manifest found (ok outside the repo)",
+            );
+        }
+    };
+
+    let expected: Vec<String> = manifest.shims.iter().map(|s| s.name.clone()).collect();
+    let problems = shim_problems(
+        &ShimsManifest::shims_dir(),
+        &expected,
+        &get_installed_shims(),
+    );
+
+    if problems.is_empty() {
+        pass(
+            "toolbox shims",
+            &format!("All {} shims are installed and executable", expected.len()),
+        )
+    } else {
+        fail(
+            "toolbox shims",
+            &problems.join("; "),
+            "Run `bkt doctor --fix` or `bkt shim sync` to regenerate shims",
+        )
+    }
+}
+
+/// Describe everything wrong with the shims in `dir`.
+fn shim_problems(dir: &Path, expected: &[String], installed: &[String]) -> Vec<String> {
+    let mut missing = Vec::new();
+    let mut no_shebang = Vec::new();
+    let mut not_executable = Vec::new();
+
+    for name in expected {
+        if !installed.contains(name) {
+            missing.push(name.as_str());
+            continue;
+        }
+
+        let path = dir.join(name);
+        if !std::fs::read(&path).is_ok_and(|content| content.starts_with(b"#!")) {
+            no_shebang.push(name.as_str());
+        }
+        if !std::fs::metadata(&path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0) {
+            not_executable.push(name.as_str());
+        }
+    }
+
+    let extra: Vec<&str> = installed
+        .iter()
+        .filter(|name| !expected.contains(name))
+        .map(String::as_str)
+        .collect();
+
+    let mut problems = Vec::new();
+    for (label, names) in [
+        ("missing", missing),
+        ("not in manifest", extra),
+        ("no shebang", no_shebang),
+        ("not executable", not_executable),
+    ] {
+        if !names.is_empty() {
+            problems.push(format!("{}: {}", label, names.join(", ")));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(passed: bool) -> PreflightResult {
+        PreflightResult {
+            name: "example".to_string(),
+            passed,
+            message: "message".to_string(),
+            fix_hint: (!passed).then(|| "fix it".to_string()),
+        }
+    }
+
+    #[test]
+    fn check_ids_are_unique() {
+        let mut ids: Vec<_> = all_check_ids().collect();
+        let total = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), total);
+    }
+
+    #[test]
+    fn advisory_failures_are_warnings() {
+        let daemon = DoctorCheck::from_result("daemon", result(false));
+        assert_eq!(daemon.status, CheckStatus::Warn);
+
+        let git = DoctorCheck::from_result("git", result(false));
+        assert_eq!(git.status, CheckStatus::Fail);
+        assert_eq!(git.remediation.as_deref(), Some("fix it"));
+    }
+
+    #[test]
+    fn shim_problems_reports_each_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str, mode: u32| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        write("good", "#!/bin/bash\nexec true\n", 0o755);
+        write("plain", "exec true\n", 0o755);
+        write("readonly", "#!/bin/bash\n", 0o644);
+        write("stray", "#!/bin/bash\n", 0o755);
+
+        let expected: Vec<String> = ["good", "plain", "readonly", "gone"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let installed: Vec<String> = ["good", "plain", "readonly", "stray"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(
+            shim_problems(dir.path(), &expected, &installed),
+            vec![
+                "missing: gone",
+                "not in manifest: stray",
+                "no shebang: plain",
+                "not executable: readonly",
+            ]
+        );
+        assert!(shim_problems(dir.path(), &expected[..1], &installed[..1]).is_empty());
+    }
+
+    #[test]
+    fn report_ok_ignores_warnings() {
+        let report = DoctorReport::new(vec![
+            DoctorCheck::from_result("git", result(true)),
+            DoctorCheck::from_result("daemon", result(false)),
+        ]);
+        assert!(report.ok);
+
+        let report = DoctorReport::new(vec![DoctorCheck::from_result("git", result(false))]);
+        assert!(!report.ok);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["checks"][0]["id"], "git");
+        assert_eq!(json["checks"][0]["status"], "fail");
+        assert_eq!(json["checks"][0]["remediation"], "fix it");
+    }
+}
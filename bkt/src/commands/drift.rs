@@ -8,38 +8,41 @@
 //! - Flatpaks are added/removed via the GUI
 //! - Extensions are enabled/disabled manually
 //! - Settings are changed via the UI
+//!
+//! `bkt drift` reports what each drift-capable subsystem is missing or has
+//! extra. `--fix missing` runs the subsystems' sync plans and `--fix extra`
+//! their capture plans; with `--dry-run` the plans are only described.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand, ValueEnum};
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use std::env;
 use std::path::PathBuf;
 
 use crate::manifest::find_repo_root;
 use crate::output::Output;
+use crate::pipeline::ExecutionPlan;
+use crate::plan::{DynPlan, ExecuteContext, PlanContext, print_report, print_summary};
+use crate::subsystem::{
+    DriftReport, Subsystem, SubsystemConfig, SubsystemContext, SubsystemRegistry,
+};
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct DriftArgs {
     #[command(subcommand)]
-    pub action: DriftAction,
+    pub action: Option<DriftAction>,
+
+    /// Options for `bkt drift` without a subcommand (same as `check`)
+    #[command(flatten)]
+    pub check: DriftCheckArgs,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum DriftAction {
     /// Check for drift between manifests and system state
-    Check {
-        /// Category to check (default: all)
-        #[arg(value_enum)]
-        category: Option<DriftCategory>,
-
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "human")]
-        format: OutputFormat,
-
-        /// Don't re-execute on host when running in toolbox
-        #[arg(long)]
-        no_host: bool,
-    },
+    Check(DriftCheckArgs),
 
     /// Show summary of last drift check
     Status,
@@ -48,36 +51,51 @@ pub enum DriftAction {
     Explain,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
-pub enum DriftCategory {
-    /// Check RPM packages
-    Packages,
-    /// Check Flatpak applications
-    Flatpaks,
-    /// Check GNOME extensions
-    Extensions,
-    /// Check all categories
-    All,
+#[derive(Debug, Clone, Args)]
+pub struct DriftCheckArgs {
+    /// Only check these subsystems (comma-separated)
+    #[arg(long, short = 's', value_delimiter = ',')]
+    pub subsystem: Vec<String>,
+
+    /// Act on the drift: sync what's missing, or capture what's extra
+    #[arg(long, value_enum)]
+    pub fix: Option<DriftFix>,
+
+    /// Capture extra items without asking (with `--fix extra`)
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Print the raw drift reports as JSON
+    #[arg(long, conflicts_with = "fix")]
+    pub json: bool,
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum, Default)]
-pub enum OutputFormat {
-    /// Human-readable output
-    #[default]
-    Human,
-    /// JSON output
-    Json,
+/// Which side of a drift report `--fix` acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DriftFix {
+    /// Install what the manifests expect (runs each subsystem's sync plan)
+    Missing,
+    /// Add untracked items to the manifests (runs each subsystem's capture plan)
+    Extra,
 }
 
-pub fn run(args: DriftArgs) -> Result<()> {
+/// One subsystem's drift, as printed by `--json` and saved for `drift status`.
+#[derive(Debug, Serialize)]
+struct SubsystemDrift {
+    id: &'static str,
+    name: &'static str,
+    #[serde(flatten)]
+    report: Option<DriftReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn run(args: DriftArgs, plan: &ExecutionPlan) -> Result<()> {
     match args.action {
-        DriftAction::Check {
-            category,
-            format,
-            no_host,
-        } => handle_check(category, format, no_host),
-        DriftAction::Status => handle_status(),
-        DriftAction::Explain => handle_explain(),
+        None => handle_check(args.check, plan),
+        Some(DriftAction::Check(check)) => handle_check(check, plan),
+        Some(DriftAction::Status) => handle_status(),
+        Some(DriftAction::Explain) => handle_explain(),
     }
 }
 
@@ -87,53 +105,232 @@ fn get_repo_root() -> Result<PathBuf> {
         .context("Not in a git repository. Run this command from within the bootc repository.")
 }
 
-fn handle_check(
-    category: Option<DriftCategory>,
-    _format: OutputFormat,
-    _no_host: bool,
+fn last_check_path(repo_root: &std::path::Path) -> PathBuf {
+    repo_root
+        .join(".local")
+        .join("state")
+        .join("bkt")
+        .join("last-drift-check.json")
+}
+
+fn handle_check(args: DriftCheckArgs, plan: &ExecutionPlan) -> Result<()> {
+    let registry = SubsystemRegistry::builtin();
+    for id in &args.subsystem {
+        if !registry.is_valid_driftable(id) {
+            bail!(
+                "Unknown subsystem '{}'. Valid: {}",
+                id,
+                registry.driftable_ids().join(", ")
+            );
+        }
+    }
+    let subsystems: Vec<&dyn Subsystem> = registry
+        .driftable()
+        .into_iter()
+        .filter(|s| args.subsystem.is_empty() || args.subsystem.iter().any(|id| id == s.id()))
+        .collect();
+
+    let repo_root = get_repo_root().ok();
+    let ctx = repo_root
+        .clone()
+        .map(SubsystemContext::with_repo_root)
+        .unwrap_or_default();
+    let drifts = collect_drift(&subsystems, &ctx);
+
+    if !plan.dry_run
+        && let Some(repo_root) = &repo_root
+    {
+        save_last_check(repo_root, &drifts)?;
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&drifts)?);
+    } else {
+        print_drift(&drifts);
+    }
+
+    let drifted: Vec<&str> = drifts
+        .iter()
+        .filter(|d| d.report.as_ref().is_some_and(DriftReport::has_drift))
+        .map(|d| d.id)
+        .collect();
+
+    match args.fix {
+        Some(fix) if !drifted.is_empty() => {
+            let plan_ctx = PlanContext::new(env::current_dir().unwrap_or_default(), plan.clone());
+            for drift in &drifts {
+                let Some(report) = &drift.report else {
+                    continue;
+                };
+                let Some(subsystem) = registry.find(drift.id) else {
+                    continue;
+                };
+                match fix {
+                    DriftFix::Missing if !report.missing.is_empty() => {
+                        fix_missing(subsystem, &plan_ctx, plan)?;
+                    }
+                    DriftFix::Extra if !report.extra.is_empty() => {
+                        fix_extra(subsystem, report, args.yes, &plan_ctx, plan)?;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        Some(_) => Ok(()),
+        None if drifted.is_empty() => Ok(()),
+        None => {
+            if !args.json {
+                Output::hint("Run `bkt drift --fix missing` to sync, or `--fix extra` to capture");
+            }
+            bail!("Drift detected in: {}", drifted.join(", "))
+        }
+    }
+}
+
+/// Run each subsystem's drift check; a failing probe is recorded, not fatal.
+fn collect_drift(subsystems: &[&dyn Subsystem], ctx: &SubsystemContext) -> Vec<SubsystemDrift> {
+    subsystems
+        .iter()
+        .map(|subsystem| {
+            let (report, error) = match subsystem.drift(ctx) {
+                Ok(report) => (report, None),
+                Err(e) => (None, Some(format!("{:#}", e))),
+            };
+            SubsystemDrift {
+                id: subsystem.id(),
+                name: subsystem.name(),
+                report,
+                error,
+            }
+        })
+        .collect()
+}
+
+fn print_drift(drifts: &[SubsystemDrift]) {
+    for drift in drifts {
+        match (&drift.report, &drift.error) {
+            (_, Some(error)) => {
+                Output::warning(format!("{}: unavailable ({})", drift.name, error));
+            }
+            (Some(report), None) if report.has_drift() => {
+                Output::subheader(format!(
+                    "{}: {} missing, {} extra",
+                    drift.name,
+                    report.missing.len(),
+                    report.extra.len()
+                ));
+                for item in &report.missing {
+                    println!("  {} {}", "-".red(), item);
+                }
+                for item in &report.extra {
+                    println!("  {} {}", "+".yellow(), item);
+                }
+            }
+            (Some(_), None) => Output::success(format!("{}: in sync", drift.name)),
+            (None, None) => {}
+        }
+    }
+}
+
+fn save_last_check(repo_root: &std::path::Path, drifts: &[SubsystemDrift]) -> Result<()> {
+    let path = last_check_path(repo_root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(drifts)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Install what the manifest expects by running the subsystem's sync plan.
+fn fix_missing(
+    subsystem: &dyn Subsystem,
+    plan_ctx: &PlanContext,
+    plan: &ExecutionPlan,
 ) -> Result<()> {
-    // TODO: Implement drift detection natively in Rust
-    // See RFC 0007 for the full design
-    //
-    // The previous implementation depended on a Python script which violates
-    // the project's "No Custom Python Scripts" axiom (see docs/VISION.md).
-    //
-    // For now, this command explains what drift detection will do and
-    // directs users to use `bkt capture` to detect changes.
-
-    Output::warning("Drift detection is not yet implemented in Rust.");
-    println!();
+    let sync = if subsystem.supports_sync() {
+        subsystem.sync(plan_ctx, &SubsystemConfig::default())?
+    } else {
+        None
+    };
+    match sync {
+        Some(sync) if !sync.is_empty_dyn() => run_fix_plan(subsystem, sync, plan),
+        _ => {
+            Output::info(format!(
+                "{}: nothing bkt can sync; install the missing items by hand",
+                subsystem.name()
+            ));
+            Ok(())
+        }
+    }
+}
 
-    if let Some(cat) = category {
-        Output::info(format!("Category filter: {:?}", cat));
+/// Add untracked items to the manifest by running the subsystem's capture
+/// plan, after confirming unless `yes` is set.
+fn fix_extra(
+    subsystem: &dyn Subsystem,
+    report: &DriftReport,
+    yes: bool,
+    plan_ctx: &PlanContext,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    let capture = if subsystem.supports_capture() {
+        subsystem.capture(plan_ctx)?
+    } else {
+        None
+    };
+    let Some(capture) = capture.filter(|c| !c.is_empty_dyn()) else {
+        Output::info(format!(
+            "{}: nothing bkt can capture; remove the extra items by hand",
+            subsystem.name()
+        ));
+        return Ok(());
+    };
+
+    // A capture plan records everything untracked, so confirmation is per
+    // subsystem, listing the items it would add
+    if !yes && !plan.dry_run {
+        if plan.json_output() {
+            bail!("--format json cannot prompt for confirmation; pass --yes");
+        }
+        let confirmed = cliclack::confirm(format!(
+            "Capture {} into the {} manifest?",
+            report.extra.join(", "),
+            subsystem.name()
+        ))
+        .initial_value(false)
+        .interact()
+        .context("Failed to read confirmation")?;
+        if !confirmed {
+            Output::info(format!("{}: skipped", subsystem.name()));
+            return Ok(());
+        }
     }
 
-    println!();
-    println!("Drift detection will compare your system state against manifests.");
-    println!("For now, you can use these alternatives:");
-    println!();
-    println!(
-        "  {} - Capture current system state to manifests",
-        "bkt capture".cyan()
-    );
-    println!(
-        "  {} - Show what capture would change",
-        "bkt capture --dry-run".cyan()
-    );
-    println!("  {} - See git diff after capture", "git diff".cyan());
-    println!();
-    println!(
-        "Run {} for more information about drift detection.",
-        "bkt drift explain".cyan()
-    );
+    run_fix_plan(subsystem, capture, plan)
+}
 
-    Ok(())
+/// Describe a fix plan and, unless this is a dry run, execute it.
+fn run_fix_plan(
+    subsystem: &dyn Subsystem,
+    fix: Box<dyn DynPlan>,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    let summary = fix.describe_dyn().with_subsystem(subsystem.id());
+    print_summary(&summary, plan, plan.dry_run)?;
+    if plan.dry_run {
+        return Ok(());
+    }
+
+    let mut exec_ctx = ExecuteContext::new(plan.clone());
+    let report = fix.execute_dyn(&mut exec_ctx)?;
+    print_report(&report.with_subsystem(subsystem.id()), plan)
 }
 
 fn handle_status() -> Result<()> {
     let repo_root = get_repo_root()?;
-    let state_dir = repo_root.join(".local").join("state").join("bkt");
-    let last_check = state_dir.join("last-drift-check.json");
+    let last_check = last_check_path(&repo_root);
 
     if !last_check.exists() {
         Output::info("No drift check has been run yet.");
@@ -173,13 +370,14 @@ fn handle_explain() -> Result<()> {
     );
     println!();
     println!("{}", "Exit Codes:".yellow().bold());
-    println!("  {} - No drift (or only optional-tier)", "0".green());
-    println!("  {} - Drift in baked/bootstrapped tiers", "1".yellow());
-    println!("  {} - Error collecting state", "2".red());
+    println!("  {} - No drift, or drift fixed with --fix", "0".green());
+    println!("  {} - Drift detected", "1".yellow());
     println!();
     println!("{}", "Commands:".yellow().bold());
-    println!("  bkt drift check          - Run drift detection");
-    println!("  bkt drift check --json   - Output as JSON");
+    println!("  bkt drift                - Run drift detection");
+    println!("  bkt drift --json         - Output as JSON");
+    println!("  bkt drift --fix missing  - Sync what the manifests expect");
+    println!("  bkt drift --fix extra    - Capture untracked items");
     println!("  bkt drift status         - Show last check results");
     println!();
     Ok(())
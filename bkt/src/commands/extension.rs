@@ -1,16 +1,25 @@
 //! GNOME extension command implementation.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::capture::CapturePlan;
 use crate::manifest::GnomeExtensionsManifest;
-use crate::output::Output;
+use crate::manifest::extension::{ExtensionConfig, ExtensionItem};
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
-use crate::validation::validate_gnome_extension;
-use anyhow::{Context, Result};
+use crate::validation::{
+    CurlExtensionsApi, ExtensionLookup, ExtensionMatch, ExtensionsApi, ExtensionsApiUnavailable,
+    GnomeExtensionInfo, gnome_shell_version, resolve_gnome_extension,
+    validate_extension_shell_support,
+};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
+use is_terminal::IsTerminal;
 use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Args)]
 pub struct ExtensionArgs {
@@ -22,11 +31,12 @@ pub struct ExtensionArgs {
 pub enum ExtensionAction {
     /// Add a GNOME extension to the manifest
     Add {
-        /// Extension UUID (e.g., dash-to-dock@micxgx.gmail.com)
-        uuid: String,
-        /// Skip validation that extension exists
-        #[arg(long)]
-        force: bool,
+        /// Extension UUID, extensions.gnome.org id, or name to search for
+        /// (e.g., dash-to-dock@micxgx.gmail.com, 307, "dash to dock")
+        query: String,
+        /// Skip the extensions.gnome.org lookup (offline); QUERY must be a UUID
+        #[arg(long, alias = "force")]
+        no_verify: bool,
     },
     /// Remove a GNOME extension from the manifest
     Remove {
@@ -46,10 +56,11 @@ pub enum ExtensionAction {
     /// List all GNOME extensions in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
-    /// Sync: enable extensions from manifest
+    /// Sync: enable extensions from manifest (and, with `install_from_web`,
+    /// download missing ones from extensions.gnome.org)
     Sync,
     /// Capture enabled extensions to manifest
     Capture {
@@ -57,6 +68,15 @@ pub enum ExtensionAction {
         #[arg(long)]
         apply: bool,
     },
+    /// Record an extension's dconf settings in its manifest entry
+    CaptureSettings {
+        /// Extension UUID (must already be in the manifest)
+        uuid: String,
+        /// dconf directory to dump instead of
+        /// /org/gnome/shell/extensions/<uuid up to @>/
+        #[arg(long, value_name = "DIR")]
+        path: Option<String>,
+    },
 }
 
 /// Check if an extension is installed.
@@ -98,8 +118,130 @@ fn enable_extension(uuid: &str, runner: &dyn CommandRunner) -> Result<bool> {
     Ok(status.success())
 }
 
+/// Download the release of `uuid` for GNOME Shell `shell_version` from
+/// extensions.gnome.org and install it with `gnome-extensions install`.
+fn install_from_web(
+    uuid: &str,
+    shell_version: Option<&str>,
+    api: &dyn ExtensionsApi,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    let shell_version = shell_version.context("Could not detect GNOME Shell version")?;
+    let info = api
+        .info(&ExtensionLookup::Uuid(uuid.to_string()))?
+        .with_context(|| format!("{} not found on extensions.gnome.org", uuid))?;
+    let version_tag = info.release_for_shell(shell_version).with_context(|| {
+        format!(
+            "no release for GNOME Shell {} (supports {})",
+            shell_version,
+            info.shell_versions().join(", ")
+        )
+    })?;
+
+    let zip = std::env::temp_dir().join(format!(
+        "bkt-extension-{}-{}.zip",
+        std::process::id(),
+        version_tag
+    ));
+    let zip_arg = zip.to_string_lossy();
+    let result = (|| {
+        let url = info.download_url(version_tag);
+        let output = runner
+            .run_output(
+                "curl",
+                &["-fsSL", "-o", &zip_arg, &url],
+                &CommandOptions::default(),
+            )
+            .context("Failed to run curl")?;
+        if !output.status.success() {
+            bail!(
+                "download failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let output = runner
+            .run_output(
+                "gnome-extensions",
+                &["install", "--force", &zip_arg],
+                &CommandOptions::default(),
+            )
+            .context("Failed to run gnome-extensions install")?;
+        if !output.status.success() {
+            bail!(
+                "gnome-extensions install failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    })();
+    let _ = std::fs::remove_file(&zip);
+    result
+}
+
+/// Read a dconf key's current value, `None` if unset.
+fn read_dconf(key: &str, runner: &dyn CommandRunner) -> Option<String> {
+    runner
+        .run_output("dconf", &["read", key], &CommandOptions::default())
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Write a GVariant value to a dconf key.
+fn write_dconf(key: &str, value: &str, runner: &dyn CommandRunner) -> Result<bool> {
+    let status = runner
+        .run_status("dconf", &["write", key, value], &CommandOptions::default())
+        .context("Failed to run dconf write")?;
+    Ok(status.success())
+}
+
+/// Dump a dconf directory in keyfile form.
+fn dump_dconf(dir: &str, runner: &dyn CommandRunner) -> Result<String> {
+    let output = runner
+        .run_output("dconf", &["dump", dir], &CommandOptions::default())
+        .context("Failed to run dconf dump")?;
+    if !output.status.success() {
+        bail!(
+            "dconf dump {} failed: {}",
+            dir,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `dconf dump` output into key paths relative to the dumped
+/// directory (`[/]` keys stay bare, `[custom]` keys become `custom/key`).
+pub fn parse_dconf_dump(dump: &str) -> BTreeMap<String, String> {
+    let mut settings = BTreeMap::new();
+    let mut prefix = String::new();
+
+    for line in dump.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = section.trim_matches('/');
+            prefix = if section.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", section)
+            };
+        } else if let Some((key, value)) = line.split_once('=') {
+            settings.insert(
+                format!("{}{}", prefix, key.trim()),
+                value.trim().to_string(),
+            );
+        }
+    }
+
+    settings
+}
+
 /// Disable an extension.
-#[allow(dead_code)]
 fn disable_extension(uuid: &str, runner: &dyn CommandRunner) -> Result<bool> {
     let status = runner
         .run_status(
@@ -115,11 +257,25 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
     let runner = plan.runner();
 
     match args.action {
-        ExtensionAction::Add { uuid, force } => {
-            // Validate that the extension exists on extensions.gnome.org
-            if !force {
-                validate_gnome_extension(runner, &uuid)?;
-            }
+        ExtensionAction::Add { query, no_verify } => {
+            // Resolve the query to a canonical UUID on extensions.gnome.org
+            let (uuid, pk) =
+                if no_verify || (query.contains('@') && plan.skip_offline("extension lookup")) {
+                    (query, None)
+                } else {
+                    let api = CurlExtensionsApi::new(runner);
+                    match lookup_extension(&api, &query, gnome_shell_version(runner).as_deref())? {
+                        Some(info) => (info.uuid, Some(info.pk)),
+                        None => (query, None),
+                    }
+                };
+            let item = match pk {
+                Some(pk) => ExtensionItem::Object(ExtensionConfig {
+                    pk: Some(pk),
+                    ..ExtensionItem::Uuid(uuid.clone()).to_config()
+                }),
+                None => ExtensionItem::Uuid(uuid.clone()),
+            };
 
             let mut manifest = GnomeExtensionsManifest::load_repo()?;
 
@@ -130,7 +286,7 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
                 if already_in_manifest {
                     Output::warning(format!("Extension already in manifest: {}", uuid));
                 } else {
-                    manifest.add(uuid.clone());
+                    manifest.add(item.clone());
                     manifest.save_repo()?;
                     Output::success(format!("Added to manifest: {}", uuid));
                 }
@@ -153,7 +309,8 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
                     }
                 } else {
                     Output::hint(
-                        "Extension not installed. Install via Extension Manager or extensions.gnome.org",
+                        "Extension not installed. Install via Extension Manager or extensions.gnome.org, \
+                         or set install_from_web in the manifest and run `bkt extension sync`",
                     );
                 }
             } else if plan.dry_run {
@@ -162,7 +319,7 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
 
             if plan.should_create_pr() {
                 let mut repo_manifest = GnomeExtensionsManifest::load_repo()?;
-                repo_manifest.add(uuid.clone());
+                repo_manifest.add(item);
                 let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
 
                 plan.maybe_create_pr(
@@ -229,28 +386,40 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
             }
 
             if plan.should_update_manifest() {
-                // Convert to disabled object format
-                if manifest.set_enabled(&uuid, false) {
-                    manifest.save_repo()?;
-                    Output::success(format!("Disabled '{}' in manifest", uuid));
-                } else {
-                    // Not in manifest as object, add as disabled
-                    manifest.add_disabled(uuid.clone());
-                    manifest.save_repo()?;
-                    Output::success(format!("Added '{}' as disabled in manifest", uuid));
-                }
+                // Keeps the entry (and install) but stops sync enabling it
+                manifest.set_enabled(&uuid, false);
+                manifest.save_repo()?;
+                Output::success(format!("Disabled '{}' in manifest", uuid));
             } else if plan.dry_run {
                 Output::dry_run(format!("Would disable '{}' in manifest", uuid));
             }
 
             // Also disable the extension on the system if it's enabled
-            if plan.should_execute_locally() && is_enabled(&uuid, runner) {
-                let spinner = Output::spinner(format!("Disabling {}...", uuid));
-                if disable_extension(&uuid, runner)? {
-                    spinner.finish_success(format!("Disabled {}", uuid));
-                } else {
-                    spinner.finish_error(format!("Failed to disable {}", uuid));
+            if plan.should_execute_locally() {
+                if is_enabled(&uuid, runner) {
+                    let spinner = Output::spinner(format!("Disabling {}...", uuid));
+                    if disable_extension(&uuid, runner)? {
+                        spinner.finish_success(format!("Disabled {}", uuid));
+                    } else {
+                        spinner.finish_error(format!("Failed to disable {}", uuid));
+                    }
                 }
+            } else if plan.dry_run && is_enabled(&uuid, runner) {
+                Output::dry_run(format!("Would disable extension: {}", uuid));
+            }
+
+            if plan.should_create_pr() {
+                let mut repo_manifest = GnomeExtensionsManifest::load_repo()?;
+                repo_manifest.set_enabled(&uuid, false);
+                let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+
+                plan.maybe_create_pr(
+                    "extension",
+                    "disable",
+                    &uuid,
+                    "gnome-extensions.json",
+                    &manifest_content,
+                )?;
             }
         }
         ExtensionAction::Enable { uuid } => {
@@ -262,35 +431,52 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
                 return Ok(());
             }
 
+            let already_enabled = manifest.get(&uuid).is_some_and(|item| item.enabled());
+
             if plan.should_update_manifest() {
-                // Set enabled=true (or remove disabled override)
-                if manifest.set_enabled(&uuid, true) {
+                if already_enabled {
+                    Output::info(format!("'{}' is already enabled in manifest", uuid));
+                } else {
+                    manifest.set_enabled(&uuid, true);
                     manifest.save_repo()?;
                     Output::success(format!("Enabled '{}' in manifest", uuid));
-                } else {
-                    Output::info(format!("'{}' is already enabled in manifest", uuid));
                 }
             } else if plan.dry_run {
                 Output::dry_run(format!("Would enable '{}' in manifest", uuid));
             }
 
             // Also enable the extension on the system
-            if plan.should_execute_locally()
-                && !is_enabled(&uuid, runner)
-                && is_installed(&uuid, runner)
-            {
-                let spinner = Output::spinner(format!("Enabling {}...", uuid));
-                if enable_extension(&uuid, runner)? {
-                    spinner.finish_success(format!("Enabled {}", uuid));
-                } else {
-                    spinner.finish_error(format!("Failed to enable {}", uuid));
+            if plan.should_execute_locally() {
+                if !is_enabled(&uuid, runner) && is_installed(&uuid, runner) {
+                    let spinner = Output::spinner(format!("Enabling {}...", uuid));
+                    if enable_extension(&uuid, runner)? {
+                        spinner.finish_success(format!("Enabled {}", uuid));
+                    } else {
+                        spinner.finish_error(format!("Failed to enable {}", uuid));
+                    }
                 }
+            } else if plan.dry_run && !is_enabled(&uuid, runner) {
+                Output::dry_run(format!("Would enable extension: {}", uuid));
+            }
+
+            if plan.should_create_pr() && !already_enabled {
+                let mut repo_manifest = GnomeExtensionsManifest::load_repo()?;
+                repo_manifest.set_enabled(&uuid, true);
+                let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+
+                plan.maybe_create_pr(
+                    "extension",
+                    "enable",
+                    &uuid,
+                    "gnome-extensions.json",
+                    &manifest_content,
+                )?;
             }
         }
         ExtensionAction::List { format } => {
-            let merged = GnomeExtensionsManifest::load_repo()?;
+            let merged = GnomeExtensionsManifest::load_effective()?;
 
-            if format == "json" {
+            if format == OutputFormat::Json {
                 println!("{}", serde_json::to_string_pretty(&merged)?);
             } else {
                 if merged.extensions.is_empty() {
@@ -342,13 +528,14 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
 
             let sync_plan = ExtensionSyncCommand.plan(&plan_ctx)?;
 
-            if sync_plan.is_empty() {
-                Output::success("All extensions are already enabled.");
+            if sync_plan.is_empty() && !plan.json_output() {
+                Output::success("All extensions match the manifest.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", sync_plan.describe());
+            let summary = sync_plan.describe().with_subsystem("extension");
+            print_summary(&summary, plan, plan.dry_run)?;
 
             if plan.dry_run {
                 return Ok(());
@@ -357,7 +544,7 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = sync_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("extension"), plan)?;
         }
         ExtensionAction::Capture { apply } => {
             // Use the Plan-based capture implementation
@@ -366,16 +553,17 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
 
             let capture_plan = ExtensionCaptureCommand.plan(&plan_ctx)?;
 
-            if capture_plan.is_empty() {
+            if capture_plan.is_empty() && !plan.json_output() {
                 Output::success("All enabled extensions are already in the manifest.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", capture_plan.describe());
+            let summary = capture_plan.describe().with_subsystem("extension");
+            print_summary(&summary, plan, plan.dry_run || !apply)?;
 
             if plan.dry_run || !apply {
-                if !apply && !plan.dry_run {
+                if !apply && !plan.dry_run && !plan.json_output() {
                     Output::hint("Use --apply to execute this plan.");
                 }
                 return Ok(());
@@ -384,12 +572,144 @@ pub fn run(args: ExtensionArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = capture_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("extension"), plan)?;
+        }
+        ExtensionAction::CaptureSettings { uuid, path } => {
+            let mut manifest = GnomeExtensionsManifest::load_repo()?;
+            let Some(item) = manifest.get(&uuid) else {
+                bail!(
+                    "Extension '{}' not in manifest; add it with `bkt extension add` first",
+                    uuid
+                );
+            };
+
+            let dir = match path {
+                Some(path) => format!("{}/", path.trim_end_matches('/')),
+                None => item.dconf_dir(),
+            };
+            // Only record a path that differs from the default
+            let dconf_path =
+                (dir != crate::manifest::extension::default_dconf_dir(&uuid)).then(|| dir.clone());
+
+            let settings = parse_dconf_dump(&dump_dconf(&dir, runner)?);
+            if settings.is_empty() {
+                Output::warning(format!("No settings under {}", dir));
+                return Ok(());
+            }
+            for (key, value) in &settings {
+                Output::list_item(format!("{} = {}", key, value));
+            }
+
+            if plan.should_update_manifest() {
+                manifest.set_settings(&uuid, dconf_path.clone(), settings.clone());
+                manifest.save_repo()?;
+                Output::success(format!("Captured {} settings for {}", settings.len(), uuid));
+            } else if plan.dry_run {
+                Output::dry_run(format!(
+                    "Would capture {} settings for {}",
+                    settings.len(),
+                    uuid
+                ));
+            }
+
+            if plan.should_create_pr() {
+                let mut repo_manifest = GnomeExtensionsManifest::load_repo()?;
+                repo_manifest.set_settings(&uuid, dconf_path, settings);
+                let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+
+                plan.maybe_create_pr(
+                    "extension",
+                    "capture-settings",
+                    &uuid,
+                    "gnome-extensions.json",
+                    &manifest_content,
+                )?;
+            }
         }
     }
     Ok(())
 }
 
+/// Resolve `query` on extensions.gnome.org and check it supports `shell_version`.
+///
+/// Returns `None` when the site is unreachable and `query` is already a UUID,
+/// so adding works offline the way it did before lookups existed.
+fn lookup_extension(
+    api: &dyn ExtensionsApi,
+    query: &str,
+    shell_version: Option<&str>,
+) -> Result<Option<GnomeExtensionInfo>> {
+    let info = match resolve_gnome_extension(api, query) {
+        Ok(ExtensionMatch::Found(info)) => info,
+        Ok(ExtensionMatch::Ambiguous(matches)) => choose_extension(query, matches)?,
+        Err(e) if e.is::<ExtensionsApiUnavailable>() && query.contains('@') => {
+            Output::warning("Could not validate extension (network unavailable)");
+            return Ok(None);
+        }
+        Err(e) if e.is::<ExtensionsApiUnavailable>() => {
+            return Err(e.context(format!(
+                "Cannot search for '{}' offline; pass its UUID with --no-verify",
+                query
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+
+    match shell_version {
+        Some(version) => validate_extension_shell_support(&info, version)?,
+        None => {
+            Output::warning("Could not detect GNOME Shell version; skipping compatibility check")
+        }
+    }
+
+    if info.uuid != query {
+        Output::info(format!(
+            "Resolved '{}' to {} ({})",
+            query, info.name, info.uuid
+        ));
+    }
+    Ok(Some(info))
+}
+
+/// Ask which of several search hits was meant; lists them and fails when
+/// not interactive.
+fn choose_extension(query: &str, matches: Vec<GnomeExtensionInfo>) -> Result<GnomeExtensionInfo> {
+    let listing: Vec<String> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, ext)| format!("{:>3}. {} ({}, pk {})", i + 1, ext.name, ext.uuid, ext.pk))
+        .collect();
+
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "'{}' matches several extensions:\n{}\n\n\
+             Re-run with the UUID or pk of the one you want.",
+            query,
+            listing.join("\n")
+        );
+    }
+
+    Output::info(format!("'{}' matches several extensions:", query));
+    for line in &listing {
+        println!("{}", line);
+    }
+    print!("Which one? [1-{}] ", matches.len());
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let choice = input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=matches.len()).contains(n))
+        .with_context(|| format!("Invalid choice '{}'", input.trim()))?;
+    Ok(matches
+        .into_iter()
+        .nth(choice - 1)
+        .expect("choice in range"))
+}
+
 // ============================================================================
 // Plan-based Extension Sync Implementation
 // ============================================================================
@@ -401,6 +721,9 @@ pub enum ExtensionState {
     Disabled,
     /// Extension is not installed.
     NotInstalled,
+    /// Extension is not installed and will be downloaded from
+    /// extensions.gnome.org, installed, then enabled.
+    FromWeb,
 }
 
 /// An extension that needs action.
@@ -412,6 +735,17 @@ pub struct ExtensionToSync {
     pub state: ExtensionState,
 }
 
+/// A recorded extension setting that differs from the live value.
+#[derive(Debug, Clone)]
+pub struct SettingToWrite {
+    /// The extension UUID.
+    pub uuid: String,
+    /// Absolute dconf key.
+    pub key: String,
+    /// GVariant text to write.
+    pub value: String,
+}
+
 /// Command to sync extensions from manifests.
 pub struct ExtensionSyncCommand;
 
@@ -421,6 +755,8 @@ pub struct ExtensionSyncPlan {
     pub to_enable: Vec<ExtensionToSync>,
     /// Extensions to disable (UUIDs).
     pub to_disable: Vec<String>,
+    /// Settings to write, after enabling.
+    pub settings: Vec<SettingToWrite>,
     /// Extensions checked.
     pub checked: usize,
 }
@@ -432,17 +768,31 @@ impl Plannable for ExtensionSyncCommand {
         let runner = ctx.execution_plan().runner();
 
         // Load manifest (read-only, no side effects)
-        let merged = GnomeExtensionsManifest::load_repo()?;
+        let merged = GnomeExtensionsManifest::load_effective()?;
 
         let mut to_enable = Vec::new();
         let mut to_disable = Vec::new();
+        let mut settings = Vec::new();
         let mut checked = 0;
 
-        for item in merged.extensions {
+        for item in &merged.extensions {
             let uuid = item.id().to_string();
             let should_be_enabled = item.enabled();
             checked += 1;
 
+            // Settings only apply to extensions that are (or will be) running
+            if should_be_enabled && is_installed(&uuid, runner) {
+                for (key, value) in item.dconf_settings() {
+                    if read_dconf(&key, runner).as_deref() != Some(value.as_str()) {
+                        settings.push(SettingToWrite {
+                            uuid: uuid.clone(),
+                            key,
+                            value,
+                        });
+                    }
+                }
+            }
+
             if is_enabled(&uuid, runner) {
                 if !should_be_enabled {
                     to_disable.push(uuid);
@@ -454,10 +804,12 @@ impl Plannable for ExtensionSyncCommand {
                         state: ExtensionState::Disabled,
                     });
                 } else {
-                    to_enable.push(ExtensionToSync {
-                        uuid,
-                        state: ExtensionState::NotInstalled,
-                    });
+                    let state = if merged.installs_from_web(item) {
+                        ExtensionState::FromWeb
+                    } else {
+                        ExtensionState::NotInstalled
+                    };
+                    to_enable.push(ExtensionToSync { uuid, state });
                 }
             }
         }
@@ -465,6 +817,7 @@ impl Plannable for ExtensionSyncCommand {
         Ok(ExtensionSyncPlan {
             to_enable,
             to_disable,
+            settings,
             checked,
         })
     }
@@ -477,16 +830,18 @@ impl Plan for ExtensionSyncPlan {
             .iter()
             .filter(|e| matches!(e.state, ExtensionState::Disabled))
             .count();
-        let _not_installed = self
+        let downloads = self
             .to_enable
             .iter()
-            .filter(|e| matches!(e.state, ExtensionState::NotInstalled))
+            .filter(|e| matches!(e.state, ExtensionState::FromWeb))
             .count();
 
         let mut summary = PlanSummary::new(format!(
-            "Extension Sync: {} to enable, {} to disable, {} checked",
+            "Extension Sync: {} to enable, {} to download, {} to disable, {} settings, {} checked",
             installable,
+            downloads,
             self.to_disable.len(),
+            self.settings.len(),
             self.checked
         ));
 
@@ -505,6 +860,13 @@ impl Plan for ExtensionSyncPlan {
                         "not installed",
                     ));
                 }
+                ExtensionState::FromWeb => {
+                    summary.add_operation(Operation::with_details(
+                        Verb::Install,
+                        format!("extension:{}", ext.uuid),
+                        "download from extensions.gnome.org, install, enable",
+                    ));
+                }
             }
         }
 
@@ -512,11 +874,20 @@ impl Plan for ExtensionSyncPlan {
             summary.add_operation(Operation::new(Verb::Disable, format!("extension:{}", uuid)));
         }
 
+        for setting in &self.settings {
+            summary.add_operation(Operation::with_details(
+                Verb::Set,
+                format!("extension:{}:{}", setting.uuid, setting.key),
+                &setting.value,
+            ));
+        }
+
         summary
     }
 
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
+        let mut shell_version = None;
 
         for ext in self.to_enable {
             match ext.state {
@@ -555,6 +926,52 @@ impl Plan for ExtensionSyncPlan {
                 ExtensionState::NotInstalled => {
                     // Skip, don't record anything for not-installed extensions
                 }
+                ExtensionState::FromWeb => {
+                    let target = format!("extension:{}", ext.uuid);
+                    let result = {
+                        let runner = ctx.execution_plan().runner();
+                        let shell_version =
+                            shell_version.get_or_insert_with(|| gnome_shell_version(runner));
+                        install_from_web(
+                            &ext.uuid,
+                            shell_version.as_deref(),
+                            &CurlExtensionsApi::new(runner),
+                            runner,
+                        )
+                    };
+                    // One extension failing doesn't stop the rest
+                    if let Err(e) = result {
+                        report.record_failure_and_notify(
+                            ctx,
+                            Verb::Install,
+                            target,
+                            format!("{:#}", e),
+                        );
+                        continue;
+                    }
+                    report.record_success_and_notify(ctx, Verb::Install, target.clone());
+
+                    let enabled = {
+                        let runner = ctx.execution_plan().runner();
+                        enable_extension(&ext.uuid, runner)
+                    };
+                    match enabled {
+                        Ok(true) => report.record_success_and_notify(ctx, Verb::Enable, target),
+                        // A running Shell only picks up new extensions at login
+                        Ok(false) => report.record_failure_and_notify(
+                            ctx,
+                            Verb::Enable,
+                            target,
+                            "installed; log out and back in, then run `bkt extension sync`",
+                        ),
+                        Err(e) => report.record_failure_and_notify(
+                            ctx,
+                            Verb::Enable,
+                            target,
+                            e.to_string(),
+                        ),
+                    }
+                }
             }
         }
 
@@ -591,6 +1008,22 @@ impl Plan for ExtensionSyncPlan {
             }
         }
 
+        for setting in self.settings {
+            let result = {
+                let runner = ctx.execution_plan().runner();
+                write_dconf(&setting.key, &setting.value, runner)
+            };
+            let target = format!("extension:{}:{}", setting.uuid, setting.key);
+
+            match result {
+                Ok(true) => report.record_success_and_notify(ctx, Verb::Set, target),
+                Ok(false) => {
+                    report.record_failure_and_notify(ctx, Verb::Set, target, "dconf write failed")
+                }
+                Err(e) => report.record_failure_and_notify(ctx, Verb::Set, target, e.to_string()),
+            }
+        }
+
         Ok(report)
     }
 
@@ -599,13 +1032,97 @@ impl Plan for ExtensionSyncPlan {
         let to_enable_count = self
             .to_enable
             .iter()
-            .filter(|e| matches!(e.state, ExtensionState::Disabled))
+            .filter(|e| !matches!(e.state, ExtensionState::NotInstalled))
             .count();
 
-        to_enable_count == 0 && self.to_disable.is_empty()
+        to_enable_count == 0 && self.to_disable.is_empty() && self.settings.is_empty()
+    }
+}
+
+/// Whether an extension's live state matches its manifest entry.
+///
+/// Enabled entries must be enabled. Disabled entries stay installed, so
+/// they're in sync when installed but not enabled.
+pub fn extension_in_sync(item: &ExtensionItem, installed: bool, enabled: bool) -> bool {
+    if item.enabled() {
+        enabled
+    } else {
+        installed && !enabled
     }
 }
 
+/// Resolve drift keys for extension state.
+///
+/// An enabled extension is keyed by its UUID and an installed, disabled one
+/// as `uuid (disabled)`, so an entry the manifest disables matches when it
+/// is installed but off. Installed, disabled extensions the manifest
+/// doesn't track aren't reported.
+pub fn extension_state_drift_keys(
+    manifest: &GnomeExtensionsManifest,
+    installed: &[String],
+    enabled: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let disabled_key = |uuid: &str| format!("{} (disabled)", uuid);
+
+    let expected = manifest
+        .extensions
+        .iter()
+        .map(|item| {
+            if item.enabled() {
+                item.id().to_string()
+            } else {
+                disabled_key(item.id())
+            }
+        })
+        .collect();
+
+    let mut actual = enabled.to_vec();
+    actual.extend(
+        installed
+            .iter()
+            .filter(|uuid| !enabled.contains(uuid) && manifest.contains(uuid))
+            .map(|uuid| disabled_key(uuid)),
+    );
+
+    (expected, actual)
+}
+
+/// Resolve per-setting drift keys for enabled, managed extensions.
+///
+/// Each recorded setting becomes an expected `uuid:key=value` key and its
+/// live value an actual one, so a changed setting shows up as both missing
+/// and extra. `read` returns a dconf key's live value.
+pub fn extension_settings_drift_keys<F>(
+    manifest: &GnomeExtensionsManifest,
+    enabled: &[String],
+    read: F,
+) -> (Vec<String>, Vec<String>)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+
+    for item in &manifest.extensions {
+        if !item.enabled() || !enabled.iter().any(|uuid| uuid == item.id()) {
+            continue;
+        }
+        for (key, value) in item.dconf_settings() {
+            expected.push(format!("{}:{}={}", item.id(), key, value));
+            if let Some(live) = read(&key) {
+                actual.push(format!("{}:{}={}", item.id(), key, live));
+            }
+        }
+    }
+
+    (expected, actual)
+}
+
+/// Live value of a dconf key, for drift checks outside a plan.
+pub fn live_dconf_value(key: &str) -> Option<String> {
+    read_dconf(key, &crate::command_runner::RealCommandRunner)
+}
+
 // ============================================================================
 // Plan-based Extension Capture Implementation
 // ============================================================================
@@ -674,7 +1191,7 @@ impl Plannable for ExtensionCaptureCommand {
             get_enabled_extensions(runner).into_iter().collect();
 
         // Load manifest to see what's already tracked
-        let merged = GnomeExtensionsManifest::load_repo()?;
+        let merged = GnomeExtensionsManifest::load_effective()?;
 
         let mut to_capture = Vec::new();
         let mut already_in_manifest = 0;
@@ -716,12 +1233,13 @@ impl Plan for ExtensionCapturePlan {
         ));
 
         for ext in &self.to_capture {
-            let desc = if ext.enabled {
-                format!("extension:{}", ext.uuid)
+            let target = format!("extension:{}", ext.uuid);
+            let op = if ext.enabled {
+                Operation::new(Verb::Capture, target)
             } else {
-                format!("extension:{} (disabled)", ext.uuid)
+                Operation::with_details(Verb::Capture, target, "disabled")
             };
-            summary.add_operation(Operation::new(Verb::Capture, desc));
+            summary.add_operation(op);
         }
 
         summary
@@ -734,15 +1252,15 @@ impl Plan for ExtensionCapturePlan {
         let mut manifest = GnomeExtensionsManifest::load_repo()?;
 
         for ext in self.to_capture {
-            let item = if ext.enabled {
-                crate::manifest::extension::ExtensionItem::Uuid(ext.uuid.clone())
+            // Keep the extensions.gnome.org id and settings if the entry
+            // already had them
+            let plain = ExtensionItem::Uuid(ext.uuid.clone());
+            let mut config = manifest.get(&ext.uuid).unwrap_or(&plain).to_config();
+            config.enabled = ext.enabled;
+            let item = if config == plain.to_config() {
+                plain
             } else {
-                crate::manifest::extension::ExtensionItem::Object(
-                    crate::manifest::extension::ExtensionConfig {
-                        id: ext.uuid.clone(),
-                        enabled: false,
-                    },
-                )
+                ExtensionItem::Object(config)
             };
 
             if manifest.add(item) {
@@ -772,3 +1290,264 @@ impl Plan for ExtensionCapturePlan {
         self.to_capture.is_empty()
     }
 }
+
+impl CapturePlan for ExtensionCapturePlan {
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.to_capture
+            .retain(|ext| keep(&format!("extension:{}", ext.uuid)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dconf_dump_flattens_sections() {
+        let dump = "[/]\n\
+                    dock-position='BOTTOM'\n\
+                    dash-max-icon-size=48\n\
+                    \n\
+                    [custom-theme]\n\
+                    shrink=true\n\
+                    label='a=b'\n";
+
+        let settings = parse_dconf_dump(dump);
+
+        assert_eq!(settings.len(), 4);
+        assert_eq!(settings["dock-position"], "'BOTTOM'");
+        assert_eq!(settings["dash-max-icon-size"], "48");
+        assert_eq!(settings["custom-theme/shrink"], "true");
+        assert_eq!(settings["custom-theme/label"], "'a=b'");
+        assert!(parse_dconf_dump("").is_empty());
+    }
+
+    #[test]
+    fn disabled_extension_is_in_sync_when_installed_but_off() {
+        let enabled = ExtensionItem::Uuid("dash-to-dock@micxgx.gmail.com".to_string());
+        let mut manifest = GnomeExtensionsManifest::default();
+        manifest.add_disabled("caffeine@patapon.info".to_string());
+        let disabled = manifest.get("caffeine@patapon.info").unwrap();
+
+        assert!(extension_in_sync(&enabled, true, true));
+        assert!(!extension_in_sync(&enabled, true, false));
+        assert!(extension_in_sync(disabled, true, false));
+        assert!(!extension_in_sync(disabled, true, true));
+        assert!(!extension_in_sync(disabled, false, false));
+    }
+
+    #[test]
+    fn state_drift_keys_match_installed_disabled_entries() {
+        let mut manifest = GnomeExtensionsManifest::default();
+        manifest.add("dash-to-dock@micxgx.gmail.com");
+        manifest.add_disabled("caffeine@patapon.info".to_string());
+        let installed = vec![
+            "caffeine@patapon.info".to_string(),
+            "dash-to-dock@micxgx.gmail.com".to_string(),
+            "untracked@example.com".to_string(),
+        ];
+        let enabled = vec!["dash-to-dock@micxgx.gmail.com".to_string()];
+
+        let (mut expected, mut actual) =
+            extension_state_drift_keys(&manifest, &installed, &enabled);
+        expected.sort();
+        actual.sort();
+
+        assert_eq!(expected, actual);
+        assert_eq!(
+            actual,
+            vec![
+                "caffeine@patapon.info (disabled)",
+                "dash-to-dock@micxgx.gmail.com"
+            ]
+        );
+
+        // Enabling the disabled entry by hand is drift
+        let enabled = vec![
+            "caffeine@patapon.info".to_string(),
+            "dash-to-dock@micxgx.gmail.com".to_string(),
+        ];
+        let (_, actual) = extension_state_drift_keys(&manifest, &installed, &enabled);
+        assert!(actual.contains(&"caffeine@patapon.info".to_string()));
+        assert!(!actual.contains(&"caffeine@patapon.info (disabled)".to_string()));
+    }
+
+    #[test]
+    fn settings_drift_keys_cover_enabled_managed_extensions() {
+        let mut manifest = GnomeExtensionsManifest::default();
+        manifest.add("dash-to-dock@micxgx.gmail.com");
+        manifest.add_disabled("caffeine@patapon.info".to_string());
+        manifest.set_settings(
+            "dash-to-dock@micxgx.gmail.com",
+            None,
+            [
+                ("dock-position".to_string(), "'BOTTOM'".to_string()),
+                ("dash-max-icon-size".to_string(), "48".to_string()),
+            ]
+            .into(),
+        );
+        manifest.set_settings(
+            "caffeine@patapon.info",
+            None,
+            [("show-indicator".to_string(), "false".to_string())].into(),
+        );
+        let enabled = vec![
+            "dash-to-dock@micxgx.gmail.com".to_string(),
+            "caffeine@patapon.info".to_string(),
+        ];
+
+        let (expected, actual) =
+            extension_settings_drift_keys(&manifest, &enabled, |key| match key {
+                "/org/gnome/shell/extensions/dash-to-dock/dock-position" => {
+                    Some("'LEFT'".to_string())
+                }
+                _ => None,
+            });
+
+        assert_eq!(
+            expected,
+            vec![
+                "dash-to-dock@micxgx.gmail.com:/org/gnome/shell/extensions/dash-to-dock/dash-max-icon-size=48",
+                "dash-to-dock@micxgx.gmail.com:/org/gnome/shell/extensions/dash-to-dock/dock-position='BOTTOM'",
+            ]
+        );
+        assert_eq!(
+            actual,
+            vec![
+                "dash-to-dock@micxgx.gmail.com:/org/gnome/shell/extensions/dash-to-dock/dock-position='LEFT'"
+            ]
+        );
+    }
+
+    struct OneExtensionApi(GnomeExtensionInfo);
+
+    impl ExtensionsApi for OneExtensionApi {
+        fn info(&self, lookup: &ExtensionLookup) -> Result<Option<GnomeExtensionInfo>> {
+            Ok(match lookup {
+                ExtensionLookup::Uuid(uuid) if *uuid == self.0.uuid => Some(self.0.clone()),
+                _ => None,
+            })
+        }
+
+        fn search(&self, _query: &str) -> Result<Vec<GnomeExtensionInfo>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Records `program args...` and succeeds.
+    #[derive(Default)]
+    struct Recorder {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl CommandRunner for Recorder {
+        fn run_output(
+            &self,
+            program: &str,
+            args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<std::process::Output> {
+            use std::os::unix::process::ExitStatusExt;
+
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", program, args.join(" ")));
+            Ok(std::process::Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+        }
+
+        fn run_status(
+            &self,
+            _program: &str,
+            _args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<std::process::ExitStatus> {
+            unreachable!("installs capture output")
+        }
+    }
+
+    fn caffeine() -> OneExtensionApi {
+        OneExtensionApi(
+            serde_json::from_value(serde_json::json!({
+                "uuid": "caffeine@patapon.info",
+                "name": "Caffeine",
+                "pk": 517,
+                "shell_version_map": {"47": {"pk": 61890, "version": 55}}
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn install_from_web_downloads_release_for_running_shell() {
+        let runner = Recorder::default();
+        install_from_web("caffeine@patapon.info", Some("47.2"), &caffeine(), &runner).unwrap();
+
+        let calls = runner.calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].starts_with("curl -fsSL -o "));
+        assert!(
+            calls[0].ends_with("caffeine%40patapon.info.shell-extension.zip?version_tag=61890")
+        );
+        assert!(calls[1].starts_with("gnome-extensions install --force "));
+    }
+
+    #[test]
+    fn install_from_web_reports_missing_release_without_downloading() {
+        let runner = Recorder::default();
+
+        let err = install_from_web("caffeine@patapon.info", Some("45.1"), &caffeine(), &runner)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("no release for GNOME Shell 45.1 (supports 47)")
+        );
+        let err =
+            install_from_web("other@example.com", Some("47"), &caffeine(), &runner).unwrap_err();
+        assert!(err.to_string().contains("not found"));
+        assert!(runner.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sync_plan_describes_downloads_separately_from_enables() {
+        let plan = ExtensionSyncPlan {
+            to_enable: vec![
+                ExtensionToSync {
+                    uuid: "a@x".to_string(),
+                    state: ExtensionState::Disabled,
+                },
+                ExtensionToSync {
+                    uuid: "b@x".to_string(),
+                    state: ExtensionState::FromWeb,
+                },
+                ExtensionToSync {
+                    uuid: "c@x".to_string(),
+                    state: ExtensionState::NotInstalled,
+                },
+            ],
+            to_disable: Vec::new(),
+            settings: Vec::new(),
+            checked: 3,
+        };
+        assert!(!plan.is_empty());
+
+        let summary = plan.describe();
+        let ops: Vec<(Verb, &str)> = summary
+            .operations
+            .iter()
+            .map(|op| (op.verb, op.target.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (Verb::Enable, "extension:a@x"),
+                (Verb::Install, "extension:b@x"),
+                (Verb::Skip, "extension:c@x"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! External repo command implementation.
+
+use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::manifest::ExternalReposManifest;
+use crate::output::Output;
+use crate::pipeline::ExecutionPlan;
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Args)]
+pub struct ExternalRepoArgs {
+    #[command(subcommand)]
+    pub action: ExternalRepoAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExternalRepoAction {
+    /// Pin a repo's signing key to the fingerprint it serves now
+    ///
+    /// Fetches the key at the repo's `gpg_key` and records its fingerprint
+    /// as `gpg_fingerprint`. From then on the image build refuses a key
+    /// that doesn't match, so check the printed fingerprint against the
+    /// one the vendor publishes before committing it.
+    PinKey {
+        /// Repo name (as in manifests/external-repos.json)
+        name: String,
+    },
+}
+
+pub fn run(args: ExternalRepoArgs, plan: &ExecutionPlan) -> Result<()> {
+    match args.action {
+        ExternalRepoAction::PinKey { name } => handle_pin_key(&name, plan),
+    }
+}
+
+fn handle_pin_key(name: &str, plan: &ExecutionPlan) -> Result<()> {
+    let path = crate::repo::find_repo_path()?.join(ExternalReposManifest::PROJECT_PATH);
+    let mut manifest = ExternalReposManifest::load(&path)?;
+    let repo = manifest
+        .repos
+        .iter_mut()
+        .find(|r| r.name == name)
+        .with_context(|| format!("External repo '{}' not found", name))?;
+
+    let key = fetch_key(&repo.gpg_key, plan.runner())?;
+    let fingerprint = match bkt_common::gpg::fingerprints(&key)
+        .with_context(|| format!("{} is not a usable GPG key", repo.gpg_key))?
+        .as_slice()
+    {
+        [fingerprint] => fingerprint.clone(),
+        many => bail!(
+            "{} holds {} keys ({}); pin-key needs exactly one",
+            repo.gpg_key,
+            many.len(),
+            many.join(", ")
+        ),
+    };
+
+    if repo
+        .gpg_fingerprint
+        .as_deref()
+        .map(bkt_common::gpg::normalize)
+        == Some(fingerprint.clone())
+    {
+        Output::info(format!("'{}' is already pinned to {}", name, fingerprint));
+        return Ok(());
+    }
+    if let Some(old) = &repo.gpg_fingerprint {
+        Output::warning(format!(
+            "'{}' was pinned to {}; the key now served is different",
+            name, old
+        ));
+    }
+
+    if plan.dry_run {
+        Output::dry_run(format!("Would pin '{}' to {}", name, fingerprint));
+        return Ok(());
+    }
+    repo.gpg_fingerprint = Some(fingerprint.clone());
+    manifest.save(&path)?;
+    Output::success(format!("Pinned '{}' to {}", name, fingerprint));
+    Output::hint("Check this against the fingerprint the vendor publishes before committing");
+    Ok(())
+}
+
+/// Fetch a key the way the build does: a `file://` path or a URL.
+fn fetch_key(url: &str, runner: &dyn CommandRunner) -> Result<Vec<u8>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return std::fs::read(path).with_context(|| format!("Failed to read {}", path));
+    }
+    let output = runner
+        .run_output("curl", &["-s", "-f", "-L", url], &CommandOptions::default())
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    if !output.status.success() {
+        bail!(
+            "Failed to fetch {} (curl exited with {})",
+            url,
+            output.status
+        );
+    }
+    Ok(output.stdout)
+}
@@ -2,10 +2,11 @@
 
 use crate::command_runner::CommandRunner;
 use crate::manifest::{HostBinariesManifest, HostBinary, HostBinarySource};
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
 use crate::pr::ensure_repo;
 use anyhow::{Context, Result, bail};
@@ -13,8 +14,8 @@ use clap::{Args, Subcommand};
 use fetchbin::manifest::{RuntimeVersionSpec, SourceSpec};
 use fetchbin::source::SourceConfig;
 use fetchbin::{
-    BinarySource, CargoSource, FetchError, GithubSource, InstalledBinary, Manifest, PackageSpec,
-    RuntimePool, RuntimeVersion,
+    BinarySource, CargoSource, FetchError, FileSource, GithubSource, GitlabSource, InstalledBinary,
+    LibcRequirement, Manifest, PackageSpec, RuntimePool, RuntimeVersion,
 };
 use std::collections::HashSet;
 use std::fs;
@@ -32,14 +33,17 @@ pub struct FetchbinArgs {
 pub enum FetchbinAction {
     /// Add a host binary to the manifest
     Add {
-        /// Source spec (e.g., npm:turbo, cargo:bat, github:owner/repo)
+        /// Source spec (e.g., npm:turbo, cargo:bat, github:owner/repo, gitlab:group/project)
         spec: String,
         /// Optional binary name (useful when packages expose multiple binaries)
         #[arg(long)]
         binary: Option<String>,
-        /// GitHub asset pattern (only for github sources)
+        /// Release asset pattern (only for github and gitlab sources)
         #[arg(long)]
         asset: Option<String>,
+        /// Consider pre-releases and `-rc`/nightly tags (only for github sources)
+        #[arg(long)]
+        include_prerelease: bool,
     },
     /// Remove a host binary from the manifest
     Remove {
@@ -49,8 +53,8 @@ pub enum FetchbinAction {
     /// List host binaries in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Sync: install binaries from manifest
     Sync,
@@ -64,9 +68,10 @@ pub fn run(args: FetchbinArgs, plan: &ExecutionPlan) -> Result<()> {
             spec,
             binary,
             asset,
-        } => handle_add(&spec, binary, asset, plan),
+            include_prerelease,
+        } => handle_add(&spec, binary, asset, include_prerelease, plan),
         FetchbinAction::Remove { name } => handle_remove(&name, plan),
-        FetchbinAction::List { format } => handle_list(&format, plan),
+        FetchbinAction::List { format } => handle_list(format, plan),
         FetchbinAction::Sync => handle_sync(plan),
         FetchbinAction::Capture => handle_capture(plan),
     }
@@ -80,6 +85,7 @@ fn handle_add(
     spec: &str,
     binary: Option<String>,
     asset: Option<String>,
+    include_prerelease: bool,
     plan: &ExecutionPlan,
 ) -> Result<()> {
     let manifests_dir = get_manifest_path(plan.runner())?;
@@ -88,15 +94,24 @@ fn handle_add(
     let mut spec = PackageSpec::from_str(spec)?;
     if let Some(asset) = asset.clone() {
         match &mut spec.source {
-            SourceConfig::Github { asset_pattern, .. } => {
+            SourceConfig::Github { asset_pattern, .. }
+            | SourceConfig::Gitlab { asset_pattern, .. } => {
                 *asset_pattern = Some(asset);
             }
-            _ => bail!("--asset is only supported for github sources"),
+            _ => bail!("--asset is only supported for github and gitlab sources"),
+        }
+    }
+    if include_prerelease {
+        match &mut spec.source {
+            SourceConfig::Github {
+                include_prerelease, ..
+            } => *include_prerelease = true,
+            _ => bail!("--include-prerelease is only supported for github sources"),
         }
     }
 
     let name = binary.clone().unwrap_or_else(|| spec.name.clone());
-    let entry = host_binary_from_spec(name.clone(), &spec, binary.clone());
+    let entry = host_binary_from_spec(name.clone(), &spec, binary.clone())?;
 
     let already_exists = manifest.find(&name).is_some();
     if already_exists {
@@ -184,7 +199,7 @@ struct FetchbinListEntry {
     installed_version: Option<String>,
 }
 
-fn handle_list(format: &str, plan: &ExecutionPlan) -> Result<()> {
+fn handle_list(format: OutputFormat, plan: &ExecutionPlan) -> Result<()> {
     let manifests_dir = get_manifest_path(plan.runner())?;
     let manifest = HostBinariesManifest::load_from_dir(&manifests_dir)?;
     let fetchbin_manifest = load_fetchbin_manifest().unwrap_or_default();
@@ -203,7 +218,7 @@ fn handle_list(format: &str, plan: &ExecutionPlan) -> Result<()> {
         });
     }
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&rows)?);
         return Ok(());
     }
@@ -281,16 +296,18 @@ fn handle_sync(plan: &ExecutionPlan) -> Result<()> {
     let cmd = FetchbinSyncCommand;
     let sync_plan = cmd.plan(&plan_ctx)?;
 
-    if sync_plan.is_empty() {
+    if sync_plan.is_empty() && !plan.json_output() {
         Output::success("All fetchbin entries are already installed.");
         return Ok(());
     }
 
-    let summary = sync_plan.describe();
-    print!("{}", summary);
+    let summary = sync_plan.describe().with_subsystem("fetchbin");
+    print_summary(&summary, plan, plan.dry_run)?;
 
     if plan.dry_run {
-        Output::info("Run without --dry-run to apply these changes.");
+        if !plan.json_output() {
+            Output::info("Run without --dry-run to apply these changes.");
+        }
         return Ok(());
     }
 
@@ -299,7 +316,7 @@ fn handle_sync(plan: &ExecutionPlan) -> Result<()> {
     exec_ctx.set_total_ops(total_ops);
 
     let report = sync_plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report.with_subsystem("fetchbin"), plan)?;
 
     Ok(())
 }
@@ -315,22 +332,24 @@ fn handle_capture(plan: &ExecutionPlan) -> Result<()> {
     let cmd = FetchbinCaptureCommand;
     let capture_plan = cmd.plan(&plan_ctx)?;
 
-    if capture_plan.is_empty() {
+    if capture_plan.is_empty() && !plan.json_output() {
         Output::success("Nothing to capture. All fetchbin binaries are already in the manifest.");
         return Ok(());
     }
 
-    let summary = capture_plan.describe();
-    print!("{}", summary);
+    let summary = capture_plan.describe().with_subsystem("fetchbin");
+    print_summary(&summary, plan, plan.dry_run)?;
 
     if plan.dry_run {
-        Output::info("Run without --dry-run to capture these binaries.");
+        if !plan.json_output() {
+            Output::info("Run without --dry-run to capture these binaries.");
+        }
         return Ok(());
     }
 
     let mut exec_ctx = ExecuteContext::new(plan.clone());
     let report = capture_plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report.with_subsystem("fetchbin"), plan)?;
 
     Ok(())
 }
@@ -564,7 +583,7 @@ fn fetchbin_manifest_path() -> PathBuf {
     fetchbin::Manifest::default_path().unwrap_or_else(|| fetchbin_data_dir().join("manifest.json"))
 }
 
-fn load_fetchbin_manifest() -> Result<fetchbin::Manifest> {
+pub(crate) fn load_fetchbin_manifest() -> Result<fetchbin::Manifest> {
     let path = fetchbin_manifest_path();
     fetchbin::Manifest::load(&path).map_err(|err| anyhow::anyhow!(err.to_string()))
 }
@@ -597,6 +616,8 @@ fn version_matches(entry: &HostBinary, installed: &InstalledBinary) -> bool {
         SourceSpec::Npm { version, .. } => version == expected,
         SourceSpec::Cargo { version, .. } => version == expected,
         SourceSpec::Github { version, .. } => version == expected,
+        SourceSpec::Gitlab { version, .. } => version == expected,
+        SourceSpec::File { version, .. } => version == expected,
     }
 }
 
@@ -604,15 +625,20 @@ fn binary_name_for_entry(entry: &HostBinary) -> String {
     entry.binary.clone().unwrap_or_else(|| entry.name.clone())
 }
 
-fn source_label(source: &HostBinarySource) -> String {
+pub(crate) fn source_label(source: &HostBinarySource) -> String {
     match source {
         HostBinarySource::Npm { package } => format!("npm:{}", package),
         HostBinarySource::Cargo { crate_name } => format!("cargo:{}", crate_name),
         HostBinarySource::Github { repo, .. } => format!("github:{}", repo),
+        HostBinarySource::Gitlab { repo, .. } => format!("gitlab:{}", repo),
     }
 }
 
-fn host_binary_from_spec(name: String, spec: &PackageSpec, binary: Option<String>) -> HostBinary {
+fn host_binary_from_spec(
+    name: String,
+    spec: &PackageSpec,
+    binary: Option<String>,
+) -> Result<HostBinary> {
     let source = match &spec.source {
         SourceConfig::Npm { package } => HostBinarySource::Npm {
             package: package.clone(),
@@ -623,18 +649,30 @@ fn host_binary_from_spec(name: String, spec: &PackageSpec, binary: Option<String
         SourceConfig::Github {
             repo,
             asset_pattern,
+            include_prerelease,
         } => HostBinarySource::Github {
             repo: repo.clone(),
             asset_pattern: asset_pattern.clone(),
+            include_prerelease: *include_prerelease,
+        },
+        SourceConfig::Gitlab {
+            repo,
+            asset_pattern,
+        } => HostBinarySource::Gitlab {
+            repo: repo.clone(),
+            asset_pattern: asset_pattern.clone(),
         },
+        SourceConfig::File { .. } => {
+            bail!("file: sources are machine-local; install them with `fetchbin install` instead")
+        }
     };
 
-    HostBinary {
+    Ok(HostBinary {
         name,
         source,
         version: spec.version_req.clone(),
         binary,
-    }
+    })
 }
 
 fn host_binary_from_installed(name: &str, installed: &InstalledBinary) -> Option<HostBinary> {
@@ -658,6 +696,7 @@ fn host_binary_from_installed(name: &str, installed: &InstalledBinary) -> Option
             repo,
             asset,
             version,
+            include_prerelease,
         } => (
             HostBinarySource::Github {
                 repo: repo.clone(),
@@ -666,9 +705,27 @@ fn host_binary_from_installed(name: &str, installed: &InstalledBinary) -> Option
                 } else {
                     Some(asset.clone())
                 },
+                include_prerelease: *include_prerelease,
+            },
+            Some(version.clone()),
+        ),
+        SourceSpec::Gitlab {
+            repo,
+            asset,
+            version,
+        } => (
+            HostBinarySource::Gitlab {
+                repo: repo.clone(),
+                asset_pattern: if asset == "platform" {
+                    None
+                } else {
+                    Some(asset.clone())
+                },
             },
             Some(version.clone()),
         ),
+        // Local paths don't exist on other machines
+        SourceSpec::File { .. } => return None,
     };
 
     Some(HostBinary {
@@ -690,9 +747,18 @@ fn package_spec_from_host_binary(entry: &HostBinary) -> PackageSpec {
         HostBinarySource::Github {
             repo,
             asset_pattern,
+            include_prerelease,
         } => SourceConfig::Github {
             repo: repo.clone(),
             asset_pattern: asset_pattern.clone(),
+            include_prerelease: *include_prerelease,
+        },
+        HostBinarySource::Gitlab {
+            repo,
+            asset_pattern,
+        } => SourceConfig::Gitlab {
+            repo: repo.clone(),
+            asset_pattern: asset_pattern.clone(),
         },
     };
 
@@ -701,6 +767,8 @@ fn package_spec_from_host_binary(entry: &HostBinary) -> PackageSpec {
         version_req: entry.version.clone(),
         source,
         binary_name: entry.binary.clone(),
+        all_bins: false,
+        fallback: None,
     }
 }
 
@@ -739,11 +807,16 @@ fn install_host_binary(
     fetchbin_manifest.binaries.insert(
         binary_name.clone(),
         InstalledBinary {
-            source: source_spec_from_package(&spec, &latest.version),
+            source: source_spec_from_package(&spec, &latest),
             binary: binary_name,
+            version_req: spec.version_req.clone(),
+            binaries: Vec::new(),
             sha256: fetched.sha256,
             installed_at: current_timestamp(),
             runtime: runtime_spec_from_version(fetched.runtime_used.as_ref()),
+            pinned_version: None,
+            native_package: fetched.native_package,
+            libc: LibcRequirement::detect(&fetched.binary_path),
         },
     );
 
@@ -764,9 +837,11 @@ fn remove_installed_binary(entry: &HostBinary) -> Result<bool> {
         None => return Ok(false),
     };
 
-    let link_path = bin_dir.join(&installed.binary);
-    if link_path.exists() {
-        fs::remove_file(&link_path)?;
+    for name in installed.linked_binaries() {
+        let link_path = bin_dir.join(name);
+        if link_path.exists() {
+            fs::remove_file(&link_path)?;
+        }
     }
 
     let store_path = store_dir_for_installed(&installed, &store_dir);
@@ -785,6 +860,8 @@ fn resolve_versions(spec: &PackageSpec, data_dir: &Path) -> Result<Vec<fetchbin:
         SourceConfig::Npm { .. } => fetchbin::source::npm::NpmSource::new().resolve(spec)?,
         SourceConfig::Cargo { .. } => CargoSource::new(data_dir.to_path_buf()).resolve(spec)?,
         SourceConfig::Github { .. } => GithubSource::new().resolve(spec)?,
+        SourceConfig::Gitlab { .. } => GitlabSource::new().resolve(spec)?,
+        SourceConfig::File { .. } => FileSource::new().resolve(spec)?,
     };
     Ok(resolved)
 }
@@ -806,6 +883,10 @@ fn fetch_version(
         SourceConfig::Github { .. } => {
             GithubSource::new().fetch(spec, version, target_dir, runtime)?
         }
+        SourceConfig::Gitlab { .. } => {
+            GitlabSource::new().fetch(spec, version, target_dir, runtime)?
+        }
+        SourceConfig::File { .. } => FileSource::new().fetch(spec, version, target_dir, runtime)?,
     };
     Ok(fetched)
 }
@@ -824,6 +905,14 @@ fn store_dir_for_spec(spec: &PackageSpec, version: &str, store_root: &Path) -> P
             .join("github")
             .join(sanitize_component(repo))
             .join(version),
+        SourceConfig::Gitlab { repo, .. } => store_root
+            .join("gitlab")
+            .join(sanitize_component(repo))
+            .join(version),
+        SourceConfig::File { .. } => store_root
+            .join("file")
+            .join(sanitize_component(&spec.name))
+            .join(version),
     }
 }
 
@@ -844,6 +933,16 @@ fn store_dir_for_installed(installed: &InstalledBinary, store_root: &Path) -> Pa
             .join("github")
             .join(sanitize_component(repo))
             .join(version),
+        SourceSpec::Gitlab { repo, version, .. } => store_root
+            .join("gitlab")
+            .join(sanitize_component(repo))
+            .join(version),
+        SourceSpec::File { path, version, .. } => store_root
+            .join("file")
+            .join(sanitize_component(&fetchbin::source::file::default_name(
+                path,
+            )))
+            .join(version),
     }
 }
 
@@ -858,7 +957,11 @@ fn binary_name_from_path(path: &Path) -> Result<String> {
         .context("binary path missing file name")
 }
 
-fn source_spec_from_package(spec: &PackageSpec, version: &str) -> SourceSpec {
+fn source_spec_from_package(
+    spec: &PackageSpec,
+    resolved: &fetchbin::ResolvedVersion,
+) -> SourceSpec {
+    let version = resolved.version.as_str();
     match &spec.source {
         SourceConfig::Npm { package } => SourceSpec::Npm {
             package: package.clone(),
@@ -871,10 +974,25 @@ fn source_spec_from_package(spec: &PackageSpec, version: &str) -> SourceSpec {
         SourceConfig::Github {
             repo,
             asset_pattern,
+            include_prerelease,
         } => SourceSpec::Github {
             repo: repo.clone(),
             asset: asset_pattern.as_deref().unwrap_or("platform").to_string(),
             version: version.to_string(),
+            include_prerelease: *include_prerelease,
+        },
+        SourceConfig::Gitlab {
+            repo,
+            asset_pattern,
+        } => SourceSpec::Gitlab {
+            repo: repo.clone(),
+            asset: asset_pattern.as_deref().unwrap_or("platform").to_string(),
+            version: version.to_string(),
+        },
+        SourceConfig::File { path } => SourceSpec::File {
+            path: path.clone(),
+            sha256: resolved.checksum.clone(),
+            version: version.to_string(),
         },
     }
 }
@@ -890,6 +1008,8 @@ fn installed_version(installed: &InstalledBinary) -> String {
         SourceSpec::Npm { version, .. } => version.clone(),
         SourceSpec::Cargo { version, .. } => version.clone(),
         SourceSpec::Github { version, .. } => version.clone(),
+        SourceSpec::Gitlab { version, .. } => version.clone(),
+        SourceSpec::File { version, .. } => version.clone(),
     }
 }
 
@@ -1,20 +1,23 @@
 //! Flatpak command implementation.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
-use crate::context::{CommandDomain, run_command};
+use crate::commands::capture::CapturePlan;
+use crate::context::{CommandDomain, HostExec, run_on_host};
 use crate::manifest::{
-    FlatpakApp, FlatpakAppsManifest, FlatpakOverrides, FlatpakRemotesManifest, FlatpakScope,
+    FlatpakApp, FlatpakAppsManifest, FlatpakOverrides, FlatpakRemote, FlatpakRemotesManifest,
+    FlatpakScope, commits_match, merge_override_flags,
 };
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, PlanWarning,
-    Plannable, Verb,
+    Plannable, Verb, print_report, print_summary,
 };
-use crate::validation::validate_flatpak_app;
-use anyhow::{Context, Result};
+use crate::validation::{fetch_flatpakrepo, validate_flatpak_app};
+use anyhow::{Context, Result, anyhow, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Args)]
 pub struct FlatpakArgs {
@@ -24,10 +27,14 @@ pub struct FlatpakArgs {
 
 #[derive(Debug, Subcommand)]
 pub enum FlatpakAction {
-    /// Add a Flatpak app to the manifest
+    /// Add Flatpak apps to the manifest
+    ///
+    /// Apps are installed one at a time; only those that install are added
+    /// to the manifest, and the rest are reported as failures.
     Add {
-        /// Application ID (e.g., org.gnome.Calculator)
-        app_id: String,
+        /// Application IDs (e.g., org.gnome.Calculator)
+        #[arg(required = true)]
+        app_ids: Vec<String>,
         /// Remote name (default: flathub)
         #[arg(short, long, default_value = "flathub")]
         remote: String,
@@ -44,13 +51,58 @@ pub enum FlatpakAction {
         app_id: String,
     },
     /// List all Flatpak apps in the manifest
+    ///
+    /// Installed apps show the runtime they use and whether that runtime
+    /// is end-of-life.
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// List installed apps on end-of-life runtimes, with what to do about them
+    CheckRuntimes {
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Pin an app to a specific commit
+    ///
+    /// Records the commit in the manifest, moves the installed app to it,
+    /// and masks it so `flatpak update` leaves it alone. Apps not yet in
+    /// the manifest are added.
+    Pin {
+        /// Application ID (e.g., org.gnome.Calculator)
+        app_id: String,
+        /// Commit to pin (default: the currently installed commit)
+        #[arg(long)]
+        commit: Option<String>,
+        /// Remote name, if the app is not yet in the manifest
+        #[arg(short, long, default_value = "flathub")]
+        remote: String,
+        /// Installation scope, if the app is not yet in the manifest
+        #[arg(short, long, default_value = "system")]
+        scope: String,
     },
+    /// Unpin an app so it follows its branch again
+    Unpin {
+        /// Application ID to unpin
+        app_id: String,
+    },
+    /// Manage `flatpak override` permissions recorded for an app
+    ///
+    /// With no flags, shows the recorded overrides. Flags are merged into
+    /// the manifest (a deny replaces an allow for the same target) and
+    /// applied with `flatpak override`.
+    Permissions(PermissionsArgs),
+    /// Manage Flatpak remotes in the manifest
+    #[command(subcommand)]
+    Remote(RemoteAction),
     /// Sync: install apps from manifest
-    Sync,
+    Sync {
+        /// Install from up to this many remotes at once
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+    },
     /// Capture installed flatpaks to manifest
     Capture {
         /// Show what would be done without making changes
@@ -62,34 +114,180 @@ pub enum FlatpakAction {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum RemoteAction {
+    /// Add a remote from its `.flatpakrepo` URL
+    ///
+    /// The file is fetched and must carry a GPG key; its title, repo URL
+    /// and key digest are recorded alongside the URL.
+    Add {
+        /// Remote name (e.g., flathub-beta)
+        name: String,
+        /// `.flatpakrepo` URL
+        url: String,
+        /// Installation scope (system or user)
+        #[arg(short, long, default_value = "system")]
+        scope: String,
+        /// Add even if the URL can't be validated, or replace a remote of
+        /// the same name with a different URL
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct PermissionsArgs {
+    #[command(subcommand)]
+    pub action: Option<PermissionsAction>,
+    /// Application ID (e.g., org.mozilla.firefox)
+    pub app_id: Option<String>,
+    /// Grant filesystem access (e.g., ~/Games:ro, xdg-download)
+    #[arg(long, value_name = "PATH")]
+    pub allow_filesystem: Vec<String>,
+    /// Revoke filesystem access
+    #[arg(long, value_name = "PATH")]
+    pub deny_filesystem: Vec<String>,
+    /// Expose a socket (e.g., wayland, x11, pulseaudio)
+    #[arg(long, value_name = "SOCKET")]
+    pub allow_socket: Vec<String>,
+    /// Hide a socket
+    #[arg(long, value_name = "SOCKET")]
+    pub deny_socket: Vec<String>,
+    /// Expose a device (e.g., dri, all)
+    #[arg(long, value_name = "DEVICE")]
+    pub allow_device: Vec<String>,
+    /// Hide a device
+    #[arg(long, value_name = "DEVICE")]
+    pub deny_device: Vec<String>,
+    /// Set an environment variable
+    #[arg(long, value_name = "VAR=VALUE")]
+    pub env: Vec<String>,
+    /// Drop all recorded overrides before applying the flags above
+    #[arg(long)]
+    pub reset: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PermissionsAction {
+    /// Import an app's existing override file into the manifest
+    Capture {
+        /// Application ID (e.g., org.mozilla.firefox)
+        app_id: String,
+    },
+}
+
+impl PermissionsArgs {
+    /// Translate the allow/deny options into `flatpak override` flags.
+    fn override_flags(&self) -> Result<Vec<String>> {
+        let mut flags = Vec::new();
+        let groups = [
+            ("--filesystem", &self.allow_filesystem),
+            ("--nofilesystem", &self.deny_filesystem),
+            ("--socket", &self.allow_socket),
+            ("--nosocket", &self.deny_socket),
+            ("--device", &self.allow_device),
+            ("--nodevice", &self.deny_device),
+        ];
+        for (flag, values) in groups {
+            flags.extend(values.iter().map(|v| format!("{}={}", flag, v)));
+        }
+        for var in &self.env {
+            if !var.contains('=') {
+                bail!("--env expects VAR=VALUE, got '{}'", var);
+            }
+            flags.push(format!("--env={}", var));
+        }
+        Ok(flags)
+    }
+}
+
 fn install_flatpak(app: &FlatpakApp, runner: &dyn CommandRunner) -> Result<bool> {
+    install_from_remote(&app.remote, app.scope, &[app.id.as_str()], runner)
+}
+
+/// Install several apps from one remote with a single `flatpak install`,
+/// so shared runtimes are resolved and downloaded once.
+fn install_from_remote(
+    remote: &str,
+    scope: FlatpakScope,
+    app_ids: &[&str],
+    runner: &dyn CommandRunner,
+) -> Result<bool> {
+    let scope_flag = match scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
+
+    let mut args = vec![
+        "install",
+        "-y",
+        "--noninteractive",
+        "--or-update",
+        scope_flag,
+        remote,
+    ];
+    args.extend_from_slice(app_ids);
+
+    let status = runner
+        .run_status("flatpak", &args, &CommandOptions::default())
+        .context("Failed to run flatpak install")?;
+
+    Ok(status.success())
+}
+
+fn uninstall_flatpak(
+    app_id: &str,
+    scope: FlatpakScope,
+    runner: &dyn CommandRunner,
+) -> Result<bool> {
+    let scope_flag = match scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
+
+    let status = runner
+        .run_status(
+            "flatpak",
+            &["uninstall", "-y", "--noninteractive", scope_flag, app_id],
+            &CommandOptions::default(),
+        )
+        .context("Failed to run flatpak uninstall")?;
+
+    Ok(status.success())
+}
+
+/// Move an installed flatpak to a specific commit.
+fn update_to_commit(app: &FlatpakApp, commit: &str, runner: &dyn CommandRunner) -> Result<bool> {
     let scope_flag = match app.scope {
         FlatpakScope::System => "--system",
         FlatpakScope::User => "--user",
     };
+    let commit_flag = format!("--commit={}", commit);
 
     let status = runner
         .run_status(
             "flatpak",
             &[
-                "install",
+                "update",
                 "-y",
                 "--noninteractive",
-                "--or-update",
                 scope_flag,
-                &app.remote,
+                &commit_flag,
                 &app.id,
             ],
             &CommandOptions::default(),
         )
-        .context("Failed to run flatpak install")?;
+        .context("Failed to run flatpak update")?;
 
     Ok(status.success())
 }
 
-fn uninstall_flatpak(
+/// Add or remove a `flatpak mask` entry so updates skip the app.
+fn set_masked(
     app_id: &str,
     scope: FlatpakScope,
+    masked: bool,
     runner: &dyn CommandRunner,
 ) -> Result<bool> {
     let scope_flag = match scope {
@@ -97,20 +295,27 @@ fn uninstall_flatpak(
         FlatpakScope::User => "--user",
     };
 
+    let mut args = vec!["mask", scope_flag];
+    if !masked {
+        args.push("--remove");
+    }
+    args.push(app_id);
+
     let status = runner
-        .run_status(
-            "flatpak",
-            &["uninstall", "-y", "--noninteractive", scope_flag, app_id],
-            &CommandOptions::default(),
-        )
-        .context("Failed to run flatpak uninstall")?;
+        .run_status("flatpak", &args, &CommandOptions::default())
+        .context("Failed to run flatpak mask")?;
 
     Ok(status.success())
 }
 
+/// Move an app to its pinned commit and mask it against updates.
+fn apply_pin(app: &FlatpakApp, commit: &str, runner: &dyn CommandRunner) -> Result<bool> {
+    Ok(update_to_commit(app, commit, runner)? && set_masked(&app.id, app.scope, true, runner)?)
+}
+
 fn is_installed(app_id: &str, runner: &dyn CommandRunner) -> bool {
-    runner
-        .run_output("flatpak", &["info", app_id], &CommandOptions::default())
+    HostExec::new(runner)
+        .run_on_host("flatpak", &["info", app_id])
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
@@ -148,100 +353,201 @@ fn apply_overrides(
     Ok(status.success())
 }
 
-pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
-    let runner = plan.runner();
+/// Remove all overrides for a flatpak app (`flatpak override --reset`).
+fn reset_overrides(app_id: &str, scope: FlatpakScope, runner: &dyn CommandRunner) -> Result<bool> {
+    let scope_flag = match scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
 
-    match args.action {
-        FlatpakAction::Add {
-            app_id,
-            remote,
-            scope,
-            force,
-        } => {
-            // Validate that flatpak operations are allowed in this context
-            plan.validate_domain(CommandDomain::Flatpak)?;
+    let status = runner
+        .run_status(
+            "flatpak",
+            &["override", scope_flag, "--reset", app_id],
+            &CommandOptions::default(),
+        )
+        .context("Failed to run flatpak override --reset")?;
 
-            // Validate that the app exists on the remote
-            if !force {
-                validate_flatpak_app(runner, &app_id, &remote)?;
-            }
+    Ok(status.success())
+}
 
-            let scope: FlatpakScope = scope.parse()?;
+/// The overrides currently in effect for an app, as sorted CLI flags.
+pub fn live_flatpak_overrides(app_id: &str, scope: FlatpakScope) -> Vec<String> {
+    FlatpakOverrides::load_for_app(app_id, scope)
+        .map(|o| o.to_cli_flags())
+        .unwrap_or_default()
+}
 
-            // Check if already in manifest
-            let mut manifest = FlatpakAppsManifest::load_repo()?;
+/// Recorded override flags that are not in effect on the system.
+fn missing_override_flags(app: &FlatpakApp, live: &[String]) -> Vec<String> {
+    app.overrides
+        .iter()
+        .flatten()
+        .filter(|flag| !live.contains(flag))
+        .cloned()
+        .collect()
+}
 
-            let already_exists = manifest.find(&app_id).is_some();
-
-            if already_exists {
-                Output::warning(format!("Flatpak already in manifest: {}", app_id));
-            } else if plan.should_update_manifest() {
-                let app = FlatpakApp {
-                    id: app_id.clone(),
-                    remote: remote.clone(),
-                    scope,
-                    branch: None,
-                    commit: None,
-                    overrides: None,
-                };
-                manifest.upsert(app.clone());
-                manifest.save_repo()?;
-                Output::success(format!(
-                    "Added to manifest: {} ({}, {})",
-                    app_id, remote, scope
-                ));
-            } else if plan.dry_run {
-                Output::dry_run(format!(
-                    "Would add to manifest: {} ({}, {})",
-                    app_id, remote, scope
-                ));
-            }
+/// Priority flatpak gives a remote when none is set.
+const DEFAULT_REMOTE_PRIORITY: i32 = 1;
 
-            // Install the flatpak
-            if plan.should_execute_locally() && !is_installed(&app_id, runner) {
-                let spinner = Output::spinner(format!("Installing {}...", app_id));
-                let app = FlatpakApp {
-                    id: app_id.clone(),
-                    remote: remote.clone(),
-                    scope,
-                    branch: None,
-                    commit: None,
-                    overrides: None,
-                };
-                if install_flatpak(&app, runner)? {
-                    spinner.finish_success(format!("Installed {}", app_id));
-                } else {
-                    spinner.finish_error(format!("Failed to install {}", app_id));
-                }
-            } else if plan.dry_run && !is_installed(&app_id, runner) {
-                Output::dry_run(format!("Would install: {}", app_id));
-            } else if is_installed(&app_id, runner) {
-                Output::info(format!("Already installed: {}", app_id));
-            }
+/// A remote as configured in one flatpak installation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfiguredRemote {
+    pub name: String,
+    pub priority: i32,
+    pub subset: Option<String>,
+    pub filter: Option<String>,
+}
 
-            // Create PR if needed
-            if plan.should_create_pr() && !already_exists {
-                let mut system_manifest = FlatpakAppsManifest::load_repo()?;
-                let app_for_pr = FlatpakApp {
-                    id: app_id.clone(),
-                    remote: remote.clone(),
-                    scope,
-                    branch: None,
-                    commit: None,
-                    overrides: None,
-                };
-                system_manifest.upsert(app_for_pr);
-                let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
-
-                plan.maybe_create_pr(
-                    "flatpak",
-                    "add",
-                    &app_id,
-                    "flatpak-apps.json",
-                    &manifest_content,
-                )?;
-            }
+/// List the remotes configured in `scope`'s installation.
+fn configured_remotes(scope: FlatpakScope, runner: &dyn CommandRunner) -> Vec<ConfiguredRemote> {
+    let scope_flag = match scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
+
+    match HostExec::new(runner).run_on_host(
+        "flatpak",
+        &[
+            "remotes",
+            scope_flag,
+            "--columns=name,priority,subset,filter",
+        ],
+    ) {
+        Ok(o) if o.status.success() => {
+            parse_configured_remotes(&String::from_utf8_lossy(&o.stdout))
         }
+        _ => Vec::new(),
+    }
+}
+
+/// Parse `flatpak remotes --columns=name,priority,subset,filter` output.
+///
+/// Columns are tab-separated when stdout isn't a terminal; unset values
+/// show as `-` or are empty.
+fn parse_configured_remotes(stdout: &str) -> Vec<ConfiguredRemote> {
+    let value = |field: Option<&str>| {
+        field
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && *v != "-")
+            .map(str::to_string)
+    };
+
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = value(fields.next())?;
+            let priority = fields
+                .next()
+                .and_then(|p| p.trim().parse().ok())
+                .unwrap_or(DEFAULT_REMOTE_PRIORITY);
+            Some(ConfiguredRemote {
+                name,
+                priority,
+                subset: value(fields.next()),
+                filter: value(fields.next()),
+            })
+        })
+        .collect()
+}
+
+/// `flatpak remote-modify` flags that bring `live` in line with `remote`.
+///
+/// Only settings the manifest records are compared.
+fn remote_modify_flags(remote: &FlatpakRemote, live: &ConfiguredRemote) -> Vec<String> {
+    let expected = FlatpakRemote {
+        priority: remote.priority.filter(|&p| p != live.priority),
+        subset: remote
+            .subset
+            .clone()
+            .filter(|s| live.subset.as_ref() != Some(s)),
+        filter_path: remote
+            .filter_path
+            .clone()
+            .filter(|f| live.filter.as_ref() != Some(f)),
+        ..remote.clone()
+    };
+    expected.option_flags()
+}
+
+/// `remote` with the priority, subset and filter from `live`, or `None` if
+/// they already match.
+fn capture_remote_settings(
+    remote: &FlatpakRemote,
+    live: &ConfiguredRemote,
+) -> Option<FlatpakRemote> {
+    let captured = FlatpakRemote {
+        // Leave flatpak's default priority unrecorded
+        priority: (remote.priority.is_some() || live.priority != DEFAULT_REMOTE_PRIORITY)
+            .then_some(live.priority),
+        subset: live.subset.clone(),
+        filter_path: live.filter.clone(),
+        ..remote.clone()
+    };
+
+    (captured.priority != remote.priority
+        || captured.subset != remote.subset
+        || captured.filter_path != remote.filter_path)
+        .then_some(captured)
+}
+
+/// Add a remote to its scope's installation.
+fn add_remote(remote: &FlatpakRemote, runner: &dyn CommandRunner) -> Result<bool> {
+    let scope_flag = match remote.scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
+
+    let flags = remote.option_flags();
+    let mut args = vec!["remote-add", scope_flag, "--if-not-exists"];
+    // .flatpakrepo URLs include GPG keys; bare repo URLs don't
+    if !remote.url.ends_with(".flatpakrepo") {
+        args.push("--no-gpg-verify");
+    }
+    args.extend(flags.iter().map(String::as_str));
+    args.push(&remote.name);
+    args.push(&remote.url);
+
+    let status = runner
+        .run_status("flatpak", &args, &CommandOptions::default())
+        .context("Failed to run flatpak remote-add")?;
+    Ok(status.success())
+}
+
+/// Change settings of an existing remote in place.
+fn modify_remote(
+    name: &str,
+    scope: FlatpakScope,
+    flags: &[String],
+    runner: &dyn CommandRunner,
+) -> Result<bool> {
+    let scope_flag = match scope {
+        FlatpakScope::System => "--system",
+        FlatpakScope::User => "--user",
+    };
+
+    let mut args = vec!["remote-modify", scope_flag];
+    args.extend(flags.iter().map(String::as_str));
+    args.push(name);
+
+    let status = runner
+        .run_status("flatpak", &args, &CommandOptions::default())
+        .context("Failed to run flatpak remote-modify")?;
+    Ok(status.success())
+}
+
+pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
+    let runner = plan.runner();
+
+    match args.action {
+        FlatpakAction::Add {
+            app_ids,
+            remote,
+            scope,
+            force,
+        } => handle_add(app_ids, remote, scope, force, plan)?,
         FlatpakAction::Remove { app_id } => {
             // Validate that flatpak operations are allowed in this context
             plan.validate_domain(CommandDomain::Flatpak)?;
@@ -298,46 +604,28 @@ pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
                 }
             }
         }
-        FlatpakAction::List { format } => {
-            let merged = FlatpakAppsManifest::load_repo()?;
-
-            if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&merged)?);
-            } else {
-                if merged.apps.is_empty() {
-                    Output::info("No flatpak apps in manifest.");
-                    return Ok(());
-                }
-
-                Output::subheader("FLATPAK APPS:");
-                println!(
-                    "{:<50} {:<12} {:<8} {} {}",
-                    "ID".cyan(),
-                    "REMOTE".cyan(),
-                    "SCOPE".cyan(),
-                    "SOURCE".cyan(),
-                    "INSTALLED".cyan()
-                );
-                Output::separator();
-
-                for app in &merged.apps {
-                    let source = "manifest".dimmed().to_string();
-                    let installed = if is_installed(&app.id, runner) {
-                        "✓".green().to_string()
-                    } else {
-                        "✗".red().to_string()
-                    };
-                    println!(
-                        "{:<50} {:<12} {:<8} {:>8} {}",
-                        app.id, app.remote, app.scope, source, installed
-                    );
-                }
-
-                Output::blank();
-                Output::info(format!("{} apps in manifest", merged.apps.len()));
+        FlatpakAction::List { format } => handle_list(format)?,
+        FlatpakAction::CheckRuntimes { format } => handle_check_runtimes(format)?,
+        FlatpakAction::Pin {
+            app_id,
+            commit,
+            remote,
+            scope,
+        } => handle_pin(app_id, commit, remote, scope, plan)?,
+        FlatpakAction::Unpin { app_id } => handle_unpin(app_id, plan)?,
+        FlatpakAction::Permissions(args) => match args.action {
+            Some(PermissionsAction::Capture { app_id }) => {
+                handle_permissions_capture(app_id, plan)?
             }
-        }
-        FlatpakAction::Sync => {
+            None => handle_permissions(args, plan)?,
+        },
+        FlatpakAction::Remote(RemoteAction::Add {
+            name,
+            url,
+            scope,
+            force,
+        }) => handle_remote_add(name, url, scope, force, plan)?,
+        FlatpakAction::Sync { jobs } => {
             // Validate that flatpak operations are allowed in this context
             plan.validate_domain(CommandDomain::Flatpak)?;
 
@@ -345,15 +633,16 @@ pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
             let plan_ctx =
                 PlanContext::new(std::env::current_dir().unwrap_or_default(), plan.clone());
 
-            let sync_plan = FlatpakSyncCommand.plan(&plan_ctx)?;
+            let sync_plan = FlatpakSyncCommand.plan(&plan_ctx)?.with_jobs(jobs);
 
-            if sync_plan.is_empty() {
+            if sync_plan.is_empty() && !plan.json_output() {
                 Output::success("All flatpaks are already installed.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", sync_plan.describe());
+            let summary = sync_plan.describe().with_subsystem("flatpak");
+            print_summary(&summary, plan, plan.dry_run)?;
 
             if plan.dry_run {
                 return Ok(());
@@ -362,7 +651,7 @@ pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = sync_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("flatpak"), plan)?;
         }
         FlatpakAction::Capture { dry_run, apply } => {
             // Note: No domain validation needed for capture since it only:
@@ -377,27 +666,581 @@ pub fn run(args: FlatpakArgs, plan: &ExecutionPlan) -> Result<()> {
 
             let capture_plan = FlatpakCaptureCommand.plan(&plan_ctx)?;
 
-            if capture_plan.is_empty() {
+            if capture_plan.is_empty() && !plan.json_output() {
                 Output::success("All installed flatpaks are already in the manifest.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", capture_plan.describe());
+            let summary = capture_plan.describe().with_subsystem("flatpak");
+            print_summary(&summary, plan, dry_run || !apply)?;
 
             if dry_run || !apply {
-                if !apply {
+                if !apply && !plan.json_output() {
                     Output::hint("Use --apply to execute this plan.");
                 }
                 return Ok(());
             }
 
-            // Execute the plan
-            let mut exec_ctx = ExecuteContext::new(plan.with_dry_run(false));
-            let report = capture_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
-        }
+            // Execute the plan
+            let mut exec_ctx = ExecuteContext::new(plan.with_dry_run(false));
+            let report = capture_plan.execute(&mut exec_ctx)?;
+            print_report(&report.with_subsystem("flatpak"), plan)?;
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Pin / Unpin
+// ============================================================================
+
+fn handle_remote_add(
+    name: String,
+    url: String,
+    scope: String,
+    force: bool,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    // Validate that flatpak operations are allowed in this context
+    plan.validate_domain(CommandDomain::Flatpak)?;
+    let runner = plan.runner();
+    let scope: FlatpakScope = scope.parse()?;
+
+    let manifest = FlatpakRemotesManifest::load_repo()?;
+    let existing = manifest.find(&name);
+    if let Some(existing) = existing
+        && existing.url != url
+        && !force
+    {
+        bail!(
+            "Remote '{}' is already in the manifest with a different URL:\n  \
+             manifest: {}\n  \
+             new:      {}\n\n\
+             Use --force to replace it.",
+            name,
+            existing.url,
+            url
+        );
+    }
+
+    // Keep priority, subset and filter when re-adding a known remote
+    let mut remote = match existing {
+        Some(existing) => FlatpakRemote {
+            url,
+            scope,
+            title: None,
+            repo_url: None,
+            gpg_key_sha256: None,
+            ..existing.clone()
+        },
+        None => FlatpakRemote {
+            name,
+            url,
+            scope,
+            filtered: None,
+            priority: None,
+            subset: None,
+            filter_path: None,
+            title: None,
+            repo_url: None,
+            gpg_key_sha256: None,
+        },
+    };
+
+    if !plan.skip_offline("Flatpak remote validation") {
+        let validated = if remote.url.ends_with(".flatpakrepo") {
+            fetch_flatpakrepo(runner, &remote.url)
+        } else {
+            Err(anyhow!(
+                "'{}' is not a .flatpakrepo URL, so the remote would have no GPG key",
+                remote.url
+            ))
+        };
+        match validated {
+            Ok(repo) => {
+                remote.title = repo.title;
+                remote.repo_url = Some(repo.url);
+                remote.gpg_key_sha256 = Some(repo.gpg_key_sha256);
+            }
+            Err(e) if force => Output::warning(format!("{:#}; adding anyway (--force)", e)),
+            Err(e) => return Err(e.context("Use --force to add the remote anyway")),
+        }
+    }
+
+    let label = match &remote.title {
+        Some(title) => format!("{} ({}, {})", remote.name, title, remote.scope),
+        None => format!("{} ({})", remote.name, remote.scope),
+    };
+
+    if plan.dry_run {
+        Output::dry_run(format!("Would add remote to manifest: {}", label));
+        if plan.pr_mode.should_execute_locally() {
+            Output::dry_run(format!("Would run flatpak remote-add: {}", remote.name));
+        }
+        return Ok(());
+    }
+
+    if plan.should_execute_locally() && !add_remote(&remote, runner)? {
+        bail!("flatpak remote-add failed for '{}'", remote.name);
+    }
+
+    if plan.should_update_manifest() {
+        let mut manifest = FlatpakRemotesManifest::load_repo()?;
+        manifest.upsert(remote.clone());
+        manifest.save_repo()?;
+        Output::success(format!("Added remote to manifest: {}", label));
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = FlatpakRemotesManifest::load_repo()?;
+        system_manifest.upsert(remote.clone());
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+        plan.maybe_create_pr(
+            "flatpak",
+            "remote-add",
+            &remote.name,
+            "flatpak-remotes.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn handle_add(
+    app_ids: Vec<String>,
+    remote: String,
+    scope: String,
+    force: bool,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    // Validate that flatpak operations are allowed in this context
+    plan.validate_domain(CommandDomain::Flatpak)?;
+    let runner = plan.runner();
+    let scope: FlatpakScope = scope.parse()?;
+
+    // Validate every app up front, so a typo doesn't stop the batch halfway
+    if !force && !plan.skip_offline("Flatpak app validation") {
+        for app_id in &app_ids {
+            validate_flatpak_app(runner, app_id, &remote)?;
+        }
+    }
+
+    let manifest = FlatpakAppsManifest::load_repo()?;
+    let mut to_add: Vec<FlatpakApp> = Vec::new();
+    for app_id in app_ids {
+        if manifest.find(&app_id).is_some() {
+            Output::warning(format!("Flatpak already in manifest: {}", app_id));
+        } else if !to_add.iter().any(|app| app.id == app_id) {
+            to_add.push(FlatpakApp {
+                id: app_id,
+                remote: remote.clone(),
+                scope,
+                branch: None,
+                commit: None,
+                pinned: false,
+                overrides: None,
+            });
+        }
+    }
+    if to_add.is_empty() {
+        return Ok(());
+    }
+
+    if plan.dry_run {
+        for app in &to_add {
+            Output::dry_run(format!(
+                "Would add to manifest: {} ({}, {})",
+                app.id, app.remote, app.scope
+            ));
+            if plan.pr_mode.should_execute_locally() && !is_installed(&app.id, runner) {
+                Output::dry_run(format!("Would install: {}", app.id));
+            }
+        }
+        return Ok(());
+    }
+
+    // Install before touching the manifest: only apps that made it onto
+    // the system are recorded
+    let total = to_add.len();
+    let (added, failed) = if plan.should_execute_locally() {
+        let mut to_install = Vec::new();
+        for app in &to_add {
+            if is_installed(&app.id, runner) {
+                Output::info(format!("Already installed: {}", app.id));
+            } else {
+                to_install.push(FlatpakToInstall { app: app.clone() });
+            }
+        }
+        let install_plan = FlatpakSyncPlan {
+            remotes_to_add: Vec::new(),
+            remotes_to_modify: Vec::new(),
+            to_install,
+            to_configure: Vec::new(),
+            already_installed: 0,
+            jobs: 1,
+        };
+        let mut exec_ctx = ExecuteContext::new(plan.clone());
+        let report = install_plan.execute(&mut exec_ctx)?;
+        partition_installed(to_add, &report)
+    } else {
+        (to_add, Vec::new())
+    };
+
+    if !added.is_empty() {
+        if plan.should_update_manifest() {
+            let mut manifest = FlatpakAppsManifest::load_repo()?;
+            for app in &added {
+                manifest.upsert(app.clone());
+                Output::success(format!(
+                    "Added to manifest: {} ({}, {})",
+                    app.id, app.remote, app.scope
+                ));
+            }
+            manifest.save_repo()?;
+        }
+
+        if plan.should_create_pr() {
+            let mut system_manifest = FlatpakAppsManifest::load_repo()?;
+            for app in &added {
+                system_manifest.upsert(app.clone());
+            }
+            let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+            let names: Vec<&str> = added.iter().map(|app| app.id.as_str()).collect();
+
+            plan.maybe_create_pr(
+                "flatpak",
+                "add",
+                &names.join(", "),
+                "flatpak-apps.json",
+                &manifest_content,
+            )?;
+        }
+    }
+
+    if !failed.is_empty() {
+        for (app_id, error) in &failed {
+            Output::error(format!("{}: {}", app_id, error));
+        }
+        bail!(
+            "{} of {} flatpaks failed to install and were not added to the manifest",
+            failed.len(),
+            total
+        );
+    }
+
+    Ok(())
+}
+
+/// Split `apps` into those that are installed after `report` ran and
+/// `(id, error)` for those whose install failed.
+fn partition_installed(
+    apps: Vec<FlatpakApp>,
+    report: &ExecutionReport,
+) -> (Vec<FlatpakApp>, Vec<(String, String)>) {
+    let mut installed = Vec::new();
+    let mut failed = Vec::new();
+    for app in apps {
+        let target = format!("flatpak:{}", app.id);
+        let failure = report.results.iter().find(|result| {
+            !result.success
+                && result.operation.verb == Verb::Install
+                && result.operation.target == target
+        });
+        match failure {
+            Some(result) => failed.push((
+                app.id,
+                result
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "install failed".to_string()),
+            )),
+            None => installed.push(app),
+        }
+    }
+    (installed, failed)
+}
+
+fn handle_pin(
+    app_id: String,
+    commit: Option<String>,
+    remote: String,
+    scope: String,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    plan.validate_domain(CommandDomain::Flatpak)?;
+    let runner = plan.runner();
+
+    let mut manifest = FlatpakAppsManifest::load_repo()?;
+
+    // Default to whatever commit is currently deployed
+    let commit = match commit {
+        Some(commit) => commit,
+        None => get_installed_flatpaks()
+            .into_iter()
+            .find(|f| f.id == app_id && !f.commit.is_empty())
+            .map(|f| f.commit)
+            .ok_or_else(|| anyhow!("{} is not installed; specify --commit", app_id))?,
+    };
+
+    let mut app = match manifest.find(&app_id) {
+        Some(existing) => existing.clone(),
+        None => {
+            Output::info(format!("Not in manifest, adding: {}", app_id));
+            FlatpakApp {
+                id: app_id.clone(),
+                remote,
+                scope: scope.parse()?,
+                branch: None,
+                commit: None,
+                pinned: false,
+                overrides: None,
+            }
+        }
+    };
+
+    if app.pinned_commit() == Some(commit.as_str()) {
+        Output::info(format!("Already pinned: {} @ {}", app_id, commit));
+        return Ok(());
+    }
+
+    app.commit = Some(commit.clone());
+    app.pinned = true;
+
+    if plan.should_update_manifest() {
+        manifest.upsert(app.clone());
+        manifest.save_repo()?;
+        Output::success(format!("Pinned in manifest: {} @ {}", app_id, commit));
+    } else if plan.dry_run {
+        Output::dry_run(format!("Would pin in manifest: {} @ {}", app_id, commit));
+    }
+
+    if plan.should_execute_locally() && is_installed(&app_id, runner) {
+        let spinner = Output::spinner(format!("Pinning {}...", app_id));
+        if apply_pin(&app, &commit, runner)? {
+            spinner.finish_success(format!("Pinned {} @ {}", app_id, commit));
+        } else {
+            spinner.finish_error(format!("Failed to pin {}", app_id));
+        }
+    } else if plan.dry_run && is_installed(&app_id, runner) {
+        Output::dry_run(format!("Would update {} to {} and mask it", app_id, commit));
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = FlatpakAppsManifest::load_repo()?;
+        system_manifest.upsert(app);
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+
+        plan.maybe_create_pr(
+            "flatpak",
+            "pin",
+            &app_id,
+            "flatpak-apps.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn handle_unpin(app_id: String, plan: &ExecutionPlan) -> Result<()> {
+    plan.validate_domain(CommandDomain::Flatpak)?;
+    let runner = plan.runner();
+
+    let mut manifest = FlatpakAppsManifest::load_repo()?;
+
+    let Some(mut app) = manifest.find(&app_id).filter(|a| a.pinned).cloned() else {
+        Output::warning(format!("Flatpak not pinned in manifest: {}", app_id));
+        return Ok(());
+    };
+
+    // Keep the last known commit for reference, but stop enforcing it
+    app.pinned = false;
+
+    if plan.should_update_manifest() {
+        manifest.upsert(app.clone());
+        manifest.save_repo()?;
+        Output::success(format!("Unpinned in manifest: {}", app_id));
+    } else if plan.dry_run {
+        Output::dry_run(format!("Would unpin in manifest: {}", app_id));
+    }
+
+    if plan.should_execute_locally() && is_installed(&app_id, runner) {
+        if set_masked(&app_id, app.scope, false, runner)? {
+            Output::success(format!("Removed update mask: {}", app_id));
+        } else {
+            Output::warning(format!(
+                "May need manual `flatpak mask --remove`: {}",
+                app_id
+            ));
+        }
+    } else if plan.dry_run && is_installed(&app_id, runner) {
+        Output::dry_run(format!("Would remove update mask: {}", app_id));
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = FlatpakAppsManifest::load_repo()?;
+        system_manifest.upsert(app);
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+
+        plan.maybe_create_pr(
+            "flatpak",
+            "unpin",
+            &app_id,
+            "flatpak-apps.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Permissions
+// ============================================================================
+
+fn handle_permissions(args: PermissionsArgs, plan: &ExecutionPlan) -> Result<()> {
+    let Some(app_id) = args.app_id.clone() else {
+        bail!("Specify an application ID, or use `bkt flatpak permissions capture <app-id>`");
+    };
+    let new_flags = args.override_flags()?;
+
+    let mut manifest = FlatpakAppsManifest::load_repo()?;
+    let Some(mut app) = manifest.find(&app_id).cloned() else {
+        bail!(
+            "Flatpak not in manifest: {} (add it with `bkt flatpak add`)",
+            app_id
+        );
+    };
+    let existing = app.overrides.clone().unwrap_or_default();
+
+    if new_flags.is_empty() && !args.reset {
+        if existing.is_empty() {
+            Output::info(format!("No overrides recorded for {}", app_id));
+        } else {
+            Output::subheader(format!("{} OVERRIDES:", app_id));
+            for flag in &existing {
+                println!("  {}", flag);
+            }
+        }
+        return Ok(());
+    }
+
+    plan.validate_domain(CommandDomain::Flatpak)?;
+    let runner = plan.runner();
+
+    let base = if args.reset { &[][..] } else { &existing[..] };
+    let merged = merge_override_flags(base, &new_flags);
+    if merged == existing {
+        Output::info(format!("Overrides already recorded for {}", app_id));
+        return Ok(());
+    }
+    app.overrides = (!merged.is_empty()).then_some(merged.clone());
+
+    if plan.should_update_manifest() {
+        manifest.upsert(app.clone());
+        manifest.save_repo()?;
+        Output::success(format!(
+            "Recorded {} override(s) for {}",
+            merged.len(),
+            app_id
+        ));
+    } else if plan.dry_run {
+        Output::dry_run(format!(
+            "Would record overrides for {}: {}",
+            app_id,
+            merged.join(" ")
+        ));
+    }
+
+    if plan.should_execute_locally() && is_installed(&app_id, runner) {
+        let applied = if args.reset {
+            reset_overrides(&app_id, app.scope, runner)?
+                && apply_overrides(&app_id, app.scope, &merged, runner)?
+        } else {
+            apply_overrides(&app_id, app.scope, &new_flags, runner)?
+        };
+        if applied {
+            Output::success(format!("Applied overrides to {}", app_id));
+        } else {
+            Output::warning(format!("flatpak override failed for {}", app_id));
+        }
+    } else if plan.dry_run && is_installed(&app_id, runner) {
+        let flags = if args.reset { &merged } else { &new_flags };
+        Output::dry_run(format!(
+            "Would run: flatpak override --{} {}{} {}",
+            app.scope,
+            if args.reset { "--reset, then " } else { "" },
+            app_id,
+            flags.join(" ")
+        ));
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = FlatpakAppsManifest::load_repo()?;
+        system_manifest.upsert(app);
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+
+        plan.maybe_create_pr(
+            "flatpak",
+            "permissions",
+            &app_id,
+            "flatpak-apps.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn handle_permissions_capture(app_id: String, plan: &ExecutionPlan) -> Result<()> {
+    let mut manifest = FlatpakAppsManifest::load_repo()?;
+    let Some(mut app) = manifest.find(&app_id).cloned() else {
+        bail!(
+            "Flatpak not in manifest: {} (capture it with `bkt flatpak capture`)",
+            app_id
+        );
+    };
+
+    let live = live_flatpak_overrides(&app_id, app.scope);
+    if app.overrides.clone().unwrap_or_default() == live {
+        Output::info(format!("Overrides for {} are already captured", app_id));
+        return Ok(());
+    }
+    app.overrides = (!live.is_empty()).then_some(live.clone());
+
+    if plan.should_update_manifest() {
+        manifest.upsert(app.clone());
+        manifest.save_repo()?;
+        Output::success(format!(
+            "Captured {} override(s) for {} from {}",
+            live.len(),
+            app_id,
+            FlatpakOverrides::override_file_path(&app_id, app.scope).display()
+        ));
+    } else if plan.dry_run {
+        Output::dry_run(format!(
+            "Would capture overrides for {}: {}",
+            app_id,
+            live.join(" ")
+        ));
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = FlatpakAppsManifest::load_repo()?;
+        system_manifest.upsert(app);
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+
+        plan.maybe_create_pr(
+            "flatpak",
+            "permissions-capture",
+            &app_id,
+            "flatpak-apps.json",
+            &manifest_content,
+        )?;
     }
+
     Ok(())
 }
 
@@ -412,15 +1255,108 @@ pub struct FlatpakToInstall {
     pub app: FlatpakApp,
 }
 
+/// A configured remote whose settings differ from the manifest.
+#[derive(Debug, Clone)]
+pub struct RemoteToModify {
+    pub name: String,
+    pub scope: FlatpakScope,
+    /// `flatpak remote-modify` flags to apply.
+    pub flags: Vec<String>,
+}
+
 /// Command to sync flatpaks from manifests.
 pub struct FlatpakSyncCommand;
 
 /// Plan for syncing flatpaks.
 pub struct FlatpakSyncPlan {
+    /// Remotes missing from their scope's installation.
+    pub remotes_to_add: Vec<FlatpakRemote>,
+    /// Remotes whose priority, subset or filter need changing.
+    pub remotes_to_modify: Vec<RemoteToModify>,
     /// Flatpaks to install.
     pub to_install: Vec<FlatpakToInstall>,
+    /// Installed flatpaks whose recorded overrides are not all in effect.
+    pub to_configure: Vec<FlatpakApp>,
     /// Flatpaks already installed.
     pub already_installed: usize,
+    /// How many remotes to install from concurrently.
+    pub jobs: usize,
+}
+
+impl FlatpakSyncPlan {
+    /// Install from up to `jobs` remotes at once.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+}
+
+/// Pending installs sharing a remote and scope.
+#[derive(Debug)]
+struct InstallBatch<'a> {
+    remote: &'a str,
+    scope: FlatpakScope,
+    apps: Vec<&'a FlatpakApp>,
+}
+
+impl InstallBatch<'_> {
+    fn install(&self, runner: &dyn CommandRunner) -> Result<bool> {
+        let ids: Vec<&str> = self.apps.iter().map(|app| app.id.as_str()).collect();
+        install_from_remote(self.remote, self.scope, &ids, runner)
+    }
+}
+
+/// Group pending installs by remote and scope, in manifest order.
+fn batch_by_remote(items: &[FlatpakToInstall]) -> Vec<InstallBatch<'_>> {
+    let mut batches: Vec<InstallBatch> = Vec::new();
+    for item in items {
+        let app = &item.app;
+        match batches
+            .iter_mut()
+            .find(|b| b.remote == app.remote && b.scope == app.scope)
+        {
+            Some(batch) => batch.apps.push(app),
+            None => batches.push(InstallBatch {
+                remote: &app.remote,
+                scope: app.scope,
+                apps: vec![app],
+            }),
+        }
+    }
+    batches
+}
+
+/// Run each batch's install, up to `jobs` remotes at a time.
+///
+/// Concurrent installs share the terminal, so their progress output
+/// interleaves.
+fn run_batches(
+    batches: &[InstallBatch],
+    jobs: usize,
+    runner: &dyn CommandRunner,
+) -> Vec<Result<bool>> {
+    if jobs <= 1 {
+        return batches.iter().map(|batch| batch.install(runner)).collect();
+    }
+    batches
+        .chunks(jobs)
+        .flat_map(|chunk| {
+            std::thread::scope(|s| {
+                let handles: Vec<_> = chunk
+                    .iter()
+                    .map(|batch| s.spawn(move || batch.install(runner)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|_| Err(anyhow::anyhow!("flatpak install panicked")))
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect()
 }
 
 impl Plannable for FlatpakSyncCommand {
@@ -428,24 +1364,61 @@ impl Plannable for FlatpakSyncCommand {
 
     fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
         // Load manifest (read-only, no side effects)
-        let merged = FlatpakAppsManifest::load_repo()?;
+        let merged = FlatpakAppsManifest::load_effective()?;
+        let remotes = FlatpakRemotesManifest::load_repo()?;
 
         let mut to_install = Vec::new();
+        let mut to_configure = Vec::new();
         let mut already_installed = 0;
 
         let runner = ctx.execution_plan().runner();
 
+        // Remotes go first so the apps' origins exist and rank as recorded
+        let mut remotes_to_add = Vec::new();
+        let mut remotes_to_modify = Vec::new();
+        if !remotes.remotes.is_empty() {
+            let configured = [FlatpakScope::System, FlatpakScope::User]
+                .map(|scope| (scope, configured_remotes(scope, runner)));
+
+            for remote in &remotes.remotes {
+                for (scope, live) in &configured {
+                    match live.iter().find(|r| r.name == remote.name) {
+                        Some(live) => {
+                            let flags = remote_modify_flags(remote, live);
+                            if !flags.is_empty() {
+                                remotes_to_modify.push(RemoteToModify {
+                                    name: remote.name.clone(),
+                                    scope: *scope,
+                                    flags,
+                                });
+                            }
+                        }
+                        None if *scope == remote.scope => remotes_to_add.push(remote.clone()),
+                        None => {}
+                    }
+                }
+            }
+        }
+
         for app in merged.apps {
             if is_installed(&app.id, runner) {
                 already_installed += 1;
+                let live = live_flatpak_overrides(&app.id, app.scope);
+                if !missing_override_flags(&app, &live).is_empty() {
+                    to_configure.push(app);
+                }
             } else {
                 to_install.push(FlatpakToInstall { app });
             }
         }
 
         Ok(FlatpakSyncPlan {
+            remotes_to_add,
+            remotes_to_modify,
             to_install,
+            to_configure,
             already_installed,
+            jobs: 1,
         })
     }
 }
@@ -458,11 +1431,54 @@ impl Plan for FlatpakSyncPlan {
             self.already_installed
         ));
 
+        for remote in &self.remotes_to_add {
+            let mut details = format!("{} ({})", remote.url, remote.scope);
+            for flag in remote.option_flags() {
+                details.push(' ');
+                details.push_str(&flag);
+            }
+            summary.add_operation(Operation::with_details(
+                Verb::Create,
+                format!("flatpak-remote:{}", remote.name),
+                details,
+            ));
+        }
+
+        for remote in &self.remotes_to_modify {
+            summary.add_operation(Operation::with_details(
+                Verb::Configure,
+                format!("flatpak-remote:{}", remote.name),
+                format!("({}) {}", remote.scope, remote.flags.join(" ")),
+            ));
+        }
+
         for item in &self.to_install {
+            let details = match item.app.pinned_commit() {
+                Some(commit) => format!(
+                    "{} ({}), pinned @ {}",
+                    item.app.remote,
+                    item.app.scope,
+                    short_commit(commit)
+                ),
+                None => format!("{} ({})", item.app.remote, item.app.scope),
+            };
             summary.add_operation(Operation::with_details(
                 Verb::Install,
                 format!("flatpak:{}", item.app.id),
-                format!("{} ({})", item.app.remote, item.app.scope),
+                details,
+            ));
+        }
+
+        for app in &self.to_configure {
+            summary.add_operation(Operation::with_details(
+                Verb::Configure,
+                format!("flatpak:{}:overrides", app.id),
+                app.overrides
+                    .iter()
+                    .flatten()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" "),
             ));
         }
 
@@ -472,88 +1488,278 @@ impl Plan for FlatpakSyncPlan {
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
 
-        for item in self.to_install {
-            let install_result = {
+        for remote in self.remotes_to_add {
+            let result = {
                 let runner = ctx.execution_plan().runner();
-                install_flatpak(&item.app, runner)
+                add_remote(&remote, runner)
             };
-
-            match install_result {
-                Ok(true) => {
-                    report.record_success_and_notify(
-                        ctx,
-                        Verb::Install,
-                        format!("flatpak:{}", item.app.id),
-                    );
-
-                    // Apply overrides if present
-                    if let Some(ref overrides) = item.app.overrides
-                        && !overrides.is_empty()
-                    {
-                        let overrides_result = {
-                            let runner = ctx.execution_plan().runner();
-                            apply_overrides(&item.app.id, item.app.scope, overrides, runner)
-                        };
-
-                        match overrides_result {
-                            Ok(true) => {
-                                report.record_success_and_notify(
-                                    ctx,
-                                    Verb::Configure,
-                                    format!("flatpak:{}:overrides", item.app.id),
-                                );
-                            }
-                            Ok(false) => {
-                                report.record_failure_and_notify(
-                                    ctx,
-                                    Verb::Configure,
-                                    format!("flatpak:{}:overrides", item.app.id),
-                                    "flatpak override failed",
-                                );
-                            }
-                            Err(e) => {
-                                report.record_failure_and_notify(
-                                    ctx,
-                                    Verb::Configure,
-                                    format!("flatpak:{}:overrides", item.app.id),
-                                    e.to_string(),
-                                );
-                            }
-                        }
-                    }
+            let target = format!("flatpak-remote:{}", remote.name);
+            match result {
+                Ok(true) => report.record_success_and_notify(ctx, Verb::Create, target),
+                Ok(false) => report.record_failure_and_notify(
+                    ctx,
+                    Verb::Create,
+                    target,
+                    "flatpak remote-add failed",
+                ),
+                Err(e) => {
+                    report.record_failure_and_notify(ctx, Verb::Create, target, e.to_string())
                 }
-                Ok(false) => {
-                    report.record_failure_and_notify(
-                        ctx,
-                        Verb::Install,
-                        format!("flatpak:{}", item.app.id),
-                        "flatpak install failed",
-                    );
+            }
+        }
+
+        for remote in self.remotes_to_modify {
+            let result = {
+                let runner = ctx.execution_plan().runner();
+                modify_remote(&remote.name, remote.scope, &remote.flags, runner)
+            };
+            let target = format!("flatpak-remote:{}", remote.name);
+            match result {
+                Ok(true) => report.record_success_and_notify(ctx, Verb::Configure, target),
+                Ok(false) => report.record_failure_and_notify(
+                    ctx,
+                    Verb::Configure,
+                    target,
+                    "flatpak remote-modify failed",
+                ),
+                Err(e) => {
+                    report.record_failure_and_notify(ctx, Verb::Configure, target, e.to_string())
                 }
+            }
+        }
+
+        let batches = batch_by_remote(&self.to_install);
+        let results = {
+            let runner = ctx.execution_plan().runner();
+            run_batches(&batches, self.jobs, runner)
+        };
+
+        for (batch, result) in batches.iter().zip(results) {
+            if let [app] = batch.apps.as_slice() {
+                finish_install(app, result, ctx, &mut report);
+                continue;
+            }
+
+            // A batch reports one exit status for all its apps: check what
+            // actually got installed, and retry the rest one by one if the
+            // batch failed so a single bad ref doesn't block the others.
+            let batch_ok = match result {
+                Ok(ok) => ok,
                 Err(e) => {
-                    report.record_failure_and_notify(
-                        ctx,
-                        Verb::Install,
-                        format!("flatpak:{}", item.app.id),
-                        e.to_string(),
-                    );
+                    tracing::debug!("Batch install from {} failed: {:#}", batch.remote, e);
+                    false
                 }
+            };
+            if !batch_ok {
+                Output::warning(format!(
+                    "Installing {} apps from {} together failed; retrying one at a time",
+                    batch.apps.len(),
+                    batch.remote
+                ));
+            }
+
+            for app in &batch.apps {
+                let outcome = {
+                    let runner = ctx.execution_plan().runner();
+                    if is_installed(&app.id, runner) {
+                        Ok(true)
+                    } else if batch_ok {
+                        Ok(false)
+                    } else {
+                        install_flatpak(app, runner)
+                    }
+                };
+                finish_install(app, outcome, ctx, &mut report);
             }
         }
 
+        for app in self.to_configure {
+            apply_recorded_overrides(&app, ctx, &mut report);
+        }
+
         Ok(report)
     }
 
     fn is_empty(&self) -> bool {
-        self.to_install.is_empty()
+        self.remotes_to_add.is_empty()
+            && self.remotes_to_modify.is_empty()
+            && self.to_install.is_empty()
+            && self.to_configure.is_empty()
+    }
+}
+
+/// Record an app's install outcome, then pin it and apply its overrides if
+/// it went in.
+fn finish_install(
+    app: &FlatpakApp,
+    install_result: Result<bool>,
+    ctx: &mut ExecuteContext,
+    report: &mut ExecutionReport,
+) {
+    match install_result {
+        Ok(true) => {
+            report.record_success_and_notify(ctx, Verb::Install, format!("flatpak:{}", app.id));
+
+            // Move to the pinned commit if present
+            if let Some(commit) = app.pinned_commit() {
+                let pin_result = {
+                    let runner = ctx.execution_plan().runner();
+                    apply_pin(app, commit, runner)
+                };
+                let target = format!("flatpak:{}@{}", app.id, short_commit(commit));
+
+                match pin_result {
+                    Ok(true) => {
+                        report.record_success_and_notify(ctx, Verb::Update, target);
+                    }
+                    Ok(false) => {
+                        report.record_failure_and_notify(
+                            ctx,
+                            Verb::Update,
+                            target,
+                            "flatpak update --commit failed",
+                        );
+                    }
+                    Err(e) => {
+                        report.record_failure_and_notify(ctx, Verb::Update, target, e.to_string());
+                    }
+                }
+            }
+
+            // Apply overrides if present
+            apply_recorded_overrides(app, ctx, report);
+        }
+        Ok(false) => {
+            report.record_failure_and_notify(
+                ctx,
+                Verb::Install,
+                format!("flatpak:{}", app.id),
+                "flatpak install failed",
+            );
+        }
+        Err(e) => {
+            report.record_failure_and_notify(
+                ctx,
+                Verb::Install,
+                format!("flatpak:{}", app.id),
+                e.to_string(),
+            );
+        }
+    }
+}
+
+/// Apply an app's recorded overrides, recording the outcome in `report`.
+fn apply_recorded_overrides(
+    app: &FlatpakApp,
+    ctx: &mut ExecuteContext,
+    report: &mut ExecutionReport,
+) {
+    let Some(overrides) = app.overrides.as_ref().filter(|o| !o.is_empty()) else {
+        return;
+    };
+
+    let result = {
+        let runner = ctx.execution_plan().runner();
+        apply_overrides(&app.id, app.scope, overrides, runner)
+    };
+    let target = format!("flatpak:{}:overrides", app.id);
+
+    match result {
+        Ok(true) => {
+            report.record_success_and_notify(ctx, Verb::Configure, target);
+        }
+        Ok(false) => {
+            report.record_failure_and_notify(
+                ctx,
+                Verb::Configure,
+                target,
+                "flatpak override failed",
+            );
+        }
+        Err(e) => {
+            report.record_failure_and_notify(ctx, Verb::Configure, target, e.to_string());
+        }
+    }
+}
+
+/// Abbreviate a commit hash for display.
+fn short_commit(commit: &str) -> &str {
+    &commit[..12.min(commit.len())]
+}
+
+/// Format a flatpak identity for drift comparison.
+///
+/// Pinned apps include their commit (`id@commit`) so a different deployed
+/// commit shows up as drift; unpinned apps compare by ID alone.
+pub fn flatpak_drift_key(id: &str, commit: Option<&str>) -> String {
+    match commit {
+        Some(commit) => format!("{}@{}", id, commit),
+        None => id.to_string(),
+    }
+}
+
+/// Resolve the drift keys for a manifest and the installed flatpaks.
+pub fn flatpak_drift_keys(
+    manifest: &FlatpakAppsManifest,
+    installed: &[InstalledFlatpak],
+) -> (Vec<String>, Vec<String>) {
+    let expected = manifest
+        .apps
+        .iter()
+        .map(|a| flatpak_drift_key(&a.id, a.pinned_commit()))
+        .collect();
+
+    let actual = installed
+        .iter()
+        .map(|f| {
+            match manifest.find(&f.id).and_then(|a| a.pinned_commit()) {
+                // Report the pinned spelling when commits agree, so an
+                // abbreviated `active` column doesn't look like drift
+                Some(pinned) if commits_match(pinned, &f.commit) => {
+                    flatpak_drift_key(&f.id, Some(pinned))
+                }
+                Some(_) => flatpak_drift_key(&f.id, Some(&f.commit)),
+                None => flatpak_drift_key(&f.id, None),
+            }
+        })
+        .collect();
+
+    (expected, actual)
+}
+
+/// Resolve per-flag override drift keys for installed, managed apps.
+///
+/// Each recorded flag becomes an expected `id:flag` key and each flag in the
+/// live override file an actual one, so a missing or extra permission shows
+/// up individually. `live` returns the flags currently in effect.
+pub fn flatpak_override_drift_keys<F>(
+    manifest: &FlatpakAppsManifest,
+    installed: &[InstalledFlatpak],
+    live: F,
+) -> (Vec<String>, Vec<String>)
+where
+    F: Fn(&str, FlatpakScope) -> Vec<String>,
+{
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+
+    for app in &manifest.apps {
+        if !installed.iter().any(|f| f.id == app.id) {
+            continue;
+        }
+        let key = |flag: &String| format!("{}:{}", app.id, flag);
+        expected.extend(app.overrides.iter().flatten().map(key));
+        actual.extend(live(&app.id, app.scope).iter().map(key));
     }
+
+    (expected, actual)
 }
 
 // ============================================================================
 // Plan-based Flatpak Capture Implementation
 // ============================================================================
 
-/// A flatpak installed on the system but not in manifest.
+/// A flatpak installed on the system.
 #[derive(Debug, Clone)]
 pub struct InstalledFlatpak {
     /// Scope: "system" or "user"
@@ -566,48 +1772,308 @@ pub struct InstalledFlatpak {
     pub branch: String,
     /// The commit hash
     pub commit: String,
+    /// The runtime ref the app runs on (e.g. org.freedesktop.Platform/x86_64/23.08)
+    pub runtime: Option<String>,
 }
 
 /// Get list of installed flatpaks from the system.
 ///
-/// When running inside a toolbox, this delegates to the host via flatpak-spawn.
-/// If flatpak-spawn is not available or fails, returns an empty vector and logs a warning.
+/// When running inside a toolbox, this runs on the host via [`HostExec`].
+/// If the host can't be reached or the command fails, returns an empty vector.
 pub fn get_installed_flatpaks() -> Vec<InstalledFlatpak> {
-    let output = run_command(
+    let output = run_on_host(
         "flatpak",
         &[
             "list",
             "--app",
-            "--columns=installation,application,origin,branch,active",
+            "--columns=installation,application,origin,branch,active,runtime",
         ],
     );
 
     match output {
         Ok(o) if o.status.success() => {
-            let stdout = String::from_utf8_lossy(&o.stdout);
-            let mut apps = Vec::new();
-
-            for line in stdout.lines() {
-                let mut parts = line.split_whitespace();
-                let installation = parts.next().unwrap_or("system");
-                let id = parts.next().unwrap_or("");
-                if id.is_empty() {
-                    continue;
-                }
+            parse_installed_flatpaks(&String::from_utf8_lossy(&o.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
 
-                apps.push(InstalledFlatpak {
-                    installation: installation.to_string(),
-                    id: id.to_string(),
-                    origin: parts.next().unwrap_or("flathub").to_string(),
-                    branch: parts.next().unwrap_or("stable").to_string(),
-                    commit: parts.next().unwrap_or("").to_string(),
-                });
+/// Parse `flatpak list --app --columns=installation,application,origin,branch,active,runtime`.
+///
+/// Columns are tab-separated when piped; whitespace-separated output is
+/// accepted too.
+fn parse_installed_flatpaks(stdout: &str) -> Vec<InstalledFlatpak> {
+    let mut apps = Vec::new();
+
+    for line in stdout.lines() {
+        let fields: Vec<&str> = if line.contains('\t') {
+            line.split('\t').map(str::trim).collect()
+        } else {
+            line.split_whitespace().collect()
+        };
+        let field = |index: usize| fields.get(index).copied().filter(|f| !f.is_empty());
+
+        let Some(id) = field(1) else {
+            continue;
+        };
+        apps.push(InstalledFlatpak {
+            installation: field(0).unwrap_or("system").to_string(),
+            id: id.to_string(),
+            origin: field(2).unwrap_or("flathub").to_string(),
+            branch: field(3).unwrap_or("stable").to_string(),
+            commit: field(4).unwrap_or("").to_string(),
+            runtime: field(5).map(str::to_string),
+        });
+    }
+
+    apps
+}
+
+// ============================================================================
+// Runtime End-of-Life
+// ============================================================================
+
+/// Why a runtime is end-of-life, as published by its remote.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RuntimeEol {
+    /// The remote's end-of-life message
+    pub reason: String,
+    /// The runtime that replaces it, if the remote names one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rebase: Option<String>,
+}
+
+/// Look up which of the installed apps' runtimes are end-of-life.
+///
+/// Each distinct runtime is checked once with `flatpak info`, which reports
+/// the EOL flag from the installed metadata without going to the network.
+/// Runtimes that can't be inspected count as maintained.
+pub fn runtime_eol_status(installed: &[InstalledFlatpak]) -> BTreeMap<String, RuntimeEol> {
+    let runtimes: BTreeSet<&str> = installed
+        .iter()
+        .filter_map(|app| app.runtime.as_deref())
+        .collect();
+
+    runtimes
+        .into_iter()
+        .filter_map(|runtime| {
+            let output = run_on_host("flatpak", &["info", runtime]).ok()?;
+            if !output.status.success() {
+                return None;
             }
+            parse_runtime_eol(&String::from_utf8_lossy(&output.stdout))
+                .map(|eol| (runtime.to_string(), eol))
+        })
+        .collect()
+}
 
-            apps
+/// The `End-of-life` fields of `flatpak info` output, if present.
+fn parse_runtime_eol(info: &str) -> Option<RuntimeEol> {
+    let mut reason = None;
+    let mut rebase = None;
+    for line in info.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "End-of-life" if !value.is_empty() => reason = Some(value.to_string()),
+            "End-of-life-rebase" if !value.is_empty() => rebase = Some(value.to_string()),
+            _ => {}
         }
-        _ => Vec::new(),
     }
+    // A rebase alone still means the old runtime is retired
+    if reason.is_none() && rebase.is_some() {
+        reason = Some("replaced by a newer runtime".to_string());
+    }
+    reason.map(|reason| RuntimeEol { reason, rebase })
+}
+
+/// `org.freedesktop.Platform/x86_64/21.08` as `org.freedesktop.Platform//21.08`.
+fn short_runtime(runtime: &str) -> String {
+    let mut parts = runtime.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(id), Some(_arch), Some(branch)) => format!("{}//{}", id, branch),
+        _ => runtime.to_string(),
+    }
+}
+
+/// An installed app whose runtime is end-of-life.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct EolRuntimeApp {
+    pub id: String,
+    pub installation: String,
+    pub runtime: String,
+    #[serde(flatten)]
+    pub eol: RuntimeEol,
+    /// What to do about it
+    pub action: String,
+}
+
+/// The installed apps that run on an end-of-life runtime, sorted by ID.
+pub fn eol_runtime_apps(
+    installed: &[InstalledFlatpak],
+    eol: &BTreeMap<String, RuntimeEol>,
+) -> Vec<EolRuntimeApp> {
+    let mut apps: Vec<EolRuntimeApp> = installed
+        .iter()
+        .filter_map(|app| {
+            let runtime = app.runtime.as_deref()?;
+            let eol = eol.get(runtime)?;
+            let update = format!("flatpak update --{} {}", app.installation, app.id);
+            let action = match &eol.rebase {
+                Some(rebase) => format!(
+                    "run `{}` to move to {}",
+                    update,
+                    short_runtime(rebase.trim_start_matches("runtime/"))
+                ),
+                None => format!(
+                    "run `{}`; if it stays on {}, the app is unmaintained and should be replaced",
+                    update,
+                    short_runtime(runtime)
+                ),
+            };
+            Some(EolRuntimeApp {
+                id: app.id.clone(),
+                installation: app.installation.clone(),
+                runtime: runtime.to_string(),
+                eol: eol.clone(),
+                action,
+            })
+        })
+        .collect();
+    apps.sort_by(|a, b| a.id.cmp(&b.id));
+    apps
+}
+
+/// An entry of `bkt flatpak list --format json`.
+#[derive(Debug, serde::Serialize)]
+struct FlatpakListEntry<'a> {
+    #[serde(flatten)]
+    app: &'a FlatpakApp,
+    installed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eol: Option<&'a RuntimeEol>,
+}
+
+fn handle_list(format: OutputFormat) -> Result<()> {
+    let merged = FlatpakAppsManifest::load_effective()?;
+    let installed = get_installed_flatpaks();
+    let eol = runtime_eol_status(&installed);
+
+    let entries: Vec<FlatpakListEntry> = merged
+        .apps
+        .iter()
+        .map(|app| {
+            let live = installed.iter().find(|f| f.id == app.id);
+            let runtime = live.and_then(|f| f.runtime.as_deref());
+            FlatpakListEntry {
+                app,
+                installed: live.is_some(),
+                runtime,
+                eol: runtime.and_then(|r| eol.get(r)),
+            }
+        })
+        .collect();
+
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "apps": entries }))?
+        );
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        Output::info("No flatpak apps in manifest.");
+        return Ok(());
+    }
+
+    Output::subheader("FLATPAK APPS:");
+    println!(
+        "{:<50} {:<12} {:<8} {:<9} {:<36} {}",
+        "ID".cyan(),
+        "REMOTE".cyan(),
+        "SCOPE".cyan(),
+        "INSTALLED".cyan(),
+        "RUNTIME".cyan(),
+        "EOL".cyan()
+    );
+    Output::separator();
+
+    for entry in &entries {
+        let installed = if entry.installed {
+            "✓".green().to_string()
+        } else {
+            "✗".red().to_string()
+        };
+        let runtime = entry
+            .runtime
+            .map(short_runtime)
+            .unwrap_or_else(|| "-".to_string());
+        let eol = match (entry.runtime, entry.eol) {
+            (None, _) => "-".dimmed().to_string(),
+            (Some(_), Some(_)) => "yes".yellow().to_string(),
+            (Some(_), None) => "no".dimmed().to_string(),
+        };
+        println!(
+            "{:<50} {:<12} {:<8} {:<9} {:<36} {}",
+            entry.app.id, entry.app.remote, entry.app.scope, installed, runtime, eol
+        );
+    }
+
+    Output::blank();
+    Output::info(format!("{} apps in manifest", entries.len()));
+    let on_eol = entries.iter().filter(|e| e.eol.is_some()).count();
+    if on_eol > 0 {
+        Output::hint(format!(
+            "{} on end-of-life runtimes; see `bkt flatpak check-runtimes`",
+            on_eol
+        ));
+    }
+    Ok(())
+}
+
+fn handle_check_runtimes(format: OutputFormat) -> Result<()> {
+    let installed = get_installed_flatpaks();
+    let eol = runtime_eol_status(&installed);
+    let apps = eol_runtime_apps(&installed, &eol);
+
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "apps": apps }))?
+        );
+        return Ok(());
+    }
+
+    if apps.is_empty() {
+        Output::success(format!(
+            "No installed apps on end-of-life runtimes ({} checked)",
+            installed.len()
+        ));
+        return Ok(());
+    }
+
+    Output::subheader("APPS ON END-OF-LIFE RUNTIMES:");
+    for app in &apps {
+        println!(
+            "  {} {}",
+            app.id.yellow(),
+            format!("({}, {})", short_runtime(&app.runtime), app.installation).dimmed()
+        );
+        println!("    {}", app.eol.reason);
+        println!("    {} {}", "→".cyan(), app.action);
+    }
+    Output::blank();
+    Output::warning(format!(
+        "{} app{} on end-of-life runtimes",
+        apps.len(),
+        if apps.len() == 1 { "" } else { "s" }
+    ));
+    Ok(())
 }
 
 /// A flatpak to capture (add to manifest).
@@ -630,6 +2096,8 @@ pub struct FlatpakCapturePlan {
     pub to_capture: Vec<FlatpakToCapture>,
     /// Flatpaks already in manifest.
     pub already_in_manifest: usize,
+    /// Managed remotes whose live priority, subset or filter aren't recorded.
+    pub remotes_to_update: Vec<FlatpakRemote>,
     /// Warnings about unmanaged remotes.
     pub warnings: Vec<PlanWarning>,
 }
@@ -637,12 +2105,12 @@ pub struct FlatpakCapturePlan {
 impl Plannable for FlatpakCaptureCommand {
     type Plan = FlatpakCapturePlan;
 
-    fn plan(&self, _ctx: &PlanContext) -> Result<Self::Plan> {
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
         // Get currently installed flatpaks on the system
         let installed = get_installed_flatpaks();
 
         // Load manifests to see what's already tracked
-        let merged = FlatpakAppsManifest::load_repo()?;
+        let merged = FlatpakAppsManifest::load_effective()?;
 
         // Load remotes manifest to check for unmanaged remotes
         let remotes = FlatpakRemotesManifest::load_cwd().unwrap_or_default();
@@ -697,6 +2165,7 @@ impl Plannable for FlatpakCaptureCommand {
                         } else {
                             Some(flatpak.commit)
                         },
+                        pinned: false,
                         overrides,
                     },
                     unmanaged_remote,
@@ -707,9 +2176,26 @@ impl Plannable for FlatpakCaptureCommand {
         // Sort for consistent output
         to_capture.sort_by(|a, b| a.app.id.cmp(&b.app.id));
 
+        // Record the live settings of managed remotes in their own scope
+        let runner = ctx.execution_plan().runner();
+        let mut remotes_to_update = Vec::new();
+        for scope in [FlatpakScope::System, FlatpakScope::User] {
+            let live = configured_remotes(scope, runner);
+            for remote in remotes.remotes.iter().filter(|r| r.scope == scope) {
+                if let Some(captured) = live
+                    .iter()
+                    .find(|r| r.name == remote.name)
+                    .and_then(|live| capture_remote_settings(remote, live))
+                {
+                    remotes_to_update.push(captured);
+                }
+            }
+        }
+
         Ok(FlatpakCapturePlan {
             to_capture,
             already_in_manifest,
+            remotes_to_update,
             warnings,
         })
     }
@@ -731,6 +2217,14 @@ impl Plan for FlatpakCapturePlan {
             ));
         }
 
+        for remote in &self.remotes_to_update {
+            summary.add_operation(Operation::with_details(
+                Verb::Capture,
+                format!("flatpak-remote:{}", remote.name),
+                remote.option_flags().join(" "),
+            ));
+        }
+
         // Add warnings about unmanaged remotes
         for warning in &self.warnings {
             summary.add_warning(warning.clone());
@@ -753,10 +2247,405 @@ impl Plan for FlatpakCapturePlan {
         // Save the updated manifest
         manifest.save_repo()?;
 
+        if !self.remotes_to_update.is_empty() {
+            let mut remotes = FlatpakRemotesManifest::load_repo()?;
+            for captured in self.remotes_to_update {
+                let target = format!("flatpak-remote:{}", captured.name);
+                if let Some(remote) = remotes.remotes.iter_mut().find(|r| r.name == captured.name) {
+                    *remote = captured;
+                }
+                report.record_success(Verb::Capture, target);
+            }
+            remotes.save_repo()?;
+        }
+
         Ok(report)
     }
 
     fn is_empty(&self) -> bool {
-        self.to_capture.is_empty()
+        self.to_capture.is_empty() && self.remotes_to_update.is_empty()
+    }
+}
+
+impl CapturePlan for FlatpakCapturePlan {
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.to_capture
+            .retain(|item| keep(&format!("flatpak:{}", item.app.id)));
+        self.remotes_to_update
+            .retain(|remote| keep(&format!("flatpak-remote:{}", remote.name)));
+        // Unmanaged-remote warnings only matter for apps still being captured
+        self.warnings.retain(|warning| keep(&warning.target));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(id: &str, overrides: &[&str]) -> FlatpakApp {
+        FlatpakApp {
+            id: id.to_string(),
+            remote: "flathub".to_string(),
+            scope: FlatpakScope::User,
+            branch: None,
+            commit: None,
+            pinned: false,
+            overrides: (!overrides.is_empty())
+                .then(|| overrides.iter().map(|s| s.to_string()).collect()),
+        }
+    }
+
+    fn installed(id: &str) -> InstalledFlatpak {
+        InstalledFlatpak {
+            installation: "user".to_string(),
+            id: id.to_string(),
+            origin: "flathub".to_string(),
+            branch: "stable".to_string(),
+            commit: "abc123".to_string(),
+            runtime: Some("org.freedesktop.Platform/x86_64/23.08".to_string()),
+        }
+    }
+
+    #[test]
+    fn parse_installed_flatpaks_reads_runtime_column() {
+        let stdout = "user\torg.gnome.Calculator\tflathub\tstable\tabc123\torg.gnome.Platform/x86_64/46\n\
+                      system\tcom.example.Old\tflathub\tstable\tdef456\torg.freedesktop.Platform/x86_64/21.08\n\
+                      system\tcom.example.Bare\tflathub\tstable\t\t\n";
+        let apps = parse_installed_flatpaks(stdout);
+
+        assert_eq!(apps.len(), 3);
+        assert_eq!(apps[0].installation, "user");
+        assert_eq!(
+            apps[0].runtime.as_deref(),
+            Some("org.gnome.Platform/x86_64/46")
+        );
+        assert_eq!(apps[1].commit, "def456");
+        assert_eq!(apps[2].commit, "");
+        assert_eq!(apps[2].runtime, None);
+
+        let spaced = parse_installed_flatpaks("system org.a.App flathub stable abc rt/x86_64/1\n");
+        assert_eq!(spaced[0].runtime.as_deref(), Some("rt/x86_64/1"));
+    }
+
+    #[test]
+    fn parse_runtime_eol_reads_flatpak_info() {
+        let info = "\
+        ID: org.freedesktop.Platform
+       Ref: runtime/org.freedesktop.Platform/x86_64/21.08
+    Branch: 21.08
+End-of-life: org.freedesktop.Platform 21.08 is no longer receiving fixes and security updates
+End-of-life-rebase: runtime/org.freedesktop.Platform/x86_64/23.08
+";
+        assert_eq!(
+            parse_runtime_eol(info),
+            Some(RuntimeEol {
+                reason: "org.freedesktop.Platform 21.08 is no longer receiving fixes and security updates"
+                    .to_string(),
+                rebase: Some("runtime/org.freedesktop.Platform/x86_64/23.08".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_runtime_eol("        ID: org.gnome.Platform\n    Branch: 46\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn eol_runtime_apps_suggest_an_action() {
+        let mut old = installed("com.example.Old");
+        old.runtime = Some("org.freedesktop.Platform/x86_64/21.08".to_string());
+        let mut stuck = installed("com.example.Stuck");
+        stuck.runtime = Some("org.kde.Platform/x86_64/5.15".to_string());
+        let current = installed("org.gnome.Calculator");
+
+        let eol = BTreeMap::from([
+            (
+                "org.freedesktop.Platform/x86_64/21.08".to_string(),
+                RuntimeEol {
+                    reason: "no longer supported".to_string(),
+                    rebase: Some("runtime/org.freedesktop.Platform/x86_64/23.08".to_string()),
+                },
+            ),
+            (
+                "org.kde.Platform/x86_64/5.15".to_string(),
+                RuntimeEol {
+                    reason: "no longer supported".to_string(),
+                    rebase: None,
+                },
+            ),
+        ]);
+
+        let apps = eol_runtime_apps(&[stuck, current, old], &eol);
+        let ids: Vec<_> = apps.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["com.example.Old", "com.example.Stuck"]);
+        assert_eq!(
+            apps[0].action,
+            "run `flatpak update --user com.example.Old` to move to org.freedesktop.Platform//23.08"
+        );
+        assert!(
+            apps[1].action.contains("unmaintained"),
+            "{}",
+            apps[1].action
+        );
+
+        let json = serde_json::to_value(&apps[0]).unwrap();
+        assert_eq!(json["reason"], "no longer supported");
+        assert_eq!(json["runtime"], "org.freedesktop.Platform/x86_64/21.08");
+    }
+
+    #[test]
+    fn short_runtime_drops_the_arch() {
+        assert_eq!(
+            short_runtime("org.freedesktop.Platform/x86_64/21.08"),
+            "org.freedesktop.Platform//21.08"
+        );
+        assert_eq!(short_runtime("odd-runtime"), "odd-runtime");
+    }
+
+    /// Records the `flatpak install` command lines it's asked to run and
+    /// fails any that mention `bad`.
+    #[derive(Default)]
+    struct InstallRecorder {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl CommandRunner for InstallRecorder {
+        fn run_output(
+            &self,
+            _program: &str,
+            _args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<std::process::Output> {
+            unreachable!("installs inherit stdio")
+        }
+
+        fn run_status(
+            &self,
+            _program: &str,
+            args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<std::process::ExitStatus> {
+            use std::os::unix::process::ExitStatusExt;
+
+            let line = args.join(" ");
+            let code = if line.contains("bad") { 1 << 8 } else { 0 };
+            self.calls.lock().unwrap().push(line);
+            Ok(std::process::ExitStatus::from_raw(code))
+        }
+    }
+
+    #[test]
+    fn pending_installs_batch_by_remote_and_scope() {
+        let mut beta = app("org.c.App", &[]);
+        beta.remote = "flathub-beta".to_string();
+        let mut system = app("org.d.App", &[]);
+        system.scope = FlatpakScope::System;
+        let items: Vec<FlatpakToInstall> =
+            [app("org.a.App", &[]), beta, system, app("org.b.App", &[])]
+                .into_iter()
+                .map(|app| FlatpakToInstall { app })
+                .collect();
+
+        let batches = batch_by_remote(&items);
+        let summary: Vec<(&str, FlatpakScope, Vec<&str>)> = batches
+            .iter()
+            .map(|b| {
+                let ids = b.apps.iter().map(|a| a.id.as_str()).collect();
+                (b.remote, b.scope, ids)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "flathub",
+                    FlatpakScope::User,
+                    vec!["org.a.App", "org.b.App"]
+                ),
+                ("flathub-beta", FlatpakScope::User, vec!["org.c.App"]),
+                ("flathub", FlatpakScope::System, vec!["org.d.App"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn batches_install_with_one_command_each_in_order() {
+        let mut bad = app("org.bad.App", &[]);
+        bad.remote = "other".to_string();
+        let items: Vec<FlatpakToInstall> = [app("org.a.App", &[]), bad, app("org.b.App", &[])]
+            .into_iter()
+            .map(|app| FlatpakToInstall { app })
+            .collect();
+        let batches = batch_by_remote(&items);
+
+        for jobs in [1, 4] {
+            let runner = InstallRecorder::default();
+            let results = run_batches(&batches, jobs, &runner);
+            let outcomes: Vec<bool> = results.into_iter().map(|r| r.unwrap()).collect();
+            assert_eq!(outcomes, [true, false]);
+
+            let mut calls = runner.calls.into_inner().unwrap();
+            calls.sort();
+            assert_eq!(
+                calls,
+                [
+                    "install -y --noninteractive --or-update --user flathub org.a.App org.b.App",
+                    "install -y --noninteractive --or-update --user other org.bad.App",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn partition_installed_keeps_only_successful_installs() {
+        let mut report = ExecutionReport::new();
+        report.record_success(Verb::Install, "flatpak:org.a.App");
+        report.record_failure(Verb::Install, "flatpak:org.b.App", "flatpak install failed");
+        // Overrides failing after a good install don't undo the install
+        report.record_failure(Verb::Configure, "flatpak:org.a.App:overrides", "no");
+
+        let (installed, failed) = partition_installed(
+            vec![
+                app("org.a.App", &[]),
+                app("org.b.App", &[]),
+                app("org.c.App", &[]),
+            ],
+            &report,
+        );
+
+        let ids: Vec<&str> = installed.iter().map(|app| app.id.as_str()).collect();
+        assert_eq!(ids, ["org.a.App", "org.c.App"]);
+        assert_eq!(
+            failed,
+            [(
+                "org.b.App".to_string(),
+                "flatpak install failed".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn permissions_args_map_to_override_flags() {
+        let args = PermissionsArgs {
+            action: None,
+            app_id: Some("org.mozilla.firefox".to_string()),
+            allow_filesystem: vec!["~/Downloads:ro".to_string()],
+            deny_filesystem: vec!["home".to_string()],
+            allow_socket: vec![],
+            deny_socket: vec!["x11".to_string()],
+            allow_device: vec!["dri".to_string()],
+            deny_device: vec![],
+            env: vec!["MOZ_ENABLE_WAYLAND=1".to_string()],
+            reset: false,
+        };
+
+        assert_eq!(
+            args.override_flags().unwrap(),
+            vec![
+                "--filesystem=~/Downloads:ro",
+                "--nofilesystem=home",
+                "--nosocket=x11",
+                "--device=dri",
+                "--env=MOZ_ENABLE_WAYLAND=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn override_drift_keys_only_cover_installed_apps() {
+        let manifest = FlatpakAppsManifest {
+            apps: vec![
+                app("org.example.Editor", &["--socket=wayland"]),
+                app("org.example.Missing", &["--device=dri"]),
+            ],
+            ..Default::default()
+        };
+        let live = |id: &str, _scope: FlatpakScope| match id {
+            "org.example.Editor" => vec!["--filesystem=home".to_string()],
+            _ => Vec::new(),
+        };
+
+        let (expected, actual) =
+            flatpak_override_drift_keys(&manifest, &[installed("org.example.Editor")], live);
+
+        assert_eq!(expected, vec!["org.example.Editor:--socket=wayland"]);
+        assert_eq!(actual, vec!["org.example.Editor:--filesystem=home"]);
+    }
+
+    fn remote(priority: Option<i32>, subset: Option<&str>) -> FlatpakRemote {
+        FlatpakRemote {
+            name: "flathub-beta".to_string(),
+            url: "https://flathub.org/beta-repo/flathub-beta.flatpakrepo".to_string(),
+            scope: FlatpakScope::User,
+            filtered: None,
+            priority,
+            subset: subset.map(str::to_string),
+            filter_path: None,
+            title: None,
+            repo_url: None,
+            gpg_key_sha256: None,
+        }
+    }
+
+    fn configured(priority: i32, subset: Option<&str>) -> ConfiguredRemote {
+        ConfiguredRemote {
+            name: "flathub-beta".to_string(),
+            priority,
+            subset: subset.map(str::to_string),
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn parse_configured_remotes_handles_unset_columns() {
+        let stdout = "flathub\t1\tverified\t-\nflathub-beta\t-5\t-\t/etc/flatpak/beta.filter\n\n";
+        assert_eq!(
+            parse_configured_remotes(stdout),
+            vec![
+                ConfiguredRemote {
+                    name: "flathub".to_string(),
+                    priority: 1,
+                    subset: Some("verified".to_string()),
+                    filter: None,
+                },
+                ConfiguredRemote {
+                    name: "flathub-beta".to_string(),
+                    priority: -5,
+                    subset: None,
+                    filter: Some("/etc/flatpak/beta.filter".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn remote_modify_flags_only_cover_recorded_differences() {
+        let remote = remote(Some(-5), None);
+        assert_eq!(
+            remote_modify_flags(&remote, &configured(1, Some("verified"))),
+            vec!["--prio=-5"]
+        );
+        assert!(remote_modify_flags(&remote, &configured(-5, None)).is_empty());
+    }
+
+    #[test]
+    fn capture_remote_settings_skips_default_priority() {
+        assert!(capture_remote_settings(&remote(None, None), &configured(1, None)).is_none());
+
+        let captured =
+            capture_remote_settings(&remote(None, None), &configured(-5, Some("verified")))
+                .unwrap();
+        assert_eq!(captured.priority, Some(-5));
+        assert_eq!(captured.subset.as_deref(), Some("verified"));
+    }
+
+    #[test]
+    fn missing_override_flags_ignores_flags_already_live() {
+        let app = app("org.example.Editor", &["--socket=wayland", "--device=dri"]);
+        let live = vec!["--device=dri".to_string()];
+        assert_eq!(
+            missing_override_flags(&app, &live),
+            vec!["--socket=wayland"]
+        );
     }
 }
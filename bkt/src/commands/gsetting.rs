@@ -1,16 +1,22 @@
 //! GSettings command implementation.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::manifest::gsetting::{schema_spec, split_schema_spec};
 use crate::manifest::{GSetting, GSettingsManifest};
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
-    ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, PlanWarning,
+    Plannable, Verb, print_report, print_summary,
 };
-use crate::validation::{validate_gsettings_key, validate_gsettings_schema};
-use anyhow::{Context, Result};
+use crate::validation::{
+    gsettings_key_type, validate_gsettings_key, validate_gsettings_relocatable_schema,
+    validate_gsettings_schema, validate_gsettings_value,
+};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
+use std::collections::HashMap;
 
 #[derive(Debug, Args)]
 pub struct GSettingArgs {
@@ -22,48 +28,111 @@ pub struct GSettingArgs {
 pub enum GSettingAction {
     /// Set a GSettings value in the manifest
     Set {
-        /// Schema name (e.g., org.gnome.desktop.interface)
+        /// Schema name (e.g., org.gnome.desktop.interface), or `schema:path`
+        /// for relocatable schemas
         schema: String,
         /// Key name
         key: String,
         /// Value (as GVariant string)
         value: String,
+        /// Path for a relocatable schema (alternative to `schema:path`)
+        #[arg(long)]
+        path: Option<String>,
         /// Optional comment
         #[arg(short, long)]
         comment: Option<String>,
-        /// Skip schema/key validation
+        /// Enforce the value system-wide with a dconf lock (takes effect
+        /// when the image is rebuilt)
+        #[arg(long)]
+        locked: bool,
+        /// Skip schema, key and value validation (for schemas not installed
+        /// locally)
         #[arg(long)]
         force: bool,
     },
     /// Remove a GSettings entry from the manifest
     Unset {
-        /// Schema name
+        /// Schema name, or `schema:path` for relocatable schemas
         schema: String,
         /// Key name
         key: String,
+        /// Path for a relocatable schema (alternative to `schema:path`)
+        #[arg(long)]
+        path: Option<String>,
     },
     /// List all GSettings in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Apply all GSettings from the manifest
     Apply,
     /// Capture current GSettings values to manifest
     Capture {
-        /// Schema name to capture (required - captures all keys from this schema)
+        /// Schema name to capture (required - captures all keys from this schema),
+        /// or `schema:path` for relocatable schemas. With --recursive, a
+        /// schema prefix such as `org.gnome.desktop`
         schema: String,
         /// Specific key to capture (optional - defaults to all keys in schema)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "recursive")]
         key: Option<String>,
+        /// Capture every installed schema under the given prefix
+        #[arg(short, long)]
+        recursive: bool,
+        /// Skip keys whose current value is the schema default
+        #[arg(long)]
+        non_default_only: bool,
         /// Apply the plan immediately (default is preview only)
         #[arg(long)]
         apply: bool,
     },
+    /// Capture GNOME custom keyboard shortcuts to manifest
+    ///
+    /// Records the list of custom keybinding paths plus the name, command,
+    /// and binding at each path.
+    CaptureKeybindings {
+        /// Apply the plan immediately (default is preview only)
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Record settings as you change them (e.g. in GNOME Settings)
+    ///
+    /// Watches dconf until Ctrl-C, then offers to add what changed to the
+    /// manifest. Keys changed back to their original value are left out.
+    Watch {
+        /// Only record schemas under this prefix (e.g. org.gnome.desktop)
+        #[arg(long)]
+        schema_prefix: Option<String>,
+        /// Add the changes without asking
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+/// Schema holding the list of custom keybinding paths.
+const MEDIA_KEYS_SCHEMA: &str = "org.gnome.settings-daemon.plugins.media-keys";
+/// Key listing custom keybinding paths in [`MEDIA_KEYS_SCHEMA`].
+const CUSTOM_KEYBINDINGS_KEY: &str = "custom-keybindings";
+/// Relocatable schema for a single custom keybinding.
+const CUSTOM_KEYBINDING_SCHEMA: &str =
+    "org.gnome.settings-daemon.plugins.media-keys.custom-keybinding";
+
+/// Combine a `schema[:path]` argument with an optional `--path` flag.
+fn resolve_schema_path(spec: &str, path: Option<String>) -> Result<(String, Option<String>)> {
+    let (schema, spec_path) = split_schema_spec(spec);
+    match (spec_path, path) {
+        (Some(_), Some(_)) => {
+            bail!("Specify the path either as schema:path or with --path, not both")
+        }
+        (Some(p), None) => Ok((schema.to_string(), Some(p.to_string()))),
+        (None, p) => Ok((schema.to_string(), p)),
+    }
 }
 
 /// Get current value of a gsetting.
+///
+/// `schema` may be a relocatable `schema:path` spec.
 fn get_current_value(schema: &str, key: &str, runner: &dyn CommandRunner) -> Option<String> {
     runner
         .run_output(
@@ -96,157 +165,165 @@ pub fn run(args: GSettingArgs, plan: &ExecutionPlan) -> Result<()> {
             schema,
             key,
             value,
+            path,
             comment,
+            locked,
             force,
         } => {
-            // Validate schema and key exist before modifying manifest
+            let (schema, path) = resolve_schema_path(&schema, path)?;
+            let mut setting = GSetting {
+                schema,
+                path,
+                key,
+                value,
+                value_type: None,
+                comment,
+                locked,
+            };
+            let id = setting.unique_key();
+            let spec = setting.schema_spec();
+
+            // Validate schema, key and value before modifying manifest
             if !force {
-                validate_gsettings_schema(runner, &schema)?;
-                validate_gsettings_key(runner, &schema, &key)?;
+                if setting.path.is_some() {
+                    validate_gsettings_relocatable_schema(runner, &setting.schema)?;
+                } else {
+                    validate_gsettings_schema(runner, &setting.schema)?;
+                }
+                validate_gsettings_key(runner, &spec, &setting.key)?;
+                setting.value_type = Some(validate_gsettings_value(
+                    runner,
+                    &spec,
+                    &setting.key,
+                    &setting.value,
+                )?);
             }
 
             let mut manifest = GSettingsManifest::load_repo()?;
 
             // Check if already set to same value
-            let existing = manifest.find(&schema, &key);
+            let existing = manifest.find(&setting.schema, setting.path.as_deref(), &setting.key);
 
             if plan.should_update_manifest() {
-                if let Some(e) = existing {
-                    if e.value == value {
-                        Output::info(format!(
-                            "Already in manifest: {}.{} = {}",
-                            schema, key, value
-                        ));
-                    } else {
-                        // Update in manifest
-                        let setting = GSetting {
-                            schema: schema.clone(),
-                            key: key.clone(),
-                            value: value.clone(),
-                            comment,
-                        };
-                        manifest.upsert(setting);
+                match existing {
+                    Some(e)
+                        if e.value == setting.value
+                            && e.value_type == setting.value_type
+                            && e.locked == setting.locked =>
+                    {
+                        Output::info(format!("Already in manifest: {} = {}", id, setting.value));
+                    }
+                    Some(_) => {
+                        manifest.upsert(setting.clone());
                         manifest.save_repo()?;
-                        Output::success(format!(
-                            "Updated in manifest: {}.{} = {}",
-                            schema, key, value
-                        ));
+                        Output::success(format!("Updated in manifest: {} = {}", id, setting.value));
+                    }
+                    None => {
+                        manifest.upsert(setting.clone());
+                        manifest.save_repo()?;
+                        Output::success(format!("Added to manifest: {} = {}", id, setting.value));
                     }
-                } else {
-                    let setting = GSetting {
-                        schema: schema.clone(),
-                        key: key.clone(),
-                        value: value.clone(),
-                        comment,
-                    };
-                    manifest.upsert(setting);
-                    manifest.save_repo()?;
-                    Output::success(format!("Added to manifest: {}.{} = {}", schema, key, value));
                 }
             } else if plan.dry_run {
-                Output::dry_run(format!(
-                    "Would set in manifest: {}.{} = {}",
-                    schema, key, value
-                ));
+                Output::dry_run(format!("Would set in manifest: {} = {}", id, setting.value));
             }
 
             // Apply immediately
-            if plan.should_execute_locally() {
-                let spinner =
-                    Output::spinner(format!("Applying {}.{} = {}...", schema, key, value));
-                if set_gsetting(&schema, &key, &value, runner)? {
-                    spinner.finish_success(format!("Applied {}.{}", schema, key));
+            if setting.locked {
+                Output::info(format!(
+                    "{} is locked; it's enforced once the image is rebuilt",
+                    id
+                ));
+            } else if plan.should_execute_locally() {
+                let spinner = Output::spinner(format!("Applying {} = {}...", id, setting.value));
+                if set_gsetting(&spec, &setting.key, &setting.value, runner)? {
+                    spinner.finish_success(format!("Applied {}", id));
                 } else {
-                    spinner.finish_error(format!("Failed to apply {}.{}", schema, key));
+                    spinner.finish_error(format!("Failed to apply {}", id));
                 }
             } else if plan.dry_run {
-                Output::dry_run(format!(
-                    "Would apply gsetting: {}.{} = {}",
-                    schema, key, value
-                ));
+                Output::dry_run(format!("Would apply gsetting: {} = {}", id, setting.value));
             }
 
             if plan.should_create_pr() {
                 let mut system_manifest = GSettingsManifest::load_repo()?;
                 let setting_for_pr = GSetting {
-                    schema: schema.clone(),
-                    key: key.clone(),
-                    value: value.clone(),
                     comment: None,
+                    ..setting
                 };
                 system_manifest.upsert(setting_for_pr);
                 let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
 
-                plan.maybe_create_pr(
-                    "gsetting",
-                    "set",
-                    &format!("{}.{}", schema, key),
-                    "gsettings.json",
-                    &manifest_content,
-                )?;
+                plan.maybe_create_pr("gsetting", "set", &id, "gsettings.json", &manifest_content)?;
             }
         }
-        GSettingAction::Unset { schema, key } => {
+        GSettingAction::Unset { schema, key, path } => {
+            let (schema, path) = resolve_schema_path(&schema, path)?;
+            let spec = schema_spec(&schema, path.as_deref());
+            let id = GSetting {
+                schema: schema.clone(),
+                path: path.clone(),
+                key: key.clone(),
+                value: String::new(),
+                value_type: None,
+                comment: None,
+                locked: false,
+            }
+            .unique_key();
+
             let mut manifest = GSettingsManifest::load_repo()?;
 
             if plan.should_update_manifest() {
-                if manifest.remove(&schema, &key) {
+                if manifest.remove(&schema, path.as_deref(), &key) {
                     manifest.save_repo()?;
-                    Output::success(format!("Removed from manifest: {}.{}", schema, key));
+                    Output::success(format!("Removed from manifest: {}", id));
                 } else {
-                    Output::warning(format!("Setting not found in manifest: {}.{}", schema, key));
+                    Output::warning(format!("Setting not found in manifest: {}", id));
                 }
             } else if plan.dry_run {
-                Output::dry_run(format!("Would remove from manifest: {}.{}", schema, key));
+                Output::dry_run(format!("Would remove from manifest: {}", id));
             }
 
             // Reset to default
             if plan.should_execute_locally() {
-                let spinner =
-                    Output::spinner(format!("Resetting {}.{} to default...", schema, key));
+                let spinner = Output::spinner(format!("Resetting {} to default...", id));
                 let status = runner
                     .run_status(
                         "gsettings",
-                        &["reset", &schema, &key],
+                        &["reset", &spec, &key],
                         &CommandOptions::default(),
                     )
                     .context("Failed to run gsettings reset")?;
                 if status.success() {
-                    spinner.finish_success(format!("Reset {}.{}", schema, key));
+                    spinner.finish_success(format!("Reset {}", id));
                 } else {
-                    spinner.finish_error(format!("Failed to reset {}.{}", schema, key));
+                    spinner.finish_error(format!("Failed to reset {}", id));
                 }
             } else if plan.dry_run {
-                Output::dry_run(format!(
-                    "Would reset gsetting to default: {}.{}",
-                    schema, key
-                ));
+                Output::dry_run(format!("Would reset gsetting to default: {}", id));
             }
 
             if plan.should_create_pr() {
                 let mut system_manifest = GSettingsManifest::load_repo()?;
-                if system_manifest.remove(&schema, &key) {
+                if system_manifest.remove(&schema, path.as_deref(), &key) {
                     let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
 
                     plan.maybe_create_pr(
                         "gsetting",
                         "unset",
-                        &format!("{}.{}", schema, key),
+                        &id,
                         "gsettings.json",
                         &manifest_content,
                     )?;
                 } else {
-                    Output::info(format!(
-                        "'{}.{}' not in manifest, no PR needed",
-                        schema, key
-                    ));
+                    Output::info(format!("'{}' not in manifest, no PR needed", id));
                 }
             }
         }
         GSettingAction::List { format } => {
-            let merged = GSettingsManifest::load_repo()?;
+            let merged = GSettingsManifest::load_effective()?;
 
-            if format == "json" {
+            if format == OutputFormat::Json {
                 println!("{}", serde_json::to_string_pretty(&merged)?);
             } else {
                 if merged.settings.is_empty() {
@@ -256,17 +333,22 @@ pub fn run(args: GSettingArgs, plan: &ExecutionPlan) -> Result<()> {
 
                 Output::subheader("GSETTINGS:");
                 println!(
-                    "{:<45} {:<20} {:<10} {}",
+                    "{:<45} {:<20} {:<12} {:<10} {}",
                     "SCHEMA.KEY".cyan(),
                     "VALUE".cyan(),
+                    "TYPE".cyan(),
                     "SOURCE".cyan(),
                     "CURRENT".cyan()
                 );
                 Output::separator();
 
                 for setting in &merged.settings {
-                    let source = "manifest".dimmed().to_string();
-                    let current = get_current_value(&setting.schema, &setting.key, runner)
+                    let source = if setting.locked {
+                        "locked".cyan().to_string()
+                    } else {
+                        "manifest".dimmed().to_string()
+                    };
+                    let current = get_current_value(&setting.schema_spec(), &setting.key, runner)
                         .unwrap_or_else(|| "(unset)".to_string());
                     let matches = if current == setting.value {
                         "✓".green().to_string()
@@ -274,10 +356,16 @@ pub fn run(args: GSettingArgs, plan: &ExecutionPlan) -> Result<()> {
                         "≠".yellow().to_string()
                     };
 
+                    let value_type = setting
+                        .value_type
+                        .as_ref()
+                        .map_or_else(|| "-".to_string(), |t| t.short());
+
                     println!(
-                        "{:<45} {:<20} {:<10} {} {}",
-                        format!("{}.{}", setting.schema, setting.key),
+                        "{:<45} {:<20} {:<12} {:<10} {} {}",
+                        setting.unique_key(),
                         truncate(&setting.value, 18),
+                        truncate(&value_type, 12),
                         source,
                         matches,
                         truncate(&current, 15)
@@ -295,13 +383,14 @@ pub fn run(args: GSettingArgs, plan: &ExecutionPlan) -> Result<()> {
 
             let apply_plan = GsettingApplyCommand.plan(&plan_ctx)?;
 
-            if apply_plan.is_empty() {
+            if apply_plan.is_empty() && !plan.json_output() {
                 Output::success("All settings are already applied.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", apply_plan.describe());
+            let summary = apply_plan.describe().with_subsystem("gsetting");
+            print_summary(&summary, plan, plan.dry_run)?;
 
             if plan.dry_run {
                 return Ok(());
@@ -310,43 +399,97 @@ pub fn run(args: GSettingArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = apply_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("gsetting"), plan)?;
+        }
+        GSettingAction::Capture {
+            schema,
+            recursive: true,
+            non_default_only,
+            apply,
+            ..
+        } => {
+            let cwd = std::env::current_dir()?;
+            let plan_ctx = PlanContext::new(cwd, plan.clone());
+
+            let capture_plan = GsettingTreeCaptureCommand {
+                prefix: schema,
+                non_default_only,
+            }
+            .plan(&plan_ctx)?;
+
+            run_capture_plan(capture_plan, apply, plan)?;
         }
-        GSettingAction::Capture { schema, key, apply } => {
+        GSettingAction::Capture {
+            schema,
+            key,
+            non_default_only,
+            apply,
+            ..
+        } => {
+            let (schema, path) = resolve_schema_path(&schema, None)?;
+
             // Validate schema exists
-            validate_gsettings_schema(runner, &schema)?;
+            if path.is_some() {
+                validate_gsettings_relocatable_schema(runner, &schema)?;
+            } else {
+                validate_gsettings_schema(runner, &schema)?;
+            }
 
             // Use the Plan-based capture implementation
             let cwd = std::env::current_dir()?;
             let plan_ctx = PlanContext::new(cwd, plan.clone());
 
             let capture_plan = GsettingCaptureCommand {
-                schema: schema.clone(),
+                schema,
+                path,
                 key: key.clone(),
+                non_default_only,
             }
             .plan(&plan_ctx)?;
 
-            if capture_plan.is_empty() {
-                Output::success("All settings are already in the manifest.");
-                return Ok(());
-            }
+            run_capture_plan(capture_plan, apply, plan)?;
+        }
+        GSettingAction::CaptureKeybindings { apply } => {
+            let cwd = std::env::current_dir()?;
+            let plan_ctx = PlanContext::new(cwd, plan.clone());
 
-            // Always show the plan
-            print!("{}", capture_plan.describe());
+            let capture_plan = KeybindingCaptureCommand.plan(&plan_ctx)?;
 
-            if plan.dry_run || !apply {
-                if !apply && !plan.dry_run {
-                    Output::hint("Use --apply to execute this plan.");
-                }
-                return Ok(());
-            }
+            run_capture_plan(capture_plan, apply, plan)?;
+        }
+        GSettingAction::Watch { schema_prefix, yes } => {
+            watch(schema_prefix.as_deref(), yes, plan)?;
+        }
+    }
+    Ok(())
+}
 
-            // Execute the plan
-            let mut exec_ctx = ExecuteContext::new(plan.clone());
-            let report = capture_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+/// Preview a capture plan, executing it when `apply` is set.
+fn run_capture_plan(
+    capture_plan: GsettingCapturePlan,
+    apply: bool,
+    plan: &ExecutionPlan,
+) -> Result<()> {
+    if capture_plan.is_empty() && !plan.json_output() {
+        Output::success("All settings are already in the manifest.");
+        return Ok(());
+    }
+
+    // Always show the plan
+    let summary = capture_plan.describe().with_subsystem("gsetting");
+    print_summary(&summary, plan, plan.dry_run || !apply)?;
+
+    if plan.dry_run || !apply {
+        if !apply && !plan.dry_run && !plan.json_output() {
+            Output::hint("Use --apply to execute this plan.");
         }
+        return Ok(());
     }
+
+    // Execute the plan
+    let mut exec_ctx = ExecuteContext::new(plan.clone());
+    let report = capture_plan.execute(&mut exec_ctx)?;
+    print_report(&report.with_subsystem("gsetting"), plan)?;
     Ok(())
 }
 
@@ -381,8 +524,13 @@ pub struct GsettingApplyCommand;
 pub struct GsettingApplyPlan {
     /// Settings to apply (value differs from current).
     pub to_apply: Vec<SettingToApply>,
+    /// Settings whose value doesn't fit their recorded type, with the
+    /// expected type.
+    pub invalid: Vec<(GSetting, String)>,
     /// Settings already in sync.
     pub already_set: usize,
+    /// Locked settings, which the image enforces through dconf.
+    pub locked: Vec<GSetting>,
 }
 
 impl Plannable for GsettingApplyCommand {
@@ -392,13 +540,30 @@ impl Plannable for GsettingApplyCommand {
         let runner = ctx.execution_plan().runner();
 
         // Load manifest (read-only, no side effects)
-        let merged = GSettingsManifest::load_repo()?;
+        let merged = GSettingsManifest::load_effective()?;
 
         let mut to_apply = Vec::new();
+        let mut invalid = Vec::new();
         let mut already_set = 0;
+        let mut locked = Vec::new();
 
         for setting in merged.settings {
-            let current = get_current_value(&setting.schema, &setting.key, runner);
+            // System-enforced; a session write would be refused anyway
+            if setting.locked {
+                locked.push(setting);
+                continue;
+            }
+
+            // The recorded type lets us reject bad values without a gsettings call
+            if let Some(value_type) = &setting.value_type
+                && !value_type.accepts(&setting.value)
+            {
+                let expected = value_type.expected();
+                invalid.push((setting, expected));
+                continue;
+            }
+
+            let current = get_current_value(&setting.schema_spec(), &setting.key, runner);
 
             if current.as_deref() == Some(&setting.value) {
                 already_set += 1;
@@ -409,7 +574,9 @@ impl Plannable for GsettingApplyCommand {
 
         Ok(GsettingApplyPlan {
             to_apply,
+            invalid,
             already_set,
+            locked,
         })
     }
 }
@@ -426,24 +593,48 @@ impl Plan for GsettingApplyPlan {
             let current_display = item.current.as_deref().unwrap_or("(unset)");
             summary.add_operation(Operation::with_details(
                 Verb::Set,
-                format!("gsetting:{}.{}", item.setting.schema, item.setting.key),
+                format!("gsetting:{}", item.setting.unique_key()),
                 format!("{} → {}", current_display, item.setting.value),
             ));
         }
 
+        for setting in &self.locked {
+            summary.add_operation(Operation::with_details(
+                Verb::Skip,
+                format!("gsetting:{}", setting.unique_key()),
+                "locked/enforced",
+            ));
+        }
+
+        for (setting, expected) in &self.invalid {
+            summary.add_warning(PlanWarning::new(
+                format!("gsetting:{}", setting.unique_key()),
+                format!("value {} is not {}; skipping", setting.value, expected),
+            ));
+        }
+
         summary
     }
 
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
 
+        for (setting, expected) in self.invalid {
+            report.record_failure_and_notify(
+                ctx,
+                Verb::Set,
+                format!("gsetting:{}", setting.unique_key()),
+                format!("value {} is not {}", setting.value, expected),
+            );
+        }
+
         for item in self.to_apply {
-            let target = format!("{}.{}", item.setting.schema, item.setting.key);
+            let target = item.setting.unique_key();
 
             let result = {
                 let runner = ctx.execution_plan().runner();
                 set_gsetting(
-                    &item.setting.schema,
+                    &item.setting.schema_spec(),
                     &item.setting.key,
                     &item.setting.value,
                     runner,
@@ -481,7 +672,7 @@ impl Plan for GsettingApplyPlan {
     }
 
     fn is_empty(&self) -> bool {
-        self.to_apply.is_empty()
+        self.to_apply.is_empty() && self.invalid.is_empty()
     }
 }
 
@@ -489,7 +680,7 @@ impl Plan for GsettingApplyPlan {
 // Plan-based GSettings Capture Implementation
 // ============================================================================
 
-/// Get all keys for a schema.
+/// Get all keys for a schema (or relocatable `schema:path` spec).
 fn get_schema_keys(schema: &str, runner: &dyn CommandRunner) -> Vec<String> {
     let output = runner.run_output(
         "gsettings",
@@ -507,19 +698,110 @@ fn get_schema_keys(schema: &str, runner: &dyn CommandRunner) -> Vec<String> {
     }
 }
 
+/// List installed (non-relocatable) schemas.
+fn list_schemas(runner: &dyn CommandRunner) -> Result<Vec<String>> {
+    let output = runner
+        .run_output("gsettings", &["list-schemas"], &CommandOptions::default())
+        .context("Failed to run gsettings list-schemas")?;
+    if !output.status.success() {
+        bail!("gsettings list-schemas failed");
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
+
+/// Whether `schema` is `prefix` itself or nested below it
+/// (`org.gnome.desktop` covers `org.gnome.desktop.interface`, not
+/// `org.gnome.desktopx`).
+fn schema_under_prefix(schema: &str, prefix: &str) -> bool {
+    schema
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Key/value pairs of a schema (or `schema:path` spec).
+///
+/// With `defaults`, reads through the in-memory GSettings backend, which has
+/// no stored values, so every key reports its schema default without
+/// resetting anything.
+fn list_recursively(
+    spec: &str,
+    defaults: bool,
+    runner: &dyn CommandRunner,
+) -> Vec<(String, String)> {
+    let mut options = CommandOptions::default();
+    if defaults {
+        options
+            .env
+            .push(("GSETTINGS_BACKEND".to_string(), "memory".to_string()));
+    }
+    let (schema, _) = split_schema_spec(spec);
+    match runner.run_output("gsettings", &["list-recursively", spec], &options) {
+        Ok(o) if o.status.success() => {
+            parse_list_recursively(&String::from_utf8_lossy(&o.stdout), schema)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Parse `gsettings list-recursively` output (`schema key value` lines),
+/// keeping only keys of `schema`; the output also covers child schemas.
+fn parse_list_recursively(output: &str, schema: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (line_schema, rest) = line.split_once(' ')?;
+            let (key, value) = rest.split_once(' ')?;
+            (line_schema == schema).then(|| (key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Drop settings whose value equals their schema default.
+fn drop_defaults(settings: Vec<GSetting>, runner: &dyn CommandRunner) -> Vec<GSetting> {
+    let mut defaults: HashMap<String, HashMap<String, String>> = HashMap::new();
+    settings
+        .into_iter()
+        .filter(|setting| {
+            let spec = setting.schema_spec();
+            let schema_defaults = defaults
+                .entry(spec.clone())
+                .or_insert_with(|| list_recursively(&spec, true, runner).into_iter().collect());
+            schema_defaults.get(&setting.key) != Some(&setting.value)
+        })
+        .collect()
+}
+
 /// A setting to capture (add to manifest).
 #[derive(Debug, Clone)]
 pub struct SettingToCapture {
     /// The gsetting entry.
     pub setting: GSetting,
+    /// Value currently in the manifest, when capture updates an entry.
+    pub previous: Option<String>,
 }
 
 /// Command to capture GSettings to manifest.
 pub struct GsettingCaptureCommand {
     /// Schema to capture from.
     pub schema: String,
+    /// Path, for relocatable schemas.
+    pub path: Option<String>,
     /// Specific key (or all keys if None).
     pub key: Option<String>,
+    /// Skip keys still at their schema default.
+    pub non_default_only: bool,
+}
+
+/// Command to capture every schema under a dotted prefix.
+pub struct GsettingTreeCaptureCommand {
+    /// Schema prefix (e.g. `org.gnome.desktop`).
+    pub prefix: String,
+    /// Skip keys still at their schema default.
+    pub non_default_only: bool,
 }
 
 /// Plan for capturing GSettings.
@@ -535,43 +817,495 @@ impl Plannable for GsettingCaptureCommand {
 
     fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
         let runner = ctx.execution_plan().runner();
+        let spec = schema_spec(&self.schema, self.path.as_deref());
 
         // Get keys to capture
         let keys = if let Some(ref key) = self.key {
             vec![key.clone()]
         } else {
-            get_schema_keys(&self.schema, runner)
+            get_schema_keys(&spec, runner)
         };
 
-        // Load manifests to see what's already tracked
-        let merged = GSettingsManifest::load_repo()?;
-
-        let mut to_capture = Vec::new();
-        let mut already_in_manifest = 0;
-
-        for key in keys {
-            if merged.find(&self.schema, &key).is_some() {
-                already_in_manifest += 1;
-            } else if let Some(value) = get_current_value(&self.schema, &key, runner) {
-                to_capture.push(SettingToCapture {
-                    setting: GSetting {
-                        schema: self.schema.clone(),
-                        key,
-                        value,
-                        comment: None,
-                    },
+        let targets = keys
+            .into_iter()
+            .map(|key| (self.schema.clone(), self.path.clone(), key));
+        let mut plan = plan_capture(targets, self.non_default_only, runner)?;
+
+        // Sort for consistent output
+        plan.to_capture
+            .sort_by(|a, b| a.setting.key.cmp(&b.setting.key));
+
+        Ok(plan)
+    }
+}
+
+impl Plannable for GsettingTreeCaptureCommand {
+    type Plan = GsettingCapturePlan;
+
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
+        let runner = ctx.execution_plan().runner();
+
+        let schemas: Vec<String> = list_schemas(runner)?
+            .into_iter()
+            .filter(|schema| schema_under_prefix(schema, &self.prefix))
+            .collect();
+        if schemas.is_empty() {
+            bail!("No installed schemas match '{}'", self.prefix);
+        }
+
+        // One list-recursively per schema reads every value in a single call
+        let mut settings = Vec::new();
+        for schema in schemas {
+            for (key, value) in list_recursively(&schema, false, runner) {
+                settings.push(GSetting {
+                    schema: schema.clone(),
+                    path: None,
+                    key,
+                    value,
+                    value_type: None,
+                    comment: None,
+                    locked: false,
                 });
             }
         }
+        if self.non_default_only {
+            settings = drop_defaults(settings, runner);
+        }
 
-        // Sort for consistent output
-        to_capture.sort_by(|a, b| a.setting.key.cmp(&b.setting.key));
+        let manifest = GSettingsManifest::load_effective()?;
+        let mut plan = plan_from_values(settings, &manifest);
+        detect_types(&mut plan, runner);
+        plan.to_capture
+            .sort_by_key(|item| item.setting.unique_key());
+        Ok(plan)
+    }
+}
+
+/// Command to capture GNOME custom keybindings to manifest.
+///
+/// Custom keybindings live in a relocatable schema, one path per binding,
+/// with the list of paths stored in the media-keys schema.
+pub struct KeybindingCaptureCommand;
+
+impl Plannable for KeybindingCaptureCommand {
+    type Plan = GsettingCapturePlan;
 
-        Ok(GsettingCapturePlan {
-            to_capture,
-            already_in_manifest,
+    fn plan(&self, ctx: &PlanContext) -> Result<Self::Plan> {
+        let runner = ctx.execution_plan().runner();
+
+        let paths = get_current_value(MEDIA_KEYS_SCHEMA, CUSTOM_KEYBINDINGS_KEY, runner)
+            .map(|value| parse_string_array(&value))
+            .unwrap_or_default();
+
+        let mut targets = vec![(
+            MEDIA_KEYS_SCHEMA.to_string(),
+            None,
+            CUSTOM_KEYBINDINGS_KEY.to_string(),
+        )];
+        for path in paths {
+            let spec = schema_spec(CUSTOM_KEYBINDING_SCHEMA, Some(&path));
+            for key in get_schema_keys(&spec, runner) {
+                targets.push((
+                    CUSTOM_KEYBINDING_SCHEMA.to_string(),
+                    Some(path.clone()),
+                    key,
+                ));
+            }
+        }
+
+        plan_capture(targets, false, runner)
+    }
+}
+
+/// Build a capture plan for `(schema, path, key)` targets from their
+/// current values.
+fn plan_capture(
+    targets: impl IntoIterator<Item = (String, Option<String>, String)>,
+    non_default_only: bool,
+    runner: &dyn CommandRunner,
+) -> Result<GsettingCapturePlan> {
+    let mut settings: Vec<GSetting> = targets
+        .into_iter()
+        .filter_map(|(schema, path, key)| {
+            let value = get_current_value(&schema_spec(&schema, path.as_deref()), &key, runner)?;
+            Some(GSetting {
+                schema,
+                path,
+                key,
+                value,
+                value_type: None,
+                comment: None,
+                locked: false,
+            })
         })
+        .collect();
+    if non_default_only {
+        settings = drop_defaults(settings, runner);
+    }
+
+    // Load manifests to see what's already tracked
+    let merged = GSettingsManifest::load_effective()?;
+    let mut plan = plan_from_values(settings, &merged);
+    detect_types(&mut plan, runner);
+    Ok(plan)
+}
+
+/// Record the schema type of each setting about to be captured.
+///
+/// Values come from gsettings itself, so one that doesn't fit its type
+/// is captured without one rather than dropped.
+fn detect_types(plan: &mut GsettingCapturePlan, runner: &dyn CommandRunner) {
+    for item in &mut plan.to_capture {
+        let setting = &mut item.setting;
+        match gsettings_key_type(runner, &setting.schema_spec(), &setting.key) {
+            Ok(value_type) if value_type.accepts(&setting.value) => {
+                setting.value_type = Some(value_type);
+            }
+            Ok(value_type) => tracing::debug!(
+                "{}: captured value {} is not {}",
+                setting.unique_key(),
+                setting.value,
+                value_type.expected()
+            ),
+            Err(e) => tracing::debug!("{}: {}", setting.unique_key(), e),
+        }
+    }
+}
+
+/// Plan captures for `settings`, skipping those the manifest already has
+/// with the same value; a different value updates the manifest entry.
+fn plan_from_values(
+    settings: impl IntoIterator<Item = GSetting>,
+    manifest: &GSettingsManifest,
+) -> GsettingCapturePlan {
+    let mut to_capture = Vec::new();
+    let mut already_in_manifest = 0;
+
+    for setting in settings {
+        let existing = manifest.find(&setting.schema, setting.path.as_deref(), &setting.key);
+        match existing {
+            // Locked settings are enforced by the image, not captured over
+            Some(e) if e.value == setting.value || e.locked => already_in_manifest += 1,
+            _ => to_capture.push(SettingToCapture {
+                previous: existing.map(|e| e.value.clone()),
+                setting,
+            }),
+        }
+    }
+
+    GsettingCapturePlan {
+        to_capture,
+        already_in_manifest,
+    }
+}
+
+/// Parse a GVariant string array as printed by `gsettings get`
+/// (e.g., `['/a/', '/b/']` or `@as []`).
+fn parse_string_array(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let value = value.strip_prefix("@as").unwrap_or(value).trim();
+    let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) else {
+        return Vec::new();
+    };
+
+    inner
+        .split(',')
+        .map(|item| item.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// ============================================================================
+// Watch
+// ============================================================================
+
+/// Relocatable schemas whose instances live one directory below a fixed
+/// parent, so a dconf path there can be mapped back to `schema:path`.
+const RELOCATABLE_PARENTS: &[(&str, &str)] = &[(
+    "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/",
+    CUSTOM_KEYBINDING_SCHEMA,
+)];
+
+/// A dconf key mapped back to the schema it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchedKey {
+    schema: String,
+    path: Option<String>,
+    key: String,
+}
+
+impl WatchedKey {
+    fn spec(&self) -> String {
+        schema_spec(&self.schema, self.path.as_deref())
+    }
+}
+
+/// Parse `gsettings list-schemas --print-paths` into dconf dir → schema.
+fn parse_schema_paths(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (schema, path) = line.trim().split_once(' ')?;
+            Some((path.trim().to_string(), schema.to_string()))
+        })
+        .collect()
+}
+
+/// Map a dconf key path (`/org/gnome/desktop/interface/color-scheme`) to
+/// its schema and key.
+fn map_dconf_path(dconf_path: &str, schema_paths: &HashMap<String, String>) -> Option<WatchedKey> {
+    let (dir, key) = dconf_path.rsplit_once('/')?;
+    if key.is_empty() {
+        return None;
+    }
+    let dir = format!("{}/", dir);
+
+    if let Some(schema) = schema_paths.get(&dir) {
+        return Some(WatchedKey {
+            schema: schema.clone(),
+            path: None,
+            key: key.to_string(),
+        });
+    }
+
+    RELOCATABLE_PARENTS.iter().find_map(|(parent, schema)| {
+        let name = dir.strip_prefix(parent)?.strip_suffix('/')?;
+        (!name.is_empty() && !name.contains('/')).then(|| WatchedKey {
+            schema: schema.to_string(),
+            path: Some(dir.clone()),
+            key: key.to_string(),
+        })
+    })
+}
+
+/// Parse `dconf dump /` into key path → value.
+fn parse_dconf_dump(output: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut dir = String::from("/");
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            dir = match section.trim_matches('/') {
+                "" => "/".to_string(),
+                section => format!("/{}/", section),
+            };
+        } else if let Some((key, value)) = line.split_once('=') {
+            values.insert(format!("{}{}", dir, key), value.to_string());
+        }
+    }
+    values
+}
+
+/// The key path a `dconf watch` output line announces, if it's one.
+///
+/// Each change prints the path, then the new value indented, then a
+/// blank line. Paths ending in `/` are whole directories being reset.
+fn watch_event_path(line: &str) -> Option<&str> {
+    (line.starts_with('/') && !line.ends_with('/')).then_some(line.trim_end())
+}
+
+/// Settings for `changed` keys (by dconf path) whose value now differs
+/// from the one they had when watching started.
+///
+/// `original` is the `dconf dump` from the start; a key missing from it
+/// was at its schema default, which `default_value` looks up.
+fn settle_changes(
+    changed: &[(String, WatchedKey)],
+    original: &HashMap<String, String>,
+    current_value: impl Fn(&WatchedKey) -> Option<String>,
+    mut default_value: impl FnMut(&WatchedKey) -> Option<String>,
+) -> Vec<GSetting> {
+    changed
+        .iter()
+        .filter_map(|(dconf_path, watched)| {
+            let value = current_value(watched)?;
+            let before = match original.get(dconf_path) {
+                Some(before) => Some(before.clone()),
+                None => default_value(watched),
+            };
+            if before.as_deref() == Some(value.as_str()) {
+                return None;
+            }
+            Some(GSetting {
+                schema: watched.schema.clone(),
+                path: watched.path.clone(),
+                key: watched.key.clone(),
+                value,
+                value_type: None,
+                comment: None,
+                locked: false,
+            })
+        })
+        .collect()
+}
+
+/// Run a command and return its stdout, failing on a non-zero exit.
+fn read_output(program: &str, args: &[&str], runner: &dyn CommandRunner) -> Result<String> {
+    let output = runner
+        .run_output(program, args, &CommandOptions::default())
+        .with_context(|| format!("Failed to run {} {}", program, args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Handle `bkt gsetting watch`.
+fn watch(schema_prefix: Option<&str>, yes: bool, plan: &ExecutionPlan) -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    let runner = plan.runner();
+    let schema_paths = parse_schema_paths(&read_output(
+        "gsettings",
+        &["list-schemas", "--print-paths"],
+        runner,
+    )?);
+    let original = parse_dconf_dump(&read_output("dconf", &["dump", "/"], runner)?);
+
+    let mut child = Command::new("dconf")
+        .args(["watch", "/"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run dconf watch")?;
+    let stdout = child.stdout.take().context("dconf watch has no stdout")?;
+
+    // The terminal delivers Ctrl-C to dconf too; ignore it here so we
+    // outlive the child and get to the summary.
+    ctrlc::set_handler(|| {}).context("Failed to set Ctrl-C handler")?;
+    Output::info("Watching for settings changes (Ctrl-C to finish)");
+
+    let mut changed: Vec<(String, WatchedKey)> = Vec::new();
+    let mut unmapped: Vec<String> = Vec::new();
+    for line in BufReader::new(stdout).lines() {
+        let line = line.context("Failed to read dconf watch output")?;
+        let Some(dconf_path) = watch_event_path(&line) else {
+            continue;
+        };
+        if changed.iter().any(|(p, _)| p == dconf_path) {
+            continue;
+        }
+        let Some(watched) = map_dconf_path(dconf_path, &schema_paths) else {
+            if schema_prefix.is_none() && !unmapped.iter().any(|p| p == dconf_path) {
+                unmapped.push(dconf_path.to_string());
+            }
+            continue;
+        };
+        if schema_prefix.is_some_and(|prefix| !schema_under_prefix(&watched.schema, prefix)) {
+            continue;
+        }
+        Output::list_item(format!("{} {}", watched.spec(), watched.key));
+        changed.push((dconf_path.to_string(), watched));
     }
+    let _ = child.wait();
+    Output::blank();
+
+    for dconf_path in &unmapped {
+        Output::info(format!("Skipping {} (no schema found)", dconf_path));
+    }
+
+    let mut defaults: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let settings = settle_changes(
+        &changed,
+        &original,
+        |watched| get_current_value(&watched.spec(), &watched.key, runner),
+        |watched| {
+            defaults
+                .entry(watched.spec())
+                .or_insert_with(|| {
+                    list_recursively(&watched.spec(), true, runner)
+                        .into_iter()
+                        .collect()
+                })
+                .get(&watched.key)
+                .cloned()
+        },
+    );
+
+    let manifest = GSettingsManifest::load_effective()?;
+    let mut capture_plan = plan_from_values(settings, &manifest);
+    detect_types(&mut capture_plan, runner);
+    capture_plan
+        .to_capture
+        .sort_by_key(|item| item.setting.unique_key());
+
+    if capture_plan.is_empty() {
+        Output::success("No settings changed that aren't already in the manifest.");
+        return Ok(());
+    }
+
+    let summary = capture_plan.describe().with_subsystem("gsetting");
+    print_summary(&summary, plan, true)?;
+    if plan.dry_run {
+        return Ok(());
+    }
+
+    if !yes && !confirm_watch_capture(capture_plan.to_capture.len())? {
+        Output::info("Nothing added.");
+        return Ok(());
+    }
+
+    // The settings are already live; only the manifest and PR remain
+    let captured: Vec<GSetting> = capture_plan
+        .to_capture
+        .iter()
+        .map(|item| item.setting.clone())
+        .collect();
+
+    if plan.should_update_manifest() {
+        let mut exec_ctx = ExecuteContext::new(plan.clone());
+        let report = capture_plan.execute(&mut exec_ctx)?;
+        print_report(&report.with_subsystem("gsetting"), plan)?;
+    }
+
+    if plan.should_create_pr() {
+        let mut system_manifest = GSettingsManifest::load_repo()?;
+        for setting in &captured {
+            system_manifest.upsert(setting.clone());
+        }
+        let manifest_content = serde_json::to_string_pretty(&system_manifest)?;
+        let name = match captured.as_slice() {
+            [only] => only.unique_key(),
+            _ => format!("{} settings", captured.len()),
+        };
+        plan.maybe_create_pr(
+            "gsetting",
+            "capture",
+            &name,
+            "gsettings.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Ask whether to add `count` watched changes to the manifest.
+///
+/// Returns `Ok(false)` when stdin isn't a terminal.
+fn confirm_watch_capture(count: usize) -> Result<bool> {
+    use is_terminal::IsTerminal;
+
+    if !std::io::stdin().is_terminal() {
+        Output::hint("Not a terminal; re-run with --yes to add the changes.");
+        return Ok(false);
+    }
+
+    print!("Add {} setting(s) to the manifest? [y/N] ", count);
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        return Ok(false);
+    }
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
 }
 
 impl Plan for GsettingCapturePlan {
@@ -583,10 +1317,18 @@ impl Plan for GsettingCapturePlan {
         ));
 
         for item in &self.to_capture {
+            let details = match &item.previous {
+                Some(previous) => format!(
+                    "{} → {}",
+                    truncate(previous, 30),
+                    truncate(&item.setting.value, 30)
+                ),
+                None => truncate(&item.setting.value, 30),
+            };
             summary.add_operation(Operation::with_details(
                 Verb::Capture,
-                format!("gsetting:{}.{}", item.setting.schema, item.setting.key),
-                truncate(&item.setting.value, 30),
+                format!("gsetting:{}", item.setting.unique_key()),
+                details,
             ));
         }
 
@@ -600,7 +1342,7 @@ impl Plan for GsettingCapturePlan {
         let mut manifest = GSettingsManifest::load_repo()?;
 
         for item in self.to_capture {
-            let target = format!("{}.{}", item.setting.schema, item.setting.key);
+            let target = item.setting.unique_key();
             manifest.upsert(item.setting);
             report.record_success(Verb::Capture, format!("gsetting:{}", target));
         }
@@ -615,3 +1357,234 @@ impl Plan for GsettingCapturePlan {
         self.to_capture.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_string_array_handles_gsettings_output() {
+        assert_eq!(
+            parse_string_array(
+                "['/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/', '/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom1/']"
+            ),
+            vec![
+                "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/",
+                "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom1/",
+            ]
+        );
+        assert!(parse_string_array("@as []").is_empty());
+        assert!(parse_string_array("nothing").is_empty());
+    }
+
+    #[test]
+    fn parse_list_recursively_keeps_only_the_schema() {
+        let output = "org.gnome.desktop.interface clock-format '24h'\n\
+                      org.gnome.desktop.interface font-name 'Cantarell 11'\n\
+                      org.gnome.desktop.interface.child key true\n";
+        assert_eq!(
+            parse_list_recursively(output, "org.gnome.desktop.interface"),
+            vec![
+                ("clock-format".to_string(), "'24h'".to_string()),
+                ("font-name".to_string(), "'Cantarell 11'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_under_prefix_matches_whole_components() {
+        assert!(schema_under_prefix(
+            "org.gnome.desktop",
+            "org.gnome.desktop"
+        ));
+        assert!(schema_under_prefix(
+            "org.gnome.desktop.interface",
+            "org.gnome.desktop"
+        ));
+        assert!(!schema_under_prefix(
+            "org.gnome.desktopx",
+            "org.gnome.desktop"
+        ));
+        assert!(!schema_under_prefix("org.gnome", "org.gnome.desktop"));
+    }
+
+    #[test]
+    fn plan_from_values_skips_same_value_and_updates_changed() {
+        let setting = |key: &str, value: &str| GSetting {
+            schema: "org.gnome.desktop.interface".to_string(),
+            path: None,
+            key: key.to_string(),
+            value: value.to_string(),
+            value_type: None,
+            comment: None,
+            locked: false,
+        };
+        let manifest = GSettingsManifest {
+            schema: None,
+            settings: vec![
+                setting("clock-format", "'24h'"),
+                setting("color-scheme", "'default'"),
+            ],
+        };
+
+        let plan = plan_from_values(
+            vec![
+                setting("clock-format", "'24h'"),
+                setting("color-scheme", "'prefer-dark'"),
+                setting("font-name", "'Cantarell 11'"),
+            ],
+            &manifest,
+        );
+
+        assert_eq!(plan.already_in_manifest, 1);
+        let captured: Vec<_> = plan
+            .to_capture
+            .iter()
+            .map(|item| (item.setting.key.as_str(), item.previous.as_deref()))
+            .collect();
+        assert_eq!(
+            captured,
+            vec![("color-scheme", Some("'default'")), ("font-name", None)]
+        );
+    }
+
+    #[test]
+    fn plan_from_values_leaves_locked_settings_alone() {
+        let manifest = GSettingsManifest {
+            schema: None,
+            settings: vec![GSetting {
+                schema: "org.gnome.desktop.screensaver".to_string(),
+                path: None,
+                key: "lock-delay".to_string(),
+                value: "uint32 0".to_string(),
+                value_type: None,
+                comment: None,
+                locked: true,
+            }],
+        };
+        let session_value = GSetting {
+            value: "uint32 300".to_string(),
+            locked: false,
+            ..manifest.settings[0].clone()
+        };
+
+        let plan = plan_from_values(vec![session_value], &manifest);
+        assert!(plan.is_empty());
+        assert_eq!(plan.already_in_manifest, 1);
+    }
+
+    fn schema_paths() -> HashMap<String, String> {
+        parse_schema_paths(
+            "org.gnome.desktop.interface /org/gnome/desktop/interface/\n\
+             org.gnome.mutter /org/gnome/mutter/\n",
+        )
+    }
+
+    #[test]
+    fn map_dconf_path_finds_schema_and_key() {
+        let paths = schema_paths();
+        assert_eq!(
+            map_dconf_path("/org/gnome/desktop/interface/color-scheme", &paths),
+            Some(WatchedKey {
+                schema: "org.gnome.desktop.interface".to_string(),
+                path: None,
+                key: "color-scheme".to_string(),
+            })
+        );
+        assert_eq!(
+            map_dconf_path(
+                "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/binding",
+                &paths
+            ),
+            Some(WatchedKey {
+                schema: CUSTOM_KEYBINDING_SCHEMA.to_string(),
+                path: Some(
+                    "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/"
+                        .to_string()
+                ),
+                key: "binding".to_string(),
+            })
+        );
+        assert_eq!(map_dconf_path("/org/example/app/key", &paths), None);
+        assert_eq!(map_dconf_path("/org/gnome/mutter/", &paths), None);
+    }
+
+    #[test]
+    fn parse_dconf_dump_keys_by_path() {
+        let dump = "[org/gnome/desktop/interface]\n\
+                    color-scheme='prefer-dark'\n\
+                    \n\
+                    [org/gnome/mutter]\n\
+                    dynamic-workspaces=false\n";
+        let values = parse_dconf_dump(dump);
+        assert_eq!(
+            values["/org/gnome/desktop/interface/color-scheme"],
+            "'prefer-dark'"
+        );
+        assert_eq!(values["/org/gnome/mutter/dynamic-workspaces"], "false");
+    }
+
+    #[test]
+    fn watch_event_path_skips_values_and_dirs() {
+        let output = "/org/gnome/desktop/interface/color-scheme\n  'prefer-dark'\n\n\
+                      /org/gnome/mutter/\n\n";
+        let paths: Vec<&str> = output.lines().filter_map(watch_event_path).collect();
+        assert_eq!(paths, ["/org/gnome/desktop/interface/color-scheme"]);
+    }
+
+    #[test]
+    fn settle_changes_drops_keys_changed_back() {
+        let paths = schema_paths();
+        let changed: Vec<(String, WatchedKey)> = [
+            "/org/gnome/desktop/interface/color-scheme",
+            "/org/gnome/desktop/interface/clock-format",
+            "/org/gnome/mutter/dynamic-workspaces",
+        ]
+        .into_iter()
+        .map(|p| (p.to_string(), map_dconf_path(p, &paths).unwrap()))
+        .collect();
+        // color-scheme was set before watching; the others were at default
+        let original =
+            parse_dconf_dump("[org/gnome/desktop/interface]\ncolor-scheme='prefer-dark'\n");
+        let current = |w: &WatchedKey| {
+            let value = match w.key.as_str() {
+                // Toggled and toggled back
+                "color-scheme" => "'prefer-dark'",
+                // Set back to its default explicitly
+                "clock-format" => "'24h'",
+                _ => "false",
+            };
+            Some(value.to_string())
+        };
+        let default = |w: &WatchedKey| {
+            let value = match w.key.as_str() {
+                "clock-format" => "'24h'",
+                _ => "true",
+            };
+            Some(value.to_string())
+        };
+
+        let settings = settle_changes(&changed, &original, current, default);
+        let kept: Vec<(String, &str)> = settings
+            .iter()
+            .map(|s| (s.unique_key(), s.value.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            [("org.gnome.mutter.dynamic-workspaces".to_string(), "false")]
+        );
+    }
+
+    #[test]
+    fn resolve_schema_path_accepts_either_form() {
+        let (schema, path) = resolve_schema_path("a.b:/x/y/", None).unwrap();
+        assert_eq!(schema, "a.b");
+        assert_eq!(path.as_deref(), Some("/x/y/"));
+
+        let (schema, path) = resolve_schema_path("a.b", Some("/x/y/".to_string())).unwrap();
+        assert_eq!(schema, "a.b");
+        assert_eq!(path.as_deref(), Some("/x/y/"));
+
+        assert!(resolve_schema_path("a.b:/x/", Some("/y/".to_string())).is_err());
+    }
+}
@@ -3,12 +3,14 @@
 //! Manages Linuxbrew/Homebrew packages on the host system.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::capture::CapturePlan;
 use crate::context::CommandDomain;
-use crate::manifest::homebrew::HomebrewManifest;
-use crate::output::Output;
+use crate::manifest::homebrew::{BrewFormula, HomebrewManifest};
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
-    ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, PlanWarning,
+    Plannable, Verb, print_report, print_summary,
 };
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
@@ -35,12 +37,12 @@ pub enum HomebrewAction {
     /// List formulae in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
-    /// Sync: install formulae from manifest
+    /// Sync: add taps, then install formulae and casks from manifest
     Sync,
-    /// Capture installed formulae to manifest
+    /// Capture installed formulae, taps and casks to manifest
     Capture,
 }
 
@@ -53,7 +55,7 @@ pub fn run(args: HomebrewArgs, plan: &ExecutionPlan) -> Result<()> {
     match args.action {
         HomebrewAction::Add { formula } => handle_add(&formula, &plan_ctx),
         HomebrewAction::Remove { formula } => handle_remove(&formula, &plan_ctx),
-        HomebrewAction::List { format } => handle_list(&format),
+        HomebrewAction::List { format } => handle_list(format),
         HomebrewAction::Sync => handle_sync(&plan_ctx),
         HomebrewAction::Capture => handle_capture(&plan_ctx),
     }
@@ -111,15 +113,15 @@ fn handle_remove(formula: &str, ctx: &PlanContext) -> Result<()> {
 // List Command
 // =============================================================================
 
-fn handle_list(format: &str) -> Result<()> {
+fn handle_list(format: OutputFormat) -> Result<()> {
     let manifest = HomebrewManifest::load_repo()?;
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&manifest)?);
         return Ok(());
     }
 
-    if manifest.formulae.is_empty() {
+    if manifest.formulae.is_empty() && manifest.casks.is_empty() {
         Output::info("No formulae in manifest");
         return Ok(());
     }
@@ -141,6 +143,17 @@ fn handle_list(format: &str) -> Result<()> {
         }
     }
 
+    if !manifest.casks.is_empty() {
+        Output::blank();
+        Output::header("Casks");
+        for cask in &manifest.casks {
+            println!("  {}", cask);
+        }
+        if !casks_supported() {
+            Output::hint("Casks are macOS-only and are skipped on this system");
+        }
+    }
+
     Ok(())
 }
 
@@ -152,16 +165,19 @@ fn handle_sync(ctx: &PlanContext) -> Result<()> {
     let cmd = HomebrewSyncCommand;
     let plan = cmd.plan(ctx)?;
 
-    if plan.is_empty() {
+    let exec_plan = ctx.execution_plan();
+    if plan.is_empty() && !exec_plan.json_output() {
         Output::success("All formulae from manifest are installed.");
         return Ok(());
     }
 
-    let summary = plan.describe();
-    print!("{}", summary);
+    let summary = plan.describe().with_subsystem("homebrew");
+    print_summary(&summary, exec_plan, ctx.is_dry_run())?;
 
     if ctx.is_dry_run() {
-        Output::info("Run without --dry-run to apply these changes.");
+        if !exec_plan.json_output() {
+            Output::info("Run without --dry-run to apply these changes.");
+        }
         return Ok(());
     }
 
@@ -170,8 +186,10 @@ fn handle_sync(ctx: &PlanContext) -> Result<()> {
     exec_ctx.set_total_ops(total_ops);
 
     let report = plan.execute(&mut exec_ctx)?;
-    println!();
-    print!("{}", report);
+    if !exec_plan.json_output() {
+        println!();
+    }
+    print_report(&report.with_subsystem("homebrew"), exec_plan)?;
 
     Ok(())
 }
@@ -184,22 +202,27 @@ fn handle_capture(ctx: &PlanContext) -> Result<()> {
     let cmd = HomebrewCaptureCommand;
     let plan = cmd.plan(ctx)?;
 
-    if plan.is_empty() {
-        Output::success("Nothing to capture. All installed formulae are in manifest.");
+    let exec_plan = ctx.execution_plan();
+    if plan.is_empty() && !exec_plan.json_output() {
+        Output::success(
+            "Nothing to capture. All installed formulae, taps and casks are in manifest.",
+        );
         return Ok(());
     }
 
-    let summary = plan.describe();
-    print!("{}", summary);
+    let summary = plan.describe().with_subsystem("homebrew");
+    print_summary(&summary, exec_plan, ctx.is_dry_run())?;
 
     if ctx.is_dry_run() {
-        Output::info("Run without --dry-run to capture these formulae.");
+        if !exec_plan.json_output() {
+            Output::info("Run without --dry-run to capture these formulae.");
+        }
         return Ok(());
     }
 
-    let mut exec_ctx = ExecuteContext::new(ctx.execution_plan().clone());
+    let mut exec_ctx = ExecuteContext::new(exec_plan.clone());
     let report = plan.execute(&mut exec_ctx)?;
-    print!("{}", report);
+    print_report(&report.with_subsystem("homebrew"), exec_plan)?;
 
     Ok(())
 }
@@ -213,8 +236,12 @@ pub struct HomebrewSyncCommand;
 pub struct HomebrewSyncPlan {
     /// Formulae to install.
     pub to_install: Vec<String>,
-    /// Taps to add.
+    /// Taps to add, including those named by `user/repo/formula` entries.
     pub taps_to_add: Vec<String>,
+    /// Casks to install.
+    pub casks_to_install: Vec<String>,
+    /// Casks left alone because this system can't install casks.
+    pub casks_skipped: Vec<String>,
     /// Already installed count.
     pub already_installed: usize,
 }
@@ -243,15 +270,31 @@ impl Plannable for HomebrewSyncCommand {
         }
 
         let taps_to_add: Vec<String> = manifest
-            .taps
-            .iter()
+            .required_taps()
+            .into_iter()
             .filter(|t| !installed_taps.contains(t.as_str()))
-            .cloned()
             .collect();
 
+        let (casks_to_install, casks_skipped) = if casks_supported() {
+            let installed_casks = get_installed_casks(runner);
+            let mut to_install = Vec::new();
+            for cask in &manifest.casks {
+                if installed_casks.contains(cask) {
+                    already_installed += 1;
+                } else {
+                    to_install.push(cask.clone());
+                }
+            }
+            (to_install, Vec::new())
+        } else {
+            (Vec::new(), manifest.casks.clone())
+        };
+
         Ok(HomebrewSyncPlan {
             to_install,
             taps_to_add,
+            casks_to_install,
+            casks_skipped,
             already_installed,
         })
     }
@@ -259,13 +302,15 @@ impl Plannable for HomebrewSyncCommand {
 
 impl Plan for HomebrewSyncPlan {
     fn is_empty(&self) -> bool {
-        self.to_install.is_empty() && self.taps_to_add.is_empty()
+        self.to_install.is_empty()
+            && self.taps_to_add.is_empty()
+            && self.casks_to_install.is_empty()
     }
 
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!(
             "Homebrew Sync: {} to install, {} already installed",
-            self.to_install.len(),
+            self.to_install.len() + self.casks_to_install.len(),
             self.already_installed
         ));
 
@@ -280,6 +325,17 @@ impl Plan for HomebrewSyncPlan {
             ));
         }
 
+        for cask in &self.casks_to_install {
+            summary.add_operation(Operation::new(Verb::Install, format!("cask:{}", cask)));
+        }
+
+        for cask in &self.casks_skipped {
+            summary.add_warning(PlanWarning::new(
+                format!("cask:{}", cask),
+                "casks are macOS-only; skipped on this system",
+            ));
+        }
+
         summary
     }
 
@@ -309,6 +365,14 @@ impl Plan for HomebrewSyncPlan {
             }
         }
 
+        for cask in self.casks_to_install {
+            if install_cask(&cask, runner)? {
+                report.record_success(Verb::Install, format!("cask:{}", cask));
+            } else {
+                report.record_failure(Verb::Install, format!("cask:{}", cask), "failed to install");
+            }
+        }
+
         Ok(report)
     }
 }
@@ -322,6 +386,10 @@ pub struct HomebrewCaptureCommand;
 pub struct HomebrewCapturePlan {
     /// Formulae to add to manifest.
     pub to_capture: Vec<String>,
+    /// Taps to add to manifest.
+    pub taps_to_capture: Vec<String>,
+    /// Casks to add to manifest.
+    pub casks_to_capture: Vec<String>,
     /// Already in manifest count.
     pub already_in_manifest: usize,
 }
@@ -350,8 +418,39 @@ impl Plannable for HomebrewCaptureCommand {
 
         to_capture.sort();
 
+        // Taps that formulae already name don't need their own entry
+        let required_taps = manifest.required_taps();
+        let mut taps_to_capture: Vec<String> = get_installed_taps(runner)
+            .into_iter()
+            .filter(|tap| !is_builtin_tap(tap))
+            .filter(|tap| {
+                let known = required_taps.contains(tap)
+                    || to_capture
+                        .iter()
+                        .any(|f| BrewFormula::from(f.clone()).tap() == Some(tap.as_str()));
+                if known {
+                    already_in_manifest += usize::from(manifest.taps.contains(tap));
+                }
+                !known
+            })
+            .collect();
+        taps_to_capture.sort();
+
+        // `brew list --cask` only works where casks do (macOS)
+        let mut casks_to_capture = Vec::new();
+        for cask in get_installed_casks(runner) {
+            if manifest.casks.contains(&cask) {
+                already_in_manifest += 1;
+            } else {
+                casks_to_capture.push(cask);
+            }
+        }
+        casks_to_capture.sort();
+
         Ok(HomebrewCapturePlan {
             to_capture,
+            taps_to_capture,
+            casks_to_capture,
             already_in_manifest,
         })
     }
@@ -360,15 +459,21 @@ impl Plannable for HomebrewCaptureCommand {
 impl Plan for HomebrewCapturePlan {
     fn is_empty(&self) -> bool {
         self.to_capture.is_empty()
+            && self.taps_to_capture.is_empty()
+            && self.casks_to_capture.is_empty()
     }
 
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!(
             "Homebrew Capture: {} to add, {} already in manifest",
-            self.to_capture.len(),
+            self.to_capture.len() + self.taps_to_capture.len() + self.casks_to_capture.len(),
             self.already_in_manifest
         ));
 
+        for tap in &self.taps_to_capture {
+            summary.add_operation(Operation::new(Verb::Capture, format!("tap:{}", tap)));
+        }
+
         for formula in &self.to_capture {
             summary.add_operation(Operation::new(
                 Verb::Capture,
@@ -376,6 +481,10 @@ impl Plan for HomebrewCapturePlan {
             ));
         }
 
+        for cask in &self.casks_to_capture {
+            summary.add_operation(Operation::new(Verb::Capture, format!("cask:{}", cask)));
+        }
+
         summary
     }
 
@@ -383,22 +492,121 @@ impl Plan for HomebrewCapturePlan {
         let mut report = ExecutionReport::new();
         let mut manifest = HomebrewManifest::load_repo()?;
 
+        for tap in self.taps_to_capture {
+            if manifest.add_tap(tap.clone()) {
+                report.record_success(Verb::Capture, format!("tap:{}", tap));
+            }
+        }
+
         for formula in self.to_capture {
             if manifest.add(formula.clone()) {
                 report.record_success(Verb::Capture, format!("formula:{}", formula));
             }
         }
 
+        for cask in self.casks_to_capture {
+            if manifest.add_cask(cask.clone()) {
+                report.record_success(Verb::Capture, format!("cask:{}", cask));
+            }
+        }
+
         manifest.save_repo()?;
 
         Ok(report)
     }
 }
 
+impl CapturePlan for HomebrewCapturePlan {
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.taps_to_capture
+            .retain(|tap| keep(&format!("tap:{}", tap)));
+        self.to_capture
+            .retain(|formula| keep(&format!("formula:{}", formula)));
+        self.casks_to_capture
+            .retain(|cask| keep(&format!("cask:{}", cask)));
+    }
+}
+
+// =============================================================================
+// Drift
+// =============================================================================
+
+/// What's installed through Homebrew, for drift detection.
+#[derive(Debug, Default)]
+pub struct InstalledHomebrew {
+    /// Every installed formula, dependencies included (short names).
+    pub formulae: HashSet<String>,
+    /// Formulae installed on request and not needed by anything else.
+    pub leaves: Vec<String>,
+    /// Tapped repositories.
+    pub taps: HashSet<String>,
+}
+
+impl InstalledHomebrew {
+    pub fn scan(runner: &dyn CommandRunner) -> Self {
+        Self {
+            formulae: get_installed_formulae(runner),
+            leaves: get_explicitly_installed_formulae(runner),
+            taps: get_installed_taps(runner),
+        }
+    }
+}
+
+/// Expected and actual drift keys: `formula:<name>` and `tap:<user/repo>`.
+///
+/// A declared formula counts as present whenever it's installed, even as a
+/// dependency; undeclared ones only count when they're leaves, so
+/// dependencies never show up as extra. Homebrew's own taps are ignored.
+pub fn homebrew_drift_keys(
+    manifest: &HomebrewManifest,
+    installed: &InstalledHomebrew,
+) -> (Vec<String>, Vec<String>) {
+    let formula_key = |name: &str| format!("formula:{}", name.rsplit('/').next().unwrap_or(name));
+    let tap_key = |tap: &str| format!("tap:{}", tap);
+
+    let mut expected: Vec<String> = manifest
+        .formulae
+        .iter()
+        .map(|f| formula_key(f.formula_name()))
+        .collect();
+    expected.extend(manifest.required_taps().iter().map(|t| tap_key(t)));
+
+    let mut actual: Vec<String> = manifest
+        .formulae
+        .iter()
+        .filter(|f| installed.formulae.contains(f.formula_name()))
+        .map(|f| formula_key(f.formula_name()))
+        .collect();
+    actual.extend(installed.leaves.iter().map(|name| formula_key(name)));
+    actual.extend(
+        installed
+            .taps
+            .iter()
+            .filter(|tap| !is_builtin_tap(tap))
+            .map(|t| tap_key(t)),
+    );
+    actual.sort();
+    actual.dedup();
+
+    (expected, actual)
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
 
+/// Taps Homebrew manages itself.
+const BUILTIN_TAPS: &[&str] = &["homebrew/core", "homebrew/cask"];
+
+fn is_builtin_tap(tap: &str) -> bool {
+    BUILTIN_TAPS.contains(&tap)
+}
+
+/// Whether this system can install casks (Homebrew on Linux can't).
+fn casks_supported() -> bool {
+    cfg!(target_os = "macos")
+}
+
 /// Get set of installed formula names.
 fn get_installed_formulae(runner: &dyn CommandRunner) -> HashSet<String> {
     let output = runner.run_output(
@@ -454,6 +662,37 @@ fn install_formula(formula: &str, runner: &dyn CommandRunner) -> Result<bool> {
     Ok(status.success())
 }
 
+/// Get installed casks.
+fn get_installed_casks(runner: &dyn CommandRunner) -> HashSet<String> {
+    let output = runner.run_output(
+        "brew",
+        &["list", "--cask", "-1"],
+        &CommandOptions::default(),
+    );
+
+    match output {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Install a cask.
+fn install_cask(cask: &str, runner: &dyn CommandRunner) -> Result<bool> {
+    let status = runner
+        .run_status(
+            "brew",
+            &["install", "--cask", cask],
+            &CommandOptions::default(),
+        )
+        .context("Failed to run brew install --cask")?;
+
+    Ok(status.success())
+}
+
 /// Add a tap.
 fn install_tap(tap: &str, runner: &dyn CommandRunner) -> Result<bool> {
     let status = runner
@@ -462,3 +701,46 @@ fn install_tap(tap: &str, runner: &dyn CommandRunner) -> Result<bool> {
 
     Ok(status.success())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(formulae: &[&str], leaves: &[&str], taps: &[&str]) -> InstalledHomebrew {
+        InstalledHomebrew {
+            formulae: formulae.iter().map(|s| s.to_string()).collect(),
+            leaves: leaves.iter().map(|s| s.to_string()).collect(),
+            taps: taps.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn drift_keys_compare_formulae_and_taps() {
+        let mut manifest = HomebrewManifest::default();
+        manifest.add("lefthook");
+        manifest.add("gh");
+        manifest.add("valkyrie00/bbrew/bbrew");
+        manifest.add("missing");
+        manifest.add_tap("acme/tools");
+
+        // lefthook is only a dependency here, jq is an undeclared leaf,
+        // openssl is a dependency nobody asked for
+        let live = installed(
+            &["lefthook", "bbrew", "jq", "openssl", "gh"],
+            &["valkyrie00/bbrew/bbrew", "jq", "gh"],
+            &["homebrew/core", "valkyrie00/bbrew", "other/tap"],
+        );
+
+        let (expected, actual) = homebrew_drift_keys(&manifest, &live);
+        let missing: Vec<&String> = expected.iter().filter(|k| !actual.contains(k)).collect();
+        let extra: Vec<&String> = actual.iter().filter(|k| !expected.contains(k)).collect();
+        assert_eq!(missing, ["formula:missing", "tap:acme/tools"]);
+        assert_eq!(extra, ["formula:jq", "tap:other/tap"]);
+    }
+
+    #[test]
+    fn builtin_taps_are_ignored() {
+        assert!(is_builtin_tap("homebrew/core"));
+        assert!(!is_builtin_tap("valkyrie00/bbrew"));
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 use std::path::{Path, PathBuf};
 
+use crate::manifest::SkelVars;
 use crate::output::Output;
 use crate::repo::find_repo_path;
 
@@ -145,6 +146,10 @@ fn list_user_manifests(dir: &Path) -> Result<Vec<PathBuf>> {
         if path.extension().and_then(|s| s.to_str()) != Some("json") {
             continue;
         }
+        // Per-machine values; they don't belong in the shared repo
+        if path.file_name().and_then(|s| s.to_str()) == Some(SkelVars::FILE_NAME) {
+            continue;
+        }
         files.push(path);
     }
 
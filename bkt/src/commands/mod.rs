@@ -11,15 +11,18 @@ pub mod changelog;
 pub mod completions;
 pub mod containerfile;
 pub mod dev;
+pub mod diff;
 pub mod distrobox;
 pub mod doctor;
 pub mod drift;
 pub mod extension;
+pub mod external_repo;
 pub mod fetchbin;
 pub mod flatpak;
 pub mod gsetting;
 pub mod homebrew;
 pub mod migrate;
+pub mod pr;
 pub mod profile;
 pub mod repo;
 pub mod schema;
@@ -29,6 +32,7 @@ pub mod status;
 pub mod system;
 pub mod try_cmd;
 pub mod tune;
+pub mod undo;
 pub mod upgrade;
 pub mod upstream;
 pub mod wrap;
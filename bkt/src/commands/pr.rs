@@ -0,0 +1,140 @@
+//! Staged PR command implementation.
+//!
+//! Mutating commands run with `--stage` record their manifest changes in a
+//! [`PrStage`] instead of opening a PR each; `bkt pr submit` then opens one
+//! PR covering all of them. PRs that couldn't be opened offline wait in a
+//! separate store until `bkt pr flush`.
+
+use crate::output::Output;
+use crate::pipeline::ExecutionPlan;
+use crate::pr::PrStage;
+use anyhow::{Result, bail};
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Args)]
+pub struct PrArgs {
+    #[command(subcommand)]
+    pub action: PrAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PrAction {
+    /// List staged manifest changes
+    Staged,
+    /// Open one PR with every staged change, then clear them
+    Submit,
+    /// Drop every staged change without opening a PR
+    Discard,
+    /// Open the PRs deferred while offline
+    Flush,
+}
+
+pub fn run(args: PrArgs, plan: &ExecutionPlan) -> Result<()> {
+    let stage = plan.stage.clone().unwrap_or_else(PrStage::open);
+
+    match args.action {
+        PrAction::Staged => {
+            let changes = stage.changes()?;
+            if plan.json_output() {
+                println!("{}", serde_json::to_string_pretty(&changes)?);
+                return Ok(());
+            }
+            if changes.is_empty() {
+                Output::info("No staged PR changes");
+                return Ok(());
+            }
+
+            println!("{:<12} {:<8} {:<40} MANIFEST", "DOMAIN", "ACTION", "TARGET");
+            for change in &changes {
+                println!(
+                    "{:<12} {:<8} {:<40} {}",
+                    change.manifest_type, change.action, change.name, change.manifest_file
+                );
+            }
+            Output::blank();
+            Output::hint("Open one PR for these with: bkt pr submit");
+        }
+        PrAction::Submit => {
+            let batch = stage.batch()?;
+            if batch.is_empty() {
+                Output::info("No staged PR changes to submit");
+                return Ok(());
+            }
+
+            if plan.dry_run {
+                Output::dry_run(format!("Would create PR: {}", batch.pr_title()));
+                for change in &batch.changes {
+                    Output::list_item(format!(
+                        "{} {} {}",
+                        change.action, change.manifest_type, change.name
+                    ));
+                }
+                return Ok(());
+            }
+
+            plan.submit_batch(&batch)?;
+            stage.discard()?;
+        }
+        PrAction::Discard => {
+            if plan.dry_run {
+                let count = stage.changes()?.len();
+                Output::dry_run(format!("Would discard {} staged PR change(s)", count));
+                return Ok(());
+            }
+            let count = stage.discard()?;
+            Output::success(format!("Discarded {} staged PR change(s)", count));
+        }
+        PrAction::Flush => flush(plan)?,
+    }
+
+    Ok(())
+}
+
+/// Open each deferred PR, keeping the ones that fail for the next flush.
+fn flush(plan: &ExecutionPlan) -> Result<()> {
+    let deferred = plan.deferred_prs();
+    let batches = deferred.batches_by_file()?;
+    if batches.is_empty() {
+        Output::info("No deferred PRs");
+        return Ok(());
+    }
+
+    if plan.dry_run {
+        for batch in &batches {
+            Output::dry_run(format!("Would create PR: {}", batch.pr_title()));
+        }
+        return Ok(());
+    }
+
+    if plan.is_offline() {
+        bail!(
+            "Still offline; {} deferred PR(s) kept for the next `bkt pr flush`",
+            batches.len()
+        );
+    }
+
+    let mut failed = 0;
+    for batch in &batches {
+        match plan.submit_batch(batch) {
+            Ok(()) => {
+                for file in batch.files.keys() {
+                    deferred.remove_file(file)?;
+                }
+            }
+            Err(e) => {
+                Output::error(format!("{}: {:#}", batch.pr_title(), e));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "{} of {} deferred PR(s) failed; they stay deferred",
+            failed,
+            batches.len()
+        );
+    }
+    Output::success(format!("Opened {} deferred PR(s)", batches.len()));
+    Ok(())
+}
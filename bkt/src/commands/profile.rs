@@ -1,11 +1,17 @@
 //! System profile command implementation.
 //!
-//! Captures current system state and compares against manifests.
+//! Captures current system state and compares against manifests, and
+//! manages named per-machine profiles that overlay the repo manifests.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
-use crate::manifest::{FlatpakAppsManifest, GSettingsManifest, GnomeExtensionsManifest};
+use crate::commands::apply::{self, ApplyArgs, Subsystem};
+use crate::manifest::{
+    FlatpakAppsManifest, GSettingsManifest, GnomeExtensionsManifest, Profile, ProfileState,
+};
 use crate::output::Output;
-use anyhow::{Context, Result};
+use crate::pipeline::ExecutionPlan;
+use crate::subsystem::{SubsystemContext, SubsystemRegistry};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
@@ -38,8 +44,34 @@ pub enum ProfileAction {
         #[arg(short, long, default_value = "/usr/local/bin")]
         dir: PathBuf,
     },
+    /// Create a named profile (~/.config/bootc/profiles/<name>/)
+    Create {
+        /// Profile name (e.g. "laptop")
+        name: String,
+    },
+    /// Make a profile the active overlay on the repo manifests
+    Switch {
+        /// Profile to activate
+        #[arg(required_unless_present = "none")]
+        name: Option<String>,
+        /// Deactivate profiles and use the repo manifests alone
+        #[arg(long, conflicts_with = "name")]
+        none: bool,
+        /// Apply the affected subsystems after switching
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Show the active profile and the available profiles
+    Show,
 }
 
+/// Subsystems whose manifests a profile can overlay.
+const PROFILE_SUBSYSTEMS: &[Subsystem] = &[
+    Subsystem::Flatpak,
+    Subsystem::Extension,
+    Subsystem::Gsetting,
+];
+
 /// Profile of installed flatpaks.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FlatpakProfile {
@@ -196,7 +228,7 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
     if show_all || section == "flatpak" || section == "fp" {
         Output::subheader("=== FLATPAK DIFF ===");
 
-        let manifest = FlatpakAppsManifest::load_repo().unwrap_or_default();
+        let manifest = FlatpakAppsManifest::load_effective().unwrap_or_default();
         let installed = get_installed_flatpaks(runner)?;
 
         let manifest_ids: HashSet<String> = manifest.apps.iter().map(|a| a.id.clone()).collect();
@@ -230,7 +262,7 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
     if show_all || section == "extension" || section == "ext" {
         Output::subheader("=== EXTENSION DIFF ===");
 
-        let manifest = GnomeExtensionsManifest::load_repo().unwrap_or_default();
+        let manifest = GnomeExtensionsManifest::load_effective().unwrap_or_default();
         let installed = get_installed_extensions(runner)?;
 
         let manifest_set: HashSet<String> = manifest
@@ -268,7 +300,7 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
     if show_all || section == "gsetting" || section == "gs" {
         Output::subheader("=== GSETTINGS DIFF ===");
 
-        let manifest = GSettingsManifest::load_repo().unwrap_or_default();
+        let manifest = GSettingsManifest::load_effective().unwrap_or_default();
 
         if manifest.settings.is_empty() {
             Output::info("(no gsettings in manifest)");
@@ -279,7 +311,7 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
                 let current = runner
                     .run_output(
                         "gsettings",
-                        &["get", &setting.schema, &setting.key],
+                        &["get", &setting.schema_spec(), &setting.key],
                         &CommandOptions::default(),
                     )
                     .ok()
@@ -294,10 +326,9 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
                         .unwrap_or(&current_val);
                     if normalized_current != setting.value {
                         println!(
-                            "{} {}.{}\n  manifest: {}\n  current:  {}",
+                            "{} {}\n  manifest: {}\n  current:  {}",
                             "≠".yellow(),
-                            setting.schema,
-                            setting.key,
+                            setting.unique_key(),
                             setting.value.green(),
                             current_val.red()
                         );
@@ -306,10 +337,9 @@ fn show_diff(section: Option<&str>, runner: &dyn CommandRunner) -> Result<()> {
                     }
                 } else {
                     println!(
-                        "{} {}.{} (could not read current value)",
+                        "{} {} (could not read current value)",
                         "?".yellow(),
-                        setting.schema,
-                        setting.key
+                        setting.unique_key()
                     );
                     Output::blank();
                     drifted += 1;
@@ -380,7 +410,125 @@ fn show_unowned(dir: &PathBuf, runner: &dyn CommandRunner) -> Result<()> {
     Ok(())
 }
 
-pub fn run(args: ProfileArgs, runner: &dyn CommandRunner) -> Result<()> {
+/// Make `name` the active profile (or none), then report or apply drift.
+fn switch_profile(name: Option<String>, apply_now: bool, plan: &ExecutionPlan) -> Result<()> {
+    let profile = name.as_deref().map(Profile::named).transpose()?;
+    if let Some(profile) = &profile
+        && !profile.exists()
+    {
+        bail!(
+            "Profile '{}' does not exist; create it with `bkt profile create {}`",
+            profile.name,
+            profile.name
+        );
+    }
+
+    let label = profile
+        .as_ref()
+        .map_or_else(|| "none".to_string(), |p| p.name.clone());
+    if plan.dry_run {
+        Output::dry_run(format!("Would switch to profile {}", label));
+        return Ok(());
+    }
+
+    let state = ProfileState {
+        active: profile.as_ref().map(|p| p.name.clone()),
+    };
+    state.save()?;
+    Output::success(format!("Switched to profile {}", label));
+
+    if apply_now {
+        return apply::run(
+            ApplyArgs {
+                only: Some(PROFILE_SUBSYSTEMS.to_vec()),
+                exclude: None,
+                confirm: false,
+                prune_appimages: false,
+                continue_on_error: false,
+            },
+            plan,
+        );
+    }
+
+    show_profile_drift()?;
+    let ids: Vec<String> = PROFILE_SUBSYSTEMS.iter().map(|s| s.to_string()).collect();
+    Output::hint(format!(
+        "Run `bkt apply --only {}` to converge",
+        ids.join(",")
+    ));
+    Ok(())
+}
+
+/// Summarize drift for the subsystems a profile affects.
+fn show_profile_drift() -> Result<()> {
+    let ctx = crate::repo::find_repo_path()
+        .map(SubsystemContext::with_repo_root)
+        .unwrap_or_else(|_| SubsystemContext::new());
+    let ids: Vec<String> = PROFILE_SUBSYSTEMS.iter().map(|s| s.to_string()).collect();
+    let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+    Output::blank();
+    let registry = SubsystemRegistry::builtin();
+    for subsystem in registry.filtered(Some(&ids), &[]) {
+        match subsystem.drift(&ctx)? {
+            Some(report) if report.has_drift() => Output::list_item(format!(
+                "{}: {} missing, {} extra",
+                subsystem.name(),
+                report.missing.len(),
+                report.extra.len()
+            )),
+            Some(_) => Output::list_item(format!("{}: in sync", subsystem.name())),
+            None => {}
+        }
+    }
+    Output::blank();
+    Ok(())
+}
+
+/// Print the active profile, its overlays, and all known profiles.
+fn show_profiles() -> Result<()> {
+    let active = ProfileState::load()?.active_profile()?;
+
+    match &active {
+        Some(profile) => {
+            Output::info(format!(
+                "Active profile: {} ({})",
+                profile.name,
+                profile.dir.display()
+            ));
+            for project_path in Profile::OVERLAY_MANIFESTS {
+                let overlay = profile.overlay_path(project_path);
+                let marker = if overlay.exists() {
+                    "overlays"
+                } else {
+                    "absent"
+                };
+                Output::list_item(format!("{} ({})", overlay.display(), marker));
+            }
+        }
+        None => Output::info("Active profile: none (repo manifests only)"),
+    }
+
+    let names = Profile::list()?;
+    Output::blank();
+    if names.is_empty() {
+        Output::info("No profiles. Create one with `bkt profile create <name>`.");
+        return Ok(());
+    }
+    Output::subheader("Profiles:");
+    for name in names {
+        let is_active = active.as_ref().is_some_and(|p| p.name == name);
+        if is_active {
+            Output::list_item(format!("{} {}", name, "(active)".green()));
+        } else {
+            Output::list_item(name);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: ProfileArgs, plan: &ExecutionPlan) -> Result<()> {
+    let runner = plan.runner();
     match args.action {
         ProfileAction::Capture { output } => {
             let profile = capture_profile(runner)?;
@@ -400,6 +548,30 @@ pub fn run(args: ProfileArgs, runner: &dyn CommandRunner) -> Result<()> {
         ProfileAction::Unowned { dir } => {
             show_unowned(&dir, runner)?;
         }
+        ProfileAction::Create { name } => {
+            let profile = Profile::named(&name)?;
+            if plan.dry_run {
+                Output::dry_run(format!("Would create {}", profile.dir.display()));
+            } else if profile.create()? {
+                Output::success(format!(
+                    "Created profile {} at {}",
+                    name,
+                    profile.dir.display()
+                ));
+                Output::hint(format!(
+                    "Add overlays there named like the repo manifests, then run `bkt profile switch {}`",
+                    name
+                ));
+            } else {
+                Output::info(format!("Profile {} already exists", name));
+            }
+        }
+        ProfileAction::Switch { name, none, apply } => {
+            switch_profile(if none { None } else { name }, apply, plan)?;
+        }
+        ProfileAction::Show => {
+            show_profiles()?;
+        }
     }
     Ok(())
 }
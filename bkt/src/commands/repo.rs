@@ -1,5 +1,8 @@
 //! Repository info command implementation.
 
+use crate::output::{Output, OutputFormat};
+use crate::pipeline::ExecutionPlan;
+use crate::pr::{REMOTE, sync_repo};
 use crate::repo::{RepoConfig, find_repo_path};
 use anyhow::Result;
 use clap::{Args, Subcommand};
@@ -15,19 +18,24 @@ pub enum RepoAction {
     /// Show repository information
     Info {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Show repository path
     Path,
+    /// Fetch upstream and fast-forward the local checkout's default branch
+    ///
+    /// Refuses to do anything if the working tree is dirty or the default
+    /// branch has local commits.
+    Sync,
 }
 
-pub fn run(args: RepoArgs) -> Result<()> {
+pub fn run(args: RepoArgs, plan: &ExecutionPlan) -> Result<()> {
     match args.action {
         RepoAction::Info { format } => {
             match RepoConfig::load() {
                 Ok(config) => {
-                    if format == "json" {
+                    if format == OutputFormat::Json {
                         println!(
                             "{}",
                             serde_json::to_string_pretty(&serde_json::json!({
@@ -58,6 +66,32 @@ pub fn run(args: RepoArgs) -> Result<()> {
             let path = find_repo_path()?;
             println!("{}", path.display());
         }
+        RepoAction::Sync => {
+            let path = find_repo_path()?;
+            let config = RepoConfig::load()?;
+            if plan.dry_run {
+                Output::dry_run(format!(
+                    "Would fetch {} and fast-forward {} in {}",
+                    REMOTE,
+                    config.default_branch,
+                    path.display()
+                ));
+                return Ok(());
+            }
+
+            let sync = sync_repo(plan.runner(), &path, &config.default_branch)?;
+            if sync.behind == 0 {
+                Output::success(format!(
+                    "{} is up to date with {}/{}",
+                    sync.branch, REMOTE, sync.branch
+                ));
+            } else {
+                Output::success(format!(
+                    "Fast-forwarded {} by {} commit(s) from {}/{}",
+                    sync.branch, sync.behind, REMOTE, sync.branch
+                ));
+            }
+        }
     }
     Ok(())
 }
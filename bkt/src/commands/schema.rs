@@ -1,16 +1,22 @@
-//! Schema generation command implementation.
+//! Schema generation and validation command implementation.
 
+use crate::manifest::image_config::ImageConfigManifest;
+use crate::manifest::json_schema;
+use crate::manifest::system_config::SystemConfigManifest;
 use crate::manifest::{
-    BaseImageAssumptions, ChangelogEntry, DistroboxManifest, ExternalReposManifest, FlatpakApp,
-    FlatpakAppsManifest, FlatpakRemote, FlatpakRemotesManifest, GSetting, GSettingsManifest,
-    GnomeExtensionsManifest, HomebrewManifest, HostBinariesManifest, Shim, ShimsManifest,
-    UpstreamManifest, VendorArtifactsManifest, VersionMetadata,
+    AppImageAppsManifest, BaseImageAssumptions, ChangelogEntry, DaemonAllowlist, DistroboxManifest,
+    ExternalReposManifest, FlatpakApp, FlatpakAppsManifest, FlatpakRemote, FlatpakRemotesManifest,
+    GSetting, GSettingsManifest, GnomeExtensionsManifest, HomebrewManifest, HostBinariesManifest,
+    Shim, ShimsManifest, SystemPackagesManifest, SystemdServicesManifest, ToolboxBinariesManifest,
+    ToolboxPackagesManifest, UpstreamManifest, VendorArtifactsManifest, VersionMetadata,
 };
-use anyhow::{Context, Result};
+use crate::output::Output;
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
-use schemars::schema_for;
+use schemars::{Schema, schema_for};
+use serde_json::Value;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Args)]
 pub struct SchemaArgs {
@@ -28,13 +34,31 @@ pub enum SchemaAction {
     },
     /// List available schema types
     List,
+    /// Validate manifest files against their schema
+    ///
+    /// The schema is taken from the file's `$schema` field, or else from its
+    /// file name. Exits non-zero if any file doesn't validate.
+    Validate {
+        /// Manifest files to validate
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Validate against this schema (name or file name from `bkt schema list`)
+        #[arg(long)]
+        schema: Option<String>,
+    },
 }
 
-/// Schema type information
+/// A generated schema, and the manifest file it describes.
 struct SchemaInfo {
     name: &'static str,
     filename: &'static str,
     description: &'static str,
+    /// Path suffix of the file this schema validates, for detecting the
+    /// schema from a file name. `None` for entry types and files that don't
+    /// have a fixed name.
+    manifest: Option<&'static str>,
+    generate: fn() -> Schema,
 }
 
 const SCHEMAS: &[SchemaInfo] = &[
@@ -42,213 +66,374 @@ const SCHEMAS: &[SchemaInfo] = &[
         name: "FlatpakApp",
         filename: "flatpak-app.schema.json",
         description: "A single Flatpak application entry",
+        manifest: None,
+        generate: || schema_for!(FlatpakApp),
     },
     SchemaInfo {
         name: "FlatpakAppsManifest",
         filename: "flatpak-apps.schema.json",
         description: "The flatpak-apps.json manifest (list of Flatpak apps)",
+        manifest: Some("flatpak-apps.json"),
+        generate: || schema_for!(FlatpakAppsManifest),
     },
     SchemaInfo {
         name: "FlatpakRemote",
         filename: "flatpak-remote.schema.json",
         description: "A single Flatpak remote entry",
+        manifest: None,
+        generate: || schema_for!(FlatpakRemote),
     },
     SchemaInfo {
         name: "FlatpakRemotesManifest",
         filename: "flatpak-remotes.schema.json",
         description: "The flatpak-remotes.json manifest (list of Flatpak remotes)",
+        manifest: Some("flatpak-remotes.json"),
+        generate: || schema_for!(FlatpakRemotesManifest),
     },
     SchemaInfo {
         name: "GnomeExtensionsManifest",
         filename: "gnome-extensions.schema.json",
         description: "The gnome-extensions.json manifest",
+        manifest: Some("gnome-extensions.json"),
+        generate: || schema_for!(GnomeExtensionsManifest),
     },
     SchemaInfo {
         name: "GSetting",
         filename: "gsetting.schema.json",
         description: "A single GSettings entry",
+        manifest: None,
+        generate: || schema_for!(GSetting),
     },
     SchemaInfo {
         name: "GSettingsManifest",
         filename: "gsettings.schema.json",
         description: "The gsettings.json manifest",
+        manifest: Some("gsettings.json"),
+        generate: || schema_for!(GSettingsManifest),
     },
     SchemaInfo {
         name: "Shim",
         filename: "shim.schema.json",
         description: "A single host shim entry",
+        manifest: None,
+        generate: || schema_for!(Shim),
     },
     SchemaInfo {
         name: "ShimsManifest",
         filename: "host-shims.schema.json",
         description: "The host-shims.json manifest",
+        manifest: Some("host-shims.json"),
+        generate: || schema_for!(ShimsManifest),
     },
     SchemaInfo {
         name: "DistroboxManifest",
         filename: "distrobox.schema.json",
         description: "The distrobox.json manifest",
+        manifest: Some("distrobox.json"),
+        generate: || schema_for!(DistroboxManifest),
     },
     SchemaInfo {
         name: "ExternalReposManifest",
         filename: "external-repos.schema.json",
         description: "The external-repos.json manifest (external RPM repos)",
+        manifest: Some("external-repos.json"),
+        generate: || schema_for!(ExternalReposManifest),
     },
     SchemaInfo {
         name: "UpstreamManifest",
         filename: "upstream-manifest.schema.json",
         description: "The upstream/manifest.json manifest for tracking upstream dependencies",
+        manifest: Some("upstream/manifest.json"),
+        generate: || schema_for!(UpstreamManifest),
     },
     SchemaInfo {
         name: "ChangelogEntry",
         filename: "changelog-entry.schema.json",
         description: "A single changelog entry stored in .changelog/pending/",
+        manifest: None,
+        generate: || schema_for!(ChangelogEntry),
     },
     SchemaInfo {
         name: "VersionMetadata",
         filename: "changelog-version.schema.json",
         description: "A released version with its changelog entries stored in .changelog/versions/",
+        manifest: None,
+        generate: || schema_for!(VersionMetadata),
     },
     SchemaInfo {
         name: "BaseImageAssumptions",
         filename: "base-image-assumptions.schema.json",
         description: "Base image assumptions for drift detection",
+        manifest: Some("base-image-assumptions.json"),
+        generate: || schema_for!(BaseImageAssumptions),
     },
     SchemaInfo {
         name: "HomebrewManifest",
         filename: "homebrew.schema.json",
         description: "The homebrew.json manifest (Homebrew/Linuxbrew packages)",
+        manifest: Some("homebrew.json"),
+        generate: || schema_for!(HomebrewManifest),
     },
     SchemaInfo {
         name: "HostBinariesManifest",
         filename: "host-binaries.schema.json",
         description: "The host-binaries.json manifest (binaries installed via fetchbin)",
+        manifest: Some("host-binaries.json"),
+        generate: || schema_for!(HostBinariesManifest),
+    },
+    SchemaInfo {
+        name: "FetchbinExport",
+        filename: "fetchbin-export.schema.json",
+        description: "The file `fetchbin export` writes and `fetchbin import` replays",
+        manifest: None,
+        generate: || schema_for!(fetchbin::Export),
+    },
+    SchemaInfo {
+        name: "DaemonAllowlist",
+        filename: "daemon-allowlist.schema.json",
+        description: "The daemon-allowlist.json manifest (programs the host daemon may run)",
+        manifest: Some("daemon-allowlist.json"),
+        generate: || schema_for!(DaemonAllowlist),
+    },
+    SchemaInfo {
+        name: "ToolboxBinariesManifest",
+        filename: "toolbox-binaries.schema.json",
+        description: "The toolbox-binaries.json manifest (dev binaries installed in the toolbox)",
+        manifest: Some("toolbox-binaries.json"),
+        generate: || schema_for!(ToolboxBinariesManifest),
     },
     SchemaInfo {
         name: "VendorArtifactsManifest",
         filename: "vendor-artifacts.schema.json",
         description: "The vendor-artifacts.json manifest (vendor-sourced packages like VS Code)",
+        manifest: Some("vendor-artifacts.json"),
+        generate: || schema_for!(VendorArtifactsManifest),
+    },
+    SchemaInfo {
+        name: "AppImageAppsManifest",
+        filename: "appimage-apps.schema.json",
+        description: "The appimage-apps.json manifest (AppImages managed by GearLever)",
+        manifest: Some("appimage-apps.json"),
+        generate: || schema_for!(AppImageAppsManifest),
+    },
+    SchemaInfo {
+        name: "SystemPackagesManifest",
+        filename: "system-packages.schema.json",
+        description: "The system-packages.json manifest (RPM packages layered into the image)",
+        manifest: Some("system-packages.json"),
+        generate: || schema_for!(SystemPackagesManifest),
+    },
+    SchemaInfo {
+        name: "SystemdServicesManifest",
+        filename: "systemd-services.schema.json",
+        description: "The systemd-services.json manifest (units enabled or disabled in the image)",
+        manifest: Some("systemd-services.json"),
+        generate: || schema_for!(SystemdServicesManifest),
+    },
+    SchemaInfo {
+        name: "ToolboxPackagesManifest",
+        filename: "toolbox-packages.schema.json",
+        description: "The toolbox-packages.json manifest (packages installed in the toolbox)",
+        manifest: Some("toolbox-packages.json"),
+        generate: || schema_for!(ToolboxPackagesManifest),
+    },
+    SchemaInfo {
+        name: "SystemConfigManifest",
+        filename: "system-config.schema.json",
+        description: "The system-config.json manifest (kargs, systemd, udev and SELinux settings)",
+        manifest: Some("system-config.json"),
+        generate: || schema_for!(SystemConfigManifest),
+    },
+    SchemaInfo {
+        name: "ImageConfigManifest",
+        filename: "image-config.schema.json",
+        description: "The image-config.json manifest (modules applied during image assembly)",
+        manifest: Some("image-config.json"),
+        generate: || schema_for!(ImageConfigManifest),
     },
 ];
 
-/// Generate all schemas and return them as (filename, json) pairs.
-fn generate_all_schemas() -> Vec<(&'static str, String)> {
-    vec![
-        (
-            "flatpak-app.schema.json",
-            serde_json::to_string_pretty(&schema_for!(FlatpakApp)).unwrap(),
-        ),
-        (
-            "flatpak-apps.schema.json",
-            serde_json::to_string_pretty(&schema_for!(FlatpakAppsManifest)).unwrap(),
-        ),
-        (
-            "flatpak-remote.schema.json",
-            serde_json::to_string_pretty(&schema_for!(FlatpakRemote)).unwrap(),
-        ),
-        (
-            "flatpak-remotes.schema.json",
-            serde_json::to_string_pretty(&schema_for!(FlatpakRemotesManifest)).unwrap(),
-        ),
-        (
-            "gnome-extensions.schema.json",
-            serde_json::to_string_pretty(&schema_for!(GnomeExtensionsManifest)).unwrap(),
-        ),
-        (
-            "gsetting.schema.json",
-            serde_json::to_string_pretty(&schema_for!(GSetting)).unwrap(),
-        ),
-        (
-            "gsettings.schema.json",
-            serde_json::to_string_pretty(&schema_for!(GSettingsManifest)).unwrap(),
-        ),
-        (
-            "shim.schema.json",
-            serde_json::to_string_pretty(&schema_for!(Shim)).unwrap(),
-        ),
-        (
-            "host-shims.schema.json",
-            serde_json::to_string_pretty(&schema_for!(ShimsManifest)).unwrap(),
-        ),
-        (
-            "distrobox.schema.json",
-            serde_json::to_string_pretty(&schema_for!(DistroboxManifest)).unwrap(),
-        ),
-        (
-            "external-repos.schema.json",
-            serde_json::to_string_pretty(&schema_for!(ExternalReposManifest)).unwrap(),
-        ),
-        (
-            "upstream-manifest.schema.json",
-            serde_json::to_string_pretty(&schema_for!(UpstreamManifest)).unwrap(),
-        ),
-        (
-            "changelog-entry.schema.json",
-            serde_json::to_string_pretty(&schema_for!(ChangelogEntry)).unwrap(),
-        ),
-        (
-            "changelog-version.schema.json",
-            serde_json::to_string_pretty(&schema_for!(VersionMetadata)).unwrap(),
-        ),
-        (
-            "base-image-assumptions.schema.json",
-            serde_json::to_string_pretty(&schema_for!(BaseImageAssumptions)).unwrap(),
-        ),
-        (
-            "homebrew.schema.json",
-            serde_json::to_string_pretty(&schema_for!(HomebrewManifest)).unwrap(),
-        ),
-        (
-            "host-binaries.schema.json",
-            serde_json::to_string_pretty(&schema_for!(HostBinariesManifest)).unwrap(),
-        ),
-        (
-            "vendor-artifacts.schema.json",
-            serde_json::to_string_pretty(&schema_for!(VendorArtifactsManifest)).unwrap(),
-        ),
-    ]
+fn render(info: &SchemaInfo) -> String {
+    serde_json::to_string_pretty(&(info.generate)()).expect("schemas serialize to JSON")
+}
+
+/// Find a schema by name (`FlatpakAppsManifest`) or file name
+/// (`flatpak-apps.schema.json`).
+fn find_schema(name: &str) -> Option<&'static SchemaInfo> {
+    SCHEMAS
+        .iter()
+        .find(|info| info.name == name || info.filename == name)
+}
+
+/// Work out which schema a manifest file should be validated against.
+///
+/// A `$schema` field wins (only its file name is compared, so both
+/// `../schemas/x.schema.json` and published URLs work). Otherwise the path
+/// is matched against the known manifest names, ignoring an architecture
+/// infix such as `flatpak-apps.arm64.json`.
+fn detect_schema(path: &Path, document: &Value) -> Option<&'static SchemaInfo> {
+    if let Some(reference) = document.get("$schema").and_then(Value::as_str) {
+        let filename = reference.rsplit('/').next().unwrap_or(reference);
+        if let Some(info) = find_schema(filename) {
+            return Some(info);
+        }
+    }
+
+    let file_name = path.file_name()?.to_str()?;
+    let mut candidates = vec![path.to_path_buf()];
+    if let [stem, _arch, "json"] = file_name.split('.').collect::<Vec<_>>()[..] {
+        candidates.push(path.with_file_name(format!("{stem}.json")));
+    }
+    SCHEMAS.iter().find(|info| {
+        info.manifest
+            .is_some_and(|m| candidates.iter().any(|c| c.ends_with(m)))
+    })
+}
+
+/// Validate one file, printing its violations. Returns how many were found.
+fn validate_file(path: &Path, schema: Option<&str>) -> Result<usize> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let document: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {} as JSON", path.display()))?;
+
+    let info = match schema {
+        Some(name) => find_schema(name)
+            .with_context(|| format!("Unknown schema '{name}'; see `bkt schema list`"))?,
+        None => detect_schema(path, &document).with_context(|| {
+            format!(
+                "Cannot tell which schema {} uses; add a \"$schema\" field or pass --schema",
+                path.display()
+            )
+        })?,
+    };
+
+    let schema = serde_json::to_value((info.generate)())?;
+    let violations = json_schema::validate(&schema, &document);
+    if violations.is_empty() {
+        Output::success(format!("{} ({})", path.display(), info.name));
+    } else {
+        Output::error(format!(
+            "{} ({}): {} violation(s)",
+            path.display(),
+            info.name,
+            violations.len()
+        ));
+        for violation in &violations {
+            Output::list_item(violation.to_string());
+        }
+    }
+    Ok(violations.len())
 }
 
 pub fn run(args: SchemaArgs) -> Result<()> {
     match args.action {
-        SchemaAction::Generate { output } => {
-            let schemas = generate_all_schemas();
-
-            match output {
-                Some(dir) => {
-                    // Write schemas to files
-                    fs::create_dir_all(&dir)
-                        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
-
-                    for (filename, json) in schemas {
-                        let path = dir.join(filename);
-                        fs::write(&path, &json)
-                            .with_context(|| format!("Failed to write {}", path.display()))?;
-                        println!("Wrote {}", path.display());
-                    }
+        SchemaAction::Generate { output } => match output {
+            Some(dir) => {
+                // Write schemas to files
+                fs::create_dir_all(&dir)
+                    .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+
+                for info in SCHEMAS {
+                    let path = dir.join(info.filename);
+                    fs::write(&path, render(info))
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("Wrote {}", path.display());
                 }
-                None => {
-                    // Print all schemas to stdout as a combined object
-                    let mut combined: serde_json::Map<String, serde_json::Value> =
-                        serde_json::Map::new();
-
-                    for (filename, json) in schemas {
-                        let value: serde_json::Value = serde_json::from_str(&json)?;
-                        let name = filename.strip_suffix(".schema.json").unwrap_or(filename);
-                        combined.insert(name.to_string(), value);
-                    }
-
-                    println!("{}", serde_json::to_string_pretty(&combined)?);
+            }
+            None => {
+                // Print all schemas to stdout as a combined object
+                let mut combined = serde_json::Map::new();
+                for info in SCHEMAS {
+                    let name = info
+                        .filename
+                        .strip_suffix(".schema.json")
+                        .unwrap_or(info.filename);
+                    combined.insert(name.to_string(), serde_json::to_value((info.generate)())?);
                 }
+                println!("{}", serde_json::to_string_pretty(&combined)?);
             }
-        }
+        },
         SchemaAction::List => {
             println!("Available schema types:\n");
             for info in SCHEMAS {
                 println!("  {} ({})", info.name, info.filename);
-                println!("    {}\n", info.description);
+                println!("    {}", info.description);
+                if let Some(manifest) = info.manifest {
+                    println!("    Validates: {manifest}");
+                }
+                println!();
+            }
+        }
+        SchemaAction::Validate { files, schema } => {
+            let mut invalid = 0;
+            for file in &files {
+                if validate_file(file, schema.as_deref())? > 0 {
+                    invalid += 1;
+                }
+            }
+            if invalid > 0 {
+                bail!(
+                    "{} of {} file(s) failed schema validation",
+                    invalid,
+                    files.len()
+                );
             }
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_schema_from_schema_field_or_file_name() {
+        let doc = json!({ "$schema": "https://wycats.github.io/bootc/flatpak-apps.schema.json" });
+        assert_eq!(
+            detect_schema(Path::new("anything.json"), &doc).map(|i| i.name),
+            Some("FlatpakAppsManifest")
+        );
+
+        let detect = |path: &str| detect_schema(Path::new(path), &json!({})).map(|i| i.name);
+        assert_eq!(
+            detect("manifests/system-packages.json"),
+            Some("SystemPackagesManifest")
+        );
+        assert_eq!(
+            detect("manifests/external-repos.arm64.json"),
+            Some("ExternalReposManifest")
+        );
+        assert_eq!(detect("upstream/manifest.json"), Some("UpstreamManifest"));
+        assert_eq!(detect("manifest.json"), None);
+    }
+
+    #[test]
+    fn test_repo_manifests_validate() {
+        let manifests = Path::new(env!("CARGO_MANIFEST_DIR")).join("../manifests");
+        let mut checked = 0;
+        for entry in fs::read_dir(&manifests).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let document: Value =
+                serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            let info = detect_schema(&path, &document)
+                .unwrap_or_else(|| panic!("no schema for {}", path.display()));
+            let schema = serde_json::to_value((info.generate)()).unwrap();
+            let violations = json_schema::validate(&schema, &document);
+            assert!(
+                violations.is_empty(),
+                "{}: {:?}",
+                path.display(),
+                violations
+            );
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -1,18 +1,20 @@
 //! Shim command implementation.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use crate::manifest::{Shim, ShimsManifest};
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
+use crate::subsystem::get_installed_shims;
 
 #[derive(Debug, Args)]
 pub struct ShimArgs {
@@ -29,6 +31,15 @@ pub enum ShimAction {
         /// Host command name (defaults to shim name)
         #[arg(short = 'H', long)]
         host: Option<String>,
+        /// Environment variable for the host command (repeatable)
+        #[arg(long = "env", value_name = "KEY=VAL", value_parser = parse_env_var)]
+        env: Vec<(String, String)>,
+        /// Don't pass the caller's environment to the host command
+        #[arg(long)]
+        clear_env: bool,
+        /// Absolute host directory to run the command in
+        #[arg(long)]
+        workdir: Option<String>,
     },
     /// Remove a shim from the manifest
     Remove {
@@ -38,89 +49,79 @@ pub enum ShimAction {
     /// List all shims in the manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Sync shims to the toolbox
-    Sync,
+    Sync {
+        /// Keep shim scripts that are no longer in the manifest
+        #[arg(long)]
+        keep_unmanaged: bool,
+    },
+}
+
+/// Parse a `KEY=VAL` environment variable argument.
+fn parse_env_var(arg: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = arg.split_once('=') else {
+        return Err(format!("Expected KEY=VAL, got '{}'", arg));
+    };
+    if key.is_empty()
+        || key.starts_with(|c: char| c.is_ascii_digit())
+        || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("Invalid environment variable name '{}'", key));
+    }
+    Ok((key.to_string(), value.to_string()))
 }
 
+/// Header line marking a shim script as written by `bkt shim sync`.
+const MANAGED_MARKER: &str = "# Managed by: bkt shim";
+
 /// Generate the content of a shim script.
 /// Uses shlex for proper POSIX-compliant shell quoting.
-fn generate_shim_script(host_cmd: &str) -> Result<String> {
-    // Use shlex for proper shell quoting (handles all special characters)
-    let quoted = shlex::try_quote(host_cmd)
-        .map_err(|e| anyhow::anyhow!("Failed to quote command '{}': {}", host_cmd, e))?;
+fn generate_shim_script(shim: &Shim) -> Result<String> {
     Ok(format!(
         r#"#!/bin/bash
 # Auto-generated shim - delegates to host command
-# Managed by: bkt shim
+{MANAGED_MARKER}
 # Host command: {host_cmd}
-exec flatpak-spawn --host {quoted} "$@"
+exec {spawn} "$@"
 "#,
-        host_cmd = host_cmd,
-        quoted = quoted
+        host_cmd = shim.host_cmd(),
+        spawn = shim.spawn_command()?
     ))
 }
 
-/// Sync all shims from merged manifest to disk.
-fn sync_shims(dry_run: bool) -> Result<()> {
-    let shims_dir = ShimsManifest::shims_dir();
-
-    // Load manifest
-    let merged = ShimsManifest::load_repo()?;
-
-    if !dry_run {
-        // Create shims directory
-        fs::create_dir_all(&shims_dir).with_context(|| {
-            format!("Failed to create shims directory: {}", shims_dir.display())
-        })?;
-
-        // Remove all existing shims (clean slate)
-        if shims_dir.exists() {
-            for entry in fs::read_dir(&shims_dir)? {
-                let entry = entry?;
-                if entry.file_type()?.is_file() {
-                    fs::remove_file(entry.path())?;
-                }
-            }
-        }
+/// Sync all shims from merged manifest to disk, removing orphaned ones.
+fn sync_shims(plan: &ExecutionPlan) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let sync_plan = ShimSyncCommand {
+        keep_unmanaged: false,
     }
-
-    // Generate shims
-    let mut count = 0;
-    for shim in &merged.shims {
-        let shim_path = shims_dir.join(&shim.name);
-        let content = generate_shim_script(shim.host_cmd())?;
-
-        if dry_run {
-            Output::dry_run(format!(
-                "Would create: {} -> {}",
-                shim.name,
-                shim.host_cmd()
-            ));
-        } else {
-            fs::write(&shim_path, &content)
-                .with_context(|| format!("Failed to write shim: {}", shim_path.display()))?;
-
-            // Make executable
-            let mut perms = fs::metadata(&shim_path)?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&shim_path, perms)?;
-        }
-        count += 1;
+    .plan(&PlanContext::new(cwd, plan.clone()))?;
+    let shims_dir = sync_plan.shims_dir.clone();
+    let removed = sync_plan.to_remove.len();
+
+    let mut exec_ctx = ExecuteContext::new(plan.clone());
+    let report = sync_plan.execute(&mut exec_ctx)?;
+    if report.has_failures() {
+        let failed = report.failure_count();
+        print_report(&report.with_subsystem("shim"), plan)?;
+        bail!("Failed to sync {} shim(s)", failed);
     }
 
-    if dry_run {
-        Output::dry_run(format!(
-            "Would generate {} shims in {}",
-            count,
+    let created = report.success_count() - removed;
+    if removed > 0 {
+        Output::success(format!(
+            "Generated {} shims and removed {} orphaned in {}",
+            created,
+            removed,
             shims_dir.display()
         ));
     } else {
         Output::success(format!(
             "Generated {} shims in {}",
-            count,
+            created,
             shims_dir.display()
         ));
     }
@@ -144,22 +145,39 @@ fn save_repo_manifest(manifest: &ShimsManifest) -> Result<()> {
 
 pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
     match args.action {
-        ShimAction::Add { name, host } => {
+        ShimAction::Add {
+            name,
+            host,
+            env,
+            clear_env,
+            workdir,
+        } => {
+            if let Some(dir) = &workdir
+                && !dir.starts_with('/')
+            {
+                bail!("Working directory must be an absolute path: {}", dir);
+            }
+
             let host_cmd = host.clone().unwrap_or_else(|| name.clone());
-            let shim = Shim {
-                name: name.clone(),
-                host: if host_cmd == name {
+            let mut shim = Shim::new(
+                name.clone(),
+                if host_cmd == name {
                     None
                 } else {
                     Some(host_cmd.clone())
                 },
-            };
+            );
+            shim.env = env.into_iter().collect();
+            shim.clear_env = clear_env;
+            shim.workdir = workdir;
+            // Fail before touching the manifest if the script can't be quoted
+            shim.spawn_command()?;
 
             // Load and update manifest
             if plan.should_update_manifest() {
                 let mut manifest = ShimsManifest::load_repo()?;
                 let is_update = manifest.find(&name).is_some();
-                manifest.upsert(shim);
+                manifest.upsert(shim.clone());
                 save_repo_manifest(&manifest)?;
 
                 if is_update {
@@ -173,7 +191,7 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
 
             // Sync shims to disk (shims are always synced locally, not host-dependent)
             if plan.should_execute_locally() {
-                sync_shims(false)?;
+                sync_shims(plan)?;
             } else if plan.dry_run {
                 Output::dry_run("Would sync shims to disk");
             }
@@ -181,15 +199,7 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
             if plan.should_create_pr() {
                 // Load repo manifest, add the shim, and create PR
                 let mut system = ShimsManifest::load_repo()?;
-                let shim_for_pr = Shim {
-                    name: name.clone(),
-                    host: if host_cmd == name {
-                        None
-                    } else {
-                        Some(host_cmd.clone())
-                    },
-                };
-                system.upsert(shim_for_pr);
+                system.upsert(shim);
                 let manifest_content = serde_json::to_string_pretty(&system)?;
 
                 plan.maybe_create_pr("shim", "add", &name, "host-shims.json", &manifest_content)?;
@@ -210,7 +220,7 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
 
             // Sync shims to disk
             if plan.should_execute_locally() {
-                sync_shims(false)?;
+                sync_shims(plan)?;
             } else if plan.dry_run {
                 Output::dry_run("Would sync shims to disk");
             }
@@ -236,7 +246,7 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
         ShimAction::List { format } => {
             let merged = ShimsManifest::load_repo()?;
 
-            if format == "json" {
+            if format == OutputFormat::Json {
                 let json = serde_json::to_string_pretty(&merged)?;
                 println!("{}", json);
             } else {
@@ -267,20 +277,21 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
                 }
             }
         }
-        ShimAction::Sync => {
+        ShimAction::Sync { keep_unmanaged } => {
             // Use the new Plan-based implementation
             let cwd = std::env::current_dir()?;
             let plan_ctx = PlanContext::new(cwd, plan.clone());
 
-            let sync_plan = ShimSyncCommand.plan(&plan_ctx)?;
+            let sync_plan = ShimSyncCommand { keep_unmanaged }.plan(&plan_ctx)?;
 
-            if sync_plan.is_empty() {
-                Output::info("No shims to generate.");
+            if sync_plan.is_empty() && !plan.json_output() {
+                Output::info("No shims to sync.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", sync_plan.describe());
+            let summary = sync_plan.describe().with_subsystem("shim");
+            print_summary(&summary, plan, plan.dry_run)?;
 
             if plan.dry_run {
                 return Ok(());
@@ -289,7 +300,7 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = sync_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("shim"), plan)?;
         }
     }
     Ok(())
@@ -300,7 +311,19 @@ pub fn run(args: ShimArgs, plan: &ExecutionPlan) -> Result<()> {
 // ============================================================================
 
 /// Command to sync shims from manifests to disk.
-pub struct ShimSyncCommand;
+pub struct ShimSyncCommand {
+    /// Whether to keep shims on disk that are no longer in the manifest.
+    pub keep_unmanaged: bool,
+}
+
+/// A bkt-generated shim on disk that the manifest no longer lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedShim {
+    /// Shim name (the script's file name).
+    pub name: String,
+    /// Symlink on `PATH` pointing at the script, if there is one.
+    pub symlink: Option<PathBuf>,
+}
 
 /// Plan for syncing shims.
 pub struct ShimSyncPlan {
@@ -308,6 +331,8 @@ pub struct ShimSyncPlan {
     shims_dir: PathBuf,
     /// Shims to create.
     to_create: Vec<Shim>,
+    /// Orphaned shims to remove.
+    to_remove: Vec<OrphanedShim>,
 }
 
 impl Plannable for ShimSyncCommand {
@@ -316,19 +341,87 @@ impl Plannable for ShimSyncCommand {
     fn plan(&self, _ctx: &PlanContext) -> Result<Self::Plan> {
         // Load manifest (read-only, no side effects)
         let merged = ShimsManifest::load_repo()?;
+        let shims_dir = ShimsManifest::shims_dir();
+
+        let to_remove = if self.keep_unmanaged {
+            Vec::new()
+        } else {
+            orphaned_shims(
+                &shims_dir,
+                &ShimsManifest::bin_dir(),
+                get_installed_shims(),
+                &merged,
+            )
+        };
 
         Ok(ShimSyncPlan {
-            shims_dir: ShimsManifest::shims_dir(),
+            shims_dir,
             to_create: merged.shims,
+            to_remove,
+        })
+    }
+}
+
+/// Installed shims that bkt generated but the manifest no longer lists.
+///
+/// Scripts without the bkt header or a `flatpak-spawn` line were put there
+/// by hand and are left alone.
+fn orphaned_shims(
+    shims_dir: &Path,
+    bin_dir: &Path,
+    installed: Vec<String>,
+    manifest: &ShimsManifest,
+) -> Vec<OrphanedShim> {
+    let mut orphans: Vec<OrphanedShim> = installed
+        .into_iter()
+        .filter(|name| manifest.find(name).is_none())
+        .filter(|name| is_generated_shim(&shims_dir.join(name)))
+        .map(|name| {
+            let link = bin_dir.join(&name);
+            let symlink = fs::read_link(&link)
+                .ok()
+                .filter(|target| points_into(bin_dir, target, shims_dir))
+                .map(|_| link);
+            OrphanedShim { name, symlink }
         })
+        .collect();
+    orphans.sort_by(|a, b| a.name.cmp(&b.name));
+    orphans
+}
+
+/// Whether the script at `path` looks like one bkt wrote.
+fn is_generated_shim(path: &Path) -> bool {
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+    content.lines().any(|line| {
+        line == MANAGED_MARKER || line.trim_start().starts_with("exec flatpak-spawn --host")
+    })
+}
+
+/// Whether a symlink in `link_dir` with `target` resolves to a file in `dir`.
+///
+/// Compared lexically, so dangling links (the script already gone) count.
+fn points_into(link_dir: &Path, target: &Path, dir: &Path) -> bool {
+    let mut resolved = PathBuf::new();
+    for component in link_dir.join(target).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
     }
+    resolved.parent() == Some(dir)
 }
 
 impl Plan for ShimSyncPlan {
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!(
-            "Shim Sync: {} shim(s) in {}",
+            "Shim Sync: {} to create, {} to remove in {}",
             self.to_create.len(),
+            self.to_remove.len(),
             self.shims_dir.display()
         ));
 
@@ -350,6 +443,18 @@ impl Plan for ShimSyncPlan {
             }
         }
 
+        for orphan in &self.to_remove {
+            let details = match &orphan.symlink {
+                Some(link) => format!("not in manifest (and {})", link.display()),
+                None => "not in manifest".to_string(),
+            };
+            summary.add_operation(Operation::with_details(
+                Verb::Remove,
+                format!("shim:{}", orphan.name),
+                details,
+            ));
+        }
+
         summary
     }
 
@@ -364,23 +469,11 @@ impl Plan for ShimSyncPlan {
             )
         })?;
 
-        // Remove all existing shims in the managed directory.
-        // This directory is exclusively managed by bkt; any files here
-        // are assumed to be bkt-generated shims that should be regenerated.
-        if self.shims_dir.exists() {
-            for entry in fs::read_dir(&self.shims_dir)? {
-                let entry = entry?;
-                if entry.file_type()?.is_file() {
-                    fs::remove_file(entry.path())?;
-                }
-            }
-        }
-
         // Generate shims
         for shim in &self.to_create {
             let shim_path = self.shims_dir.join(&shim.name);
 
-            match generate_shim_script(shim.host_cmd()) {
+            match generate_shim_script(shim) {
                 Ok(content) => {
                     if let Err(e) = fs::write(&shim_path, &content) {
                         report.record_failure_and_notify(
@@ -425,10 +518,100 @@ impl Plan for ShimSyncPlan {
             }
         }
 
+        // Remove orphaned shims and their symlinks
+        for orphan in &self.to_remove {
+            let result = fs::remove_file(self.shims_dir.join(&orphan.name)).and_then(|()| {
+                match &orphan.symlink {
+                    Some(link) => fs::remove_file(link),
+                    None => Ok(()),
+                }
+            });
+            match result {
+                Ok(()) => report.record_success_and_notify(
+                    ctx,
+                    Verb::Remove,
+                    format!("shim:{}", orphan.name),
+                ),
+                Err(e) => report.record_failure_and_notify(
+                    ctx,
+                    Verb::Remove,
+                    format!("shim:{}", orphan.name),
+                    e.to_string(),
+                ),
+            }
+        }
+
         Ok(report)
     }
 
     fn is_empty(&self) -> bool {
-        self.to_create.is_empty()
+        self.to_create.is_empty() && self.to_remove.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn orphans_are_generated_shims_missing_from_manifest() {
+        let temp = TempDir::new().unwrap();
+        let shims_dir = temp.path().join(".local/toolbox/shims");
+        let bin_dir = temp.path().join(".local/bin");
+        fs::create_dir_all(&shims_dir).unwrap();
+        fs::create_dir_all(&bin_dir).unwrap();
+
+        let write = |name: &str, content: &str| fs::write(shims_dir.join(name), content).unwrap();
+        write(
+            "podman",
+            &generate_shim_script(&Shim::new("podman", None)).unwrap(),
+        );
+        write(
+            "nmcli",
+            &generate_shim_script(&Shim::new("nmcli", None)).unwrap(),
+        );
+        // Baked into the image from /etc/skel: no header, only the spawn line
+        write("bootc", &Shim::new("bootc", None).script().unwrap());
+        write("mine", "#!/bin/sh\necho hand-written\n");
+
+        std::os::unix::fs::symlink("../toolbox/shims/nmcli", bin_dir.join("nmcli")).unwrap();
+        std::os::unix::fs::symlink("/usr/bin/true", bin_dir.join("bootc")).unwrap();
+
+        let manifest = ShimsManifest {
+            schema: None,
+            shims: vec![Shim::new("podman", None)],
+        };
+        let installed = ["bootc", "mine", "nmcli", "podman"]
+            .map(String::from)
+            .to_vec();
+
+        assert_eq!(
+            orphaned_shims(&shims_dir, &bin_dir, installed, &manifest),
+            vec![
+                OrphanedShim {
+                    name: "bootc".to_string(),
+                    symlink: None,
+                },
+                OrphanedShim {
+                    name: "nmcli".to_string(),
+                    symlink: Some(bin_dir.join("nmcli")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn symlink_targets_resolve_lexically() {
+        let bin = Path::new("/home/me/.local/bin");
+        let shims = Path::new("/home/me/.local/toolbox/shims");
+        assert!(points_into(bin, Path::new("../toolbox/shims/x"), shims));
+        assert!(points_into(
+            bin,
+            Path::new("/home/me/.local/toolbox/shims/x"),
+            shims
+        ));
+        assert!(!points_into(bin, Path::new("/usr/bin/x"), shims));
+        assert!(!points_into(bin, Path::new("x"), shims));
     }
 }
@@ -2,6 +2,13 @@
 //!
 //! Manages dotfiles that get copied to /etc/skel in the image.
 //!
+//! # Templates
+//!
+//! Files ending in `.tmpl` are deployed without the suffix, with `${VAR}`
+//! placeholders filled in from `~/.config/bootc/skel-vars.json` and the
+//! built-ins `HOSTNAME`, `USER`, and `HOME`. Write `$${VAR}` for a literal
+//! `${VAR}`. An unknown variable is an error, never copied through.
+//!
 //! # Security
 //!
 //! This module validates file paths to prevent path traversal attacks.
@@ -9,15 +16,25 @@
 //! are rejected.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::manifest::SkelVars;
+use crate::manifest::parsers::{ConfigFileType, SemanticDiff, compute_semantic_diff};
 use crate::output::Output;
 use crate::pipeline::ExecutionPlan;
 use crate::repo::find_repo_path;
 use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Where the image deploys skel files on ostree-based systems.
+pub const IMAGE_SKEL_DIR: &str = "/usr/etc/skel";
+
+/// Suffix of skel files that are rendered before being deployed.
+pub const TEMPLATE_SUFFIX: &str = ".tmpl";
+
 #[derive(Debug, Args)]
 pub struct SkelArgs {
     #[command(subcommand)]
@@ -35,6 +52,10 @@ pub enum SkelAction {
     Diff {
         /// Specific file to diff (optional, relative to $HOME)
         file: Option<String>,
+
+        /// Compare against the image's deployed skel instead of $HOME
+        #[arg(long)]
+        image: bool,
     },
     /// List all files in skel/
     List,
@@ -47,14 +68,14 @@ pub enum SkelAction {
 }
 
 /// Get the skel directory in the repo.
-fn skel_dir() -> Result<PathBuf> {
+pub(crate) fn skel_dir() -> Result<PathBuf> {
     let repo = find_repo_path()?;
     Ok(repo.join("skel"))
 }
 
 /// Get home directory.
 /// Uses the directories crate for reliable cross-platform support.
-fn home_dir() -> Result<PathBuf> {
+pub(crate) fn home_dir() -> Result<PathBuf> {
     directories::BaseDirs::new()
         .map(|d| d.home_dir().to_path_buf())
         .or_else(|| std::env::var("HOME").ok().map(PathBuf::from))
@@ -72,8 +93,20 @@ fn validate_skel_path(path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Get the deployed skel directory in the image.
+///
+/// Falls back to /etc/skel on systems without a /usr/etc split.
+pub(crate) fn image_skel_dir() -> PathBuf {
+    let usr_etc = PathBuf::from(IMAGE_SKEL_DIR);
+    if usr_etc.exists() {
+        usr_etc
+    } else {
+        PathBuf::from("/etc/skel")
+    }
+}
+
 /// List all files in skel directory recursively.
-fn list_skel_files(skel: &Path) -> Result<Vec<PathBuf>> {
+pub(crate) fn list_skel_files(skel: &Path) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
     if !skel.exists() {
@@ -99,48 +132,349 @@ fn list_skel_files(skel: &Path) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
-/// Result of comparing two files
-enum DiffResult {
-    Identical,
-    Different(String),
-    MissingInHome,
-    MissingInSkel,
+/// Whether a skel file is a template.
+pub(crate) fn is_template(file: &Path) -> bool {
+    file.to_str()
+        .is_some_and(|name| name.ends_with(TEMPLATE_SUFFIX))
 }
 
-/// Compare two files and return diff result.
-fn diff_files(
-    skel_file: &Path,
-    home_file: &Path,
-    runner: &dyn CommandRunner,
-) -> Result<DiffResult> {
-    if !home_file.exists() {
-        return Ok(DiffResult::MissingInHome);
+/// Where a skel file is deployed, relative to $HOME: templates lose their
+/// `.tmpl` suffix, everything else keeps its path.
+pub(crate) fn deployed_path(file: &Path) -> PathBuf {
+    match file
+        .to_str()
+        .and_then(|name| name.strip_suffix(TEMPLATE_SUFFIX))
+    {
+        Some(stripped) if is_template(file) => PathBuf::from(stripped),
+        _ => file.to_path_buf(),
+    }
+}
+
+/// Template variables: the built-ins, overridden by the user's skel-vars.json.
+pub(crate) fn template_vars() -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    let hostname = fs::read_to_string("/etc/hostname")
+        .map(|s| s.trim().to_string())
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok());
+    if let Some(hostname) = hostname {
+        vars.insert("HOSTNAME".to_string(), hostname);
+    }
+    if let Ok(user) = std::env::var("USER") {
+        vars.insert("USER".to_string(), user);
+    }
+    if let Ok(home) = home_dir() {
+        vars.insert("HOME".to_string(), home.display().to_string());
+    }
+
+    vars.extend(SkelVars::load_user()?.vars);
+    Ok(vars)
+}
+
+/// Substitute `${VAR}` placeholders in `text`.
+///
+/// `$${VAR}` renders as a literal `${VAR}`. On failure, returns the names of
+/// the variables that have no value.
+pub(crate) fn render_template(
+    text: &str,
+    vars: &BTreeMap<String, String>,
+) -> std::result::Result<String, Vec<String>> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut unknown = BTreeSet::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let escaped = rest[..start].ends_with('$');
+        let after = &rest[start + 2..];
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        let closed = after[name_len..].starts_with('}');
+
+        if name.is_empty() || !closed || name.starts_with(|c: char| c.is_ascii_digit()) {
+            // Not a placeholder (e.g. `${#array[@]}`); leave it alone
+            rendered.push_str(&rest[..start + 2]);
+            rest = after;
+            continue;
+        }
+
+        if escaped {
+            rendered.push_str(&rest[..start - 1]);
+            rendered.push_str(&rest[start..start + 3 + name_len]);
+        } else {
+            rendered.push_str(&rest[..start]);
+            match vars.get(name) {
+                Some(value) => rendered.push_str(value),
+                None => {
+                    unknown.insert(name.to_string());
+                }
+            }
+        }
+        rest = &after[name_len + 1..];
+    }
+    rendered.push_str(rest);
+
+    if unknown.is_empty() {
+        Ok(rendered)
+    } else {
+        Err(unknown.into_iter().collect())
+    }
+}
+
+/// The bytes a skel file deploys as: templates rendered, other files as-is.
+pub(crate) fn deployed_content(
+    skel: &Path,
+    file: &Path,
+    vars: &BTreeMap<String, String>,
+) -> Result<Vec<u8>> {
+    let path = skel.join(file);
+    let bytes = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !is_template(file) {
+        return Ok(bytes);
     }
 
-    if !skel_file.exists() {
-        return Ok(DiffResult::MissingInSkel);
+    let text = String::from_utf8(bytes)
+        .with_context(|| format!("Template skel/{} is not valid UTF-8", file.display()))?;
+    let rendered = render_template(&text, vars).map_err(|unknown| {
+        let names: Vec<String> = unknown
+            .iter()
+            .map(|name| format!("${{{}}}", name))
+            .collect();
+        anyhow::anyhow!(
+            "skel/{}: unknown template variable{} {} (set in {})",
+            file.display(),
+            if names.len() == 1 { "" } else { "s" },
+            names.join(", "),
+            SkelVars::user_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| SkelVars::FILE_NAME.to_string())
+        )
+    })?;
+    Ok(rendered.into_bytes())
+}
+
+/// How a deployed copy of a skel file compares to the repo source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyState {
+    InSync,
+    Modified,
+    Missing,
+}
+
+/// Per-file comparison of a repo skel file against its deployed copies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkelFileStatus {
+    /// Deployed path, relative to $HOME (and to the image skel directory)
+    pub file: String,
+    /// Path relative to skel/; differs from `file` for templates
+    pub source: String,
+    /// The copy in the image's skel directory
+    pub image: CopyState,
+    /// The copy in $HOME
+    pub home: CopyState,
+}
+
+impl SkelFileStatus {
+    pub fn in_sync(&self) -> bool {
+        self.image == CopyState::InSync && self.home == CopyState::InSync
     }
+}
 
+fn file_sha256(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn copy_state(source_sha256: &str, copy: &Path) -> Result<CopyState> {
+    if !copy.is_file() {
+        return Ok(CopyState::Missing);
+    }
+    Ok(if file_sha256(copy)? == source_sha256 {
+        CopyState::InSync
+    } else {
+        CopyState::Modified
+    })
+}
+
+/// Compare every repo skel file (by sha256 of its rendered content) against
+/// the image and $HOME copies.
+pub fn skel_file_statuses(
+    skel: &Path,
+    image_skel: &Path,
+    home: &Path,
+    vars: &BTreeMap<String, String>,
+) -> Result<Vec<SkelFileStatus>> {
+    list_skel_files(skel)?
+        .iter()
+        .map(|file| {
+            let source_sha256 =
+                format!("{:x}", Sha256::digest(deployed_content(skel, file, vars)?));
+            let deployed = deployed_path(file);
+            Ok(SkelFileStatus {
+                file: deployed.display().to_string(),
+                source: file.display().to_string(),
+                image: copy_state(&source_sha256, &image_skel.join(&deployed))?,
+                home: copy_state(&source_sha256, &home.join(&deployed))?,
+            })
+        })
+        .collect()
+}
+
+/// Unified diff between two files, or `None` if they are identical.
+fn diff_files(
+    skel_file: &Path,
+    local_file: &Path,
+    runner: &dyn CommandRunner,
+) -> Result<Option<String>> {
     let skel_arg = skel_file.to_str().unwrap_or_default();
-    let home_arg = home_file.to_str().unwrap_or_default();
+    let local_arg = local_file.to_str().unwrap_or_default();
 
     let output = runner
         .run_output(
             "diff",
-            &["-u", "--", skel_arg, home_arg],
+            &["-u", "--", skel_arg, local_arg],
             &CommandOptions::default(),
         )
         .context("Failed to run diff")?;
 
     if output.status.success() {
-        Ok(DiffResult::Identical)
+        Ok(None)
     } else {
-        Ok(DiffResult::Different(
-            String::from_utf8_lossy(&output.stdout).to_string(),
-        ))
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
     }
 }
 
+/// Heuristic binary check: NUL bytes or invalid UTF-8.
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+/// Print how a local copy differs from the repo source.
+///
+/// `source` is what the skel file deploys as (rendered, for a template).
+/// Binary files are only reported, config files with a known format get a
+/// semantic diff, and everything else falls back to a unified diff.
+fn print_file_diff(
+    file: &str,
+    source: &[u8],
+    skel_file: &Path,
+    local_file: &Path,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    let local =
+        fs::read(local_file).with_context(|| format!("Failed to read {}", local_file.display()))?;
+
+    if source == local {
+        Output::success("Files are identical");
+        return Ok(());
+    }
+
+    if is_binary(source) || is_binary(&local) {
+        println!("  {} binary differs", "≠".yellow());
+        return Ok(());
+    }
+
+    let source_text = String::from_utf8_lossy(source);
+    let file_type = ConfigFileType::detect(file, Some(&source_text));
+    if file_type != ConfigFileType::Other {
+        let diff = compute_semantic_diff(
+            file_type,
+            Some(&source_text),
+            Some(&String::from_utf8_lossy(&local)),
+        );
+        if print_semantic_diff(&diff) {
+            return Ok(());
+        }
+    }
+
+    // diff(1) needs the rendered template on disk
+    if is_template(skel_file) {
+        let rendered = std::env::temp_dir().join(format!(
+            "bkt-skel-{}-{}",
+            std::process::id(),
+            file.replace('/', "_")
+        ));
+        fs::write(&rendered, source)
+            .with_context(|| format!("Failed to write {}", rendered.display()))?;
+        let diff = diff_files(&rendered, local_file, runner);
+        let _ = fs::remove_file(&rendered);
+        if let Some(diff) = diff? {
+            print_colored_diff(&diff);
+        }
+        return Ok(());
+    }
+
+    if let Some(diff) = diff_files(skel_file, local_file, runner)? {
+        print_colored_diff(&diff);
+    }
+    Ok(())
+}
+
+/// Print a semantic diff. Returns false if it has nothing to show
+/// (e.g. only comments or whitespace changed).
+fn print_semantic_diff(diff: &SemanticDiff) -> bool {
+    let root = String::new();
+    let mut changes = Vec::new();
+    match diff {
+        SemanticDiff::Keyd(diff) => {
+            for (section, bindings) in &diff.sections {
+                for binding in bindings {
+                    changes.push((section, &binding.key, &binding.from, &binding.to));
+                }
+            }
+        }
+        SemanticDiff::Systemd(diff) => {
+            for (section, properties) in &diff.sections {
+                for prop in properties {
+                    changes.push((section, &prop.property, &prop.from, &prop.to));
+                }
+            }
+        }
+        SemanticDiff::KeyValue(diff) => {
+            for (section, properties) in &diff.sections {
+                for prop in properties {
+                    changes.push((section, &prop.property, &prop.from, &prop.to));
+                }
+            }
+        }
+        SemanticDiff::Xml(diff) => {
+            for change in diff.changes() {
+                changes.push((&root, &change.property, &change.from, &change.to));
+            }
+        }
+        SemanticDiff::LineSummary(_) => return false,
+    }
+
+    if changes.is_empty() {
+        return false;
+    }
+
+    for (section, key, from, to) in changes {
+        let name = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("[{}] {}", section, key)
+        };
+        match (from, to) {
+            (None, Some(new)) => println!("  {} {} = {}", "+".green(), name, new.green()),
+            (Some(old), None) => println!("  {} {} = {}", "-".red(), name, old.red()),
+            (Some(old), Some(new)) => {
+                println!(
+                    "  {} {}: {} → {}",
+                    "~".yellow(),
+                    name,
+                    old.red(),
+                    new.green()
+                )
+            }
+            (None, None) => {}
+        }
+    }
+    true
+}
+
 /// Print a colored unified diff
 fn print_colored_diff(diff: &str) {
     for line in diff.lines() {
@@ -232,9 +566,15 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                 plan.maybe_create_pr("skel", "add", &file, &format!("skel/{}", file), &content)?;
             }
         }
-        SkelAction::Diff { file } => {
-            let home = home_dir()?;
+        SkelAction::Diff { file, image } => {
             let skel = skel_dir()?;
+            let (local_dir, local_label) = if image {
+                let dir = image_skel_dir();
+                let label = dir.display().to_string();
+                (dir, label)
+            } else {
+                (home_dir()?, "$HOME".to_string())
+            };
 
             if let Some(file) = file {
                 // Diff specific file
@@ -242,21 +582,28 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                     .trim_start_matches("./")
                     .trim_start_matches("~/")
                     .to_string();
+                validate_skel_path(&file)?;
 
-                let skel_file = skel.join(&file);
-                let home_file = home.join(&file);
+                // Accept either the template's name or the file it deploys as
+                let mut source = PathBuf::from(&file);
+                if !skel.join(&source).exists() && !is_template(&source) {
+                    source = PathBuf::from(format!("{}{}", file, TEMPLATE_SUFFIX));
+                }
+                let file = deployed_path(&source).display().to_string();
+                let skel_file = skel.join(&source);
+                let local_file = local_dir.join(&file);
 
                 println!("\n{}", format!("━━━ {} ━━━", file).bold());
-                match diff_files(&skel_file, &home_file, runner)? {
-                    DiffResult::Identical => Output::success("Files are identical"),
-                    DiffResult::Different(diff) => print_colored_diff(&diff),
-                    DiffResult::MissingInHome => {
-                        println!("  {} File missing in $HOME", "⚠".yellow());
+                if !skel_file.exists() {
+                    println!("  {} File missing in skel/", "⚠".yellow());
+                } else if !local_file.exists() {
+                    println!("  {} File missing in {}", "⚠".yellow(), local_label);
+                    if !image {
                         println!("  Run {} to create it", "bkt skel sync".cyan());
                     }
-                    DiffResult::MissingInSkel => {
-                        println!("  {} File missing in skel/", "⚠".yellow());
-                    }
+                } else {
+                    let content = deployed_content(&skel, &source, &template_vars()?)?;
+                    print_file_diff(&file, &content, &skel_file, &local_file, runner)?;
                 }
             } else {
                 // Diff all skel files
@@ -267,20 +614,17 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                     return Ok(());
                 }
 
+                let vars = template_vars()?;
                 let mut identical_files = Vec::new();
                 let mut different_files = Vec::new();
                 let mut missing_files = Vec::new();
 
                 // First pass: categorize files
-                for file in &files {
-                    let skel_file = skel.join(file);
-                    let home_file = home.join(file);
-
-                    match diff_files(&skel_file, &home_file, runner)? {
-                        DiffResult::Identical => identical_files.push(file.clone()),
-                        DiffResult::Different(_) => different_files.push(file.clone()),
-                        DiffResult::MissingInHome => missing_files.push(file.clone()),
-                        DiffResult::MissingInSkel => {} // Shouldn't happen when iterating skel files
+                for status in skel_file_statuses(&skel, &local_dir, &local_dir, &vars)? {
+                    match status.home {
+                        CopyState::InSync => identical_files.push(status.file),
+                        CopyState::Modified => different_files.push(status),
+                        CopyState::Missing => missing_files.push(status.file),
                     }
                 }
 
@@ -296,37 +640,42 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                     different_files.len().to_string().yellow(),
                     "differ".yellow(),
                     missing_files.len().to_string().cyan(),
-                    "missing in $HOME".cyan()
+                    format!("missing in {}", local_label).cyan()
                 );
                 println!();
 
                 // Show differing files with diffs
-                if !different_files.is_empty() {
-                    for file in &different_files {
-                        let skel_file = skel.join(file);
-                        let home_file = home.join(file);
-
-                        println!("{}", format!("━━━ {} ━━━", file.display()).bold().yellow());
-                        println!("  {} skel/{}", "←".red(), file.display());
-                        println!("  {} $HOME/{}", "→".green(), file.display());
-                        println!();
-
-                        if let DiffResult::Different(diff) =
-                            diff_files(&skel_file, &home_file, runner)?
-                        {
-                            print_colored_diff(&diff);
-                        }
-                        println!();
-                    }
+                for status in &different_files {
+                    let file = &status.file;
+                    println!("{}", format!("━━━ {} ━━━", file).bold().yellow());
+                    println!("  {} skel/{}", "←".red(), status.source);
+                    println!("  {} {}/{}", "→".green(), local_label, file);
+                    println!();
+
+                    let source = Path::new(&status.source);
+                    let content = deployed_content(&skel, source, &vars)?;
+                    print_file_diff(
+                        file,
+                        &content,
+                        &skel.join(source),
+                        &local_dir.join(file),
+                        runner,
+                    )?;
+                    println!();
                 }
 
                 // Show missing files
                 if !missing_files.is_empty() {
-                    println!("{}", "━━━ Missing in $HOME ━━━".bold().cyan());
+                    println!(
+                        "{}",
+                        format!("━━━ Missing in {} ━━━", local_label).bold().cyan()
+                    );
                     for file in &missing_files {
-                        println!("  {} {}", "⚠".yellow(), file.display());
+                        println!("  {} {}", "⚠".yellow(), file);
+                    }
+                    if !image {
+                        println!("\n  Run {} to create these files", "bkt skel sync".cyan());
                     }
-                    println!("\n  Run {} to create these files", "bkt skel sync".cyan());
                     println!();
                 }
 
@@ -351,7 +700,15 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
 
             Output::subheader(format!("FILES IN SKEL/ ({}):", files.len()));
             for file in &files {
-                Output::list_item(file.display().to_string());
+                if is_template(file) {
+                    Output::list_item(format!(
+                        "{} → ~/{}",
+                        file.display(),
+                        deployed_path(file).display()
+                    ));
+                } else {
+                    Output::list_item(file.display().to_string());
+                }
             }
         }
         SkelAction::Sync { force } => {
@@ -364,12 +721,20 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                 return Ok(());
             }
 
+            // Render every template up front so an unknown variable stops the
+            // sync before anything is written
+            let vars = template_vars()?;
+            let mut rendered = BTreeMap::new();
+            for file in files.iter().filter(|f| is_template(f)) {
+                rendered.insert(file.clone(), deployed_content(&skel, file, &vars)?);
+            }
+
             let mut copied = 0;
             let mut skipped = 0;
 
             for file in &files {
                 let skel_file = skel.join(file);
-                let home_file = home.join(file);
+                let home_file = home.join(deployed_path(file));
 
                 if home_file.exists() && !force {
                     if plan.dry_run {
@@ -391,14 +756,21 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
                         fs::create_dir_all(parent)?;
                     }
 
-                    fs::copy(&skel_file, &home_file).with_context(|| {
-                        format!(
-                            "Failed to copy {} to {}",
-                            skel_file.display(),
-                            home_file.display()
-                        )
-                    })?;
-                    Output::success(format!("Copied: {}", file.display()));
+                    if let Some(content) = rendered.get(file) {
+                        fs::write(&home_file, content)
+                            .with_context(|| format!("Failed to write {}", home_file.display()))?;
+                        fs::set_permissions(&home_file, fs::metadata(&skel_file)?.permissions())?;
+                        Output::success(format!("Rendered: {}", deployed_path(file).display()));
+                    } else {
+                        fs::copy(&skel_file, &home_file).with_context(|| {
+                            format!(
+                                "Failed to copy {} to {}",
+                                skel_file.display(),
+                                home_file.display()
+                            )
+                        })?;
+                        Output::success(format!("Copied: {}", file.display()));
+                    }
                 }
                 copied += 1;
             }
@@ -419,3 +791,155 @@ pub fn run(args: SkelArgs, plan: &ExecutionPlan) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, file: &str, content: &[u8]) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_skel_file_statuses_reports_each_location() {
+        let temp = TempDir::new().unwrap();
+        let (skel, image, home) = (
+            temp.path().join("skel"),
+            temp.path().join("image"),
+            temp.path().join("home"),
+        );
+
+        write(&skel, ".bashrc", b"alias ll='ls -l'\n");
+        write(&skel, ".config/app/app.conf", b"[main]\nkey=1\n");
+        write(&skel, ".profile", b"export A=1\n");
+
+        write(&image, ".bashrc", b"alias ll='ls -l'\n");
+        write(&image, ".config/app/app.conf", b"[main]\nkey=1\n");
+        write(&home, ".bashrc", b"alias ll='ls -la'\n");
+        write(&home, ".config/app/app.conf", b"[main]\nkey=1\n");
+        write(&home, ".profile", b"export A=1\n");
+
+        let statuses = skel_file_statuses(&skel, &image, &home, &BTreeMap::new()).unwrap();
+        let summary: Vec<_> = statuses
+            .iter()
+            .map(|s| (s.file.as_str(), s.image, s.home))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (".bashrc", CopyState::InSync, CopyState::Modified),
+                (".config/app/app.conf", CopyState::InSync, CopyState::InSync),
+                (".profile", CopyState::Missing, CopyState::InSync),
+            ]
+        );
+        assert!(statuses[1].in_sync());
+        assert!(!statuses[2].in_sync());
+    }
+
+    #[test]
+    fn test_skel_file_statuses_empty_without_skel_dir() {
+        let temp = TempDir::new().unwrap();
+        let missing = temp.path().join("nope");
+        assert!(
+            skel_file_statuses(&missing, temp.path(), temp.path(), &BTreeMap::new())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_template() {
+        let vars = vars(&[("EMAIL", "me@example.com"), ("HOSTNAME", "kepler")]);
+
+        assert_eq!(
+            render_template("email = ${EMAIL}\n# ${HOSTNAME}\n", &vars).unwrap(),
+            "email = me@example.com\n# kepler\n"
+        );
+        // Escapes and shell syntax that isn't a placeholder pass through
+        assert_eq!(
+            render_template("PATH=\"$${HOME}/bin:$PATH\" n=${#arr[@]} ${1}", &vars).unwrap(),
+            "PATH=\"${HOME}/bin:$PATH\" n=${#arr[@]} ${1}"
+        );
+        assert_eq!(
+            render_template("${NAME} ${EMAIL} ${BOX} ${NAME}", &vars).unwrap_err(),
+            vec!["BOX", "NAME"]
+        );
+    }
+
+    #[test]
+    fn test_deployed_path_strips_template_suffix() {
+        assert_eq!(
+            deployed_path(Path::new(".config/git/config.tmpl")),
+            PathBuf::from(".config/git/config")
+        );
+        assert_eq!(
+            deployed_path(Path::new(".bashrc")),
+            PathBuf::from(".bashrc")
+        );
+        assert!(!is_template(Path::new(".tmplrc")));
+    }
+
+    #[test]
+    fn test_skel_file_statuses_compare_rendered_templates() {
+        let temp = TempDir::new().unwrap();
+        let (skel, home) = (temp.path().join("skel"), temp.path().join("home"));
+
+        write(&skel, ".gitconfig.tmpl", b"[user]\nemail = ${EMAIL}\n");
+        write(&home, ".gitconfig", b"[user]\nemail = me@example.com\n");
+
+        let statuses =
+            skel_file_statuses(&skel, &home, &home, &vars(&[("EMAIL", "me@example.com")])).unwrap();
+        assert_eq!(statuses[0].file, ".gitconfig");
+        assert_eq!(statuses[0].source, ".gitconfig.tmpl");
+        assert_eq!(statuses[0].home, CopyState::InSync);
+
+        let statuses = skel_file_statuses(
+            &skel,
+            &home,
+            &home,
+            &vars(&[("EMAIL", "other@example.com")]),
+        )
+        .unwrap();
+        assert_eq!(statuses[0].home, CopyState::Modified);
+
+        let err = skel_file_statuses(&skel, &home, &home, &BTreeMap::new()).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("skel/.gitconfig.tmpl"), "{message}");
+        assert!(message.contains("${EMAIL}"), "{message}");
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(b"plain text\n"));
+        assert!(!is_binary("unicode ✓\n".as_bytes()));
+        assert!(is_binary(b"\x7fELF\x02\x01\x00"));
+        assert!(is_binary(&[0xff, 0xfe, 0xfd]));
+    }
+
+    #[test]
+    fn test_print_semantic_diff_skips_empty_diffs() {
+        let unchanged = compute_semantic_diff(
+            ConfigFileType::Ini,
+            Some("[main]\nkey=1\n"),
+            Some("# comment\n[main]\nkey=1\n"),
+        );
+        assert!(!print_semantic_diff(&unchanged));
+
+        let changed = compute_semantic_diff(
+            ConfigFileType::Ini,
+            Some("[main]\nkey=1\n"),
+            Some("[main]\nkey=2\n"),
+        );
+        assert!(print_semantic_diff(&changed));
+    }
+}
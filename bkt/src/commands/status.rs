@@ -14,35 +14,39 @@ use crate::manifest::{
     FlatpakAppsManifest, GSettingsManifest, GnomeExtensionsManifest, ShimsManifest,
     changelog::ChangelogManager,
 };
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use crate::repo::find_repo_path;
-use anyhow::Result;
-use clap::{Args, ValueEnum};
+use crate::subsystem::{Subsystem, SubsystemContext, SubsystemRegistry};
+use anyhow::{Result, bail};
+use clap::Args;
 use owo_colors::OwoColorize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 use super::flatpak::get_installed_flatpaks;
 
-#[derive(Debug, Clone, Copy, ValueEnum, Default)]
-pub enum OutputFormat {
-    /// Human-readable table output
-    #[default]
-    Table,
-    /// JSON output for scripting
-    Json,
-}
-
 #[derive(Debug, Args)]
 pub struct StatusArgs {
     /// Output format
     #[arg(short, long, value_enum, default_value = "table")]
     format: OutputFormat,
 
-    /// Show verbose output with more details
-    #[arg(short, long)]
-    verbose: bool,
+    /// Emit JSON (shorthand for `--format json`)
+    #[arg(long)]
+    json: bool,
+
+    /// Only show the subsystem dashboard row for this subsystem
+    #[arg(long, value_name = "ID")]
+    subsystem: Option<String>,
+
+    /// Exit non-zero if any subsystem has pending or drifted entries
+    #[arg(long)]
+    check: bool,
+
+    /// Include GNOME extension settings in drift (can be noisy)
+    #[arg(long)]
+    settings: bool,
 
     /// Skip OS status (faster, useful in toolbox)
     #[arg(long)]
@@ -65,10 +69,65 @@ pub struct StatusReport {
     /// Changelog status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub changelog: Option<ChangelogStatus>,
+    /// Per-subsystem status from the subsystem registry
+    pub subsystems: Vec<SubsystemRow>,
     /// Suggested next actions
     pub next_actions: Vec<NextAction>,
 }
 
+/// One row of the subsystem dashboard.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SubsystemRow {
+    pub id: String,
+    pub name: String,
+    /// Counts from `Subsystem::status`, if the subsystem reports them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub untracked: Option<usize>,
+    /// In-sync entries that need attention, e.g. apps on end-of-life runtimes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<usize>,
+    /// Whether `Subsystem::drift` found drift, if the subsystem supports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<bool>,
+    /// Why the subsystem could not be probed (the row is "unavailable")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SubsystemRow {
+    fn unavailable(subsystem: &dyn Subsystem, error: &anyhow::Error) -> Self {
+        Self {
+            id: subsystem.id().to_string(),
+            name: subsystem.name().to_string(),
+            total: None,
+            synced: None,
+            pending: None,
+            untracked: None,
+            warnings: None,
+            drift: None,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+
+    /// Whether the subsystem could not be probed.
+    pub fn is_unavailable(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Whether the subsystem has pending, drifted or warned-about entries.
+    pub fn needs_attention(&self) -> bool {
+        self.pending.unwrap_or(0) > 0
+            || self.warnings.unwrap_or(0) > 0
+            || self.drift.unwrap_or(false)
+    }
+}
+
 /// Changelog status information
 #[derive(Debug, serde::Serialize)]
 pub struct ChangelogStatus {
@@ -139,6 +198,8 @@ pub struct GSettingStatus {
     /// Settings whose current system value differs from the manifest value
     /// (need to be synced back to match manifest, not captured)
     drifted: usize,
+    /// Settings the image enforces with a dconf lock
+    locked: usize,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -178,6 +239,78 @@ pub struct NextAction {
     pub priority: u8,
 }
 
+/// Build dashboard rows for every subsystem that supports status or drift.
+///
+/// A subsystem whose probe fails gets an "unavailable" row rather than
+/// aborting the whole report.
+fn subsystem_rows(subsystems: &[&dyn Subsystem], ctx: &SubsystemContext) -> Vec<SubsystemRow> {
+    let mut rows = Vec::new();
+
+    for subsystem in subsystems {
+        let status = match subsystem.status(ctx) {
+            Ok(status) => status,
+            Err(e) => {
+                debug!("{} status failed: {:#}", subsystem.id(), e);
+                rows.push(SubsystemRow::unavailable(*subsystem, &e));
+                continue;
+            }
+        };
+
+        let drift = if subsystem.supports_drift() {
+            match subsystem.drift(ctx) {
+                Ok(report) => report.map(|r| r.has_drift()),
+                Err(e) => {
+                    debug!("{} drift failed: {:#}", subsystem.id(), e);
+                    rows.push(SubsystemRow::unavailable(*subsystem, &e));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        if status.is_none() && drift.is_none() {
+            continue;
+        }
+
+        rows.push(SubsystemRow {
+            id: subsystem.id().to_string(),
+            name: subsystem.name().to_string(),
+            total: status.as_ref().map(|s| s.total()),
+            synced: status.as_ref().map(|s| s.synced()),
+            pending: status.as_ref().map(|s| s.pending()),
+            untracked: status.as_ref().map(|s| s.untracked()),
+            warnings: status.as_ref().map(|s| s.warnings()),
+            drift,
+            error: None,
+        });
+    }
+
+    rows
+}
+
+/// Gather dashboard rows from the builtin registry, optionally for one subsystem.
+fn gather_subsystem_rows(only: Option<&str>, settings: bool) -> Result<Vec<SubsystemRow>> {
+    let registry = SubsystemRegistry::builtin();
+    let subsystems = match only {
+        Some(id) => match registry.find(id) {
+            Some(subsystem) => vec![subsystem],
+            None => {
+                let ids: Vec<_> = registry.all().iter().map(|s| s.id()).collect();
+                bail!("Unknown subsystem '{}'. Valid: {}", id, ids.join(", "));
+            }
+        },
+        None => registry.by_phase(),
+    };
+
+    let ctx = find_repo_path()
+        .map(SubsystemContext::with_repo_root)
+        .unwrap_or_else(|_| SubsystemContext::new())
+        .with_extension_settings(settings);
+
+    Ok(subsystem_rows(&subsystems, &ctx))
+}
+
 /// Get OS status from rpm-ostree
 fn get_os_status() -> Option<OsStatus> {
     let output = run_command("rpm-ostree", &["status", "--json"]).ok()?;
@@ -269,13 +402,22 @@ fn list_skel_files(skel: &PathBuf) -> Vec<String> {
     files
 }
 
-fn skel_differs(skel_path: &PathBuf, home_path: &PathBuf) -> bool {
+fn skel_differs(
+    skel: &Path,
+    file: &Path,
+    home: &Path,
+    vars: &std::collections::BTreeMap<String, String>,
+) -> bool {
+    let home_path = home.join(crate::commands::skel::deployed_path(file));
     if !home_path.exists() {
         return true; // Missing in home = differs
     }
 
-    let skel_content = fs::read_to_string(skel_path).unwrap_or_default();
-    let home_content = fs::read_to_string(home_path).unwrap_or_default();
+    // A template that can't be rendered can't be in sync
+    let Ok(skel_content) = crate::commands::skel::deployed_content(skel, file, vars) else {
+        return true;
+    };
+    let home_content = fs::read(&home_path).unwrap_or_default();
     skel_content != home_content
 }
 
@@ -295,6 +437,30 @@ fn get_gsetting(schema: &str, key: &str) -> Option<String> {
 pub fn run(args: StatusArgs) -> Result<()> {
     debug!("Gathering status information");
 
+    let format = if args.json {
+        OutputFormat::Json
+    } else {
+        args.format
+    };
+
+    // A single subsystem only gets its dashboard row
+    if let Some(ref id) = args.subsystem {
+        let rows = gather_subsystem_rows(Some(id), args.settings)?;
+        match format {
+            OutputFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({ "subsystems": rows }))?
+                );
+            }
+            OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html => {
+                print_subsystem_table(&rows)
+            }
+        }
+        exit_if_needs_attention(args.check, &rows);
+        return Ok(());
+    }
+
     // Gather OS status (unless skipped)
     let os_status = if args.skip_os { None } else { get_os_status() };
 
@@ -304,7 +470,7 @@ pub fn run(args: StatusArgs) -> Result<()> {
 
     // Gather flatpak status
     let flatpak_status = {
-        let merged = FlatpakAppsManifest::load_repo().unwrap_or_default();
+        let merged = FlatpakAppsManifest::load_effective().unwrap_or_default();
 
         let manifest_ids: std::collections::HashSet<_> =
             merged.apps.iter().map(|a| a.id.as_str()).collect();
@@ -337,7 +503,7 @@ pub fn run(args: StatusArgs) -> Result<()> {
 
     // Gather extension status
     let extension_status = {
-        let merged = GnomeExtensionsManifest::load_repo().unwrap_or_default();
+        let merged = GnomeExtensionsManifest::load_effective().unwrap_or_default();
 
         let manifest_uuids: std::collections::HashSet<_> =
             merged.extensions.iter().map(|s| s.id()).collect();
@@ -395,14 +561,19 @@ pub fn run(args: StatusArgs) -> Result<()> {
 
     // Gather gsettings status
     let gsetting_status = {
-        let merged = GSettingsManifest::load_repo().unwrap_or_default();
+        let merged = GSettingsManifest::load_effective().unwrap_or_default();
 
         let total = merged.settings.len();
         let mut applied = 0;
         let mut drifted = 0;
+        let mut locked = 0;
 
         for s in &merged.settings {
-            match get_gsetting(&s.schema, &s.key) {
+            if s.locked {
+                locked += 1;
+                continue;
+            }
+            match get_gsetting(&s.schema_spec(), &s.key) {
                 Some(current) if current == s.value => applied += 1,
                 Some(_) => drifted += 1, // Value differs from manifest
                 None => drifted += 1,    // Schema/key missing = needs sync
@@ -413,6 +584,7 @@ pub fn run(args: StatusArgs) -> Result<()> {
             total,
             applied,
             drifted,
+            locked,
         }
     };
 
@@ -435,12 +607,17 @@ pub fn run(args: StatusArgs) -> Result<()> {
     let skel_status = {
         if let Some(skel) = skel_dir() {
             let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+            let vars = crate::commands::skel::template_vars().unwrap_or_default();
             let files = list_skel_files(&skel);
             let total = files.len();
             let differing_files: Vec<String> = files
                 .iter()
-                .filter(|f| skel_differs(&skel.join(f), &home.join(f)))
-                .cloned()
+                .filter(|f| skel_differs(&skel, Path::new(f), &home, &vars))
+                .map(|f| {
+                    crate::commands::skel::deployed_path(Path::new(f))
+                        .display()
+                        .to_string()
+                })
                 .collect();
             let differs = differing_files.len();
 
@@ -560,26 +737,80 @@ pub fn run(args: StatusArgs) -> Result<()> {
     // Sort by priority
     next_actions.sort_by_key(|a| a.priority);
 
+    let subsystems = gather_subsystem_rows(None, args.settings)?;
+
     let report = StatusReport {
         os: os_status,
         manifests: manifest_status,
         drift: drift_status,
         changelog: changelog_status,
+        subsystems,
         next_actions,
     };
 
-    match args.format {
+    match format {
         OutputFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&report)?);
         }
-        OutputFormat::Table => {
-            print_table_output(&report, args.verbose);
+        OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html => {
+            print_table_output(&report, Output::is_verbose());
         }
     }
 
+    exit_if_needs_attention(args.check, &report.subsystems);
+
     Ok(())
 }
 
+/// With `--check`, exit non-zero when any subsystem has pending or drifted entries.
+fn exit_if_needs_attention(check: bool, rows: &[SubsystemRow]) {
+    if check && rows.iter().any(SubsystemRow::needs_attention) {
+        std::process::exit(1);
+    }
+}
+
+/// Print the per-subsystem dashboard table.
+fn print_subsystem_table(rows: &[SubsystemRow]) {
+    fn count(value: Option<usize>) -> String {
+        value.map_or_else(|| "-".to_string(), |v| v.to_string())
+    }
+
+    println!("{}", "  Subsystems".bold());
+    println!(
+        "    {:<20} {:>6} {:>7} {:>8} {:>10} {:>9}  {}",
+        "SUBSYSTEM".dimmed(),
+        "TOTAL".dimmed(),
+        "SYNCED".dimmed(),
+        "PENDING".dimmed(),
+        "UNTRACKED".dimmed(),
+        "WARNINGS".dimmed(),
+        "DRIFT".dimmed()
+    );
+
+    for row in rows {
+        if row.is_unavailable() {
+            println!("    {:<20} {}", row.id, "unavailable".dimmed());
+            continue;
+        }
+
+        let drift = match row.drift {
+            Some(true) => "yes".yellow().to_string(),
+            Some(false) => "no".green().to_string(),
+            None => "-".dimmed().to_string(),
+        };
+        println!(
+            "    {:<20} {:>6} {:>7} {:>8} {:>10} {:>9}  {}",
+            row.id,
+            count(row.total),
+            count(row.synced),
+            count(row.pending),
+            count(row.untracked),
+            count(row.warnings),
+            drift
+        );
+    }
+}
+
 fn print_table_output(report: &StatusReport, verbose: bool) {
     Output::header("bkt status");
     Output::blank();
@@ -711,7 +942,9 @@ fn print_table_output(report: &StatusReport, verbose: bool) {
     );
 
     // GSettings
-    let gs_pending = report.manifests.gsettings.total - report.manifests.gsettings.applied;
+    let gs_pending = report.manifests.gsettings.total
+        - report.manifests.gsettings.applied
+        - report.manifests.gsettings.locked;
     let gs_info = if gs_pending > 0 {
         format!(
             "{} settings ({} to apply)",
@@ -733,11 +966,20 @@ fn print_table_output(report: &StatusReport, verbose: bool) {
     } else {
         String::new()
     };
+    let locked_gs = if report.manifests.gsettings.locked > 0 {
+        format!(
+            " | {} locked/enforced",
+            report.manifests.gsettings.locked.to_string().cyan()
+        )
+    } else {
+        String::new()
+    };
     println!(
-        "    {:<12} {}{}",
+        "    {:<12} {}{}{}",
         "GSettings:".dimmed(),
         gs_info,
-        drifted_gs
+        drifted_gs,
+        locked_gs
     );
 
     // Shims
@@ -781,6 +1023,11 @@ fn print_table_output(report: &StatusReport, verbose: bool) {
         println!("      Run {} to see changes", "bkt skel diff".cyan());
     }
 
+    if !report.subsystems.is_empty() {
+        Output::blank();
+        print_subsystem_table(&report.subsystems);
+    }
+
     // Drift Detection Section - show untracked items that need capture
     if report.drift.pending_capture > 0 {
         Output::blank();
@@ -996,6 +1243,7 @@ mod tests {
                 total: 20,
                 applied: 18,
                 drifted: 2,
+                locked: 0,
             },
             shims: ShimStatus {
                 total: 3,
@@ -1049,6 +1297,7 @@ mod tests {
                     total: 0,
                     applied: 0,
                     drifted: 0,
+                    locked: 0,
                 },
                 shims: ShimStatus {
                     total: 0,
@@ -1066,6 +1315,7 @@ mod tests {
                 pending_capture: 0,
             },
             changelog: None,
+            subsystems: vec![],
             next_actions: vec![],
         };
 
@@ -1126,6 +1376,7 @@ mod tests {
                     total: 0,
                     applied: 0,
                     drifted: 0,
+                    locked: 0,
                 },
                 shims: ShimStatus {
                     total: 0,
@@ -1146,6 +1397,7 @@ mod tests {
                 pending_count: 2,
                 has_drafts: false,
             }),
+            subsystems: vec![],
             next_actions: vec![],
         };
 
@@ -1153,4 +1405,171 @@ mod tests {
         assert!(json.contains("\"changelog\""));
         assert!(json.contains("\"pending_count\": 2"));
     }
+
+    mod dashboard {
+        use super::*;
+        use crate::plan::{DynPlan, PlanContext};
+        use crate::subsystem::DriftReport;
+        use crate::subsystem::{Manifest, SubsystemConfig, SubsystemStatus, SubsystemTier};
+
+        #[derive(Debug)]
+        struct Counts(usize, usize, usize, usize);
+
+        impl SubsystemStatus for Counts {
+            fn total(&self) -> usize {
+                self.0
+            }
+            fn synced(&self) -> usize {
+                self.1
+            }
+            fn pending(&self) -> usize {
+                self.2
+            }
+            fn untracked(&self) -> usize {
+                self.3
+            }
+        }
+
+        /// All synced, with some entries warned about.
+        #[derive(Debug)]
+        struct Warned(usize);
+
+        impl SubsystemStatus for Warned {
+            fn total(&self) -> usize {
+                self.0
+            }
+            fn synced(&self) -> usize {
+                self.0
+            }
+            fn pending(&self) -> usize {
+                0
+            }
+            fn untracked(&self) -> usize {
+                0
+            }
+            fn warnings(&self) -> usize {
+                self.0
+            }
+        }
+
+        enum Probe {
+            Counts(usize, usize, usize, usize),
+            Warnings(usize),
+            Drift(bool),
+            Fails,
+            Nothing,
+        }
+
+        struct Fake(&'static str, Probe);
+
+        impl Subsystem for Fake {
+            fn name(&self) -> &'static str {
+                self.0
+            }
+            fn id(&self) -> &'static str {
+                self.0
+            }
+            fn tier(&self) -> SubsystemTier {
+                SubsystemTier::Convergent
+            }
+            fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+                bail!("not used")
+            }
+            fn capture(&self, _ctx: &PlanContext) -> Result<Option<Box<dyn DynPlan>>> {
+                Ok(None)
+            }
+            fn sync(
+                &self,
+                _ctx: &PlanContext,
+                _config: &SubsystemConfig,
+            ) -> Result<Option<Box<dyn DynPlan>>> {
+                Ok(None)
+            }
+            fn status(&self, _ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
+                match self.1 {
+                    Probe::Counts(t, s, p, u) => Ok(Some(Box::new(Counts(t, s, p, u)))),
+                    Probe::Warnings(w) => Ok(Some(Box::new(Warned(w)))),
+                    Probe::Fails => bail!("probe failed"),
+                    _ => Ok(None),
+                }
+            }
+            fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+                match self.1 {
+                    Probe::Drift(drifted) => Ok(Some(DriftReport {
+                        extra: if drifted {
+                            vec!["x".to_string()]
+                        } else {
+                            vec![]
+                        },
+                        ..Default::default()
+                    })),
+                    _ => Ok(None),
+                }
+            }
+            fn supports_drift(&self) -> bool {
+                matches!(self.1, Probe::Drift(_))
+            }
+        }
+
+        fn rows(fakes: &[Fake]) -> Vec<SubsystemRow> {
+            let subsystems: Vec<&dyn Subsystem> =
+                fakes.iter().map(|f| f as &dyn Subsystem).collect();
+            subsystem_rows(&subsystems, &SubsystemContext::new())
+        }
+
+        #[test]
+        fn test_rows_skip_subsystems_without_status_or_drift() {
+            let rows = rows(&[
+                Fake("counted", Probe::Counts(3, 2, 1, 0)),
+                Fake("silent", Probe::Nothing),
+                Fake("drifty", Probe::Drift(false)),
+            ]);
+
+            let ids: Vec<_> = rows.iter().map(|r| r.id.as_str()).collect();
+            assert_eq!(ids, vec!["counted", "drifty"]);
+            assert_eq!(rows[0].pending, Some(1));
+            assert_eq!(rows[0].drift, None);
+            assert_eq!(rows[1].total, None);
+            assert_eq!(rows[1].drift, Some(false));
+        }
+
+        #[test]
+        fn test_failed_probe_degrades_to_unavailable_row() {
+            let rows = rows(&[
+                Fake("broken", Probe::Fails),
+                Fake("fine", Probe::Counts(1, 1, 0, 0)),
+            ]);
+
+            assert_eq!(rows.len(), 2);
+            assert!(rows[0].is_unavailable());
+            assert!(rows[0].error.as_deref().unwrap().contains("probe failed"));
+            assert!(!rows[0].needs_attention());
+            assert!(!rows[1].is_unavailable());
+        }
+
+        #[test]
+        fn test_needs_attention_on_pending_warnings_or_drift() {
+            let rows = rows(&[
+                Fake("clean", Probe::Counts(2, 2, 0, 1)),
+                Fake("pending", Probe::Counts(2, 1, 1, 0)),
+                Fake("warned", Probe::Warnings(1)),
+                Fake("drifted", Probe::Drift(true)),
+            ]);
+
+            assert_eq!(rows[0].warnings, Some(0));
+            assert_eq!(rows[2].warnings, Some(1));
+            let flagged: Vec<_> = rows.iter().map(SubsystemRow::needs_attention).collect();
+            assert_eq!(flagged, vec![false, true, true, true]);
+        }
+
+        #[test]
+        fn test_row_serialization_omits_missing_fields() {
+            let rows = rows(&[Fake("drifty", Probe::Drift(true))]);
+            let json = serde_json::to_string(&rows[0]).unwrap();
+            assert!(json.contains("\"id\":\"drifty\""));
+            assert!(json.contains("\"drift\":true"));
+            assert!(!json.contains("\"total\""));
+            assert!(!json.contains("\"error\""));
+        }
+    }
 }
@@ -8,8 +8,12 @@
 //!
 //! - `add` — Add to recipe (deferred, appears after image rebuild)
 //! - `remove` — Remove from recipe (deferred)
+//! - `swap` — Replace one package with another in a single change
 //! - `list` — Show what's in the manifest
+//! - `pin` / `unpin` — Hold a package at a specific version in the image
+//! - `group add` / `group remove` — Add or remove a package group (`@group`)
 //! - `capture` — Capture rpm-ostree layered packages to manifest
+//! - `history` — When packages entered or left the manifest, per commit
 //!
 //! # Examples
 //!
@@ -17,25 +21,41 @@
 //! # Add a package to the image (creates PR)
 //! bkt system add virt-manager
 //!
+//! # Replace a package with a variant (one PR; `--now` also swaps locally)
+//! bkt system swap mesa-va-drivers mesa-va-drivers-freeworld --now
+//!
+//! # Hold a package at a known-good version
+//! bkt system pin code 1.95.0
+//!
 //! # Capture layered packages to manifest
 //! bkt system capture --apply
+//!
+//! # Find the commit that added a package
+//! bkt system history mesa-va-drivers-freeworld
 //! ```
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::capture::CapturePlan;
 use crate::containerfile::{
     ContainerfileEditor, Section, generate_copr_repos, generate_system_packages,
+    is_placeholder_content,
 };
-use crate::context::CommandDomain;
-use crate::manifest::{CoprRepo, SystemPackagesManifest};
-use crate::output::Output;
+use crate::context::{CommandDomain, ExecutionContext, HostExec};
+use crate::manifest::{CoprRepo, SystemPackagesManifest, group_spec};
+use crate::output::{Output, OutputFormat};
 use crate::pipeline::ExecutionPlan;
 use crate::plan::{
     ExecuteContext, ExecutionReport, Operation, Plan, PlanContext, PlanSummary, Plannable, Verb,
+    print_report, print_summary,
 };
+use crate::repo::find_repo_path;
+use crate::repo::git::{CommitInfo, get_file_at_commit, log_path};
 use crate::validation::validate_dnf_package;
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Args)]
 pub struct SystemArgs {
@@ -52,7 +72,7 @@ pub enum SystemAction {
     Add {
         /// Package names to add
         packages: Vec<String>,
-        /// Skip package validation
+        /// Skip package validation and version pin checks
         #[arg(long)]
         force: bool,
     },
@@ -61,11 +81,42 @@ pub enum SystemAction {
         /// Package names to remove
         packages: Vec<String>,
     },
+    /// Replace one package with another
+    ///
+    /// Removes `remove` and adds `install` in the manifest as a single
+    /// change (and a single PR).
+    Swap {
+        /// Package to replace
+        remove: String,
+        /// Package to install in its place
+        install: String,
+        /// Also swap on this machine (rpm-ostree on the host, dnf in dev)
+        #[arg(long)]
+        now: bool,
+        /// Skip package validation and version pin checks
+        #[arg(long)]
+        force: bool,
+    },
     /// List managed packages from manifest
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Pin a package to a specific version
+    ///
+    /// The image installs `name-version` instead of the latest package.
+    Pin {
+        /// Package name
+        package: String,
+        /// Version to pin (e.g., 1.95.0 or 1.95.0-1.fc42)
+        #[arg(id = "pin_version", value_name = "VERSION")]
+        version: String,
+    },
+    /// Remove a package version pin
+    Unpin {
+        /// Package name
+        package: String,
     },
     /// Capture layered packages not in manifest
     ///
@@ -75,20 +126,42 @@ pub enum SystemAction {
         /// Apply immediately (add packages to manifest)
         #[arg(long)]
         apply: bool,
+        /// Record each captured package's installed version in `versions`
+        #[arg(long)]
+        with_versions: bool,
     },
     /// Manage COPR repositories
     Copr {
         #[command(subcommand)]
         action: CoprAction,
     },
+    /// Manage package groups (installed as `@group` in the image)
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
     /// Show what will change on next reboot
     ///
     /// Compares the staged bootc deployment against the running system
     /// and shows package upgrades, new binaries, and other changes.
     Staged {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Show when packages were added to or removed from the manifest
+    ///
+    /// Walks the git history of manifests/system-packages.json and lists
+    /// each commit that changed the package set.
+    History {
+        /// Only show the commits where this package (or @group) came or went
+        package: Option<String>,
+        /// Show at most this many commits
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Print the timeline as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -108,30 +181,58 @@ pub enum CoprAction {
     List,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum GroupAction {
+    /// Add package groups to the system image
+    Add {
+        /// Group IDs (e.g., development-tools or @development-tools)
+        #[arg(required = true)]
+        groups: Vec<String>,
+    },
+    /// Remove package groups from the system image
+    Remove {
+        /// Group IDs
+        #[arg(required = true)]
+        groups: Vec<String>,
+    },
+}
+
 pub fn run(args: SystemArgs, plan: &ExecutionPlan) -> Result<()> {
     let runner = plan.runner();
 
     match args.action {
         SystemAction::Add { packages, force } => handle_add(packages, force, plan, runner),
         SystemAction::Remove { packages } => handle_remove(packages, plan),
+        SystemAction::Swap {
+            remove,
+            install,
+            now,
+            force,
+        } => handle_swap(remove, install, now, force, plan, runner),
         SystemAction::List { format } => handle_list(format, runner),
-        SystemAction::Capture { apply } => {
+        SystemAction::Pin { package, version } => handle_pin(package, version, plan),
+        SystemAction::Unpin { package } => handle_unpin(package, plan),
+        SystemAction::Capture {
+            apply,
+            with_versions,
+        } => {
             // Use the Plan-based implementation
             let plan_ctx =
                 PlanContext::new(std::env::current_dir().unwrap_or_default(), plan.clone());
 
-            let capture_plan = SystemCaptureCommand.plan(&plan_ctx)?;
+            let capture_plan = SystemCaptureCommand { with_versions }.plan(&plan_ctx)?;
 
-            if capture_plan.is_empty() {
+            if capture_plan.is_empty() && !plan.json_output() {
                 Output::success("All layered packages are already in the manifest.");
                 return Ok(());
             }
 
             // Always show the plan
-            print!("{}", capture_plan.describe());
+            let summary = capture_plan.describe().with_subsystem("system");
+            print_summary(&summary, plan, plan.dry_run || !apply)?;
 
             if plan.dry_run || !apply {
-                if !apply {
+                if !apply && !plan.json_output() {
                     Output::hint("Use --apply to execute this plan.");
                 }
                 return Ok(());
@@ -140,12 +241,18 @@ pub fn run(args: SystemArgs, plan: &ExecutionPlan) -> Result<()> {
             // Execute the plan
             let mut exec_ctx = ExecuteContext::new(plan.clone());
             let report = capture_plan.execute(&mut exec_ctx)?;
-            print!("{}", report);
+            print_report(&report.with_subsystem("system"), plan)?;
 
             Ok(())
         }
         SystemAction::Copr { action } => handle_copr(action, plan),
+        SystemAction::Group { action } => handle_group(action, plan),
         SystemAction::Staged { format } => handle_staged(format, runner),
+        SystemAction::History {
+            package,
+            limit,
+            json,
+        } => handle_history(package.as_deref(), limit, json, runner),
     }
 }
 
@@ -165,14 +272,28 @@ fn handle_add(
         bail!("No packages specified");
     }
 
-    // Validate that packages exist in repositories
+    let mut manifest = SystemPackagesManifest::load_repo()?;
+
     if !force {
+        // Refuse to request a different version of a pinned package
         for pkg in &packages {
-            validate_dnf_package(runner, pkg)?;
+            if let Some((name, pinned)) = manifest.conflicting_pin(pkg) {
+                bail!(
+                    "{} is pinned to version {} (use --force or `bkt system unpin {}`)",
+                    name,
+                    pinned,
+                    name
+                );
+            }
         }
-    }
 
-    let mut manifest = SystemPackagesManifest::load_repo()?;
+        // Validate that packages exist in repositories
+        if !plan.skip_offline("package validation") {
+            for pkg in &packages {
+                validate_dnf_package(runner, pkg)?;
+            }
+        }
+    }
 
     // Track which packages are new
     let mut new_packages = Vec::new();
@@ -307,14 +428,128 @@ fn handle_remove(packages: Vec<String>, plan: &ExecutionPlan) -> Result<()> {
     Ok(())
 }
 
+// =============================================================================
+// Swap Command
+// =============================================================================
+
+fn handle_swap(
+    remove: String,
+    install: String,
+    now: bool,
+    force: bool,
+    plan: &ExecutionPlan,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    // Context-dependent like dnf: rpm-ostree on the host, dnf in the toolbox
+    plan.validate_domain(CommandDomain::Dnf)?;
+
+    if remove == install {
+        bail!("Cannot swap {} for itself", remove);
+    }
+
+    // Everything is checked before the manifest or the system is touched
+    let mut manifest = SystemPackagesManifest::load_repo()?;
+    let in_manifest = manifest.find_package(&remove);
+    if !in_manifest && !is_package_installed(&remove, runner) {
+        bail!("{} is not in the manifest or installed", remove);
+    }
+
+    if !force {
+        if let Some((name, pinned)) = manifest.conflicting_pin(&install) {
+            bail!(
+                "{} is pinned to version {} (use --force or `bkt system unpin {}`)",
+                name,
+                pinned,
+                name
+            );
+        }
+        if !plan.skip_offline("package validation") {
+            validate_dnf_package(runner, &install)?;
+        }
+    }
+
+    let swap = format!("{} → {}", remove, install);
+
+    if plan.dry_run {
+        if in_manifest {
+            Output::dry_run(format!("Would remove from manifest: {}", remove));
+        } else {
+            Output::dry_run(format!("Not in manifest, nothing to remove: {}", remove));
+        }
+        Output::dry_run(format!("Would add to manifest: {}", install));
+        if let (true, Some((program, args))) = (now, swap_command(plan.context, &remove, &install))
+        {
+            Output::dry_run(format!("Would run: {} {}", program, args.join(" ")));
+        }
+        return Ok(());
+    }
+
+    // Swap locally first so a failed transaction leaves the manifest alone
+    if now && plan.should_execute_locally() {
+        let Some((program, args)) = swap_command(plan.context, &remove, &install) else {
+            bail!("--now has no effect in the image context");
+        };
+        Output::running(format!("{} {}", program, args.join(" ")));
+        let status = runner
+            .run_status(program, &args, &CommandOptions::default())
+            .with_context(|| format!("Failed to run {}", program))?;
+        if !status.success() {
+            bail!("{} failed; manifest left unchanged", program);
+        }
+    }
+
+    if plan.should_update_manifest() {
+        manifest.swap_package(&remove, install.clone());
+        save_repo_manifest(&manifest)?;
+        sync_all_containerfile_sections(&manifest)?;
+        Output::success(format!("Swapped in manifest: {}", swap));
+    }
+
+    if plan.should_create_pr() {
+        let mut repo_manifest = SystemPackagesManifest::load_repo()?;
+        repo_manifest.swap_package(&remove, install.clone());
+
+        // Sync Containerfile before creating PR so both files are committed together
+        sync_all_containerfile_sections(&repo_manifest)?;
+
+        let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+        plan.maybe_create_pr(
+            "system",
+            "swap",
+            &swap,
+            "system-packages.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The command that swaps `remove` for `install` in one transaction, or
+/// `None` in the image context, which never touches the running system.
+fn swap_command<'a>(
+    context: ExecutionContext,
+    remove: &'a str,
+    install: &'a str,
+) -> Option<(&'static str, Vec<&'a str>)> {
+    match context {
+        ExecutionContext::Host => Some((
+            "rpm-ostree",
+            vec!["override", "remove", remove, "--install", install],
+        )),
+        ExecutionContext::Dev => Some(("dnf", vec!["swap", "-y", remove, install])),
+        ExecutionContext::Image => None,
+    }
+}
+
 // =============================================================================
 // List Command
 // =============================================================================
 
-fn handle_list(format: String, runner: &dyn CommandRunner) -> Result<()> {
+fn handle_list(format: OutputFormat, runner: &dyn CommandRunner) -> Result<()> {
     let manifest = SystemPackagesManifest::load_repo()?;
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&manifest)?);
         return Ok(());
     }
@@ -329,16 +564,17 @@ fn handle_list(format: String, runner: &dyn CommandRunner) -> Result<()> {
     // List packages
     if !manifest.packages.is_empty() {
         Output::subheader("PACKAGES:");
-        println!("{:<40} SOURCE  INSTALLED", "NAME".cyan());
+        println!("{:<40} {:<16} SOURCE  INSTALLED", "NAME".cyan(), "PIN");
         Output::separator();
         for pkg in &manifest.packages {
+            let pin = manifest.pinned_version(pkg).unwrap_or("-");
             let source = "manifest".dimmed().to_string();
             let installed = if is_package_installed(pkg, runner) {
                 "✓".green().to_string()
             } else {
                 "✗".red().to_string()
             };
-            println!("{:<40} {:<7} {}", pkg, source, installed);
+            println!("{:<40} {:<16} {:<7} {}", pkg, pin, source, installed);
         }
         Output::blank();
     }
@@ -374,8 +610,9 @@ fn handle_list(format: String, runner: &dyn CommandRunner) -> Result<()> {
     }
 
     Output::success(format!(
-        "{} packages, {} groups, {} COPR repos",
+        "{} packages ({} pinned), {} groups, {} COPR repos",
         manifest.packages.len(),
+        manifest.pins.len(),
         manifest.groups.len(),
         manifest.copr_repos.len()
     ));
@@ -383,6 +620,99 @@ fn handle_list(format: String, runner: &dyn CommandRunner) -> Result<()> {
     Ok(())
 }
 
+// =============================================================================
+// Pin Commands
+// =============================================================================
+
+fn handle_pin(package: String, version: String, plan: &ExecutionPlan) -> Result<()> {
+    plan.validate_domain(CommandDomain::System)?;
+
+    let mut manifest = SystemPackagesManifest::load_repo()?;
+
+    if manifest.pinned_version(&package) == Some(version.as_str()) {
+        Output::info(format!("Already pinned: {} = {}", package, version));
+        return Ok(());
+    }
+
+    if !manifest.find_package(&package) {
+        Output::warning(format!(
+            "{} is not in the manifest; the pin takes effect once it is added",
+            package
+        ));
+    }
+
+    // Update manifest
+    if plan.should_update_manifest() {
+        manifest.pin(package.clone(), version.clone());
+        save_repo_manifest(&manifest)?;
+        sync_all_containerfile_sections(&manifest)?;
+        Output::success(format!("Pinned {} to {}", package, version));
+    } else if plan.dry_run {
+        Output::dry_run(format!("Would pin {} to {}", package, version));
+    }
+
+    // Create PR if needed
+    if plan.should_create_pr() {
+        let mut repo_manifest = SystemPackagesManifest::load_repo()?;
+        repo_manifest.pin(package.clone(), version.clone());
+        let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+
+        // Sync Containerfile before creating PR so both files are committed together
+        sync_all_containerfile_sections(&repo_manifest)?;
+
+        plan.maybe_create_pr(
+            "system",
+            "pin",
+            &format!("{}-{}", package, version),
+            "system-packages.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn handle_unpin(package: String, plan: &ExecutionPlan) -> Result<()> {
+    plan.validate_domain(CommandDomain::System)?;
+
+    let mut manifest = SystemPackagesManifest::load_repo()?;
+
+    if manifest.pinned_version(&package).is_none() {
+        Output::warning(format!("Package not pinned: {}", package));
+        return Ok(());
+    }
+
+    // Update manifest
+    if plan.should_update_manifest() {
+        manifest.unpin(&package);
+        save_repo_manifest(&manifest)?;
+        sync_all_containerfile_sections(&manifest)?;
+        Output::success(format!("Unpinned {}", package));
+    } else if plan.dry_run {
+        Output::dry_run(format!("Would unpin {}", package));
+    }
+
+    // Create PR if needed
+    if plan.should_create_pr() {
+        let mut repo_manifest = SystemPackagesManifest::load_repo()?;
+        if repo_manifest.unpin(&package) {
+            // Sync Containerfile before creating PR so both files are committed together
+            sync_all_containerfile_sections(&repo_manifest)?;
+
+            let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+            plan.maybe_create_pr(
+                "system",
+                "unpin",
+                &package,
+                "system-packages.json",
+                &manifest_content,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
 // =============================================================================
 // COPR Commands
 // =============================================================================
@@ -512,6 +842,87 @@ fn handle_copr_list() -> Result<()> {
     Ok(())
 }
 
+// =============================================================================
+// Group Commands
+// =============================================================================
+
+fn handle_group(action: GroupAction, plan: &ExecutionPlan) -> Result<()> {
+    plan.validate_domain(CommandDomain::System)?;
+
+    let (verb, groups) = match &action {
+        GroupAction::Add { groups } => ("add", groups),
+        GroupAction::Remove { groups } => ("remove", groups),
+    };
+    let adding = matches!(action, GroupAction::Add { .. });
+
+    let mut manifest = SystemPackagesManifest::load_repo()?;
+
+    let mut changed = Vec::new();
+    for group in groups {
+        let spec = group_spec(group);
+        if manifest.find_group(&spec) == adding {
+            if adding {
+                Output::info(format!("Already in manifest: {}", spec));
+            } else {
+                Output::warning(format!("Group not found in manifest: {}", spec));
+            }
+        } else {
+            changed.push(spec);
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let apply = |manifest: &mut SystemPackagesManifest| {
+        for spec in &changed {
+            if adding {
+                manifest.add_group(spec);
+            } else {
+                manifest.remove_group(spec);
+            }
+        }
+    };
+
+    if plan.should_update_manifest() {
+        apply(&mut manifest);
+        save_repo_manifest(&manifest)?;
+        for spec in &changed {
+            if adding {
+                Output::success(format!("Added to manifest: {}", spec));
+            } else {
+                Output::success(format!("Removed from manifest: {}", spec));
+            }
+        }
+    } else if plan.dry_run {
+        for spec in &changed {
+            Output::dry_run(format!("Would {} group: {}", verb, spec));
+        }
+    }
+
+    // NOTE: No local execution! Groups are installed when the image rebuilds.
+
+    if plan.should_create_pr() {
+        let mut repo_manifest = SystemPackagesManifest::load_repo()?;
+        apply(&mut repo_manifest);
+        let manifest_content = serde_json::to_string_pretty(&repo_manifest)?;
+
+        // Sync Containerfile before creating PR so both files are committed together
+        sync_all_containerfile_sections(&repo_manifest)?;
+
+        plan.maybe_create_pr(
+            "system",
+            verb,
+            &changed.join(", "),
+            "system-packages.json",
+            &manifest_content,
+        )?;
+    }
+
+    Ok(())
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
@@ -553,30 +964,38 @@ fn sync_all_containerfile_sections(manifest: &SystemPackagesManifest) -> Result<
     };
 
     // SYSTEM_PACKAGES
-    if editor.has_section(Section::SystemPackages) {
-        let new_content = generate_system_packages(&manifest.packages, has_external_rpms);
-        editor.update_section(Section::SystemPackages, new_content);
-        Output::success("Synced Containerfile SYSTEM_PACKAGES section");
+    let new_content = generate_system_packages(
+        &manifest.packages,
+        &manifest.groups,
+        &manifest.pins,
+        &manifest.arches,
+        has_external_rpms,
+        false,
+    );
+    if editor.has_section(Section::SystemPackages) || !is_placeholder_content(&new_content) {
+        if editor.upsert_section(Section::SystemPackages, new_content) {
+            Output::success("Added Containerfile SYSTEM_PACKAGES section");
+        } else {
+            Output::success("Synced Containerfile SYSTEM_PACKAGES section");
+        }
         updated_any = true;
-    } else {
-        Output::warning("Containerfile has no SYSTEM_PACKAGES section - skipping sync");
     }
 
     // COPR_REPOS
-    if editor.has_section(Section::CoprRepos) {
-        let repo_names: Vec<String> = manifest
-            .copr_repos
-            .iter()
-            .filter(|c| c.enabled)
-            .map(|c| c.name.clone())
-            .collect();
-
-        let new_content = generate_copr_repos(&repo_names);
-        editor.update_section(Section::CoprRepos, new_content);
-        Output::success("Synced Containerfile COPR_REPOS section");
+    let repo_names: Vec<String> = manifest
+        .copr_repos
+        .iter()
+        .filter(|c| c.enabled)
+        .map(|c| c.name.clone())
+        .collect();
+    let new_content = generate_copr_repos(&repo_names, false);
+    if editor.has_section(Section::CoprRepos) || !is_placeholder_content(&new_content) {
+        if editor.upsert_section(Section::CoprRepos, new_content) {
+            Output::success("Added Containerfile COPR_REPOS section");
+        } else {
+            Output::success("Synced Containerfile COPR_REPOS section");
+        }
         updated_any = true;
-    } else if manifest.copr_repos.iter().any(|c| c.enabled) {
-        Output::warning("Containerfile has no COPR_REPOS section - skipping sync");
     }
 
     if !updated_any {
@@ -592,7 +1011,11 @@ fn sync_all_containerfile_sections(manifest: &SystemPackagesManifest) -> Result<
 // ============================================================================
 
 /// Command to capture layered packages not in manifest.
-pub struct SystemCaptureCommand;
+#[derive(Debug, Default)]
+pub struct SystemCaptureCommand {
+    /// Also record the installed version of each captured package.
+    pub with_versions: bool,
+}
 
 /// Plan for capturing layered packages.
 pub struct SystemCapturePlan {
@@ -600,6 +1023,10 @@ pub struct SystemCapturePlan {
     pub to_capture: Vec<String>,
     /// Packages already in manifest.
     pub already_in_manifest: usize,
+    /// Layered packages skipped because they're in `capture_exclude`.
+    pub excluded: Vec<String>,
+    /// Installed versions of captured packages (only with `--with-versions`).
+    pub versions: BTreeMap<String, String>,
 }
 
 impl Plannable for SystemCaptureCommand {
@@ -614,37 +1041,103 @@ impl Plannable for SystemCaptureCommand {
         // Load manifest to check what's already tracked
         let merged = SystemPackagesManifest::load_repo()?;
 
-        let mut to_capture = Vec::new();
-        let mut already_in_manifest = 0;
+        let mut plan = plan_from_layered(layered, &merged);
+        if self.with_versions && !plan.to_capture.is_empty() {
+            plan.versions = query_installed_versions(runner, &plan.to_capture);
+        }
 
-        for pkg in layered {
-            if merged.packages.contains(&pkg) {
-                already_in_manifest += 1;
-            } else {
-                to_capture.push(pkg);
-            }
+        Ok(plan)
+    }
+}
+
+/// Split layered packages into ones to capture, ones already tracked, and
+/// ones the manifest's `capture_exclude` list says to leave alone.
+fn plan_from_layered(layered: Vec<String>, manifest: &SystemPackagesManifest) -> SystemCapturePlan {
+    let mut to_capture = Vec::new();
+    let mut already_in_manifest = 0;
+    let mut excluded = Vec::new();
+
+    for pkg in layered {
+        if manifest.packages.contains(&pkg) {
+            already_in_manifest += 1;
+        } else if manifest.is_capture_excluded(&pkg) {
+            excluded.push(pkg);
+        } else {
+            to_capture.push(pkg);
         }
+    }
 
-        // Sort for consistent output
-        to_capture.sort();
+    // Sort for consistent output
+    to_capture.sort();
+    excluded.sort();
 
-        Ok(SystemCapturePlan {
-            to_capture,
-            already_in_manifest,
-        })
+    SystemCapturePlan {
+        to_capture,
+        already_in_manifest,
+        excluded,
+        versions: BTreeMap::new(),
     }
 }
 
+/// Query the installed EVR of each package with `rpm -q`.
+///
+/// Packages rpm doesn't know about are left out rather than failing the capture.
+fn query_installed_versions(
+    runner: &dyn CommandRunner,
+    packages: &[String],
+) -> BTreeMap<String, String> {
+    let mut args = vec![
+        "-q",
+        "--qf",
+        "%{NAME} %|EPOCH?{%{EPOCH}:}:{}|%{VERSION}-%{RELEASE}\\n",
+    ];
+    args.extend(packages.iter().map(String::as_str));
+
+    // rpm exits non-zero if any package is missing, but still prints the rest
+    match HostExec::new(runner).run_on_host("rpm", &args) {
+        Ok(output) => parse_rpm_versions(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// Parse `name evr` lines from `rpm -q --qf`, ignoring "not installed" noise.
+fn parse_rpm_versions(stdout: &str) -> BTreeMap<String, String> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, evr) = line.trim().split_once(' ')?;
+            (!evr.contains(' ')).then(|| (name.to_string(), evr.to_string()))
+        })
+        .collect()
+}
+
 impl Plan for SystemCapturePlan {
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!(
-            "System Capture: {} to add, {} already in manifest",
+            "System Capture: {} to add, {} already in manifest, {} excluded",
             self.to_capture.len(),
-            self.already_in_manifest
+            self.already_in_manifest,
+            self.excluded.len()
         ));
 
         for pkg in &self.to_capture {
-            summary.add_operation(Operation::new(Verb::Capture, format!("package:{}", pkg)));
+            let op = match self.versions.get(pkg) {
+                Some(version) => Operation::with_details(
+                    Verb::Capture,
+                    format!("package:{}", pkg),
+                    format!("version {}", version),
+                ),
+                None => Operation::new(Verb::Capture, format!("package:{}", pkg)),
+            };
+            summary.add_operation(op);
+        }
+
+        for pkg in &self.excluded {
+            summary.add_operation(Operation::with_details(
+                Verb::Skip,
+                format!("package:{}", pkg),
+                "in capture_exclude",
+            ));
         }
 
         summary
@@ -666,6 +1159,9 @@ impl Plan for SystemCapturePlan {
             } else {
                 report.record_success(Verb::Skip, format!("package:{} (already in manifest)", pkg));
             }
+            if let Some(version) = self.versions.get(pkg) {
+                manifest.versions.insert(pkg.clone(), version.clone());
+            }
         }
 
         // Save the updated manifest
@@ -679,13 +1175,18 @@ impl Plan for SystemCapturePlan {
     }
 }
 
+impl CapturePlan for SystemCapturePlan {
+    fn retain_targets(&mut self, keep: &dyn Fn(&str) -> bool) {
+        self.to_capture
+            .retain(|pkg| keep(&format!("package:{}", pkg)));
+        let to_capture = &self.to_capture;
+        self.versions.retain(|pkg, _| to_capture.contains(pkg));
+    }
+}
+
 /// Get layered packages from rpm-ostree status.
 fn get_layered_packages(runner: &dyn CommandRunner) -> Vec<String> {
-    let output = runner.run_output(
-        "rpm-ostree",
-        &["status", "--json"],
-        &CommandOptions::default(),
-    );
+    let output = HostExec::new(runner).run_on_host("rpm-ostree", &["status", "--json"]);
 
     let output = match output {
         Ok(o) if o.status.success() => o,
@@ -767,7 +1268,7 @@ pub struct StagedDiff {
     // Future: upstreams, wrappers, etc.
 }
 
-fn handle_staged(format: String, runner: &dyn CommandRunner) -> Result<()> {
+fn handle_staged(format: OutputFormat, runner: &dyn CommandRunner) -> Result<()> {
     let diff = compute_staged_diff(runner)?;
 
     if !diff.has_staged {
@@ -775,7 +1276,7 @@ fn handle_staged(format: String, runner: &dyn CommandRunner) -> Result<()> {
         return Ok(());
     }
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&diff)?);
         return Ok(());
     }
@@ -838,11 +1339,7 @@ fn compute_staged_diff(runner: &dyn CommandRunner) -> Result<StagedDiff> {
     let mut diff = StagedDiff::default();
 
     // Get deployment info from rpm-ostree
-    let output = runner.run_output(
-        "rpm-ostree",
-        &["status", "--json"],
-        &CommandOptions::default(),
-    )?;
+    let output = HostExec::new(runner).run_on_host("rpm-ostree", &["status", "--json"])?;
 
     if !output.status.success() {
         bail!("Failed to get rpm-ostree status");
@@ -989,3 +1486,259 @@ fn parse_rpm_list(output: &str) -> std::collections::HashMap<String, String> {
         })
         .collect()
 }
+
+// =============================================================================
+// History Command — package changes per commit of the manifest
+// =============================================================================
+
+/// Repo path of the manifest whose history `bkt system history` walks.
+const SYSTEM_PACKAGES_PATH: &str = "manifests/system-packages.json";
+
+/// A commit that changed the manifest's package set.
+#[derive(Debug, Serialize)]
+struct PackageHistoryEntry {
+    #[serde(flatten)]
+    commit: CommitInfo,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+fn handle_history(
+    package: Option<&str>,
+    limit: Option<usize>,
+    json: bool,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
+    let repo_path = find_repo_path()?;
+
+    // Filtering by package has to look further back than `limit` commits
+    let log_limit = if package.is_some() { None } else { limit };
+    let commits = log_path(&repo_path, SYSTEM_PACKAGES_PATH, log_limit, runner)?;
+
+    let mut entries = Vec::new();
+    for commit in commits {
+        let parent = format!("{}^", commit.hash);
+        let before = manifest_packages(get_file_at_commit(
+            &repo_path,
+            &parent,
+            SYSTEM_PACKAGES_PATH,
+            runner,
+        )?);
+        let after = manifest_packages(get_file_at_commit(
+            &repo_path,
+            &commit.hash,
+            SYSTEM_PACKAGES_PATH,
+            runner,
+        )?);
+
+        let Some(entry) = history_entry(commit, &before, &after, package) else {
+            continue;
+        };
+        entries.push(entry);
+        if limit.is_some_and(|limit| entries.len() >= limit) {
+            break;
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        match package {
+            Some(package) => Output::info(format!(
+                "{} was never added to {}",
+                package, SYSTEM_PACKAGES_PATH
+            )),
+            None => Output::info(format!("No package changes in {}", SYSTEM_PACKAGES_PATH)),
+        }
+        return Ok(());
+    }
+
+    match package {
+        Some(package) => Output::header(format!("History of {}", package)),
+        None => Output::header(format!("History of {}", SYSTEM_PACKAGES_PATH)),
+    }
+    for entry in &entries {
+        println!(
+            "{}  {}  {}  {}",
+            entry.commit.date,
+            (&entry.commit.hash[..8.min(entry.commit.hash.len())]).cyan(),
+            entry.commit.author,
+            entry.commit.subject.dimmed()
+        );
+        for name in &entry.added {
+            println!("    {} {}", "+".green(), name);
+        }
+        for name in &entry.removed {
+            println!("    {} {}", "-".red(), name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Packages and groups in a version of system-packages.json.
+///
+/// Read loosely, so commits from before a schema change still parse.
+fn manifest_packages(content: Option<String>) -> BTreeSet<String> {
+    let Some(value) = content.and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    else {
+        return BTreeSet::new();
+    };
+    ["packages", "groups"]
+        .iter()
+        .filter_map(|key| value.get(key)?.as_array())
+        .flatten()
+        .filter_map(|name| name.as_str().map(str::to_string))
+        .collect()
+}
+
+/// The packages `commit` added and removed, limited to `package` if given;
+/// `None` when that leaves nothing.
+fn history_entry(
+    commit: CommitInfo,
+    before: &BTreeSet<String>,
+    after: &BTreeSet<String>,
+    package: Option<&str>,
+) -> Option<PackageHistoryEntry> {
+    let wanted = |name: &&String| package.is_none_or(|package| name.as_str() == package);
+    let added: Vec<String> = after.difference(before).filter(wanted).cloned().collect();
+    let removed: Vec<String> = before.difference(after).filter(wanted).cloned().collect();
+    if added.is_empty() && removed.is_empty() {
+        return None;
+    }
+    Some(PackageHistoryEntry {
+        commit,
+        added,
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layered(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn capture_plan_skips_tracked_and_excluded_packages() {
+        let mut manifest = SystemPackagesManifest::default();
+        manifest.add_package("htop".to_string());
+        manifest.capture_exclude = layered(&["langpacks-en", "fedora-repos-archive"]);
+
+        let plan = plan_from_layered(
+            layered(&[
+                "zsh",
+                "langpacks-en",
+                "htop",
+                "code",
+                "fedora-repos-archive",
+            ]),
+            &manifest,
+        );
+
+        assert_eq!(plan.to_capture, vec!["code", "zsh"]);
+        assert_eq!(plan.already_in_manifest, 1);
+        assert_eq!(plan.excluded, vec!["fedora-repos-archive", "langpacks-en"]);
+        assert!(!plan.is_empty());
+    }
+
+    #[test]
+    fn capture_plan_describe_reports_excluded_and_versions() {
+        let manifest = SystemPackagesManifest {
+            capture_exclude: layered(&["langpacks-en"]),
+            ..Default::default()
+        };
+
+        let mut plan = plan_from_layered(layered(&["code", "langpacks-en"]), &manifest);
+        plan.versions
+            .insert("code".to_string(), "1.95.0-1731069451.el8".to_string());
+
+        let summary = plan.describe();
+        assert_eq!(
+            summary.summary,
+            "System Capture: 1 to add, 0 already in manifest, 1 excluded"
+        );
+        assert_eq!(summary.action_count(), 1);
+
+        let ops: Vec<_> = summary
+            .operations
+            .iter()
+            .map(|op| (op.verb, op.target.as_str(), op.details.as_deref()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (
+                    Verb::Capture,
+                    "package:code",
+                    Some("version 1.95.0-1731069451.el8")
+                ),
+                (
+                    Verb::Skip,
+                    "package:langpacks-en",
+                    Some("in capture_exclude")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn capture_plan_with_only_excluded_packages_is_empty() {
+        let manifest = SystemPackagesManifest {
+            capture_exclude: layered(&["langpacks-en"]),
+            ..Default::default()
+        };
+
+        let plan = plan_from_layered(layered(&["langpacks-en"]), &manifest);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn parse_rpm_versions_ignores_missing_packages() {
+        let stdout = "code 1.95.0-1731069451.el8\n\
+                      package nope is not installed\n\
+                      kernel-devel 6.11.5-300.fc41\n\
+                      steam 1:1.0.0.81-3.fc41\n";
+        let versions = parse_rpm_versions(stdout);
+
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions["code"], "1.95.0-1731069451.el8");
+        assert_eq!(versions["steam"], "1:1.0.0.81-3.fc41");
+        assert!(!versions.contains_key("package"));
+    }
+
+    #[test]
+    fn history_entry_reports_package_set_changes() {
+        let commit = || CommitInfo {
+            hash: "abc123".to_string(),
+            date: "2026-01-15".to_string(),
+            author: "Ada".to_string(),
+            subject: "feat: swap editors (#42)".to_string(),
+        };
+        let before = manifest_packages(Some(
+            r#"{"packages": ["htop", "nano"], "groups": ["@development-tools"]}"#.to_string(),
+        ));
+        let after = manifest_packages(Some(
+            r#"{"packages": ["htop", "vim"], "pins": {"vim": "9.1"}}"#.to_string(),
+        ));
+
+        let entry = history_entry(commit(), &before, &after, None).unwrap();
+        assert_eq!(entry.added, ["vim"]);
+        assert_eq!(entry.removed, ["@development-tools", "nano"]);
+
+        let entry = history_entry(commit(), &before, &after, Some("nano")).unwrap();
+        assert!(entry.added.is_empty());
+        assert_eq!(entry.removed, ["nano"]);
+
+        // Untouched packages and pin-only commits aren't history
+        assert!(history_entry(commit(), &before, &after, Some("htop")).is_none());
+        assert!(history_entry(commit(), &after, &after, None).is_none());
+        assert!(manifest_packages(None).is_empty());
+        assert!(manifest_packages(Some("not json".to_string())).is_empty());
+    }
+}
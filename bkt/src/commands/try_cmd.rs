@@ -11,8 +11,10 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::containerfile::refresh_split_parts;
 use crate::containerfile::{
     ContainerfileEditor, Section, generate_copr_repos, generate_system_packages,
+    is_placeholder_content,
 };
 use crate::context::CommandDomain;
 use crate::dbus::SystemdManager;
@@ -150,6 +152,7 @@ fn handle_remove(package: &str, plan: &ExecutionPlan) -> Result<()> {
         let mut changed_files = vec![PathBuf::from("manifests/system-packages.json")];
         if sync_containerfile_sections(repo_path, &repo_manifest)? {
             changed_files.push(PathBuf::from("Containerfile"));
+            changed_files.extend(refresh_split_parts(repo_path)?);
         }
 
         let title = format!("chore(try): remove {}", package);
@@ -311,6 +314,7 @@ fn handle_try_install(packages: &[String], plan: &ExecutionPlan) -> Result<()> {
 
         if sync_containerfile_sections(repo_path, &repo_manifest)? {
             changed_files.push(PathBuf::from("Containerfile"));
+            changed_files.extend(refresh_split_parts(repo_path)?);
         }
 
         let title = format!("feat(try): add {}", new_packages.join(", "));
@@ -548,21 +552,28 @@ fn sync_containerfile_sections(
         }
     };
 
-    if editor.has_section(Section::SystemPackages) {
-        let new_content = generate_system_packages(&manifest.packages, has_external_rpms);
-        editor.update_section(Section::SystemPackages, new_content);
+    let new_content = generate_system_packages(
+        &manifest.packages,
+        &manifest.groups,
+        &manifest.pins,
+        &manifest.arches,
+        has_external_rpms,
+        false,
+    );
+    if editor.has_section(Section::SystemPackages) || !is_placeholder_content(&new_content) {
+        editor.upsert_section(Section::SystemPackages, new_content);
         updated_any = true;
     }
 
-    if editor.has_section(Section::CoprRepos) {
-        let repo_names: Vec<String> = manifest
-            .copr_repos
-            .iter()
-            .filter(|c| c.enabled)
-            .map(|c| c.name.clone())
-            .collect();
-        let new_content = generate_copr_repos(&repo_names);
-        editor.update_section(Section::CoprRepos, new_content);
+    let repo_names: Vec<String> = manifest
+        .copr_repos
+        .iter()
+        .filter(|c| c.enabled)
+        .map(|c| c.name.clone())
+        .collect();
+    let new_content = generate_copr_repos(&repo_names, false);
+    if editor.has_section(Section::CoprRepos) || !is_placeholder_content(&new_content) {
+        editor.upsert_section(Section::CoprRepos, new_content);
         updated_any = true;
     }
 
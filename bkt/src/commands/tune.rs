@@ -5,7 +5,7 @@
 //! - `tune layers` - Analyze and suggest layer groupings (RFC-0050, not yet implemented)
 //! - `tune prune` - Clean up ostree deployments/objects (RFC-0050, not yet implemented)
 
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
@@ -77,8 +77,8 @@ pub struct MemoryArgs {
     yes: bool,
 
     /// Output format
-    #[arg(short, long, default_value = "table")]
-    format: String,
+    #[arg(short, long, value_enum, default_value = "table")]
+    format: OutputFormat,
 }
 
 /// Collected system status
@@ -135,7 +135,7 @@ fn run_memory(args: MemoryArgs) -> Result<()> {
         return Ok(());
     }
 
-    if args.format == "json" {
+    if args.format == OutputFormat::Json {
         return output_json(&status, &actions);
     }
 
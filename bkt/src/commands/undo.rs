@@ -0,0 +1,231 @@
+//! Undo command implementation.
+//!
+//! Restores manifests from the history that every manifest save keeps
+//! (see [`crate::manifest::history`]).
+
+use crate::manifest::diff::{Diffable, diff_collections};
+use crate::manifest::history::{self, HistoryEntry};
+use crate::manifest::{
+    AppImageAppsManifest, FlatpakAppsManifest, FlatpakRemotesManifest, GSettingsManifest,
+    GnomeExtensionsManifest, ShimsManifest,
+};
+use crate::output::Output;
+use crate::pipeline::ExecutionPlan;
+use anyhow::{Context, Result};
+use clap::Args;
+use owo_colors::OwoColorize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Args)]
+pub struct UndoArgs {
+    /// Manifest to undo (e.g., flatpak-apps.json); defaults to the most
+    /// recently changed one
+    pub manifest: Option<String>,
+    /// List saved manifest versions, newest first
+    #[arg(long)]
+    pub list: bool,
+    /// Restore without asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+pub fn run(args: UndoArgs, plan: &ExecutionPlan) -> Result<()> {
+    let root = history::history_root().context("Cannot determine $HOME")?;
+    let mut entries = history::entries(&root)?;
+    if let Some(name) = &args.manifest {
+        entries.retain(|entry| manifest_name(entry) == name.as_str());
+    }
+
+    if args.list {
+        return list(&entries);
+    }
+
+    let Some(entry) = entries.first() else {
+        match &args.manifest {
+            Some(name) => Output::info(format!("No saved versions of {}.", name)),
+            None => Output::info("No manifest changes to undo."),
+        }
+        return Ok(());
+    };
+
+    let previous = entry.read()?;
+    let current = std::fs::read_to_string(&entry.target).unwrap_or_default();
+    Output::header(format!("Undo change to {}", entry.target.display()));
+    Output::kv(
+        "Changed",
+        entry
+            .replaced_at
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string(),
+    );
+    let changes = summarize(manifest_name(entry), &current, &previous);
+    if changes.is_empty() {
+        Output::info("The saved version matches the current file.");
+    }
+    for change in &changes {
+        println!("  {}", change);
+    }
+
+    if plan.dry_run {
+        Output::dry_run(format!("Would restore {}", entry.target.display()));
+        return Ok(());
+    }
+    if !args.yes && !confirm_restore()? {
+        Output::info("Nothing restored.");
+        return Ok(());
+    }
+
+    history::restore(entry)?;
+    Output::success(format!("Restored {}", entry.target.display()));
+    Ok(())
+}
+
+fn list(entries: &[HistoryEntry]) -> Result<()> {
+    if entries.is_empty() {
+        Output::info("No saved manifest versions.");
+        return Ok(());
+    }
+    for entry in entries {
+        println!(
+            "{}  {}  {}",
+            entry.replaced_at.format("%Y-%m-%d %H:%M:%S"),
+            manifest_name(entry).bold(),
+            entry.target.display().dimmed()
+        );
+    }
+    Ok(())
+}
+
+fn confirm_restore() -> Result<bool> {
+    use is_terminal::IsTerminal;
+
+    if !std::io::stdin().is_terminal() {
+        Output::hint("Not a terminal; re-run with --yes to restore.");
+        return Ok(false);
+    }
+
+    print!("Restore the previous version? [y/N] ");
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        return Ok(false);
+    }
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
+
+fn manifest_name(entry: &HistoryEntry) -> &str {
+    entry
+        .target
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+/// What restoring `to` over `from` changes, one line per item for the
+/// manifests whose items are diffable and per top-level field otherwise.
+pub fn summarize(name: &str, from: &str, to: &str) -> Vec<String> {
+    let typed = match name {
+        "flatpak-apps.json" => items(from, to, |m: FlatpakAppsManifest| m.apps),
+        "flatpak-remotes.json" => items(from, to, |m: FlatpakRemotesManifest| m.remotes),
+        "gnome-extensions.json" => items(from, to, |m: GnomeExtensionsManifest| m.extensions),
+        "gsettings.json" => items(from, to, |m: GSettingsManifest| m.settings),
+        "host-shims.json" => items(from, to, |m: ShimsManifest| m.shims),
+        AppImageAppsManifest::FILENAME => items(from, to, |m: AppImageAppsManifest| m.apps),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| fields(from, to))
+}
+
+fn items<M, T>(from: &str, to: &str, get: fn(M) -> Vec<T>) -> Option<Vec<String>>
+where
+    M: DeserializeOwned,
+    T: Diffable + Clone,
+{
+    let parse = |content: &str| match content.trim() {
+        "" => Some(Vec::new()),
+        content => serde_json::from_str(content).ok().map(get),
+    };
+    let diff = diff_collections(&parse(from)?, &parse(to)?);
+
+    let keys = |items: &[T]| {
+        items
+            .iter()
+            .map(Diffable::diff_key)
+            .collect::<BTreeSet<_>>()
+    };
+    let changed: Vec<T> = diff.changed.into_iter().map(|c| c.to).collect();
+    let mut lines = Vec::new();
+    lines.extend(keys(&diff.added).into_iter().map(|k| format!("+ {}", k)));
+    lines.extend(keys(&diff.removed).into_iter().map(|k| format!("- {}", k)));
+    lines.extend(keys(&changed).into_iter().map(|k| format!("~ {}", k)));
+    Some(lines)
+}
+
+fn fields(from: &str, to: &str) -> Vec<String> {
+    let parse = |content: &str| match content.trim() {
+        "" => Some(serde_json::Map::new()),
+        content => serde_json::from_str(content).ok(),
+    };
+    let (Some(from), Some(to)) = (parse(from), parse(to)) else {
+        return vec!["~ contents differ".to_string()];
+    };
+
+    let keys: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+    let mut lines = Vec::new();
+    for key in keys {
+        match (from.get(key), to.get(key)) {
+            (Some(a), Some(b)) if a == b => {}
+            (None, Some(_)) => lines.push(format!("+ {}", key)),
+            (Some(_), None) => lines.push(format!("- {}", key)),
+            (Some(serde_json::Value::Array(a)), Some(serde_json::Value::Array(b))) => {
+                let added = b.iter().filter(|v| !a.contains(v)).count();
+                let removed = a.iter().filter(|v| !b.contains(v)).count();
+                lines.push(format!("~ {} (+{} -{})", key, added, removed));
+            }
+            _ => lines.push(format!("~ {}", key)),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_known_manifests_by_item() {
+        let before = r#"{"apps": [
+            {"id": "org.gnome.Boxes", "remote": "flathub", "scope": "system"},
+            {"id": "org.gnome.Calculator", "remote": "flathub", "scope": "system"}
+        ]}"#;
+        let after = r#"{"apps": [
+            {"id": "org.gnome.Boxes", "remote": "flathub", "scope": "user"},
+            {"id": "org.mozilla.firefox", "remote": "flathub", "scope": "system"}
+        ]}"#;
+        assert_eq!(
+            summarize("flatpak-apps.json", before, after),
+            [
+                "+ org.mozilla.firefox",
+                "- org.gnome.Calculator",
+                "~ org.gnome.Boxes"
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_other_manifests_by_field() {
+        let before = r#"{"packages": ["htop", "vim"], "groups": [], "copr": "x"}"#;
+        let after = r#"{"packages": ["htop", "tmux", "git"], "copr": "y", "excluded": []}"#;
+        assert_eq!(
+            summarize("system-packages.json", before, after),
+            ["~ copr", "+ excluded", "- groups", "~ packages (+2 -1)"]
+        );
+        assert_eq!(
+            summarize("system-packages.json", "not json", after),
+            ["~ contents differ"]
+        );
+    }
+}
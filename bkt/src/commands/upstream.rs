@@ -4,16 +4,20 @@
 //! pinning and cryptographic verification.
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::commands::containerfile::{BUMP_CACHE_CONCURRENCY, BUMP_CACHE_TIMEOUT};
 use crate::manifest::{
-    InstallConfig, ManifestRepo, PinnedVersion, ReleaseType, Upstream, UpstreamManifest,
-    UpstreamSource,
+    ExternalRepoHashes, ExternalReposManifest, InstallConfig, ManifestRepo, PinnedVersion,
+    ReleaseType, Upstream, UpstreamManifest, UpstreamSource,
 };
-use crate::output::Output;
+use crate::output::{Output, OutputFormat};
 use anyhow::{Context, Result, bail};
+use bkt_common::checksum::{Algorithm, Checksum};
 use chrono::Utc;
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
-use sha2::{Digest, Sha256};
+use rpmcheck::lockfile::{LockedPackage, Lockfile};
+use rpmcheck::repodata::{PackageVersion, repo_hash};
+use serde::Serialize;
 
 #[derive(Debug, Args)]
 pub struct UpstreamArgs {
@@ -26,8 +30,8 @@ pub enum UpstreamAction {
     /// List all tracked upstream dependencies
     List {
         /// Output format (table, json)
-        #[arg(short, long, default_value = "table")]
-        format: String,
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
     },
     /// Check for available updates
     Check {
@@ -55,6 +59,13 @@ pub enum UpstreamAction {
         /// Asset pattern for GitHub releases
         #[arg(long)]
         asset: Option<String>,
+        /// URL of a detached GPG signature ({version} is substituted)
+        #[arg(long, requires = "gpg_key")]
+        signature_url: Option<String>,
+        /// Signing key: a repo-relative path to an armored public key, or
+        /// the armored key itself
+        #[arg(long, requires = "signature_url")]
+        gpg_key: Option<String>,
     },
     /// Pin an upstream to a specific version
     Pin {
@@ -79,11 +90,23 @@ pub enum UpstreamAction {
         /// Upstream name
         name: String,
     },
+    /// Report the versions external RPM repos publish for their tracked packages
+    ///
+    /// Compares each repo against the hash recorded by
+    /// `bkt containerfile bump-cache`, so a repo reported as changed will
+    /// be re-downloaded on the next build once the cache is bumped.
+    CheckRpms {
+        /// Only check this repo (from external-repos.json)
+        repo: Option<String>,
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
 }
 
 pub fn run(args: UpstreamArgs, runner: &dyn CommandRunner) -> Result<()> {
     match args.action {
-        UpstreamAction::List { format } => handle_list(&format),
+        UpstreamAction::List { format } => handle_list(format),
         UpstreamAction::Check {
             name,
             include_prereleases,
@@ -93,17 +116,169 @@ pub fn run(args: UpstreamArgs, runner: &dyn CommandRunner) -> Result<()> {
             source,
             name,
             asset,
-        } => handle_add(source, name, asset),
+            signature_url,
+            gpg_key,
+        } => handle_add(source, name, asset, signature_url, gpg_key),
         UpstreamAction::Pin { name, version } => handle_pin(name, version),
         UpstreamAction::Remove { name } => handle_remove(name),
         UpstreamAction::Verify => handle_verify(runner),
         UpstreamAction::Lock => handle_lock(runner),
         UpstreamAction::Generate => handle_generate(),
         UpstreamAction::Info { name } => handle_info(&name),
+        UpstreamAction::CheckRpms { repo, format } => handle_check_rpms(repo, format),
     }
 }
 
-fn handle_list(format: &str) -> Result<()> {
+/// One external repo's tracked packages, as `check-rpms` reports them.
+#[derive(Debug, Serialize)]
+struct RpmRepoReport {
+    name: String,
+    /// `current`, `changed` (since the last bump-cache) or `failed`
+    status: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    packages: Vec<RpmPackageReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpmPackageReport {
+    name: String,
+    /// Newest `[epoch:]version-release` in the repo, `None` when missing
+    evr: Option<String>,
+}
+
+fn handle_check_rpms(repo: Option<String>, format: OutputFormat) -> Result<()> {
+    let repo_path = crate::repo::find_repo_path()?;
+    let mut external =
+        ExternalReposManifest::load(&repo_path.join(ExternalReposManifest::PROJECT_PATH))?;
+    if let Some(name) = &repo {
+        external.repos.retain(|r| &r.name == name);
+        if external.repos.is_empty() {
+            bail!("External repo '{}' not found", name);
+        }
+    }
+    if external.repos.is_empty() {
+        Output::info("No external repos to check.");
+        return Ok(());
+    }
+
+    let manifest = external.to_rpmcheck();
+    let spinner = Output::spinner(format!(
+        "Checking {} external repos...",
+        manifest.repos.len()
+    ));
+    let results = rpmcheck::repodata::repo_versions(
+        &manifest,
+        None,
+        BUMP_CACHE_CONCURRENCY,
+        BUMP_CACHE_TIMEOUT,
+    )?;
+    spinner.finish_clear();
+
+    let recorded = ExternalRepoHashes::load(&repo_path.join(ExternalRepoHashes::PROJECT_PATH))?;
+    let reports: Vec<RpmRepoReport> = manifest
+        .repos
+        .iter()
+        .zip(results)
+        .map(|(entry, (name, result))| {
+            rpm_repo_report(
+                name,
+                &entry.packages,
+                result,
+                recorded.repos.get(&entry.name),
+            )
+        })
+        .collect();
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        print_rpm_reports(&reports);
+    }
+
+    let failed = reports.iter().filter(|r| r.status == "failed").count();
+    if failed > 0 {
+        bail!("{} repo(s) could not be checked", failed);
+    }
+    Ok(())
+}
+
+fn rpm_repo_report(
+    name: String,
+    tracked: &[String],
+    result: Result<Vec<PackageVersion>>,
+    recorded_hash: Option<&String>,
+) -> RpmRepoReport {
+    let versions = match result {
+        Ok(versions) => versions,
+        Err(e) => {
+            return RpmRepoReport {
+                name,
+                status: "failed",
+                packages: Vec::new(),
+                error: Some(format!("{:#}", e)),
+            };
+        }
+    };
+
+    // Repos often carry several builds of a package; report the newest
+    let mut newest = Lockfile::default();
+    for pv in &versions {
+        newest.record(&pv.name, pv.locked(&name));
+    }
+    let packages = tracked
+        .iter()
+        .map(|package| RpmPackageReport {
+            name: package.clone(),
+            evr: newest.packages.get(package).map(LockedPackage::evr),
+        })
+        .collect();
+    let status = if recorded_hash == Some(&repo_hash(&versions)) {
+        "current"
+    } else {
+        "changed"
+    };
+
+    RpmRepoReport {
+        name,
+        status,
+        packages,
+        error: None,
+    }
+}
+
+fn print_rpm_reports(reports: &[RpmRepoReport]) {
+    Output::header("EXTERNAL RPM REPOS");
+    for report in reports {
+        let status = match report.status {
+            "current" => "current".green().to_string(),
+            "changed" => "changed since bump-cache".yellow().to_string(),
+            other => other.red().to_string(),
+        };
+        println!("{} ({})", report.name.cyan(), status);
+        if let Some(error) = &report.error {
+            Output::list_item(error.dimmed().to_string());
+        }
+        for package in &report.packages {
+            match &package.evr {
+                Some(evr) => Output::list_item(format!("{:<30} {}", package.name, evr)),
+                None => Output::list_item(format!(
+                    "{:<30} {}",
+                    package.name,
+                    "not found in repo".yellow()
+                )),
+            }
+        }
+    }
+
+    if reports.iter().any(|r| r.status == "changed") {
+        Output::blank();
+        Output::hint("Run `bkt containerfile bump-cache` to rebuild the changed repos.");
+    }
+}
+
+fn handle_list(format: OutputFormat) -> Result<()> {
     let manifest = UpstreamManifest::load()?;
 
     if manifest.upstreams.is_empty() {
@@ -112,7 +287,7 @@ fn handle_list(format: &str) -> Result<()> {
         return Ok(());
     }
 
-    if format == "json" {
+    if format == OutputFormat::Json {
         println!("{}", serde_json::to_string_pretty(&manifest.upstreams)?);
         return Ok(());
     }
@@ -371,16 +546,21 @@ fn update_upstream(
         if let Some(u) = manifest.find_mut(name) {
             u.pinned.version = version.clone();
             u.pinned.pinned_at = Utc::now();
-            // SHA256 needs to be recomputed
-            u.pinned.sha256 =
-                "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+            // The checksum needs to be recomputed
+            u.pinned.reset_checksum();
         }
     }
 
     Ok(new_version)
 }
 
-fn handle_add(source: String, name: Option<String>, asset: Option<String>) -> Result<()> {
+fn handle_add(
+    source: String,
+    name: Option<String>,
+    asset: Option<String>,
+    signature_url: Option<String>,
+    gpg_key: Option<String>,
+) -> Result<()> {
     let mut manifest = UpstreamManifest::load()?;
 
     let (upstream_source, derived_name) = parse_source(&source, asset)?;
@@ -402,12 +582,28 @@ fn handle_add(source: String, name: Option<String>, asset: Option<String>) -> Re
             commit: None,
             url: None,
             sha256: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            checksum: None,
             gpg_verified: false,
             pinned_at: Utc::now(),
         },
         install: None,
+        signature_url,
+        gpg_key,
+        arches: Vec::new(),
+        mirrors: Vec::new(),
     };
 
+    if let Some(path) = upstream.gpg_key_path() {
+        let repo = crate::repo::find_repo_path()?;
+        if !repo.join(path).is_file() {
+            bail!(
+                "GPG key '{}' not found in repository (expected a path relative to {})",
+                path,
+                repo.display()
+            );
+        }
+    }
+
     manifest.upsert(upstream);
     manifest.save()?;
 
@@ -464,9 +660,8 @@ fn handle_pin(name: String, version: String) -> Result<()> {
     let old_version = upstream.pinned.version.clone();
     upstream.pinned.version = version.clone();
     upstream.pinned.pinned_at = Utc::now();
-    // SHA256 needs to be recomputed
-    upstream.pinned.sha256 =
-        "0000000000000000000000000000000000000000000000000000000000000000".to_string();
+    // The checksum needs to be recomputed
+    upstream.pinned.reset_checksum();
 
     manifest.save()?;
 
@@ -504,9 +699,7 @@ fn handle_verify(runner: &dyn CommandRunner) -> Result<()> {
 
     for upstream in &manifest.upstreams {
         // Check if we have a placeholder checksum
-        if upstream.pinned.sha256
-            == "0000000000000000000000000000000000000000000000000000000000000000"
-        {
+        if upstream.pinned.has_placeholder_checksum() {
             println!(
                 "  {} {}",
                 upstream.name.yellow(),
@@ -568,9 +761,10 @@ fn handle_verify(runner: &dyn CommandRunner) -> Result<()> {
 
 fn verify_upstream(upstream: &Upstream, runner: &dyn CommandRunner) -> Result<bool> {
     // Download and compute checksum
+    let expected: Checksum = upstream.pinned.expected_checksum().parse()?;
     let url = get_download_url(upstream, runner)?;
-    let computed_hash = download_and_hash(&url, runner)?;
-    Ok(computed_hash == upstream.pinned.sha256)
+    let computed = download_and_hash(&url, expected.algorithm, runner)?;
+    Ok(computed == expected)
 }
 
 fn get_download_url(upstream: &Upstream, runner: &dyn CommandRunner) -> Result<String> {
@@ -631,7 +825,11 @@ fn get_download_url(upstream: &Upstream, runner: &dyn CommandRunner) -> Result<S
     }
 }
 
-fn download_and_hash(url: &str, runner: &dyn CommandRunner) -> Result<String> {
+fn download_and_hash(
+    url: &str,
+    algorithm: Algorithm,
+    runner: &dyn CommandRunner,
+) -> Result<Checksum> {
     let output = runner
         .run_output("curl", &["-fsSL", url], &CommandOptions::default())
         .context("Failed to download")?;
@@ -643,8 +841,7 @@ fn download_and_hash(url: &str, runner: &dyn CommandRunner) -> Result<String> {
         );
     }
 
-    let hash = Sha256::digest(&output.stdout);
-    Ok(hex::encode(hash))
+    Ok(Checksum::of(algorithm, &output.stdout))
 }
 
 fn handle_lock(runner: &dyn CommandRunner) -> Result<()> {
@@ -666,17 +863,17 @@ fn handle_lock(runner: &dyn CommandRunner) -> Result<()> {
         let spinner = Output::spinner(format!("Downloading {}...", upstream.name));
 
         match lock_upstream(&upstream, runner) {
-            Ok((sha256, url)) => {
+            Ok((checksum, url)) => {
                 spinner.finish_clear();
                 if let Some(u) = manifest.find_mut(&name) {
-                    u.pinned.sha256 = sha256.clone();
+                    u.pinned.set_checksum(&checksum);
                     u.pinned.url = Some(url);
                 }
                 println!(
                     "  {} {} → {}",
                     "✓".green(),
                     upstream.name,
-                    format!("{}...", &sha256[..16]).dimmed()
+                    format!("{}...", &checksum.digest[..16]).dimmed()
                 );
                 locked += 1;
             }
@@ -701,9 +898,9 @@ fn handle_lock(runner: &dyn CommandRunner) -> Result<()> {
     Ok(())
 }
 
-fn lock_upstream(upstream: &Upstream, runner: &dyn CommandRunner) -> Result<(String, String)> {
+fn lock_upstream(upstream: &Upstream, runner: &dyn CommandRunner) -> Result<(Checksum, String)> {
     let url = get_download_url(upstream, runner)?;
-    let hash = download_and_hash(&url, runner)?;
+    let hash = download_and_hash(&url, upstream.pinned.algorithm(), runner)?;
     Ok((hash, url))
 }
 
@@ -759,7 +956,11 @@ fn handle_info(name: &str) -> Result<()> {
     if let Some(url) = &upstream.pinned.url {
         println!("  {} {}", "URL:".dimmed(), url);
     }
-    println!("  {} {}", "SHA256:".dimmed(), upstream.pinned.sha256);
+    println!(
+        "  {} {}",
+        "Checksum:".dimmed(),
+        upstream.pinned.expected_checksum()
+    );
     println!(
         "  {} {}",
         "GPG Verified:".dimmed(),
@@ -771,6 +972,14 @@ fn handle_info(name: &str) -> Result<()> {
     );
     println!("  {} {}", "Pinned At:".dimmed(), upstream.pinned.pinned_at);
 
+    if let Some(url) = upstream.resolved_signature_url() {
+        Output::blank();
+        println!("  {}", "Signature:".cyan());
+        println!("  {} {}", "URL:".dimmed(), url);
+        let key = upstream.gpg_key_path().unwrap_or("(inline armored key)");
+        println!("  {} {}", "GPG Key:".dimmed(), key);
+    }
+
     if let Some(install) = &upstream.install {
         Output::blank();
         println!("  {}", "Installation:".cyan());
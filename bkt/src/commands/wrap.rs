@@ -52,9 +52,58 @@ pub struct WrapperConfig {
     pub remote_cli: bool,
     #[serde(default)]
     pub description: Option<String>,
+    #[serde(default)]
+    pub memory_high: Option<String>,
+    #[serde(default)]
+    pub memory_max: Option<String>,
+    #[serde(default)]
+    pub cpu_weight: Option<u32>,
+    #[serde(default)]
+    pub properties: Vec<String>,
 }
 
 impl WrapperConfig {
+    /// Resource limits for the slice, as systemd `Key=Value` properties.
+    pub fn slice_limits(&self) -> Vec<String> {
+        let mut limits = Vec::new();
+        if let Some(ref memory_high) = self.memory_high {
+            limits.push(format!("MemoryHigh={}", memory_high));
+        }
+        if let Some(ref memory_max) = self.memory_max {
+            limits.push(format!("MemoryMax={}", memory_max));
+        }
+        if let Some(cpu_weight) = self.cpu_weight {
+            limits.push(format!("CPUWeight={}", cpu_weight));
+        }
+        limits
+    }
+
+    /// Generate the slice unit carrying the configured limits.
+    ///
+    /// Returns `None` when no limits are set, in which case the slice is
+    /// either provided elsewhere or created implicitly by systemd-run.
+    pub fn generate_slice_unit(&self) -> Option<String> {
+        let limits = self.slice_limits();
+        if limits.is_empty() {
+            return None;
+        }
+
+        let description = self
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("{} (managed)", self.name));
+
+        let mut unit = format!(
+            "# Auto-generated by bkt from image-config.json ({})\n[Slice]\nDescription={}\n",
+            self.name, description
+        );
+        for limit in limits {
+            unit.push_str(&limit);
+            unit.push('\n');
+        }
+        Some(unit)
+    }
+
     /// Generate the Rust source code for this wrapper
     pub fn generate_source(&self) -> String {
         let description = self
@@ -96,6 +145,15 @@ fn find_remote_cli() -> Option<String> {
             ""
         };
 
+        // Limits are applied to the scope as well, so they hold even if the
+        // slice unit is missing; extra properties are appended verbatim.
+        let extra_properties: String = self
+            .slice_limits()
+            .iter()
+            .chain(&self.properties)
+            .map(|property| format!("            {:?},\n", format!("--property={}", property)))
+            .collect();
+
         format!(
             r#"//! Auto-generated wrapper by bkt wrap
 //! Target: {target}
@@ -151,7 +209,7 @@ fn main() {{
             &format!("--unit={{}}", unit_name),
             "--description={description}",
             "--property=OOMPolicy=kill",
-            "--",
+{extra_properties}            "--",
             target,
         ])
         .args(std::env::args().skip(1))
@@ -173,6 +231,7 @@ fn main() {{
             description = description,
             remote_cli_code = remote_cli_code,
             remote_cli_fn = remote_cli_fn,
+            extra_properties = extra_properties,
         )
     }
 
@@ -393,6 +452,10 @@ mod tests {
             output: "/usr/bin/test".to_string(),
             remote_cli: false,
             description: None,
+            memory_high: None,
+            memory_max: None,
+            cpu_weight: None,
+            properties: Vec::new(),
         };
 
         let source = config.generate_source();
@@ -412,6 +475,10 @@ mod tests {
             output: "/usr/bin/code".to_string(),
             remote_cli: true,
             description: Some("VS Code (managed)".to_string()),
+            memory_high: None,
+            memory_max: None,
+            cpu_weight: None,
+            properties: Vec::new(),
         };
 
         let source = config.generate_source();
@@ -419,4 +486,58 @@ mod tests {
         assert!(source.contains("find_remote_cli"));
         assert!(source.contains("/remote-cli/"));
     }
+
+    #[test]
+    fn test_generate_source_with_limits() {
+        let config = WrapperConfig {
+            name: "code".to_string(),
+            target: "/usr/share/code/bin/code".to_string(),
+            slice: "app-vscode.slice".to_string(),
+            output: "/usr/bin/code".to_string(),
+            remote_cli: true,
+            description: None,
+            memory_high: Some("20G".to_string()),
+            memory_max: Some("24G".to_string()),
+            cpu_weight: Some(50),
+            properties: vec!["IOWeight=50".to_string()],
+        };
+
+        let source = config.generate_source();
+        assert!(source.contains(
+            "            \"--property=OOMPolicy=kill\",\n\
+             \x20           \"--property=MemoryHigh=20G\",\n\
+             \x20           \"--property=MemoryMax=24G\",\n\
+             \x20           \"--property=CPUWeight=50\",\n\
+             \x20           \"--property=IOWeight=50\",\n\
+             \x20           \"--\","
+        ));
+        // Re-entry guard and remote-cli passthrough are preserved
+        assert!(source.contains("already_in_slice(\"app-vscode.slice\")"));
+        assert!(source.contains("VSCODE_IPC_HOOK_CLI"));
+    }
+
+    #[test]
+    fn test_generate_slice_unit() {
+        let mut config = WrapperConfig {
+            name: "msedge".to_string(),
+            target: "/usr/bin/msedge".to_string(),
+            slice: "app-msedge.slice".to_string(),
+            output: "/usr/bin/microsoft-edge-stable".to_string(),
+            remote_cli: false,
+            description: Some("Microsoft Edge (managed)".to_string()),
+            memory_high: None,
+            memory_max: None,
+            cpu_weight: None,
+            properties: vec!["IOWeight=50".to_string()],
+        };
+        // Scope-only properties don't warrant a slice unit
+        assert!(config.generate_slice_unit().is_none());
+
+        config.memory_max = Some("8G".to_string());
+        config.cpu_weight = Some(200);
+        let unit = config.generate_slice_unit().unwrap();
+        assert!(unit.contains("[Slice]\nDescription=Microsoft Edge (managed)\n"));
+        assert!(unit.ends_with("MemoryMax=8G\nCPUWeight=200\n"));
+        assert!(!unit.contains("IOWeight"));
+    }
 }
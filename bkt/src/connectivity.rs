@@ -0,0 +1,100 @@
+//! Network reachability, for degrading gracefully when offline.
+//!
+//! Commands that need the network (package validation, PR creation) ask
+//! [`Connectivity::is_offline`] first. With `--offline` the answer is fixed;
+//! otherwise a quick probe runs the first time it's asked, so purely local
+//! commands never pay for it.
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// How long the probe may take before the network counts as unreachable.
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Hosts bkt needs for validation and PRs; reaching any one is enough.
+const PROBE_HOSTS: &[&str] = &["github.com:443", "api.github.com:443"];
+
+/// Whether bkt should act as if the network is down.
+#[derive(Debug, Clone)]
+pub enum Connectivity {
+    /// Assume the network is up, without probing.
+    Online,
+    /// `--offline`: skip anything that needs the network.
+    Offline,
+    /// Probe on first use and remember the answer.
+    Detect(Arc<OnceLock<bool>>),
+}
+
+impl Connectivity {
+    /// Detect reachability lazily, the first time it matters.
+    pub fn detect() -> Self {
+        Self::Detect(Arc::new(OnceLock::new()))
+    }
+
+    pub fn is_offline(&self) -> bool {
+        match self {
+            Self::Online => false,
+            Self::Offline => true,
+            Self::Detect(offline) => *offline.get_or_init(|| !probe(PROBE_HOSTS, PROBE_TIMEOUT)),
+        }
+    }
+}
+
+/// Whether any of `hosts` (`host:port`) accepts a TCP connection within
+/// `timeout`.
+///
+/// Name resolution has no timeout of its own, so the probe runs on a thread
+/// and is abandoned once `timeout` passes.
+pub fn probe(hosts: &[&str], timeout: Duration) -> bool {
+    let hosts: Vec<String> = hosts.iter().map(|host| host.to_string()).collect();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let reachable = hosts.iter().any(|host| reachable(host, timeout));
+        let _ = tx.send(reachable);
+    });
+    rx.recv_timeout(timeout).unwrap_or(false)
+}
+
+fn reachable(host: &str, timeout: Duration) -> bool {
+    host.to_socket_addrs()
+        .map(|mut addrs| addrs.any(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    #[test]
+    fn probe_reaches_a_listening_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = listener.local_addr().unwrap().to_string();
+        assert!(probe(&[&host], PROBE_TIMEOUT));
+    }
+
+    #[test]
+    fn probe_fails_fast_for_closed_ports_and_bad_hosts() {
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        };
+        let started = Instant::now();
+        assert!(!probe(&[&closed, "not a host"], Duration::from_millis(500)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn fixed_connectivity_never_probes() {
+        assert!(!Connectivity::Online.is_offline());
+        assert!(Connectivity::Offline.is_offline());
+
+        let detected = Arc::new(OnceLock::new());
+        detected.set(true).unwrap();
+        assert!(Connectivity::Detect(detected).is_offline());
+    }
+}
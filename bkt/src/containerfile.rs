@@ -20,15 +20,24 @@
 //! - `COPR_REPOS`: COPR repository enablement commands
 
 use crate::manifest::ExternalReposManifest;
+use crate::manifest::GSettingsManifest;
 use crate::manifest::Shim;
 use crate::manifest::VendorArtifactsManifest;
 use crate::manifest::external_repos::LayerGroup;
+use crate::manifest::group_spec;
 use crate::manifest::image_config::{FileCopy, ImageConfigManifest, ImageModule};
-use crate::manifest::system_config::SystemConfigManifest;
+use crate::manifest::system_config::{
+    AUTO_UPGRADE_SERVICE, AUTO_UPGRADE_TIMER, BOOTC_UPDATES_TIMER, IMAGE_UNIT_DIR,
+    SystemConfigManifest,
+};
 use anyhow::{Context, Result, bail};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use bkt_common::manifest::{InstallConfig, Upstream, UpstreamManifest};
+use rpmcheck::repodata::cache_arg_name;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -42,6 +51,12 @@ const SECTION_END: &str = "# === END ";
 const SECTION_END_SUFFIX: &str = " ===";
 const HEADER_WIDTH: usize = 79;
 const LINE_CONT: &str = "\\";
+/// BuildKit cache mount for dnf's package cache, shared across builds.
+const DNF_CACHE_MOUNT: &str = "--mount=type=cache,target=/var/cache/libdnf5,sharing=locked";
+/// Namespace for the image's OCI labels
+const LABEL_PREFIX: &str = "org.wycats.bootc";
+/// Version of the label set; bump when a label is renamed or changes meaning
+const LABEL_SCHEMA_VERSION: u32 = 1;
 
 /// Types of managed sections in the Containerfile
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -57,6 +72,14 @@ pub enum Section {
 }
 
 impl Section {
+    /// All sections, in the order they appear in the final image stage
+    pub const ORDERED: [Section; 4] = [
+        Section::CoprRepos,
+        Section::KernelArguments,
+        Section::SystemPackages,
+        Section::SystemdUnits,
+    ];
+
     /// Get the section name as it appears in markers
     pub fn marker_name(&self) -> &'static str {
         match self {
@@ -103,6 +126,63 @@ impl Section {
             _ => None,
         }
     }
+
+    /// The items generated content installs or configures, e.g. package
+    /// specs for SYSTEM_PACKAGES or `enable foo.service` for SYSTEMD_UNITS.
+    ///
+    /// This reads back what the `generate_*` functions emit, so a build
+    /// report can say what changed rather than which lines did.
+    pub fn items(&self, content: &[String]) -> Vec<String> {
+        let words = content
+            .iter()
+            .map(|line| line.trim().trim_end_matches(LINE_CONT).trim());
+        let mut items: Vec<String> = match self {
+            Section::SystemPackages => words
+                .filter(|line| {
+                    !line.is_empty()
+                        && ![
+                            "#",
+                            "RUN ",
+                            "ARG ",
+                            "&&",
+                            ";;",
+                            "esac",
+                            "dnf ",
+                            "/tmp/rpms/",
+                        ]
+                        .iter()
+                        .any(|prefix| line.starts_with(prefix))
+                })
+                .map(str::to_string)
+                .collect(),
+            Section::CoprRepos => words
+                .filter_map(|line| line.strip_prefix("dnf copr enable -y "))
+                .map(|repo| repo.trim_end_matches(';').to_string())
+                .collect(),
+            Section::KernelArguments => words
+                .filter(|line| line.starts_with("--append=") || line.starts_with("--delete="))
+                .map(str::to_string)
+                .collect(),
+            Section::SystemdUnits => words
+                .flat_map(|line| {
+                    if let Some(copy) = line.strip_prefix("COPY ") {
+                        let dest = copy.split_whitespace().last().unwrap_or_default();
+                        return vec![format!("drop-in {}", dest)];
+                    }
+                    let line = line.trim_end_matches(';');
+                    let Some(command) = line.strip_prefix("systemctl ") else {
+                        return vec![];
+                    };
+                    let mut parts = command.split_whitespace();
+                    let verb = parts.next().unwrap_or_default();
+                    parts.map(|unit| format!("{} {}", verb, unit)).collect()
+                })
+                .collect(),
+        };
+        items.sort();
+        items.dedup();
+        items
+    }
 }
 
 /// A managed block in the Containerfile
@@ -118,6 +198,134 @@ pub struct ManagedBlock {
     pub end_line: usize,
 }
 
+/// Where [`ContainerfileEditor::insert_section`] places a new managed block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionAnchor {
+    /// Directly after the last managed section (end of file if there is none)
+    AfterLastManaged,
+    /// Before the first unmanaged line starting with this text, ignoring
+    /// leading whitespace (end of file if no line matches)
+    Before(String),
+    /// At the end of the file
+    EndOfFile,
+}
+
+/// Whether generated section content is only a placeholder comment
+/// (e.g. "# No packages configured") with nothing to build.
+pub fn is_placeholder_content(content: &[String]) -> bool {
+    content.iter().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#')
+    })
+}
+
+/// A heredoc opened on a `RUN` or `COPY` line, waiting for its terminator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Heredoc {
+    terminator: String,
+    /// `<<-`: leading tabs are stripped before matching the terminator
+    strip_tabs: bool,
+}
+
+impl Heredoc {
+    fn is_terminator(&self, line: &str) -> bool {
+        let line = if self.strip_tabs {
+            line.trim_start_matches('\t')
+        } else {
+            line
+        };
+        line == self.terminator
+    }
+}
+
+/// The heredocs (`<<EOF`, `<<-EOF`, `<<'EOF'`) a `RUN` or `COPY` line
+/// opens, in the order their bodies follow it.
+fn heredocs_opened(line: &str) -> Vec<Heredoc> {
+    let trimmed = line.trim_start();
+    let instruction = trimmed.split_whitespace().next().unwrap_or_default();
+    if !instruction.eq_ignore_ascii_case("RUN") && !instruction.eq_ignore_ascii_case("COPY") {
+        return Vec::new();
+    }
+
+    let mut heredocs = Vec::new();
+    let mut rest = &trimmed[instruction.len()..];
+    while let Some(at) = rest.find("<<") {
+        rest = &rest[at + 2..];
+        // `<<<` is a shell here-string, not a heredoc
+        if rest.starts_with('<') {
+            rest = rest.trim_start_matches('<');
+            continue;
+        }
+        let strip_tabs = rest.starts_with('-');
+        if strip_tabs {
+            rest = &rest[1..];
+        }
+
+        let terminator = match rest.chars().next() {
+            Some(quote @ ('\'' | '"')) => {
+                let Some(end) = rest[1..].find(quote) else {
+                    break;
+                };
+                let word = &rest[1..1 + end];
+                rest = &rest[end + 2..];
+                word
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                rest = &rest[end..];
+                word
+            }
+        };
+        if !terminator.is_empty() {
+            heredocs.push(Heredoc {
+                terminator: terminator.to_string(),
+                strip_tabs,
+            });
+        }
+    }
+    heredocs
+}
+
+/// Tracks heredoc bodies line by line, so their content is never read as
+/// Containerfile structure.
+#[derive(Debug, Default)]
+struct HeredocScanner {
+    open: std::collections::VecDeque<Heredoc>,
+}
+
+impl HeredocScanner {
+    /// Feed the next line; returns `true` if it belongs to a heredoc body
+    /// (including its terminator line).
+    fn in_body(&mut self, line: &str) -> bool {
+        if let Some(heredoc) = self.open.front() {
+            if heredoc.is_terminator(line) {
+                self.open.pop_front();
+            }
+            return true;
+        }
+        self.open.extend(heredocs_opened(line));
+        false
+    }
+}
+
+/// Split Containerfile content into its managed blocks and the unmanaged
+/// lines around them, in file order.
+pub fn split_managed(content: &str) -> Result<(Vec<ManagedBlock>, Vec<String>)> {
+    let editor = ContainerfileEditor::parse(PathBuf::from("Containerfile"), content)?;
+    let mut blocks = Vec::new();
+    let mut unmanaged = Vec::new();
+    for segment in editor.segments {
+        match segment {
+            ContainerfileSegment::Managed(block) => blocks.push(block),
+            ContainerfileSegment::Unmanaged(lines) => unmanaged.extend(lines),
+        }
+    }
+    Ok((blocks, unmanaged))
+}
+
 /// Represents either a managed section or unmanaged content
 #[derive(Debug, Clone)]
 enum ContainerfileSegment {
@@ -146,15 +354,25 @@ impl ContainerfileEditor {
     }
 
     /// Parse Containerfile content into segments
+    ///
+    /// Heredoc bodies in unmanaged content are kept verbatim: a line that
+    /// looks like a section marker inside one is just part of the script.
     fn parse(path: PathBuf, content: &str) -> Result<Self> {
         let lines: Vec<&str> = content.lines().collect();
         let mut segments = Vec::new();
         let mut current_unmanaged: Vec<String> = Vec::new();
+        let mut heredocs = HeredocScanner::default();
         let mut i = 0;
 
         while i < lines.len() {
             let line = lines[i];
 
+            if heredocs.in_body(line) {
+                current_unmanaged.push(line.to_string());
+                i += 1;
+                continue;
+            }
+
             // Check if this is a section start marker
             if let Some(section) = Section::from_start_marker(line) {
                 // Save any accumulated unmanaged content
@@ -209,6 +427,9 @@ impl ContainerfileEditor {
     }
 
     /// Update a managed section with new content
+    ///
+    /// Warns and leaves the file unchanged if the section is absent; use
+    /// [`Self::upsert_section`] to create it on first use.
     pub fn update_section(&mut self, section: Section, content: Vec<String>) {
         for segment in &mut self.segments {
             if let ContainerfileSegment::Managed(block) = segment
@@ -227,6 +448,164 @@ impl ContainerfileEditor {
         );
     }
 
+    /// Update a managed section, creating it if absent
+    ///
+    /// New sections are placed after the nearest section that precedes them
+    /// in [`Section::ORDERED`], else before the nearest one that follows,
+    /// else at the end of the file. Returns `true` if the section was created.
+    pub fn upsert_section(&mut self, section: Section, content: Vec<String>) -> bool {
+        if self.has_section(section) {
+            self.update_section(section, content);
+            return false;
+        }
+
+        let at = self.ordered_insert_index(section);
+        self.insert_block_at(at, section, content);
+        true
+    }
+
+    /// Insert a new managed section at `anchor`
+    ///
+    /// Fails if the section already exists. A blank line separates the new
+    /// block from adjacent content.
+    pub fn insert_section(
+        &mut self,
+        section: Section,
+        content: Vec<String>,
+        anchor: &SectionAnchor,
+    ) -> Result<()> {
+        if self.has_section(section) {
+            bail!(
+                "managed section {} already exists in Containerfile {}",
+                section.marker_name(),
+                self.path.display()
+            );
+        }
+
+        let at = match anchor {
+            SectionAnchor::AfterLastManaged => self.after_last_managed_index(),
+            SectionAnchor::Before(prefix) => self
+                .split_before_line(prefix)
+                .unwrap_or(self.segments.len()),
+            SectionAnchor::EndOfFile => self.segments.len(),
+        };
+        self.insert_block_at(at, section, content);
+        Ok(())
+    }
+
+    /// Segment index just past the last managed block (or end of file)
+    fn after_last_managed_index(&self) -> usize {
+        self.segments
+            .iter()
+            .rposition(|seg| matches!(seg, ContainerfileSegment::Managed(_)))
+            .map_or(self.segments.len(), |i| i + 1)
+    }
+
+    /// Segment index where `section` belongs relative to existing sections
+    fn ordered_insert_index(&self, section: Section) -> usize {
+        let rank = |s: Section| Section::ORDERED.iter().position(|o| *o == s);
+        let target = rank(section);
+        let managed = || {
+            self.segments
+                .iter()
+                .enumerate()
+                .filter_map(|(i, seg)| match seg {
+                    ContainerfileSegment::Managed(block) => Some((i, rank(block.section))),
+                    ContainerfileSegment::Unmanaged(_) => None,
+                })
+        };
+
+        if let Some((i, _)) = managed().rev().find(|(_, r)| *r < target) {
+            return i + 1;
+        }
+        if let Some((i, _)) = managed().find(|(_, r)| *r > target) {
+            return i;
+        }
+        self.segments.len()
+    }
+
+    /// Split the unmanaged segment containing the first line that starts with
+    /// `prefix`, returning the segment index that line now begins.
+    fn split_before_line(&mut self, prefix: &str) -> Option<usize> {
+        // Heredoc bodies never end a segment, so they aren't anchors
+        let (seg_idx, line_idx) =
+            self.segments
+                .iter()
+                .enumerate()
+                .find_map(|(i, seg)| match seg {
+                    ContainerfileSegment::Unmanaged(lines) => {
+                        let mut heredocs = HeredocScanner::default();
+                        lines
+                            .iter()
+                            .position(|line| {
+                                !heredocs.in_body(line) && line.trim_start().starts_with(prefix)
+                            })
+                            .map(|l| (i, l))
+                    }
+                    ContainerfileSegment::Managed(_) => None,
+                })?;
+
+        if line_idx == 0 {
+            return Some(seg_idx);
+        }
+
+        let ContainerfileSegment::Unmanaged(lines) = &mut self.segments[seg_idx] else {
+            unreachable!("matched segment is unmanaged");
+        };
+        let tail = lines.split_off(line_idx);
+        self.segments
+            .insert(seg_idx + 1, ContainerfileSegment::Unmanaged(tail));
+        Some(seg_idx + 1)
+    }
+
+    /// Insert a managed block at a segment index, padding it with blank lines
+    fn insert_block_at(&mut self, at: usize, section: Section, content: Vec<String>) {
+        let blank_before = at == 0
+            || matches!(
+                &self.segments[at - 1],
+                ContainerfileSegment::Unmanaged(lines)
+                    if lines.last().is_some_and(|l| l.trim().is_empty())
+            );
+        let blank_after = match self.segments.get(at) {
+            None => true,
+            Some(ContainerfileSegment::Managed(_)) => false,
+            Some(ContainerfileSegment::Unmanaged(lines)) => {
+                lines.first().is_some_and(|l| l.trim().is_empty())
+            }
+        };
+
+        let mut inserted = Vec::new();
+        if !blank_before {
+            inserted.push(ContainerfileSegment::Unmanaged(vec![String::new()]));
+        }
+        inserted.push(ContainerfileSegment::Managed(ManagedBlock {
+            section,
+            content,
+            start_line: 0,
+            end_line: 0,
+        }));
+        if !blank_after {
+            inserted.push(ContainerfileSegment::Unmanaged(vec![String::new()]));
+        }
+        self.segments.splice(at..at, inserted);
+        self.renumber_blocks();
+    }
+
+    /// Recompute block line numbers after segments change
+    fn renumber_blocks(&mut self) {
+        let mut line = 0;
+        for segment in &mut self.segments {
+            match segment {
+                ContainerfileSegment::Unmanaged(lines) => line += lines.len(),
+                ContainerfileSegment::Managed(block) => {
+                    block.start_line = line;
+                    block.end_line = line + block.content.len() + 1;
+                    line = block.end_line + 1;
+                }
+            }
+        }
+    }
+
     /// Check if a section exists in the Containerfile
     pub fn has_section(&self, section: Section) -> bool {
         self.segments.iter().any(
@@ -281,41 +660,120 @@ impl ContainerfileEditor {
 }
 
 /// Input data for full Containerfile generation.
+#[derive(Clone)]
 pub struct ContainerfileGeneratorInput {
     pub external_repos: ExternalReposManifest,
     pub upstreams: UpstreamManifest,
     pub packages: Vec<String>,
+    /// Package groups, installed as `@group` alongside `packages`
+    pub groups: Vec<String>,
+    pub pins: BTreeMap<String, String>,
+    pub package_arches: BTreeMap<String, Vec<String>>,
     pub copr_repos: Vec<String>,
     pub system_config: SystemConfigManifest,
     pub image_config: ImageConfigManifest,
+    /// GSettings; locked ones are written to the dconf system database
+    pub gsettings: GSettingsManifest,
     pub shims: Vec<Shim>,
     pub has_external_rpms: bool,
     pub vendor_artifacts: VendorArtifactsManifest,
+    /// Mount dnf's cache as a BuildKit cache so local rebuilds reuse
+    /// downloaded packages. Off by default so CI output is reproducible.
+    pub cache_mounts: bool,
+    /// Per-repo package hashes (external-repo-hashes.json), used as the
+    /// dl-* stages' cache-busting ARG defaults
+    pub cache_epochs: BTreeMap<String, String>,
+}
+
+impl ContainerfileGeneratorInput {
+    /// The input for image variant `name`: its base image, and only the
+    /// modules and packages it keeps. Everything else is shared, so the
+    /// variant's Containerfile differs from the default one only there.
+    pub fn for_variant(&self, name: &str) -> Result<Self> {
+        let variant = self.image_config.variant(name)?;
+        let keep = |pkg: &String| !variant.exclude_packages.contains(pkg);
+
+        let mut input = self.clone();
+        input.image_config = self.image_config.for_variant(variant);
+        input.packages.retain(keep);
+        input.groups.retain(keep);
+        input.pins.retain(|pkg, _| keep(pkg));
+        input.package_arches.retain(|pkg, _| keep(pkg));
+        Ok(input)
+    }
+}
+
+/// Directory the split layout writes its part files to, next to the Containerfile.
+pub const SPLIT_DIR: &str = "Containerfile.d";
+
+/// One per-concern file of a split Containerfile (`Containerfile.d/<name>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerfilePart {
+    pub name: &'static str,
+    pub content: String,
 }
 
 /// Generate the full Containerfile from manifests.
 pub fn generate_full_containerfile(input: &ContainerfileGeneratorInput) -> String {
-    let mut lines = Vec::new();
+    generate_containerfile_parts(input)
+        .into_iter()
+        .map(|part| part.content)
+        .collect()
+}
+
+/// Generate the Containerfile as per-concern parts.
+///
+/// Concatenating the parts in order gives exactly
+/// [`generate_full_containerfile`]'s output. Parts with nothing to emit
+/// (e.g. no vendor artifacts) are left out.
+pub fn generate_containerfile_parts(input: &ContainerfileGeneratorInput) -> Vec<ContainerfilePart> {
+    let mut parts = Vec::new();
+    let mut part = |name: &'static str, emit: &dyn Fn(&mut Vec<String>)| {
+        let mut lines = Vec::new();
+        emit(&mut lines);
+        if !lines.is_empty() {
+            parts.push(ContainerfilePart {
+                name,
+                content: lines.join("\n") + "\n",
+            });
+        }
+    };
+
+    part("10-base", &|lines| {
+        emit_tools_stage(lines);
+        emit_base_stage(lines, input.image_config.base_image());
+    });
+    part("20-downloads", &|lines| {
+        emit_dl_stages(
+            lines,
+            &input.external_repos,
+            &input.cache_epochs,
+            input.cache_mounts,
+        );
+    });
+    part("30-install", &|lines| {
+        emit_install_stages(lines, &input.external_repos);
+        emit_bundled_stage(lines, &input.external_repos);
+    });
+    part("40-vendor", &|lines| {
+        emit_vendor_artifact_stages(lines, &input.vendor_artifacts);
+        emit_vendor_bundled_stage(lines, &input.vendor_artifacts);
+    });
+    part("50-upstream", &|lines| {
+        emit_fetch_stages(lines, &input.upstreams);
+        emit_script_stages(lines, &input.upstreams);
+        emit_wrapper_build_stage(lines, &input.image_config);
+    });
+    part("60-config", &|lines| {
+        emit_collect_config(lines, &input.image_config, &input.gsettings);
+        emit_collect_outputs(lines, &input.upstreams, &input.image_config);
+    });
+    part("90-image", &|lines| {
+        emit_image_assembly(lines, input);
+        emit_labels(lines, input);
+    });
 
-    emit_tools_stage(&mut lines);
-    emit_base_stage(&mut lines);
-    emit_dl_stages(&mut lines, &input.external_repos);
-    emit_install_stages(&mut lines, &input.external_repos);
-    emit_bundled_stage(&mut lines, &input.external_repos);
-    emit_vendor_artifact_stages(&mut lines, &input.vendor_artifacts);
-    emit_vendor_bundled_stage(&mut lines, &input.vendor_artifacts);
-    emit_fetch_stages(&mut lines, &input.upstreams);
-    emit_script_stages(&mut lines, &input.upstreams);
-    emit_wrapper_build_stage(&mut lines, &input.image_config);
-    emit_collect_config(&mut lines, &input.image_config);
-    emit_collect_outputs(&mut lines, &input.upstreams, &input.image_config);
-    emit_image_assembly(&mut lines, input);
-
-    let mut result = lines.join("\n");
-    if !result.is_empty() && !result.ends_with('\n') {
-        result.push('\n');
-    }
-    result
+    parts
 }
 
 fn emit_managed_section(lines: &mut Vec<String>, section: Section, content: &[String]) {
@@ -348,10 +806,10 @@ fn emit_tools_stage(lines: &mut Vec<String>) {
     lines.push("COPY scripts/bkt-build /bkt-build".to_string());
 }
 
-fn emit_base_stage(lines: &mut Vec<String>) {
+fn emit_base_stage(lines: &mut Vec<String>, base_image: &str) {
     lines.push("".to_string());
     lines.push(section_header("Base stage (repos configured)"));
-    lines.push("FROM ghcr.io/ublue-os/bazzite-gnome:stable AS base".to_string());
+    lines.push(format!("FROM {} AS base", base_image));
     lines.push("COPY --from=tools /bkt-build /usr/bin/bkt-build".to_string());
     lines.push("COPY manifests/external-repos.json /tmp/external-repos.json".to_string());
     lines.push(format!("RUN set -eu; {}", LINE_CONT));
@@ -362,23 +820,21 @@ fn emit_base_stage(lines: &mut Vec<String>) {
     lines.push("    bkt-build setup-repos".to_string());
 }
 
-/// Convert a repo name to a Dockerfile ARG name for cache busting.
-/// e.g. "microsoft-edge" -> "CACHE_EPOCH_MICROSOFT_EDGE"
-fn cache_arg_name(repo_name: &str) -> String {
-    let sanitized: String = repo_name
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() {
-                c.to_ascii_uppercase()
-            } else {
-                '_'
-            }
-        })
-        .collect();
-    format!("CACHE_EPOCH_{sanitized}")
-}
-
-fn emit_dl_stages(lines: &mut Vec<String>, repos: &ExternalReposManifest) {
+/// Emit one download stage per external repo.
+///
+/// Each stage's `CACHE_EPOCH_<REPO>` ARG defaults to the repo's recorded
+/// package hash (`0` until one is recorded), so it's rebuilt when that
+/// repo's packages change and not otherwise.
+///
+/// With `cache_mounts`, dnf's metadata and package cache is mounted rather
+/// than /rpms itself: the install stages `COPY --from=dl-*` out of /rpms,
+/// and a cache mount's contents never land in the stage's layer.
+fn emit_dl_stages(
+    lines: &mut Vec<String>,
+    repos: &ExternalReposManifest,
+    cache_epochs: &BTreeMap<String, String>,
+    cache_mounts: bool,
+) {
     lines.push("".to_string());
     lines.push(section_header(
         "RPM download stages (parallel, each downloads from one external repo)",
@@ -390,8 +846,16 @@ fn emit_dl_stages(lines: &mut Vec<String>, repos: &ExternalReposManifest) {
             lines.push("".to_string());
         }
         lines.push(format!("FROM base AS dl-{}", repo.name));
-        lines.push(format!("ARG {}=0", cache_arg_name(&repo.name)));
-        lines.push(format!("RUN bkt-build download-rpms {}", repo.name));
+        lines.push(format!(
+            "ARG {}={}",
+            cache_arg_name(&repo.name),
+            cache_epochs.get(&repo.name).map_or("0", String::as_str)
+        ));
+        lines.push(format!(
+            "RUN {}bkt-build download-rpms {}",
+            run_mount(cache_mounts),
+            repo.name
+        ));
     }
 }
 
@@ -643,7 +1107,37 @@ fn emit_fetch_stages(lines: &mut Vec<String>, upstreams: &UpstreamManifest) {
         }
         lines.push(format!("FROM base AS fetch-{}", upstream.name));
         lines.push("COPY upstream/manifest.json /tmp/upstream-manifest.json".to_string());
-        lines.push(format!("RUN bkt-build fetch {}", upstream.name));
+        if let Some(key) = upstream.gpg_key_path() {
+            lines.push(format!("COPY {} /tmp/upstream-keys/{}", key, key));
+        }
+
+        if upstream.arches.is_empty() {
+            lines.push(format!("RUN bkt-build fetch {}", upstream.name));
+            continue;
+        }
+
+        // Arch-restricted: stage outputs under /out/ so other arches produce
+        // an empty directory instead of a missing COPY source.
+        lines.push("ARG TARGETARCH".to_string());
+        lines.push(format!("RUN set -eu; {}", LINE_CONT));
+        lines.push(format!("    mkdir -p /out; {}", LINE_CONT));
+        lines.push(format!(
+            "    case \"$TARGETARCH\" in {}) {}",
+            target_arch_pattern(&upstream.arches),
+            LINE_CONT
+        ));
+        lines.push(format!(
+            "        bkt-build fetch {}; {}",
+            upstream.name, LINE_CONT
+        ));
+        for output in upstream_outputs(upstream.install.as_ref()) {
+            lines.push(format!(
+                "        cp -a --parents {} /out/; {}",
+                output, LINE_CONT
+            ));
+        }
+        lines.push(format!("        ;; {}", LINE_CONT));
+        lines.push("    esac".to_string());
     }
 }
 
@@ -676,8 +1170,19 @@ fn emit_script_stages(lines: &mut Vec<String>, upstreams: &UpstreamManifest) {
 
         lines.push(format!("FROM base AS {}", stage));
         lines.push("COPY upstream/manifest.json /tmp/upstream-manifest.json".to_string());
+        if !upstream.arches.is_empty() {
+            lines.push("ARG TARGETARCH".to_string());
+        }
         if let Some(script) = script_lines {
             lines.push("RUN <<'EOF'".to_string());
+            if !upstream.arches.is_empty() {
+                // Leave an empty /out/ on unsupported arches
+                lines.push("mkdir -p /out".to_string());
+                lines.push(format!(
+                    "case \"$TARGETARCH\" in {}) ;; *) exit 0 ;; esac",
+                    target_arch_pattern(&upstream.arches)
+                ));
+            }
             lines.extend(script.into_iter());
 
             // Collect outputs into /out/ for single-layer COPY (RFC-0050)
@@ -712,8 +1217,14 @@ fn emit_script_stages(lines: &mut Vec<String>, upstreams: &UpstreamManifest) {
             }
 
             lines.push("EOF".to_string());
-        } else {
+        } else if upstream.arches.is_empty() {
             lines.push(format!("RUN bkt-build fetch {}", upstream.name));
+        } else {
+            lines.push(format!(
+                "RUN mkdir -p /out && case \"$TARGETARCH\" in {}) bkt-build fetch {} ;; esac",
+                target_arch_pattern(&upstream.arches),
+                upstream.name
+            ));
         }
     }
 }
@@ -723,7 +1234,11 @@ fn emit_script_stages(lines: &mut Vec<String>, upstreams: &UpstreamManifest) {
 /// This stage assembles all static configuration files via COPY instructions
 /// only (no RUN — FROM scratch has no shell). The image stage imports the
 /// result with a single `COPY --from=collect-config / /`.
-fn emit_collect_config(lines: &mut Vec<String>, image_config: &ImageConfigManifest) {
+fn emit_collect_config(
+    lines: &mut Vec<String>,
+    image_config: &ImageConfigManifest,
+    gsettings: &GSettingsManifest,
+) {
     lines.push("".to_string());
     lines.push(section_header("Config collector (parallel, FROM scratch)"));
     lines.push("FROM scratch AS collect-config".to_string());
@@ -750,10 +1265,44 @@ fn emit_collect_config(lines: &mut Vec<String>, image_config: &ImageConfigManife
             }
             // Run and SystemdEnable modules need a shell — handled in image stage
             ImageModule::Run { .. } | ImageModule::SystemdEnable { .. } => {}
-            // Wrapper modules are handled separately (built binaries copied in)
-            ImageModule::Wrapper { .. } => {}
+            // Wrapper binaries are built separately; only their slice lands here
+            ImageModule::Wrapper { slice, .. } => {
+                let Some(unit) = module
+                    .wrapper_config()
+                    .and_then(|w| w.generate_slice_unit())
+                else {
+                    continue;
+                };
+                lines.push("".to_string());
+                lines.push(format!("# Slice limits for {}", module.name()));
+                lines.push(format!("COPY <<EOF /usr/lib/systemd/user/{}", slice));
+                lines.extend(unit.lines().map(str::to_string));
+                lines.push("EOF".to_string());
+            }
         }
     }
+
+    emit_dconf_locks(lines, gsettings);
+}
+
+/// Emit the dconf keyfile and lock file for locked gsettings.
+///
+/// The consolidated RUN compiles them with `dconf update`.
+fn emit_dconf_locks(lines: &mut Vec<String>, gsettings: &GSettingsManifest) {
+    let (Some(keyfile), Some(locks)) = (gsettings.dconf_keyfile(), gsettings.dconf_locks()) else {
+        return;
+    };
+
+    lines.push("".to_string());
+    lines.push("# Locked gsettings (enforced system-wide)".to_string());
+    for (path, content) in [
+        (GSettingsManifest::DCONF_KEYFILE, keyfile),
+        (GSettingsManifest::DCONF_LOCKS, locks),
+    ] {
+        lines.push(format!("COPY <<EOF {}", path));
+        lines.extend(content.lines().map(str::to_string));
+        lines.push("EOF".to_string());
+    }
 }
 
 /// Collect all RUN-requiring operations from image modules and emit them
@@ -764,6 +1313,7 @@ fn emit_collect_config(lines: &mut Vec<String>, image_config: &ImageConfigManife
 fn emit_consolidated_run(
     lines: &mut Vec<String>,
     image_config: &ImageConfigManifest,
+    gsettings: &GSettingsManifest,
     shims: &[Shim],
 ) {
     let mut commands: Vec<String> = Vec::new();
@@ -800,6 +1350,10 @@ fn emit_consolidated_run(
 
     commands.extend(generate_host_shim_commands(shims));
 
+    if gsettings.locked().next().is_some() {
+        commands.push("dconf update".to_string());
+    }
+
     for module in &image_config.modules {
         if let ImageModule::OptionalFeature {
             arg,
@@ -873,6 +1427,12 @@ fn emit_collect_outputs(
         )
     });
     for upstream in &fetch_upstreams {
+        // Arch-restricted stages collect into /out/ (empty on other arches)
+        if !upstream.arches.is_empty() {
+            lines.push(format!("COPY --from=fetch-{} /out/ /", upstream.name));
+            continue;
+        }
+
         let install = upstream.install.as_ref();
         let outputs = upstream_outputs(install);
         for output in outputs {
@@ -912,7 +1472,7 @@ fn emit_image_assembly(lines: &mut Vec<String>, input: &ContainerfileGeneratorIn
     lines.push("FROM base AS image".to_string());
     lines.push("".to_string());
 
-    let copr = generate_copr_repos(&input.copr_repos);
+    let copr = generate_copr_repos(&input.copr_repos, input.cache_mounts);
     emit_managed_section(lines, Section::CoprRepos, &copr);
     lines.push("".to_string());
 
@@ -927,7 +1487,14 @@ fn emit_image_assembly(lines: &mut Vec<String>, input: &ContainerfileGeneratorIn
     lines.push("".to_string());
 
     // System packages only (external RPMs handled via install stages)
-    let pkgs = generate_system_packages(&input.packages, false);
+    let pkgs = generate_system_packages(
+        &input.packages,
+        &input.groups,
+        &input.pins,
+        &input.package_arches,
+        false,
+        input.cache_mounts,
+    );
     emit_managed_section(lines, Section::SystemPackages, &pkgs);
     lines.push("".to_string());
 
@@ -977,7 +1544,7 @@ fn emit_image_assembly(lines: &mut Vec<String>, input: &ContainerfileGeneratorIn
     }
 
     // Consolidated RUN for all post-overlay operations
-    emit_consolidated_run(lines, &input.image_config, &input.shims);
+    emit_consolidated_run(lines, &input.image_config, &input.gsettings, &input.shims);
     lines.push("".to_string());
 
     // Font cache after all fonts and config are in place
@@ -988,6 +1555,80 @@ fn emit_image_assembly(lines: &mut Vec<String>, input: &ContainerfileGeneratorIn
     emit_rpm_snapshot(lines);
 }
 
+/// OCI labels describing what went into the image, sorted by key.
+///
+/// Values are derived from manifest content only (no timestamps), so
+/// unchanged manifests produce identical labels.
+pub fn image_labels(input: &ContainerfileGeneratorInput) -> BTreeMap<String, String> {
+    let system_packages = serde_json::json!({
+        "packages": input.packages,
+        "groups": input.groups,
+        "pins": input.pins,
+        "arches": input.package_arches,
+        "copr_repos": input.copr_repos,
+    });
+    let manifests = [
+        ("external-repos", content_sha256(&input.external_repos)),
+        ("upstream", content_sha256(&input.upstreams)),
+        ("system-packages", content_sha256(&system_packages)),
+        ("system-config", content_sha256(&input.system_config)),
+        ("image-config", content_sha256(&input.image_config)),
+        ("shims", content_sha256(&input.shims)),
+        ("vendor-artifacts", content_sha256(&input.vendor_artifacts)),
+    ];
+
+    let mut labels: BTreeMap<String, String> = manifests
+        .into_iter()
+        .map(|(name, hash)| (format!("{LABEL_PREFIX}.manifest.{name}.sha256"), hash))
+        .collect();
+
+    let mut upstreams: Vec<String> = input
+        .upstreams
+        .upstreams
+        .iter()
+        .map(|u| format!("{}@{}", u.name, u.pinned.version))
+        .collect();
+    upstreams.sort();
+    labels.insert(format!("{LABEL_PREFIX}.upstreams"), upstreams.join(","));
+    labels.insert(
+        format!("{LABEL_PREFIX}.bkt.version"),
+        env!("CARGO_PKG_VERSION").to_string(),
+    );
+    labels.insert(
+        format!("{LABEL_PREFIX}.labels.schema-version"),
+        LABEL_SCHEMA_VERSION.to_string(),
+    );
+    labels
+}
+
+/// sha256 of a manifest's JSON. Going through `serde_json::Value` sorts
+/// object keys, so `HashMap` fields hash the same on every run.
+fn content_sha256<T: Serialize>(manifest: &T) -> String {
+    let value = serde_json::to_value(manifest).expect("manifests serialize to JSON");
+    hex::encode(Sha256::digest(value.to_string().as_bytes()))
+}
+
+/// Emit the image labels as one `LABEL` instruction at the end of the final
+/// stage, where a changed value only replaces the image config.
+fn emit_labels(lines: &mut Vec<String>, input: &ContainerfileGeneratorInput) {
+    let labels = image_labels(input);
+    lines.push("".to_string());
+    lines.push(section_header(
+        "Image labels (manifest hashes and versions)",
+    ));
+    let last = labels.len().saturating_sub(1);
+    for (idx, (key, value)) in labels.iter().enumerate() {
+        let keyword = if idx == 0 { "LABEL" } else { "     " };
+        let cont = if idx == last {
+            String::new()
+        } else {
+            format!(" {LINE_CONT}")
+        };
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        lines.push(format!("{keyword} {key}=\"{value}\"{cont}"));
+    }
+}
+
 fn emit_tmpfiles(lines: &mut Vec<String>, repos: &ExternalReposManifest) {
     let opt_entries = opt_symlink_entries(repos);
 
@@ -1144,6 +1785,28 @@ fn upstream_outputs(install: Option<&InstallConfig>) -> Vec<String> {
     }
 }
 
+/// Map a manifest architecture name to its BuildKit `TARGETARCH` value.
+///
+/// Manifests use RPM-style names (`x86_64`, `aarch64`); BuildKit uses OCI
+/// names (`amd64`, `arm64`). Unknown names pass through unchanged.
+pub fn target_arch(arch: &str) -> &str {
+    match arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "ppc64le" => "ppc64le",
+        "s390x" => "s390x",
+        other => other,
+    }
+}
+
+/// Build a shell `case` pattern (`amd64|arm64`) matching the given arches.
+fn target_arch_pattern(arches: &[String]) -> String {
+    let mut names: Vec<&str> = arches.iter().map(|a| target_arch(a)).collect();
+    names.sort();
+    names.dedup();
+    names.join("|")
+}
+
 fn ensure_trailing_slash(path: &str) -> String {
     if path.ends_with('/') {
         path.to_string()
@@ -1165,31 +1828,116 @@ fn opt_symlink_entries(repos: &ExternalReposManifest) -> Vec<String> {
 /// When `has_external_rpms` is true, the install line starts with
 /// `/tmp/rpms/*.rpm` to install pre-downloaded RPMs from dl-* stages
 /// before the Fedora-native packages.
-pub fn generate_system_packages(packages: &[String], has_external_rpms: bool) -> Vec<String> {
-    if packages.is_empty() && !has_external_rpms {
+///
+/// `groups` are installed as `@group` in the same transaction, listed before
+/// the individual packages.
+///
+/// Packages with an entry in `pins` are emitted as `name-version` so dnf
+/// installs exactly the pinned version.
+///
+/// Packages with an entry in `arches` are installed in a separate RUN gated
+/// on `TARGETARCH`, one per distinct arch set, so the same Containerfile
+/// builds on every architecture.
+///
+/// With `cache_mounts`, each install mounts dnf's cache and keeps downloaded
+/// packages in it instead of running `dnf clean all`, which would empty the
+/// cache mount.
+pub fn generate_system_packages(
+    packages: &[String],
+    groups: &[String],
+    pins: &BTreeMap<String, String>,
+    arches: &BTreeMap<String, Vec<String>>,
+    has_external_rpms: bool,
+    cache_mounts: bool,
+) -> Vec<String> {
+    if packages.is_empty() && groups.is_empty() && !has_external_rpms {
         return vec!["# No packages configured".to_string()];
     }
 
+    let mut group_specs: Vec<String> = groups.iter().map(|g| group_spec(g)).collect();
+    group_specs.sort();
+    group_specs.dedup();
+
     let mut sorted_packages: Vec<_> = packages.iter().collect();
     sorted_packages.sort();
 
+    let pkg_spec = |pkg: &str| match pins.get(pkg) {
+        Some(version) => format!("{}-{}", pkg, version),
+        None => pkg.to_string(),
+    };
+
+    // Group arch-restricted packages by their TARGETARCH pattern
+    let mut common = Vec::new();
+    let mut by_arch: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for pkg in sorted_packages {
+        match arches.get(pkg.as_str()).filter(|a| !a.is_empty()) {
+            Some(pkg_arches) => by_arch
+                .entry(target_arch_pattern(pkg_arches))
+                .or_default()
+                .push(pkg_spec(pkg)),
+            None => common.push(pkg_spec(pkg)),
+        }
+    }
+
+    let install = if cache_mounts {
+        "dnf install -y --setopt=keepcache=True"
+    } else {
+        "dnf install -y"
+    };
+
     let mut lines = Vec::new();
-    lines.push("RUN dnf install -y \\".to_string());
 
-    if has_external_rpms {
-        lines.push("    /tmp/rpms/*.rpm \\".to_string());
+    if !common.is_empty() || !group_specs.is_empty() || has_external_rpms {
+        lines.push(format!("RUN {}{} \\", run_mount(cache_mounts), install));
+
+        let mut specs = Vec::new();
+        if has_external_rpms {
+            specs.push("/tmp/rpms/*.rpm".to_string());
+        }
+        specs.extend(group_specs);
+        specs.extend(common);
+
+        for (i, spec) in specs.iter().enumerate() {
+            if cache_mounts && i == specs.len() - 1 {
+                lines.push(format!("    {}", spec));
+            } else {
+                lines.push(format!("    {} \\", spec));
+            }
+        }
+        if !cache_mounts {
+            lines.push("    && dnf clean all".to_string());
+        }
     }
 
-    for pkg in sorted_packages.iter() {
-        lines.push(format!("    {} \\", pkg));
+    if !by_arch.is_empty() {
+        lines.push("ARG TARGETARCH".to_string());
+    }
+    for (pattern, specs) in &by_arch {
+        lines.push(format!(
+            "RUN {}case \"$TARGETARCH\" in {}) \\",
+            run_mount(cache_mounts),
+            pattern
+        ));
+        lines.push(format!("        {} \\", install));
+        for spec in specs {
+            lines.push(format!("        {} \\", spec));
+        }
+        if cache_mounts {
+            lines.push("        ;; \\".to_string());
+        } else {
+            lines.push("        && dnf clean all ;; \\".to_string());
+        }
+        lines.push("    esac".to_string());
     }
-    lines.push("    && dnf clean all".to_string());
 
     lines
 }
 
 /// Generate the COPR_REPOS section content from a list of COPR repos
-pub fn generate_copr_repos(repos: &[String]) -> Vec<String> {
+///
+/// With `cache_mounts`, the enable block mounts dnf's cache so the COPR
+/// metadata it fetches is reused by later builds.
+pub fn generate_copr_repos(repos: &[String], cache_mounts: bool) -> Vec<String> {
     if repos.is_empty() {
         return vec!["# No COPR repositories configured".to_string()];
     }
@@ -1198,7 +1946,7 @@ pub fn generate_copr_repos(repos: &[String]) -> Vec<String> {
     sorted_repos.sort();
 
     let mut lines = Vec::new();
-    lines.push("RUN set -eu; \\".to_string());
+    lines.push(format!("RUN {}set -eu; \\", run_mount(cache_mounts)));
 
     for (i, repo) in sorted_repos.iter().enumerate() {
         if i < sorted_repos.len() - 1 {
@@ -1211,6 +1959,15 @@ pub fn generate_copr_repos(repos: &[String]) -> Vec<String> {
     lines
 }
 
+/// The `--mount` flag (plus trailing space) to put after `RUN`, if any.
+fn run_mount(cache_mounts: bool) -> String {
+    if cache_mounts {
+        format!("{} ", DNF_CACHE_MOUNT)
+    } else {
+        String::new()
+    }
+}
+
 /// Generate the HOST_SHIMS section content from a list of shims.
 ///
 /// Creates shell commands that:
@@ -1233,12 +1990,14 @@ pub fn generate_host_shim_commands(shims: &[Shim]) -> Vec<String> {
     commands.push(format!("mkdir -p {} {}", shims_dir, bin_dir));
 
     for shim in sorted_shims.iter() {
-        let host_cmd = shim.host_cmd();
-        // Use shlex for proper POSIX-compliant shell quoting
-        let quoted = shlex::try_quote(host_cmd).unwrap_or_else(|_| host_cmd.into());
-
-        // Generate the shim script content
-        let script_content = format!("#!/bin/bash\nexec flatpak-spawn --host {} \"$@\"\n", quoted);
+        // Generate the shim script content (shlex-quoted flatpak-spawn call)
+        let script_content = match shim.script() {
+            Ok(script) => script,
+            Err(e) => {
+                tracing::warn!("Skipping shim {}: {:#}", shim.name, e);
+                continue;
+            }
+        };
 
         // Base64 encode to avoid heredoc parsing issues in Dockerfile
         let encoded = BASE64_STANDARD.encode(script_content.as_bytes());
@@ -1308,6 +2067,58 @@ COPY . /app
         assert_eq!(pkg_content.len(), 3);
     }
 
+    #[test]
+    fn test_section_items_read_back_generated_content() {
+        let mut arches = BTreeMap::new();
+        arches.insert("edk2-ovmf".to_string(), vec!["x86_64".to_string()]);
+        let packages = vec!["htop".to_string(), "edk2-ovmf".to_string()];
+        let lines = generate_system_packages(&packages, &[], &BTreeMap::new(), &arches, true, true);
+        assert_eq!(
+            Section::SystemPackages.items(&lines),
+            vec!["edk2-ovmf", "htop"]
+        );
+
+        let repos = vec!["atim/starship".to_string(), "che/nerd-fonts".to_string()];
+        assert_eq!(
+            Section::CoprRepos.items(&generate_copr_repos(&repos, false)),
+            repos
+        );
+
+        let mut manifest = SystemConfigManifest::default();
+        manifest.systemd = Some(crate::manifest::system_config::SystemdConfig {
+            enable: vec!["keyd.service".to_string(), "sshd.service".to_string()],
+            mask: vec!["packagekit.service".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            Section::SystemdUnits.items(&generate_systemd_units(&manifest)),
+            vec![
+                "enable keyd.service",
+                "enable sshd.service",
+                "mask packagekit.service"
+            ]
+        );
+
+        assert!(
+            Section::KernelArguments
+                .items(&["# No kernel arguments configured".to_string()])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_split_managed_separates_blocks_from_the_rest() {
+        let content = "FROM fedora:41\n\
+                       # === COPR_REPOS (managed by bkt) ===\n\
+                       # No COPR repositories configured\n\
+                       # === END COPR_REPOS ===\n\
+                       RUN echo done\n";
+        let (blocks, unmanaged) = split_managed(content).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].section, Section::CoprRepos);
+        assert_eq!(unmanaged, vec!["FROM fedora:41", "RUN echo done"]);
+    }
+
     #[test]
     fn test_update_section() {
         let content = r#"FROM fedora:41
@@ -1321,7 +2132,14 @@ COPY . /app
 
         let mut editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
 
-        let new_content = generate_system_packages(&["htop".to_string(), "vim".to_string()], false);
+        let new_content = generate_system_packages(
+            &["htop".to_string(), "vim".to_string()],
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            false,
+            false,
+        );
         editor.update_section(Section::SystemPackages, new_content);
 
         let rendered = editor.render();
@@ -1330,15 +2148,228 @@ COPY . /app
     }
 
     #[test]
-    fn test_generate_system_packages() {
-        let packages = vec!["vim".to_string(), "htop".to_string(), "curl".to_string()];
-        let lines = generate_system_packages(&packages, false);
+    fn test_upsert_section_creates_missing_section_in_order() {
+        let content = r#"FROM fedora:41
 
-        assert!(lines[0].contains("dnf install"));
-        // Should be sorted alphabetically
-        assert!(lines[1].contains("curl"));
-        assert!(lines[2].contains("htop"));
-        assert!(lines[3].contains("vim"));
+# === COPR_REPOS (managed by bkt) ===
+RUN dnf copr enable -y foo/bar
+# === END COPR_REPOS ===
+
+# === SYSTEM_PACKAGES (managed by bkt) ===
+RUN dnf install -y htop
+# === END SYSTEM_PACKAGES ===
+
+COPY . /app
+"#;
+
+        let mut editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
+        let created = editor.upsert_section(
+            Section::KernelArguments,
+            vec!["RUN rpm-ostree kargs --append=quiet".to_string()],
+        );
+        assert!(created);
+
+        let rendered = editor.render();
+        let copr = rendered.find("# === END COPR_REPOS ===").unwrap();
+        let kargs = rendered.find("# === KERNEL_ARGUMENTS").unwrap();
+        let pkgs = rendered.find("# === SYSTEM_PACKAGES").unwrap();
+        assert!(copr < kargs && kargs < pkgs);
+        assert!(rendered.contains(
+            "# === END COPR_REPOS ===\n\n# === KERNEL_ARGUMENTS (managed by bkt) ===\n\
+             RUN rpm-ostree kargs --append=quiet\n# === END KERNEL_ARGUMENTS ===\n\n"
+        ));
+
+        // Second upsert updates in place rather than adding another block
+        assert!(!editor.upsert_section(Section::KernelArguments, vec!["# none".to_string()]));
+        assert_eq!(editor.render().matches("# === KERNEL_ARGUMENTS").count(), 1);
+    }
+
+    #[test]
+    fn test_upsert_section_without_managed_sections_appends() {
+        let content = "FROM fedora:41\nRUN echo hi\n";
+        let mut editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
+        editor.upsert_section(Section::SystemdUnits, vec!["RUN true".to_string()]);
+
+        assert_eq!(
+            editor.render(),
+            "FROM fedora:41\nRUN echo hi\n\n# === SYSTEMD_UNITS (managed by bkt) ===\n\
+             RUN true\n# === END SYSTEMD_UNITS ===\n"
+        );
+    }
+
+    #[test]
+    fn test_heredocs_opened() {
+        let heredoc = |terminator: &str, strip_tabs| Heredoc {
+            terminator: terminator.to_string(),
+            strip_tabs,
+        };
+        assert_eq!(heredocs_opened("RUN <<EOF"), vec![heredoc("EOF", false)]);
+        assert_eq!(
+            heredocs_opened("run <<-'SCRIPT' bash"),
+            vec![heredoc("SCRIPT", true)]
+        );
+        assert_eq!(
+            heredocs_opened("COPY <<one <<\"two\" /dest/"),
+            vec![heredoc("one", false), heredoc("two", false)]
+        );
+        assert_eq!(heredocs_opened("RUN cat <<< \"$x\""), vec![]);
+        assert_eq!(heredocs_opened("# RUN <<EOF"), vec![]);
+        assert_eq!(heredocs_opened("ENV A=<<EOF"), vec![]);
+    }
+
+    #[test]
+    fn test_parse_ignores_markers_inside_heredocs() {
+        let content = "FROM fedora:41\n\
+RUN <<'EOF'\n\
+echo '# === SYSTEM_PACKAGES (managed by bkt) ==='\n\
+# === COPR_REPOS (managed by bkt) ===\n\
+  indented   \n\
+EOF\n\
+RUN <<-END bash\n\
+\t# === END COPR_REPOS ===\n\
+\tEND\n\
+# === SYSTEM_PACKAGES (managed by bkt) ===\n\
+RUN dnf install -y vim\n\
+# === END SYSTEM_PACKAGES ===\n";
+
+        let editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
+        let (blocks, unmanaged) = split_managed(content).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].section, Section::SystemPackages);
+        assert_eq!(blocks[0].content, vec!["RUN dnf install -y vim"]);
+        assert_eq!(unmanaged.len(), 9);
+        assert_eq!(editor.render(), content);
+    }
+
+    #[test]
+    fn test_heredoc_right_before_managed_section_stays_whole() {
+        let content = "FROM fedora:41\n\
+RUN <<EOF\n\
+RUN bootc container lint\n\
+EOF\n\
+# === COPR_REPOS (managed by bkt) ===\n\
+RUN dnf copr enable -y foo/bar\n\
+# === END COPR_REPOS ===\n\
+RUN bootc container lint\n";
+
+        let mut editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
+        assert_eq!(editor.render(), content);
+
+        // The anchor only matches outside the heredoc body
+        editor
+            .insert_section(
+                Section::SystemdUnits,
+                vec!["RUN true".to_string()],
+                &SectionAnchor::Before("RUN bootc".to_string()),
+            )
+            .unwrap();
+        let rendered = editor.render();
+        assert!(
+            rendered.starts_with("FROM fedora:41\nRUN <<EOF\nRUN bootc container lint\nEOF\n"),
+            "{rendered}"
+        );
+        assert!(
+            rendered.ends_with(
+                "# === SYSTEMD_UNITS (managed by bkt) ===\nRUN true\n\
+                 # === END SYSTEMD_UNITS ===\n\nRUN bootc container lint\n"
+            ),
+            "{rendered}"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_anchors() {
+        let content = r#"FROM fedora:41
+# === COPR_REPOS (managed by bkt) ===
+RUN dnf copr enable -y foo/bar
+# === END COPR_REPOS ===
+RUN echo custom
+RUN bootc container lint
+"#;
+
+        let mut editor = ContainerfileEditor::parse(PathBuf::from("test"), content).unwrap();
+        editor
+            .insert_section(
+                Section::SystemdUnits,
+                vec!["RUN true".to_string()],
+                &SectionAnchor::Before("RUN bootc".to_string()),
+            )
+            .unwrap();
+        editor
+            .insert_section(
+                Section::KernelArguments,
+                vec!["RUN kargs".to_string()],
+                &SectionAnchor::AfterLastManaged,
+            )
+            .unwrap();
+        editor
+            .insert_section(
+                Section::SystemPackages,
+                vec!["RUN dnf".to_string()],
+                &SectionAnchor::EndOfFile,
+            )
+            .unwrap();
+
+        let rendered = editor.render();
+        let order: Vec<usize> = [
+            "# === COPR_REPOS",
+            "RUN echo custom",
+            "# === SYSTEMD_UNITS",
+            "# === KERNEL_ARGUMENTS",
+            "RUN bootc container lint",
+            "# === SYSTEM_PACKAGES",
+        ]
+        .iter()
+        .map(|needle| rendered.find(needle).unwrap())
+        .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{rendered}");
+
+        // Inserting an existing section is an error
+        assert!(
+            editor
+                .insert_section(Section::CoprRepos, vec![], &SectionAnchor::EndOfFile)
+                .is_err()
+        );
+
+        // Rendered output parses back to the same sections
+        let reparsed = ContainerfileEditor::parse(PathBuf::from("test"), &rendered).unwrap();
+        for section in Section::ORDERED {
+            assert_eq!(
+                reparsed.get_section_content(section),
+                editor.get_section_content(section)
+            );
+        }
+        assert_eq!(reparsed.render(), rendered);
+    }
+
+    #[test]
+    fn test_is_placeholder_content() {
+        assert!(is_placeholder_content(&[
+            "# No packages configured".to_string()
+        ]));
+        assert!(!is_placeholder_content(&generate_copr_repos(
+            &["foo/bar".to_string()],
+            false
+        )));
+    }
+
+    #[test]
+    fn test_generate_system_packages() {
+        let packages = vec!["vim".to_string(), "htop".to_string(), "curl".to_string()];
+        let lines = generate_system_packages(
+            &packages,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            false,
+            false,
+        );
+
+        assert!(lines[0].contains("dnf install"));
+        // Should be sorted alphabetically
+        assert!(lines[1].contains("curl"));
+        assert!(lines[2].contains("htop"));
+        assert!(lines[3].contains("vim"));
     }
 
     #[test]
@@ -1354,7 +2385,7 @@ COPY . /app
     #[test]
     fn test_generate_copr_repos() {
         let repos = vec!["atim/starship".to_string(), "someone/thing".to_string()];
-        let lines = generate_copr_repos(&repos);
+        let lines = generate_copr_repos(&repos, false);
 
         assert!(lines[0].contains("set -eu"));
         // Should be sorted alphabetically
@@ -1389,7 +2420,14 @@ COPY . /app
     #[test]
     fn test_generate_system_packages_empty() {
         let packages: Vec<String> = vec![];
-        let lines = generate_system_packages(&packages, false);
+        let lines = generate_system_packages(
+            &packages,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            false,
+            false,
+        );
 
         assert_eq!(lines.len(), 1);
         assert_eq!(lines[0], "# No packages configured");
@@ -1399,7 +2437,14 @@ COPY . /app
     fn test_generate_system_packages_format() {
         // Verify exact output format including trailing backslashes
         let packages = vec!["pkg1".to_string(), "pkg2".to_string()];
-        let lines = generate_system_packages(&packages, false);
+        let lines = generate_system_packages(
+            &packages,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            false,
+            false,
+        );
 
         assert_eq!(lines.len(), 4);
         assert_eq!(lines[0], "RUN dnf install -y \\");
@@ -1408,10 +2453,148 @@ COPY . /app
         assert_eq!(lines[3], "    && dnf clean all");
     }
 
+    #[test]
+    fn test_generate_system_packages_groups_before_packages() {
+        let packages = vec!["htop".to_string()];
+        let groups = vec![
+            "development-tools".to_string(),
+            "@c-development".to_string(),
+        ];
+        let lines = generate_system_packages(
+            &packages,
+            &groups,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            true,
+            false,
+        );
+
+        assert_eq!(
+            lines,
+            vec![
+                "RUN dnf install -y \\",
+                "    /tmp/rpms/*.rpm \\",
+                "    @c-development \\",
+                "    @development-tools \\",
+                "    htop \\",
+                "    && dnf clean all",
+            ]
+        );
+
+        let groups_only = generate_system_packages(
+            &[],
+            &groups,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            false,
+            false,
+        );
+        assert_eq!(groups_only[0], "RUN dnf install -y \\");
+    }
+
+    #[test]
+    fn test_generate_system_packages_with_pins() {
+        let packages = vec!["code".to_string(), "htop".to_string()];
+        let pins = BTreeMap::from([("code".to_string(), "1.95.0".to_string())]);
+        let lines = generate_system_packages(&packages, &[], &pins, &BTreeMap::new(), false, false);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "    code-1.95.0 \\");
+        assert_eq!(lines[2], "    htop \\");
+    }
+
+    #[test]
+    fn test_generate_system_packages_with_arches() {
+        let packages = vec![
+            "htop".to_string(),
+            "steam-devices".to_string(),
+            "qemu-user".to_string(),
+        ];
+        let arches = BTreeMap::from([
+            ("steam-devices".to_string(), vec!["x86_64".to_string()]),
+            (
+                "qemu-user".to_string(),
+                vec!["aarch64".to_string(), "x86_64".to_string()],
+            ),
+        ]);
+        let lines =
+            generate_system_packages(&packages, &[], &BTreeMap::new(), &arches, false, false);
+
+        assert_eq!(
+            lines,
+            vec![
+                "RUN dnf install -y \\",
+                "    htop \\",
+                "    && dnf clean all",
+                "ARG TARGETARCH",
+                "RUN case \"$TARGETARCH\" in amd64) \\",
+                "        dnf install -y \\",
+                "        steam-devices \\",
+                "        && dnf clean all ;; \\",
+                "    esac",
+                "RUN case \"$TARGETARCH\" in amd64|arm64) \\",
+                "        dnf install -y \\",
+                "        qemu-user \\",
+                "        && dnf clean all ;; \\",
+                "    esac",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_system_packages_cache_mounts() {
+        let packages = vec!["htop".to_string(), "steam-devices".to_string()];
+        let arches = BTreeMap::from([("steam-devices".to_string(), vec!["x86_64".to_string()])]);
+        let lines = generate_system_packages(&packages, &[], &BTreeMap::new(), &arches, true, true);
+
+        assert_eq!(
+            lines,
+            vec![
+                "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked dnf install -y --setopt=keepcache=True \\",
+                "    /tmp/rpms/*.rpm \\",
+                "    htop",
+                "ARG TARGETARCH",
+                "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked case \"$TARGETARCH\" in amd64) \\",
+                "        dnf install -y --setopt=keepcache=True \\",
+                "        steam-devices \\",
+                "        ;; \\",
+                "    esac",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_copr_repos_cache_mounts() {
+        let repos = vec!["atim/starship".to_string()];
+
+        assert_eq!(
+            generate_copr_repos(&repos, true),
+            vec![
+                "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked set -eu; \\",
+                "    dnf copr enable -y atim/starship",
+            ]
+        );
+        assert!(!generate_copr_repos(&repos, false)[0].contains("--mount"));
+    }
+
+    #[test]
+    fn test_target_arch_maps_rpm_names() {
+        assert_eq!(target_arch("x86_64"), "amd64");
+        assert_eq!(target_arch("aarch64"), "arm64");
+        assert_eq!(target_arch("arm64"), "arm64");
+    }
+
     #[test]
     fn test_generate_system_packages_with_external_rpms() {
         let packages = vec!["pkg1".to_string(), "pkg2".to_string()];
-        let lines = generate_system_packages(&packages, true);
+        let lines = generate_system_packages(
+            &packages,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            true,
+            false,
+        );
 
         assert_eq!(lines.len(), 5);
         assert_eq!(lines[0], "RUN dnf install -y \\");
@@ -1424,7 +2607,14 @@ COPY . /app
     #[test]
     fn test_generate_system_packages_external_rpms_only() {
         let packages: Vec<String> = vec![];
-        let lines = generate_system_packages(&packages, true);
+        let lines = generate_system_packages(
+            &packages,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            true,
+            false,
+        );
 
         assert_eq!(lines.len(), 3);
         assert_eq!(lines[0], "RUN dnf install -y \\");
@@ -1435,7 +2625,7 @@ COPY . /app
     #[test]
     fn test_generate_copr_repos_empty() {
         let repos: Vec<String> = vec![];
-        let lines = generate_copr_repos(&repos);
+        let lines = generate_copr_repos(&repos, false);
 
         assert_eq!(lines.len(), 1);
         assert_eq!(lines[0], "# No COPR repositories configured");
@@ -1466,17 +2656,11 @@ COPY . /app
     // =========================================================================
 
     fn sample_shim(name: &str) -> Shim {
-        Shim {
-            name: name.to_string(),
-            host: None,
-        }
+        Shim::new(name, None)
     }
 
     fn sample_shim_with_host(name: &str, host: &str) -> Shim {
-        Shim {
-            name: name.to_string(),
-            host: Some(host.to_string()),
-        }
+        Shim::new(name, Some(host.to_string()))
     }
 
     #[test]
@@ -1560,6 +2744,26 @@ COPY . /app
         );
     }
 
+    #[test]
+    fn test_generate_host_shim_commands_with_env_and_workdir() {
+        let mut shim = sample_shim("nmcli");
+        shim.env.insert("LANG".to_string(), "C".to_string());
+        shim.env.insert("NOTE".to_string(), "it's set".to_string());
+        shim.workdir = Some("/var/home".to_string());
+        let commands = generate_host_shim_commands(&[shim]);
+
+        let encoded = commands[1]
+            .strip_prefix("echo '")
+            .and_then(|rest| rest.split('\'').next())
+            .unwrap();
+        let script = String::from_utf8(BASE64_STANDARD.decode(encoded).unwrap()).unwrap();
+        assert_eq!(
+            script,
+            "#!/bin/bash\nexec flatpak-spawn --host '--directory=/var/home' '--env=LANG=C' \
+             \"--env=NOTE=it's set\" nmcli \"$@\"\n"
+        );
+    }
+
     #[test]
     #[test]
     fn test_generate_full_containerfile_contains_sections() {
@@ -1567,15 +2771,18 @@ COPY . /app
             external_repos: ExternalReposManifest::default(),
             upstreams: UpstreamManifest::default(),
             packages: Vec::new(),
+            groups: Vec::new(),
+            pins: BTreeMap::new(),
+            package_arches: BTreeMap::new(),
             copr_repos: Vec::new(),
             system_config: SystemConfigManifest::default(),
-            image_config: ImageConfigManifest {
-                schema: None,
-                modules: Vec::new(),
-            },
+            image_config: ImageConfigManifest::default(),
+            gsettings: GSettingsManifest::default(),
             shims: Vec::new(),
             has_external_rpms: false,
             vendor_artifacts: VendorArtifactsManifest::default(),
+            cache_mounts: false,
+            cache_epochs: BTreeMap::new(),
         };
 
         let output = generate_full_containerfile(&input);
@@ -1586,6 +2793,653 @@ COPY . /app
         assert!(output.contains("# === RPM VERSION SNAPSHOT ==="));
         assert!(output.ends_with('\n'));
     }
+
+    #[test]
+    fn test_generate_full_containerfile_cache_mounts_opt_in() {
+        let mut input = ContainerfileGeneratorInput {
+            external_repos: ExternalReposManifest {
+                schema: None,
+                repos: vec![crate::manifest::ExternalRepo {
+                    name: "code".to_string(),
+                    display_name: "VS Code".to_string(),
+                    baseurl: "https://packages.microsoft.com/yumrepos/vscode".to_string(),
+                    gpg_key: "https://packages.microsoft.com/keys/microsoft.asc".to_string(),
+                    gpg_fingerprint: None,
+                    packages: vec!["code".to_string()],
+                    opt_path: None,
+                    layer_group: LayerGroup::default(),
+                    resolve_deps: false,
+                    mirrors: Vec::new(),
+                }],
+            },
+            upstreams: UpstreamManifest::default(),
+            packages: vec!["htop".to_string()],
+            groups: Vec::new(),
+            pins: BTreeMap::new(),
+            package_arches: BTreeMap::new(),
+            copr_repos: vec!["atim/starship".to_string()],
+            system_config: SystemConfigManifest::default(),
+            image_config: ImageConfigManifest::default(),
+            gsettings: GSettingsManifest::default(),
+            shims: Vec::new(),
+            has_external_rpms: false,
+            vendor_artifacts: VendorArtifactsManifest::default(),
+            cache_mounts: false,
+            cache_epochs: BTreeMap::new(),
+        };
+
+        let output = generate_full_containerfile(&input);
+        assert!(!output.contains("--mount=type=cache"));
+        assert!(output.contains("RUN bkt-build download-rpms code\n"));
+        assert!(output.contains("    && dnf clean all\n"));
+
+        input.cache_mounts = true;
+        let output = generate_full_containerfile(&input);
+        assert!(output.contains(
+            "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked bkt-build download-rpms code\n"
+        ));
+        assert!(output.contains(
+            "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked set -eu; \\\n"
+        ));
+        assert!(output.contains(
+            "RUN --mount=type=cache,target=/var/cache/libdnf5,sharing=locked dnf install -y --setopt=keepcache=True \\\n    htop\n"
+        ));
+        assert!(!output.contains("dnf clean all"));
+    }
+
+    #[test]
+    fn test_dl_stages_default_cache_epoch_to_recorded_hash() {
+        let repo = |name: &str| crate::manifest::ExternalRepo {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            baseurl: format!("https://example.com/{}", name),
+            gpg_key: format!("https://example.com/{}.asc", name),
+            gpg_fingerprint: None,
+            packages: vec![name.to_string()],
+            opt_path: None,
+            layer_group: LayerGroup::default(),
+            resolve_deps: false,
+            mirrors: Vec::new(),
+        };
+        let repos = ExternalReposManifest {
+            schema: None,
+            repos: vec![repo("code"), repo("microsoft-edge")],
+        };
+        let epochs = BTreeMap::from([("code".to_string(), "4f2a".to_string())]);
+
+        let mut lines = Vec::new();
+        emit_dl_stages(&mut lines, &repos, &epochs, false);
+        let output = lines.join("\n");
+
+        assert!(output.contains("FROM base AS dl-code\nARG CACHE_EPOCH_CODE=4f2a\n"));
+        // Repos without a recorded hash keep the old default
+        assert!(output.contains("ARG CACHE_EPOCH_MICROSOFT_EDGE=0\n"));
+    }
+
+    #[test]
+    fn test_collect_config_emits_wrapper_slice_unit() {
+        let wrapper = |name: &str, memory_max: Option<&str>| ImageModule::Wrapper {
+            name: name.to_string(),
+            comment: None,
+            target: format!("/usr/lib/{}", name),
+            slice: format!("app-{}.slice", name),
+            output: format!("/usr/bin/{}", name),
+            remote_cli: false,
+            description: None,
+            memory_high: None,
+            memory_max: memory_max.map(str::to_string),
+            cpu_weight: None,
+            properties: Vec::new(),
+        };
+        let image_config = ImageConfigManifest {
+            modules: vec![wrapper("limited", Some("4G")), wrapper("plain", None)],
+            ..Default::default()
+        };
+
+        let mut lines = Vec::new();
+        emit_collect_config(&mut lines, &image_config, &GSettingsManifest::default());
+        let output = lines.join("\n");
+
+        assert!(output.contains(
+            "COPY <<EOF /usr/lib/systemd/user/app-limited.slice\n\
+             # Auto-generated by bkt from image-config.json (limited)\n\
+             [Slice]\n\
+             Description=limited (managed)\n\
+             MemoryMax=4G\n\
+             EOF"
+        ));
+        assert!(!output.contains("app-plain.slice"));
+    }
+
+    #[test]
+    fn test_locked_gsettings_emit_dconf_files_and_update() {
+        let gsettings = GSettingsManifest {
+            schema: None,
+            settings: vec![crate::manifest::GSetting {
+                schema: "org.gnome.desktop.screensaver".to_string(),
+                path: None,
+                key: "lock-delay".to_string(),
+                value: "uint32 0".to_string(),
+                value_type: None,
+                comment: None,
+                locked: true,
+            }],
+        };
+
+        let image_config = ImageConfigManifest::default();
+
+        let mut lines = Vec::new();
+        emit_collect_config(&mut lines, &image_config, &gsettings);
+        let output = lines.join("\n");
+        assert!(output.contains(
+            "COPY <<EOF /etc/dconf/db/local.d/50-bkt\n\
+             # Managed by bkt (locked gsettings)\n\
+             \n\
+             [org/gnome/desktop/screensaver]\n\
+             lock-delay=uint32 0\n\
+             EOF"
+        ));
+        assert!(output.contains(
+            "COPY <<EOF /etc/dconf/db/local.d/locks/50-bkt\n\
+             # Managed by bkt (locked gsettings)\n\
+             /org/gnome/desktop/screensaver/lock-delay\n\
+             EOF"
+        ));
+
+        let mut lines = Vec::new();
+        emit_consolidated_run(&mut lines, &image_config, &gsettings, &[]);
+        assert_eq!(lines.last().map(String::as_str), Some("    dconf update"));
+
+        let mut lines = Vec::new();
+        emit_collect_config(&mut lines, &image_config, &GSettingsManifest::default());
+        assert!(!lines.join("\n").contains("dconf"));
+    }
+
+    fn binary_upstream(name: &str, arches: &[&str]) -> Upstream {
+        Upstream {
+            name: name.to_string(),
+            description: None,
+            source: bkt_common::manifest::UpstreamSource::Url {
+                url: format!("https://example.com/{}", name),
+            },
+            pinned: bkt_common::manifest::PinnedVersion {
+                version: "1.0.0".to_string(),
+                commit: None,
+                url: None,
+                sha256: "abc123".to_string(),
+                checksum: None,
+                gpg_verified: false,
+                pinned_at: chrono::Utc::now(),
+            },
+            install: Some(InstallConfig::Binary {
+                install_path: format!("/usr/bin/{}", name),
+            }),
+            signature_url: None,
+            gpg_key: None,
+            arches: arches.iter().map(|a| a.to_string()).collect(),
+            mirrors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fetch_stage_copies_repo_gpg_key() {
+        let mut signed = binary_upstream("signed", &[]);
+        signed.signature_url = Some("https://example.com/signed.asc".to_string());
+        signed.gpg_key = Some("upstream/keys/signed.asc".to_string());
+        let mut inline = binary_upstream("inline", &[]);
+        inline.signature_url = Some("https://example.com/inline.asc".to_string());
+        inline.gpg_key = Some("-----BEGIN PGP PUBLIC KEY BLOCK-----\n...".to_string());
+        let upstreams = UpstreamManifest {
+            schema: None,
+            upstreams: vec![signed, inline],
+        };
+
+        let mut lines = Vec::new();
+        emit_fetch_stages(&mut lines, &upstreams);
+        let output = lines.join("\n");
+
+        assert!(output.contains(
+            "COPY upstream/manifest.json /tmp/upstream-manifest.json\n\
+             COPY upstream/keys/signed.asc /tmp/upstream-keys/upstream/keys/signed.asc\n\
+             RUN bkt-build fetch signed"
+        ));
+        assert_eq!(output.matches("/tmp/upstream-keys/").count(), 1);
+    }
+
+    #[test]
+    fn test_generate_full_containerfile_multi_arch() {
+        let input = ContainerfileGeneratorInput {
+            external_repos: ExternalReposManifest::default(),
+            upstreams: UpstreamManifest {
+                schema: None,
+                upstreams: vec![
+                    binary_upstream("everywhere", &[]),
+                    binary_upstream("x86tool", &["x86_64"]),
+                ],
+            },
+            packages: vec!["htop".to_string(), "steam-devices".to_string()],
+            groups: Vec::new(),
+            pins: BTreeMap::new(),
+            package_arches: BTreeMap::from([(
+                "steam-devices".to_string(),
+                vec!["x86_64".to_string()],
+            )]),
+            copr_repos: Vec::new(),
+            system_config: SystemConfigManifest::default(),
+            image_config: ImageConfigManifest::default(),
+            gsettings: GSettingsManifest::default(),
+            shims: Vec::new(),
+            has_external_rpms: false,
+            vendor_artifacts: VendorArtifactsManifest::default(),
+            cache_mounts: false,
+            cache_epochs: BTreeMap::new(),
+        };
+
+        let output = generate_full_containerfile(&input);
+
+        // Unrestricted upstream keeps the plain fetch stage and direct COPY
+        assert!(output.contains("RUN bkt-build fetch everywhere\n"));
+        assert!(
+            output.contains("COPY --from=fetch-everywhere /usr/bin/everywhere /usr/bin/everywhere")
+        );
+
+        // x86_64-only upstream is gated on TARGETARCH and collected via /out/
+        assert!(output.contains(
+            "FROM base AS fetch-x86tool\n\
+             COPY upstream/manifest.json /tmp/upstream-manifest.json\n\
+             ARG TARGETARCH\n\
+             RUN set -eu; \\\n\
+             \x20   mkdir -p /out; \\\n\
+             \x20   case \"$TARGETARCH\" in amd64) \\\n\
+             \x20       bkt-build fetch x86tool; \\\n\
+             \x20       cp -a --parents /usr/bin/x86tool /out/; \\\n\
+             \x20       ;; \\\n\
+             \x20   esac\n"
+        ));
+        assert!(output.contains("COPY --from=fetch-x86tool /out/ /"));
+        assert!(!output.contains("COPY --from=fetch-x86tool /usr/bin/x86tool"));
+
+        // x86_64-only package is installed separately
+        assert!(output.contains(
+            "RUN dnf install -y \\\n    htop \\\n    && dnf clean all\nARG TARGETARCH\n"
+        ));
+        assert!(output.contains(
+            "RUN case \"$TARGETARCH\" in amd64) \\\n        dnf install -y \\\n        steam-devices \\\n"
+        ));
+    }
+
+    fn labels_input(upstreams: Vec<Upstream>) -> ContainerfileGeneratorInput {
+        ContainerfileGeneratorInput {
+            external_repos: ExternalReposManifest::default(),
+            upstreams: UpstreamManifest {
+                schema: None,
+                upstreams,
+            },
+            packages: vec!["htop".to_string()],
+            groups: Vec::new(),
+            pins: BTreeMap::new(),
+            package_arches: BTreeMap::new(),
+            copr_repos: Vec::new(),
+            system_config: SystemConfigManifest::default(),
+            image_config: ImageConfigManifest::default(),
+            gsettings: GSettingsManifest::default(),
+            shims: Vec::new(),
+            has_external_rpms: false,
+            vendor_artifacts: VendorArtifactsManifest::default(),
+            cache_mounts: false,
+            cache_epochs: BTreeMap::new(),
+        }
+    }
+
+    /// An input that exercises every part of the generated Containerfile.
+    fn split_input() -> ContainerfileGeneratorInput {
+        let repo =
+            |name: &str, opt_path: Option<&str>, layer_group| crate::manifest::ExternalRepo {
+                name: name.to_string(),
+                display_name: name.to_string(),
+                baseurl: format!("https://example.com/{}", name),
+                gpg_key: format!("https://example.com/{}.asc", name),
+                gpg_fingerprint: None,
+                packages: vec![name.to_string()],
+                opt_path: opt_path.map(str::to_string),
+                layer_group,
+                resolve_deps: false,
+                mirrors: Vec::new(),
+            };
+
+        let mut input = labels_input(vec![
+            binary_upstream("age", &[]),
+            binary_upstream("x86tool", &["x86_64"]),
+        ]);
+        input.external_repos.repos = vec![
+            repo("code", None, LayerGroup::Independent),
+            repo("edge", Some("microsoft"), LayerGroup::Bundled),
+        ];
+        input.has_external_rpms = true;
+        input.vendor_artifacts = serde_json::from_value(serde_json::json!({
+            "artifacts": [{
+                "name": "code",
+                "display_name": "Visual Studio Code",
+                "kind": "rpm",
+                "source": {
+                    "type": "vendor-feed",
+                    "url": "https://example.com/{platform}",
+                    "platforms": { "x86_64": "linux-rpm-x64" },
+                    "response_map": { "url": "url", "version": "version", "sha256": "sha256" }
+                }
+            }]
+        }))
+        .unwrap();
+        input.copr_repos = vec!["atim/starship".to_string()];
+        input
+    }
+
+    /// The Containerfile emitted as one file, the way it was before the
+    /// split layout existed.
+    fn single_file(input: &ContainerfileGeneratorInput) -> String {
+        let mut lines = Vec::new();
+        emit_tools_stage(&mut lines);
+        emit_base_stage(&mut lines, input.image_config.base_image());
+        emit_dl_stages(
+            &mut lines,
+            &input.external_repos,
+            &input.cache_epochs,
+            input.cache_mounts,
+        );
+        emit_install_stages(&mut lines, &input.external_repos);
+        emit_bundled_stage(&mut lines, &input.external_repos);
+        emit_vendor_artifact_stages(&mut lines, &input.vendor_artifacts);
+        emit_vendor_bundled_stage(&mut lines, &input.vendor_artifacts);
+        emit_fetch_stages(&mut lines, &input.upstreams);
+        emit_script_stages(&mut lines, &input.upstreams);
+        emit_wrapper_build_stage(&mut lines, &input.image_config);
+        emit_collect_config(&mut lines, &input.image_config, &input.gsettings);
+        emit_collect_outputs(&mut lines, &input.upstreams, &input.image_config);
+        emit_image_assembly(&mut lines, input);
+        emit_labels(&mut lines, input);
+        lines.join("\n") + "\n"
+    }
+
+    #[test]
+    fn test_split_parts_concatenate_to_single_file() {
+        for input in [split_input(), labels_input(Vec::new())] {
+            let parts = generate_containerfile_parts(&input);
+            let concatenated: String = parts.iter().map(|part| part.content.as_str()).collect();
+
+            assert_eq!(concatenated, single_file(&input));
+            assert_eq!(concatenated, generate_full_containerfile(&input));
+            assert!(parts.iter().all(|part| part.content.ends_with('\n')));
+        }
+    }
+
+    #[test]
+    fn test_variants_differ_only_in_base_and_excluded_content() {
+        let mut input = split_input();
+        input.packages = vec!["gnome-tweaks".to_string(), "htop".to_string()];
+        input.image_config = serde_json::from_value(serde_json::json!({
+            "modules": [
+                {
+                    "type": "systemd-enable",
+                    "name": "keyd",
+                    "scope": "system",
+                    "unit": "keyd.service",
+                    "target": "multi-user.target"
+                },
+                {
+                    "type": "run",
+                    "name": "desktop-tweaks",
+                    "commands": ["glib-compile-schemas /usr/share/glib-2.0/schemas"]
+                }
+            ],
+            "variants": {
+                "desktop": {},
+                "server": {
+                    "base_image": "quay.io/fedora/fedora-bootc:42",
+                    "exclude_modules": ["desktop-tweaks"],
+                    "exclude_packages": ["gnome-tweaks"]
+                }
+            }
+        }))
+        .unwrap();
+
+        let desktop = generate_full_containerfile(&input.for_variant("desktop").unwrap());
+        let server = generate_full_containerfile(&input.for_variant("server").unwrap());
+        assert_eq!(desktop, generate_full_containerfile(&input));
+
+        let only_in = |a: &str, b: &str| -> Vec<String> {
+            let b: Vec<&str> = b.lines().collect();
+            a.lines()
+                .filter(|line| !b.contains(line))
+                .map(str::to_string)
+                .collect()
+        };
+        // The manifest hash labels record what went into each image
+        let hash_label = |line: &String| {
+            line.contains("manifest.image-config.sha256")
+                || line.contains("manifest.system-packages.sha256")
+        };
+        let (labels, desktop_only): (Vec<String>, Vec<String>) =
+            only_in(&desktop, &server).into_iter().partition(hash_label);
+        assert_eq!(labels.len(), 2);
+        assert_eq!(
+            desktop_only,
+            [
+                "FROM ghcr.io/ublue-os/bazzite-gnome:stable AS base",
+                "    gnome-tweaks \\",
+                "    ln -sf ../keyd.service /usr/lib/systemd/system/multi-user.target.wants/keyd.service; \\",
+                "    glib-compile-schemas /usr/share/glib-2.0/schemas",
+            ]
+        );
+        let (labels, server_only): (Vec<String>, Vec<String>) =
+            only_in(&server, &desktop).into_iter().partition(hash_label);
+        assert_eq!(labels.len(), 2);
+        assert_eq!(
+            server_only,
+            [
+                "FROM quay.io/fedora/fedora-bootc:42 AS base",
+                "    ln -sf ../keyd.service /usr/lib/systemd/system/multi-user.target.wants/keyd.service",
+            ]
+        );
+        assert_eq!(desktop.lines().count(), server.lines().count() + 2);
+
+        let unknown = input.for_variant("laptop").err().unwrap().to_string();
+        assert!(unknown.contains("known: desktop, server"), "{}", unknown);
+        input
+            .image_config
+            .variants
+            .get_mut("server")
+            .unwrap()
+            .exclude_modules
+            .push("missing".to_string());
+        let err = input.for_variant("server").err().unwrap().to_string();
+        assert!(err.contains("unknown module 'missing'"), "{}", err);
+    }
+
+    #[test]
+    fn test_split_parts_are_ordered_and_skip_empty_concerns() {
+        let names = |input: &ContainerfileGeneratorInput| -> Vec<&'static str> {
+            generate_containerfile_parts(input)
+                .iter()
+                .map(|part| part.name)
+                .collect()
+        };
+
+        let full = names(&split_input());
+        assert_eq!(
+            full,
+            vec![
+                "10-base",
+                "20-downloads",
+                "30-install",
+                "40-vendor",
+                "50-upstream",
+                "60-config",
+                "90-image"
+            ]
+        );
+        let mut sorted = full.clone();
+        sorted.sort();
+        assert_eq!(full, sorted);
+
+        // No vendor artifacts, no vendor part
+        assert!(!names(&labels_input(Vec::new())).contains(&"40-vendor"));
+
+        let parts = generate_containerfile_parts(&split_input());
+        let part = |name| &parts.iter().find(|part| part.name == name).unwrap().content;
+        assert!(part("20-downloads").contains("FROM base AS dl-edge"));
+        assert!(part("30-install").contains("FROM scratch AS install-bundled"));
+        assert!(part("90-image").contains("FROM base AS image"));
+    }
+
+    #[test]
+    fn test_image_labels_format() {
+        let mut zed = binary_upstream("zed", &[]);
+        zed.pinned.version = "0.150.2".to_string();
+        let input = labels_input(vec![zed, binary_upstream("age", &[])]);
+
+        let labels = image_labels(&input);
+        assert_eq!(
+            labels["org.wycats.bootc.upstreams"],
+            "age@1.0.0,zed@0.150.2"
+        );
+        assert_eq!(labels["org.wycats.bootc.labels.schema-version"], "1");
+        let hash = &labels["org.wycats.bootc.manifest.system-packages.sha256"];
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let output = generate_full_containerfile(&input);
+        let label_block = output
+            .split(&section_header(
+                "Image labels (manifest hashes and versions)",
+            ))
+            .nth(1)
+            .unwrap();
+        assert!(label_block.starts_with(&format!(
+            "\nLABEL org.wycats.bootc.bkt.version=\"{}\" \\\n",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(label_block.contains(&format!(
+            "\n      org.wycats.bootc.manifest.system-packages.sha256=\"{hash}\" \\\n"
+        )));
+        assert!(
+            label_block.ends_with("\n      org.wycats.bootc.upstreams=\"age@1.0.0,zed@0.150.2\"\n")
+        );
+    }
+
+    #[test]
+    fn test_image_labels_deterministic() {
+        let selinux = |order: &[&str]| crate::manifest::system_config::SelinuxConfig {
+            booleans: order.iter().map(|name| (name.to_string(), true)).collect(),
+        };
+        let age = binary_upstream("age", &[]);
+        let mut input = labels_input(vec![age.clone()]);
+        input.system_config.selinux = Some(selinux(&["a", "b", "c", "d", "e", "f"]));
+        let mut reordered = labels_input(vec![age]);
+        reordered.system_config.selinux = Some(selinux(&["f", "e", "d", "c", "b", "a"]));
+
+        assert_eq!(image_labels(&input), image_labels(&reordered));
+        assert_eq!(
+            generate_full_containerfile(&input),
+            generate_full_containerfile(&reordered)
+        );
+
+        // Only the changed manifest's label moves
+        reordered.packages.push("btop".to_string());
+        let before = image_labels(&input);
+        let after = image_labels(&reordered);
+        let changed: Vec<&String> = before
+            .keys()
+            .filter(|key| before[*key] != after[*key])
+            .collect();
+        assert_eq!(
+            changed,
+            ["org.wycats.bootc.manifest.system-packages.sha256"]
+        );
+    }
+
+    #[test]
+    fn test_systemd_units_install_dropins() {
+        use crate::manifest::system_config::{SystemdConfig, SystemdDropin};
+
+        let mut systemd = SystemdConfig::default();
+        systemd
+            .dropin_mut("docker.service", "override.conf")
+            .content = Some("[Service]\nMemoryMax=8G\n".to_string());
+        systemd.dropins.insert(
+            "sshd.service".to_string(),
+            vec![SystemdDropin {
+                filename: "10-hardening.conf".to_string(),
+                content: None,
+                source: Some("system/sshd/10-hardening.conf".to_string()),
+            }],
+        );
+        let manifest = SystemConfigManifest {
+            systemd: Some(systemd.clone()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            generate_systemd_units(&manifest),
+            [
+                "COPY <<'EOF' /usr/lib/systemd/system/docker.service.d/override.conf",
+                "[Service]",
+                "MemoryMax=8G",
+                "EOF",
+                "COPY system/sshd/10-hardening.conf \
+                 /usr/lib/systemd/system/sshd.service.d/10-hardening.conf",
+            ]
+        );
+
+        systemd.enable.push("docker.service".to_string());
+        let manifest = SystemConfigManifest {
+            systemd: Some(systemd),
+            ..Default::default()
+        };
+        let lines = generate_systemd_units(&manifest);
+        assert_eq!(lines[5], "RUN set -eu; \\");
+        assert_eq!(lines[6], "    systemctl enable docker.service");
+    }
+
+    #[test]
+    fn test_generate_systemd_units_upgrade_policy() {
+        use crate::manifest::system_config::UpgradePolicy;
+
+        let policy = UpgradePolicy {
+            schedule: "Sun *-*-* 03:00".to_string(),
+            auto_reboot: true,
+            only_if_idle: true,
+        };
+        let manifest = SystemConfigManifest {
+            upgrade_policy: Some(policy.clone()),
+            ..Default::default()
+        };
+        let lines = generate_systemd_units(&manifest);
+
+        let service_lines = policy.service_unit().lines().count();
+        let timer_start = service_lines + 2;
+        assert_eq!(
+            lines[0],
+            "COPY <<'EOF' /usr/lib/systemd/system/bootc-auto-upgrade.service"
+        );
+        assert_eq!(
+            lines[1..=service_lines].join("\n") + "\n",
+            policy.service_unit()
+        );
+        assert_eq!(lines[service_lines + 1], "EOF");
+        assert_eq!(
+            lines[timer_start],
+            "COPY <<'EOF' /usr/lib/systemd/system/bootc-auto-upgrade.timer"
+        );
+        assert!(lines.contains(&"OnCalendar=Sun *-*-* 03:00".to_string()));
+        assert_eq!(
+            lines[lines.len() - 3..],
+            [
+                "RUN set -eu; \\",
+                "    systemctl enable bootc-auto-upgrade.timer; \\",
+                "    systemctl disable bootc-fetch-apply-updates.timer",
+            ]
+        );
+    }
 }
 
 /// Generate the KERNEL_ARGUMENTS section content from a manifest
@@ -1623,15 +3477,54 @@ pub fn generate_kernel_arguments(manifest: &SystemConfigManifest) -> Vec<String>
 
 /// Generate the SYSTEMD_UNITS section content from a manifest
 pub fn generate_systemd_units(manifest: &SystemConfigManifest) -> Vec<String> {
-    let systemd = match &manifest.systemd {
-        Some(s) => s,
-        None => return vec!["# No systemd units configured".to_string()],
-    };
+    let mut systemd = manifest.systemd.clone().unwrap_or_default();
+    let mut lines = Vec::new();
+
+    // The upgrade window's units, enabled like any other managed unit.
+    // bootc's own update timer would upgrade outside the window.
+    if let Some(policy) = &manifest.upgrade_policy {
+        for (unit, content) in [
+            (AUTO_UPGRADE_SERVICE, policy.service_unit()),
+            (AUTO_UPGRADE_TIMER, policy.timer_unit()),
+        ] {
+            lines.push(format!("COPY <<'EOF' {}/{}", IMAGE_UNIT_DIR, unit));
+            lines.extend(content.lines().map(str::to_string));
+            lines.push("EOF".to_string());
+        }
+        if !systemd.enable.iter().any(|u| u == AUTO_UPGRADE_TIMER) {
+            systemd.enable.push(AUTO_UPGRADE_TIMER.to_string());
+        }
+        if !systemd.disable.iter().any(|u| u == BOOTC_UPDATES_TIMER)
+            && !systemd.mask.iter().any(|u| u == BOOTC_UPDATES_TIMER)
+        {
+            systemd.disable.push(BOOTC_UPDATES_TIMER.to_string());
+        }
+    }
 
-    if systemd.enable.is_empty() && systemd.disable.is_empty() && systemd.mask.is_empty() {
+    if systemd.enable.is_empty()
+        && systemd.disable.is_empty()
+        && systemd.mask.is_empty()
+        && systemd.dropins.is_empty()
+    {
         return vec!["# No systemd units configured".to_string()];
     }
 
+    // Drop-in overrides, installed before any unit state changes
+    for (unit, dropins) in &systemd.dropins {
+        for dropin in dropins {
+            let dest = dropin.path(IMAGE_UNIT_DIR, unit);
+            match (&dropin.source, &dropin.content) {
+                (Some(source), _) => lines.push(format!("COPY {} {}", source, dest)),
+                (None, Some(content)) => {
+                    lines.push(format!("COPY <<'EOF' {}", dest));
+                    lines.extend(content.lines().map(str::to_string));
+                    lines.push("EOF".to_string());
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
     let mut commands = Vec::new();
 
     if !systemd.enable.is_empty() {
@@ -1649,7 +3542,10 @@ pub fn generate_systemd_units(manifest: &SystemConfigManifest) -> Vec<String> {
         commands.push(format!("systemctl mask {}", units));
     }
 
-    let mut lines = Vec::new();
+    if commands.is_empty() {
+        return lines;
+    }
+
     lines.push("RUN set -eu; \\".to_string());
 
     for (i, cmd) in commands.iter().enumerate() {
@@ -23,6 +23,7 @@ use std::path::{Path, PathBuf};
 use std::process::Output;
 
 use crate::command_runner::{CommandOptions, CommandRunner, RealCommandRunner};
+use crate::daemon;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Environment Trait (RFC-0019)
@@ -425,6 +426,8 @@ pub enum CommandDomain {
     System,
     /// Homebrew/Linuxbrew packages (host-only)
     Homebrew,
+    /// Dev binaries installed inside the toolbox (dev-only)
+    ToolboxBinaries,
     /// Profile/status commands (read-only, any context)
     Profile,
     /// Repository info (read-only)
@@ -469,6 +472,9 @@ impl CommandDomain {
             (CommandDomain::Shim, ExecutionContext::Dev) => false,
             (CommandDomain::Homebrew, ExecutionContext::Dev) => false,
 
+            // Toolbox binaries are installed inside the toolbox, never on the host
+            (CommandDomain::ToolboxBinaries, ExecutionContext::Host) => false,
+
             // DNF is valid in both host (rpm-ostree) and dev (dnf) contexts
             (CommandDomain::Dnf, _) => true,
 
@@ -527,6 +533,13 @@ impl CommandDomain {
                  For toolbox packages, use: bkt dev install <package>"
                     .to_string()
             }
+            (CommandDomain::ToolboxBinaries, ExecutionContext::Host) => {
+                "Toolbox binaries are installed inside the development toolbox.\n\n\
+                 This command requires dev context. Run it from inside the toolbox\n\
+                 (bkt dev enter) or pass --context dev.\n\n\
+                 For host binaries, use: bkt fetchbin sync"
+                    .to_string()
+            }
             _ => format!("{:?} is not valid in {} context", self, context),
         }
     }
@@ -624,7 +637,8 @@ pub fn is_in_toolbox_with_env(env: &dyn Environment) -> bool {
 /// Run a command and return its output.
 ///
 /// This is a simple wrapper around Command::new().output() with error context.
-/// Since bkt always runs on the host (via host-only shim), no delegation is needed.
+/// It runs wherever bkt is running; use [`run_on_host`] for commands that
+/// must reach the host from the dev context.
 ///
 /// # Arguments
 /// * `program` - The program to run (e.g., "flatpak", "gnome-extensions")
@@ -649,6 +663,124 @@ pub fn run_command_with(
         .with_context(|| format!("Failed to run '{}'", program))
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Host Command Routing
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How host commands reach the host from where bkt is running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostRoute {
+    /// Already on the host: run the program directly.
+    Direct,
+    /// Through the host daemon socket, falling back to [`HostRoute::Spawn`]
+    /// if the daemon can't run the command.
+    Daemon(PathBuf),
+    /// Through `flatpak-spawn --host`.
+    Spawn,
+}
+
+impl HostRoute {
+    /// Detect the route for the current process.
+    pub fn detect() -> Self {
+        Self::detect_with_env(&REAL_ENV)
+    }
+
+    /// Detect the route using the provided Environment.
+    ///
+    /// Outside a toolbox commands run directly. Inside one, the daemon is
+    /// used when its socket is connectable, unless `BKT_NO_DAEMON=1` forces
+    /// `flatpak-spawn` (for debugging the slow path).
+    pub fn detect_with_env(env: &dyn Environment) -> Self {
+        if !is_in_toolbox_with_env(env) {
+            return HostRoute::Direct;
+        }
+
+        if env.var("BKT_NO_DAEMON").is_ok_and(|value| value == "1") {
+            return HostRoute::Spawn;
+        }
+
+        match env.var("XDG_RUNTIME_DIR") {
+            Ok(runtime_dir) => {
+                let socket = daemon::socket_path_in(Path::new(&runtime_dir));
+                if daemon::socket_connectable(&socket) {
+                    HostRoute::Daemon(socket)
+                } else {
+                    HostRoute::Spawn
+                }
+            }
+            Err(_) => HostRoute::Spawn,
+        }
+    }
+}
+
+/// Runs commands on the host, wherever bkt itself is running.
+///
+/// From the dev context this goes through the daemon when it's up (~3ms)
+/// and `flatpak-spawn --host` otherwise (~120ms). The fallback always goes
+/// through the [`CommandRunner`].
+pub struct HostExec<'a> {
+    runner: &'a dyn CommandRunner,
+    route: HostRoute,
+}
+
+impl<'a> HostExec<'a> {
+    /// Create a host executor for the current process's route.
+    pub fn new(runner: &'a dyn CommandRunner) -> Self {
+        Self::with_route(runner, HostRoute::detect())
+    }
+
+    /// Create a host executor that uses `route`.
+    pub fn with_route(runner: &'a dyn CommandRunner, route: HostRoute) -> Self {
+        Self { runner, route }
+    }
+
+    /// The route commands take to the host.
+    pub fn route(&self) -> &HostRoute {
+        &self.route
+    }
+
+    /// Run a command on the host and capture its output.
+    pub fn run_on_host(&self, program: &str, args: &[&str]) -> Result<Output> {
+        match &self.route {
+            HostRoute::Direct => run_command_with(self.runner, program, args),
+            HostRoute::Daemon(socket) => match run_via_daemon(socket, program, args) {
+                Ok(output) => Ok(output),
+                Err(e) => {
+                    tracing::debug!("Daemon could not run '{}': {:#}", program, e);
+                    self.run_via_spawn(program, args)
+                }
+            },
+            HostRoute::Spawn => self.run_via_spawn(program, args),
+        }
+    }
+
+    fn run_via_spawn(&self, program: &str, args: &[&str]) -> Result<Output> {
+        let mut spawn_args = vec!["--host", program];
+        spawn_args.extend_from_slice(args);
+        run_command_with(self.runner, "flatpak-spawn", &spawn_args)
+            .with_context(|| format!("Failed to run '{}' on the host", program))
+    }
+}
+
+/// Run `program` through the daemon at `socket` with our environment.
+fn run_via_daemon(socket: &Path, program: &str, args: &[&str]) -> Result<Output> {
+    let mut argv = vec![program.to_string()];
+    argv.extend(args.iter().map(|arg| arg.to_string()));
+    let envp: Vec<String> = std::env::vars()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+
+    daemon::DaemonClient::new(socket).output(&argv, &envp, &cwd)
+}
+
+/// Run a command on the host and return its output.
+///
+/// See [`HostExec`] for how the command gets there.
+pub fn run_on_host(program: &str, args: &[&str]) -> Result<Output> {
+    HostExec::new(&RealCommandRunner).run_on_host(program, args)
+}
+
 /// Determine the effective execution context.
 ///
 /// If explicitly specified, use that. Otherwise, auto-detect based on environment.
@@ -730,6 +862,18 @@ mod tests {
         assert!(CommandDomain::Flatpak.valid_for_context(ExecutionContext::Image));
     }
 
+    #[test]
+    fn test_toolbox_binaries_invalid_in_host_context() {
+        assert!(!CommandDomain::ToolboxBinaries.valid_for_context(ExecutionContext::Host));
+        assert!(CommandDomain::ToolboxBinaries.valid_for_context(ExecutionContext::Dev));
+        assert!(CommandDomain::ToolboxBinaries.valid_for_context(ExecutionContext::Image));
+        assert!(
+            CommandDomain::ToolboxBinaries
+                .context_error_message(ExecutionContext::Host)
+                .contains("--context dev")
+        );
+    }
+
     #[test]
     fn test_dnf_valid_in_all_contexts() {
         assert!(CommandDomain::Dnf.valid_for_context(ExecutionContext::Host));
@@ -883,6 +1027,122 @@ mod tests {
         assert!(!is_in_toolbox_with_env(&env));
     }
 
+    // ─────────────────────────────────────────────────────────────────────
+    // HostExec Tests
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Records the commands it's asked to run and reports success.
+    #[derive(Default)]
+    struct RecordingRunner {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl CommandRunner for RecordingRunner {
+        fn run_output(
+            &self,
+            program: &str,
+            args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<Output> {
+            use std::os::unix::process::ExitStatusExt;
+
+            let mut call = vec![program];
+            call.extend_from_slice(args);
+            self.calls.lock().unwrap().push(call.join(" "));
+            Ok(Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"ran".to_vec(),
+                stderr: Vec::new(),
+            })
+        }
+
+        fn run_status(
+            &self,
+            _program: &str,
+            _args: &[&str],
+            _options: &CommandOptions,
+        ) -> Result<std::process::ExitStatus> {
+            unreachable!("HostExec only captures output")
+        }
+    }
+
+    fn toolbox_with_runtime_dir(dir: &Path) -> MockEnvironment {
+        MockEnvironment::new()
+            .with_var("TOOLBOX_PATH", "/usr/bin/toolbox")
+            .with_var("XDG_RUNTIME_DIR", dir.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_host_route_direct_on_host() {
+        assert_eq!(
+            HostRoute::detect_with_env(&MockEnvironment::new()),
+            HostRoute::Direct
+        );
+    }
+
+    #[test]
+    fn test_host_route_uses_connectable_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = daemon::socket_path_in(dir.path());
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        let _daemon = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+
+        let env = toolbox_with_runtime_dir(dir.path());
+        assert_eq!(
+            HostRoute::detect_with_env(&env),
+            HostRoute::Daemon(socket.clone())
+        );
+
+        let forced = env.with_var("BKT_NO_DAEMON", "1");
+        assert_eq!(HostRoute::detect_with_env(&forced), HostRoute::Spawn);
+    }
+
+    #[test]
+    fn test_host_route_skips_stale_daemon_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = daemon::socket_path_in(dir.path());
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        // The socket file outlives the listener, like after a daemon crash
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+
+        let env = toolbox_with_runtime_dir(dir.path());
+        assert_eq!(HostRoute::detect_with_env(&env), HostRoute::Spawn);
+    }
+
+    #[test]
+    fn test_host_exec_direct_and_spawn_commands() {
+        let runner = RecordingRunner::default();
+        HostExec::with_route(&runner, HostRoute::Direct)
+            .run_on_host("rpm-ostree", &["status", "--json"])
+            .unwrap();
+        HostExec::with_route(&runner, HostRoute::Spawn)
+            .run_on_host("rpm-ostree", &["status", "--json"])
+            .unwrap();
+
+        assert_eq!(
+            *runner.calls.lock().unwrap(),
+            vec![
+                "rpm-ostree status --json",
+                "flatpak-spawn --host rpm-ostree status --json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_host_exec_falls_back_when_daemon_unreachable() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = RecordingRunner::default();
+        let exec = HostExec::with_route(&runner, HostRoute::Daemon(dir.path().join("host.sock")));
+
+        let output = exec.run_on_host("flatpak", &["list", "--app"]).unwrap();
+
+        assert_eq!(output.stdout, b"ran");
+        assert_eq!(
+            *runner.calls.lock().unwrap(),
+            vec!["flatpak-spawn --host flatpak list --app"]
+        );
+    }
+
     // ─────────────────────────────────────────────────────────────────────
     // resolve_context Tests (using MockEnvironment)
     // ─────────────────────────────────────────────────────────────────────
@@ -5,11 +5,30 @@
 use anyhow::{Context, Result};
 use std::os::fd::AsRawFd;
 use std::os::unix::net::UnixStream;
+use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
 use std::time::Duration;
 
 use super::DEFAULT_TIMEOUT;
-use super::protocol::{self, Request};
+use super::protocol::{
+    self, FLAG_STREAM, OutputStream, Request, Response, ResponseStart, StreamFrame, VERSION_REQUEST,
+};
+
+/// The daemon refused a request (wrong UID, or a program not on its
+/// allowlist). Callers can fall back to another way of reaching the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonDenied {
+    pub reason: String,
+}
+
+impl std::fmt::Display for DaemonDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "daemon denied request: {}", self.reason)
+    }
+}
+
+impl std::error::Error for DaemonDenied {}
 
 /// Client for communicating with the daemon.
 pub struct DaemonClient {
@@ -41,6 +60,97 @@ impl DaemonClient {
     ///
     /// Returns the exit code of the executed command.
     pub fn execute(&self, argv: &[String], envp: &[String], cwd: &Path) -> Result<i32> {
+        let stream = self.send(argv, envp, cwd, 0)?;
+
+        // Wait for response
+        let response = protocol::recv_response(&stream)?;
+
+        exit_code(response)
+    }
+
+    /// Connect to the daemon and execute a command, streaming its output.
+    ///
+    /// `on_chunk` is called with each piece of stdout/stderr as the daemon
+    /// forwards it. stdin is still passed through. Daemons that predate
+    /// streaming write directly to this process's stdout/stderr instead, in
+    /// which case `on_chunk` is never called.
+    ///
+    /// Returns the exit code of the executed command.
+    pub fn execute_streaming<F>(
+        &self,
+        argv: &[String],
+        envp: &[String],
+        cwd: &Path,
+        mut on_chunk: F,
+    ) -> Result<i32>
+    where
+        F: FnMut(OutputStream, &[u8]),
+    {
+        let stream = self.send(argv, envp, cwd, FLAG_STREAM)?;
+
+        // Long-running commands may go quiet for longer than the timeout
+        stream
+            .set_read_timeout(None)
+            .context("Failed to clear read timeout")?;
+
+        let response = match protocol::recv_response_start(&stream)? {
+            ResponseStart::Buffered(response) => response,
+            ResponseStart::Streamed => loop {
+                match protocol::recv_frame(&stream)? {
+                    StreamFrame::Chunk { stream, data } => on_chunk(stream, &data),
+                    StreamFrame::Exit(response) => break response,
+                }
+            },
+        };
+
+        exit_code(response)
+    }
+
+    /// Connect to the daemon and execute a command, capturing its output
+    /// like [`std::process::Command::output`].
+    ///
+    /// Daemons that predate streaming write to this process's stdout/stderr
+    /// directly, so nothing is captured from them.
+    pub fn output(&self, argv: &[String], envp: &[String], cwd: &Path) -> Result<Output> {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let code = self.execute_streaming(argv, envp, cwd, |stream, data| match stream {
+            OutputStream::Stdout => stdout.extend_from_slice(data),
+            OutputStream::Stderr => stderr.extend_from_slice(data),
+        })?;
+
+        Ok(Output {
+            status: ExitStatus::from_raw((code & 0xff) << 8),
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Ask the daemon which version it is running.
+    ///
+    /// Returns `None` when the daemon doesn't understand the request, which
+    /// means it predates version reporting.
+    pub fn version(&self) -> Result<Option<String>> {
+        let mut stdout = Vec::new();
+        let argv = [VERSION_REQUEST.to_string()];
+        let result = self.execute_streaming(&argv, &[], Path::new("/"), |stream, data| {
+            if stream == OutputStream::Stdout {
+                stdout.extend_from_slice(data);
+            }
+        });
+
+        match result {
+            Ok(0) if !stdout.is_empty() => {
+                Ok(Some(String::from_utf8_lossy(&stdout).trim().to_string()))
+            }
+            Ok(_) => Ok(None),
+            Err(e) if e.is::<DaemonDenied>() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Connect to the daemon and send a request with our stdin/stdout/stderr.
+    fn send(&self, argv: &[String], envp: &[String], cwd: &Path, flags: u32) -> Result<UnixStream> {
         // Connect to the daemon
         let stream = UnixStream::connect(&self.socket_path).with_context(|| {
             format!(
@@ -62,6 +172,7 @@ impl DaemonClient {
             argv: argv.to_vec(),
             envp: envp.to_vec(),
             cwd: cwd.to_path_buf(),
+            flags,
         };
 
         // Send request with our stdin/stdout/stderr
@@ -73,11 +184,7 @@ impl DaemonClient {
             std::io::stderr().as_raw_fd(),
         )?;
 
-        // Wait for response
-        let response = protocol::recv_response(&stream)?;
-
-        // Extract exit code
-        Ok(response.exit_code().unwrap_or(1))
+        Ok(stream)
     }
 
     /// Execute a command using the current environment.
@@ -94,6 +201,14 @@ impl DaemonClient {
     }
 }
 
+/// Exit code of a completed command, or [`DaemonDenied`] if it never ran.
+fn exit_code(response: Response) -> Result<i32> {
+    match response {
+        Response::Denied { reason } => Err(DaemonDenied { reason }.into()),
+        completed => Ok(completed.exit_code().unwrap_or(1)),
+    }
+}
+
 /// Execute a command via the daemon, falling back to direct execution if unavailable.
 ///
 /// This is the main entry point for daemon-accelerated execution.
@@ -107,3 +222,66 @@ pub fn execute_via_daemon(
     let client = DaemonClient::new(socket_path);
     client.execute(argv, envp, cwd)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_output_captures_streamed_chunks_and_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        // A fake daemon that answers one request with canned output
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let (request, _fds) = protocol::recv_request(&stream).unwrap();
+            protocol::send_stream_start(&stream).unwrap();
+            for (output, data) in [
+                (OutputStream::Stdout, b"out\n".as_slice()),
+                (OutputStream::Stderr, b"err\n".as_slice()),
+            ] {
+                let frame = StreamFrame::Chunk {
+                    stream: output,
+                    data: data.to_vec(),
+                };
+                protocol::send_frame(&stream, &frame).unwrap();
+            }
+            let exit = StreamFrame::Exit(Response::Completed {
+                wait_status: 3 << 8,
+            });
+            protocol::send_frame(&stream, &exit).unwrap();
+            request.argv
+        });
+
+        let argv = ["rpm-ostree".to_string(), "status".to_string()];
+        let output = DaemonClient::new(&path)
+            .output(&argv, &[], Path::new("/"))
+            .unwrap();
+
+        assert_eq!(server.join().unwrap(), argv);
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_denied_response_is_typed_error() {
+        let err = exit_code(Response::Denied {
+            reason: "'sh' is not in the daemon allowlist".to_string(),
+        })
+        .unwrap_err();
+
+        let denied = err.downcast_ref::<DaemonDenied>().expect("typed denial");
+        assert!(denied.reason.contains("allowlist"));
+        assert_eq!(
+            exit_code(Response::Completed {
+                wait_status: 2 << 8
+            })
+            .unwrap(),
+            2
+        );
+    }
+}
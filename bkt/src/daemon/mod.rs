@@ -16,7 +16,9 @@
 //! ```
 //!
 //! The socket is placed in `$XDG_RUNTIME_DIR/bkt/host.sock`, which is
-//! bind-mounted into distrobox containers, allowing direct access.
+//! bind-mounted into distrobox containers, allowing direct access. Only
+//! connections from the daemon's own UID are served, and only for programs
+//! listed in `manifests/daemon-allowlist.json` (see [`AccessPolicy`]).
 //!
 //! # Usage
 //!
@@ -31,18 +33,23 @@
 //! See [RFC-0048](../../../docs/rfcs/0048-persistent-host-command-helper.md) for design details.
 
 mod client;
+mod policy;
 mod protocol;
 mod server;
 
-pub use client::DaemonClient;
-pub use protocol::{Request, Response};
+pub use client::{DaemonClient, DaemonDenied};
+pub use policy::AccessPolicy;
+pub use protocol::{OutputStream, Request, Response, StreamFrame};
 pub use server::DaemonServer;
 
 use anyhow::{Context, Result};
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+/// Version reported by this build's daemon in reply to a version request.
+pub const DAEMON_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Default connection timeout for daemon operations.
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -52,24 +59,32 @@ pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 /// This directory is bind-mounted into distrobox containers.
 pub fn socket_path() -> Result<PathBuf> {
     let runtime_dir = std::env::var("XDG_RUNTIME_DIR").context("XDG_RUNTIME_DIR not set")?;
-    Ok(PathBuf::from(runtime_dir).join("bkt").join("host.sock"))
+    Ok(socket_path_in(Path::new(&runtime_dir)))
+}
+
+/// Returns the path to the daemon socket under `runtime_dir`.
+pub fn socket_path_in(runtime_dir: &Path) -> PathBuf {
+    runtime_dir.join("bkt").join("host.sock")
 }
 
 /// Check if the daemon socket exists and is connectable.
 ///
 /// This performs an actual connection attempt to detect stale sockets
-/// (e.g., from a crashed daemon that didn't clean up).
+/// (e.g., from a crashed daemon that didn't clean up). When `bkt-hostd.socket`
+/// is active, systemd accepts the connection itself and spawns the daemon, so
+/// an idle-exited daemon still counts as available.
 pub fn daemon_available() -> bool {
-    let Ok(path) = socket_path() else {
-        return false;
-    };
+    socket_path().is_ok_and(|path| socket_connectable(&path))
+}
 
+/// Check if the daemon socket at `path` exists and is connectable.
+pub fn socket_connectable(path: &Path) -> bool {
     if !path.exists() {
         return false;
     }
 
     // Try to connect with a short timeout to detect stale sockets
-    match UnixStream::connect(&path) {
+    match UnixStream::connect(path) {
         Ok(stream) => {
             // Set a read timeout to avoid blocking forever
             let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
@@ -0,0 +1,72 @@
+//! Access control for the daemon socket.
+//!
+//! Anything that can connect to the socket gets to run host commands, so
+//! every connection is checked twice before a fork: the peer must have the
+//! daemon's UID (via SO_PEERCRED), and argv[0] must be on the allowlist.
+
+use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+use std::collections::BTreeSet;
+use std::os::unix::net::UnixStream;
+
+use super::protocol::Request;
+use crate::manifest::DaemonAllowlist;
+
+/// Who may use the daemon, and for what.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessPolicy {
+    /// The only UID allowed to connect.
+    pub uid: u32,
+    /// Permitted argv[0] values; `None` allows any program.
+    pub allowed: Option<BTreeSet<String>>,
+}
+
+impl AccessPolicy {
+    /// Serve our own UID, running only programs on `allowlist`.
+    pub fn new(allowlist: &DaemonAllowlist) -> Self {
+        Self {
+            uid: nix::unistd::getuid().as_raw(),
+            allowed: Some(allowlist.commands.iter().cloned().collect()),
+        }
+    }
+
+    /// Serve our own UID, running any program.
+    pub fn allow_all() -> Self {
+        Self {
+            uid: nix::unistd::getuid().as_raw(),
+            allowed: None,
+        }
+    }
+
+    /// Check the connecting process's credentials; `Err` holds the reason.
+    pub fn check_peer(&self, stream: &UnixStream) -> Result<(), String> {
+        let creds = getsockopt(stream, PeerCredentials)
+            .map_err(|e| format!("could not read peer credentials: {}", e))?;
+        if creds.uid() != self.uid {
+            return Err(format!(
+                "peer uid {} does not match daemon uid {}",
+                creds.uid(),
+                self.uid
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that the request runs a permitted program.
+    pub fn check_request(&self, request: &Request) -> Result<(), String> {
+        let Some(allowed) = &self.allowed else {
+            return Ok(());
+        };
+        let program = request.argv.first().map(String::as_str).unwrap_or("");
+        if allowed.contains(program) {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not in the daemon allowlist", program))
+        }
+    }
+}
+
+impl Default for AccessPolicy {
+    fn default() -> Self {
+        Self::new(&DaemonAllowlist::default())
+    }
+}
@@ -10,7 +10,7 @@
 //! │   n_argv: u32      - Number of argument strings              │
 //! │   n_envp: u32      - Number of environment strings           │
 //! │   cwd_len: u32     - Length of working directory path        │
-//! │   reserved: u32    - Reserved for future use                 │
+//! │   flags: u32       - Client capabilities (FLAG_*)            │
 //! ├──────────────────────────────────────────────────────────────┤
 //! │ Body (variable length)                                       │
 //! │   cwd: [u8; cwd_len]           - Working directory (UTF-8)   │
@@ -32,6 +32,41 @@
 //! │   Raw waitpid(2) status, use WIFEXITED/WEXITSTATUS macros    │
 //! └──────────────────────────────────────────────────────────────┘
 //! ```
+//!
+//! # Streamed Response Format
+//!
+//! When the request sets [`FLAG_STREAM`], the server runs the command with
+//! its stdout/stderr attached to pipes instead of the passed fds, and
+//! forwards output over the socket as it is produced:
+//!
+//! ```text
+//! ┌──────────────────────────────────────────────────────────────┐
+//! │ magic: b"BKTS"                                               │
+//! ├──────────────────────────────────────────────────────────────┤
+//! │ Frame (repeated)                                             │
+//! │   kind: u8         - 1 = stdout, 2 = stderr, 3 = exit        │
+//! │   len: u32         - Payload length (little-endian)          │
+//! │   payload: [u8; len]                                         │
+//! └──────────────────────────────────────────────────────────────┘
+//! ```
+//!
+//! # Denied Response Format
+//!
+//! When the daemon refuses a request (see [`super::AccessPolicy`]) it sends
+//! this instead of any other response:
+//!
+//! ```text
+//! ┌──────────────────────────────────────────────────────────────┐
+//! │ magic: b"BKTD"                                               │
+//! │ len: u32           - Reason length (little-endian)           │
+//! │ reason: [u8; len]  - Why the request was refused (UTF-8)     │
+//! └──────────────────────────────────────────────────────────────┘
+//! ```
+//!
+//! The exit frame carries the wait status as an `i32` and is always last.
+//! Servers that predate streaming ignore the flag and send the buffered
+//! response; the magic is never a valid wait status, so clients can tell
+//! the two apart from the first four bytes.
 
 use anyhow::{Context, Result, bail};
 use nix::sys::socket::{self, ControlMessage, ControlMessageOwned, MsgFlags, UnixAddr};
@@ -46,6 +81,27 @@ const HEADER_SIZE: usize = 16;
 /// Maximum message size (16 MB should be plenty for env + args).
 const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
 
+/// Request flag: the client accepts a streamed response.
+pub const FLAG_STREAM: u32 = 1 << 0;
+
+/// argv of a request asking the daemon for its version instead of running a
+/// command. Answered as a streamed response with the version on stdout.
+pub const VERSION_REQUEST: &str = "bkt-daemon:version";
+
+/// Marker that opens a streamed response.
+const STREAM_MAGIC: [u8; 4] = *b"BKTS";
+
+/// Marker that opens a denied response.
+const DENIED_MAGIC: [u8; 4] = *b"BKTD";
+
+/// Longest denial reason a client will accept.
+const MAX_REASON_SIZE: usize = 64 * 1024;
+
+/// Frame kinds in a streamed response.
+const FRAME_STDOUT: u8 = 1;
+const FRAME_STDERR: u8 = 2;
+const FRAME_EXIT: u8 = 3;
+
 /// A request to execute a command on the host.
 #[derive(Debug, Clone)]
 pub struct Request {
@@ -55,33 +111,73 @@ pub struct Request {
     pub envp: Vec<String>,
     /// Working directory.
     pub cwd: PathBuf,
+    /// Client capability flags (`FLAG_*`).
+    pub flags: u32,
+}
+
+impl Request {
+    /// Whether the client asked for a streamed response.
+    pub fn wants_stream(&self) -> bool {
+        self.flags & FLAG_STREAM != 0
+    }
 }
 
-/// Response from the daemon after command execution.
-#[derive(Debug, Clone, Copy)]
-pub struct Response {
-    /// Raw waitpid(2) status.
-    pub wait_status: i32,
+/// Response from the daemon to a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// The command ran.
+    Completed {
+        /// Raw waitpid(2) status.
+        wait_status: i32,
+    },
+    /// The daemon refused to run the command.
+    Denied { reason: String },
 }
 
 impl Response {
     /// Check if the process exited normally.
     pub fn exited(&self) -> bool {
-        // WIFEXITED: (status & 0x7f) == 0
-        (self.wait_status & 0x7f) == 0
+        match self {
+            // WIFEXITED: (status & 0x7f) == 0
+            Response::Completed { wait_status } => (wait_status & 0x7f) == 0,
+            Response::Denied { .. } => false,
+        }
     }
 
     /// Get the exit code if the process exited normally.
     pub fn exit_code(&self) -> Option<i32> {
-        if self.exited() {
+        match self {
             // WEXITSTATUS: (status >> 8) & 0xff
-            Some((self.wait_status >> 8) & 0xff)
-        } else {
-            None
+            Response::Completed { wait_status } if self.exited() => Some((wait_status >> 8) & 0xff),
+            _ => None,
         }
     }
 }
 
+/// Which child output stream a chunk came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single frame of a streamed response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamFrame {
+    /// A chunk of child output, in the order it was read.
+    Chunk { stream: OutputStream, data: Vec<u8> },
+    /// The child exited. Always the final frame.
+    Exit(Response),
+}
+
+/// The first part of a response: either the whole buffered response, or
+/// the start of a stream of [`StreamFrame`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseStart {
+    Buffered(Response),
+    Streamed,
+}
+
 /// Send a request over the socket with fd passing.
 pub fn send_request(
     stream: &UnixStream,
@@ -111,7 +207,7 @@ pub fn send_request(
         (request.argv.len() as u32).to_le_bytes(),
         (request.envp.len() as u32).to_le_bytes(),
         (cwd_bytes.len() as u32).to_le_bytes(),
-        [0u8; 4], // reserved
+        request.flags.to_le_bytes(),
     ]
     .concat();
 
@@ -180,7 +276,8 @@ pub fn recv_request(stream: &UnixStream) -> Result<(Request, [OwnedFd; 3])> {
     let n_argv = u32::from_le_bytes(buf[0..4].try_into().unwrap()) as usize;
     let n_envp = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as usize;
     let cwd_len = u32::from_le_bytes(buf[8..12].try_into().unwrap()) as usize;
-    // buf[12..16] is reserved
+    // Older clients send zero here
+    let flags = u32::from_le_bytes(buf[12..16].try_into().unwrap());
 
     // Parse body
     let body = &buf[HEADER_SIZE..bytes_received];
@@ -214,7 +311,12 @@ pub fn recv_request(stream: &UnixStream) -> Result<(Request, [OwnedFd; 3])> {
         pos += 1; // Skip NUL
     }
 
-    let request = Request { argv, envp, cwd };
+    let request = Request {
+        argv,
+        envp,
+        cwd,
+        flags,
+    };
 
     Ok((request, fds))
 }
@@ -223,7 +325,15 @@ pub fn recv_request(stream: &UnixStream) -> Result<(Request, [OwnedFd; 3])> {
 pub fn send_response(stream: &UnixStream, response: &Response) -> Result<()> {
     use std::io::Write;
 
-    let bytes = response.wait_status.to_le_bytes();
+    let bytes = match response {
+        Response::Completed { wait_status } => wait_status.to_le_bytes().to_vec(),
+        Response::Denied { reason } => {
+            let mut bytes = DENIED_MAGIC.to_vec();
+            bytes.extend_from_slice(&(reason.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(reason.as_bytes());
+            bytes
+        }
+    };
     (&*stream)
         .write_all(&bytes)
         .context("Failed to send response")?;
@@ -239,11 +349,136 @@ pub fn recv_response(stream: &UnixStream) -> Result<Response> {
         .read_exact(&mut bytes)
         .context("Failed to receive response")?;
 
-    Ok(Response {
-        wait_status: i32::from_le_bytes(bytes),
+    finish_response(stream, bytes)
+}
+
+/// Decode a buffered response from its first four bytes, reading the rest
+/// of a denial from the socket.
+fn finish_response(stream: &UnixStream, bytes: [u8; 4]) -> Result<Response> {
+    use std::io::Read;
+
+    if bytes != DENIED_MAGIC {
+        return Ok(Response::Completed {
+            wait_status: i32::from_le_bytes(bytes),
+        });
+    }
+
+    let mut len = [0u8; 4];
+    (&*stream)
+        .read_exact(&mut len)
+        .context("Failed to receive denial")?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_REASON_SIZE {
+        bail!("Denial reason too large: {} bytes", len);
+    }
+
+    let mut reason = vec![0u8; len];
+    (&*stream)
+        .read_exact(&mut reason)
+        .context("Failed to receive denial reason")?;
+    Ok(Response::Denied {
+        reason: String::from_utf8_lossy(&reason).to_string(),
     })
 }
 
+/// Announce that a streamed response follows.
+pub fn send_stream_start(stream: &UnixStream) -> Result<()> {
+    use std::io::Write;
+
+    (&*stream)
+        .write_all(&STREAM_MAGIC)
+        .context("Failed to send stream header")?;
+    Ok(())
+}
+
+/// Receive the start of a response, which may be buffered or streamed.
+pub fn recv_response_start(stream: &UnixStream) -> Result<ResponseStart> {
+    use std::io::Read;
+
+    let mut bytes = [0u8; 4];
+    (&*stream)
+        .read_exact(&mut bytes)
+        .context("Failed to receive response")?;
+
+    if bytes == STREAM_MAGIC {
+        Ok(ResponseStart::Streamed)
+    } else {
+        Ok(ResponseStart::Buffered(finish_response(stream, bytes)?))
+    }
+}
+
+/// Write one frame of a streamed response.
+pub fn send_frame(mut writer: impl std::io::Write, frame: &StreamFrame) -> Result<()> {
+    let status_bytes;
+    let (kind, payload): (u8, &[u8]) = match frame {
+        StreamFrame::Chunk {
+            stream: OutputStream::Stdout,
+            data,
+        } => (FRAME_STDOUT, data),
+        StreamFrame::Chunk {
+            stream: OutputStream::Stderr,
+            data,
+        } => (FRAME_STDERR, data),
+        StreamFrame::Exit(Response::Completed { wait_status }) => {
+            status_bytes = wait_status.to_le_bytes();
+            (FRAME_EXIT, &status_bytes)
+        }
+        StreamFrame::Exit(Response::Denied { .. }) => {
+            bail!("Denials are sent before a stream starts, not as exit frames")
+        }
+    };
+
+    let mut message = Vec::with_capacity(5 + payload.len());
+    message.push(kind);
+    message.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    message.extend_from_slice(payload);
+
+    writer
+        .write_all(&message)
+        .context("Failed to send stream frame")?;
+    Ok(())
+}
+
+/// Read one frame of a streamed response.
+pub fn recv_frame(mut reader: impl std::io::Read) -> Result<StreamFrame> {
+    let mut header = [0u8; 5];
+    reader
+        .read_exact(&mut header)
+        .context("Failed to receive stream frame")?;
+
+    let kind = header[0];
+    let len = u32::from_le_bytes(header[1..5].try_into().unwrap()) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        bail!("Stream frame too large: {} bytes", len);
+    }
+
+    let mut payload = vec![0u8; len];
+    reader
+        .read_exact(&mut payload)
+        .context("Failed to receive stream frame payload")?;
+
+    match kind {
+        FRAME_STDOUT => Ok(StreamFrame::Chunk {
+            stream: OutputStream::Stdout,
+            data: payload,
+        }),
+        FRAME_STDERR => Ok(StreamFrame::Chunk {
+            stream: OutputStream::Stderr,
+            data: payload,
+        }),
+        FRAME_EXIT => {
+            let bytes: [u8; 4] = payload
+                .as_slice()
+                .try_into()
+                .context("Malformed exit frame")?;
+            Ok(StreamFrame::Exit(Response::Completed {
+                wait_status: i32::from_le_bytes(bytes),
+            }))
+        }
+        other => bail!("Unknown stream frame kind: {}", other),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,20 +486,120 @@ mod tests {
     #[test]
     fn test_response_exit_code() {
         // Normal exit with code 0
-        let resp = Response { wait_status: 0 };
+        let resp = Response::Completed { wait_status: 0 };
         assert!(resp.exited());
         assert_eq!(resp.exit_code(), Some(0));
 
         // Normal exit with code 42
-        let resp = Response {
+        let resp = Response::Completed {
             wait_status: 42 << 8,
         };
         assert!(resp.exited());
         assert_eq!(resp.exit_code(), Some(42));
 
         // Killed by signal (not exited)
-        let resp = Response { wait_status: 9 }; // SIGKILL
+        let resp = Response::Completed { wait_status: 9 }; // SIGKILL
         assert!(!resp.exited());
         assert_eq!(resp.exit_code(), None);
     }
+
+    #[test]
+    fn test_request_flags_roundtrip() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let request = Request {
+            argv: vec!["true".to_string()],
+            envp: vec!["A=1".to_string()],
+            cwd: PathBuf::from("/tmp"),
+            flags: FLAG_STREAM,
+        };
+
+        let fd = a.as_raw_fd();
+        send_request(&a, &request, fd, fd, fd).unwrap();
+        let (received, _fds) = recv_request(&b).unwrap();
+
+        assert_eq!(received.argv, request.argv);
+        assert_eq!(received.envp, request.envp);
+        assert_eq!(received.cwd, request.cwd);
+        assert!(received.wants_stream());
+    }
+
+    #[test]
+    fn test_stream_frame_roundtrip() {
+        let frames = vec![
+            StreamFrame::Chunk {
+                stream: OutputStream::Stdout,
+                data: b"hello\n".to_vec(),
+            },
+            StreamFrame::Chunk {
+                stream: OutputStream::Stderr,
+                data: Vec::new(),
+            },
+            StreamFrame::Exit(Response::Completed {
+                wait_status: 3 << 8,
+            }),
+        ];
+
+        let mut buf = Vec::new();
+        for frame in &frames {
+            send_frame(&mut buf, frame).unwrap();
+        }
+
+        let mut reader = buf.as_slice();
+        for expected in &frames {
+            assert_eq!(&recv_frame(&mut reader).unwrap(), expected);
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_recv_frame_rejects_unknown_kind() {
+        let buf = [9u8, 0, 0, 0, 0];
+        assert!(recv_frame(buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_stream_magic_is_not_a_wait_status() {
+        // Wait statuses only use the low 16 bits
+        assert!(i32::from_le_bytes(STREAM_MAGIC) > 0xffff);
+    }
+
+    #[test]
+    fn test_response_start_distinguishes_legacy() {
+        let (a, b) = UnixStream::pair().unwrap();
+
+        send_response(&a, &Response::Completed { wait_status: 0 }).unwrap();
+        assert_eq!(
+            recv_response_start(&b).unwrap(),
+            ResponseStart::Buffered(Response::Completed { wait_status: 0 })
+        );
+
+        send_stream_start(&a).unwrap();
+        assert_eq!(recv_response_start(&b).unwrap(), ResponseStart::Streamed);
+    }
+
+    #[test]
+    fn test_denied_response_roundtrip() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let denied = Response::Denied {
+            reason: "'sh' is not in the daemon allowlist".to_string(),
+        };
+
+        send_response(&a, &denied).unwrap();
+        assert_eq!(recv_response(&b).unwrap(), denied);
+        assert!(!denied.exited());
+        assert_eq!(denied.exit_code(), None);
+
+        send_response(&a, &denied).unwrap();
+        assert_eq!(
+            recv_response_start(&b).unwrap(),
+            ResponseStart::Buffered(denied.clone())
+        );
+
+        assert!(send_frame(Vec::new(), &StreamFrame::Exit(denied)).is_err());
+    }
+
+    #[test]
+    fn test_denied_magic_is_not_a_wait_status() {
+        assert!(i32::from_le_bytes(DENIED_MAGIC) > 0xffff);
+    }
 }
@@ -2,25 +2,47 @@
 //!
 //! The server listens on a Unix socket and handles command execution requests.
 //! Each request forks a child process to execute the command, passing through
-//! the client's stdin/stdout/stderr via fd passing.
+//! the client's stdin/stdout/stderr via fd passing. Clients that ask for a
+//! streamed response get the child's stdout/stderr forwarded over the socket
+//! instead, chunk by chunk. Connections that fail the [`AccessPolicy`] get a
+//! [`Response::Denied`] and nothing is run.
+//!
+//! Under systemd socket activation (`bkt-hostd.socket`) the listening socket
+//! is inherited via the sd_listen_fds protocol instead of bound here.
 
 use anyhow::{Context, Result, bail};
 use nix::sys::wait::{WaitStatus, waitpid};
-use nix::unistd::{self, ForkResult};
-use std::os::fd::{AsRawFd, OwnedFd};
+use nix::unistd::{self, ForkResult, Pid};
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use super::protocol::{self, Request, Response};
+use std::sync::{Arc, Mutex};
+
+use super::DAEMON_VERSION;
+use super::policy::AccessPolicy;
+use super::protocol::{self, OutputStream, Request, Response, StreamFrame, VERSION_REQUEST};
+
+/// Read size for forwarding streamed output.
+const STREAM_CHUNK_SIZE: usize = 8192;
+
+/// First file descriptor passed by systemd (`SD_LISTEN_FDS_START`).
+const SD_LISTEN_FDS_START: RawFd = 3;
 
 /// The daemon server.
 pub struct DaemonServer {
     socket_path: PathBuf,
     listener: UnixListener,
+    /// The listener came from systemd, which owns the socket file.
+    socket_activated: bool,
+    /// Exit after this long without a connection.
+    idle_exit: Option<Duration>,
+    policy: AccessPolicy,
     shutdown: Arc<AtomicBool>,
     /// Number of connections served since startup.
     connections_served: AtomicU64,
@@ -30,6 +52,8 @@ pub struct DaemonServer {
 
 impl DaemonServer {
     /// Bind to the socket path and create a new server.
+    ///
+    /// The server uses the default [`AccessPolicy`]; see [`Self::with_policy`].
     pub fn bind(socket_path: &Path) -> Result<Self> {
         // Create parent directory if needed
         if let Some(parent) = socket_path.parent() {
@@ -58,13 +82,74 @@ impl DaemonServer {
 
         info!("Daemon listening on: {}", socket_path.display());
 
-        Ok(Self {
+        Ok(Self::from_listener(socket_path, listener, false))
+    }
+
+    /// Use the socket systemd passed us if socket-activated, otherwise bind
+    /// `socket_path` ourselves.
+    pub fn listen(socket_path: &Path) -> Result<Self> {
+        let Some(count) = listen_fds(
+            std::env::var("LISTEN_PID").ok().as_deref(),
+            std::env::var("LISTEN_FDS").ok().as_deref(),
+            std::process::id(),
+        ) else {
+            return Self::bind(socket_path);
+        };
+
+        // Don't pass the sockets on to commands we run
+        // SAFETY: single-threaded at startup; nothing else reads these
+        unsafe {
+            std::env::remove_var("LISTEN_PID");
+            std::env::remove_var("LISTEN_FDS");
+            std::env::remove_var("LISTEN_FDNAMES");
+        }
+        if count > 1 {
+            warn!("systemd passed {} sockets; using the first", count);
+        }
+
+        // SAFETY: sd_listen_fds guarantees fd 3 is an open socket owned by us
+        let listener = unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+        // SAFETY: plain fcntl on an fd we own
+        if unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("Failed to set FD_CLOEXEC on activated socket");
+        }
+
+        info!("Daemon socket-activated on: {}", socket_path.display());
+        Ok(Self::from_listener(socket_path, listener, true))
+    }
+
+    fn from_listener(socket_path: &Path, listener: UnixListener, socket_activated: bool) -> Self {
+        Self {
             socket_path: socket_path.to_path_buf(),
             listener,
+            socket_activated,
+            idle_exit: None,
+            policy: AccessPolicy::default(),
             shutdown: Arc::new(AtomicBool::new(false)),
             connections_served: AtomicU64::new(0),
             start_time: Instant::now(),
-        })
+        }
+    }
+
+    /// Exit the main loop after `idle` without a connection.
+    ///
+    /// Meant for socket activation, where systemd starts the daemon again on
+    /// the next connection.
+    pub fn with_idle_exit(mut self, idle: Option<Duration>) -> Self {
+        self.idle_exit = idle;
+        self
+    }
+
+    /// Whether the listening socket was passed in by systemd.
+    pub fn socket_activated(&self) -> bool {
+        self.socket_activated
+    }
+
+    /// Replace the access policy.
+    pub fn with_policy(mut self, policy: AccessPolicy) -> Self {
+        self.policy = policy;
+        self
     }
 
     /// Get the number of connections served since startup.
@@ -89,6 +174,7 @@ impl DaemonServer {
 
         // Set non-blocking so we can check shutdown flag
         self.listener.set_nonblocking(true)?;
+        let mut last_activity = Instant::now();
 
         while !self.shutdown.load(Ordering::SeqCst) {
             match self.listener.accept() {
@@ -106,8 +192,17 @@ impl DaemonServer {
                             warn!("Connection error: {}", e);
                         }
                     }
+                    last_activity = Instant::now();
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if self
+                        .idle_exit
+                        .is_some_and(|idle| last_activity.elapsed() >= idle)
+                    {
+                        info!("Idle for {:?}, exiting", last_activity.elapsed());
+                        break;
+                    }
+
                     // No connection ready, sleep briefly and check shutdown
                     std::thread::sleep(std::time::Duration::from_millis(100));
                 }
@@ -124,34 +219,97 @@ impl DaemonServer {
             "Shutting down after {:?}, {} connections served",
             uptime, served
         );
-        let _ = std::fs::remove_file(&self.socket_path);
+        // systemd keeps listening on an activated socket; leave it in place
+        if !self.socket_activated {
+            let _ = std::fs::remove_file(&self.socket_path);
+        }
 
         Ok(())
     }
 
     /// Handle a single client connection.
     fn handle_connection(&self, stream: UnixStream) -> Result<()> {
+        // Refuse other users before reading anything they send
+        if let Err(reason) = self.policy.check_peer(&stream) {
+            return deny(&stream, reason);
+        }
+
         // Receive the request with file descriptors
         let (request, fds) = protocol::recv_request(&stream)?;
 
+        if request.argv == [VERSION_REQUEST] && request.wants_stream() {
+            drop(fds);
+            return answer_version(&stream);
+        }
+
+        if let Err(reason) = self.policy.check_request(&request) {
+            drop(fds);
+            return deny(&stream, reason);
+        }
+
         debug!(
             command = %request.argv.join(" "),
             cwd = %request.cwd.display(),
             "Executing command"
         );
 
+        if request.wants_stream() {
+            return self.handle_streaming(&stream, &request, fds);
+        }
+
         // Fork and exec
-        let wait_status = self.fork_exec(&request, fds)?;
+        let child = self.spawn(&request, fds)?;
+        let wait_status = wait_child(child)?;
 
         // Send response
-        let response = Response { wait_status };
+        let response = Response::Completed { wait_status };
         protocol::send_response(&stream, &response)?;
 
         Ok(())
     }
 
-    /// Fork a child process and execute the command.
-    fn fork_exec(&self, request: &Request, fds: [OwnedFd; 3]) -> Result<i32> {
+    /// Run a command with its output forwarded over the socket.
+    ///
+    /// The client's stdin is still passed through; stdout and stderr go to
+    /// pipes that are drained concurrently so neither can fill up and block
+    /// the child.
+    fn handle_streaming(
+        &self,
+        stream: &UnixStream,
+        request: &Request,
+        fds: [OwnedFd; 3],
+    ) -> Result<()> {
+        let [stdin, _stdout, _stderr] = fds;
+        let (stdout_read, stdout_write) = cloexec_pipe()?;
+        let (stderr_read, stderr_write) = cloexec_pipe()?;
+
+        let child = self.spawn(request, [stdin, stdout_write, stderr_write])?;
+
+        protocol::send_stream_start(stream)?;
+        let writer = Mutex::new(stream);
+
+        let forwarded = std::thread::scope(|s| {
+            let stdout = s.spawn(|| forward_output(stdout_read, OutputStream::Stdout, &writer));
+            let stderr = forward_output(stderr_read, OutputStream::Stderr, &writer);
+            let stdout = stdout.join().expect("stdout forwarder panicked");
+            stdout.and(stderr)
+        });
+
+        // Always reap the child, even if the client went away
+        let wait_status = wait_child(child)?;
+        forwarded?;
+
+        let exit = StreamFrame::Exit(Response::Completed { wait_status });
+        protocol::send_frame(*writer.lock().unwrap(), &exit)?;
+
+        Ok(())
+    }
+
+    /// Fork a child process to execute the command.
+    ///
+    /// The fds become the child's stdin/stdout/stderr and are closed in the
+    /// parent before returning.
+    fn spawn(&self, request: &Request, fds: [OwnedFd; 3]) -> Result<Pid> {
         use std::ffi::CString;
 
         if request.argv.is_empty() {
@@ -196,18 +354,9 @@ impl DaemonServer {
         // SAFETY: We're about to fork. The child will exec immediately.
         match unsafe { unistd::fork() }? {
             ForkResult::Parent { child } => {
-                // Parent: wait for child and return status
                 // Close the fds in parent (child has them now via fork)
                 drop(fds);
-
-                match waitpid(child, None)? {
-                    WaitStatus::Exited(_, code) => Ok(code << 8), // Encode as waitpid status
-                    WaitStatus::Signaled(_, sig, _) => Ok(sig as i32),
-                    other => {
-                        eprintln!("Unexpected wait status: {:?}", other);
-                        Ok(1 << 8) // Generic failure
-                    }
-                }
+                Ok(child)
             }
             ForkResult::Child => {
                 // Child: set up fds and exec
@@ -256,9 +405,206 @@ impl DaemonServer {
     }
 }
 
+/// Tell the client its request was refused.
+fn deny(stream: &UnixStream, reason: String) -> Result<()> {
+    warn!("Denied request: {}", reason);
+    protocol::send_response(stream, &Response::Denied { reason })
+}
+
+/// Wait for a child and return its status encoded like waitpid(2).
+fn wait_child(child: Pid) -> Result<i32> {
+    match waitpid(child, None)? {
+        WaitStatus::Exited(_, code) => Ok(code << 8), // Encode as waitpid status
+        WaitStatus::Signaled(_, sig, _) => Ok(sig as i32),
+        other => {
+            eprintln!("Unexpected wait status: {:?}", other);
+            Ok(1 << 8) // Generic failure
+        }
+    }
+}
+
+/// Create a pipe whose ends are not inherited across exec.
+///
+/// The child's copies are made with dup2, which clears close-on-exec, so
+/// only the stdio ends survive into the executed program.
+fn cloexec_pipe() -> Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    // SAFETY: fds is a valid two-element buffer for pipe2 to fill
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to create pipe");
+    }
+    // SAFETY: pipe2 succeeded, so both fds are open and owned by us
+    unsafe { Ok((OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1]))) }
+}
+
+/// Reply to a version request without running anything.
+fn answer_version(stream: &UnixStream) -> Result<()> {
+    protocol::send_stream_start(stream)?;
+    let version = StreamFrame::Chunk {
+        stream: OutputStream::Stdout,
+        data: DAEMON_VERSION.as_bytes().to_vec(),
+    };
+    protocol::send_frame(stream, &version)?;
+    protocol::send_frame(
+        stream,
+        &StreamFrame::Exit(Response::Completed { wait_status: 0 }),
+    )
+}
+
+/// Forward everything read from `pipe` to the client as chunk frames.
+fn forward_output(pipe: OwnedFd, stream: OutputStream, writer: &Mutex<&UnixStream>) -> Result<()> {
+    let mut pipe = File::from(pipe);
+    let mut buf = [0u8; STREAM_CHUNK_SIZE];
+
+    loop {
+        let n = match pipe.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Failed to read child output"),
+        };
+
+        let frame = StreamFrame::Chunk {
+            stream,
+            data: buf[..n].to_vec(),
+        };
+        protocol::send_frame(*writer.lock().unwrap(), &frame)?;
+    }
+}
+
+/// Number of sockets systemd passed us, per the sd_listen_fds protocol.
+///
+/// The variables only count if `LISTEN_PID` names this process, since they
+/// are inherited by children.
+fn listen_fds(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Option<u32> {
+    if listen_pid?.trim().parse::<u32>().ok()? != pid {
+        return None;
+    }
+    listen_fds?.trim().parse::<u32>().ok().filter(|&n| n > 0)
+}
+
 impl Drop for DaemonServer {
     fn drop(&mut self) {
+        if self.socket_activated {
+            return;
+        }
         // Clean up socket on drop
         let _ = std::fs::remove_file(&self.socket_path);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn server_with(policy: AccessPolicy) -> (tempfile::TempDir, DaemonServer) {
+        let dir = tempfile::tempdir().unwrap();
+        let server = DaemonServer::bind(&dir.path().join("host.sock"))
+            .unwrap()
+            .with_policy(policy);
+        (dir, server)
+    }
+
+    /// Send `argv` through a socketpair and return the server's response.
+    fn roundtrip(server: &DaemonServer, argv: &[&str]) -> Response {
+        let (client, server_end) = UnixStream::pair().unwrap();
+        let request = Request {
+            argv: argv.iter().map(|s| s.to_string()).collect(),
+            envp: Vec::new(),
+            cwd: PathBuf::from("/"),
+            flags: 0,
+        };
+        let fd = client.as_raw_fd();
+        protocol::send_request(&client, &request, fd, fd, fd).unwrap();
+
+        server.handle_connection(server_end).unwrap();
+        protocol::recv_response(&client).unwrap()
+    }
+
+    #[test]
+    fn test_listen_fds_requires_our_pid() {
+        assert_eq!(listen_fds(Some("42"), Some("1"), 42), Some(1));
+        assert_eq!(listen_fds(Some("42"), Some("2"), 42), Some(2));
+        assert_eq!(listen_fds(Some("41"), Some("1"), 42), None);
+        assert_eq!(listen_fds(Some("42"), Some("0"), 42), None);
+        assert_eq!(listen_fds(None, Some("1"), 42), None);
+        assert_eq!(listen_fds(Some("42"), None, 42), None);
+        assert_eq!(listen_fds(Some("x"), Some("1"), 42), None);
+    }
+
+    #[test]
+    fn test_denies_peer_with_other_uid() {
+        let uid = nix::unistd::getuid().as_raw();
+        let (_dir, server) = server_with(AccessPolicy {
+            uid: uid.wrapping_add(1),
+            allowed: None,
+        });
+
+        match roundtrip(&server, &["true"]) {
+            Response::Denied { reason } => assert!(reason.contains("uid"), "{reason}"),
+            other => panic!("expected denial, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_denies_program_not_in_allowlist() {
+        let (_dir, server) = server_with(AccessPolicy {
+            uid: nix::unistd::getuid().as_raw(),
+            allowed: Some(BTreeSet::from(["bkt".to_string()])),
+        });
+
+        match roundtrip(&server, &["sh", "-c", "true"]) {
+            Response::Denied { reason } => {
+                assert!(reason.contains("'sh' is not in the daemon allowlist"))
+            }
+            other => panic!("expected denial, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_runs_allowlisted_program() {
+        let (_dir, server) = server_with(AccessPolicy {
+            uid: nix::unistd::getuid().as_raw(),
+            allowed: Some(BTreeSet::from(["/bin/sh".to_string()])),
+        });
+
+        let response = roundtrip(&server, &["/bin/sh", "-c", "exit 3"]);
+        assert_eq!(response.exit_code(), Some(3));
+    }
+
+    #[test]
+    fn test_answers_version_request_without_allowlist_entry() {
+        let (_dir, server) = server_with(AccessPolicy {
+            uid: nix::unistd::getuid().as_raw(),
+            allowed: Some(BTreeSet::from(["bkt".to_string()])),
+        });
+
+        let (client, server_end) = UnixStream::pair().unwrap();
+        let request = Request {
+            argv: vec![VERSION_REQUEST.to_string()],
+            envp: Vec::new(),
+            cwd: PathBuf::from("/"),
+            flags: protocol::FLAG_STREAM,
+        };
+        let fd = client.as_raw_fd();
+        protocol::send_request(&client, &request, fd, fd, fd).unwrap();
+        server.handle_connection(server_end).unwrap();
+
+        assert_eq!(
+            protocol::recv_response_start(&client).unwrap(),
+            protocol::ResponseStart::Streamed
+        );
+        assert_eq!(
+            protocol::recv_frame(&client).unwrap(),
+            StreamFrame::Chunk {
+                stream: OutputStream::Stdout,
+                data: DAEMON_VERSION.as_bytes().to_vec(),
+            }
+        );
+        match protocol::recv_frame(&client).unwrap() {
+            StreamFrame::Exit(response) => assert_eq!(response.exit_code(), Some(0)),
+            other => panic!("expected exit frame, got {:?}", other),
+        }
+    }
+}
@@ -7,9 +7,11 @@
 //!
 //! - **From host**: Direct connection to system bus
 //! - **From toolbox**: System bus routes to host automatically via flatpak-portal
+//! - **User units**: Session bus, which a toolbox only sees if it shares the
+//!   host's runtime directory
 //!
 //! Polkit handles authorization - wheel group members get passwordless access.
 
 pub mod systemd;
 
-pub use systemd::SystemdManager;
+pub use systemd::{SystemdManager, UnitScope};
@@ -4,22 +4,40 @@
 //!
 //! ## D-Bus Interface
 //!
-//! - **Bus**: System bus (`org.freedesktop.systemd1`)
+//! - **Bus**: System bus (`org.freedesktop.systemd1`), or the session bus for
+//!   the user manager
 //! - **Path**: `/org/freedesktop/systemd1`
 //! - **Interface**: `org.freedesktop.systemd1.Manager`
 //!
+//! The user manager is reached the same way (same destination and path),
+//! just over the session bus; see [`UnitScope`].
+//!
 //! ## Authorization
 //!
 //! Polkit handles authorization automatically. Wheel group members get
-//! passwordless access to service control operations.
+//! passwordless access to service control operations. User units belong to
+//! the caller, so the user manager doesn't consult polkit at all.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use tracing::warn;
 use zbus::blocking::Connection;
 use zbus::zvariant::OwnedObjectPath;
 
 type UnitFileSymlinkChange = (String, String, String);
 type EnableUnitFilesResult = (bool, Vec<UnitFileSymlinkChange>);
+/// (name, description, load, active, sub, following, path, job id, job type, job path)
+type ListedUnit = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    OwnedObjectPath,
+    u32,
+    String,
+    OwnedObjectPath,
+);
 
 /// Proxy for the systemd Manager interface.
 #[zbus::proxy(
@@ -86,6 +104,13 @@ trait Systemd1Manager {
 
     /// Load a unit (creates it if not loaded).
     fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// List the units systemd has loaded.
+    fn list_units(&self) -> zbus::Result<Vec<ListedUnit>>;
+
+    /// List installed unit files.
+    /// Returns Vec<(path, state)>
+    fn list_unit_files(&self) -> zbus::Result<Vec<(String, String)>>;
 }
 
 /// Proxy for individual systemd Unit properties.
@@ -115,9 +140,46 @@ trait Systemd1Unit {
     fn unit_file_state(&self) -> zbus::Result<String>;
 }
 
+/// Which systemd instance a unit belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitScope {
+    /// The system manager (PID 1), on the system bus
+    #[default]
+    System,
+    /// The calling user's manager, on the session bus (`systemctl --user`)
+    User,
+}
+
+impl UnitScope {
+    /// The scope selected by a `--user` flag.
+    pub fn from_user_flag(user: bool) -> Self {
+        if user {
+            UnitScope::User
+        } else {
+            UnitScope::System
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UnitScope::System => "system",
+            UnitScope::User => "user",
+        }
+    }
+
+    /// The `systemctl`/`journalctl` flag for this scope, if it needs one.
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            UnitScope::System => None,
+            UnitScope::User => Some("--user"),
+        }
+    }
+}
+
 /// High-level wrapper for systemd operations.
 pub struct SystemdManager {
     connection: Connection,
+    scope: UnitScope,
 }
 
 /// Status information for a systemd unit.
@@ -162,7 +224,38 @@ impl SystemdManager {
     /// Connect to the system bus.
     pub fn new() -> Result<Self> {
         let connection = Connection::system().context("Failed to connect to system D-Bus")?;
-        Ok(Self { connection })
+        Ok(Self {
+            connection,
+            scope: UnitScope::System,
+        })
+    }
+
+    /// Connect to the session bus, managing the user's systemd instance.
+    pub fn user() -> Result<Self> {
+        let connection = Connection::session().map_err(|e| {
+            anyhow!(session_bus_unreachable(
+                &e.to_string(),
+                std::env::var("DBUS_SESSION_BUS_ADDRESS").ok().as_deref(),
+                crate::context::is_in_toolbox(),
+            ))
+        })?;
+        Ok(Self {
+            connection,
+            scope: UnitScope::User,
+        })
+    }
+
+    /// Connect to the manager for `scope`.
+    pub fn connect(scope: UnitScope) -> Result<Self> {
+        match scope {
+            UnitScope::System => Self::new(),
+            UnitScope::User => Self::user(),
+        }
+    }
+
+    /// The systemd instance this manager talks to.
+    pub fn scope(&self) -> UnitScope {
+        self.scope
     }
 
     /// Get the Manager proxy.
@@ -181,7 +274,7 @@ impl SystemdManager {
     }
 
     /// Normalize a unit name by appending .service if no suffix present.
-    fn normalize_unit_name(name: &str) -> String {
+    pub(crate) fn normalize_unit_name(name: &str) -> String {
         if name.contains('.') {
             name.to_string()
         } else {
@@ -361,6 +454,29 @@ impl SystemdManager {
             .context(format!("Failed to get unit file state: {}", name))
     }
 
+    /// Names of the units systemd has loaded (`systemctl list-units --all`).
+    pub fn list_units(&self) -> Result<Vec<String>> {
+        let manager = self.manager()?;
+        let units = manager.list_units().context("Failed to list units")?;
+        Ok(units.into_iter().map(|unit| unit.0).collect())
+    }
+
+    /// Names of the installed unit files (`systemctl list-unit-files`),
+    /// leaving out templates, which can't be used without an instance.
+    pub fn list_unit_files(&self) -> Result<Vec<String>> {
+        let manager = self.manager()?;
+        let files = manager
+            .list_unit_files()
+            .context("Failed to list unit files")?;
+        Ok(files
+            .into_iter()
+            .filter_map(|(path, _state)| {
+                let name = path.rsplit('/').next()?.to_string();
+                (!name.contains("@.")).then_some(name)
+            })
+            .collect())
+    }
+
     /// Reload the systemd daemon (daemon-reload).
     pub fn daemon_reload(&self) -> Result<()> {
         let manager = self.manager()?;
@@ -371,10 +487,62 @@ impl SystemdManager {
     }
 }
 
+/// Explain a failed session bus connection.
+///
+/// Inside a toolbox the session bus is only there if the container shares
+/// the host's `$XDG_RUNTIME_DIR`, which isn't a given the way the system
+/// bus is, so say where to run the command instead.
+fn session_bus_unreachable(error: &str, address: Option<&str>, in_toolbox: bool) -> String {
+    let mut message = match address {
+        Some(address) => format!(
+            "User session bus at {} is not reachable: {}",
+            address, error
+        ),
+        None => format!(
+            "User session bus is not reachable (DBUS_SESSION_BUS_ADDRESS is not set): {}",
+            error
+        ),
+    };
+    if in_toolbox {
+        message.push_str(
+            "\nThe toolbox can't see the host's session bus; run the command on the host \
+             (without --no-delegate) to manage user units.",
+        );
+    } else {
+        message.push_str("\nUser units need a running login session for this user.");
+    }
+    message
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unit_scope_labels_and_flags() {
+        assert_eq!(UnitScope::from_user_flag(false), UnitScope::System);
+        assert_eq!(UnitScope::from_user_flag(true), UnitScope::User);
+        assert_eq!(UnitScope::System.label(), "system");
+        assert_eq!(UnitScope::User.label(), "user");
+        assert_eq!(UnitScope::System.flag(), None);
+        assert_eq!(UnitScope::User.flag(), Some("--user"));
+    }
+
+    #[test]
+    fn test_session_bus_unreachable_explains_toolbox() {
+        let host = session_bus_unreachable("connection refused", None, false);
+        assert!(host.contains("DBUS_SESSION_BUS_ADDRESS is not set"));
+        assert!(host.contains("login session"));
+
+        let toolbox = session_bus_unreachable(
+            "No such file or directory",
+            Some("unix:path=/run/user/1000/bus"),
+            true,
+        );
+        assert!(toolbox.contains("unix:path=/run/user/1000/bus"));
+        assert!(toolbox.contains("run the command on the host"));
+    }
+
     #[test]
     fn test_normalize_unit_name_with_suffix() {
         assert_eq!(
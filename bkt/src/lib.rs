@@ -23,6 +23,7 @@
 pub mod cli;
 pub mod command_runner;
 pub mod commands;
+pub mod connectivity;
 pub mod containerfile;
 pub mod context;
 pub mod daemon;
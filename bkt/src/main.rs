@@ -27,12 +27,16 @@ fn maybe_delegate(cli: &Cli) -> Result<()> {
 
     let runtime = context::detect_environment();
     let target = cli.command.target();
+    // Keep stdout parseable for `--format json`
+    let quiet = cli.format == Some(output::OutputFormat::Json);
 
     match (runtime, target) {
         // In toolbox, command wants host → delegate to host
         (context::RuntimeEnvironment::Toolbox, context::CommandTarget::Host) => {
             if cli.dry_run {
-                output::Output::dry_run("Would delegate to host: distrobox-host-exec bkt ...");
+                if !quiet {
+                    output::Output::dry_run("Would delegate to host: distrobox-host-exec bkt ...");
+                }
                 return Ok(());
             }
             delegate_to_host()?;
@@ -41,9 +45,11 @@ fn maybe_delegate(cli: &Cli) -> Result<()> {
         // On host, command wants dev → delegate to toolbox
         (context::RuntimeEnvironment::Host, context::CommandTarget::Dev) => {
             if cli.dry_run {
-                output::Output::dry_run(
-                    "Would delegate to toolbox: distrobox enter bootc-dev -- bkt ...",
-                );
+                if !quiet {
+                    output::Output::dry_run(
+                        "Would delegate to toolbox: distrobox enter bootc-dev -- bkt ...",
+                    );
+                }
                 return Ok(());
             }
             delegate_to_toolbox()?;
@@ -77,6 +83,10 @@ fn delegate_to_host() -> Result<()> {
     if daemon::daemon_available() {
         match delegate_via_daemon() {
             Ok(()) => return Ok(()),
+            Err(e) if e.is::<daemon::DaemonDenied>() => {
+                // The daemon refused us; flatpak-spawn has its own checks
+                tracing::warn!("{}, falling back to flatpak-spawn", e);
+            }
             Err(e) => {
                 // Daemon failed - fall back to flatpak-spawn
                 tracing::warn!(
@@ -160,14 +170,26 @@ fn delegate_to_toolbox() -> Result<()> {
 }
 
 fn main() -> Result<()> {
-    // Initialize tracing with RUST_LOG env filter
-    // e.g., RUST_LOG=bkt=debug
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    // Completion scripts call this on every <TAB>; skip all setup
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(commands::completions::HELPER) {
+        commands::completions::complete(&args[2..]);
+        return Ok(());
+    }
 
     let cli = Cli::parse();
 
+    let verbosity = output::Verbosity::from_flags(cli.quiet, cli.verbose);
+    output::Output::set_verbosity(verbosity);
+
+    // Initialize tracing with RUST_LOG env filter
+    // e.g., RUST_LOG=bkt=debug; -v/-vv raise bkt's own level on top of it
+    let mut filter = EnvFilter::from_default_env();
+    if let Some(directive) = verbosity.log_directive() {
+        filter = filter.add_directive(directive.parse()?);
+    }
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
     // Check if we need to delegate to a different context (RFC-0010)
     maybe_delegate(&cli)?;
 
@@ -199,15 +221,19 @@ fn main() -> Result<()> {
         Commands::Gsetting(args) => commands::gsetting::run(args, &plan),
         Commands::Homebrew(args) => commands::homebrew::run(args, &plan),
         Commands::Skel(args) => commands::skel::run(args, &plan),
-        Commands::Profile(args) => commands::profile::run(args, plan.runner()),
-        Commands::Repo(args) => commands::repo::run(args),
+        Commands::Profile(args) => commands::profile::run(args, &plan),
+        Commands::Repo(args) => commands::repo::run(args, &plan),
+        Commands::Pr(args) => commands::pr::run(args, &plan),
         Commands::Schema(args) => commands::schema::run(args),
         Commands::Completions(args) => commands::completions::run(args),
         Commands::Doctor(args) => commands::doctor::run(args),
         Commands::Status(args) => commands::status::run(args),
         Commands::Upstream(args) => commands::upstream::run(args, plan.runner()),
+        Commands::ExternalRepo(args) => commands::external_repo::run(args, &plan),
+        Commands::Undo(args) => commands::undo::run(args, &plan),
         Commands::Changelog(args) => commands::changelog::run(args),
-        Commands::Drift(args) => commands::drift::run(args),
+        Commands::Drift(args) => commands::drift::run(args, &plan),
+        Commands::Diff(args) => commands::diff::run(args, &plan),
         Commands::Base(args) => commands::base::run(args, plan.runner()),
         Commands::BuildInfo(args) => commands::build_info::run(args, plan.runner()),
         Commands::Containerfile(args) => commands::containerfile::run(args, &plan),
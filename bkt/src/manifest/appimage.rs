@@ -4,10 +4,11 @@
 //! The manifest format is simplified and backend-agnostic, with conversion
 //! to GearLever's native format happening at sync time.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -31,6 +32,15 @@ pub struct AppImageApp {
     /// Whether this app is disabled (won't sync).
     #[serde(default, skip_serializing_if = "is_false")]
     pub disabled: bool,
+    /// Release asset URL that `sha256` and `size` were recorded from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Expected SHA256 of the file at `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Expected size in bytes of the file at `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
 #[allow(dead_code)]
@@ -48,6 +58,9 @@ impl AppImageApp {
             asset: asset.into(),
             prereleases: false,
             disabled: false,
+            url: None,
+            sha256: None,
+            size: None,
         }
     }
 
@@ -58,6 +71,52 @@ impl AppImageApp {
         self
     }
 
+    /// Whether a checksum has been pinned for this app.
+    pub fn is_pinned(&self) -> bool {
+        self.url.is_some() && self.sha256.is_some()
+    }
+
+    /// Record the checksum and size of `bytes`, downloaded from `url`.
+    pub fn pin(&mut self, url: impl Into<String>, bytes: &[u8]) {
+        self.url = Some(url.into());
+        self.sha256 = Some(hex::encode(Sha256::digest(bytes)));
+        self.size = Some(bytes.len() as u64);
+    }
+
+    /// Check downloaded `bytes` against the pinned size and checksum.
+    ///
+    /// Apps without a pinned checksum always pass.
+    pub fn verify_download(&self, bytes: &[u8]) -> Result<()> {
+        let url = self.url.as_deref().unwrap_or("(no url)");
+
+        if let Some(expected) = self.size
+            && expected != bytes.len() as u64
+        {
+            bail!(
+                "Size mismatch for AppImage '{}' ({}): expected {} bytes, got {}",
+                self.name,
+                url,
+                expected,
+                bytes.len()
+            );
+        }
+
+        if let Some(expected) = &self.sha256 {
+            let actual = hex::encode(Sha256::digest(bytes));
+            if !expected.eq_ignore_ascii_case(&actual) {
+                bail!(
+                    "Checksum mismatch for AppImage '{}' ({}): expected sha256 {}, got {}",
+                    self.name,
+                    url,
+                    expected,
+                    actual
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// Generate base64 key for GearLever's format.
     pub fn b64_key(&self) -> String {
         BASE64.encode(&self.name)
@@ -118,7 +177,7 @@ impl AppImageAppsManifest {
             fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content + "\n")?;
+        super::history::write(path, &(content + "\n"))?;
         Ok(())
     }
 
@@ -208,6 +267,9 @@ impl GearLeverNativeEntry {
             asset: self.update_manager_config.repo_filename.clone(),
             prereleases: self.update_manager_config.allow_prereleases,
             disabled: false,
+            url: None,
+            sha256: None,
+            size: None,
         })
     }
 }
@@ -387,11 +449,8 @@ mod tests {
         let mut manifest = AppImageAppsManifest::default();
         manifest.upsert(AppImageApp::new("App1", "owner/app1", "app1.AppImage"));
         manifest.upsert(AppImageApp {
-            name: "App2".to_string(),
-            repo: "owner/app2".to_string(),
-            asset: "app2.AppImage".to_string(),
-            prereleases: false,
             disabled: true,
+            ..AppImageApp::new("App2", "owner/app2", "app2.AppImage")
         });
 
         let enabled: Vec<_> = manifest.enabled_apps().collect();
@@ -399,6 +458,50 @@ mod tests {
         assert_eq!(enabled[0].name, "App1");
     }
 
+    #[test]
+    fn test_pinned_download_verification() {
+        let url = "https://github.com/owner/repo/releases/download/v1/app.AppImage";
+        let mut app = AppImageApp::new("App", "owner/repo", "app.AppImage");
+        assert!(!app.is_pinned());
+        assert!(app.verify_download(b"anything").is_ok());
+
+        app.pin(url, b"vetted bytes");
+        assert!(app.is_pinned());
+        assert_eq!(app.size, Some(12));
+        assert!(app.verify_download(b"vetted bytes").is_ok());
+
+        let err = app
+            .verify_download(b"other bytes!")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Checksum mismatch for AppImage 'App'"),
+            "{err}"
+        );
+        assert!(err.contains(app.sha256.as_deref().unwrap()), "{err}");
+
+        let err = app.verify_download(b"short").unwrap_err().to_string();
+        assert!(err.contains("expected 12 bytes, got 5"), "{err}");
+    }
+
+    #[test]
+    fn test_pin_fields_roundtrip_and_are_omitted_when_unset() {
+        let app = AppImageApp::new("App", "owner/repo", "app.AppImage");
+        let json = serde_json::to_string(&app).unwrap();
+        assert!(!json.contains("sha256"));
+
+        let mut pinned = app.clone();
+        pinned.pin("https://example.com/app.AppImage", b"bytes");
+        let parsed: AppImageApp =
+            serde_json::from_str(&serde_json::to_string(&pinned).unwrap()).unwrap();
+        assert_eq!(parsed.sha256, pinned.sha256);
+        assert_eq!(parsed.size, Some(5));
+        assert_eq!(
+            parsed.url.as_deref(),
+            Some("https://example.com/app.AppImage")
+        );
+    }
+
     #[test]
     fn test_gearlever_roundtrip() {
         let app = AppImageApp::new("TestApp", "owner/repo", "test.AppImage").with_prereleases();
@@ -116,7 +116,8 @@ impl BaseImageAssumptions {
 
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize base image assumptions")?;
-        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        super::history::write(path, &content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
         Ok(())
     }
 
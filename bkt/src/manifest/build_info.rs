@@ -7,7 +7,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::diff::{ChangedItem, DiffResult};
-use super::parsers::SemanticDiff;
+use super::parsers::{LineSummary, SemanticDiff};
 use super::{AppImageApp, ExtensionItem, FlatpakApp, FlatpakRemote, GSetting, Shim};
 
 /// Schema version for forward compatibility.
@@ -25,6 +25,9 @@ pub struct BuildInfo {
     /// System config diffs (Phase 3)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_config: Option<SystemConfigDiffs>,
+    /// Containerfile changes, including ones no manifest accounts for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub containerfile: Option<ContainerfileDiffs>,
     /// Upstream changes (Phase 2)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub upstream: Option<UpstreamChanges>,
@@ -41,6 +44,7 @@ impl BuildInfo {
             build,
             manifests,
             system_config: None,
+            containerfile: None,
             upstream: None,
             provenance: vec![],
         }
@@ -50,6 +54,7 @@ impl BuildInfo {
     pub fn is_empty(&self) -> bool {
         self.manifests.is_empty()
             && self.system_config.as_ref().is_none_or(|s| s.is_empty())
+            && self.containerfile.as_ref().is_none_or(|c| c.is_empty())
             && self.upstream.is_none()
             && self.provenance.is_empty()
     }
@@ -146,6 +151,12 @@ pub struct FlatpakRemoteDiff {
     pub url: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_path: Option<String>,
 }
 
 impl From<&FlatpakRemote> for FlatpakRemoteDiff {
@@ -154,6 +165,9 @@ impl From<&FlatpakRemote> for FlatpakRemoteDiff {
             name: remote.name.clone(),
             url: remote.url.clone(),
             scope: Some(remote.scope.to_string()),
+            priority: remote.priority,
+            subset: remote.subset.clone(),
+            filter_path: remote.filter_path.clone(),
         }
     }
 }
@@ -321,15 +335,48 @@ pub struct SystemConfigModified {
     pub diff: Option<String>,
 }
 
+/// Changes to the Containerfile between two builds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerfileDiffs {
+    /// Managed sections whose content changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<ContainerfileSectionDiff>,
+    /// Line changes outside the managed sections (cache epochs, wrappers, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unmanaged: Option<LineSummary>,
+}
+
+impl ContainerfileDiffs {
+    /// Returns true if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty() && self.unmanaged.is_none()
+    }
+}
+
+/// A managed Containerfile section that changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerfileSectionDiff {
+    /// Section marker name, e.g. `SYSTEM_PACKAGES`
+    pub section: String,
+    /// Items the section now installs or configures (packages, repos, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Items the section no longer installs or configures
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// Changed lines, which also covers changes that don't touch an item
+    pub lines: LineSummary,
+}
+
 /// Upstream changes (Phase 2).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpstreamChanges {
     /// Base image changes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_image: Option<BaseImageChange>,
-    /// Upstream tool changes
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<ToolChanges>,
+    /// Upstream and fetchbin-managed tools whose pinned version changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolChange>,
 }
 
 /// Base image change information.
@@ -361,30 +408,29 @@ pub struct PackageUpdate {
     pub to: String,
 }
 
-/// Upstream tool changes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolChanges {
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub added: Vec<ToolEntry>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub removed: Vec<ToolEntry>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub updated: Vec<ToolUpdate>,
-}
-
-/// A tool entry.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolEntry {
+/// A tool added, removed or moved to another version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolChange {
     pub name: String,
-    pub version: String,
+    /// Version before the build; absent for a new tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    /// Version in this build; absent for a removed tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_version: Option<String>,
+    /// Where it's tracked: `upstream` or `fetchbin`
+    pub source: String,
 }
 
-/// A tool update entry.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolUpdate {
-    pub name: String,
-    pub from: String,
-    pub to: String,
+impl ToolChange {
+    /// `v1 → v2`, or `new` / `removed` for a tool that came or went.
+    pub fn describe(&self) -> String {
+        match (&self.previous_version, &self.current_version) {
+            (Some(from), Some(to)) => format!("{} → {}", from, to),
+            (None, _) => "new".to_string(),
+            (_, None) => "removed".to_string(),
+        }
+    }
 }
 
 /// Provenance entry (Phase 4).
@@ -0,0 +1,115 @@
+//! Items `bkt capture` should leave out of the manifests.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The capture-ignore.json user manifest.
+///
+/// Maps a capture subsystem to the operation targets it should never
+/// capture (e.g. `"flatpak": ["flatpak:org.example.Experiment"]`). Filled in
+/// by "always ignore" in `bkt capture --interactive`; one-off experiments
+/// are per machine, so it lives in `~/.config/bootc/` rather than the repo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureIgnore {
+    /// Subsystem name to ignored targets.
+    #[serde(flatten)]
+    pub subsystems: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CaptureIgnore {
+    /// File name within the user config directory.
+    pub const FILE_NAME: &'static str = "capture-ignore.json";
+
+    /// Path to the user's capture-ignore.json.
+    pub fn user_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("Cannot determine $HOME")?;
+        Ok(PathBuf::from(home)
+            .join(".config/bootc")
+            .join(Self::FILE_NAME))
+    }
+
+    /// Load from a path, or an empty list if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).with_context(|| {
+            format!("Failed to read capture ignore list from {}", path.display())
+        })?;
+        serde_json::from_str(&content).with_context(|| {
+            format!(
+                "Failed to parse capture ignore list from {}",
+                path.display()
+            )
+        })
+    }
+
+    /// Load the user's capture-ignore.json.
+    pub fn load_user() -> Result<Self> {
+        Self::load(&Self::user_path()?)
+    }
+
+    /// Save to a path, creating its directory.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        super::history::write(path, &format!("{}\n", content))
+    }
+
+    /// Save the user's capture-ignore.json.
+    pub fn save_user(&self) -> Result<()> {
+        self.save(&Self::user_path()?)
+    }
+
+    /// Whether `subsystem` should never capture `target`.
+    pub fn is_ignored(&self, subsystem: &str, target: &str) -> bool {
+        self.subsystems
+            .get(subsystem)
+            .is_some_and(|targets| targets.contains(target))
+    }
+
+    /// Add `target` to `subsystem`'s list. Returns false if it was already there.
+    pub fn ignore(&mut self, subsystem: &str, target: impl Into<String>) -> bool {
+        self.subsystems
+            .entry(subsystem.to_string())
+            .or_default()
+            .insert(target.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn ignore_list_round_trips_per_subsystem() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("bootc").join(CaptureIgnore::FILE_NAME);
+        assert_eq!(
+            CaptureIgnore::load(&path).unwrap(),
+            CaptureIgnore::default()
+        );
+
+        let mut ignore = CaptureIgnore::default();
+        assert!(ignore.ignore("flatpak", "flatpak:org.example.Test"));
+        assert!(!ignore.ignore("flatpak", "flatpak:org.example.Test"));
+        ignore.ignore("system", "package:htop");
+        ignore.save(&path).unwrap();
+
+        let loaded = CaptureIgnore::load(&path).unwrap();
+        assert!(loaded.is_ignored("flatpak", "flatpak:org.example.Test"));
+        assert!(loaded.is_ignored("system", "package:htop"));
+        assert!(!loaded.is_ignored("homebrew", "package:htop"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["system"], serde_json::json!(["package:htop"]));
+    }
+}
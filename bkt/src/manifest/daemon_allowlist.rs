@@ -0,0 +1,89 @@
+//! Daemon allowlist manifest types.
+//!
+//! daemon-allowlist.json lists the programs the host daemon will run on
+//! behalf of containers. Anything not listed is refused unless the daemon
+//! was started with `--allow-all`.
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Programs permitted when no allowlist manifest exists: just delegation.
+pub const DEFAULT_ALLOWED_COMMANDS: &[&str] = &["bkt"];
+
+/// The daemon-allowlist.json manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DaemonAllowlist {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Permitted argv[0] values, matched exactly (e.g. "bkt" or
+    /// "/usr/bin/rpm-ostree")
+    #[serde(default)]
+    pub commands: Vec<String>,
+}
+
+impl Default for DaemonAllowlist {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            commands: DEFAULT_ALLOWED_COMMANDS
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl DaemonAllowlist {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/daemon-allowlist.json";
+
+    /// Load a manifest from a path.
+    pub fn load(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read daemon allowlist from {}", path.display()))?;
+        let manifest: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse daemon allowlist from {}", path.display()))?;
+        Ok(manifest)
+    }
+
+    /// Load from the repository's manifests directory.
+    pub fn load_repo() -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load(&repo.join(Self::PROJECT_PATH))
+    }
+
+    /// Whether `program` (a request's argv[0]) may be run.
+    pub fn permits(&self, program: &str) -> bool {
+        self.commands.iter().any(|c| c == program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_manifest_allows_only_bkt() {
+        let temp_dir = TempDir::new().unwrap();
+        let allowlist =
+            DaemonAllowlist::load(&temp_dir.path().join("daemon-allowlist.json")).unwrap();
+        assert!(allowlist.permits("bkt"));
+        assert!(!allowlist.permits("sh"));
+    }
+
+    #[test]
+    fn test_permits_matches_argv0_exactly() {
+        let allowlist: DaemonAllowlist =
+            serde_json::from_str(r#"{"commands": ["bkt", "/usr/bin/rpm-ostree"]}"#).unwrap();
+        assert!(allowlist.permits("/usr/bin/rpm-ostree"));
+        assert!(!allowlist.permits("rpm-ostree"));
+        assert!(!allowlist.permits("/tmp/bkt"));
+    }
+}
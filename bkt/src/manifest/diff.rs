@@ -115,6 +115,7 @@ impl Diffable for FlatpakApp {
             || self.scope != other.scope
             || self.branch != other.branch
             || self.commit != other.commit
+            || self.pinned != other.pinned
             || self.overrides != other.overrides
     }
 }
@@ -125,7 +126,12 @@ impl Diffable for FlatpakRemote {
     }
 
     fn content_differs(&self, other: &Self) -> bool {
-        self.url != other.url || self.scope != other.scope || self.filtered != other.filtered
+        self.url != other.url
+            || self.scope != other.scope
+            || self.filtered != other.filtered
+            || self.priority != other.priority
+            || self.subset != other.subset
+            || self.filter_path != other.filter_path
     }
 }
 
@@ -141,7 +147,7 @@ impl Diffable for ExtensionItem {
 
 impl Diffable for GSetting {
     fn diff_key(&self) -> String {
-        format!("{}:{}", self.schema, self.key)
+        format!("{}:{}", self.schema_spec(), self.key)
     }
 
     fn content_differs(&self, other: &Self) -> bool {
@@ -54,14 +54,18 @@ pub struct DistroboxContainer {
     pub image: String,
 
     /// Additional packages to install (distrobox additional_packages)
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        alias = "additional_packages",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub packages: Vec<String>,
 
     /// Binary exports
     #[serde(default, skip_serializing_if = "DistroboxBins::is_empty")]
     pub bins: DistroboxBins,
 
-    /// Apps to export (desktop entries)
+    /// Apps to export (desktop entries, named as for `distrobox-export --app`)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub exported_apps: Vec<String>,
 
@@ -132,7 +136,7 @@ impl DistroboxManifest {
         }
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize distrobox manifest")?;
-        fs::write(path, content)
+        super::history::write(path, &content)
             .with_context(|| format!("Failed to write distrobox manifest to {}", path.display()))?;
         Ok(())
     }
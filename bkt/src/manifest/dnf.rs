@@ -3,6 +3,7 @@
 use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -22,6 +23,36 @@ fn default_true() -> bool {
     true
 }
 
+/// A package group as stored in manifests and passed to `dnf install`:
+/// `development-tools` and `@development-tools` both become the latter.
+pub fn group_spec(name: &str) -> String {
+    format!("@{}", name.trim().trim_start_matches('@'))
+}
+
+/// A group's ID, as `dnf group` subcommands take it (no `@`).
+pub fn group_id(name: &str) -> &str {
+    name.trim().trim_start_matches('@')
+}
+
+/// Add `name` to a sorted group list. Returns true if it wasn't there.
+pub(crate) fn insert_group(groups: &mut Vec<String>, name: &str) -> bool {
+    let spec = group_spec(name);
+    if groups.iter().any(|g| group_spec(g) == spec) {
+        return false;
+    }
+    groups.push(spec);
+    groups.sort();
+    true
+}
+
+/// Remove `name` from a group list, with or without its `@`.
+pub(crate) fn delete_group(groups: &mut Vec<String>, name: &str) -> bool {
+    let spec = group_spec(name);
+    let len = groups.len();
+    groups.retain(|g| group_spec(g) != spec);
+    groups.len() < len
+}
+
 impl CoprRepo {
     pub fn new(name: String) -> Self {
         Self {
@@ -55,6 +86,26 @@ pub struct SystemPackagesManifest {
     /// COPR repositories
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub copr_repos: Vec<CoprRepo>,
+
+    /// Packages pinned to a specific version (package name -> version)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pins: BTreeMap<String, String>,
+
+    /// Packages only available on some architectures
+    /// (package name -> arches, e.g. `{"steam-devices": ["x86_64"]}`).
+    /// Packages without an entry are installed on every architecture.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub arches: BTreeMap<String, Vec<String>>,
+
+    /// Versions recorded by `bkt system capture --with-versions`
+    /// (package name -> EVR). Informational only: the image build ignores
+    /// these unless the package also has an entry in `pins`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub versions: BTreeMap<String, String>,
+
+    /// Layered packages that `bkt system capture` should never add
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capture_exclude: Vec<String>,
 }
 
 impl SystemPackagesManifest {
@@ -89,7 +140,7 @@ impl SystemPackagesManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize system packages manifest")?;
-        fs::write(path, content).with_context(|| {
+        super::history::write(path, &content).with_context(|| {
             format!(
                 "Failed to write system packages manifest to {}",
                 path.display()
@@ -131,6 +182,66 @@ impl SystemPackagesManifest {
         self.packages.len() < len
     }
 
+    /// Replace `remove` with `install`, dropping any pin on `remove`.
+    /// Returns true if `remove` was in the manifest.
+    pub fn swap_package(&mut self, remove: &str, install: String) -> bool {
+        let removed = self.remove_package(remove);
+        self.unpin(remove);
+        self.add_package(install);
+        removed
+    }
+
+    /// Check whether a package group is in the manifest.
+    pub fn find_group(&self, name: &str) -> bool {
+        let spec = group_spec(name);
+        self.groups.iter().any(|g| group_spec(g) == spec)
+    }
+
+    /// Add a package group (stored as `@name`).
+    /// Returns true if the group was added, false if it already existed.
+    pub fn add_group(&mut self, name: &str) -> bool {
+        insert_group(&mut self.groups, name)
+    }
+
+    /// Remove a package group.
+    pub fn remove_group(&mut self, name: &str) -> bool {
+        delete_group(&mut self.groups, name)
+    }
+
+    /// Get the pinned version of a package, if any.
+    pub fn pinned_version(&self, name: &str) -> Option<&str> {
+        self.pins.get(name).map(String::as_str)
+    }
+
+    /// Pin a package to a specific version.
+    /// Returns the previously pinned version, if any.
+    pub fn pin(&mut self, name: String, version: String) -> Option<String> {
+        self.pins.insert(name, version)
+    }
+
+    /// Remove a package pin.
+    pub fn unpin(&mut self, name: &str) -> bool {
+        self.pins.remove(name).is_some()
+    }
+
+    /// Check whether `bkt system capture` should skip a package.
+    pub fn is_capture_excluded(&self, name: &str) -> bool {
+        self.capture_exclude.iter().any(|p| p == name)
+    }
+
+    /// Find a pin that conflicts with a `name-version` package spec.
+    ///
+    /// Returns `(name, pinned_version)` when the spec names a pinned package
+    /// with a version other than the pinned one. Only suffixes starting with a
+    /// digit are treated as versions, so `code-insiders` doesn't match `code`.
+    pub fn conflicting_pin(&self, spec: &str) -> Option<(&str, &str)> {
+        self.pins.iter().find_map(|(name, version)| {
+            let requested = spec.strip_prefix(name.as_str())?.strip_prefix('-')?;
+            (requested.starts_with(|c: char| c.is_ascii_digit()) && requested != version)
+                .then_some((name.as_str(), version.as_str()))
+        })
+    }
+
     /// Find a COPR repo by name.
     pub fn find_copr(&self, name: &str) -> Option<&CoprRepo> {
         self.copr_repos.iter().find(|c| c.name == name)
@@ -200,6 +311,22 @@ mod tests {
         assert!(!manifest.remove_package("htop")); // Already removed
     }
 
+    #[test]
+    fn manifest_swap_package() {
+        let mut manifest = SystemPackagesManifest::default();
+        manifest.add_package("htop".to_string());
+        manifest.add_package("mesa-va-drivers".to_string());
+        manifest.pin("mesa-va-drivers".to_string(), "25.0.0".to_string());
+
+        assert!(manifest.swap_package("mesa-va-drivers", "mesa-va-drivers-freeworld".to_string()));
+        assert_eq!(manifest.packages, vec!["htop", "mesa-va-drivers-freeworld"]);
+        assert_eq!(manifest.pinned_version("mesa-va-drivers"), None);
+
+        // Swapping out a package only installed locally still adds the other
+        assert!(!manifest.swap_package("nano", "vim-enhanced".to_string()));
+        assert!(manifest.find_package("vim-enhanced"));
+    }
+
     #[test]
     fn manifest_copr_operations() {
         let mut manifest = SystemPackagesManifest::default();
@@ -210,6 +337,75 @@ mod tests {
         assert!(manifest.find_copr("atim/starship").is_none());
     }
 
+    #[test]
+    fn manifest_group_operations() {
+        let mut manifest = SystemPackagesManifest::default();
+        assert!(manifest.add_group("development-tools"));
+        assert!(manifest.add_group("@c-development"));
+        assert!(!manifest.add_group("@development-tools"));
+        assert_eq!(
+            manifest.groups,
+            vec!["@c-development", "@development-tools"]
+        );
+        assert!(manifest.find_group("c-development"));
+        assert!(manifest.remove_group("c-development"));
+        assert!(!manifest.remove_group("c-development"));
+        assert_eq!(group_id("@development-tools"), "development-tools");
+    }
+
+    #[test]
+    fn manifest_pin_operations() {
+        let mut manifest = SystemPackagesManifest::default();
+        assert!(
+            manifest
+                .pin("code".to_string(), "1.95.0".to_string())
+                .is_none()
+        );
+        assert_eq!(manifest.pinned_version("code"), Some("1.95.0"));
+        assert_eq!(
+            manifest.pin("code".to_string(), "1.96.0".to_string()),
+            Some("1.95.0".to_string())
+        );
+        assert!(manifest.unpin("code"));
+        assert!(!manifest.unpin("code"));
+        assert!(manifest.pinned_version("code").is_none());
+    }
+
+    #[test]
+    fn manifest_conflicting_pin() {
+        let mut manifest = SystemPackagesManifest::default();
+        manifest.pin("code".to_string(), "1.95.0".to_string());
+
+        assert_eq!(
+            manifest.conflicting_pin("code-1.96.0"),
+            Some(("code", "1.95.0"))
+        );
+        assert!(manifest.conflicting_pin("code-1.95.0").is_none());
+        assert!(manifest.conflicting_pin("code").is_none());
+        assert!(manifest.conflicting_pin("codelite-2.0").is_none());
+        assert!(manifest.conflicting_pin("code-insiders").is_none());
+    }
+
+    #[test]
+    fn manifest_capture_fields_are_optional() {
+        let manifest: SystemPackagesManifest =
+            serde_json::from_str(r#"{"packages": ["htop"]}"#).unwrap();
+        assert!(manifest.versions.is_empty());
+        assert!(!manifest.is_capture_excluded("htop"));
+
+        let mut manifest = manifest;
+        manifest.capture_exclude.push("langpacks-en".to_string());
+        manifest
+            .versions
+            .insert("htop".to_string(), "3.3.0-4.fc42".to_string());
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: SystemPackagesManifest = serde_json::from_str(&json).unwrap();
+
+        assert!(parsed.is_capture_excluded("langpacks-en"));
+        assert_eq!(parsed.versions["htop"], "3.3.0-4.fc42");
+        assert!(parsed.pinned_version("htop").is_none());
+    }
+
     #[test]
     fn manifest_load_nonexistent_returns_default() {
         let result = SystemPackagesManifest::load(&PathBuf::from("/nonexistent/path.json"));
@@ -233,6 +429,7 @@ mod tests {
         assert_eq!(loaded.packages, manifest.packages);
         assert_eq!(loaded.copr_repos.len(), 1);
         assert_eq!(loaded.copr_repos[0].name, "atim/starship");
+        assert!(loaded.pins.is_empty());
     }
 
     #[test]
@@ -3,8 +3,9 @@
 use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The gnome-extensions.json manifest.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
@@ -14,6 +15,11 @@ pub struct GnomeExtensionsManifest {
     /// List of extension items, either string UUIDs or objects with state
     #[serde(default)]
     pub extensions: Vec<ExtensionItem>,
+    /// Have `bkt extension sync` download extensions that aren't installed
+    /// from extensions.gnome.org. Off by default: only extensions shipped
+    /// in the image (or installed by hand) are enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_from_web: Option<bool>,
 }
 
 /// A GNOME extension entry in the manifest.
@@ -33,12 +39,32 @@ pub struct ExtensionConfig {
     pub id: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Numeric id on extensions.gnome.org, recorded when added by lookup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pk: Option<u64>,
+    /// dconf directory holding the extension's settings, when it isn't
+    /// `/org/gnome/shell/extensions/<uuid up to @>/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dconf_path: Option<String>,
+    /// Settings under the dconf directory, keyed by path relative to it,
+    /// with GVariant text values (as `dconf dump` prints them)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+    /// Overrides the manifest-wide `install_from_web` for this extension
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_from_web: Option<bool>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// The dconf directory extensions conventionally keep their settings in.
+pub fn default_dconf_dir(uuid: &str) -> String {
+    let slug = uuid.split('@').next().unwrap_or(uuid);
+    format!("/org/gnome/shell/extensions/{}/", slug)
+}
+
 impl ExtensionItem {
     /// Get the UUID of the extension.
     pub fn id(&self) -> &str {
@@ -48,6 +74,22 @@ impl ExtensionItem {
         }
     }
 
+    /// The extensions.gnome.org id, if recorded.
+    pub fn pk(&self) -> Option<u64> {
+        match self {
+            ExtensionItem::Uuid(_) => None,
+            ExtensionItem::Object(config) => config.pk,
+        }
+    }
+
+    /// This entry's `install_from_web` override, if any.
+    pub fn install_from_web(&self) -> Option<bool> {
+        match self {
+            ExtensionItem::Uuid(_) => None,
+            ExtensionItem::Object(config) => config.install_from_web,
+        }
+    }
+
     /// Check if the extension should be enabled.
     pub fn enabled(&self) -> bool {
         match self {
@@ -55,6 +97,47 @@ impl ExtensionItem {
             ExtensionItem::Object(config) => config.enabled,
         }
     }
+
+    /// The dconf directory the extension's settings live in.
+    pub fn dconf_dir(&self) -> String {
+        match self {
+            ExtensionItem::Object(ExtensionConfig {
+                dconf_path: Some(path),
+                ..
+            }) => format!("{}/", path.trim_end_matches('/')),
+            _ => default_dconf_dir(self.id()),
+        }
+    }
+
+    /// Recorded settings as (absolute dconf key, GVariant value) pairs.
+    pub fn dconf_settings(&self) -> Vec<(String, String)> {
+        match self {
+            ExtensionItem::Uuid(_) => Vec::new(),
+            ExtensionItem::Object(config) => {
+                let dir = self.dconf_dir();
+                config
+                    .settings
+                    .iter()
+                    .map(|(key, value)| (format!("{}{}", dir, key), value.clone()))
+                    .collect()
+            }
+        }
+    }
+
+    /// The entry in object form, for updating its fields.
+    pub fn to_config(&self) -> ExtensionConfig {
+        match self {
+            ExtensionItem::Uuid(id) => ExtensionConfig {
+                id: id.clone(),
+                enabled: true,
+                pk: None,
+                dconf_path: None,
+                settings: BTreeMap::new(),
+                install_from_web: None,
+            },
+            ExtensionItem::Object(config) => config.clone(),
+        }
+    }
 }
 
 impl From<String> for ExtensionItem {
@@ -98,7 +181,7 @@ impl GnomeExtensionsManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize extensions manifest")?;
-        fs::write(path, content).with_context(|| {
+        super::history::write(path, &content).with_context(|| {
             format!("Failed to write extensions manifest to {}", path.display())
         })?;
         Ok(())
@@ -110,12 +193,53 @@ impl GnomeExtensionsManifest {
         Self::load(&repo.join(Self::PROJECT_PATH))
     }
 
+    /// Load the repo manifest with the active profile's overlay, if any,
+    /// merged on top.
+    pub fn load_effective() -> Result<Self> {
+        let overlay = super::ProfileState::overlay_path(Self::PROJECT_PATH)?;
+        Self::load_with_overlay(overlay.as_deref())
+    }
+
+    /// Load the repo manifest with `overlay` (a profile's copy) merged on top.
+    pub fn load_with_overlay(overlay: Option<&Path>) -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load_merged(&repo.join(Self::PROJECT_PATH), overlay)
+    }
+
+    /// Load the manifest at `path` with `overlay` merged on top.
+    pub fn load_merged(path: &Path, overlay: Option<&Path>) -> Result<Self> {
+        let mut manifest = Self::load(&path.to_path_buf())?;
+        if let Some(path) = overlay {
+            manifest.merge(Self::load(&path.to_path_buf())?);
+        }
+        Ok(manifest)
+    }
+
     /// Save to the repository's manifests directory.
     pub fn save_repo(&self) -> Result<()> {
         let repo = crate::repo::find_repo_path()?;
         self.save(&repo.join(Self::PROJECT_PATH))
     }
 
+    /// Overlay another manifest's extensions; entries in `overlay` win, so
+    /// a profile can disable an extension the repo enables.
+    pub fn merge(&mut self, overlay: Self) {
+        for item in overlay.extensions {
+            self.add(item);
+        }
+        if overlay.install_from_web.is_some() {
+            self.install_from_web = overlay.install_from_web;
+        }
+    }
+
+    /// Whether sync may download `item` from extensions.gnome.org when it
+    /// isn't installed: its own setting, else the manifest's, else no.
+    pub fn installs_from_web(&self, item: &ExtensionItem) -> bool {
+        item.install_from_web()
+            .or(self.install_from_web)
+            .unwrap_or(false)
+    }
+
     /// Check if an extension exists.
     pub fn contains(&self, uuid: &str) -> bool {
         self.extensions.iter().any(|ext| ext.id() == uuid)
@@ -148,10 +272,28 @@ impl GnomeExtensionsManifest {
     pub fn set_enabled(&mut self, uuid: &str, enabled: bool) -> bool {
         if let Some(pos) = self.extensions.iter().position(|ext| ext.id() == uuid) {
             // Replace with object format that has the enabled state
-            self.extensions[pos] = ExtensionItem::Object(ExtensionConfig {
-                id: uuid.to_string(),
-                enabled,
-            });
+            let mut config = self.extensions[pos].to_config();
+            config.enabled = enabled;
+            self.extensions[pos] = ExtensionItem::Object(config);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Record an extension's settings, replacing any recorded before.
+    /// Returns true if the extension was found and updated.
+    pub fn set_settings(
+        &mut self,
+        uuid: &str,
+        dconf_path: Option<String>,
+        settings: BTreeMap<String, String>,
+    ) -> bool {
+        if let Some(pos) = self.extensions.iter().position(|ext| ext.id() == uuid) {
+            let mut config = self.extensions[pos].to_config();
+            config.dconf_path = dconf_path;
+            config.settings = settings;
+            self.extensions[pos] = ExtensionItem::Object(config);
             true
         } else {
             false
@@ -160,10 +302,9 @@ impl GnomeExtensionsManifest {
 
     /// Add an extension as disabled.
     pub fn add_disabled(&mut self, uuid: String) {
-        self.extensions.push(ExtensionItem::Object(ExtensionConfig {
-            id: uuid,
-            enabled: false,
-        }));
+        let mut config = ExtensionItem::Uuid(uuid).to_config();
+        config.enabled = false;
+        self.extensions.push(ExtensionItem::Object(config));
         self.extensions.sort_by(|a, b| a.id().cmp(b.id()));
     }
 
@@ -191,6 +332,28 @@ mod tests {
         assert!(manifest.schema.is_none());
     }
 
+    #[test]
+    fn manifest_merge_lets_overlay_disable_extension() {
+        let mut base = GnomeExtensionsManifest::default();
+        base.add("dash-to-dock@micxgx.gmail.com");
+        base.add("appindicatorsupport@rgcjonas.gmail.com");
+
+        let mut overlay = GnomeExtensionsManifest::default();
+        overlay.add_disabled("dash-to-dock@micxgx.gmail.com".to_string());
+        overlay.add("caffeine@patapon.info");
+
+        base.merge(overlay);
+
+        assert_eq!(base.extensions.len(), 3);
+        let dock = base
+            .extensions
+            .iter()
+            .find(|e| e.id() == "dash-to-dock@micxgx.gmail.com")
+            .unwrap();
+        assert!(!dock.enabled());
+        assert!(base.contains("caffeine@patapon.info"));
+    }
+
     #[test]
     fn manifest_contains_checks_existence() {
         let mut manifest = GnomeExtensionsManifest::default();
@@ -318,10 +481,115 @@ mod tests {
         manifest.add(ExtensionItem::Object(ExtensionConfig {
             id: "disabled@example.com".to_string(),
             enabled: false,
+            pk: None,
+            dconf_path: None,
+            settings: BTreeMap::new(),
+            install_from_web: None,
         }));
 
         assert!(manifest.contains("disabled@example.com"));
         let item = manifest.get("disabled@example.com").unwrap();
         assert!(!item.enabled());
     }
+
+    #[test]
+    fn install_from_web_entry_overrides_manifest() {
+        let mut manifest: GnomeExtensionsManifest = serde_json::from_str(
+            r#"{
+                "install_from_web": true,
+                "extensions": [
+                    "caffeine@patapon.info",
+                    {"id": "dash-to-dock@micxgx.gmail.com", "install_from_web": false}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(manifest.installs_from_web(&manifest.extensions[0]));
+        assert!(!manifest.installs_from_web(&manifest.extensions[1]));
+
+        manifest.merge(serde_json::from_str(r#"{"install_from_web": false}"#).unwrap());
+        assert!(!manifest.installs_from_web(&manifest.extensions[0]));
+        assert!(!GnomeExtensionsManifest::default().installs_from_web(&"x@y".into()));
+    }
+
+    #[test]
+    fn manifest_keeps_pk_when_toggling_state() {
+        let mut manifest = GnomeExtensionsManifest::default();
+        manifest.add(ExtensionItem::Object(ExtensionConfig {
+            id: "dash-to-dock@micxgx.gmail.com".to_string(),
+            enabled: true,
+            pk: Some(307),
+            dconf_path: None,
+            settings: BTreeMap::new(),
+            install_from_web: None,
+        }));
+        assert!(manifest.set_enabled("dash-to-dock@micxgx.gmail.com", false));
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert!(json.contains(r#""pk":307"#));
+        let item = manifest.get("dash-to-dock@micxgx.gmail.com").unwrap();
+        assert_eq!(item.pk(), Some(307));
+        assert!(!item.enabled());
+
+        let legacy: ExtensionItem = "legacy@example.com".into();
+        assert_eq!(legacy.pk(), None);
+        assert!(!serde_json::to_string(&legacy).unwrap().contains("pk"));
+    }
+
+    #[test]
+    fn manifest_settings_resolve_under_dconf_dir() {
+        let mut manifest = GnomeExtensionsManifest::default();
+        manifest.add("dash-to-dock@micxgx.gmail.com");
+        manifest.add("appindicatorsupport@rgcjonas.gmail.com");
+
+        let settings: BTreeMap<String, String> = [
+            ("dock-position".to_string(), "'BOTTOM'".to_string()),
+            ("custom/size".to_string(), "48".to_string()),
+        ]
+        .into();
+        assert!(manifest.set_settings("dash-to-dock@micxgx.gmail.com", None, settings));
+        assert!(manifest.set_settings(
+            "appindicatorsupport@rgcjonas.gmail.com",
+            Some("/org/gnome/shell/extensions/appindicator".to_string()),
+            [("icon-size".to_string(), "0".to_string())].into(),
+        ));
+        assert!(!manifest.set_settings("missing@example.com", None, BTreeMap::new()));
+        // Toggling state keeps the recorded settings
+        assert!(manifest.set_enabled("dash-to-dock@micxgx.gmail.com", false));
+
+        let dock = manifest.get("dash-to-dock@micxgx.gmail.com").unwrap();
+        assert_eq!(
+            dock.dconf_settings(),
+            vec![
+                (
+                    "/org/gnome/shell/extensions/dash-to-dock/custom/size".to_string(),
+                    "48".to_string()
+                ),
+                (
+                    "/org/gnome/shell/extensions/dash-to-dock/dock-position".to_string(),
+                    "'BOTTOM'".to_string()
+                ),
+            ]
+        );
+        let indicator = manifest
+            .get("appindicatorsupport@rgcjonas.gmail.com")
+            .unwrap();
+        assert_eq!(
+            indicator.dconf_settings(),
+            vec![(
+                "/org/gnome/shell/extensions/appindicator/icon-size".to_string(),
+                "0".to_string()
+            )]
+        );
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        let parsed: GnomeExtensionsManifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.extensions, manifest.extensions);
+        assert!(
+            !serde_json::to_string(&ExtensionItem::from("x@y").to_config())
+                .unwrap()
+                .contains("settings")
+        );
+    }
 }
@@ -1,7 +1,11 @@
 //! External RPM repositories manifest types.
+use anyhow::{Context, Result};
 pub use bkt_common::manifest::LayerGroup;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct ExternalReposManifest {
@@ -11,12 +15,72 @@ pub struct ExternalReposManifest {
     pub repos: Vec<ExternalRepo>,
 }
 
+impl ExternalReposManifest {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/external-repos.json";
+
+    /// Load from a path; a missing file means no external repos.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read external repos manifest from {}",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&content).with_context(|| {
+            format!(
+                "Failed to parse external repos manifest from {}",
+                path.display()
+            )
+        })
+    }
+
+    /// Save to a path.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize external repos manifest")?;
+        content.push('\n');
+        super::history::write(path, &content).with_context(|| {
+            format!(
+                "Failed to write external repos manifest to {}",
+                path.display()
+            )
+        })
+    }
+
+    /// The repos as rpmcheck checks them, with `$basearch` left to the host.
+    pub fn to_rpmcheck(&self) -> rpmcheck::Manifest {
+        rpmcheck::Manifest {
+            repos: self
+                .repos
+                .iter()
+                .map(|repo| rpmcheck::RepoEntry {
+                    name: repo.name.clone(),
+                    baseurl: repo.baseurl.clone(),
+                    packages: repo.packages.clone(),
+                    basearch: None,
+                    gpg_key_url: None,
+                })
+                .collect(),
+            basearch: None,
+            releasever: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExternalRepo {
     pub name: String,
     pub display_name: String,
     pub baseurl: String,
     pub gpg_key: String,
+    /// Fingerprint of the signing key at `gpg_key`; the build fails if the
+    /// key served there doesn't match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpg_fingerprint: Option<String>,
     pub packages: Vec<String>,
     /// Optional path for /opt relocation (e.g., "microsoft" or "1Password")
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -24,4 +88,48 @@ pub struct ExternalRepo {
     /// Controls deployment layer grouping. Defaults to bundled.
     #[serde(default)]
     pub layer_group: LayerGroup,
+    /// Also download the packages' dependencies that this repo provides,
+    /// skipping any the base image already satisfies.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resolve_deps: bool,
+    /// Alternative base URLs, tried in order after `baseurl`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+}
+
+/// The external-repo-hashes.json manifest: each external repo's hash of its
+/// tracked package versions, written by `bkt containerfile bump-cache`.
+///
+/// The generated Containerfile uses these as the `CACHE_EPOCH_<REPO>`
+/// defaults, so a dl-* stage is rebuilt only when its repo's packages change.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ExternalRepoHashes {
+    /// Repo name -> sha256 of its tracked package versions
+    #[serde(default)]
+    pub repos: BTreeMap<String, String>,
+}
+
+impl ExternalRepoHashes {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/external-repo-hashes.json";
+
+    /// Load from a path; a missing file means no hashes yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read repo hashes from {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse repo hashes from {}", path.display()))
+    }
+
+    /// Save to a path.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content =
+            serde_json::to_string_pretty(self).context("Failed to serialize repo hashes")?;
+        content.push('\n');
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write repo hashes to {}", path.display()))
+    }
 }
@@ -23,7 +23,7 @@ pub struct HostBinary {
     pub binary: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum HostBinarySource {
     Npm {
@@ -36,6 +36,15 @@ pub enum HostBinarySource {
         repo: String,
         #[serde(default)]
         asset_pattern: Option<String>,
+        /// Consider pre-releases and `-rc`/nightly tags
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        include_prerelease: bool,
+    },
+    Gitlab {
+        /// Project path, optionally prefixed with a self-hosted instance host
+        repo: String,
+        #[serde(default)]
+        asset_pattern: Option<String>,
     },
 }
 
@@ -76,7 +85,7 @@ impl HostBinariesManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize host binaries manifest")?;
-        fs::write(path, content + "\n").with_context(|| {
+        super::history::write(path, &(content + "\n")).with_context(|| {
             format!(
                 "Failed to write host binaries manifest to {}",
                 path.display()
@@ -311,11 +311,82 @@ pub struct FlatpakApp {
     /// Commit hash
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit: Option<String>,
+    /// Hold the app at `commit` (sync installs that commit and masks updates)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
     /// Overrides (e.g. "--filesystem=home")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<String>>,
 }
 
+impl FlatpakApp {
+    /// The commit this app is pinned to, if it is pinned.
+    pub fn pinned_commit(&self) -> Option<&str> {
+        if self.pinned {
+            self.commit.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+/// Compare two flatpak commits, allowing either side to be abbreviated.
+///
+/// `flatpak list --columns=active` reports shortened commits, while pins
+/// may record the full hash.
+pub fn commits_match(a: &str, b: &str) -> bool {
+    !a.is_empty() && !b.is_empty() && (a.starts_with(b) || b.starts_with(a))
+}
+
+/// Identify what a `flatpak override` flag controls, ignoring allow/deny.
+///
+/// `--filesystem=~/Games:ro` and `--nofilesystem=~/Games` both control
+/// `("filesystem", "~/Games")`, so a later one replaces the earlier.
+fn override_flag_key(flag: &str) -> Option<(&'static str, String)> {
+    let (name, value) = flag.strip_prefix("--")?.split_once('=')?;
+    let kind = match name {
+        "filesystem" | "nofilesystem" => {
+            // Access mode suffixes (:ro, :rw, :create) don't change the target
+            let path = value.rsplit_once(':').map_or(value, |(path, _)| path);
+            return Some(("filesystem", path.to_string()));
+        }
+        "env" | "unset-env" => {
+            let key = value.split_once('=').map_or(value, |(key, _)| key);
+            return Some(("env", key.to_string()));
+        }
+        "socket" | "nosocket" => "socket",
+        "device" | "nodevice" => "device",
+        "share" | "unshare" => "share",
+        "persist" => "persist",
+        "talk-name" | "own-name" | "see-name" | "no-talk-name" => "session-bus",
+        "system-talk-name" | "system-own-name" | "system-see-name" | "system-no-talk-name" => {
+            "system-bus"
+        }
+        _ => return None,
+    };
+    Some((kind, value.to_string()))
+}
+
+/// Merge new `flatpak override` flags into an existing list.
+///
+/// A new flag replaces any existing flag for the same permission target
+/// (so `--nosocket=x11` replaces `--socket=x11`). Unrecognized flags are
+/// kept as-is. The result is sorted, matching [`FlatpakOverrides::to_cli_flags`].
+pub fn merge_override_flags(existing: &[String], new: &[String]) -> Vec<String> {
+    let replaced: Vec<_> = new.iter().filter_map(|f| override_flag_key(f)).collect();
+
+    let mut merged: Vec<String> = existing
+        .iter()
+        .filter(|flag| !new.contains(flag))
+        .filter(|flag| override_flag_key(flag).is_none_or(|key| !replaced.contains(&key)))
+        .cloned()
+        .collect();
+    merged.extend(new.iter().cloned());
+    merged.sort();
+    merged.dedup();
+    merged
+}
+
 /// The flatpak-apps.json manifest.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct FlatpakAppsManifest {
@@ -348,7 +419,7 @@ impl FlatpakAppsManifest {
         }
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize flatpak manifest")?;
-        fs::write(path, content)
+        super::history::write(path, &content)
             .with_context(|| format!("Failed to write flatpak manifest to {}", path.display()))?;
         Ok(())
     }
@@ -359,12 +430,41 @@ impl FlatpakAppsManifest {
         Self::load(&repo.join(Self::PROJECT_PATH))
     }
 
+    /// Load the repo manifest with the active profile's overlay, if any,
+    /// merged on top.
+    pub fn load_effective() -> Result<Self> {
+        let overlay = super::ProfileState::overlay_path(Self::PROJECT_PATH)?;
+        Self::load_with_overlay(overlay.as_deref())
+    }
+
+    /// Load the repo manifest with `overlay` (a profile's copy) merged on top.
+    pub fn load_with_overlay(overlay: Option<&Path>) -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load_merged(&repo.join(Self::PROJECT_PATH), overlay)
+    }
+
+    /// Load the manifest at `path` with `overlay` merged on top.
+    pub fn load_merged(path: &Path, overlay: Option<&Path>) -> Result<Self> {
+        let mut manifest = Self::load(path)?;
+        if let Some(path) = overlay {
+            manifest.merge(Self::load(path)?);
+        }
+        Ok(manifest)
+    }
+
     /// Save to the repository's manifests directory.
     pub fn save_repo(&self) -> Result<()> {
         let repo = crate::repo::find_repo_path()?;
         self.save(&repo.join(Self::PROJECT_PATH))
     }
 
+    /// Overlay another manifest's apps; entries in `overlay` win.
+    pub fn merge(&mut self, overlay: Self) {
+        for app in overlay.apps {
+            self.upsert(app);
+        }
+    }
+
     /// Find an app by id.
     pub fn find(&self, id: &str) -> Option<&FlatpakApp> {
         self.apps.iter().find(|a| a.id == id)
@@ -393,13 +493,49 @@ impl FlatpakAppsManifest {
 pub struct FlatpakRemote {
     /// Remote name
     pub name: String,
-    /// Remote URL
+    /// Remote URL (usually a `.flatpakrepo` file, which carries the GPG key)
     pub url: String,
     /// Installation scope
     pub scope: FlatpakScope,
     /// Whether the remote is filtered (Flathub verified only)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filtered: Option<bool>,
+    /// Priority relative to other remotes; higher wins (flatpak's default is 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    /// Only offer refs in this subset (e.g. `verified` on Flathub)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subset: Option<String>,
+    /// Path to a flatpak filter file limiting which refs are visible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_path: Option<String>,
+    /// Title from the `.flatpakrepo` file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Repo URL the `.flatpakrepo` file points at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_url: Option<String>,
+    /// SHA-256 of the `.flatpakrepo` file's GPG key when it was added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpg_key_sha256: Option<String>,
+}
+
+impl FlatpakRemote {
+    /// Flags for the recorded priority, subset and filter, accepted by both
+    /// `flatpak remote-add` and `flatpak remote-modify`.
+    pub fn option_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(priority) = self.priority {
+            flags.push(format!("--prio={}", priority));
+        }
+        if let Some(subset) = &self.subset {
+            flags.push(format!("--subset={}", subset));
+        }
+        if let Some(filter) = &self.filter_path {
+            flags.push(format!("--filter={}", filter));
+        }
+        flags
+    }
 }
 
 /// The flatpak-remotes.json manifest.
@@ -411,6 +547,9 @@ pub struct FlatpakRemotesManifest {
 }
 
 impl FlatpakRemotesManifest {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/flatpak-remotes.json";
+
     /// Load a manifest from a path.
     pub fn load(path: &Path) -> Result<Self> {
         if !path.exists() {
@@ -431,9 +570,48 @@ impl FlatpakRemotesManifest {
         Ok(manifest)
     }
 
+    /// Save a manifest to a path.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize flatpak remotes manifest")?;
+        super::history::write(path, &content).with_context(|| {
+            format!(
+                "Failed to write flatpak remotes manifest to {}",
+                path.display()
+            )
+        })?;
+        Ok(())
+    }
+
     /// Load from current working directory (for manifest repos).
     pub fn load_cwd() -> Result<Self> {
-        Self::load(&PathBuf::from("manifests/flatpak-remotes.json"))
+        Self::load(&PathBuf::from(Self::PROJECT_PATH))
+    }
+
+    /// Load from the repository's manifests directory.
+    pub fn load_repo() -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load(&repo.join(Self::PROJECT_PATH))
+    }
+
+    /// Save to the repository's manifests directory.
+    pub fn save_repo(&self) -> Result<()> {
+        let repo = crate::repo::find_repo_path()?;
+        self.save(&repo.join(Self::PROJECT_PATH))
+    }
+
+    /// Find a remote by name.
+    pub fn find(&self, name: &str) -> Option<&FlatpakRemote> {
+        self.remotes.iter().find(|r| r.name == name)
+    }
+
+    /// Add or replace a remote by name.
+    pub fn upsert(&mut self, remote: FlatpakRemote) {
+        if let Some(existing) = self.remotes.iter_mut().find(|r| r.name == remote.name) {
+            *existing = remote;
+        } else {
+            self.remotes.push(remote);
+        }
     }
 
     /// Check if a remote name is managed by this manifest.
@@ -459,6 +637,7 @@ mod tests {
             scope: FlatpakScope::System,
             branch: None,
             commit: None,
+            pinned: false,
             overrides: None,
         }
     }
@@ -470,6 +649,7 @@ mod tests {
             scope: FlatpakScope::User,
             branch: None,
             commit: None,
+            pinned: false,
             overrides: None,
         }
     }
@@ -595,6 +775,82 @@ mod tests {
         assert!(manifest.apps.is_empty());
     }
 
+    // Pin tests
+    #[test]
+    fn pinned_commit_requires_pinned_flag() {
+        let mut app = sample_app("org.test.App");
+        app.commit = Some("abc123".to_string());
+        assert_eq!(app.pinned_commit(), None);
+
+        app.pinned = true;
+        assert_eq!(app.pinned_commit(), Some("abc123"));
+    }
+
+    #[test]
+    fn pinned_flag_omitted_when_false() {
+        let json = serde_json::to_string(&sample_app("org.test.App")).unwrap();
+        assert!(!json.contains("pinned"));
+
+        let parsed: FlatpakApp = serde_json::from_str(&json).unwrap();
+        assert!(!parsed.pinned);
+    }
+
+    #[test]
+    fn commits_match_allows_abbreviation() {
+        assert!(commits_match("abc123def456", "abc123def456"));
+        assert!(commits_match("abc123def456", "abc123"));
+        assert!(commits_match("abc123", "abc123def456"));
+        assert!(!commits_match("abc123", "def456"));
+        assert!(!commits_match("", "abc123"));
+    }
+
+    // ========================================================================
+    // Override flag merging tests
+    // ========================================================================
+
+    fn flags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn merge_override_flags_replaces_same_target() {
+        let existing = flags(&[
+            "--filesystem=~/Games:ro",
+            "--socket=x11",
+            "--env=GTK_THEME=Adwaita",
+        ]);
+        let merged = merge_override_flags(
+            &existing,
+            &flags(&[
+                "--nofilesystem=~/Games",
+                "--nosocket=x11",
+                "--env=GTK_THEME=Adwaita-dark",
+            ]),
+        );
+        assert_eq!(
+            merged,
+            flags(&[
+                "--env=GTK_THEME=Adwaita-dark",
+                "--nofilesystem=~/Games",
+                "--nosocket=x11",
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_override_flags_keeps_unrelated_flags() {
+        let existing = flags(&["--socket=wayland", "--talk-name=org.freedesktop.secrets"]);
+        let merged = merge_override_flags(&existing, &flags(&["--device=dri", "--socket=wayland"]));
+        assert_eq!(
+            merged,
+            flags(&[
+                "--device=dri",
+                "--socket=wayland",
+                "--talk-name=org.freedesktop.secrets",
+            ])
+        );
+    }
+
     // ========================================================================
     // FlatpakOverrides tests
     // ========================================================================
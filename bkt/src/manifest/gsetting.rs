@@ -3,27 +3,370 @@
 use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A GSettings entry.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GSetting {
     /// Schema name (e.g., "org.gnome.settings-daemon.plugins.power")
     pub schema: String,
+    /// Path for relocatable schemas
+    /// (e.g., "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     /// Key name (e.g., "sleep-inactive-ac-type")
     pub key: String,
     /// Value as a GVariant string (e.g., "'nothing'" or "0")
     pub value: String,
+    /// Type the schema declares for the key, recorded when the value was set
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<GSettingType>,
     /// Optional comment explaining the setting
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    /// Enforce the value system-wide: the image writes it to the dconf
+    /// system database with a lock, and `bkt gsetting apply` leaves it alone
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
 }
 
 impl GSetting {
     /// Get a unique key for this setting (schema + key).
+    ///
+    /// Relocatable settings include their path (`schema:/path/key`) so the
+    /// same key at different paths stays distinct.
     pub fn unique_key(&self) -> String {
-        format!("{}.{}", self.schema, self.key)
+        match &self.path {
+            Some(path) => format!("{}:{}{}", self.schema, path, self.key),
+            None => format!("{}.{}", self.schema, self.key),
+        }
+    }
+
+    /// The schema argument for the gsettings CLI (`schema` or `schema:path`).
+    pub fn schema_spec(&self) -> String {
+        schema_spec(&self.schema, self.path.as_deref())
+    }
+
+    /// The dconf directory holding this setting, without slashes at either
+    /// end (`org/gnome/desktop/screensaver`).
+    ///
+    /// Relocatable settings live at their path; fixed schemas conventionally
+    /// live at their id with dots turned into slashes.
+    pub fn dconf_dir(&self) -> String {
+        match &self.path {
+            Some(path) => path.trim_matches('/').to_string(),
+            None => self.schema.replace('.', "/"),
+        }
+    }
+
+    /// The value as dconf keyfile text.
+    ///
+    /// dconf has no schema to go by, so integer types other than int32 need
+    /// their type keyword (`uint32 300`) or the key is read back as the
+    /// wrong type and ignored.
+    pub fn dconf_value(&self) -> String {
+        let signature = match &self.value_type {
+            Some(GSettingType::Type { signature } | GSettingType::Range { signature, .. }) => {
+                signature.as_str()
+            }
+            _ => return self.value.clone(),
+        };
+        let keyword = match signature {
+            "y" => "byte",
+            "n" => "int16",
+            "q" => "uint16",
+            "u" => "uint32",
+            "x" => "int64",
+            "t" => "uint64",
+            "h" => "handle",
+            _ => return self.value.clone(),
+        };
+        if strip_type_keyword(&self.value) != self.value.trim() {
+            self.value.clone()
+        } else {
+            format!("{} {}", keyword, self.value.trim())
+        }
+    }
+
+    fn matches(&self, schema: &str, path: Option<&str>, key: &str) -> bool {
+        self.schema == schema && self.path.as_deref() == path && self.key == key
+    }
+}
+
+/// The values a key accepts, as reported by `gsettings range`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum GSettingType {
+    /// Any value of a GVariant type (e.g. `b`, `s`, `as`)
+    Type { signature: String },
+    /// A number of GVariant type `signature` between `min` and `max` inclusive
+    Range {
+        signature: String,
+        min: String,
+        max: String,
+    },
+    /// One of `values`
+    Enum { values: Vec<String> },
+    /// Any combination of `values`
+    Flags { values: Vec<String> },
+}
+
+impl GSettingType {
+    /// Parse `gsettings range <schema> <key>` output.
+    pub fn parse_range(output: &str) -> Option<Self> {
+        let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+        let mut words = lines.next()?.split_whitespace();
+        match words.next()? {
+            "type" => Some(Self::Type {
+                signature: words.next()?.to_string(),
+            }),
+            "range" => Some(Self::Range {
+                signature: words.next()?.to_string(),
+                min: words.next()?.to_string(),
+                max: words.next()?.to_string(),
+            }),
+            "enum" => Some(Self::Enum {
+                values: lines.map(unquote).collect(),
+            }),
+            "flags" => Some(Self::Flags {
+                values: lines.map(unquote).collect(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Short form for listings (`b`, `i 0..100`, `enum`, ...).
+    pub fn short(&self) -> String {
+        match self {
+            Self::Type { signature } => signature.clone(),
+            Self::Range {
+                signature,
+                min,
+                max,
+            } => format!("{} {}..{}", signature, min, max),
+            Self::Enum { .. } => "enum".to_string(),
+            Self::Flags { .. } => "flags".to_string(),
+        }
+    }
+
+    /// What a valid value looks like, for error messages.
+    pub fn expected(&self) -> String {
+        let quoted = |values: &[String]| {
+            values
+                .iter()
+                .map(|v| format!("'{}'", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Self::Type { signature } => describe_signature(signature),
+            Self::Range {
+                signature,
+                min,
+                max,
+            } => format!(
+                "{} between {} and {}",
+                describe_signature(signature),
+                min,
+                max
+            ),
+            Self::Enum { values } => format!("one of {}", quoted(values)),
+            Self::Flags { values } => format!("a list of flags from {}", quoted(values)),
+        }
+    }
+
+    /// Whether gsettings will accept `value` (GVariant text) for the key.
+    pub fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            Self::Type { signature } => signature_accepts(signature, value),
+            Self::Range {
+                signature,
+                min,
+                max,
+            } => {
+                if !signature_accepts(signature, value) {
+                    return false;
+                }
+                let number = strip_type_keyword(value);
+                match (
+                    parse_integer(number),
+                    min.parse::<i128>(),
+                    max.parse::<i128>(),
+                ) {
+                    (Some(n), Ok(min), Ok(max)) => (min..=max).contains(&n),
+                    _ => match (
+                        number.parse::<f64>(),
+                        min.parse::<f64>(),
+                        max.parse::<f64>(),
+                    ) {
+                        (Ok(n), Ok(min), Ok(max)) => (min..=max).contains(&n),
+                        _ => false,
+                    },
+                }
+            }
+            // gsettings takes a bare word as a string, so quotes are optional
+            Self::Enum { values } => values.contains(&unquote(value)),
+            Self::Flags { values } => parse_string_array(value)
+                .is_some_and(|flags| flags.iter().all(|f| values.contains(f))),
+        }
+    }
+}
+
+/// Bounds of the GVariant integer types, by signature.
+fn integer_bounds(signature: &str) -> Option<(i128, i128)> {
+    Some(match signature {
+        "y" => (0, u8::MAX as i128),
+        "n" => (i16::MIN as i128, i16::MAX as i128),
+        "q" => (0, u16::MAX as i128),
+        "i" | "h" => (i32::MIN as i128, i32::MAX as i128),
+        "u" => (0, u32::MAX as i128),
+        "x" => (i64::MIN as i128, i64::MAX as i128),
+        "t" => (0, u64::MAX as i128),
+        _ => return None,
+    })
+}
+
+/// Human-readable name for a GVariant type signature.
+fn describe_signature(signature: &str) -> String {
+    match signature {
+        "b" => "a boolean (true or false)".to_string(),
+        "y" => "a byte (0-255)".to_string(),
+        "n" => "an int16".to_string(),
+        "q" => "a uint16".to_string(),
+        "i" => "an int32".to_string(),
+        "u" => "a uint32".to_string(),
+        "x" => "an int64".to_string(),
+        "t" => "a uint64".to_string(),
+        "h" => "a handle".to_string(),
+        "d" => "a double".to_string(),
+        "s" => "a string".to_string(),
+        "o" => "an object path (e.g. '/org/example/')".to_string(),
+        "as" => "a string array (e.g. ['a', 'b'])".to_string(),
+        other => format!("a GVariant value of type '{}'", other),
+    }
+}
+
+/// Drop a leading GVariant type keyword (`uint32 5` -> `5`), as
+/// `gsettings get` prints for types other than int32, string and boolean.
+fn strip_type_keyword(value: &str) -> &str {
+    const KEYWORDS: &[&str] = &[
+        "byte",
+        "int16",
+        "uint16",
+        "int32",
+        "uint32",
+        "int64",
+        "uint64",
+        "handle",
+        "double",
+        "objectpath",
+    ];
+    match value.split_once(' ') {
+        Some((keyword, rest)) if KEYWORDS.contains(&keyword) => rest.trim(),
+        _ => value,
+    }
+}
+
+/// Whether `value` parses as GVariant text of type `signature`.
+///
+/// Scalars and string arrays are checked exactly; other containers only by
+/// their opening bracket.
+fn signature_accepts(signature: &str, value: &str) -> bool {
+    if let Some((min, max)) = integer_bounds(signature) {
+        return parse_integer(strip_type_keyword(value)).is_some_and(|n| (min..=max).contains(&n));
+    }
+    match signature {
+        "b" => matches!(value, "true" | "false"),
+        "d" => strip_type_keyword(value).parse::<f64>().is_ok(),
+        // gsettings takes a bare word as a string
+        "s" | "g" => true,
+        "o" => unquote(strip_type_keyword(value)).starts_with('/'),
+        "as" => parse_string_array(value).is_some(),
+        sig if sig.starts_with("a{") => value.starts_with('{') || value.starts_with('@'),
+        sig if sig.starts_with('a') => value.starts_with('[') || value.starts_with('@'),
+        sig if sig.starts_with('(') => value.starts_with('('),
+        _ => true,
+    }
+}
+
+/// Parse a GVariant integer literal, decimal or hex (`byte 0x05`).
+fn parse_integer(text: &str) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let n = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+    Some(if negative { -n } else { n })
+}
+
+/// Strip GVariant string quotes and escapes (`'it\'s'` -> `it's`); other
+/// text is returned as is.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match parse_string_literal(value) {
+        Some((s, "")) => s,
+        _ => value.to_string(),
+    }
+}
+
+/// Parse a quoted GVariant string at the start of `text`, returning it and
+/// the remaining text.
+fn parse_string_literal(text: &str) -> Option<(String, &str)> {
+    let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let mut out = String::new();
+    let mut chars = text[1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?.1),
+            c if c == quote => return Some((out, &text[i + 2..])),
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Parse a GVariant string array (`['a', 'b']`, `@as []`).
+fn parse_string_array(value: &str) -> Option<Vec<String>> {
+    let value = value.trim();
+    let value = value.strip_prefix("@as").unwrap_or(value).trim();
+    let mut rest = value.strip_prefix('[')?.strip_suffix(']')?.trim();
+
+    let mut items = Vec::new();
+    while !rest.is_empty() {
+        let (item, after) = parse_string_literal(rest)?;
+        items.push(item);
+        let after = after.trim_start();
+        rest = match after.strip_prefix(',') {
+            Some(next) => next.trim_start(),
+            None if after.is_empty() => after,
+            None => return None,
+        };
+    }
+    Some(items)
+}
+
+/// Format a schema and optional path as a gsettings `schema[:path]` argument.
+pub fn schema_spec(schema: &str, path: Option<&str>) -> String {
+    match path {
+        Some(path) => format!("{}:{}", schema, path),
+        None => schema.to_string(),
+    }
+}
+
+/// Split a gsettings `schema[:path]` argument into schema and path.
+pub fn split_schema_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.split_once(':') {
+        Some((schema, path)) => (schema, Some(path)),
+        None => (spec, None),
     }
 }
 
@@ -39,6 +382,12 @@ impl GSettingsManifest {
     /// Project manifest path (relative to workspace root).
     pub const PROJECT_PATH: &'static str = "manifests/gsettings.json";
 
+    /// Where the image writes locked settings in the dconf system database.
+    pub const DCONF_KEYFILE: &'static str = "/etc/dconf/db/local.d/50-bkt";
+
+    /// Where the image writes the locks for locked settings.
+    pub const DCONF_LOCKS: &'static str = "/etc/dconf/db/local.d/locks/50-bkt";
+
     /// Load a manifest from a path.
     pub fn load(path: &PathBuf) -> Result<Self> {
         if !path.exists() {
@@ -61,7 +410,7 @@ impl GSettingsManifest {
         }
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize gsettings manifest")?;
-        fs::write(path, content)
+        super::history::write(path, &content)
             .with_context(|| format!("Failed to write gsettings manifest to {}", path.display()))?;
         Ok(())
     }
@@ -72,17 +421,44 @@ impl GSettingsManifest {
         Self::load(&repo.join(Self::PROJECT_PATH))
     }
 
+    /// Load the repo manifest with the active profile's overlay, if any,
+    /// merged on top.
+    pub fn load_effective() -> Result<Self> {
+        let overlay = super::ProfileState::overlay_path(Self::PROJECT_PATH)?;
+        Self::load_with_overlay(overlay.as_deref())
+    }
+
+    /// Load the repo manifest with `overlay` (a profile's copy) merged on top.
+    pub fn load_with_overlay(overlay: Option<&Path>) -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load_merged(&repo.join(Self::PROJECT_PATH), overlay)
+    }
+
+    /// Load the manifest at `path` with `overlay` merged on top.
+    pub fn load_merged(path: &Path, overlay: Option<&Path>) -> Result<Self> {
+        let mut manifest = Self::load(&path.to_path_buf())?;
+        if let Some(path) = overlay {
+            manifest.merge(Self::load(&path.to_path_buf())?);
+        }
+        Ok(manifest)
+    }
+
     /// Save to the repository's manifests directory.
     pub fn save_repo(&self) -> Result<()> {
         let repo = crate::repo::find_repo_path()?;
         self.save(&repo.join(Self::PROJECT_PATH))
     }
 
-    /// Find a setting by schema and key.
-    pub fn find(&self, schema: &str, key: &str) -> Option<&GSetting> {
-        self.settings
-            .iter()
-            .find(|s| s.schema == schema && s.key == key)
+    /// Overlay another manifest's settings; entries in `overlay` win.
+    pub fn merge(&mut self, overlay: Self) {
+        for setting in overlay.settings {
+            self.upsert(setting);
+        }
+    }
+
+    /// Find a setting by schema, path, and key.
+    pub fn find(&self, schema: &str, path: Option<&str>, key: &str) -> Option<&GSetting> {
+        self.settings.iter().find(|s| s.matches(schema, path, key))
     }
 
     /// Add or update a setting.
@@ -90,7 +466,7 @@ impl GSettingsManifest {
         if let Some(existing) = self
             .settings
             .iter_mut()
-            .find(|s| s.schema == setting.schema && s.key == setting.key)
+            .find(|s| s.matches(&setting.schema, setting.path.as_deref(), &setting.key))
         {
             *existing = setting;
         } else {
@@ -99,11 +475,56 @@ impl GSettingsManifest {
         self.settings.sort_by_key(|a| a.unique_key());
     }
 
+    /// Settings enforced by the image rather than applied in the session.
+    pub fn locked(&self) -> impl Iterator<Item = &GSetting> {
+        self.settings.iter().filter(|s| s.locked)
+    }
+
+    /// The dconf keyfile for [`Self::DCONF_KEYFILE`], or `None` if nothing
+    /// is locked.
+    pub fn dconf_keyfile(&self) -> Option<String> {
+        let mut dirs: BTreeMap<String, Vec<&GSetting>> = BTreeMap::new();
+        for setting in self.locked() {
+            dirs.entry(setting.dconf_dir()).or_default().push(setting);
+        }
+        if dirs.is_empty() {
+            return None;
+        }
+
+        let mut out = String::from("# Managed by bkt (locked gsettings)\n");
+        for (dir, settings) in dirs {
+            out.push_str(&format!("\n[{}]\n", dir));
+            for setting in settings {
+                out.push_str(&format!("{}={}\n", setting.key, setting.dconf_value()));
+            }
+        }
+        Some(out)
+    }
+
+    /// The lock file for [`Self::DCONF_LOCKS`], or `None` if nothing is
+    /// locked.
+    pub fn dconf_locks(&self) -> Option<String> {
+        let mut keys: Vec<String> = self
+            .locked()
+            .map(|s| format!("/{}/{}", s.dconf_dir(), s.key))
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        keys.sort();
+
+        let mut out = String::from("# Managed by bkt (locked gsettings)\n");
+        for key in keys {
+            out.push_str(&key);
+            out.push('\n');
+        }
+        Some(out)
+    }
+
     /// Remove a setting. Returns true if removed.
-    pub fn remove(&mut self, schema: &str, key: &str) -> bool {
+    pub fn remove(&mut self, schema: &str, path: Option<&str>, key: &str) -> bool {
         let len_before = self.settings.len();
-        self.settings
-            .retain(|s| !(s.schema == schema && s.key == key));
+        self.settings.retain(|s| !s.matches(schema, path, key));
         self.settings.len() < len_before
     }
 }
@@ -115,9 +536,12 @@ mod tests {
     fn sample_setting(schema: &str, key: &str, value: &str) -> GSetting {
         GSetting {
             schema: schema.to_string(),
+            path: None,
             key: key.to_string(),
             value: value.to_string(),
+            value_type: None,
             comment: None,
+            locked: false,
         }
     }
 
@@ -129,12 +553,55 @@ mod tests {
     ) -> GSetting {
         GSetting {
             schema: schema.to_string(),
+            path: None,
             key: key.to_string(),
             value: value.to_string(),
+            value_type: None,
             comment: Some(comment.to_string()),
+            locked: false,
         }
     }
 
+    #[test]
+    fn merge_overlay_overrides_and_extends() {
+        let mut base = GSettingsManifest {
+            schema: None,
+            settings: vec![
+                sample_setting("org.gnome.desktop.interface", "color-scheme", "'default'"),
+                sample_setting("org.gnome.desktop.interface", "clock-format", "'24h'"),
+            ],
+        };
+        let overlay = GSettingsManifest {
+            schema: None,
+            settings: vec![
+                sample_setting(
+                    "org.gnome.desktop.interface",
+                    "color-scheme",
+                    "'prefer-dark'",
+                ),
+                sample_setting(
+                    "org.gnome.desktop.peripherals.touchpad",
+                    "tap-to-click",
+                    "true",
+                ),
+            ],
+        };
+
+        base.merge(overlay);
+
+        assert_eq!(base.settings.len(), 3);
+        assert_eq!(
+            base.find("org.gnome.desktop.interface", None, "color-scheme")
+                .unwrap()
+                .value,
+            "'prefer-dark'"
+        );
+        assert!(
+            base.find("org.gnome.desktop.interface", None, "clock-format")
+                .is_some()
+        );
+    }
+
     #[test]
     fn gsetting_unique_key() {
         let setting = sample_setting(
@@ -164,7 +631,7 @@ mod tests {
             "'prefer-dark'",
         ));
 
-        let found = manifest.find("org.gnome.desktop.interface", "color-scheme");
+        let found = manifest.find("org.gnome.desktop.interface", None, "color-scheme");
         assert!(found.is_some());
         assert_eq!(found.unwrap().value, "'prefer-dark'");
     }
@@ -172,7 +639,7 @@ mod tests {
     #[test]
     fn manifest_find_returns_none_for_missing() {
         let manifest = GSettingsManifest::default();
-        assert!(manifest.find("nonexistent.schema", "key").is_none());
+        assert!(manifest.find("nonexistent.schema", None, "key").is_none());
     }
 
     #[test]
@@ -204,7 +671,7 @@ mod tests {
         assert_eq!(manifest.settings.len(), 1);
         assert_eq!(
             manifest
-                .find("org.gnome.desktop.interface", "color-scheme")
+                .find("org.gnome.desktop.interface", None, "color-scheme")
                 .unwrap()
                 .value,
             "'prefer-light'"
@@ -230,14 +697,72 @@ mod tests {
             "'prefer-dark'",
         ));
 
-        assert!(manifest.remove("org.gnome.desktop.interface", "color-scheme"));
+        assert!(manifest.remove("org.gnome.desktop.interface", None, "color-scheme"));
         assert!(manifest.settings.is_empty());
     }
 
     #[test]
     fn manifest_remove_returns_false_when_not_found() {
         let mut manifest = GSettingsManifest::default();
-        assert!(!manifest.remove("nonexistent.schema", "key"));
+        assert!(!manifest.remove("nonexistent.schema", None, "key"));
+    }
+
+    const KEYBINDING: &str = "org.gnome.settings-daemon.plugins.media-keys.custom-keybinding";
+
+    fn keybinding(slot: &str, value: &str) -> GSetting {
+        GSetting {
+            path: Some(format!(
+                "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/{}/",
+                slot
+            )),
+            ..sample_setting(KEYBINDING, "binding", value)
+        }
+    }
+
+    #[test]
+    fn relocatable_unique_key_includes_path() {
+        assert_eq!(
+            keybinding("custom0", "'<Super>t'").unique_key(),
+            format!(
+                "{}:/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/binding",
+                KEYBINDING
+            )
+        );
+    }
+
+    #[test]
+    fn relocatable_settings_at_different_paths_do_not_collide() {
+        let mut manifest = GSettingsManifest::default();
+        manifest.upsert(keybinding("custom0", "'<Super>t'"));
+        manifest.upsert(keybinding("custom1", "'<Super>e'"));
+        assert_eq!(manifest.settings.len(), 2);
+
+        let path1 = keybinding("custom1", "").path.unwrap();
+        assert_eq!(
+            manifest
+                .find(KEYBINDING, Some(&path1), "binding")
+                .unwrap()
+                .value,
+            "'<Super>e'"
+        );
+        assert!(manifest.find(KEYBINDING, None, "binding").is_none());
+
+        assert!(manifest.remove(KEYBINDING, Some(&path1), "binding"));
+        assert_eq!(manifest.settings.len(), 1);
+    }
+
+    #[test]
+    fn schema_spec_roundtrip() {
+        let setting = keybinding("custom0", "'<Super>t'");
+        let spec = setting.schema_spec();
+        assert_eq!(
+            split_schema_spec(&spec),
+            (KEYBINDING, setting.path.as_deref())
+        );
+        assert_eq!(
+            split_schema_spec("org.gnome.desktop.interface"),
+            ("org.gnome.desktop.interface", None)
+        );
     }
 
     #[test]
@@ -284,4 +809,161 @@ mod tests {
         let manifest = GSettingsManifest::load(&path).unwrap();
         assert!(manifest.settings.is_empty());
     }
+
+    #[test]
+    fn parse_range_reads_each_form() {
+        assert_eq!(
+            GSettingType::parse_range("type b\n"),
+            Some(GSettingType::Type {
+                signature: "b".to_string()
+            })
+        );
+        assert_eq!(
+            GSettingType::parse_range("range i 0 100\n"),
+            Some(GSettingType::Range {
+                signature: "i".to_string(),
+                min: "0".to_string(),
+                max: "100".to_string(),
+            })
+        );
+        assert_eq!(
+            GSettingType::parse_range("enum\n'default'\n'prefer-dark'\n"),
+            Some(GSettingType::Enum {
+                values: vec!["default".to_string(), "prefer-dark".to_string()]
+            })
+        );
+        assert_eq!(GSettingType::parse_range(""), None);
+    }
+
+    #[test]
+    fn accepts_checks_scalars_and_ranges() {
+        let boolean = GSettingType::parse_range("type b").unwrap();
+        assert!(boolean.accepts("true"));
+        assert!(!boolean.accepts("yes"));
+
+        let uint = GSettingType::parse_range("type u").unwrap();
+        assert!(uint.accepts("uint32 300"));
+        assert!(
+            GSettingType::parse_range("type y")
+                .unwrap()
+                .accepts("byte 0x05")
+        );
+        assert!(!uint.accepts("-1"));
+        assert!(!uint.accepts("'300'"));
+
+        let range = GSettingType::parse_range("range i 0 100").unwrap();
+        assert!(range.accepts("100"));
+        assert!(!range.accepts("101"));
+        assert_eq!(range.expected(), "an int32 between 0 and 100");
+    }
+
+    #[test]
+    fn accepts_checks_enums_flags_and_string_arrays() {
+        let scheme = GSettingType::parse_range("enum\n'default'\n'prefer-dark'").unwrap();
+        assert!(scheme.accepts("'prefer-dark'"));
+        assert!(scheme.accepts("prefer-dark"));
+        assert!(!scheme.accepts("'dark'"));
+
+        let flags = GSettingType::parse_range("flags\n'a'\n'b'").unwrap();
+        assert!(flags.accepts("['a', 'b']"));
+        assert!(flags.accepts("@as []"));
+        assert!(!flags.accepts("['c']"));
+
+        let strv = GSettingType::parse_range("type as").unwrap();
+        assert!(strv.accepts("['it\\'s', \"x\"]"));
+        assert!(!strv.accepts("['a' 'b']"));
+        assert!(!strv.accepts("a, b"));
+    }
+
+    #[test]
+    fn dconf_value_adds_type_keyword_for_non_int32_integers() {
+        let mut delay = sample_setting("org.gnome.desktop.session", "idle-delay", "300");
+        assert_eq!(delay.dconf_value(), "300");
+
+        delay.value_type = GSettingType::parse_range("type u");
+        assert_eq!(delay.dconf_value(), "uint32 300");
+
+        delay.value = "uint32 300".to_string();
+        assert_eq!(delay.dconf_value(), "uint32 300");
+
+        let mut mode = sample_setting("org.gnome.desktop.screensaver", "lock-delay", "'x'");
+        mode.value_type = GSettingType::parse_range("type s");
+        assert_eq!(mode.dconf_value(), "'x'");
+    }
+
+    #[test]
+    fn dconf_keyfile_and_locks_cover_only_locked_settings() {
+        let locked = |setting: GSetting| GSetting {
+            locked: true,
+            ..setting
+        };
+        let mut delay = sample_setting("org.gnome.desktop.session", "idle-delay", "300");
+        delay.value_type = GSettingType::parse_range("type u");
+
+        let manifest = GSettingsManifest {
+            schema: None,
+            settings: vec![
+                sample_setting("org.gnome.desktop.interface", "clock-format", "'24h'"),
+                locked(sample_setting(
+                    "org.gnome.desktop.screensaver",
+                    "lock-enabled",
+                    "true",
+                )),
+                locked(delay),
+                locked(sample_setting(
+                    "org.gnome.desktop.screensaver",
+                    "lock-delay",
+                    "uint32 0",
+                )),
+                locked(keybinding("custom0", "'<Super>t'")),
+            ],
+        };
+
+        assert_eq!(
+            manifest.dconf_keyfile().unwrap(),
+            "# Managed by bkt (locked gsettings)\n\
+             \n\
+             [org/gnome/desktop/screensaver]\n\
+             lock-enabled=true\n\
+             lock-delay=uint32 0\n\
+             \n\
+             [org/gnome/desktop/session]\n\
+             idle-delay=uint32 300\n\
+             \n\
+             [org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0]\n\
+             binding='<Super>t'\n"
+        );
+        assert_eq!(
+            manifest.dconf_locks().unwrap(),
+            "# Managed by bkt (locked gsettings)\n\
+             /org/gnome/desktop/screensaver/lock-delay\n\
+             /org/gnome/desktop/screensaver/lock-enabled\n\
+             /org/gnome/desktop/session/idle-delay\n\
+             /org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/binding\n"
+        );
+    }
+
+    #[test]
+    fn dconf_files_are_absent_without_locked_settings() {
+        let manifest = GSettingsManifest {
+            schema: None,
+            settings: vec![sample_setting(
+                "org.gnome.desktop.interface",
+                "clock-format",
+                "'24h'",
+            )],
+        };
+        assert!(manifest.dconf_keyfile().is_none());
+        assert!(manifest.dconf_locks().is_none());
+    }
+
+    #[test]
+    fn locked_defaults_to_false_and_is_omitted() {
+        let setting: GSetting = serde_json::from_str(
+            r#"{"schema": "org.gnome.desktop.interface", "key": "clock-format", "value": "'24h'"}"#,
+        )
+        .unwrap();
+        assert!(!setting.locked);
+        assert!(!serde_json::to_string(&setting).unwrap().contains("locked"));
+    }
 }
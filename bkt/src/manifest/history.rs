@@ -0,0 +1,239 @@
+//! Manifest write history, for `bkt undo`.
+//!
+//! Manifest saves go through [`write`], which replaces the file atomically
+//! (temp file + rename) and first copies the version being replaced into
+//! `~/.config/bootc/.history/<manifest>/<timestamp>.json`. A crash between
+//! saving a manifest and acting on it can then be reverted.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Versions kept per manifest; older ones are pruned.
+pub const HISTORY_LIMIT: usize = 20;
+
+/// Records the manifest a history directory belongs to.
+const TARGET_FILE: &str = "target";
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+/// One saved version of a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The manifest it was saved from.
+    pub target: PathBuf,
+    /// When it was replaced.
+    pub replaced_at: DateTime<Utc>,
+    /// The saved copy.
+    pub path: PathBuf,
+}
+
+impl HistoryEntry {
+    /// The saved version's contents.
+    pub fn read(&self) -> Result<String> {
+        fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))
+    }
+}
+
+/// `~/.config/bootc/.history`, or `None` when there is nowhere to keep it.
+pub fn history_root() -> Option<PathBuf> {
+    // Tests save manifests all over the place; keep them out of $HOME
+    if cfg!(test) {
+        return None;
+    }
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".config/bootc/.history"))
+}
+
+/// Write a manifest, saving the version it replaces to the history.
+pub fn write(path: impl AsRef<Path>, content: &str) -> Result<()> {
+    write_in(history_root().as_deref(), path.as_ref(), content)
+}
+
+/// [`write`] with an explicit history root (`None` keeps no history).
+pub fn write_in(history: Option<&Path>, path: &Path, content: &str) -> Result<()> {
+    if let Some(root) = history
+        && let Ok(previous) = fs::read_to_string(path)
+        && previous != content
+    {
+        record(root, path, &previous)
+            .with_context(|| format!("Failed to save history for {}", path.display()))?;
+    }
+    replace(path, content)
+}
+
+/// Atomically replace `path` with `content`, without touching the history.
+pub fn replace(path: &Path, content: &str) -> Result<()> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    fs::write(&tmp, content).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Copy `previous` into `root` as the newest version of `path`.
+fn record(root: &Path, path: &Path, previous: &str) -> Result<()> {
+    let target = std::path::absolute(path)?;
+    let dir = history_dir(root, &target)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(TARGET_FILE), target.to_string_lossy().as_bytes())?;
+
+    let mut now = Utc::now();
+    let mut entry = dir.join(format!("{}.json", now.format(TIMESTAMP_FORMAT)));
+    while entry.exists() {
+        now += chrono::Duration::microseconds(1);
+        entry = dir.join(format!("{}.json", now.format(TIMESTAMP_FORMAT)));
+    }
+    fs::write(&entry, previous)?;
+
+    let versions = versions(&dir)?;
+    let excess = versions.len().saturating_sub(HISTORY_LIMIT);
+    for old in &versions[..excess] {
+        fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+/// The directory holding `target`'s versions: named after the manifest,
+/// with a suffix when another manifest of the same name got there first.
+fn history_dir(root: &Path, target: &Path) -> Result<PathBuf> {
+    let name = target
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", target.display()))?
+        .to_string_lossy()
+        .into_owned();
+    for n in 1.. {
+        let dir = match n {
+            1 => root.join(&name),
+            _ => root.join(format!("{}.{}", name, n)),
+        };
+        match fs::read_to_string(dir.join(TARGET_FILE)) {
+            Ok(existing) if Path::new(&existing) != target => continue,
+            _ => return Ok(dir),
+        }
+    }
+    unreachable!()
+}
+
+/// Saved versions in one history directory, oldest first.
+fn versions(dir: &Path) -> Result<Vec<HistoryEntry>> {
+    let target = match fs::read_to_string(dir.join(TARGET_FILE)) {
+        Ok(target) => PathBuf::from(target),
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut entries = Vec::new();
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        let Some(stamp) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        if let Ok(time) = NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT) {
+            entries.push(HistoryEntry {
+                target: target.clone(),
+                replaced_at: time.and_utc(),
+                path,
+            });
+        }
+    }
+    entries.sort_by_key(|entry| entry.replaced_at);
+    Ok(entries)
+}
+
+/// Every saved version under `root`, newest first.
+pub fn entries(root: &Path) -> Result<Vec<HistoryEntry>> {
+    let mut entries = Vec::new();
+    let dirs = match fs::read_dir(root) {
+        Ok(dirs) => dirs,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(entries),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", root.display())),
+    };
+    for dir in dirs {
+        let dir = dir?.path();
+        if dir.is_dir() {
+            entries.extend(versions(&dir)?);
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.replaced_at));
+    Ok(entries)
+}
+
+/// Put `entry` back in place of its manifest and drop it from the
+/// history, so the next undo goes back one further.
+pub fn restore(entry: &HistoryEntry) -> Result<()> {
+    replace(&entry.target, &entry.read()?)?;
+    fs::remove_file(&entry.path)
+        .with_context(|| format!("Failed to remove {}", entry.path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn writes_keep_the_replaced_version() {
+        let temp = TempDir::new().unwrap();
+        let history = temp.path().join("history");
+        let manifest = temp.path().join("flatpak-apps.json");
+
+        write_in(Some(&history), &manifest, "one").unwrap();
+        assert!(entries(&history).unwrap().is_empty());
+
+        write_in(Some(&history), &manifest, "two").unwrap();
+        // Unchanged content isn't a new version
+        write_in(Some(&history), &manifest, "two").unwrap();
+        write_in(Some(&history), &manifest, "three").unwrap();
+
+        let saved = entries(&history).unwrap();
+        let contents: Vec<String> = saved.iter().map(|e| e.read().unwrap()).collect();
+        assert_eq!(contents, ["two", "one"]);
+        assert_eq!(saved[0].target, manifest);
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "three");
+        assert!(!temp.path().join(".flatpak-apps.json.tmp").exists());
+    }
+
+    #[test]
+    fn restore_pops_the_newest_version() {
+        let temp = TempDir::new().unwrap();
+        let history = temp.path().join("history");
+        let manifest = temp.path().join("gsettings.json");
+        for content in ["one", "two", "three"] {
+            write_in(Some(&history), &manifest, content).unwrap();
+        }
+
+        restore(&entries(&history).unwrap()[0]).unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "two");
+        restore(&entries(&history).unwrap()[0]).unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "one");
+        assert!(entries(&history).unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_is_bounded_and_kept_per_manifest() {
+        let temp = TempDir::new().unwrap();
+        let history = temp.path().join("history");
+        let repo = temp.path().join("repo/flatpak-apps.json");
+        let profile = temp.path().join("profile/flatpak-apps.json");
+        fs::create_dir_all(repo.parent().unwrap()).unwrap();
+        fs::create_dir_all(profile.parent().unwrap()).unwrap();
+
+        for n in 0..HISTORY_LIMIT + 5 {
+            write_in(Some(&history), &repo, &n.to_string()).unwrap();
+        }
+        write_in(Some(&history), &profile, "a").unwrap();
+        write_in(Some(&history), &profile, "b").unwrap();
+
+        let saved = entries(&history).unwrap();
+        let repo_versions = saved.iter().filter(|e| e.target == repo).count();
+        assert_eq!(repo_versions, HISTORY_LIMIT);
+        assert_eq!(saved[0].target, profile);
+        assert_eq!(saved[0].read().unwrap(), "a");
+    }
+}
@@ -88,6 +88,9 @@ pub struct HomebrewManifest {
     /// List of taps to add
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub taps: Vec<String>,
+    /// List of casks to install (macOS only; skipped on Linux)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub casks: Vec<String>,
 }
 
 impl HomebrewManifest {
@@ -115,7 +118,7 @@ impl HomebrewManifest {
         }
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize homebrew manifest")?;
-        fs::write(path, content)
+        super::history::write(path, &content)
             .with_context(|| format!("Failed to write homebrew manifest to {}", path.display()))?;
         Ok(())
     }
@@ -155,6 +158,46 @@ impl HomebrewManifest {
         self.formulae.len() < len_before
     }
 
+    /// Add a tap if not present.
+    pub fn add_tap(&mut self, tap: impl Into<String>) -> bool {
+        let tap = tap.into();
+        if self.taps.contains(&tap) {
+            return false;
+        }
+        self.taps.push(tap);
+        self.taps.sort();
+        true
+    }
+
+    /// Add a cask if not present.
+    pub fn add_cask(&mut self, cask: impl Into<String>) -> bool {
+        let cask = cask.into();
+        if self.casks.contains(&cask) {
+            return false;
+        }
+        self.casks.push(cask);
+        self.casks.sort();
+        true
+    }
+
+    /// Every tap the manifest needs: the listed taps plus those named by
+    /// `user/repo/formula` entries, sorted and deduplicated.
+    pub fn required_taps(&self) -> Vec<String> {
+        let mut taps: Vec<String> = self
+            .taps
+            .iter()
+            .cloned()
+            .chain(
+                self.formulae
+                    .iter()
+                    .filter_map(|f| f.tap().map(str::to_string)),
+            )
+            .collect();
+        taps.sort();
+        taps.dedup();
+        taps
+    }
+
     /// List formula names.
     #[allow(dead_code)]
     pub fn list(&self) -> Vec<String> {
@@ -197,6 +240,31 @@ mod tests {
         assert_eq!(formula.formula_name(), "bbrew");
     }
 
+    #[test]
+    fn required_taps_include_formula_taps() {
+        let mut manifest = HomebrewManifest::default();
+        manifest.add("valkyrie00/bbrew/bbrew");
+        manifest.add("lefthook");
+        assert!(manifest.add_tap("valkyrie00/bbrew"));
+        assert!(manifest.add_tap("acme/tools"));
+        assert!(!manifest.add_tap("acme/tools"));
+
+        assert_eq!(
+            manifest.required_taps(),
+            vec!["acme/tools".to_string(), "valkyrie00/bbrew".to_string()]
+        );
+    }
+
+    #[test]
+    fn casks_round_trip_and_stay_optional() {
+        let manifest: HomebrewManifest =
+            serde_json::from_str(r#"{"formulae": ["lefthook"], "casks": ["ghostty"]}"#).unwrap();
+        assert_eq!(manifest.casks, vec!["ghostty"]);
+
+        let empty = serde_json::to_string(&HomebrewManifest::default()).unwrap();
+        assert!(!empty.contains("casks"), "{empty}");
+    }
+
     #[test]
     fn simple_formula_has_no_tap() {
         let formula: BrewFormula = "lefthook".into();
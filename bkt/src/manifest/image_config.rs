@@ -3,14 +3,23 @@
 //! Describes the system configuration modules applied during the image
 //! assembly stage of the Containerfile. Each module maps to a contiguous
 //! block of Dockerfile instructions.
+//!
+//! Variants build other images from the same manifests (e.g. a headless
+//! server next to the desktop), each with its own base image and a subset
+//! of the modules and packages.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
+/// Base image used when the manifest doesn't name one.
+pub const DEFAULT_BASE_IMAGE: &str = "ghcr.io/ublue-os/bazzite-gnome:stable";
+
 /// A file to COPY into the image.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileCopy {
     /// Source path (relative to repo root)
     pub src: String,
@@ -25,7 +34,7 @@ pub struct FileCopy {
 }
 
 /// A module in the image configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ImageModule {
     /// Copy files into the image
@@ -95,6 +104,18 @@ pub enum ImageModule {
         /// Description for the systemd scope
         #[serde(default, skip_serializing_if = "Option::is_none")]
         description: Option<String>,
+        /// Soft memory limit for the slice (systemd `MemoryHigh=`, e.g. "20G")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        memory_high: Option<String>,
+        /// Hard memory limit for the slice (systemd `MemoryMax=`, e.g. "24G")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        memory_max: Option<String>,
+        /// CPU weight for the slice (systemd `CPUWeight=`, 1-10000)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cpu_weight: Option<u32>,
+        /// Extra `--property=` values passed to systemd-run (e.g. "IOWeight=50")
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        properties: Vec<String>,
     },
 }
 
@@ -120,16 +141,72 @@ impl ImageModule {
             | ImageModule::Wrapper { comment, .. } => comment.as_deref(),
         }
     }
+
+    /// Get the wrapper configuration, for wrapper modules.
+    pub fn wrapper_config(&self) -> Option<crate::commands::wrap::WrapperConfig> {
+        match self {
+            ImageModule::Wrapper {
+                name,
+                target,
+                slice,
+                output,
+                remote_cli,
+                description,
+                memory_high,
+                memory_max,
+                cpu_weight,
+                properties,
+                ..
+            } => Some(crate::commands::wrap::WrapperConfig {
+                name: name.clone(),
+                target: target.clone(),
+                slice: slice.clone(),
+                output: output.clone(),
+                remote_cli: *remote_cli,
+                description: description.clone(),
+                memory_high: memory_high.clone(),
+                memory_max: memory_max.clone(),
+                cpu_weight: *cpu_weight,
+                properties: properties.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A named image variant, generated as `Containerfile.<name>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ImageVariant {
+    /// Base image for this variant (defaults to the manifest's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_image: Option<String>,
+    /// Only apply these modules (default: all of them)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_modules: Vec<String>,
+    /// Leave these modules out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_modules: Vec<String>,
+    /// Leave these system packages and package groups out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_packages: Vec<String>,
 }
 
 /// The image-config.json manifest.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ImageConfigManifest {
     #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
     pub schema: Option<String>,
 
+    /// Image the Containerfile builds on (default: Bazzite GNOME stable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_image: Option<String>,
+
     /// Ordered list of modules to apply during image assembly.
     pub modules: Vec<ImageModule>,
+
+    /// Other images built from the same manifests, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variants: BTreeMap<String, ImageVariant>,
 }
 
 impl ImageConfigManifest {
@@ -168,29 +245,58 @@ impl ImageConfigManifest {
         Self::load_from_path(&path)
     }
 
+    /// The image the Containerfile builds on.
+    pub fn base_image(&self) -> &str {
+        self.base_image.as_deref().unwrap_or(DEFAULT_BASE_IMAGE)
+    }
+
+    /// Look up a variant, checking that the modules it names exist.
+    pub fn variant(&self, name: &str) -> Result<&ImageVariant> {
+        let Some(variant) = self.variants.get(name) else {
+            let known: Vec<&str> = self.variants.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                bail!("Unknown variant '{}': image-config.json defines none", name);
+            }
+            bail!("Unknown variant '{}' (known: {})", name, known.join(", "));
+        };
+        // Variants are written to `Containerfile.<name>`
+        if name.is_empty() || name.contains(['/', '.']) {
+            bail!("Invalid variant name '{}'", name);
+        }
+        for module in variant
+            .include_modules
+            .iter()
+            .chain(&variant.exclude_modules)
+        {
+            if !self.modules.iter().any(|m| m.name() == module) {
+                bail!("Variant '{}' names unknown module '{}'", name, module);
+            }
+        }
+        Ok(variant)
+    }
+
+    /// This manifest as `variant` sees it: its base image and only the
+    /// modules it keeps, in their original order. A variant that changes
+    /// nothing gets an identical manifest.
+    pub fn for_variant(&self, variant: &ImageVariant) -> Self {
+        let keep = |module: &ImageModule| {
+            let name = module.name().to_string();
+            (variant.include_modules.is_empty() || variant.include_modules.contains(&name))
+                && !variant.exclude_modules.contains(&name)
+        };
+        let mut manifest = self.clone();
+        if variant.base_image.is_some() {
+            manifest.base_image = variant.base_image.clone();
+        }
+        manifest.modules.retain(keep);
+        manifest
+    }
+
     /// Extract wrapper configurations from the manifest.
     pub fn wrappers(&self) -> Vec<crate::commands::wrap::WrapperConfig> {
         self.modules
             .iter()
-            .filter_map(|m| match m {
-                ImageModule::Wrapper {
-                    name,
-                    target,
-                    slice,
-                    output,
-                    remote_cli,
-                    description,
-                    ..
-                } => Some(crate::commands::wrap::WrapperConfig {
-                    name: name.clone(),
-                    target: target.clone(),
-                    slice: slice.clone(),
-                    output: output.clone(),
-                    remote_cli: *remote_cli,
-                    description: description.clone(),
-                }),
-                _ => None,
-            })
+            .filter_map(ImageModule::wrapper_config)
             .collect()
     }
 }
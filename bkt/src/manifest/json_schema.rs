@@ -0,0 +1,420 @@
+//! Validation of manifest JSON against the schemas bkt generates.
+//!
+//! This is not a general JSON Schema implementation: it covers the keywords
+//! schemars emits for our manifest types (`type`, `properties`, `required`,
+//! `additionalProperties`, `items`, `enum`, `const`, `oneOf`/`anyOf`/`allOf`,
+//! numeric bounds and local `$ref`s). Annotations such as `format`,
+//! `description` and `default` are ignored.
+
+use serde_json::Value;
+use std::fmt;
+
+/// One place where a document does not match its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer to the offending value (`""` for the document root).
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Validate `instance` against `schema`, returning every violation found.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check(schema, schema, instance, "", &mut violations);
+    violations
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, pointer: &str, out: &mut Vec<Violation>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            out.push(violation(pointer, "no value is allowed here"));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => check(root, target, value, pointer, out),
+            None => out.push(violation(
+                pointer,
+                format!("schema reference {reference} cannot be resolved"),
+            )),
+        }
+    }
+
+    if let Some(expected) = schema.get("type")
+        && !matches_type(expected, value)
+    {
+        out.push(violation(
+            pointer,
+            format!(
+                "expected {}, found {}",
+                describe_type(expected),
+                kind(value)
+            ),
+        ));
+        // Nothing below makes sense for a value of the wrong type
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        out.push(violation(
+            pointer,
+            format!("{} is not one of {}", value, allowed.join(", ")),
+        ));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        out.push(violation(
+            pointer,
+            format!("must be {expected}, found {value}"),
+        ));
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+            && n < min
+        {
+            out.push(violation(pointer, format!("{value} is less than {min}")));
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+            && n > max
+        {
+            out.push(violation(pointer, format!("{value} is greater than {max}")));
+        }
+    }
+
+    if let Value::Object(fields) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    out.push(violation(
+                        pointer,
+                        format!("missing required property \"{name}\""),
+                    ));
+                }
+            }
+        }
+        for (name, field) in fields {
+            let field_pointer = format!("{}/{}", pointer, escape(name));
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => check(root, field_schema, field, &field_pointer, out),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        out.push(violation(&field_pointer, "unknown property"));
+                    }
+                    Some(extra) => check(root, extra, field, &field_pointer, out),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check(root, item_schema, item, &format!("{pointer}/{i}"), out);
+        }
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for branch in all {
+            check(root, branch, value, pointer, out);
+        }
+    }
+
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+        let results = branch_results(root, any, value, pointer);
+        if !results.iter().any(Vec::is_empty) {
+            report_closest(root, any, value, results, pointer, out);
+        }
+    }
+
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let results = branch_results(root, one, value, pointer);
+        match results.iter().filter(|r| r.is_empty()).count() {
+            1 => {}
+            0 => report_closest(root, one, value, results, pointer, out),
+            n => out.push(violation(
+                pointer,
+                format!("matches {n} of the allowed forms, expected exactly one"),
+            )),
+        }
+    }
+}
+
+fn branch_results(
+    root: &Value,
+    branches: &[Value],
+    value: &Value,
+    pointer: &str,
+) -> Vec<Vec<Violation>> {
+    branches
+        .iter()
+        .map(|branch| {
+            let mut violations = Vec::new();
+            check(root, branch, value, pointer, &mut violations);
+            violations
+        })
+        .collect()
+}
+
+/// When no alternative matches, report why the closest one didn't; that is
+/// almost always the variant the author meant to write.
+///
+/// Alternatives whose type, tag or constant doesn't match are for some other
+/// variant, so they are left out of the running.
+fn report_closest(
+    root: &Value,
+    branches: &[Value],
+    value: &Value,
+    results: Vec<Vec<Violation>>,
+    pointer: &str,
+    out: &mut Vec<Violation>,
+) {
+    // Internally tagged enums: a missing or unknown tag is the whole story
+    if let Some((tag, tags)) = discriminator(root, branches) {
+        match value.get(tag) {
+            Some(found) if tags.contains(&found.to_string()) => {}
+            Some(found) => {
+                out.push(violation(
+                    &format!("{}/{}", pointer, escape(tag)),
+                    format!("{} is not one of {}", found, tags.join(", ")),
+                ));
+                return;
+            }
+            None => {
+                out.push(violation(
+                    pointer,
+                    format!("missing tag \"{}\" (one of {})", tag, tags.join(", ")),
+                ));
+                return;
+            }
+        }
+    }
+
+    let rules_out = |v: &Violation| {
+        v.message.starts_with("must be ")
+            || v.message.contains(" is not one of ")
+            || (v.pointer == pointer && v.message.starts_with("expected "))
+    };
+    let closest = results
+        .into_iter()
+        .filter(|r| !r.iter().any(rules_out))
+        .min_by_key(Vec::len);
+    if let Some(violations) = closest {
+        out.extend(violations);
+        return;
+    }
+
+    // Unit enum variants with doc comments become one `const` per variant
+    let consts: Option<Vec<String>> = branches
+        .iter()
+        .map(|b| b.get("const").map(Value::to_string))
+        .collect();
+    let message = match consts {
+        Some(consts) => format!("{} is not one of {}", value, consts.join(", ")),
+        None => "does not match any of the allowed forms".to_string(),
+    };
+    out.push(violation(pointer, message));
+}
+
+/// The property every alternative pins to a `const`, and its allowed values.
+fn discriminator<'a>(root: &'a Value, branches: &'a [Value]) -> Option<(&'a str, Vec<String>)> {
+    let properties: Vec<&serde_json::Map<String, Value>> = branches
+        .iter()
+        .map(|b| {
+            let b = match b.get("$ref").and_then(Value::as_str) {
+                Some(reference) => resolve(root, reference)?,
+                None => b,
+            };
+            b.get("properties")?.as_object()
+        })
+        .collect::<Option<_>>()?;
+    let (first, rest) = properties.split_first()?;
+    let tag = first.iter().find_map(|(name, schema)| {
+        schema.get("const")?;
+        rest.iter()
+            .all(|p| p.get(name).and_then(|s| s.get("const")).is_some())
+            .then_some(name.as_str())
+    })?;
+    let tags = properties
+        .iter()
+        .filter_map(|p| p.get(tag)?.get("const"))
+        .map(Value::to_string)
+        .collect();
+    Some((tag, tags))
+}
+
+/// Resolve a local reference such as `#/$defs/FlatpakApp`.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    root.pointer(pointer)
+}
+
+fn matches_type(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(name) => is_type(name, value),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| is_type(name, value)),
+        _ => true,
+    }
+}
+
+fn is_type(name: &str, value: &Value) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Value::String(name) => name.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escape a property name for use as a JSON pointer segment (RFC 6901).
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn violation(pointer: &str, message: impl Into<String>) -> Violation {
+    Violation {
+        pointer: pointer.to_string(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::FlatpakAppsManifest;
+    use crate::manifest::image_config::ImageConfigManifest;
+    use schemars::schema_for;
+    use serde_json::json;
+
+    fn flatpak_schema() -> Value {
+        serde_json::to_value(schema_for!(FlatpakAppsManifest)).unwrap()
+    }
+
+    #[test]
+    fn test_valid_manifest_has_no_violations() {
+        let manifest = json!({
+            "$schema": "../schemas/flatpak-apps.schema.json",
+            "apps": [{ "id": "org.gnome.Calculator", "remote": "flathub", "scope": "system" }]
+        });
+        assert_eq!(validate(&flatpak_schema(), &manifest), vec![]);
+    }
+
+    #[test]
+    fn test_violations_carry_json_pointers() {
+        let manifest = json!({
+            "apps": [
+                { "id": "org.gnome.Calculator", "remote": "flathub", "scope": "system" },
+                { "remote": 7, "scope": "everywhere" }
+            ]
+        });
+        let violations = validate(&flatpak_schema(), &manifest);
+        let pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+
+        assert!(pointers.contains(&"/apps/1"), "{violations:?}");
+        assert!(pointers.contains(&"/apps/1/remote"), "{violations:?}");
+        assert!(pointers.contains(&"/apps/1/scope"), "{violations:?}");
+        assert!(violations.iter().any(|v| v.message.contains("\"id\"")));
+    }
+
+    #[test]
+    fn test_keywords() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "a/b": { "type": "integer", "minimum": 0 },
+                "tag": { "oneOf": [{ "const": "x" }, { "const": "y" }] }
+            },
+            "additionalProperties": false
+        });
+
+        let violations = validate(&schema, &json!({ "a/b": -1, "tag": "z", "extra": true }));
+        let rendered: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "/a~1b: -1 is less than 0",
+                "/extra: unknown property",
+                "/tag: \"z\" is not one of \"x\", \"y\"",
+            ]
+        );
+
+        assert_eq!(
+            validate(&schema, &json!([])),
+            vec![violation("", "expected object, found array")]
+        );
+    }
+
+    #[test]
+    fn test_tagged_enum_reports_the_tag_or_the_variant() {
+        let schema = serde_json::to_value(schema_for!(ImageConfigManifest)).unwrap();
+        let manifest = json!({
+            "modules": [
+                { "type": "run", "name": "x" },
+                { "type": "bogus", "name": "y" }
+            ]
+        });
+
+        let violations = validate(&schema, &manifest);
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        assert_eq!(
+            violations[0],
+            violation("/modules/0", "missing required property \"commands\"")
+        );
+        assert_eq!(violations[1].pointer, "/modules/1/type");
+        assert!(violations[1].message.starts_with("\"bogus\" is not one of"));
+    }
+}
@@ -6,7 +6,9 @@ pub mod appimage;
 pub mod base;
 pub mod base_image;
 pub mod build_info;
+pub mod capture_ignore;
 pub mod changelog;
+pub mod daemon_allowlist;
 pub mod diff;
 pub mod distrobox;
 pub mod dnf;
@@ -15,20 +17,27 @@ pub mod external_repos;
 pub mod fetchbin;
 pub mod flatpak;
 pub mod gsetting;
+pub mod history;
 pub mod homebrew;
 pub mod image_config;
+pub mod json_schema;
 pub mod parsers;
+pub mod profile;
 pub mod shim;
+pub mod skel_vars;
 pub mod system_config;
 pub mod systemd_services;
 pub mod toolbox;
+pub mod toolbox_binaries;
 pub mod try_pending;
 pub mod upstream;
 pub mod vendor_artifacts;
 
 pub use appimage::*;
 pub use base::*;
+pub use capture_ignore::*;
 pub use changelog::*;
+pub use daemon_allowlist::*;
 pub use distrobox::*;
 pub use dnf::*;
 pub use extension::*;
@@ -37,9 +46,12 @@ pub use fetchbin::*;
 pub use flatpak::*;
 pub use gsetting::*;
 pub use homebrew::*;
+pub use profile::*;
 pub use shim::*;
+pub use skel_vars::*;
 pub use systemd_services::*;
 pub use toolbox::*;
+pub use toolbox_binaries::*;
 pub use try_pending::*;
 pub use upstream::*;
 pub use vendor_artifacts::*;
@@ -6,6 +6,7 @@
 
 pub mod keyd;
 pub mod systemd;
+pub mod xml;
 
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -20,6 +21,8 @@ pub enum SemanticDiff {
     Systemd(SystemdDiff),
     /// Generic key-value config (INI, TOML)
     KeyValue(KeyValueDiff),
+    /// XML config (fontconfig): flattened element paths
+    Xml(XmlDiff),
     /// Fallback: line count summary
     LineSummary(LineSummary),
 }
@@ -79,6 +82,26 @@ impl KeyValueDiff {
     }
 }
 
+/// Semantic diff for XML config files, keyed by flattened element path
+/// (e.g., `match/test[name=family]/string`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XmlDiff {
+    pub added: Vec<PropertyChange>,
+    pub removed: Vec<PropertyChange>,
+    pub changed: Vec<PropertyChange>,
+}
+
+impl XmlDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// All changes, in added, removed, changed order.
+    pub fn changes(&self) -> impl Iterator<Item = &PropertyChange> {
+        self.added.iter().chain(&self.removed).chain(&self.changed)
+    }
+}
+
 /// Fallback line-based summary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineSummary {
@@ -92,6 +115,7 @@ pub enum ConfigFileType {
     Keyd,
     Systemd,
     Ini,
+    Xml,
     Other,
 }
 
@@ -104,12 +128,31 @@ impl ConfigFileType {
             && (path.ends_with(".service") || path.ends_with(".timer"))
         {
             ConfigFileType::Systemd
+        } else if path.starts_with("system/fontconfig/") || path.ends_with(".xml") {
+            ConfigFileType::Xml
         } else if path.ends_with(".conf") || path.ends_with(".ini") {
             ConfigFileType::Ini
         } else {
             ConfigFileType::Other
         }
     }
+
+    /// Determine file type from path, then content: XML that lives
+    /// somewhere unrecognized (e.g. `/etc/fonts/local.conf`) would
+    /// otherwise be read as INI.
+    pub fn detect(path: &str, content: Option<&str>) -> Self {
+        match Self::from_path(path) {
+            ConfigFileType::Ini | ConfigFileType::Other if content.is_some_and(looks_like_xml) => {
+                ConfigFileType::Xml
+            }
+            file_type => file_type,
+        }
+    }
+}
+
+fn looks_like_xml(content: &str) -> bool {
+    let start = content.trim_start();
+    start.starts_with("<?xml") || start.starts_with("<!DOCTYPE") || start.starts_with("<fontconfig")
 }
 
 /// Compute a semantic diff between two file contents.
@@ -140,18 +183,31 @@ pub fn compute_semantic_diff(
             };
             SemanticDiff::KeyValue(kv_diff)
         }
-        ConfigFileType::Other => {
-            // Fallback to line count summary
-            let old_lines = old_content.map(|c| c.lines().count()).unwrap_or(0);
-            let new_lines = new_content.map(|c| c.lines().count()).unwrap_or(0);
-            SemanticDiff::LineSummary(LineSummary {
-                added: new_lines.saturating_sub(old_lines),
-                removed: old_lines.saturating_sub(new_lines),
-            })
+        ConfigFileType::Xml => {
+            // Malformed XML degrades to the line summary
+            let parse = |content: Option<&str>| match content {
+                Some(content) => xml::parse(content),
+                None => Some(xml::XmlDocument::default()),
+            };
+            match (parse(old_content), parse(new_content)) {
+                (Some(old), Some(new)) => SemanticDiff::Xml(xml::diff(&old, &new)),
+                _ => line_summary(old_content, new_content),
+            }
         }
+        ConfigFileType::Other => line_summary(old_content, new_content),
     }
 }
 
+/// Fallback to line count summary.
+fn line_summary(old_content: Option<&str>, new_content: Option<&str>) -> SemanticDiff {
+    let old_lines = old_content.map(|c| c.lines().count()).unwrap_or(0);
+    let new_lines = new_content.map(|c| c.lines().count()).unwrap_or(0);
+    SemanticDiff::LineSummary(LineSummary {
+        added: new_lines.saturating_sub(old_lines),
+        removed: old_lines.saturating_sub(new_lines),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +228,10 @@ mod tests {
         );
         assert_eq!(
             ConfigFileType::from_path("system/fontconfig/99-emoji-fix.conf"),
+            ConfigFileType::Xml
+        );
+        assert_eq!(
+            ConfigFileType::from_path("system/NetworkManager/conf.d/dns.conf"),
             ConfigFileType::Ini
         );
         assert_eq!(
@@ -179,4 +239,46 @@ mod tests {
             ConfigFileType::Other
         );
     }
+
+    #[test]
+    fn test_detect_sniffs_xml_content() {
+        assert_eq!(
+            ConfigFileType::detect(
+                "etc/fonts/local.conf",
+                Some("<?xml version=\"1.0\"?>\n<fontconfig/>")
+            ),
+            ConfigFileType::Xml
+        );
+        assert_eq!(
+            ConfigFileType::detect("etc/foo.conf", Some("[main]\nkey=1\n")),
+            ConfigFileType::Ini
+        );
+        assert_eq!(
+            ConfigFileType::detect("system/keyd/default.conf", Some("<fontconfig/>")),
+            ConfigFileType::Keyd
+        );
+    }
+
+    #[test]
+    fn test_malformed_xml_falls_back_to_line_summary() {
+        let diff = compute_semantic_diff(
+            ConfigFileType::Xml,
+            Some("<fontconfig>\n</fontconfig>\n"),
+            Some("<fontconfig>\n<match>\n</fontconfig>\n"),
+        );
+        assert!(matches!(
+            diff,
+            SemanticDiff::LineSummary(LineSummary {
+                added: 1,
+                removed: 0
+            })
+        ));
+
+        let diff = compute_semantic_diff(
+            ConfigFileType::Xml,
+            Some("<fontconfig><dir>/a</dir></fontconfig>"),
+            Some("<fontconfig><dir>/b</dir></fontconfig>"),
+        );
+        assert!(matches!(diff, SemanticDiff::Xml(ref d) if d.changed.len() == 1));
+    }
 }
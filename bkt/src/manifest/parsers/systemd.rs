@@ -51,6 +51,24 @@ pub fn parse(content: &str) -> SystemdUnit {
     unit
 }
 
+/// Render a unit back to INI form, one blank line between sections.
+///
+/// Comments and repeated keys aren't kept by [`parse`], so this is only
+/// faithful for files bkt writes itself.
+pub fn render(unit: &SystemdUnit) -> String {
+    let mut out = String::new();
+    for (section, props) in &unit.sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("[{}]\n", section));
+        for (key, value) in props {
+            out.push_str(&format!("{}={}\n", key, value));
+        }
+    }
+    out
+}
+
 /// Compute diff between two systemd units.
 pub fn diff(old: &SystemdUnit, new: &SystemdUnit) -> SystemdDiff {
     let mut result = SystemdDiff::default();
@@ -169,6 +187,14 @@ WantedBy=default.target
         );
     }
 
+    #[test]
+    fn test_render_roundtrips_parse() {
+        let content = "[Service]\nMemoryMax=8G\nRestart=always\n\n[Unit]\nAfter=network.target\n";
+        let unit = parse(content);
+        assert_eq!(render(&unit), content);
+        assert!(diff(&unit, &parse(&render(&unit))).is_empty());
+    }
+
     #[test]
     fn test_diff_systemd_added_property() {
         let old = parse("[Service]\nType=oneshot\n");
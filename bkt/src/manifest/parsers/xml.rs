@@ -0,0 +1,257 @@
+//! Parser for XML config files (fontconfig).
+//!
+//! The document is flattened into element paths, so
+//! `<match><test name="family"><string>Noto Color Emoji</string></test></match>`
+//! becomes `match/test[name=family]/string = Noto Color Emoji`.
+//!
+//! - The root element (`<fontconfig>`) is left out of paths.
+//! - A `name` attribute identifies an element and goes into its path segment.
+//!   Other attributes become `path/@attr` entries.
+//! - Repeated siblings get an XPath-style index from the second one on
+//!   (`alias[2]/family`).
+
+use super::{PropertyChange, XmlDiff};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::{BTreeMap, HashMap};
+
+/// Parsed XML document as a flat path -> value map.
+#[derive(Debug, Clone, Default)]
+pub struct XmlDocument {
+    pub entries: BTreeMap<String, String>,
+}
+
+/// An element being read, with what's been seen inside it so far.
+struct Frame {
+    path: String,
+    text: String,
+    has_children: bool,
+    /// Occurrences of each child segment, for indexing repeats
+    child_counts: HashMap<String, usize>,
+}
+
+/// Parse XML content into flattened entries.
+///
+/// Returns `None` if the document is malformed or has no root element.
+pub fn parse(content: &str) -> Option<XmlDocument> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut doc = XmlDocument::default();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut seen_root = false;
+
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) => {
+                let frame = open_element(&e, &mut stack, &mut doc)?;
+                seen_root = true;
+                stack.push(frame);
+            }
+            Event::Empty(e) => {
+                let frame = open_element(&e, &mut stack, &mut doc)?;
+                seen_root = true;
+                close_element(frame, &mut doc);
+            }
+            Event::End(_) => {
+                let frame = stack.pop()?;
+                close_element(frame, &mut doc);
+            }
+            Event::Text(t) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&t.unescape().ok()?);
+                }
+            }
+            Event::CData(t) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame
+                        .text
+                        .push_str(&String::from_utf8_lossy(&t.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            // Declarations, comments, doctypes, processing instructions
+            _ => {}
+        }
+    }
+
+    (seen_root && stack.is_empty()).then_some(doc)
+}
+
+fn open_element(e: &BytesStart<'_>, stack: &mut [Frame], doc: &mut XmlDocument) -> Option<Frame> {
+    let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+
+    let mut name = None;
+    let mut attributes = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr.ok()?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let value = attr.unescape_value().ok()?.to_string();
+        if key == "name" {
+            name = Some(value);
+        } else {
+            attributes.push((key, value));
+        }
+    }
+
+    let mut segment = match name {
+        Some(name) => format!("{}[name={}]", tag, name),
+        None => tag,
+    };
+
+    let path = match stack.last_mut() {
+        // The root element doesn't appear in paths
+        None => String::new(),
+        Some(parent) => {
+            parent.has_children = true;
+            let count = parent.child_counts.entry(segment.clone()).or_insert(0);
+            *count += 1;
+            if *count > 1 {
+                segment = format!("{}[{}]", segment, count);
+            }
+            if parent.path.is_empty() {
+                segment
+            } else {
+                format!("{}/{}", parent.path, segment)
+            }
+        }
+    };
+
+    for (key, value) in attributes {
+        let attr_path = if path.is_empty() {
+            format!("@{}", key)
+        } else {
+            format!("{}/@{}", path, key)
+        };
+        doc.entries.insert(attr_path, value);
+    }
+
+    Some(Frame {
+        path,
+        text: String::new(),
+        has_children: false,
+        child_counts: HashMap::new(),
+    })
+}
+
+fn close_element(frame: Frame, doc: &mut XmlDocument) {
+    if frame.path.is_empty() {
+        return;
+    }
+    let text = frame.text.trim();
+    // Leaves are recorded even when empty (`<rejectfont/>`) so adding or
+    // removing them shows up
+    if !frame.has_children || !text.is_empty() {
+        doc.entries.insert(frame.path, text.to_string());
+    }
+}
+
+/// Compute diff between two XML documents.
+pub fn diff(old: &XmlDocument, new: &XmlDocument) -> XmlDiff {
+    let mut result = XmlDiff::default();
+
+    for (path, old_val) in &old.entries {
+        match new.entries.get(path) {
+            None => result.removed.push(PropertyChange {
+                property: path.clone(),
+                from: Some(old_val.clone()),
+                to: None,
+            }),
+            Some(new_val) if new_val != old_val => result.changed.push(PropertyChange {
+                property: path.clone(),
+                from: Some(old_val.clone()),
+                to: Some(new_val.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (path, new_val) in &new.entries {
+        if !old.entries.contains_key(path) {
+            result.added.push(PropertyChange {
+                property: path.clone(),
+                from: None,
+                to: Some(new_val.clone()),
+            });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMOJI_FIX: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE fontconfig SYSTEM "urn:fontconfig:fonts.dtd">
+<fontconfig>
+  <!-- Prefer color emoji -->
+  <match target="pattern">
+    <test name="family" qual="any"><string>emoji</string></test>
+    <edit name="family" mode="assign" binding="strong">
+      <string>Noto Color Emoji</string>
+    </edit>
+  </match>
+  <alias>
+    <family>serif</family>
+    <prefer><family>Noto Serif</family></prefer>
+  </alias>
+  <alias>
+    <family>sans-serif</family>
+    <prefer><family>Noto Sans</family><family>Noto Color Emoji</family></prefer>
+  </alias>
+  <selectfont><rejectfont/></selectfont>
+</fontconfig>
+"#;
+
+    #[test]
+    fn test_parse_flattens_element_paths() {
+        let doc = parse(EMOJI_FIX).unwrap();
+        let get = |path: &str| doc.entries.get(path).map(String::as_str);
+
+        assert_eq!(get("match/@target"), Some("pattern"));
+        assert_eq!(get("match/test[name=family]/@qual"), Some("any"));
+        assert_eq!(get("match/test[name=family]/string"), Some("emoji"));
+        assert_eq!(get("match/edit[name=family]/@mode"), Some("assign"));
+        assert_eq!(
+            get("match/edit[name=family]/string"),
+            Some("Noto Color Emoji")
+        );
+        assert_eq!(get("alias/family"), Some("serif"));
+        assert_eq!(get("alias[2]/family"), Some("sans-serif"));
+        assert_eq!(get("alias[2]/prefer/family[2]"), Some("Noto Color Emoji"));
+        assert_eq!(get("selectfont/rejectfont"), Some(""));
+        // Containers without text of their own aren't entries
+        assert_eq!(get("match"), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_xml() {
+        assert!(parse("<fontconfig><match></fontconfig>").is_none());
+        assert!(parse("<fontconfig><match>").is_none());
+        assert!(parse("[main]\nkey=value\n").is_none());
+    }
+
+    #[test]
+    fn test_diff_xml_changes() {
+        let old = parse(EMOJI_FIX).unwrap();
+        let new = parse(
+            &EMOJI_FIX
+                .replace("Noto Color Emoji</string>", "Twemoji</string>")
+                .replace(r#" mode="assign""#, r#" mode="prepend""#)
+                .replace("<selectfont><rejectfont/></selectfont>", ""),
+        )
+        .unwrap();
+
+        let diff = diff(&old, &new);
+
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].property, "selectfont/rejectfont");
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].property, "match/edit[name=family]/@mode");
+        assert_eq!(diff.changed[1].property, "match/edit[name=family]/string");
+        assert_eq!(diff.changed[1].to, Some("Twemoji".to_string()));
+    }
+}
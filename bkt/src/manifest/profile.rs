@@ -0,0 +1,240 @@
+//! Named per-machine profiles.
+//!
+//! A profile is a directory of user manifests in
+//! `~/.config/bootc/profiles/<name>/` whose entries are overlaid on the repo
+//! manifests, so one image can carry different flatpaks, extensions, and
+//! gsettings per machine. The active profile is recorded in
+//! `~/.local/state/bkt/profile.json`.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which profile is active, persisted across invocations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileState {
+    /// Name of the active profile; `None` uses the repo manifests alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+}
+
+impl ProfileState {
+    /// Path to the profile state file.
+    pub fn path() -> PathBuf {
+        let state_dir = std::env::var("XDG_STATE_HOME")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var("HOME")
+                    .ok()
+                    .map(|h| PathBuf::from(h).join(".local/state"))
+            })
+            .unwrap_or_else(|| PathBuf::from(".local/state"));
+        state_dir.join("bkt").join("profile.json")
+    }
+
+    /// Load the profile state, or the default if none has been saved.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profile state from {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse profile state from {}", path.display()))
+    }
+
+    /// Save the profile state.
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize profile state")?;
+        super::history::write(path, &(content + "\n"))
+            .with_context(|| format!("Failed to write profile state to {}", path.display()))
+    }
+
+    /// The active profile, if one is set.
+    pub fn active_profile(&self) -> Result<Option<Profile>> {
+        self.active.as_deref().map(Profile::named).transpose()
+    }
+
+    /// Path of the active profile's overlay for a repo manifest, e.g.
+    /// `manifests/flatpak-apps.json` -> `<profile>/flatpak-apps.json`.
+    ///
+    /// Returns `None` when no profile is active.
+    pub fn overlay_path(project_path: &str) -> Result<Option<PathBuf>> {
+        Ok(Self::load()?
+            .active_profile()?
+            .map(|profile| profile.overlay_path(project_path)))
+    }
+}
+
+/// A named profile directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl Profile {
+    /// Repo manifests a profile can overlay.
+    pub const OVERLAY_MANIFESTS: &'static [&'static str] = &[
+        super::FlatpakAppsManifest::PROJECT_PATH,
+        super::GnomeExtensionsManifest::PROJECT_PATH,
+        super::GSettingsManifest::PROJECT_PATH,
+    ];
+
+    /// Directory holding all profiles.
+    pub fn root() -> PathBuf {
+        std::env::var("XDG_CONFIG_HOME")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var("HOME")
+                    .ok()
+                    .map(|h| PathBuf::from(h).join(".config"))
+            })
+            .unwrap_or_else(|| PathBuf::from(".config"))
+            .join("bootc")
+            .join("profiles")
+    }
+
+    /// The profile called `name` under [`Profile::root`].
+    pub fn named(name: &str) -> Result<Self> {
+        Self::in_root(&Self::root(), name)
+    }
+
+    fn in_root(root: &Path, name: &str) -> Result<Self> {
+        validate_profile_name(name)?;
+        Ok(Self {
+            name: name.to_string(),
+            dir: root.join(name),
+        })
+    }
+
+    /// Names of all existing profiles, sorted.
+    pub fn list() -> Result<Vec<String>> {
+        Self::list_in(&Self::root())
+    }
+
+    fn list_in(root: &Path) -> Result<Vec<String>> {
+        if !root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in
+            fs::read_dir(root).with_context(|| format!("Failed to read {}", root.display()))?
+        {
+            let entry = entry?;
+            if entry.file_type()?.is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && validate_profile_name(name).is_ok()
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn exists(&self) -> bool {
+        self.dir.is_dir()
+    }
+
+    /// Create the profile directory. Returns false if it already existed.
+    pub fn create(&self) -> Result<bool> {
+        if self.exists() {
+            return Ok(false);
+        }
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create profile at {}", self.dir.display()))?;
+        Ok(true)
+    }
+
+    /// Where this profile's overlay for a repo manifest lives.
+    pub fn overlay_path(&self, project_path: &str) -> PathBuf {
+        let file_name = Path::new(project_path)
+            .file_name()
+            .unwrap_or(project_path.as_ref());
+        self.dir.join(file_name)
+    }
+}
+
+/// Profile names become directory names, so keep them to a safe charset.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with(['.', '-'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "Invalid profile name '{}': use letters, digits, '-', '_' or '.', not starting with '.' or '-'",
+            name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert!(validate_profile_name("laptop").is_ok());
+        assert!(validate_profile_name("work_desk-2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("..").is_err());
+        assert!(validate_profile_name("a/b").is_err());
+        assert!(validate_profile_name("-x").is_err());
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("bkt/profile.json");
+
+        assert_eq!(
+            ProfileState::load_from(&path).unwrap(),
+            ProfileState::default()
+        );
+
+        let state = ProfileState {
+            active: Some("laptop".to_string()),
+        };
+        state.save_to(&path).unwrap();
+        assert_eq!(ProfileState::load_from(&path).unwrap(), state);
+    }
+
+    #[test]
+    fn test_create_and_list_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let laptop = Profile::in_root(root, "laptop").unwrap();
+        assert!(laptop.create().unwrap());
+        assert!(!laptop.create().unwrap());
+        Profile::in_root(root, "desktop").unwrap().create().unwrap();
+        fs::write(root.join("stray.json"), "{}").unwrap();
+
+        assert_eq!(Profile::list_in(root).unwrap(), vec!["desktop", "laptop"]);
+        assert_eq!(
+            laptop.overlay_path("manifests/flatpak-apps.json"),
+            root.join("laptop/flatpak-apps.json")
+        );
+    }
+}
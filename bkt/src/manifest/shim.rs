@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use directories::BaseDirs;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -18,13 +19,72 @@ pub struct Shim {
     /// Name of the command on the host (defaults to name if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub host: Option<String>,
+    /// Environment variables to set for the host command
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Start the host command with an empty environment (plus `env`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clear_env: bool,
+    /// Host directory to run the command in, instead of the caller's CWD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<String>,
 }
 
 impl Shim {
+    /// Create a shim that runs `host` (or `name` itself) with no extra options.
+    pub fn new(name: impl Into<String>, host: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            host,
+            env: BTreeMap::new(),
+            clear_env: false,
+            workdir: None,
+        }
+    }
+
     /// Get the host command name (defaults to shim name if not specified).
     pub fn host_cmd(&self) -> &str {
         self.host.as_deref().unwrap_or(&self.name)
     }
+
+    /// The shell-quoted `flatpak-spawn` invocation for the host command.
+    ///
+    /// Environment and working directory are passed as `flatpak-spawn`
+    /// options: variables exported inside the toolbox never reach the
+    /// host process, and the toolbox CWD may not exist on the host.
+    pub fn spawn_command(&self) -> Result<String> {
+        let mut args = vec!["flatpak-spawn".to_string(), "--host".to_string()];
+        if self.clear_env {
+            args.push("--clear-env".to_string());
+        }
+        if let Some(workdir) = &self.workdir {
+            args.push(format!("--directory={}", workdir));
+        }
+        for (key, value) in &self.env {
+            args.push(format!("--env={}={}", key, value));
+        }
+        args.push(self.host_cmd().to_string());
+
+        shlex::try_join(args.iter().map(String::as_str))
+            .with_context(|| format!("Failed to quote command for shim '{}'", self.name))
+    }
+
+    /// The shim script: runs the host command with the caller's arguments.
+    pub fn script(&self) -> Result<String> {
+        Ok(format!(
+            "#!/bin/bash\nexec {} \"$@\"\n",
+            self.spawn_command()?
+        ))
+    }
+}
+
+/// The home directory, preferring `$HOME` for test isolation.
+fn home_dir() -> PathBuf {
+    std::env::var("HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| BaseDirs::new().map(|d| d.home_dir().to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."))
 }
 
 /// The host-shims.json manifest.
@@ -59,7 +119,7 @@ impl ShimsManifest {
         }
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize shims manifest")?;
-        fs::write(path, content)
+        super::history::write(path, &content)
             .with_context(|| format!("Failed to write shims manifest to {}", path.display()))?;
         Ok(())
     }
@@ -68,13 +128,12 @@ impl ShimsManifest {
     ///
     /// Respects `$HOME` environment variable for test isolation.
     pub fn shims_dir() -> PathBuf {
-        // Prefer $HOME for test isolation, fall back to BaseDirs
-        let home = std::env::var("HOME")
-            .ok()
-            .map(PathBuf::from)
-            .or_else(|| BaseDirs::new().map(|d| d.home_dir().to_path_buf()))
-            .unwrap_or_else(|| PathBuf::from("."));
-        home.join(".local").join("toolbox").join("shims")
+        home_dir().join(".local").join("toolbox").join("shims")
+    }
+
+    /// Get the directory on `PATH` holding symlinks to the shims.
+    pub fn bin_dir() -> PathBuf {
+        home_dir().join(".local").join("bin")
     }
 
     /// Load from the repository's manifests directory.
@@ -114,17 +173,11 @@ mod tests {
     use super::*;
 
     fn sample_shim(name: &str) -> Shim {
-        Shim {
-            name: name.to_string(),
-            host: None,
-        }
+        Shim::new(name, None)
     }
 
     fn sample_shim_with_host(name: &str, host: &str) -> Shim {
-        Shim {
-            name: name.to_string(),
-            host: Some(host.to_string()),
-        }
+        Shim::new(name, Some(host.to_string()))
     }
 
     #[test]
@@ -139,6 +192,37 @@ mod tests {
         assert_eq!(shim.host_cmd(), "podman");
     }
 
+    #[test]
+    fn shim_spawn_command_passes_env_and_workdir_to_flatpak_spawn() {
+        let mut shim = sample_shim_with_host("vm", "virsh");
+        shim.env.insert(
+            "LIBVIRT_DEFAULT_URI".to_string(),
+            "qemu:///system".to_string(),
+        );
+        shim.env
+            .insert("GREETING".to_string(), "hello world".to_string());
+        shim.workdir = Some("/var/lib/libvirt".to_string());
+        shim.clear_env = true;
+
+        assert_eq!(
+            shim.spawn_command().unwrap(),
+            "flatpak-spawn --host --clear-env '--directory=/var/lib/libvirt' \
+             '--env=GREETING=hello world' '--env=LIBVIRT_DEFAULT_URI=qemu:///system' virsh"
+        );
+    }
+
+    #[test]
+    fn shim_options_are_omitted_from_json_when_unset() {
+        let json = serde_json::to_string(&sample_shim("podman")).unwrap();
+        assert_eq!(json, r#"{"name":"podman"}"#);
+
+        let shim: Shim =
+            serde_json::from_str(r#"{"name":"nmcli","env":{"LANG":"C"},"workdir":"/"}"#).unwrap();
+        assert_eq!(shim.env["LANG"], "C");
+        assert_eq!(shim.workdir.as_deref(), Some("/"));
+        assert!(!shim.clear_env);
+    }
+
     #[test]
     fn manifest_default_is_empty() {
         let manifest = ShimsManifest::default();
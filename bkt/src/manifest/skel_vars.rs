@@ -0,0 +1,48 @@
+//! Per-machine values for skel templates.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The skel-vars.json user manifest.
+///
+/// Holds the values substituted into `*.tmpl` skel files (email, machine
+/// name, ...). It differs per machine, so it lives in `~/.config/bootc/`
+/// rather than in the repo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkelVars {
+    /// Variable name to value, e.g. `"EMAIL": "me@example.com"`.
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+impl SkelVars {
+    /// File name within the user config directory.
+    pub const FILE_NAME: &'static str = "skel-vars.json";
+
+    /// Path to the user's skel-vars.json.
+    pub fn user_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("Cannot determine $HOME")?;
+        Ok(PathBuf::from(home)
+            .join(".config/bootc")
+            .join(Self::FILE_NAME))
+    }
+
+    /// Load from a path, or an empty set of variables if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read skel vars from {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse skel vars from {}", path.display()))
+    }
+
+    /// Load the user's skel-vars.json.
+    pub fn load_user() -> Result<Self> {
+        Self::load(&Self::user_path()?)
+    }
+}
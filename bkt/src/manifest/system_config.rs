@@ -6,7 +6,7 @@
 use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 
@@ -19,6 +19,23 @@ pub struct KargsConfig {
     /// Arguments to remove from the kernel command line
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub remove: Vec<String>,
+    /// Applied presets, and the `append`/`remove` entries each contributed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub presets: BTreeMap<String, PresetKargs>,
+}
+
+/// The kernel arguments an applied preset put in the manifest.
+///
+/// Entries that were already there (added by hand) aren't recorded, so
+/// removing the preset leaves them alone.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+pub struct PresetKargs {
+    /// Entries the preset added to `append`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub append: Vec<String>,
+    /// Entries the preset added to `remove`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
 }
 
 /// Systemd units configuration.
@@ -36,6 +53,170 @@ pub struct SystemdConfig {
     /// Custom unit files
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub custom: Vec<String>,
+    /// Drop-in overrides by unit name (e.g. `docker.service`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dropins: BTreeMap<String, Vec<SystemdDropin>>,
+}
+
+/// Where drop-ins are installed in the image.
+pub const IMAGE_UNIT_DIR: &str = "/usr/lib/systemd/system";
+
+/// Where drop-ins are written on the running system.
+pub const LOCAL_UNIT_DIR: &str = "/etc/systemd/system";
+
+/// A drop-in override for a systemd unit, installed as `<unit>.d/<filename>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SystemdDropin {
+    /// File name within the unit's `.d` directory, e.g. `override.conf`
+    pub filename: String,
+    /// Inline drop-in content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Repo-relative path to the drop-in file, used instead of `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl SystemdDropin {
+    /// Path of the drop-in for `unit` under a unit directory.
+    pub fn path(&self, unit_dir: &str, unit: &str) -> String {
+        format!("{}/{}.d/{}", unit_dir, unit, self.filename)
+    }
+}
+
+impl SystemdConfig {
+    /// The drop-in `filename` for `unit`, created empty if missing.
+    pub fn dropin_mut(&mut self, unit: &str, filename: &str) -> &mut SystemdDropin {
+        let dropins = self.dropins.entry(unit.to_string()).or_default();
+        let index = match dropins.iter().position(|d| d.filename == filename) {
+            Some(index) => index,
+            None => {
+                dropins.push(SystemdDropin {
+                    filename: filename.to_string(),
+                    content: Some(String::new()),
+                    source: None,
+                });
+                dropins.len() - 1
+            }
+        };
+        &mut dropins[index]
+    }
+
+    /// Remove the drop-in `filename` for `unit`. Returns whether it existed.
+    pub fn remove_dropin(&mut self, unit: &str, filename: &str) -> bool {
+        let Some(dropins) = self.dropins.get_mut(unit) else {
+            return false;
+        };
+        let before = dropins.len();
+        dropins.retain(|d| d.filename != filename);
+        let removed = dropins.len() != before;
+        if dropins.is_empty() {
+            self.dropins.remove(unit);
+        }
+        removed
+    }
+}
+
+/// Unattended `bootc upgrade` during a maintenance window.
+///
+/// Becomes a `bootc-auto-upgrade.timer` and `.service` in the image,
+/// enabled alongside the other managed units.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct UpgradePolicy {
+    /// When the window opens, in systemd OnCalendar syntax (e.g. `Sun *-*-* 03:00`)
+    pub schedule: String,
+    /// Reboot into the new deployment once one is staged
+    #[serde(default)]
+    pub auto_reboot: bool,
+    /// Only reboot when logind reports the system idle
+    #[serde(default)]
+    pub only_if_idle: bool,
+}
+
+/// Name of the timer generated from an [`UpgradePolicy`].
+pub const AUTO_UPGRADE_TIMER: &str = "bootc-auto-upgrade.timer";
+
+/// Name of the service generated from an [`UpgradePolicy`].
+pub const AUTO_UPGRADE_SERVICE: &str = "bootc-auto-upgrade.service";
+
+/// bootc's own update timer, which would upgrade and reboot outside the window.
+pub const BOOTC_UPDATES_TIMER: &str = "bootc-fetch-apply-updates.timer";
+
+/// ostree leaves this behind while a deployment is staged for next boot.
+const STAGED_DEPLOYMENT_MARKER: &str = "/run/ostree/staged-deployment";
+
+impl UpgradePolicy {
+    /// Check the schedule can be written into a unit file.
+    pub fn validate(&self) -> Result<()> {
+        let schedule = self.schedule.trim();
+        if schedule.is_empty() {
+            anyhow::bail!("Upgrade schedule must not be empty");
+        }
+        if schedule.contains(['\n', '\r']) {
+            anyhow::bail!(
+                "Upgrade schedule must be a single line: {:?}",
+                self.schedule
+            );
+        }
+        Ok(())
+    }
+
+    /// Content of `bootc-auto-upgrade.timer`.
+    ///
+    /// Not `Persistent=`: a window missed while powered off is skipped
+    /// rather than run at the next boot, outside the window.
+    pub fn timer_unit(&self) -> String {
+        format!(
+            "[Unit]\n\
+             Description=Scheduled bootc upgrade window (managed by bkt)\n\
+             \n\
+             [Timer]\n\
+             OnCalendar={}\n\
+             Persistent=false\n\
+             \n\
+             [Install]\n\
+             WantedBy=timers.target\n",
+            self.schedule.trim()
+        )
+    }
+
+    /// Content of `bootc-auto-upgrade.service`.
+    pub fn service_unit(&self) -> String {
+        let mut unit = String::from(
+            "[Unit]\n\
+             Description=Scheduled bootc upgrade (managed by bkt)\n\
+             Wants=network-online.target\n\
+             After=network-online.target\n\
+             ConditionPathExists=/usr/bin/bkt\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             ExecStart=/usr/bin/bkt admin bootc upgrade --yes --quiet\n",
+        );
+        if let Some(reboot) = self.reboot_command() {
+            unit.push_str(&format!("ExecStart=/bin/sh -c '{}'\n", reboot));
+        }
+        unit
+    }
+
+    /// Shell run after the upgrade when `auto_reboot` is set.
+    ///
+    /// `$$` is systemd's escape for a literal `$`.
+    fn reboot_command(&self) -> Option<String> {
+        if !self.auto_reboot {
+            return None;
+        }
+        let mut steps = vec![format!("test -e {} || exit 0", STAGED_DEPLOYMENT_MARKER)];
+        if self.only_if_idle {
+            steps.push(
+                "if [ \"$$(loginctl show --property=IdleHint --value)\" != yes ]; then \
+                 echo \"System not idle; reboot deferred\"; exit 0; fi"
+                    .to_string(),
+            );
+        }
+        steps.push("systemctl reboot".to_string());
+        Some(steps.join("; "))
+    }
 }
 
 /// Udev configuration.
@@ -81,13 +262,20 @@ pub struct SystemConfigManifest {
     /// Firmware notes/reminders
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub firmware_notes: Vec<String>,
+
+    /// Scheduled unattended upgrades
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade_policy: Option<UpgradePolicy>,
 }
 
 impl SystemConfigManifest {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/system-config.json";
+
     /// Resolve the path to the system-config.json file in the repo.
     pub fn path() -> Result<PathBuf> {
         let repo_path = crate::repo::find_repo_path()?;
-        Ok(repo_path.join("manifests").join("system-config.json"))
+        Ok(repo_path.join(Self::PROJECT_PATH))
     }
 
     /// Load the manifest from the repository.
@@ -130,7 +318,7 @@ impl SystemConfigManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize system config manifest")?;
-        fs::write(path, content).with_context(|| {
+        super::history::write(path, &content).with_context(|| {
             format!(
                 "Failed to write system config manifest to {}",
                 path.display()
@@ -174,4 +362,99 @@ mod tests {
         assert!(json.contains("\"systemd\":{"));
         assert!(json.contains("\"enable\":[\"foo.service\"]"));
     }
+
+    #[test]
+    fn test_dropins_upsert_and_remove() {
+        let mut systemd = SystemdConfig::default();
+        systemd
+            .dropin_mut("docker.service", "override.conf")
+            .content = Some("[Service]\nMemoryMax=8G\n".to_string());
+        systemd.dropin_mut("docker.service", "override.conf");
+        systemd.dropin_mut("docker.service", "10-limits.conf");
+
+        let dropins = &systemd.dropins["docker.service"];
+        assert_eq!(dropins.len(), 2);
+        assert_eq!(
+            dropins[0].content.as_deref(),
+            Some("[Service]\nMemoryMax=8G\n")
+        );
+        assert_eq!(
+            dropins[0].path(IMAGE_UNIT_DIR, "docker.service"),
+            "/usr/lib/systemd/system/docker.service.d/override.conf"
+        );
+
+        assert!(systemd.remove_dropin("docker.service", "override.conf"));
+        assert!(systemd.remove_dropin("docker.service", "10-limits.conf"));
+        assert!(!systemd.remove_dropin("docker.service", "10-limits.conf"));
+        assert!(systemd.dropins.is_empty());
+    }
+
+    fn policy(auto_reboot: bool, only_if_idle: bool) -> UpgradePolicy {
+        UpgradePolicy {
+            schedule: "Sun *-*-* 03:00".to_string(),
+            auto_reboot,
+            only_if_idle,
+        }
+    }
+
+    #[test]
+    fn test_upgrade_timer_unit() {
+        assert_eq!(
+            policy(false, false).timer_unit(),
+            "[Unit]\n\
+             Description=Scheduled bootc upgrade window (managed by bkt)\n\
+             \n\
+             [Timer]\n\
+             OnCalendar=Sun *-*-* 03:00\n\
+             Persistent=false\n\
+             \n\
+             [Install]\n\
+             WantedBy=timers.target\n"
+        );
+    }
+
+    #[test]
+    fn test_upgrade_service_unit_without_reboot() {
+        let unit = policy(false, true).service_unit();
+        assert_eq!(
+            unit,
+            "[Unit]\n\
+             Description=Scheduled bootc upgrade (managed by bkt)\n\
+             Wants=network-online.target\n\
+             After=network-online.target\n\
+             ConditionPathExists=/usr/bin/bkt\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             ExecStart=/usr/bin/bkt admin bootc upgrade --yes --quiet\n"
+        );
+    }
+
+    #[test]
+    fn test_upgrade_service_unit_reboots_when_staged() {
+        let unit = policy(true, false).service_unit();
+        assert!(unit.ends_with(
+            "ExecStart=/bin/sh -c 'test -e /run/ostree/staged-deployment || exit 0; \
+             systemctl reboot'\n"
+        ));
+
+        let unit = policy(true, true).service_unit();
+        let reboot = unit.lines().last().unwrap();
+        assert_eq!(
+            reboot,
+            "ExecStart=/bin/sh -c 'test -e /run/ostree/staged-deployment || exit 0; \
+             if [ \"$$(loginctl show --property=IdleHint --value)\" != yes ]; then \
+             echo \"System not idle; reboot deferred\"; exit 0; fi; systemctl reboot'"
+        );
+    }
+
+    #[test]
+    fn test_upgrade_policy_validation() {
+        assert!(policy(false, false).validate().is_ok());
+        let mut bad = policy(false, false);
+        bad.schedule = "  ".to_string();
+        assert!(bad.validate().is_err());
+        bad.schedule = "daily\nExecStart=/bin/true".to_string();
+        assert!(bad.validate().is_err());
+    }
 }
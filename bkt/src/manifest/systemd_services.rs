@@ -88,7 +88,7 @@ impl SystemdServicesManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize systemd services manifest")?;
-        fs::write(path, content).with_context(|| {
+        super::history::write(path, &content).with_context(|| {
             format!(
                 "Failed to write systemd services manifest to {}",
                 path.display()
@@ -4,7 +4,7 @@
 //! toolbox-packages.json tracks packages installed in the development
 //! toolbox container.
 
-use super::dnf::{CoprRepo, SystemPackagesManifest};
+use super::dnf::{CoprRepo, SystemPackagesManifest, delete_group, group_spec, insert_group};
 use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -72,7 +72,7 @@ impl ToolboxPackagesManifest {
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize toolbox packages manifest")?;
-        fs::write(path, content).with_context(|| {
+        super::history::write(path, &content).with_context(|| {
             format!(
                 "Failed to write toolbox packages manifest to {}",
                 path.display()
@@ -103,6 +103,10 @@ impl ToolboxPackagesManifest {
             groups: self.groups.clone(),
             excluded: self.excluded.clone(),
             copr_repos: self.copr_repos.clone(),
+            pins: Default::default(),
+            arches: Default::default(),
+            versions: Default::default(),
+            capture_exclude: Default::default(),
         }
     }
 
@@ -136,6 +140,23 @@ impl ToolboxPackagesManifest {
         self.packages.len() < len
     }
 
+    /// Check whether a package group is in the manifest.
+    pub fn find_group(&self, name: &str) -> bool {
+        let spec = group_spec(name);
+        self.groups.iter().any(|g| group_spec(g) == spec)
+    }
+
+    /// Add a package group (stored as `@name`).
+    /// Returns true if the group was added, false if it already existed.
+    pub fn add_group(&mut self, name: &str) -> bool {
+        insert_group(&mut self.groups, name)
+    }
+
+    /// Remove a package group.
+    pub fn remove_group(&mut self, name: &str) -> bool {
+        delete_group(&mut self.groups, name)
+    }
+
     /// Check if manifest is empty.
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
@@ -0,0 +1,176 @@
+//! Toolbox binaries manifest types.
+//!
+//! Unlike host-binaries.json which tracks fetchbin binaries on the host,
+//! toolbox-binaries.json tracks dev tools installed *inside* the development
+//! toolbox, typically via `cargo install` or `cargo binstall`.
+
+use super::fetchbin::HostBinarySource;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// The toolbox-binaries.json manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct ToolboxBinariesManifest {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub binaries: Vec<ToolboxBinary>,
+}
+
+/// A dev binary installed in the toolbox.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolboxBinary {
+    /// Crate or package name (e.g., "cargo-nextest")
+    pub name: String,
+    /// Where the binary comes from; cargo crates install via cargo-binstall,
+    /// everything else via fetchbin
+    pub source: HostBinarySource,
+    /// Version to install (latest if omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl ToolboxBinary {
+    /// Entry for a crate installed with `cargo install`/`cargo binstall`.
+    pub fn cargo(crate_name: &str, version: Option<String>) -> Self {
+        Self {
+            name: crate_name.to_string(),
+            source: HostBinarySource::Cargo {
+                crate_name: crate_name.to_string(),
+            },
+            version,
+        }
+    }
+
+    /// The crate name, for cargo-sourced entries.
+    pub fn crate_name(&self) -> Option<&str> {
+        match &self.source {
+            HostBinarySource::Cargo { crate_name } => Some(crate_name),
+            _ => None,
+        }
+    }
+}
+
+impl ToolboxBinariesManifest {
+    /// Project manifest path (relative to workspace root).
+    pub const PROJECT_PATH: &'static str = "manifests/toolbox-binaries.json";
+
+    /// Load a manifest from a path.
+    pub fn load(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read toolbox binaries manifest from {}",
+                path.display()
+            )
+        })?;
+        let manifest: Self = serde_json::from_str(&content).with_context(|| {
+            format!(
+                "Failed to parse toolbox binaries manifest from {}",
+                path.display()
+            )
+        })?;
+        Ok(manifest)
+    }
+
+    /// Save a manifest to a path.
+    pub fn save(&self, path: &PathBuf) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize toolbox binaries manifest")?;
+        super::history::write(path, &(content + "\n")).with_context(|| {
+            format!(
+                "Failed to write toolbox binaries manifest to {}",
+                path.display()
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Load from the repository's manifests directory.
+    pub fn load_repo() -> Result<Self> {
+        let repo = crate::repo::find_repo_path()?;
+        Self::load(&repo.join(Self::PROJECT_PATH))
+    }
+
+    /// Save to the repository's manifests directory.
+    pub fn save_repo(&self) -> Result<()> {
+        let repo = crate::repo::find_repo_path()?;
+        self.save(&repo.join(Self::PROJECT_PATH))
+    }
+
+    /// Find a binary by name.
+    pub fn find(&self, name: &str) -> Option<&ToolboxBinary> {
+        self.binaries.iter().find(|b| b.name == name)
+    }
+
+    /// Add or update a binary. Returns true if updated.
+    pub fn upsert(&mut self, binary: ToolboxBinary) -> bool {
+        if let Some(existing) = self.binaries.iter_mut().find(|b| b.name == binary.name) {
+            *existing = binary;
+            true
+        } else {
+            self.binaries.push(binary);
+            self.binaries.sort_by(|a, b| a.name.cmp(&b.name));
+            false
+        }
+    }
+
+    /// Remove a binary by name. Returns true if removed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len_before = self.binaries.len();
+        self.binaries.retain(|b| b.name != name);
+        self.binaries.len() < len_before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_upsert_keeps_binaries_sorted() {
+        let mut manifest = ToolboxBinariesManifest::default();
+        assert!(!manifest.upsert(ToolboxBinary::cargo("ripgrep", None)));
+        assert!(!manifest.upsert(ToolboxBinary::cargo("cargo-nextest", None)));
+        assert!(manifest.upsert(ToolboxBinary::cargo("ripgrep", Some("14.1.0".to_string()))));
+
+        let names: Vec<_> = manifest.binaries.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["cargo-nextest", "ripgrep"]);
+        assert_eq!(
+            manifest.find("ripgrep").unwrap().version.as_deref(),
+            Some("14.1.0")
+        );
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("toolbox-binaries.json");
+
+        let mut manifest = ToolboxBinariesManifest::default();
+        manifest.upsert(ToolboxBinary::cargo("just", Some("1.36.0".to_string())));
+        manifest.upsert(ToolboxBinary {
+            name: "turbo".to_string(),
+            source: HostBinarySource::Npm {
+                package: "turbo".to_string(),
+            },
+            version: None,
+        });
+        manifest.save(&path).unwrap();
+
+        let loaded = ToolboxBinariesManifest::load(&path).unwrap();
+        assert_eq!(loaded.binaries, manifest.binaries);
+        assert_eq!(loaded.find("just").unwrap().crate_name(), Some("just"));
+        assert_eq!(loaded.find("turbo").unwrap().crate_name(), None);
+    }
+}
@@ -46,7 +46,10 @@ impl ManifestRepo for UpstreamManifest {
             fs::create_dir_all(&dir)?;
 
             fs::write(dir.join("version"), &upstream.pinned.version)?;
-            fs::write(dir.join("sha256"), &upstream.pinned.sha256)?;
+            if !upstream.pinned.sha256.is_empty() {
+                fs::write(dir.join("sha256"), &upstream.pinned.sha256)?;
+            }
+            fs::write(dir.join("checksum"), upstream.pinned.expected_checksum())?;
 
             if let Some(url) = &upstream.pinned.url {
                 fs::write(dir.join("url"), url)?;
@@ -155,10 +158,15 @@ mod tests {
                 commit: None,
                 url: None,
                 sha256: sha256.to_string(),
+                checksum: None,
                 gpg_verified: false,
                 pinned_at: Utc::now(),
             },
             install: None,
+            signature_url: None,
+            gpg_key: None,
+            arches: Vec::new(),
+            mirrors: Vec::new(),
         }
     }
 
@@ -174,10 +182,15 @@ mod tests {
                 commit: None,
                 url: None,
                 sha256: sha256.to_string(),
+                checksum: None,
                 gpg_verified: false,
                 pinned_at: Utc::now(),
             },
             install: None,
+            signature_url: None,
+            gpg_key: None,
+            arches: Vec::new(),
+            mirrors: Vec::new(),
         }
     }
 
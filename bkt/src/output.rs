@@ -19,80 +19,223 @@
 //! // ... do work ...
 //! spinner.finish_success("Installed 3 packages");
 //! ```
+//!
+//! How much of this is printed is governed by a process-wide [`Verbosity`],
+//! set once from the global `-q`/`-v` flags. Under `--quiet`, banners and
+//! status chatter are dropped while errors, warnings, and the data a command
+//! was asked for (tables, key-value listings, JSON) still print.
 
 use indicatif::{ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::time::Duration;
 
+/// Output format selected with `--format`.
+///
+/// Shared by the global flag and every subcommand-level `--format`, so a
+/// subcommand's own flag can shadow the global one. The document formats
+/// only mean something to `build-info render`; elsewhere they print the
+/// human-readable output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    #[value(alias = "human", alias = "text")]
+    Table,
+    /// JSON output for scripting
+    Json,
+    /// Markdown document (`build-info render`)
+    #[value(hide = true)]
+    Markdown,
+    /// Standalone HTML page (`build-info render`)
+    #[value(hide = true)]
+    Html,
+}
+
+/// How much incidental output to print, from the global `-q`/`-v` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Only errors, warnings, and requested data (`-q`)
+    Quiet,
+    /// The default
+    #[default]
+    Normal,
+    /// Also bkt's debug logging (`-v`)
+    Verbose,
+    /// Also bkt's trace logging (`-vv` or more)
+    Trace,
+}
+
+impl Verbosity {
+    /// Resolve the global flags. `--quiet` wins over any `-v`.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Trace,
+        }
+    }
+
+    /// Extra tracing directive to add on top of `RUST_LOG`, if any.
+    pub fn log_directive(self) -> Option<&'static str> {
+        match self {
+            Verbosity::Quiet | Verbosity::Normal => None,
+            Verbosity::Verbose => Some("bkt=debug"),
+            Verbosity::Trace => Some("bkt=trace"),
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            2 => Verbosity::Verbose,
+            _ => Verbosity::Trace,
+        }
+    }
+}
+
+/// Process-wide verbosity, stored as `Verbosity as u8`.
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+thread_local! {
+    /// Output captured on this thread instead of printed, with the verbosity
+    /// it was captured at. Only set by [`Output::capture`] in tests.
+    static CAPTURE: RefCell<Option<(Verbosity, String)>> = const { RefCell::new(None) };
+}
+
 /// Standard output helper for consistent CLI formatting.
 pub struct Output;
 
 impl Output {
+    /// Set the process-wide verbosity. Called once from `main`.
+    pub fn set_verbosity(verbosity: Verbosity) {
+        VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    }
+
+    /// The current verbosity.
+    pub fn verbosity() -> Verbosity {
+        CAPTURE
+            .with_borrow(|capture| capture.as_ref().map(|(verbosity, _)| *verbosity))
+            .unwrap_or_else(|| Verbosity::from_u8(VERBOSITY.load(Ordering::Relaxed)))
+    }
+
+    /// Whether status chatter is suppressed (`--quiet`).
+    pub fn is_quiet() -> bool {
+        Self::verbosity() == Verbosity::Quiet
+    }
+
+    /// Whether extra detail was requested (`--verbose`).
+    pub fn is_verbose() -> bool {
+        Self::verbosity() >= Verbosity::Verbose
+    }
+
+    /// Write a line to stdout (or stderr), unless it is being captured.
+    fn emit(line: String, stderr: bool) {
+        let captured = CAPTURE.with_borrow_mut(|capture| match capture {
+            Some((_, buffer)) => {
+                buffer.push_str(&line);
+                buffer.push('\n');
+                true
+            }
+            None => false,
+        });
+        if captured {
+            return;
+        }
+        if stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    /// Write a status line, which `--quiet` suppresses.
+    fn chatter(line: String) {
+        if !Self::is_quiet() {
+            Self::emit(line, false);
+        }
+    }
+
+    /// Run `f` at the given verbosity and return what it printed through
+    /// `Output`, stderr included, instead of printing it.
+    #[cfg(test)]
+    pub fn capture(verbosity: Verbosity, f: impl FnOnce()) -> String {
+        CAPTURE.set(Some((verbosity, String::new())));
+        f();
+        CAPTURE.take().map(|(_, buffer)| buffer).unwrap_or_default()
+    }
+
     /// Print a success message with a green checkmark.
     ///
     /// Example: `✓ Installed 3 packages`
     pub fn success(msg: impl AsRef<str>) {
-        println!("{} {}", "✓".green().bold(), msg.as_ref());
+        Self::chatter(format!("{} {}", "✓".green().bold(), msg.as_ref()));
     }
 
     /// Print an error message with a red X to stderr.
     ///
     /// Example: `✗ Failed to install package`
     pub fn error(msg: impl AsRef<str>) {
-        eprintln!("{} {}", "✗".red().bold(), msg.as_ref().red());
+        Self::emit(format!("{} {}", "✗".red().bold(), msg.as_ref().red()), true);
     }
 
     /// Print a warning message with a yellow warning symbol.
     ///
     /// Example: `⚠ Package already exists`
     pub fn warning(msg: impl AsRef<str>) {
-        println!("{} {}", "⚠".yellow(), msg.as_ref());
+        Self::emit(format!("{} {}", "⚠".yellow(), msg.as_ref()), false);
     }
 
     /// Print an info/status message with a cyan arrow.
     ///
     /// Example: `→ Checking manifest...`
     pub fn info(msg: impl AsRef<str>) {
-        println!("{} {}", "→".cyan(), msg.as_ref().dimmed());
+        Self::chatter(format!("{} {}", "→".cyan(), msg.as_ref().dimmed()));
     }
 
     /// Print a step message (for multi-step operations).
     ///
     /// Example: `• Processing flatpaks`
     pub fn step(msg: impl AsRef<str>) {
-        println!("  {} {}", "•".cyan(), msg.as_ref());
+        Self::chatter(format!("  {} {}", "•".cyan(), msg.as_ref()));
     }
 
     /// Print a header/section title.
     ///
     /// Example: `=== Development Toolbox Status ===`
     pub fn header(msg: impl AsRef<str>) {
-        println!("\n{}\n", msg.as_ref().bold().cyan());
+        Self::chatter(format!("\n{}\n", msg.as_ref().bold().cyan()));
     }
 
     /// Print a subheader for sections within output.
     ///
     /// Example: `PACKAGES:`
     pub fn subheader(msg: impl AsRef<str>) {
-        println!("{}", msg.as_ref().bold());
+        Self::chatter(format!("{}", msg.as_ref().bold()));
     }
 
     /// Print an item in a list (indented).
     ///
     /// Example: `  gcc`
     pub fn list_item(msg: impl AsRef<str>) {
-        println!("  {}", msg.as_ref());
+        Self::emit(format!("  {}", msg.as_ref()), false);
     }
 
     /// Print a key-value pair with alignment.
     ///
     /// Example: `  Source:        user`
     pub fn kv(key: impl AsRef<str>, value: impl AsRef<str>) {
-        println!(
-            "  {:<14} {}",
-            format!("{}:", key.as_ref()).cyan(),
-            value.as_ref()
+        Self::emit(
+            format!(
+                "  {:<14} {}",
+                format!("{}:", key.as_ref()).cyan(),
+                value.as_ref()
+            ),
+            false,
         );
     }
 
@@ -100,21 +243,26 @@ impl Output {
     ///
     /// Example: `  → Run: gh auth login`
     pub fn hint(msg: impl AsRef<str>) {
-        println!("  {} {}", "→".cyan(), msg.as_ref());
+        Self::chatter(format!("  {} {}", "→".cyan(), msg.as_ref()));
     }
 
     /// Print a dry-run message.
     ///
+    /// Dry-run output is what the user asked for, so `--quiet` keeps it.
+    ///
     /// Example: `[dry-run] Would install: gcc`
     pub fn dry_run(msg: impl AsRef<str>) {
-        println!("{} {}", "[dry-run]".dimmed(), msg.as_ref().dimmed());
+        Self::emit(
+            format!("{} {}", "[dry-run]".dimmed(), msg.as_ref().dimmed()),
+            false,
+        );
     }
 
     /// Print the running command (for transparency).
     ///
     /// Example: `Running: rpm-ostree install gcc`
     pub fn running(cmd: impl AsRef<str>) {
-        println!("{} {}", "Running:".dimmed(), cmd.as_ref().dimmed());
+        Self::chatter(format!("{} {}", "Running:".dimmed(), cmd.as_ref().dimmed()));
     }
 
     /// Create a spinner for long-running operations.
@@ -129,6 +277,9 @@ impl Output {
     /// spinner.finish_success("Installed 3 packages");
     /// ```
     pub fn spinner(msg: impl Into<Cow<'static, str>>) -> Spinner {
+        if Self::is_quiet() {
+            return Spinner(ProgressBar::hidden());
+        }
         let pb = ProgressBar::new_spinner();
         pb.set_style(
             ProgressStyle::default_spinner()
@@ -154,6 +305,9 @@ impl Output {
     /// progress.finish_success("Installed all packages");
     /// ```
     pub fn progress(total: u64, msg: impl Into<Cow<'static, str>>) -> Progress {
+        if Self::is_quiet() {
+            return Progress(ProgressBar::hidden());
+        }
         let pb = ProgressBar::new(total);
         pb.set_style(
             ProgressStyle::default_bar()
@@ -167,12 +321,12 @@ impl Output {
 
     /// Print a separator line.
     pub fn separator() {
-        println!("{}", "-".repeat(50).dimmed());
+        Self::chatter(format!("{}", "-".repeat(50).dimmed()));
     }
 
     /// Print a blank line.
     pub fn blank() {
-        println!();
+        Self::chatter(String::new());
     }
 }
 
@@ -270,6 +424,85 @@ mod tests {
         Output::blank();
     }
 
+    #[test]
+    fn test_quiet_keeps_errors_warnings_and_data() {
+        let print_all = || {
+            Output::header("Status");
+            Output::subheader("PACKAGES:");
+            Output::list_item("gcc");
+            Output::kv("Source", "user");
+            Output::info("Checking manifest...");
+            Output::hint("Run: bkt apply");
+            Output::success("Installed 3 packages");
+            Output::warning("Package already exists");
+            Output::error("Failed to connect");
+            Output::dry_run("Would install: gcc");
+            Output::separator();
+        };
+
+        let normal = Output::capture(Verbosity::Normal, print_all);
+        for text in [
+            "Status",
+            "PACKAGES:",
+            "gcc",
+            "Checking manifest...",
+            "Run: bkt apply",
+            "Installed 3 packages",
+            "Package already exists",
+            "Failed to connect",
+            "Would install: gcc",
+            "-----",
+        ] {
+            assert!(normal.contains(text), "missing {text:?} in {normal}");
+        }
+
+        let quiet = Output::capture(Verbosity::Quiet, print_all);
+        for text in [
+            "gcc",
+            "user",
+            "Package already exists",
+            "Failed to connect",
+            "Would install: gcc",
+        ] {
+            assert!(quiet.contains(text), "missing {text:?} in {quiet}");
+        }
+        for text in [
+            "Status",
+            "PACKAGES:",
+            "Checking manifest...",
+            "Run: bkt apply",
+            "Installed 3 packages",
+            "-----",
+        ] {
+            assert!(!quiet.contains(text), "unexpected {text:?} in {quiet}");
+        }
+
+        assert_eq!(Output::capture(Verbosity::Verbose, print_all), normal);
+    }
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 3), Verbosity::Trace);
+        assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+
+        assert_eq!(Verbosity::Normal.log_directive(), None);
+        assert_eq!(Verbosity::Verbose.log_directive(), Some("bkt=debug"));
+
+        for verbosity in [
+            Verbosity::Quiet,
+            Verbosity::Normal,
+            Verbosity::Verbose,
+            Verbosity::Trace,
+        ] {
+            assert_eq!(Verbosity::from_u8(verbosity as u8), verbosity);
+            let mut seen = None;
+            Output::capture(verbosity, || seen = Some(Output::verbosity()));
+            assert_eq!(seen, Some(verbosity));
+        }
+    }
+
     #[test]
     fn test_spinner_lifecycle() {
         let spinner = Output::spinner("Testing...");
@@ -9,11 +9,13 @@
 
 use crate::cli::Cli;
 use crate::command_runner::{CommandRunner, RealCommandRunner};
+use crate::connectivity::Connectivity;
 use crate::context::{
     CommandDomain, ExecutionContext, PrMode, resolve_context, validate_context_for_domain,
 };
-use crate::pr::{GitHubBackend, PrBackend, PrChange};
-use anyhow::Result;
+use crate::output::{Output, OutputFormat};
+use crate::pr::{GitHubBackend, PrBackend, PrBatch, PrChange, PrStage};
+use anyhow::{Result, bail};
 use std::sync::Arc;
 
 /// Execution plan for a bkt command.
@@ -29,6 +31,16 @@ pub struct ExecutionPlan {
     pub dry_run: bool,
     /// Whether to skip preflight checks
     pub skip_preflight: bool,
+    /// Whether to branch PRs from the checkout without syncing upstream
+    pub no_sync: bool,
+    /// Where to stage PR changes instead of opening them (--stage)
+    pub stage: Option<PrStage>,
+    /// Requested output format for plans and reports
+    pub format: OutputFormat,
+    /// Whether the network is reachable (--offline, or probed on first use)
+    connectivity: Connectivity,
+    /// Where PRs that couldn't be opened offline wait for `bkt pr flush`
+    deferred: PrStage,
     /// Backend for PR creation (enables testing)
     pr_backend: Arc<dyn PrBackend>,
     /// Backend for external command execution (enables testing)
@@ -53,6 +65,15 @@ impl ExecutionPlan {
             pr_mode,
             dry_run: cli.dry_run,
             skip_preflight: cli.skip_preflight,
+            no_sync: cli.no_sync,
+            stage: cli.stage.then(PrStage::open),
+            format: cli.format.unwrap_or_default(),
+            connectivity: if cli.offline {
+                Connectivity::Offline
+            } else {
+                Connectivity::detect()
+            },
+            deferred: PrStage::deferred(),
             pr_backend: Arc::new(GitHubBackend::new(command_runner.clone())),
             command_runner,
         }
@@ -65,6 +86,11 @@ impl ExecutionPlan {
             pr_mode: self.pr_mode,
             dry_run,
             skip_preflight: self.skip_preflight,
+            no_sync: self.no_sync,
+            stage: self.stage.clone(),
+            format: self.format,
+            connectivity: self.connectivity.clone(),
+            deferred: self.deferred.clone(),
             pr_backend: self.pr_backend.clone(),
             command_runner: self.command_runner.clone(),
         }
@@ -80,6 +106,11 @@ impl ExecutionPlan {
         self.command_runner.clone()
     }
 
+    /// Check if plans and reports should be printed as JSON.
+    pub fn json_output(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
     /// Check if this plan allows local execution.
     pub fn should_execute_locally(&self) -> bool {
         !self.dry_run && self.pr_mode.should_execute_locally()
@@ -88,13 +119,14 @@ impl ExecutionPlan {
     /// Check if this plan should create a PR.
     ///
     /// PRs are not created for Dev context since toolbox packages are personal
-    /// and not part of the system image.
+    /// and not part of the system image. With `--stage`, the change is staged
+    /// for a later `bkt pr submit` instead.
     pub fn should_create_pr(&self) -> bool {
         // Toolbox changes are personal, not part of system image
         if self.context == ExecutionContext::Dev {
             return false;
         }
-        !self.dry_run && self.pr_mode.should_create_pr()
+        !self.dry_run && (self.stage.is_some() || self.pr_mode.should_create_pr())
     }
 
     /// Check if this plan should update manifests.
@@ -104,6 +136,29 @@ impl ExecutionPlan {
         !self.dry_run && self.pr_mode != PrMode::PrOnly
     }
 
+    /// Check if the network is unreachable, or `--offline` was given.
+    ///
+    /// Without `--offline` this probes the network the first time it's
+    /// called, so only ask when about to need it.
+    pub fn is_offline(&self) -> bool {
+        self.connectivity.is_offline()
+    }
+
+    /// Check if a network-backed `check` should be skipped because we're
+    /// offline, warning that it was.
+    pub fn skip_offline(&self, check: &str) -> bool {
+        let offline = self.is_offline();
+        if offline {
+            Output::warning(format!("Offline: skipping {}", check));
+        }
+        offline
+    }
+
+    /// PRs deferred while offline, waiting for `bkt pr flush`.
+    pub fn deferred_prs(&self) -> &PrStage {
+        &self.deferred
+    }
+
     /// Validate that the given domain is allowed for this plan's context.
     pub fn validate_domain(&self, domain: CommandDomain) -> Result<()> {
         validate_context_for_domain(domain, self.context)
@@ -127,16 +182,73 @@ impl ExecutionPlan {
                 name: name.to_string(),
                 manifest_file: manifest_file.to_string(),
             };
-            self.pr_backend
-                .create_pr(&change, manifest_content, self.skip_preflight)?;
+            if let Some(stage) = &self.stage {
+                self.hold_pr(stage, &change, manifest_content, "staged", "bkt pr submit")?;
+                Output::success(format!(
+                    "Staged PR change: {} {} {} (submit with `bkt pr submit`)",
+                    action, manifest_type, name
+                ));
+            } else if self.is_offline() {
+                self.hold_pr(
+                    &self.deferred,
+                    &change,
+                    manifest_content,
+                    "deferred",
+                    "bkt pr flush",
+                )?;
+                Output::warning(format!(
+                    "Offline: deferred PR for {} {} {} (open it with `bkt pr flush` once online)",
+                    action, manifest_type, name
+                ));
+            } else {
+                self.pr_backend.create_pr(
+                    &change,
+                    manifest_content,
+                    self.skip_preflight,
+                    self.no_sync,
+                )?;
+            }
         } else if self.dry_run {
+            let verb = if self.stage.is_some() {
+                "stage"
+            } else {
+                "create"
+            };
             println!(
-                "[dry-run] Would create PR: {} {} {}",
-                action, manifest_type, name
+                "[dry-run] Would {} PR: {} {} {}",
+                verb, action, manifest_type, name
             );
         }
         Ok(())
     }
+
+    /// Record `change` in `store` instead of opening its PR now.
+    fn hold_pr(
+        &self,
+        store: &PrStage,
+        change: &PrChange,
+        manifest_content: &str,
+        held_as: &str,
+        submit_with: &str,
+    ) -> Result<()> {
+        // Without a local manifest update, the content was built from the
+        // checkout and would drop what's already held for it.
+        if !self.should_update_manifest() && store.touches(&change.manifest_file)? {
+            bail!(
+                "{} already has {} changes; run `{}` first or retry without --pr-only",
+                change.manifest_file,
+                held_as,
+                submit_with
+            );
+        }
+        store.stage(change, manifest_content)
+    }
+
+    /// Open one PR for a batch of staged changes.
+    pub fn submit_batch(&self, batch: &PrBatch) -> Result<()> {
+        self.pr_backend
+            .create_batch_pr(batch, self.skip_preflight, self.no_sync)
+    }
 }
 
 impl Default for ExecutionPlan {
@@ -147,6 +259,11 @@ impl Default for ExecutionPlan {
             pr_mode: PrMode::Default,
             dry_run: false,
             skip_preflight: false,
+            no_sync: false,
+            stage: None,
+            format: OutputFormat::default(),
+            connectivity: Connectivity::Online,
+            deferred: PrStage::deferred(),
             pr_backend: Arc::new(GitHubBackend::new(command_runner.clone())),
             command_runner,
         }
@@ -160,6 +277,11 @@ pub struct ExecutionPlanBuilder {
     pr_mode: Option<PrMode>,
     dry_run: bool,
     skip_preflight: bool,
+    no_sync: bool,
+    stage: Option<PrStage>,
+    format: OutputFormat,
+    offline: bool,
+    deferred: Option<PrStage>,
     pr_backend: Option<Arc<dyn PrBackend>>,
     command_runner: Option<Arc<dyn CommandRunner>>,
 }
@@ -189,6 +311,31 @@ impl ExecutionPlanBuilder {
         self
     }
 
+    pub fn no_sync(mut self, no_sync: bool) -> Self {
+        self.no_sync = no_sync;
+        self
+    }
+
+    pub fn stage(mut self, stage: PrStage) -> Self {
+        self.stage = Some(stage);
+        self
+    }
+
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn deferred(mut self, deferred: PrStage) -> Self {
+        self.deferred = Some(deferred);
+        self
+    }
+
     pub fn pr_backend(mut self, backend: Arc<dyn PrBackend>) -> Self {
         self.pr_backend = Some(backend);
         self
@@ -212,6 +359,15 @@ impl ExecutionPlanBuilder {
             pr_mode: self.pr_mode.unwrap_or(PrMode::Default),
             dry_run: self.dry_run,
             skip_preflight: self.skip_preflight,
+            no_sync: self.no_sync,
+            stage: self.stage,
+            format: self.format,
+            connectivity: if self.offline {
+                Connectivity::Offline
+            } else {
+                Connectivity::Online
+            },
+            deferred: self.deferred.unwrap_or_else(PrStage::deferred),
             pr_backend,
             command_runner,
         }
@@ -309,4 +465,79 @@ mod tests {
             .build();
         assert!(host_plan.should_create_pr());
     }
+
+    #[test]
+    fn test_stage_defers_pr_creation() {
+        use crate::pr::testing::MockPrBackend;
+
+        let temp = tempfile::tempdir().unwrap();
+        let backend = Arc::new(MockPrBackend::new());
+        let plan = ExecutionPlanBuilder::new()
+            .context(ExecutionContext::Host)
+            .stage(PrStage::at(temp.path()))
+            .pr_backend(backend.clone())
+            .build();
+        assert!(plan.should_execute_locally());
+        assert!(plan.should_update_manifest());
+        assert!(plan.should_create_pr());
+
+        plan.maybe_create_pr("shim", "add", "nmcli", "host-shims.json", "{}")
+            .unwrap();
+        plan.maybe_create_pr("gsetting", "set", "x", "gsettings.json", "{}")
+            .unwrap();
+        backend.assert_no_pr();
+
+        let batch = PrStage::at(temp.path()).batch().unwrap();
+        assert_eq!(batch.changes.len(), 2);
+        plan.submit_batch(&batch).unwrap();
+        assert_eq!(backend.batch_calls().len(), 1);
+    }
+
+    #[test]
+    fn test_offline_defers_pr_creation() {
+        use crate::pr::testing::MockPrBackend;
+
+        let temp = tempfile::tempdir().unwrap();
+        let backend = Arc::new(MockPrBackend::new());
+        let plan = ExecutionPlanBuilder::new()
+            .context(ExecutionContext::Host)
+            .pr_mode(PrMode::Pr)
+            .offline(true)
+            .deferred(PrStage::at(temp.path()))
+            .pr_backend(backend.clone())
+            .build();
+        assert!(plan.is_offline());
+        assert!(plan.skip_offline("package validation"));
+        // Local work still happens; only the PR waits
+        assert!(plan.should_execute_locally());
+        assert!(plan.should_update_manifest());
+
+        plan.maybe_create_pr("shim", "add", "nmcli", "host-shims.json", "{}")
+            .unwrap();
+        backend.assert_no_pr();
+        let deferred = plan.deferred_prs().batches_by_file().unwrap();
+        assert_eq!(deferred.len(), 1);
+        assert_eq!(deferred[0].changes[0].name, "nmcli");
+
+        let online = ExecutionPlanBuilder::new().build();
+        assert!(!online.is_offline());
+        assert!(!online.skip_offline("package validation"));
+    }
+
+    #[test]
+    fn test_pr_only_stage_refuses_to_overwrite_staged_manifest() {
+        let temp = tempfile::tempdir().unwrap();
+        let plan = ExecutionPlanBuilder::new()
+            .context(ExecutionContext::Host)
+            .pr_mode(PrMode::PrOnly)
+            .stage(PrStage::at(temp.path()))
+            .build();
+
+        plan.maybe_create_pr("shim", "add", "a", "host-shims.json", "{}")
+            .unwrap();
+        let err = plan
+            .maybe_create_pr("shim", "add", "b", "host-shims.json", "{}")
+            .unwrap_err();
+        assert!(err.to_string().contains("already has staged changes"));
+    }
 }
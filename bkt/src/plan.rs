@@ -13,7 +13,7 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! let plan = ShimSyncCommand.plan(&ctx)?;
+//! let plan = ShimSyncCommand { keep_unmanaged: false }.plan(&ctx)?;
 //!
 //! // Always show what will happen
 //! println!("{}", plan.describe());
@@ -24,13 +24,19 @@
 //!     println!("{}", report);
 //! }
 //! ```
+//!
+//! Commands print through [`print_summary`] and [`print_report`] so that
+//! `--format json` emits the serialized [`PlanSummary`] for dry runs and the
+//! serialized [`ExecutionReport`] for real runs.
 
 use anyhow::Result;
 use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::path::PathBuf;
 
 use crate::effects::Executor;
+use crate::output::OutputFormat;
 use crate::pipeline::ExecutionPlan;
 
 // ============================================================================
@@ -232,7 +238,8 @@ impl ExecuteContext {
 // ============================================================================
 
 /// A verb describing an operation type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Verb {
     /// Install something (flatpak, package)
     Install,
@@ -295,13 +302,17 @@ impl fmt::Display for Verb {
 }
 
 /// A single operation in a plan.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
+    /// The subsystem that owns this operation (e.g., "flatpak").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subsystem: Option<String>,
     /// The verb/action type.
     pub verb: Verb,
     /// The target of the operation (e.g., "flatpak:org.gnome.Boxes").
     pub target: String,
     /// Optional additional details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
 }
 
@@ -309,6 +320,7 @@ impl Operation {
     /// Create a new operation.
     pub fn new(verb: Verb, target: impl Into<String>) -> Self {
         Self {
+            subsystem: None,
             verb,
             target: target.into(),
             details: None,
@@ -318,11 +330,19 @@ impl Operation {
     /// Create a new operation with details.
     pub fn with_details(verb: Verb, target: impl Into<String>, details: impl Into<String>) -> Self {
         Self {
+            subsystem: None,
             verb,
             target: target.into(),
             details: Some(details.into()),
         }
     }
+
+    /// Attribute this operation to a subsystem, unless it already is.
+    fn tag_subsystem(&mut self, subsystem: &str) {
+        if self.subsystem.is_none() {
+            self.subsystem = Some(subsystem.to_string());
+        }
+    }
 }
 
 impl fmt::Display for Operation {
@@ -340,7 +360,7 @@ impl fmt::Display for Operation {
 // ============================================================================
 
 /// A warning message generated during planning.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanWarning {
     /// The target this warning relates to (e.g., "flatpak:com.example.App").
     pub target: String,
@@ -365,7 +385,7 @@ impl fmt::Display for PlanWarning {
 }
 
 /// Structured description of a plan for display.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanSummary {
     /// Brief summary of the plan.
     pub summary: String,
@@ -422,6 +442,14 @@ impl PlanSummary {
     pub fn has_warnings(&self) -> bool {
         !self.warnings.is_empty()
     }
+
+    /// Attribute every operation to `subsystem` (for JSON output).
+    pub fn with_subsystem(mut self, subsystem: &str) -> Self {
+        for op in &mut self.operations {
+            op.tag_subsystem(subsystem);
+        }
+        self
+    }
 }
 
 impl fmt::Display for PlanSummary {
@@ -459,13 +487,14 @@ impl fmt::Display for PlanSummary {
 // ============================================================================
 
 /// Result of a single operation execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OperationResult {
     /// The operation that was attempted.
     pub operation: Operation,
     /// Whether it succeeded.
     pub success: bool,
     /// Optional error message if failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
@@ -490,7 +519,7 @@ impl OperationResult {
 }
 
 /// Report of plan execution.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionReport {
     /// Results of each operation.
     pub results: Vec<OperationResult>,
@@ -597,6 +626,52 @@ impl ExecutionReport {
     pub fn has_failures(&self) -> bool {
         self.results.iter().any(|r| !r.success)
     }
+
+    /// Attribute every result to `subsystem` (for JSON output).
+    pub fn with_subsystem(mut self, subsystem: &str) -> Self {
+        for result in &mut self.results {
+            result.operation.tag_subsystem(subsystem);
+        }
+        self
+    }
+
+    /// Per-subsystem success/failure counts, in the order subsystems first
+    /// appear. Untagged results are counted under "other".
+    pub fn subsystem_tallies(&self) -> Vec<SubsystemTally> {
+        let mut tallies: Vec<SubsystemTally> = Vec::new();
+        for result in &self.results {
+            let subsystem = result.operation.subsystem.as_deref().unwrap_or("other");
+            let index = match tallies.iter().position(|t| t.subsystem == subsystem) {
+                Some(index) => index,
+                None => {
+                    tallies.push(SubsystemTally {
+                        subsystem: subsystem.to_string(),
+                        ..Default::default()
+                    });
+                    tallies.len() - 1
+                }
+            };
+            let tally = &mut tallies[index];
+            if result.success {
+                tally.succeeded += 1;
+            } else {
+                tally.failed += 1;
+                if tally.first_error.is_none() {
+                    tally.first_error = result.error.clone();
+                }
+            }
+        }
+        tallies
+    }
+}
+
+/// How one subsystem's operations went in an [`ExecutionReport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubsystemTally {
+    pub subsystem: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub first_error: Option<String>,
 }
 
 impl fmt::Display for ExecutionReport {
@@ -635,6 +710,42 @@ impl fmt::Display for ExecutionReport {
     }
 }
 
+// ============================================================================
+// Printing
+// ============================================================================
+
+/// Print a plan summary in the requested output format.
+///
+/// With `--format json` the summary is only printed for previews (dry runs);
+/// when the plan goes on to execute, the report is the JSON output instead.
+pub fn print_summary(
+    summary: &PlanSummary,
+    exec_plan: &ExecutionPlan,
+    preview: bool,
+) -> Result<()> {
+    match exec_plan.format {
+        OutputFormat::Json if preview => {
+            println!("{}", serde_json::to_string_pretty(summary)?);
+        }
+        OutputFormat::Json => {}
+        OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html => {
+            print!("{}", summary)
+        }
+    }
+    Ok(())
+}
+
+/// Print an execution report in the requested output format.
+pub fn print_report(report: &ExecutionReport, exec_plan: &ExecutionPlan) -> Result<()> {
+    match exec_plan.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(report)?),
+        OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html => {
+            print!("{}", report)
+        }
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Composite Plans
 // ============================================================================
@@ -645,8 +756,8 @@ impl fmt::Display for ExecutionReport {
 pub struct CompositePlan {
     /// The name of this composite plan (e.g., "Apply").
     name: String,
-    /// The sub-plans to execute.
-    plans: Vec<Box<dyn DynPlan>>,
+    /// The sub-plans to execute, with the subsystem each belongs to.
+    plans: Vec<(Option<String>, Box<dyn DynPlan>)>,
 }
 
 impl CompositePlan {
@@ -661,8 +772,94 @@ impl CompositePlan {
     /// Add a plan to this composite.
     pub fn add<P: Plan + 'static>(&mut self, plan: P) {
         if !plan.is_empty() {
-            self.plans.push(Box::new(plan));
+            self.plans.push((None, Box::new(plan)));
+        }
+    }
+
+    /// Add a subsystem's plan, attributing its operations to `subsystem`.
+    pub fn add_for<P: Plan + 'static>(&mut self, subsystem: impl Into<String>, plan: P) {
+        if !plan.is_empty() {
+            self.plans.push((Some(subsystem.into()), Box::new(plan)));
+        }
+    }
+
+    /// Record that `subsystem` couldn't be planned. It shows up as a warning
+    /// in the summary and as a failure when executed.
+    pub fn add_failed(&mut self, subsystem: impl Into<String>, error: &anyhow::Error) {
+        let subsystem = subsystem.into();
+        self.plans.push((
+            Some(subsystem.clone()),
+            Box::new(FailedPlan {
+                subsystem,
+                error: format!("{:#}", error),
+            }),
+        ));
+    }
+
+    /// Execute each sub-plan on its own, turning a sub-plan that errors out
+    /// into a failed operation for its subsystem.
+    ///
+    /// With `continue_on_error`, every sub-plan runs. Otherwise execution
+    /// stops after the first sub-plan that errors, and the subsystems that
+    /// didn't run are returned alongside the report.
+    pub fn execute_each(
+        self,
+        ctx: &mut ExecuteContext,
+        continue_on_error: bool,
+    ) -> (ExecutionReport, Vec<String>) {
+        let mut report = ExecutionReport::new();
+        let mut plans = self.plans.into_iter();
+
+        for (subsystem, plan) in plans.by_ref() {
+            let label = subsystem.as_deref().unwrap_or("other").to_string();
+            let (sub_report, errored) = match plan.execute_dyn(ctx) {
+                Ok(sub_report) => (sub_report, false),
+                Err(e) => {
+                    let mut failed = ExecutionReport::new();
+                    failed.record_failure_and_notify(
+                        ctx,
+                        Verb::Configure,
+                        label.clone(),
+                        format!("{:#}", e),
+                    );
+                    (failed, true)
+                }
+            };
+            report.merge(sub_report.with_subsystem(&label));
+            if errored && !continue_on_error {
+                break;
+            }
         }
+
+        let skipped = plans
+            .map(|(subsystem, _)| subsystem.unwrap_or_else(|| "other".to_string()))
+            .collect();
+        (report, skipped)
+    }
+}
+
+/// Stand-in for a subsystem whose planning failed.
+struct FailedPlan {
+    subsystem: String,
+    error: String,
+}
+
+impl Plan for FailedPlan {
+    fn describe(&self) -> PlanSummary {
+        let mut summary = PlanSummary::new(format!("{} (planning failed)", self.subsystem));
+        summary.add_warning(PlanWarning::new(
+            &self.subsystem,
+            format!("planning failed: {}", self.error),
+        ));
+        summary
+    }
+
+    fn execute(self, _ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
+        anyhow::bail!("planning failed: {}", self.error)
+    }
+
+    fn is_empty(&self) -> bool {
+        false
     }
 }
 
@@ -670,8 +867,11 @@ impl Plan for CompositePlan {
     fn describe(&self) -> PlanSummary {
         let mut summary = PlanSummary::new(format!("{} Plan", self.name));
 
-        for plan in &self.plans {
-            let sub = plan.describe_dyn();
+        for (subsystem, plan) in &self.plans {
+            let mut sub = plan.describe_dyn();
+            if let Some(subsystem) = subsystem {
+                sub = sub.with_subsystem(subsystem);
+            }
             summary.add_operations(sub.operations);
             summary.add_warnings(sub.warnings);
         }
@@ -682,8 +882,11 @@ impl Plan for CompositePlan {
     fn execute(self, ctx: &mut ExecuteContext) -> Result<ExecutionReport> {
         let mut report = ExecutionReport::new();
 
-        for plan in self.plans {
-            let sub_report = plan.execute_dyn(ctx)?;
+        for (subsystem, plan) in self.plans {
+            let mut sub_report = plan.execute_dyn(ctx)?;
+            if let Some(subsystem) = subsystem {
+                sub_report = sub_report.with_subsystem(&subsystem);
+            }
             report.merge(sub_report);
         }
 
@@ -691,7 +894,7 @@ impl Plan for CompositePlan {
     }
 
     fn is_empty(&self) -> bool {
-        self.plans.is_empty() || self.plans.iter().all(|p| p.is_empty_dyn())
+        self.plans.is_empty() || self.plans.iter().all(|(_, p)| p.is_empty_dyn())
     }
 }
 
@@ -864,6 +1067,53 @@ mod tests {
         assert_eq!(summary.action_count(), 3);
     }
 
+    #[test]
+    fn test_plan_summary_json_includes_subsystem_verb_and_target() {
+        let mut composite = CompositePlan::new("Apply");
+        composite.add_for(
+            "flatpak",
+            TestPlan::new(vec![Operation::with_details(
+                Verb::Install,
+                "flatpak:org.gnome.Boxes",
+                "flathub",
+            )]),
+        );
+        composite.add(TestPlan::new(vec![Operation::new(Verb::Create, "a")]));
+
+        let json = serde_json::to_value(composite.describe()).unwrap();
+        assert_eq!(
+            json["operations"][0],
+            serde_json::json!({
+                "subsystem": "flatpak",
+                "verb": "install",
+                "target": "flatpak:org.gnome.Boxes",
+                "details": "flathub",
+            })
+        );
+        assert_eq!(
+            json["operations"][1],
+            serde_json::json!({"verb": "create", "target": "a"})
+        );
+    }
+
+    #[test]
+    fn test_execution_report_json_round_trip() {
+        let mut report = ExecutionReport::new();
+        report.record_success(Verb::Install, "package:ripgrep");
+        report.record_failure(Verb::Install, "package:nope", "No match for argument");
+        let report = report.with_subsystem("dev");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["results"][0]["success"], true);
+        assert!(json["results"][0].get("error").is_none());
+        assert_eq!(json["results"][1]["error"], "No match for argument");
+        assert_eq!(json["results"][1]["operation"]["subsystem"], "dev");
+
+        let parsed: ExecutionReport = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.failure_count(), 1);
+        assert_eq!(parsed.results[0].operation.verb, Verb::Install);
+    }
+
     #[test]
     fn test_composite_plan_execute_merges_reports() {
         let mut composite = CompositePlan::new("Test");
@@ -876,4 +1126,86 @@ mod tests {
         // Both sub-plans executed (though TestPlan returns empty reports)
         assert!(report.all_succeeded());
     }
+
+    fn test_plan_with(execute_fn: fn() -> Result<ExecutionReport>) -> TestPlan {
+        TestPlan {
+            operations: vec![Operation::new(Verb::Install, "x")],
+            execute_fn,
+        }
+    }
+
+    fn flatpak_plan() -> TestPlan {
+        test_plan_with(|| anyhow::bail!("flathub: could not resolve host"))
+    }
+
+    fn shim_plan() -> TestPlan {
+        test_plan_with(|| {
+            let mut report = ExecutionReport::new();
+            report.record_success(Verb::Create, "shim:rg");
+            report.record_failure(Verb::Create, "shim:fd", "exists");
+            report.record_failure(Verb::Create, "shim:bat", "denied");
+            Ok(report)
+        })
+    }
+
+    #[test]
+    fn test_execute_each_continues_past_failing_subsystem() {
+        let mut composite = CompositePlan::new("Apply");
+        composite.add_for("flatpak", flatpak_plan());
+        composite.add_for("shim", shim_plan());
+        composite.add_failed("extension", &anyhow::anyhow!("gnome-extensions not found"));
+
+        let mut ctx = ExecuteContext::new(ExecutionPlan::default());
+        let (report, skipped) = composite.execute_each(&mut ctx, true);
+
+        assert!(skipped.is_empty());
+        assert_eq!(
+            report.subsystem_tallies(),
+            vec![
+                SubsystemTally {
+                    subsystem: "flatpak".to_string(),
+                    succeeded: 0,
+                    failed: 1,
+                    first_error: Some("flathub: could not resolve host".to_string()),
+                },
+                SubsystemTally {
+                    subsystem: "shim".to_string(),
+                    succeeded: 1,
+                    failed: 2,
+                    first_error: Some("exists".to_string()),
+                },
+                SubsystemTally {
+                    subsystem: "extension".to_string(),
+                    succeeded: 0,
+                    failed: 1,
+                    first_error: Some("planning failed: gnome-extensions not found".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_each_stops_at_failing_subsystem_by_default() {
+        let mut composite = CompositePlan::new("Apply");
+        composite.add_for("flatpak", flatpak_plan());
+        composite.add_for("shim", shim_plan());
+
+        let mut ctx = ExecuteContext::new(ExecutionPlan::default());
+        let (report, skipped) = composite.execute_each(&mut ctx, false);
+
+        assert_eq!(report.failure_count(), 1);
+        assert_eq!(skipped, vec!["shim"]);
+    }
+
+    #[test]
+    fn test_failed_plan_describes_as_warning() {
+        let mut composite = CompositePlan::new("Apply");
+        composite.add_failed("flatpak", &anyhow::anyhow!("remote unreachable"));
+
+        let summary = composite.describe();
+        assert!(!composite.is_empty());
+        assert_eq!(summary.action_count(), 0);
+        assert_eq!(summary.warnings[0].target, "flatpak");
+        assert!(summary.warnings[0].message.contains("remote unreachable"));
+    }
 }
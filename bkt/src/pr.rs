@@ -17,11 +17,20 @@
 //! - Network: Failed to clone/push to remote
 //! - Auth: GitHub authentication issues
 //! - Git state: Conflicts, dirty working directory
+//!
+//! Before branching, the checkout is synced with the remote (see
+//! [`sync_repo`]) so PRs are based on the current upstream tip. `--no-sync`
+//! skips that.
+//!
+//! With `--stage`, changes are recorded in a [`PrStage`] instead and
+//! `bkt pr submit` opens a single PR for all of them (see [`PrBatch`]).
 
 use crate::command_runner::{CommandOptions, CommandRunner, RealCommandRunner};
 use crate::repo::{RepoConfig, find_repo_path};
 use anyhow::{Context, Result, bail};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Trait for PR creation operations - enables testing without git/gh.
@@ -32,7 +41,11 @@ pub trait PrBackend: Send + Sync {
         change: &PrChange,
         manifest_content: &str,
         skip_preflight: bool,
+        no_sync: bool,
     ) -> Result<()>;
+
+    /// Create one PR covering every change in a batch.
+    fn create_batch_pr(&self, batch: &PrBatch, skip_preflight: bool, no_sync: bool) -> Result<()>;
 }
 
 /// Production backend that uses real git/gh commands.
@@ -60,12 +73,23 @@ impl PrBackend for GitHubBackend {
         change: &PrChange,
         manifest_content: &str,
         skip_preflight: bool,
+        no_sync: bool,
     ) -> Result<()> {
         run_pr_workflow(
             &*self.command_runner,
             change,
             manifest_content,
             skip_preflight,
+            no_sync,
+        )
+    }
+
+    fn create_batch_pr(&self, batch: &PrBatch, skip_preflight: bool, no_sync: bool) -> Result<()> {
+        run_commit_workflow(
+            &*self.command_runner,
+            &batch.commit()?,
+            skip_preflight,
+            no_sync,
         )
     }
 }
@@ -312,7 +336,7 @@ pub fn ensure_preflight(runner: &dyn CommandRunner, skip: bool) -> Result<()> {
 }
 
 /// Information about a manifest change for PR creation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrChange {
     pub manifest_type: String, // "shim", "flatpak", "extension", etc.
     pub action: String,        // "add", "remove"
@@ -320,24 +344,31 @@ pub struct PrChange {
     pub manifest_file: String, // e.g., "host-shims.json"
 }
 
+/// Seconds since the epoch, used to keep branch names unique.
+fn branch_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_else(|_| {
+            // Fallback: use process ID for uniqueness
+            std::process::id() as u64
+        })
+}
+
 impl PrChange {
     /// Generate a safe branch name for this change.
     /// All components are sanitized to prevent command injection.
     pub fn branch_name(&self) -> String {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or_else(|_| {
-                // Fallback: use process ID for uniqueness
-                std::process::id() as u64
-            });
         // Sanitize all user-controlled components
         let safe_type = sanitize_for_ref(&self.manifest_type);
         let safe_action = sanitize_for_ref(&self.action);
         let safe_name = sanitize_for_ref(&self.name);
         format!(
             "bkt/{}-{}-{}-{}",
-            safe_type, safe_action, safe_name, timestamp
+            safe_type,
+            safe_action,
+            safe_name,
+            branch_timestamp()
         )
     }
 
@@ -361,15 +392,317 @@ impl PrChange {
              *Created by bkt CLI*",
             self.manifest_type,
             self.action,
-            if self.action == "add" {
-                "Added"
-            } else {
-                "Removed"
+            match self.action.as_str() {
+                "add" => "Added",
+                "swap" => "Swapped",
+                _ => "Removed",
             },
             self.name,
             self.manifest_file
         )
     }
+
+    /// The commit that applies this change as `manifest_content`.
+    pub fn commit(&self, manifest_content: &str) -> PrCommit {
+        PrCommit {
+            branch: self.branch_name(),
+            message: self.commit_message(),
+            title: self.pr_title(),
+            body: self.pr_body(),
+            files: BTreeMap::from([(self.manifest_file.clone(), manifest_content.to_string())]),
+        }
+    }
+}
+
+/// A set of manifest files committed on a fresh branch and opened as one PR.
+#[derive(Debug, Clone)]
+pub struct PrCommit {
+    pub branch: String,
+    pub message: String,
+    pub title: String,
+    pub body: String,
+    /// New content keyed by manifest file (as in [`PrChange::manifest_file`])
+    pub files: BTreeMap<String, String>,
+}
+
+/// Several manifest changes submitted together (`bkt pr submit`).
+#[derive(Debug, Clone, Default)]
+pub struct PrBatch {
+    /// The changes, in the order they were staged
+    pub changes: Vec<PrChange>,
+    /// Latest content of every manifest file the changes touch
+    pub files: BTreeMap<String, String>,
+}
+
+impl PrBatch {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Distinct manifest types in the batch, in first-staged order.
+    fn domains(&self) -> Vec<&str> {
+        let mut domains: Vec<&str> = Vec::new();
+        for change in &self.changes {
+            if !domains.contains(&change.manifest_type.as_str()) {
+                domains.push(&change.manifest_type);
+            }
+        }
+        domains
+    }
+
+    pub fn branch_name(&self) -> String {
+        if let [change] = self.changes.as_slice() {
+            return change.branch_name();
+        }
+        format!(
+            "bkt/batch-{}-changes-{}",
+            self.changes.len(),
+            branch_timestamp()
+        )
+    }
+
+    pub fn commit_message(&self) -> String {
+        let mut message = format!(
+            "feat(manifests): {} changes to {}\n",
+            self.changes.len(),
+            self.domains().join(", ")
+        );
+        for change in &self.changes {
+            message.push_str(&format!(
+                "\n- {} {} {}",
+                change.action, change.manifest_type, change.name
+            ));
+        }
+        message
+    }
+
+    pub fn pr_title(&self) -> String {
+        match self.changes.as_slice() {
+            [change] => change.pr_title(),
+            changes => format!(
+                "Update {} ({} changes)",
+                self.domains().join(", "),
+                changes.len()
+            ),
+        }
+    }
+
+    pub fn pr_body(&self) -> String {
+        let mut body = String::from(
+            "This PR was automatically created by `bkt pr submit`.\n\n\
+             ## Changes\n\n\
+             | Domain | Action | Target | Manifest |\n\
+             |--------|--------|--------|----------|\n",
+        );
+        for change in &self.changes {
+            body.push_str(&format!(
+                "| {} | {} | `{}` | `{}` |\n",
+                change.manifest_type,
+                change.action,
+                change.name,
+                manifest_repo_path(&change.manifest_file).display()
+            ));
+        }
+        body.push_str("\n---\n*Created by bkt CLI*");
+        body
+    }
+
+    /// The commit that applies every staged file at once.
+    pub fn commit(&self) -> Result<PrCommit> {
+        if self.is_empty() {
+            bail!("No staged changes to submit");
+        }
+        Ok(PrCommit {
+            branch: self.branch_name(),
+            message: self.commit_message(),
+            title: self.pr_title(),
+            body: self.pr_body(),
+            files: self.files.clone(),
+        })
+    }
+}
+
+/// Manifest changes staged with `--stage`, waiting for `bkt pr submit`.
+///
+/// Lives in `~/.local/state/bkt/pr-staged/`: `changes.json` lists the staged
+/// changes in order, and `files/` holds the latest content of each manifest
+/// they touch. Staging a manifest again replaces its content, so the last
+/// snapshot wins.
+#[derive(Debug, Clone)]
+pub struct PrStage {
+    dir: PathBuf,
+}
+
+impl PrStage {
+    /// The staging store in the user's state directory.
+    pub fn open() -> Self {
+        Self::at(state_dir().join("pr-staged"))
+    }
+
+    /// Where PRs that couldn't be opened offline wait for `bkt pr flush`.
+    ///
+    /// Kept apart from `--stage` changes: each deferred change was meant
+    /// to be its own PR, not part of the next batch.
+    pub fn deferred() -> Self {
+        Self::at(state_dir().join("pr-deferred"))
+    }
+
+    /// A staging store rooted at `dir`.
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("changes.json")
+    }
+
+    fn file_path(&self, manifest_file: &str) -> Result<PathBuf> {
+        validate_manifest_path(manifest_file)?;
+        Ok(self.dir.join("files").join(manifest_file))
+    }
+
+    /// Staged changes, in the order they were staged.
+    pub fn changes(&self) -> Result<Vec<PrChange>> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Whether any staged change touches `manifest_file`.
+    pub fn touches(&self, manifest_file: &str) -> Result<bool> {
+        Ok(self
+            .changes()?
+            .iter()
+            .any(|change| change.manifest_file == manifest_file))
+    }
+
+    /// Record `change`, with `manifest_content` as its manifest's new content.
+    pub fn stage(&self, change: &PrChange, manifest_content: &str) -> Result<()> {
+        let file_path = self.file_path(&change.manifest_file)?;
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&file_path, manifest_content)
+            .with_context(|| format!("Failed to write {}", file_path.display()))?;
+
+        let mut changes = self.changes()?;
+        if !changes.contains(change) {
+            changes.push(change.clone());
+        }
+        let index = serde_json::to_string_pretty(&changes)?;
+        std::fs::write(self.index_path(), index)
+            .with_context(|| format!("Failed to write {}", self.index_path().display()))?;
+        Ok(())
+    }
+
+    /// Everything staged, ready to submit.
+    pub fn batch(&self) -> Result<PrBatch> {
+        let changes = self.changes()?;
+        let mut files = BTreeMap::new();
+        for change in &changes {
+            if files.contains_key(&change.manifest_file) {
+                continue;
+            }
+            let path = self.file_path(&change.manifest_file)?;
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read staged {}", path.display()))?;
+            files.insert(change.manifest_file.clone(), content);
+        }
+        Ok(PrBatch { changes, files })
+    }
+
+    /// What's staged, split into one batch per manifest file (in first-staged
+    /// order), so each can go out as the PR it would have been on its own.
+    pub fn batches_by_file(&self) -> Result<Vec<PrBatch>> {
+        let mut batch = self.batch()?;
+        let mut batches: Vec<PrBatch> = Vec::new();
+        for change in batch.changes {
+            match batches
+                .iter_mut()
+                .find(|b| b.files.contains_key(&change.manifest_file))
+            {
+                Some(existing) => existing.changes.push(change),
+                None => {
+                    let content = batch
+                        .files
+                        .remove(&change.manifest_file)
+                        .unwrap_or_default();
+                    batches.push(PrBatch {
+                        files: BTreeMap::from([(change.manifest_file.clone(), content)]),
+                        changes: vec![change],
+                    });
+                }
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Drop the changes to `manifest_file` (once they've been submitted).
+    pub fn remove_file(&self, manifest_file: &str) -> Result<()> {
+        let changes: Vec<PrChange> = self
+            .changes()?
+            .into_iter()
+            .filter(|change| change.manifest_file != manifest_file)
+            .collect();
+        if changes.is_empty() {
+            self.discard()?;
+            return Ok(());
+        }
+
+        let file_path = self.file_path(manifest_file)?;
+        if file_path.exists() {
+            std::fs::remove_file(&file_path)
+                .with_context(|| format!("Failed to remove {}", file_path.display()))?;
+        }
+        let index = serde_json::to_string_pretty(&changes)?;
+        std::fs::write(self.index_path(), index)
+            .with_context(|| format!("Failed to write {}", self.index_path().display()))?;
+        Ok(())
+    }
+
+    /// Drop every staged change, returning how many there were.
+    pub fn discard(&self) -> Result<usize> {
+        let count = self.changes()?.len();
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("Failed to remove {}", self.dir.display()))?;
+        }
+        Ok(count)
+    }
+}
+
+/// bkt's directory under the user's state directory.
+fn state_dir() -> PathBuf {
+    std::env::var("XDG_STATE_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|h| PathBuf::from(h).join(".local/state"))
+        })
+        .unwrap_or_else(|| PathBuf::from(".local/state"))
+        .join("bkt")
+}
+
+/// Where a manifest file lives in the repo: skel files as-is, everything
+/// else under `manifests/`.
+fn manifest_repo_path(manifest_file: &str) -> PathBuf {
+    if manifest_file.starts_with("skel/") {
+        PathBuf::from(manifest_file)
+    } else {
+        Path::new("manifests").join(manifest_file)
+    }
 }
 
 /// Find or clone the source repository.
@@ -469,47 +802,181 @@ pub fn ensure_repo(runner: &dyn CommandRunner) -> Result<PathBuf> {
     Ok(repo_path)
 }
 
+/// Remote that PR branches are pushed to and synced from.
+pub const REMOTE: &str = "origin";
+
+/// How a checkout's default branch compared to the remote before a sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoSync {
+    pub branch: String,
+    /// Local commits not on the remote
+    pub ahead: usize,
+    /// Remote commits fast-forwarded into the checkout
+    pub behind: usize,
+}
+
+/// Fetch the remote and fast-forward the checkout's default branch.
+///
+/// Refuses (without touching anything) when the working tree is dirty, when
+/// another branch is checked out, or when the default branch has commits the
+/// remote doesn't: those need a human to decide what to keep.
+pub fn sync_repo(
+    runner: &dyn CommandRunner,
+    repo_path: &Path,
+    default_branch: &str,
+) -> Result<RepoSync> {
+    validate_branch_pattern(default_branch)?;
+    let options = CommandOptions::with_cwd(repo_path);
+    let git = |args: &[&str]| -> Result<String> {
+        let output = runner
+            .run_output("git", args, &options)
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    if !git(&["status", "--porcelain"])?.trim().is_empty() {
+        bail!(
+            "{} has uncommitted changes; commit or stash them before syncing",
+            repo_path.display()
+        );
+    }
+
+    let current = git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if current.trim() != default_branch {
+        bail!(
+            "{} is on branch '{}', not '{}'; switch with: git checkout {}",
+            repo_path.display(),
+            current.trim(),
+            default_branch,
+            default_branch
+        );
+    }
+
+    git(&["fetch", REMOTE, default_branch])?;
+
+    let upstream = format!("{}/{}", REMOTE, default_branch);
+    let counts = git(&[
+        "rev-list",
+        "--left-right",
+        "--count",
+        &format!("{}...{}", default_branch, upstream),
+    ])?;
+    let (ahead, behind) = parse_left_right_count(&counts)
+        .with_context(|| format!("Unexpected git rev-list output: {}", counts.trim()))?;
+
+    if ahead > 0 {
+        bail!(
+            "'{}' has {} local commit(s) not on {}; push or reset them before syncing",
+            default_branch,
+            ahead,
+            upstream
+        );
+    }
+
+    if behind > 0 {
+        git(&["merge", "--ff-only", &upstream])?;
+    }
+
+    Ok(RepoSync {
+        branch: default_branch.to_string(),
+        ahead,
+        behind,
+    })
+}
+
+/// Parse `git rev-list --left-right --count a...b` output (`<left>\t<right>`).
+fn parse_left_right_count(output: &str) -> Option<(usize, usize)> {
+    let mut counts = output.split_whitespace().map(str::parse::<usize>);
+    match (counts.next(), counts.next(), counts.next()) {
+        (Some(Ok(left)), Some(Ok(right)), None) => Some((left, right)),
+        _ => None,
+    }
+}
+
 /// Run the PR workflow for a manifest change.
-/// Set `skip_preflight` to true to bypass pre-flight checks.
+/// Set `skip_preflight` to true to bypass pre-flight checks, and `no_sync`
+/// to branch from the checkout as-is instead of the current upstream tip.
+///
+/// # Errors
+///
+/// See [`run_commit_workflow`].
+pub fn run_pr_workflow(
+    runner: &dyn CommandRunner,
+    change: &PrChange,
+    manifest_content: &str,
+    skip_preflight: bool,
+    no_sync: bool,
+) -> Result<()> {
+    run_commit_workflow(
+        runner,
+        &change.commit(manifest_content),
+        skip_preflight,
+        no_sync,
+    )
+}
+
+/// Commit every file in `commit` on a new branch, push it, and open a PR.
+///
+/// Checkout files that already hold exactly the content being committed
+/// (changes staged without `--pr-only` were also written locally) are reset
+/// first, so they don't block the sync and come back with the merged PR.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Pre-flight checks fail (gh/git not configured)
 /// - Repository cannot be cloned or updated
+/// - The checkout can't be fast-forwarded to the remote (unless `no_sync`)
 /// - Git operations fail (branch creation, commit, push)
 /// - GitHub PR creation fails
 /// - Manifest path validation fails (path traversal attempt)
-pub fn run_pr_workflow(
+pub fn run_commit_workflow(
     runner: &dyn CommandRunner,
-    change: &PrChange,
-    manifest_content: &str,
+    commit: &PrCommit,
     skip_preflight: bool,
+    no_sync: bool,
 ) -> Result<()> {
-    // Validate manifest path before proceeding
-    validate_manifest_path(&change.manifest_file)?;
+    // Validate manifest paths before proceeding
+    for manifest_file in commit.files.keys() {
+        validate_manifest_path(manifest_file)?;
+    }
 
     ensure_preflight(runner, skip_preflight)?;
 
     let repo_path = ensure_repo(runner)?;
+    let config = RepoConfig::load()?;
 
-    // Determine the full path - skel files go directly, others go in manifests/
-    let manifest_path = if change.manifest_file.starts_with("skel/") {
-        repo_path.join(&change.manifest_file)
-    } else {
-        repo_path.join("manifests").join(&change.manifest_file)
-    };
+    reset_committed_files(runner, &repo_path, commit)?;
+
+    if !no_sync {
+        let sync = sync_repo(runner, &repo_path, &config.default_branch).context(
+            "Failed to sync with upstream (use --no-sync to branch from the checkout as-is)",
+        )?;
+        if sync.behind > 0 {
+            println!(
+                "Fast-forwarded {} by {} commit(s) from {}",
+                sync.branch, sync.behind, REMOTE
+            );
+        }
+    }
 
     // Create branch
-    let branch = change.branch_name();
+    let branch = &commit.branch;
     // Validate branch name is safe (should always pass due to sanitization)
-    validate_branch_pattern(&branch)?;
+    validate_branch_pattern(branch)?;
     println!("Creating branch: {}", branch);
 
     let status = runner
         .run_status(
             "git",
-            &["checkout", "-b", &branch],
+            &["checkout", "-b", branch],
             &CommandOptions::with_cwd(&repo_path),
         )
         .context("Failed to create branch")?;
@@ -517,26 +984,35 @@ pub fn run_pr_workflow(
         bail!("Failed to create branch {}", branch);
     }
 
-    // Write updated manifest
-    std::fs::write(&manifest_path, manifest_content)
-        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+    // Write updated manifests
+    let mut paths = Vec::new();
+    for (manifest_file, content) in &commit.files {
+        let manifest_path = repo_path.join(manifest_repo_path(manifest_file));
+        if let Some(parent) = manifest_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&manifest_path, content)
+            .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+        paths.push(
+            manifest_path
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid manifest path"))?
+                .to_string(),
+        );
+    }
 
     // Commit
-    let manifest_path_str = manifest_path
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid manifest path"))?;
-    let status = runner.run_status(
-        "git",
-        &["add", "--", manifest_path_str],
-        &CommandOptions::with_cwd(&repo_path),
-    )?;
+    let mut add_args = vec!["add", "--"];
+    add_args.extend(paths.iter().map(String::as_str));
+    let status = runner.run_status("git", &add_args, &CommandOptions::with_cwd(&repo_path))?;
     if !status.success() {
         bail!("git add failed");
     }
 
     let status = runner.run_status(
         "git",
-        &["commit", "-m", &change.commit_message()],
+        &["commit", "-m", &commit.message],
         &CommandOptions::with_cwd(&repo_path),
     )?;
     if !status.success() {
@@ -547,7 +1023,7 @@ pub fn run_pr_workflow(
     println!("Pushing branch...");
     let status = runner.run_status(
         "git",
-        &["push", "-u", "origin", &branch],
+        &["push", "-u", "origin", branch],
         &CommandOptions::with_cwd(&repo_path),
     )?;
     if !status.success() {
@@ -562,9 +1038,9 @@ pub fn run_pr_workflow(
             "pr",
             "create",
             "--title",
-            &change.pr_title(),
+            &commit.title,
             "--body",
-            &change.pr_body(),
+            &commit.body,
         ],
         &CommandOptions::with_cwd(&repo_path),
     )?;
@@ -573,7 +1049,6 @@ pub fn run_pr_workflow(
     }
 
     // Return to default branch
-    let config = RepoConfig::load()?;
     validate_branch_pattern(&config.default_branch)?;
     match runner.run_status(
         "git",
@@ -596,6 +1071,47 @@ pub fn run_pr_workflow(
     Ok(())
 }
 
+/// Reset checkout files whose working copy is exactly what `commit` writes.
+///
+/// Tracked files are restored from HEAD and untracked ones removed; anything
+/// holding other edits is left alone.
+fn reset_committed_files(
+    runner: &dyn CommandRunner,
+    repo_path: &Path,
+    commit: &PrCommit,
+) -> Result<()> {
+    let options = CommandOptions::with_cwd(repo_path);
+    for (manifest_file, content) in &commit.files {
+        let relative = manifest_repo_path(manifest_file);
+        let path = repo_path.join(&relative);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+            continue;
+        }
+        let relative = relative
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid manifest path"))?;
+
+        let tracked = runner
+            .run_status(
+                "git",
+                &["ls-files", "--error-unmatch", "--", relative],
+                &options,
+            )
+            .context("Failed to run git ls-files")?
+            .success();
+        if tracked {
+            let status = runner.run_status("git", &["checkout", "--", relative], &options)?;
+            if !status.success() {
+                bail!("Failed to reset {}", relative);
+            }
+        } else {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
 /// Test utilities for PR backend mocking.
 #[cfg(test)]
 #[allow(dead_code)]
@@ -609,12 +1125,14 @@ pub mod testing {
         pub change: PrChange,
         pub manifest_content: String,
         pub skip_preflight: bool,
+        pub no_sync: bool,
     }
 
     /// Mock backend that records PR creation calls for testing.
     #[derive(Default)]
     pub struct MockPrBackend {
         calls: Arc<Mutex<Vec<PrCall>>>,
+        batch_calls: Arc<Mutex<Vec<PrBatch>>>,
     }
 
     impl MockPrBackend {
@@ -627,10 +1145,21 @@ pub mod testing {
             self.calls.lock().unwrap().clone()
         }
 
+        /// Get all recorded batch PR creation calls.
+        pub fn batch_calls(&self) -> Vec<PrBatch> {
+            self.batch_calls.lock().unwrap().clone()
+        }
+
         /// Assert that no PRs were created.
         pub fn assert_no_pr(&self) {
             let calls = self.calls();
             assert!(calls.is_empty(), "Expected no PRs, got {}", calls.len());
+            let batches = self.batch_calls();
+            assert!(
+                batches.is_empty(),
+                "Expected no batch PRs, got {}",
+                batches.len()
+            );
         }
     }
 
@@ -640,13 +1169,222 @@ pub mod testing {
             change: &PrChange,
             manifest_content: &str,
             skip_preflight: bool,
+            no_sync: bool,
         ) -> Result<()> {
             self.calls.lock().unwrap().push(PrCall {
                 change: change.clone(),
                 manifest_content: manifest_content.to_string(),
                 skip_preflight,
+                no_sync,
             });
             Ok(())
         }
+
+        fn create_batch_pr(
+            &self,
+            batch: &PrBatch,
+            _skip_preflight: bool,
+            _no_sync: bool,
+        ) -> Result<()> {
+            self.batch_calls.lock().unwrap().push(batch.clone());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// An upstream repo with one commit on `main`, and a clone of it.
+    fn upstream_and_clone(root: &Path) -> (PathBuf, PathBuf) {
+        let upstream = root.join("upstream");
+        let clone = root.join("clone");
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q", "-b", "main"]);
+        git(&upstream, &["commit", "-q", "--allow-empty", "-m", "one"]);
+        git(
+            root,
+            &[
+                "clone",
+                "-q",
+                upstream.to_str().unwrap(),
+                clone.to_str().unwrap(),
+            ],
+        );
+        (upstream, clone)
+    }
+
+    #[test]
+    fn test_parse_left_right_count() {
+        assert_eq!(parse_left_right_count("0\t3\n"), Some((0, 3)));
+        assert_eq!(parse_left_right_count("2 1"), Some((2, 1)));
+        assert_eq!(parse_left_right_count("fatal: bad revision"), None);
+        assert_eq!(parse_left_right_count("1\t2\t3"), None);
+    }
+
+    #[test]
+    fn test_sync_repo_fast_forwards_default_branch() {
+        let temp = tempfile::tempdir().unwrap();
+        let (upstream, clone) = upstream_and_clone(temp.path());
+        git(&upstream, &["commit", "-q", "--allow-empty", "-m", "two"]);
+        git(&upstream, &["commit", "-q", "--allow-empty", "-m", "three"]);
+
+        let sync = sync_repo(&RealCommandRunner, &clone, "main").unwrap();
+        assert_eq!(sync.behind, 2);
+        assert_eq!(sync.ahead, 0);
+
+        let again = sync_repo(&RealCommandRunner, &clone, "main").unwrap();
+        assert_eq!(again.behind, 0);
+    }
+
+    #[test]
+    fn test_sync_repo_refuses_dirty_tree_and_local_commits() {
+        let temp = tempfile::tempdir().unwrap();
+        let (_upstream, clone) = upstream_and_clone(temp.path());
+
+        std::fs::write(clone.join("scratch.txt"), "wip").unwrap();
+        let err = sync_repo(&RealCommandRunner, &clone, "main").unwrap_err();
+        assert!(err.to_string().contains("uncommitted changes"));
+
+        git(&clone, &["add", "scratch.txt"]);
+        git(&clone, &["commit", "-q", "-m", "local"]);
+        let err = sync_repo(&RealCommandRunner, &clone, "main").unwrap_err();
+        assert!(err.to_string().contains("1 local commit(s)"));
+    }
+
+    fn change(manifest_type: &str, action: &str, name: &str, file: &str) -> PrChange {
+        PrChange {
+            manifest_type: manifest_type.to_string(),
+            action: action.to_string(),
+            name: name.to_string(),
+            manifest_file: file.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_stage_keeps_changes_in_order_and_latest_content() {
+        let temp = tempfile::tempdir().unwrap();
+        let stage = PrStage::at(temp.path().join("pr-staged"));
+        assert!(stage.batch().unwrap().is_empty());
+
+        let boxes = change("flatpak", "add", "org.gnome.Boxes", "flatpak-apps.json");
+        let dark = change("gsetting", "set", "color-scheme", "gsettings.json");
+        let maps = change("flatpak", "add", "org.gnome.Maps", "flatpak-apps.json");
+        stage.stage(&boxes, "{\"apps\": [\"Boxes\"]}").unwrap();
+        stage.stage(&dark, "{\"settings\": []}").unwrap();
+        stage
+            .stage(&maps, "{\"apps\": [\"Boxes\", \"Maps\"]}")
+            .unwrap();
+        // Re-staging the same change doesn't list it twice
+        stage
+            .stage(&maps, "{\"apps\": [\"Boxes\", \"Maps\"]}")
+            .unwrap();
+
+        assert!(stage.touches("gsettings.json").unwrap());
+        assert!(!stage.touches("host-shims.json").unwrap());
+
+        let batch = stage.batch().unwrap();
+        assert_eq!(batch.changes, vec![boxes, dark, maps]);
+        assert_eq!(batch.files.len(), 2);
+        assert_eq!(
+            batch.files["flatpak-apps.json"],
+            "{\"apps\": [\"Boxes\", \"Maps\"]}"
+        );
+
+        assert_eq!(stage.discard().unwrap(), 3);
+        assert!(stage.changes().unwrap().is_empty());
+        assert!(!stage.dir().exists());
+    }
+
+    #[test]
+    fn test_batches_by_file_split_and_remove_one_at_a_time() {
+        let temp = tempfile::tempdir().unwrap();
+        let stage = PrStage::at(temp.path().join("pr-deferred"));
+
+        let boxes = change("flatpak", "add", "org.gnome.Boxes", "flatpak-apps.json");
+        let dark = change("gsetting", "set", "color-scheme", "gsettings.json");
+        let maps = change("flatpak", "add", "org.gnome.Maps", "flatpak-apps.json");
+        stage.stage(&boxes, "{\"apps\": [\"Boxes\"]}").unwrap();
+        stage.stage(&dark, "{\"settings\": []}").unwrap();
+        stage
+            .stage(&maps, "{\"apps\": [\"Boxes\", \"Maps\"]}")
+            .unwrap();
+
+        let batches = stage.batches_by_file().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].changes, vec![boxes, maps]);
+        assert_eq!(
+            batches[0].files["flatpak-apps.json"],
+            "{\"apps\": [\"Boxes\", \"Maps\"]}"
+        );
+        assert_eq!(batches[1].changes, vec![dark.clone()]);
+        assert_eq!(batches[1].pr_title(), dark.pr_title());
+        assert!(
+            batches[1]
+                .branch_name()
+                .starts_with("bkt/gsetting-set-color-scheme-")
+        );
+
+        stage.remove_file("flatpak-apps.json").unwrap();
+        assert_eq!(stage.changes().unwrap(), vec![dark]);
+        assert!(!stage.dir().join("files/flatpak-apps.json").exists());
+        stage.remove_file("gsettings.json").unwrap();
+        assert!(!stage.dir().exists());
+    }
+
+    #[test]
+    fn test_stage_rejects_unsafe_manifest_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        let stage = PrStage::at(temp.path());
+        let escape = change("skel", "add", "x", "../../etc/passwd");
+        assert!(stage.stage(&escape, "").is_err());
+    }
+
+    #[test]
+    fn test_batch_title_and_body_list_every_change() {
+        let batch = PrBatch {
+            changes: vec![
+                change("flatpak", "add", "org.gnome.Boxes", "flatpak-apps.json"),
+                change("gsetting", "set", "color-scheme", "gsettings.json"),
+                change("skel", "add", ".bashrc", "skel/.bashrc"),
+            ],
+            files: BTreeMap::new(),
+        };
+
+        assert_eq!(
+            batch.pr_title(),
+            "Update flatpak, gsetting, skel (3 changes)"
+        );
+        let body = batch.pr_body();
+        assert!(
+            body.contains("| flatpak | add | `org.gnome.Boxes` | `manifests/flatpak-apps.json` |")
+        );
+        assert!(body.contains("| skel | add | `.bashrc` | `skel/.bashrc` |"));
+        assert!(
+            batch
+                .commit_message()
+                .ends_with("- set gsetting color-scheme\n- add skel .bashrc")
+        );
+        assert!(batch.branch_name().starts_with("bkt/batch-3-changes-"));
+        assert!(PrBatch::default().commit().is_err());
+
+        let single = PrBatch {
+            changes: batch.changes[..1].to_vec(),
+            files: BTreeMap::new(),
+        };
+        assert_eq!(single.pr_title(), "add flatpak `org.gnome.Boxes`");
     }
 }
@@ -0,0 +1,142 @@
+//! Reading the config repo's git history.
+//!
+//! Shared by `bkt build-info` (diffing manifests between two commits) and
+//! `bkt system history` (walking a manifest's commits).
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::command_runner::{CommandOptions, CommandRunner};
+
+/// Field separator for `git log --format`, unlikely to appear in a subject.
+const FIELD_SEP: char = '\x1f';
+
+/// A commit from [`log_path`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitInfo {
+    pub hash: String,
+    /// Author date, `YYYY-MM-DD`
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+}
+
+pub fn is_shallow_clone(repo_path: &Path) -> Result<bool> {
+    let shallow_file = repo_path.join(".git/shallow");
+    Ok(shallow_file.exists())
+}
+
+pub fn resolve_commit(
+    repo_path: &Path,
+    ref_spec: &str,
+    runner: &dyn CommandRunner,
+) -> Result<String> {
+    let output = runner
+        .run_output(
+            "git",
+            &["rev-parse", ref_spec],
+            &CommandOptions::with_cwd(repo_path),
+        )
+        .context("Failed to run git rev-parse")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("Failed to resolve commit '{}': {}", ref_spec, stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn get_file_at_commit(
+    repo_path: &Path,
+    commit: &str,
+    file_path: &str,
+    runner: &dyn CommandRunner,
+) -> Result<Option<String>> {
+    let git_path = format!("{}:{}", commit, file_path);
+    let output = runner
+        .run_output(
+            "git",
+            &["show", &git_path],
+            &CommandOptions::with_cwd(repo_path),
+        )
+        .context("Failed to run git show")?;
+
+    if !output.status.success() {
+        // File might not exist at this commit
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("does not exist") || stderr.contains("not found") {
+            return Ok(None);
+        }
+        // If it's a different error, still return None but log it
+        tracing::debug!("git show failed for {}: {}", git_path, stderr.trim());
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Commits that touched `file_path`, newest first, at most `limit` of them.
+pub fn log_path(
+    repo_path: &Path,
+    file_path: &str,
+    limit: Option<usize>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<CommitInfo>> {
+    let mut args = vec![
+        "log".to_string(),
+        format!("--format=%H{0}%ad{0}%an{0}%s", FIELD_SEP),
+        "--date=short".to_string(),
+    ];
+    if let Some(limit) = limit {
+        args.push(format!("--max-count={}", limit));
+    }
+    args.push("--".to_string());
+    args.push(file_path.to_string());
+
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = runner
+        .run_output("git", &arg_refs, &CommandOptions::with_cwd(repo_path))
+        .context("Failed to run git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git log failed: {}", stderr.trim());
+    }
+
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_log(stdout: &str) -> Vec<CommitInfo> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, FIELD_SEP);
+            Some(CommitInfo {
+                hash: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_splits_fields() {
+        let stdout = "abc123\x1f2026-01-15\x1fAda Lovelace\x1ffeat: add htop (#42)\n\
+                      def456\x1f2026-01-10\x1fGrace\x1f\n";
+        let commits = parse_log(stdout);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "abc123");
+        assert_eq!(commits[0].date, "2026-01-15");
+        assert_eq!(commits[0].author, "Ada Lovelace");
+        assert_eq!(commits[0].subject, "feat: add htop (#42)");
+        assert_eq!(commits[1].subject, "");
+    }
+}
@@ -4,6 +4,8 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::PathBuf;
 
+pub mod git;
+
 /// Repository identity and metadata.
 #[derive(Debug, Clone, Deserialize)]
 pub struct RepoConfig {
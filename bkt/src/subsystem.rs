@@ -37,6 +37,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
+use crate::manifest::{Profile, ProfileState};
 use crate::plan::{DynPlan, Plan, PlanContext};
 
 // ============================================================================
@@ -149,6 +150,12 @@ pub trait Subsystem: Send + Sync {
         Ok(None)
     }
 
+    /// Repo manifests (relative to the repo root) that `drift()` compares
+    /// against. Empty when drift isn't based on a repo manifest.
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Returns true if this subsystem supports capture operations.
     fn supports_capture(&self) -> bool {
         true
@@ -176,9 +183,14 @@ pub trait SubsystemStatus: std::fmt::Debug + Send + Sync {
     fn synced(&self) -> usize;
     fn pending(&self) -> usize;
     fn untracked(&self) -> usize;
+    /// Entries that are in sync but need attention (e.g. apps on an
+    /// end-of-life runtime).
+    fn warnings(&self) -> usize {
+        0
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, serde::Serialize)]
 pub struct DriftReport {
     /// In manifest, should exist
     pub expected: Vec<String>,
@@ -202,6 +214,7 @@ struct BasicSubsystemStatus {
     synced: usize,
     pending: usize,
     untracked: usize,
+    warnings: usize,
 }
 
 impl SubsystemStatus for BasicSubsystemStatus {
@@ -220,6 +233,10 @@ impl SubsystemStatus for BasicSubsystemStatus {
     fn untracked(&self) -> usize {
         self.untracked
     }
+
+    fn warnings(&self) -> usize {
+        self.warnings
+    }
 }
 
 fn build_drift_report(mut expected: Vec<String>, mut actual: Vec<String>) -> DriftReport {
@@ -258,15 +275,20 @@ pub struct SubsystemContext {
     pub repo_root: PathBuf,
     /// System manifest directory (/usr/share/bootc-bootstrap/).
     pub system_manifest_dir: PathBuf,
+    /// Active profile, whose user manifests overlay the repo's.
+    pub profile: Option<Profile>,
+    /// Include GNOME extension settings in extension drift. Off by default
+    /// since settings change often through the UI.
+    pub extension_settings: bool,
+    /// Read repo manifests from here instead of the checkout, e.g. a copy
+    /// of them as of a git ref (`bkt diff --ref`).
+    pub manifest_root: Option<PathBuf>,
 }
 
 impl SubsystemContext {
     /// Create a new subsystem context with default paths.
     pub fn new() -> Self {
-        Self {
-            repo_root: std::env::current_dir().unwrap_or_default(),
-            system_manifest_dir: PathBuf::from("/usr/share/bootc-bootstrap"),
-        }
+        Self::with_repo_root(std::env::current_dir().unwrap_or_default())
     }
 
     /// Create a context with a custom repo root.
@@ -274,9 +296,38 @@ impl SubsystemContext {
         Self {
             repo_root,
             system_manifest_dir: PathBuf::from("/usr/share/bootc-bootstrap"),
+            profile: ProfileState::load()
+                .and_then(|state| state.active_profile())
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Ignoring profile state: {:#}", e);
+                    None
+                }),
+            extension_settings: false,
+            manifest_root: None,
         }
     }
 
+    /// Include (or not) GNOME extension settings in extension drift.
+    pub fn with_extension_settings(mut self, enabled: bool) -> Self {
+        self.extension_settings = enabled;
+        self
+    }
+
+    /// Read repo manifests from `root` instead of the checkout.
+    pub fn with_manifest_root(mut self, root: PathBuf) -> Self {
+        self.manifest_root = Some(root);
+        self
+    }
+
+    /// Where to read a repo manifest (e.g. `manifests/gsettings.json`) from.
+    pub fn manifest_path(&self, project_path: &str) -> Result<PathBuf> {
+        let root = match &self.manifest_root {
+            Some(root) => root.clone(),
+            None => crate::repo::find_repo_path()?,
+        };
+        Ok(root.join(project_path))
+    }
+
     /// Get the path to a system manifest file.
     pub fn system_manifest_path(&self, filename: &str) -> PathBuf {
         self.system_manifest_dir.join(filename)
@@ -286,6 +337,14 @@ impl SubsystemContext {
     pub fn repo_manifest_path(&self, filename: &str) -> PathBuf {
         self.repo_root.join("manifests").join(filename)
     }
+
+    /// Get the active profile's overlay for a repo manifest (e.g.
+    /// `manifests/gsettings.json`), if a profile is active.
+    pub fn user_manifest_path(&self, project_path: &str) -> Option<PathBuf> {
+        self.profile
+            .as_ref()
+            .map(|profile| profile.overlay_path(project_path))
+    }
 }
 
 impl Default for SubsystemContext {
@@ -354,10 +413,13 @@ impl SubsystemRegistry {
                 Box::new(GsettingSubsystem),
                 Box::new(SystemdServicesSubsystem),
                 Box::new(ShimSubsystem),
+                Box::new(SkelSubsystem),
                 Box::new(AppImageSubsystem),
                 Box::new(FetchbinSubsystem),
+                Box::new(ToolboxBinariesSubsystem),
                 Box::new(HomebrewSubsystem),
                 Box::new(SystemSubsystem),
+                Box::new(KargsSubsystem),
             ],
         }
     }
@@ -524,8 +586,11 @@ impl fmt::Debug for SubsystemRegistry {
 // Extension Subsystem
 // ----------------------------------------------------------------------------
 
-use crate::commands::extension::{ExtensionCaptureCommand, ExtensionSyncCommand};
-use crate::context::run_command;
+use crate::commands::extension::{
+    ExtensionCaptureCommand, ExtensionSyncCommand, extension_in_sync,
+    extension_settings_drift_keys, extension_state_drift_keys, live_dconf_value,
+};
+use crate::context::run_on_host;
 use crate::manifest::GnomeExtensionsManifest;
 use crate::plan::Plannable;
 
@@ -549,8 +614,9 @@ impl Subsystem for ExtensionSubsystem {
         SubsystemTier::Convergent
     }
 
-    fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
-        let manifest = GnomeExtensionsManifest::load_repo()?;
+    fn load_manifest(&self, ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let overlay = ctx.user_manifest_path(GnomeExtensionsManifest::PROJECT_PATH);
+        let manifest = GnomeExtensionsManifest::load_with_overlay(overlay.as_deref())?;
         Ok(Box::new(manifest))
     }
 
@@ -576,11 +642,14 @@ impl Subsystem for ExtensionSubsystem {
         }
     }
 
-    fn status(&self, _ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
-        let manifest = GnomeExtensionsManifest::load_repo()?;
+    fn status(&self, ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
+        let overlay = ctx.user_manifest_path(GnomeExtensionsManifest::PROJECT_PATH);
+        let manifest = GnomeExtensionsManifest::load_with_overlay(overlay.as_deref())?;
 
         let enabled_extensions: std::collections::HashSet<String> =
             get_enabled_extensions().into_iter().collect();
+        let installed_extensions: std::collections::HashSet<String> =
+            get_installed_extensions().into_iter().collect();
         let manifest_uuids: std::collections::HashSet<_> =
             manifest.extensions.iter().map(|s| s.id()).collect();
 
@@ -588,7 +657,13 @@ impl Subsystem for ExtensionSubsystem {
         let synced = manifest
             .extensions
             .iter()
-            .filter(|u| enabled_extensions.contains(u.id()))
+            .filter(|item| {
+                extension_in_sync(
+                    item,
+                    installed_extensions.contains(item.id()),
+                    enabled_extensions.contains(item.id()),
+                )
+            })
             .count();
         let pending = total.saturating_sub(synced);
 
@@ -602,22 +677,37 @@ impl Subsystem for ExtensionSubsystem {
             synced,
             pending,
             untracked,
+            warnings: 0,
         })))
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = GnomeExtensionsManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let overlay = ctx.user_manifest_path(GnomeExtensionsManifest::PROJECT_PATH);
+        let manifest = GnomeExtensionsManifest::load_merged(
+            &ctx.manifest_path(GnomeExtensionsManifest::PROJECT_PATH)?,
+            overlay.as_deref(),
+        )?;
 
-        let expected: Vec<String> = manifest
-            .extensions
-            .iter()
-            .map(|s| s.id().to_string())
-            .collect();
-        let actual = get_enabled_extensions();
+        let enabled = get_enabled_extensions();
+        let (mut expected, mut actual) =
+            extension_state_drift_keys(&manifest, &get_installed_extensions(), &enabled);
+
+        // Settings compare per key as `uuid:key=value` when asked for.
+        let settings = if ctx.extension_settings {
+            extension_settings_drift_keys(&manifest, &enabled, live_dconf_value)
+        } else {
+            Default::default()
+        };
+        expected.extend(settings.0);
+        actual.extend(settings.1);
 
         Ok(Some(build_drift_report(expected, actual)))
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[GnomeExtensionsManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
@@ -625,7 +715,17 @@ impl Subsystem for ExtensionSubsystem {
 
 /// Get list of enabled GNOME extension UUIDs.
 fn get_enabled_extensions() -> Vec<String> {
-    let output = run_command("gnome-extensions", &["list", "--enabled"]);
+    list_extensions(&["list", "--enabled"])
+}
+
+/// Get list of installed GNOME extension UUIDs, enabled or not.
+fn get_installed_extensions() -> Vec<String> {
+    list_extensions(&["list"])
+}
+
+/// Run `gnome-extensions` with `args` and collect the UUIDs it lists.
+fn list_extensions(args: &[&str]) -> Vec<String> {
+    let output = run_on_host("gnome-extensions", args);
 
     match output {
         Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
@@ -647,7 +747,11 @@ impl Manifest for GnomeExtensionsManifest {
 // Flatpak Subsystem
 // ----------------------------------------------------------------------------
 
-use crate::commands::flatpak::{FlatpakCaptureCommand, FlatpakSyncCommand, get_installed_flatpaks};
+use crate::commands::flatpak::{
+    FlatpakCaptureCommand, FlatpakSyncCommand, eol_runtime_apps, flatpak_drift_keys,
+    flatpak_override_drift_keys, get_installed_flatpaks, live_flatpak_overrides,
+    runtime_eol_status,
+};
 use crate::manifest::FlatpakAppsManifest;
 
 /// Flatpak applications subsystem.
@@ -670,8 +774,9 @@ impl Subsystem for FlatpakSubsystem {
         SubsystemTier::Convergent
     }
 
-    fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
-        let manifest = FlatpakAppsManifest::load_repo()?;
+    fn load_manifest(&self, ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let overlay = ctx.user_manifest_path(FlatpakAppsManifest::PROJECT_PATH);
+        let manifest = FlatpakAppsManifest::load_with_overlay(overlay.as_deref())?;
         Ok(Box::new(manifest))
     }
 
@@ -697,11 +802,15 @@ impl Subsystem for FlatpakSubsystem {
         }
     }
 
-    fn status(&self, _ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
-        let manifest = FlatpakAppsManifest::load_repo()?;
+    fn status(&self, ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
+        let overlay = ctx.user_manifest_path(FlatpakAppsManifest::PROJECT_PATH);
+        let manifest = FlatpakAppsManifest::load_with_overlay(overlay.as_deref())?;
 
+        let installed = get_installed_flatpaks();
+        // Apps on an end-of-life runtime still work, until they don't
+        let warnings = eol_runtime_apps(&installed, &runtime_eol_status(&installed)).len();
         let installed_flatpaks: std::collections::HashSet<String> =
-            get_installed_flatpaks().into_iter().map(|f| f.id).collect();
+            installed.into_iter().map(|f| f.id).collect();
         let manifest_ids: std::collections::HashSet<_> =
             manifest.apps.iter().map(|a| a.id.as_str()).collect();
 
@@ -723,18 +832,35 @@ impl Subsystem for FlatpakSubsystem {
             synced,
             pending,
             untracked,
+            warnings,
         })))
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = FlatpakAppsManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let overlay = ctx.user_manifest_path(FlatpakAppsManifest::PROJECT_PATH);
+        let manifest = FlatpakAppsManifest::load_merged(
+            &ctx.manifest_path(FlatpakAppsManifest::PROJECT_PATH)?,
+            overlay.as_deref(),
+        )?;
+
+        // Pinned apps compare as `id@commit`, so a mismatched commit shows
+        // up as the pinned key missing and the deployed key extra.
+        let installed = get_installed_flatpaks();
+        let (mut expected, mut actual) = flatpak_drift_keys(&manifest, &installed);
 
-        let expected: Vec<String> = manifest.apps.iter().map(|a| a.id.clone()).collect();
-        let actual: Vec<String> = get_installed_flatpaks().into_iter().map(|f| f.id).collect();
+        // Overrides compare per flag as `id:flag` for installed apps.
+        let (expected_overrides, actual_overrides) =
+            flatpak_override_drift_keys(&manifest, &installed, live_flatpak_overrides);
+        expected.extend(expected_overrides);
+        actual.extend(actual_overrides);
 
         Ok(Some(build_drift_report(expected, actual)))
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[FlatpakAppsManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
@@ -750,7 +876,9 @@ impl Manifest for FlatpakAppsManifest {
 // Distrobox Subsystem
 // ----------------------------------------------------------------------------
 
-use crate::commands::distrobox::{DistroboxCaptureCommand, DistroboxSyncCommand};
+use crate::commands::distrobox::{
+    DistroboxCaptureCommand, DistroboxSyncCommand, distrobox_export_drift_keys, scan_exports,
+};
 use crate::manifest::DistroboxManifest;
 
 /// Distrobox containers subsystem.
@@ -800,6 +928,23 @@ impl Subsystem for DistroboxSubsystem {
             Ok(Some(Box::new(plan)))
         }
     }
+
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest =
+            DistroboxManifest::load(&ctx.manifest_path(DistroboxManifest::PROJECT_PATH)?)?;
+
+        // Exports compare as `box:app:name` and `box:bin:path`.
+        let (expected, actual) = distrobox_export_drift_keys(&manifest, scan_exports);
+        Ok(Some(build_drift_report(expected, actual)))
+    }
+
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[DistroboxManifest::PROJECT_PATH]
+    }
+
+    fn supports_drift(&self) -> bool {
+        true
+    }
 }
 
 impl Manifest for DistroboxManifest {
@@ -835,8 +980,9 @@ impl Subsystem for GsettingSubsystem {
         SubsystemTier::Convergent
     }
 
-    fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
-        let manifest = GSettingsManifest::load_repo()?;
+    fn load_manifest(&self, ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let overlay = ctx.user_manifest_path(GSettingsManifest::PROJECT_PATH);
+        let manifest = GSettingsManifest::load_with_overlay(overlay.as_deref())?;
         Ok(Box::new(manifest))
     }
 
@@ -860,15 +1006,21 @@ impl Subsystem for GsettingSubsystem {
         }
     }
 
-    fn status(&self, _ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
-        let manifest = GSettingsManifest::load_repo()?;
+    fn status(&self, ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
+        let overlay = ctx.user_manifest_path(GSettingsManifest::PROJECT_PATH);
+        let manifest = GSettingsManifest::load_with_overlay(overlay.as_deref())?;
 
         let total = manifest.settings.len();
         let mut synced = 0;
         let mut pending = 0;
 
         for s in &manifest.settings {
-            match get_gsetting(&s.schema, &s.key) {
+            // Locked settings are enforced by the image, not the session
+            if s.locked {
+                synced += 1;
+                continue;
+            }
+            match get_gsetting(&s.schema_spec(), &s.key) {
                 Some(current) if current == s.value => synced += 1,
                 Some(_) => pending += 1,
                 None => pending += 1,
@@ -880,20 +1032,25 @@ impl Subsystem for GsettingSubsystem {
             synced,
             pending,
             untracked: 0,
+            warnings: 0,
         })))
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = GSettingsManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let overlay = ctx.user_manifest_path(GSettingsManifest::PROJECT_PATH);
+        let manifest = GSettingsManifest::load_merged(
+            &ctx.manifest_path(GSettingsManifest::PROJECT_PATH)?,
+            overlay.as_deref(),
+        )?;
 
         let mut report = DriftReport::default();
 
-        for setting in &manifest.settings {
-            let key = format!("{}.{}", setting.schema, setting.key);
+        for setting in manifest.settings.iter().filter(|s| !s.locked) {
+            let key = setting.unique_key();
             let expected_entry = format!("{} = {}", key, setting.value);
             report.expected.push(expected_entry);
 
-            match get_gsetting(&setting.schema, &setting.key) {
+            match get_gsetting(&setting.schema_spec(), &setting.key) {
                 Some(current) => {
                     report.actual.push(format!("{} = {}", key, current));
                     if current != setting.value {
@@ -924,14 +1081,20 @@ impl Subsystem for GsettingSubsystem {
         false
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[GSettingsManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
 }
 
 /// Get current value of a gsetting.
+///
+/// `schema` may be a relocatable `schema:path` spec.
 fn get_gsetting(schema: &str, key: &str) -> Option<String> {
-    run_command("gsettings", &["get", schema, key])
+    run_on_host("gsettings", &["get", schema, key])
         .ok()
         .filter(|o| o.status.success())
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
@@ -987,8 +1150,10 @@ impl Subsystem for SystemdServicesSubsystem {
         Ok(None)
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = SystemdServicesManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest = SystemdServicesManifest::load(
+            &ctx.manifest_path(SystemdServicesManifest::PROJECT_PATH)?,
+        )?;
 
         let manager = SystemdManager::new()?;
         let mut report = DriftReport::default();
@@ -1035,6 +1200,10 @@ impl Subsystem for SystemdServicesSubsystem {
         false
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[SystemdServicesManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
@@ -1088,7 +1257,10 @@ impl Subsystem for ShimSubsystem {
         ctx: &PlanContext,
         _config: &SubsystemConfig,
     ) -> Result<Option<Box<dyn DynPlan>>> {
-        let plan = ShimSyncCommand.plan(ctx)?;
+        let plan = ShimSyncCommand {
+            keep_unmanaged: false,
+        }
+        .plan(ctx)?;
         if plan.is_empty() {
             Ok(None)
         } else {
@@ -1113,11 +1285,12 @@ impl Subsystem for ShimSubsystem {
             synced,
             pending,
             untracked: 0,
+            warnings: 0,
         })))
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = ShimsManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest = ShimsManifest::load(&ctx.manifest_path(ShimsManifest::PROJECT_PATH)?)?;
 
         let expected: Vec<String> = manifest.shims.iter().map(|s| s.name.clone()).collect();
         let actual = get_installed_shims();
@@ -1129,6 +1302,10 @@ impl Subsystem for ShimSubsystem {
         false
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[ShimsManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
@@ -1139,7 +1316,8 @@ fn shims_dir() -> PathBuf {
     ShimsManifest::shims_dir()
 }
 
-fn get_installed_shims() -> Vec<String> {
+/// Names of the shim scripts currently on disk.
+pub(crate) fn get_installed_shims() -> Vec<String> {
     let dir = shims_dir();
     if !dir.exists() {
         return Vec::new();
@@ -1166,6 +1344,145 @@ impl Manifest for ShimsManifest {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Skel Subsystem
+// ----------------------------------------------------------------------------
+
+use crate::commands::skel::{
+    CopyState, SkelFileStatus, deployed_path, home_dir, image_skel_dir, list_skel_files, skel_dir,
+    skel_file_statuses, template_vars,
+};
+
+/// Skel files subsystem.
+///
+/// Files under `skel/` are baked into the image's `/usr/etc/skel` and copied
+/// into `$HOME` by `bkt skel sync`; this subsystem only reports drift.
+pub struct SkelSubsystem;
+
+/// The skel files tracked in the repo.
+#[derive(Debug, serde::Serialize)]
+pub struct SkelFiles {
+    pub files: Vec<String>,
+}
+
+impl Manifest for SkelFiles {
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Subsystem for SkelSubsystem {
+    fn name(&self) -> &'static str {
+        "Skel Files"
+    }
+
+    fn id(&self) -> &'static str {
+        "skel"
+    }
+
+    fn phase(&self) -> ExecutionPhase {
+        ExecutionPhase::Configuration
+    }
+
+    fn tier(&self) -> SubsystemTier {
+        SubsystemTier::Convergent
+    }
+
+    fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let files = list_skel_files(&skel_dir()?)?
+            .iter()
+            .map(|f| deployed_path(f).display().to_string())
+            .collect();
+        Ok(Box::new(SkelFiles { files }))
+    }
+
+    fn capture(&self, _ctx: &PlanContext) -> Result<Option<Box<dyn DynPlan>>> {
+        Ok(None)
+    }
+
+    fn sync(
+        &self,
+        _ctx: &PlanContext,
+        _config: &SubsystemConfig,
+    ) -> Result<Option<Box<dyn DynPlan>>> {
+        // Overwriting $HOME dotfiles is left to an explicit `bkt skel sync`
+        Ok(None)
+    }
+
+    fn status(&self, _ctx: &SubsystemContext) -> Result<Option<Box<dyn SubsystemStatus>>> {
+        let statuses = current_skel_statuses()?;
+
+        let total = statuses.len();
+        let synced = statuses.iter().filter(|s| s.in_sync()).count();
+
+        Ok(Some(Box::new(BasicSubsystemStatus {
+            total,
+            synced,
+            pending: total - synced,
+            untracked: 0,
+            warnings: 0,
+        })))
+    }
+
+    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        Ok(Some(skel_drift_report(
+            &current_skel_statuses()?,
+            &image_skel_dir().display().to_string(),
+        )))
+    }
+
+    fn supports_capture(&self) -> bool {
+        false
+    }
+
+    fn supports_sync(&self) -> bool {
+        false
+    }
+
+    fn supports_drift(&self) -> bool {
+        true
+    }
+}
+
+fn current_skel_statuses() -> Result<Vec<SkelFileStatus>> {
+    skel_file_statuses(
+        &skel_dir()?,
+        &image_skel_dir(),
+        &home_dir()?,
+        &template_vars()?,
+    )
+}
+
+/// Build a drift report where each missing or modified copy is its own entry.
+fn skel_drift_report(statuses: &[SkelFileStatus], image_dir: &str) -> DriftReport {
+    let mut report = DriftReport::default();
+
+    for status in statuses {
+        report.expected.push(status.file.clone());
+        if status.in_sync() {
+            report.actual.push(status.file.clone());
+        }
+
+        let copies = [
+            (format!("{}/{}", image_dir, status.file), status.image),
+            (format!("~/{}", status.file), status.home),
+        ];
+        for (path, state) in copies {
+            match state {
+                CopyState::InSync => {}
+                CopyState::Modified => report.missing.push(format!("{} (modified)", path)),
+                CopyState::Missing => report.missing.push(format!("{} (missing)", path)),
+            }
+        }
+    }
+
+    report.expected.sort();
+    report.actual.sort();
+    report.missing.sort();
+
+    report
+}
+
 // ----------------------------------------------------------------------------
 // AppImage Subsystem
 // ----------------------------------------------------------------------------
@@ -1292,11 +1609,99 @@ impl Manifest for HostBinariesManifest {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Toolbox Binaries Subsystem
+// ----------------------------------------------------------------------------
+
+use crate::command_runner::RealCommandRunner;
+use crate::commands::dev::{
+    ToolboxBinariesCaptureCommand, ToolboxBinariesSyncCommand, toolbox_binaries_drift_keys,
+};
+use crate::manifest::ToolboxBinariesManifest;
+
+/// Dev binaries installed inside the toolbox (cargo-binstall, fetchbin).
+///
+/// Sync only runs in the dev context; the plan refuses to install on the host.
+pub struct ToolboxBinariesSubsystem;
+
+impl Subsystem for ToolboxBinariesSubsystem {
+    fn name(&self) -> &'static str {
+        "Toolbox Binaries"
+    }
+
+    fn id(&self) -> &'static str {
+        "toolbox-binaries"
+    }
+
+    fn phase(&self) -> ExecutionPhase {
+        ExecutionPhase::Packages
+    }
+
+    fn tier(&self) -> SubsystemTier {
+        SubsystemTier::Convergent
+    }
+
+    fn load_manifest(&self, ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let manifest = ToolboxBinariesManifest::load(
+            &ctx.repo_root.join(ToolboxBinariesManifest::PROJECT_PATH),
+        )?;
+        Ok(Box::new(manifest))
+    }
+
+    fn capture(&self, ctx: &PlanContext) -> Result<Option<Box<dyn DynPlan>>> {
+        let plan = ToolboxBinariesCaptureCommand.plan(ctx)?;
+        if plan.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Box::new(plan)))
+        }
+    }
+
+    fn sync(
+        &self,
+        ctx: &PlanContext,
+        _config: &SubsystemConfig,
+    ) -> Result<Option<Box<dyn DynPlan>>> {
+        let plan = ToolboxBinariesSyncCommand.plan(ctx)?;
+        if plan.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Box::new(plan)))
+        }
+    }
+
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest = ToolboxBinariesManifest::load(
+            &ctx.manifest_path(ToolboxBinariesManifest::PROJECT_PATH)?,
+        )?;
+
+        let (expected, actual) = toolbox_binaries_drift_keys(&manifest, &RealCommandRunner);
+
+        Ok(Some(build_drift_report(expected, actual)))
+    }
+
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[ToolboxBinariesManifest::PROJECT_PATH]
+    }
+
+    fn supports_drift(&self) -> bool {
+        true
+    }
+}
+
+impl Manifest for ToolboxBinariesManifest {
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Homebrew Subsystem
 // ----------------------------------------------------------------------------
 
-use crate::commands::homebrew::{HomebrewCaptureCommand, HomebrewSyncCommand};
+use crate::commands::homebrew::{
+    HomebrewCaptureCommand, HomebrewSyncCommand, InstalledHomebrew, homebrew_drift_keys,
+};
 use crate::manifest::homebrew::HomebrewManifest;
 
 /// Homebrew/Linuxbrew subsystem.
@@ -1345,6 +1750,21 @@ impl Subsystem for HomebrewSubsystem {
             Ok(Some(Box::new(plan)))
         }
     }
+
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest = HomebrewManifest::load(&ctx.manifest_path(HomebrewManifest::PROJECT_PATH)?)?;
+        let installed = InstalledHomebrew::scan(&RealCommandRunner);
+        let (expected, actual) = homebrew_drift_keys(&manifest, &installed);
+        Ok(Some(build_drift_report(expected, actual)))
+    }
+
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[HomebrewManifest::PROJECT_PATH]
+    }
+
+    fn supports_drift(&self) -> bool {
+        true
+    }
 }
 
 impl Manifest for HomebrewManifest {
@@ -1386,7 +1806,7 @@ impl Subsystem for SystemSubsystem {
     }
 
     fn capture(&self, ctx: &PlanContext) -> Result<Option<Box<dyn DynPlan>>> {
-        let plan = SystemCaptureCommand.plan(ctx)?;
+        let plan = SystemCaptureCommand::default().plan(ctx)?;
         if plan.is_empty() {
             Ok(None)
         } else {
@@ -1403,8 +1823,10 @@ impl Subsystem for SystemSubsystem {
         Ok(None)
     }
 
-    fn drift(&self, _ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
-        let manifest = SystemPackagesManifest::load_repo()?;
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let manifest = SystemPackagesManifest::load(
+            &ctx.manifest_path(SystemPackagesManifest::PROJECT_PATH)?,
+        )?;
 
         let expected = manifest.packages;
         let actual = get_layered_packages();
@@ -1417,6 +1839,10 @@ impl Subsystem for SystemSubsystem {
         false
     }
 
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[SystemPackagesManifest::PROJECT_PATH]
+    }
+
     fn supports_drift(&self) -> bool {
         true
     }
@@ -1429,7 +1855,7 @@ impl Manifest for SystemPackagesManifest {
 }
 
 fn get_layered_packages() -> Vec<String> {
-    let output = run_command("rpm-ostree", &["status", "--json"]);
+    let output = run_on_host("rpm-ostree", &["status", "--json"]);
 
     let output = match output {
         Ok(o) if o.status.success() => o,
@@ -1470,6 +1896,90 @@ fn get_layered_packages() -> Vec<String> {
 // Tests
 // ============================================================================
 
+// ----------------------------------------------------------------------------
+// Kargs Subsystem
+// ----------------------------------------------------------------------------
+
+use crate::commands::admin::{KargsDiff, running_cmdline};
+use crate::manifest::system_config::SystemConfigManifest;
+
+/// Kernel arguments subsystem (drift only; kargs are set at image build).
+pub struct KargsSubsystem;
+
+impl Subsystem for KargsSubsystem {
+    fn name(&self) -> &'static str {
+        "Kernel Arguments"
+    }
+
+    fn id(&self) -> &'static str {
+        "kargs"
+    }
+
+    fn tier(&self) -> SubsystemTier {
+        SubsystemTier::Atomic
+    }
+
+    fn load_manifest(&self, _ctx: &SubsystemContext) -> Result<Box<dyn Manifest>> {
+        let manifest = SystemConfigManifest::load()?;
+        Ok(Box::new(manifest))
+    }
+
+    fn capture(&self, _ctx: &PlanContext) -> Result<Option<Box<dyn DynPlan>>> {
+        Ok(None)
+    }
+
+    fn sync(
+        &self,
+        _ctx: &PlanContext,
+        _config: &SubsystemConfig,
+    ) -> Result<Option<Box<dyn DynPlan>>> {
+        Ok(None)
+    }
+
+    fn drift(&self, ctx: &SubsystemContext) -> Result<Option<DriftReport>> {
+        let path = ctx.manifest_path(SystemConfigManifest::PROJECT_PATH)?;
+        let kargs = SystemConfigManifest::load_from_path(&path)?
+            .kargs
+            .unwrap_or_default();
+        let mut actual = running_cmdline()?;
+        let diff = KargsDiff::compute(&kargs, &actual);
+
+        // Untracked kargs are informational, so only manifest entries drift
+        let mut expected = kargs.append;
+        expected.sort();
+        actual.sort();
+
+        Ok(Some(DriftReport {
+            expected,
+            actual,
+            missing: diff.missing,
+            extra: diff.unremoved,
+        }))
+    }
+
+    fn supports_capture(&self) -> bool {
+        false
+    }
+
+    fn supports_sync(&self) -> bool {
+        false
+    }
+
+    fn manifest_files(&self) -> &'static [&'static str] {
+        &[SystemConfigManifest::PROJECT_PATH]
+    }
+
+    fn supports_drift(&self) -> bool {
+        true
+    }
+}
+
+impl Manifest for SystemConfigManifest {
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1479,8 +1989,8 @@ mod tests {
         let registry = SubsystemRegistry::builtin();
         let all = registry.all();
 
-        // Should have all 10 subsystems
-        assert_eq!(all.len(), 10);
+        // Should have all 12 subsystems
+        assert_eq!(all.len(), 13);
 
         // Verify expected IDs
         let ids: Vec<_> = all.iter().map(|s| s.id()).collect();
@@ -1489,11 +1999,14 @@ mod tests {
         assert!(ids.contains(&"distrobox"));
         assert!(ids.contains(&"gsetting"));
         assert!(ids.contains(&"shim"));
+        assert!(ids.contains(&"skel"));
         assert!(ids.contains(&"appimage"));
         assert!(ids.contains(&"fetchbin"));
+        assert!(ids.contains(&"toolbox-binaries"));
         assert!(ids.contains(&"homebrew"));
         assert!(ids.contains(&"system"));
         assert!(ids.contains(&"systemd-services"));
+        assert!(ids.contains(&"kargs"));
     }
 
     #[test]
@@ -1508,12 +2021,15 @@ mod tests {
                 "flatpak",
                 "appimage",
                 "fetchbin",
+                "toolbox-binaries",
                 "homebrew",
                 "system",
                 "extension",
                 "gsetting",
                 "systemd-services",
                 "shim",
+                "skel",
+                "kargs",
             ]
         );
     }
@@ -1524,7 +2040,7 @@ mod tests {
 
         for subsystem in registry.all() {
             let expected = match subsystem.id() {
-                "system" => SubsystemTier::Atomic,
+                "system" | "kargs" => SubsystemTier::Atomic,
                 _ => SubsystemTier::Convergent,
             };
 
@@ -1547,7 +2063,7 @@ mod tests {
 
         // Exclude gsetting
         let selected = registry.filtered(None, &["gsetting"]);
-        assert_eq!(selected.len(), 9);
+        assert_eq!(selected.len(), 12);
 
         // Include extension but exclude it (exclude wins)
         let selected = registry.filtered(Some(&["extension"]), &["extension"]);
@@ -1581,6 +2097,7 @@ mod tests {
                 "flatpak",
                 "appimage",
                 "fetchbin",
+                "toolbox-binaries",
                 "homebrew",
                 "system",
                 "distrobox"
@@ -1599,6 +2116,47 @@ mod tests {
         assert!(ids.contains(&"flatpak"));
         assert!(ids.contains(&"gsetting"));
         assert!(!ids.contains(&"system"));
+        assert!(!ids.contains(&"skel"));
+    }
+
+    #[test]
+    fn test_skel_drift_report_separates_missing_and_modified() {
+        use crate::commands::skel::{CopyState, SkelFileStatus};
+
+        let statuses = vec![
+            SkelFileStatus {
+                file: ".bashrc".to_string(),
+                source: ".bashrc".to_string(),
+                image: CopyState::InSync,
+                home: CopyState::Modified,
+            },
+            SkelFileStatus {
+                file: ".profile".to_string(),
+                source: ".profile".to_string(),
+                image: CopyState::Missing,
+                home: CopyState::Missing,
+            },
+            SkelFileStatus {
+                file: ".vimrc".to_string(),
+                source: ".vimrc".to_string(),
+                image: CopyState::InSync,
+                home: CopyState::InSync,
+            },
+        ];
+
+        let report = skel_drift_report(&statuses, "/usr/etc/skel");
+        assert!(report.has_drift());
+        assert_eq!(report.expected, vec![".bashrc", ".profile", ".vimrc"]);
+        assert_eq!(report.actual, vec![".vimrc"]);
+        assert_eq!(
+            report.missing,
+            vec![
+                "/usr/etc/skel/.profile (missing)",
+                "~/.bashrc (modified)",
+                "~/.profile (missing)",
+            ]
+        );
+        assert!(report.extra.is_empty());
     }
 
     #[test]
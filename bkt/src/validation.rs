@@ -4,14 +4,15 @@
 //! before adding them to manifests. This prevents typos and invalid entries.
 
 use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
 use crate::command_runner::{CommandOptions, CommandRunner};
+use crate::manifest::GSettingType;
 
 #[cfg(test)]
 use crate::command_runner::RealCommandRunner;
 
-use crate::output::Output;
-
 /// Validate that a GSettings schema exists.
 pub fn validate_gsettings_schema(runner: &dyn CommandRunner, schema: &str) -> Result<()> {
     let output = runner
@@ -63,6 +64,45 @@ pub fn validate_gsettings_schema(runner: &dyn CommandRunner, schema: &str) -> Re
     }
 }
 
+/// Validate that a relocatable GSettings schema exists.
+///
+/// Relocatable schemas (used with `schema:path`) are not listed by
+/// `gsettings list-schemas`, so they are checked separately.
+pub fn validate_gsettings_relocatable_schema(
+    runner: &dyn CommandRunner,
+    schema: &str,
+) -> Result<()> {
+    let output = runner
+        .run_output(
+            "gsettings",
+            &["list-relocatable-schemas"],
+            &CommandOptions::default(),
+        )
+        .context("Failed to list relocatable GSettings schemas")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Cannot validate GSettings schema: gsettings command failed.\n\n\
+             stderr: {}\n\n\
+             Make sure gsettings and GLib schemas are available.",
+            stderr.trim()
+        );
+    }
+
+    let schemas = String::from_utf8_lossy(&output.stdout);
+    if schemas.lines().any(|s| s == schema) {
+        return Ok(());
+    }
+
+    bail!(
+        "Relocatable GSettings schema '{}' not found.\n\n\
+         To list relocatable schemas:\n  \
+         gsettings list-relocatable-schemas | grep <term>",
+        schema
+    );
+}
+
 /// Validate that a key exists in a GSettings schema.
 pub fn validate_gsettings_key(runner: &dyn CommandRunner, schema: &str, key: &str) -> Result<()> {
     let output = runner
@@ -110,6 +150,82 @@ pub fn validate_gsettings_key(runner: &dyn CommandRunner, schema: &str, key: &st
     );
 }
 
+/// Look up the values a GSettings key accepts (`gsettings range`).
+pub fn gsettings_key_type(
+    runner: &dyn CommandRunner,
+    schema: &str,
+    key: &str,
+) -> Result<GSettingType> {
+    let output = runner
+        .run_output(
+            "gsettings",
+            &["range", schema, key],
+            &CommandOptions::default(),
+        )
+        .context("Failed to query GSettings key range")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Cannot read the type of {} {}: {}",
+            schema,
+            key,
+            stderr.trim()
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    GSettingType::parse_range(&stdout).with_context(|| {
+        format!(
+            "Unrecognized `gsettings range {} {}` output: {}",
+            schema,
+            key,
+            stdout.trim()
+        )
+    })
+}
+
+/// Validate that `value` is acceptable for a GSettings key, returning the
+/// key's type.
+pub fn validate_gsettings_value(
+    runner: &dyn CommandRunner,
+    schema: &str,
+    key: &str,
+    value: &str,
+) -> Result<GSettingType> {
+    let value_type = gsettings_key_type(runner, schema, key)?;
+    if value_type.accepts(value) {
+        return Ok(value_type);
+    }
+
+    let description = runner
+        .run_output(
+            "gsettings",
+            &["describe", schema, key],
+            &CommandOptions::default(),
+        )
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|d| !d.is_empty())
+        .map(|d| format!("{}: {}\n\n", key, d))
+        .unwrap_or_default();
+
+    bail!(
+        "Invalid value {} for {} {}: expected {}.\n\n\
+         {}\
+         To see the accepted values:\n  \
+         gsettings range {} {}",
+        value,
+        schema,
+        key,
+        value_type.expected(),
+        description,
+        schema,
+        key
+    );
+}
+
 /// Validate that a DNF package exists in repositories.
 pub fn validate_dnf_package(runner: &dyn CommandRunner, package: &str) -> Result<()> {
     // Try dnf5 first, fall back to dnf
@@ -253,48 +369,317 @@ pub fn validate_flatpak_app(runner: &dyn CommandRunner, app_id: &str, remote: &s
     }
 }
 
-/// Validate that a GNOME Shell extension UUID exists.
-///
-/// This checks against extensions.gnome.org. If network is unavailable,
-/// it will warn but not fail.
-pub fn validate_gnome_extension(runner: &dyn CommandRunner, uuid: &str) -> Result<()> {
-    // Use curl to query the API (avoiding extra dependencies)
-    let url = format!(
-        "https://extensions.gnome.org/extension-info/?uuid={}",
-        urlencoding::encode(uuid)
-    );
+/// What a `.flatpakrepo` file says about a remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatpakRepoFile {
+    pub title: Option<String>,
+    /// The repo itself, as opposed to the `.flatpakrepo` that points at it.
+    pub url: String,
+    pub homepage: Option<String>,
+    /// SHA-256 (hex) of the decoded `GPGKey`.
+    pub gpg_key_sha256: String,
+}
 
-    let output = runner.run_output(
-        "curl",
-        &["-s", "-f", "-o", "/dev/null", "-w", "%{http_code}", &url],
-        &CommandOptions::default(),
-    );
+/// Parse a `.flatpakrepo` keyfile, requiring a `Url` and a `GPGKey`.
+pub fn parse_flatpakrepo(content: &str) -> Result<FlatpakRepoFile> {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    let mut in_group = false;
+    let mut fields = BTreeMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_group = line == "[Flatpak Repo]";
+            continue;
+        }
+        if in_group && let Some((key, value)) = line.split_once('=') {
+            fields.insert(key.trim(), value.trim());
+        }
+    }
+    if fields.is_empty() {
+        bail!("Not a .flatpakrepo file: no [Flatpak Repo] group");
+    }
+
+    let Some(url) = fields.get("Url").filter(|url| !url.is_empty()) else {
+        bail!(".flatpakrepo file has no Url");
+    };
+    let Some(key) = fields.get("GPGKey").filter(|key| !key.is_empty()) else {
+        bail!(
+            ".flatpakrepo file has no GPGKey; flatpak would add the remote \
+             without signature verification"
+        );
+    };
+    let key = base64::engine::general_purpose::STANDARD
+        .decode(key)
+        .context(".flatpakrepo GPGKey is not valid base64")?;
+
+    let text = |name: &str| {
+        fields
+            .get(name)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+    };
+    Ok(FlatpakRepoFile {
+        title: text("Title"),
+        url: url.to_string(),
+        homepage: text("Homepage"),
+        gpg_key_sha256: format!("{:x}", Sha256::digest(&key)),
+    })
+}
+
+/// Fetch and parse the `.flatpakrepo` file at `url`.
+pub fn fetch_flatpakrepo(runner: &dyn CommandRunner, url: &str) -> Result<FlatpakRepoFile> {
+    let output = runner
+        .run_output("curl", &["-fsSL", url], &CommandOptions::default())
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to fetch {}: {}\n\n\
+             Check the URL; Flathub's is https://dl.flathub.org/repo/flathub.flatpakrepo",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_flatpakrepo(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("Invalid .flatpakrepo at {}", url))
+}
+
+/// Base URL of the extensions.gnome.org API.
+const EXTENSIONS_API: &str = "https://extensions.gnome.org";
+
+/// An extension as described by extensions.gnome.org.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GnomeExtensionInfo {
+    pub uuid: String,
+    pub name: String,
+    /// Numeric id on extensions.gnome.org.
+    pub pk: u64,
+    /// Supported GNOME Shell versions ("46", "3.38", ...) to release info.
+    #[serde(default)]
+    pub shell_version_map: BTreeMap<String, serde_json::Value>,
+}
+
+impl GnomeExtensionInfo {
+    /// Whether a release supports GNOME Shell `version` (e.g. "47.2").
+    pub fn supports_shell(&self, version: &str) -> bool {
+        self.shell_version_map
+            .contains_key(&shell_version_key(version))
+    }
+
+    /// The `version_tag` of the release for GNOME Shell `version`, which
+    /// names the zip to download.
+    pub fn release_for_shell(&self, version: &str) -> Option<u64> {
+        self.shell_version_map
+            .get(&shell_version_key(version))?
+            .get("pk")?
+            .as_u64()
+    }
+
+    /// Where to download the release with `version_tag`.
+    pub fn download_url(&self, version_tag: u64) -> String {
+        format!(
+            "{}/download-extension/{}.shell-extension.zip?version_tag={}",
+            EXTENSIONS_API,
+            urlencoding::encode(&self.uuid),
+            version_tag
+        )
+    }
+
+    /// Supported GNOME Shell versions, for error messages.
+    pub fn shell_versions(&self) -> Vec<&str> {
+        self.shell_version_map.keys().map(String::as_str).collect()
+    }
+}
+
+/// The `shell_version_map` key for GNOME Shell `version`: since GNOME 40
+/// releases are keyed by major version; before that by major.minor.
+fn shell_version_key(version: &str) -> String {
+    let mut parts = version.split('.');
+    let major = parts.next().unwrap_or_default();
+    match major.parse::<u32>() {
+        Ok(major) if major >= 40 => major.to_string(),
+        _ => format!("{}.{}", major, parts.next().unwrap_or("0")),
+    }
+}
+
+/// How to look up a single extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionLookup {
+    Uuid(String),
+    Pk(u64),
+}
+
+/// The extensions.gnome.org API, behind a trait so tests can fake it.
+pub trait ExtensionsApi {
+    /// Look up one extension; `None` if it doesn't exist.
+    fn info(&self, lookup: &ExtensionLookup) -> Result<Option<GnomeExtensionInfo>>;
+    /// Search extensions by name or description.
+    fn search(&self, query: &str) -> Result<Vec<GnomeExtensionInfo>>;
+}
+
+/// The extensions.gnome.org API could not be reached.
+#[derive(Debug)]
+pub struct ExtensionsApiUnavailable(pub String);
+
+impl std::fmt::Display for ExtensionsApiUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "extensions.gnome.org unavailable: {}", self.0)
+    }
+}
+
+impl std::error::Error for ExtensionsApiUnavailable {}
+
+/// [`ExtensionsApi`] that queries extensions.gnome.org with curl.
+pub struct CurlExtensionsApi<'a> {
+    runner: &'a dyn CommandRunner,
+}
+
+impl<'a> CurlExtensionsApi<'a> {
+    pub fn new(runner: &'a dyn CommandRunner) -> Self {
+        Self { runner }
+    }
 
-    match output {
-        Ok(o) if o.status.success() => {
-            let code = String::from_utf8_lossy(&o.stdout);
-            if code.trim() == "200" {
-                return Ok(());
+    /// Fetch `url` as JSON; `None` on an HTTP error status (e.g. 404).
+    fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Option<T>> {
+        let output = self
+            .runner
+            .run_output("curl", &["-s", "-f", url], &CommandOptions::default())
+            .map_err(|e| ExtensionsApiUnavailable(e.to_string()))?;
+
+        // curl exits 22 for HTTP errors under -f; anything else is the network
+        match output.status.code() {
+            Some(0) => {}
+            Some(22) => return Ok(None),
+            code => {
+                return Err(ExtensionsApiUnavailable(format!(
+                    "curl exited with {}",
+                    code.map_or_else(|| "signal".to_string(), |c| c.to_string())
+                ))
+                .into());
             }
-            // 404 or other error - extension not found
         }
-        Ok(_) | Err(_) => {
-            // Network error - warn but continue
-            Output::warning("Could not validate extension (network unavailable)");
-            return Ok(());
+
+        let value = serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("Failed to parse response from {}", url))?;
+        Ok(Some(value))
+    }
+}
+
+impl ExtensionsApi for CurlExtensionsApi<'_> {
+    fn info(&self, lookup: &ExtensionLookup) -> Result<Option<GnomeExtensionInfo>> {
+        let query = match lookup {
+            ExtensionLookup::Uuid(uuid) => format!("uuid={}", urlencoding::encode(uuid)),
+            ExtensionLookup::Pk(pk) => format!("pk={}", pk),
+        };
+        self.get_json(&format!("{}/extension-info/?{}", EXTENSIONS_API, query))
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<GnomeExtensionInfo>> {
+        #[derive(Deserialize)]
+        struct QueryResponse {
+            #[serde(default)]
+            extensions: Vec<GnomeExtensionInfo>,
+        }
+
+        let url = format!(
+            "{}/extension-query/?search={}",
+            EXTENSIONS_API,
+            urlencoding::encode(query)
+        );
+        let response: Option<QueryResponse> = self.get_json(&url)?;
+        Ok(response.map(|r| r.extensions).unwrap_or_default())
+    }
+}
+
+/// Result of resolving a user-supplied extension query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionMatch {
+    /// A single extension: an exact UUID, pk or name match, or the only hit.
+    Found(GnomeExtensionInfo),
+    /// Several search hits and none matched exactly.
+    Ambiguous(Vec<GnomeExtensionInfo>),
+}
+
+/// Resolve a UUID, numeric pk, or search term to extensions.gnome.org entries.
+pub fn resolve_gnome_extension(api: &dyn ExtensionsApi, query: &str) -> Result<ExtensionMatch> {
+    let lookup = if query.contains('@') {
+        Some(ExtensionLookup::Uuid(query.to_string()))
+    } else {
+        query.parse().ok().map(ExtensionLookup::Pk)
+    };
+
+    if let Some(lookup) = lookup {
+        if let Some(info) = api.info(&lookup)? {
+            return Ok(ExtensionMatch::Found(info));
+        }
+        if matches!(lookup, ExtensionLookup::Uuid(_)) {
+            bail!(
+                "GNOME extension '{}' not found on extensions.gnome.org.\n\n\
+                 To browse extensions:\n  \
+                 https://extensions.gnome.org\n\n\
+                 Verify the exact UUID from the extension's page URL.",
+                query
+            );
         }
     }
 
-    // Extension not found
+    let mut results = api.search(query)?;
+    let exact = results.iter().position(|ext| {
+        ext.uuid == query || ext.pk.to_string() == query || ext.name.eq_ignore_ascii_case(query)
+    });
+
+    match (exact, results.len()) {
+        (Some(index), _) => Ok(ExtensionMatch::Found(results.swap_remove(index))),
+        (None, 0) => bail!(
+            "No GNOME extensions match '{}' on extensions.gnome.org.\n\n\
+             To browse extensions:\n  \
+             https://extensions.gnome.org",
+            query
+        ),
+        (None, 1) => Ok(ExtensionMatch::Found(results.remove(0))),
+        (None, _) => Ok(ExtensionMatch::Ambiguous(results)),
+    }
+}
+
+/// Check that an extension has a release for GNOME Shell `shell_version`.
+pub fn validate_extension_shell_support(
+    info: &GnomeExtensionInfo,
+    shell_version: &str,
+) -> Result<()> {
+    if info.supports_shell(shell_version) {
+        return Ok(());
+    }
+
     bail!(
-        "GNOME extension '{}' not found on extensions.gnome.org.\n\n\
-         To browse extensions:\n  \
-         https://extensions.gnome.org\n\n\
-         Verify the exact UUID from the extension's page URL.",
-        uuid
+        "GNOME extension '{}' ({}) does not support GNOME Shell {}.\n\n\
+         Supported versions: {}",
+        info.name,
+        info.uuid,
+        shell_version,
+        info.shell_versions().join(", ")
     );
 }
 
+/// The running GNOME Shell version (e.g. "47.2"), if gnome-shell is available.
+pub fn gnome_shell_version(runner: &dyn CommandRunner) -> Option<String> {
+    let output = runner
+        .run_output("gnome-shell", &["--version"], &CommandOptions::default())
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // "GNOME Shell 47.2"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .last()
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,6 +701,143 @@ mod tests {
         }
     }
 
+    struct FakeExtensionsApi {
+        extensions: Vec<GnomeExtensionInfo>,
+    }
+
+    impl ExtensionsApi for FakeExtensionsApi {
+        fn info(&self, lookup: &ExtensionLookup) -> Result<Option<GnomeExtensionInfo>> {
+            Ok(self
+                .extensions
+                .iter()
+                .find(|ext| match lookup {
+                    ExtensionLookup::Uuid(uuid) => &ext.uuid == uuid,
+                    ExtensionLookup::Pk(pk) => ext.pk == *pk,
+                })
+                .cloned())
+        }
+
+        fn search(&self, query: &str) -> Result<Vec<GnomeExtensionInfo>> {
+            let query = query.to_lowercase();
+            Ok(self
+                .extensions
+                .iter()
+                .filter(|ext| ext.name.to_lowercase().contains(&query))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn extension(uuid: &str, name: &str, pk: u64, shells: &[&str]) -> GnomeExtensionInfo {
+        GnomeExtensionInfo {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            pk,
+            shell_version_map: shells
+                .iter()
+                .map(|v| (v.to_string(), serde_json::json!({})))
+                .collect(),
+        }
+    }
+
+    fn fake_api() -> FakeExtensionsApi {
+        FakeExtensionsApi {
+            extensions: vec![
+                extension(
+                    "dash-to-dock@micxgx.gmail.com",
+                    "Dash to Dock",
+                    307,
+                    &["46", "47"],
+                ),
+                extension(
+                    "dash-to-panel@jderose9.github.com",
+                    "Dash to Panel",
+                    1160,
+                    &["47"],
+                ),
+                extension(
+                    "appindicatorsupport@rgcjonas.gmail.com",
+                    "AppIndicator",
+                    615,
+                    &["3.38"],
+                ),
+            ],
+        }
+    }
+
+    fn found_uuid(result: ExtensionMatch) -> String {
+        match result {
+            ExtensionMatch::Found(info) => info.uuid,
+            ExtensionMatch::Ambiguous(matches) => panic!("ambiguous: {:?}", matches),
+        }
+    }
+
+    #[test]
+    fn resolve_extension_by_uuid_pk_and_exact_name() {
+        let api = fake_api();
+        for query in ["dash-to-dock@micxgx.gmail.com", "307", "dash to dock"] {
+            assert_eq!(
+                found_uuid(resolve_gnome_extension(&api, query).unwrap()),
+                "dash-to-dock@micxgx.gmail.com",
+                "query {query}"
+            );
+        }
+        assert_eq!(
+            found_uuid(resolve_gnome_extension(&api, "appindicator").unwrap()),
+            "appindicatorsupport@rgcjonas.gmail.com"
+        );
+    }
+
+    #[test]
+    fn resolve_extension_reports_ambiguity_and_misses() {
+        let api = fake_api();
+        match resolve_gnome_extension(&api, "dash").unwrap() {
+            ExtensionMatch::Ambiguous(matches) => assert_eq!(matches.len(), 2),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+
+        let err = resolve_gnome_extension(&api, "missing@example.com").unwrap_err();
+        assert!(err.to_string().contains("not found"));
+        let err = resolve_gnome_extension(&api, "nothing-like-this").unwrap_err();
+        assert!(err.to_string().contains("No GNOME extensions match"));
+    }
+
+    #[test]
+    fn extension_shell_support_uses_major_or_major_minor() {
+        let api = fake_api();
+        let dock = &api.extensions[0];
+        assert!(dock.supports_shell("47.2"));
+        assert!(!dock.supports_shell("45.0"));
+        assert!(api.extensions[2].supports_shell("3.38.4"));
+
+        let err = validate_extension_shell_support(dock, "45.1").unwrap_err();
+        assert!(err.to_string().contains("Supported versions: 46, 47"));
+    }
+
+    #[test]
+    fn extension_release_for_shell_reads_version_tag() {
+        let info: GnomeExtensionInfo = serde_json::from_str(
+            r#"{
+                "uuid": "caffeine@patapon.info",
+                "name": "Caffeine",
+                "pk": 517,
+                "shell_version_map": {
+                    "46": {"pk": 60512, "version": 53},
+                    "47": {"pk": 61890, "version": 55}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.release_for_shell("47.1"), Some(61890));
+        assert_eq!(info.release_for_shell("45.0"), None);
+        assert_eq!(
+            info.download_url(61890),
+            "https://extensions.gnome.org/download-extension/\
+             caffeine%40patapon.info.shell-extension.zip?version_tag=61890"
+        );
+    }
+
     #[test]
     fn test_dnf_validation_format() {
         // This test documents the error message format
@@ -331,4 +853,42 @@ mod tests {
             );
         }
     }
+
+    const FLATHUB: &str = "[Flatpak Repo]\n\
+        Title=Flathub\n\
+        Url=https://dl.flathub.org/repo/\n\
+        Homepage=https://flathub.org/\n\
+        Comment=Central repository of Flatpak applications\n\
+        GPGKey=a2V5IG1hdGVyaWFs\n";
+
+    #[test]
+    fn parses_flatpakrepo_files() {
+        use sha2::Digest;
+
+        let repo = parse_flatpakrepo(FLATHUB).unwrap();
+        assert_eq!(repo.title.as_deref(), Some("Flathub"));
+        assert_eq!(repo.url, "https://dl.flathub.org/repo/");
+        assert_eq!(repo.homepage.as_deref(), Some("https://flathub.org/"));
+        assert_eq!(
+            repo.gpg_key_sha256,
+            format!("{:x}", sha2::Sha256::digest(b"key material"))
+        );
+
+        // Keys in other groups don't count
+        let other_group = "[Other]\nUrl=https://example.com/\n";
+        assert!(parse_flatpakrepo(other_group).is_err());
+    }
+
+    #[test]
+    fn flatpakrepo_without_key_or_url_is_rejected() {
+        let no_key = FLATHUB.replace("GPGKey=a2V5IG1hdGVyaWFs\n", "");
+        let err = parse_flatpakrepo(&no_key).unwrap_err();
+        assert!(err.to_string().contains("no GPGKey"));
+
+        let no_url = FLATHUB.replace("Url=https://dl.flathub.org/repo/\n", "");
+        assert!(parse_flatpakrepo(&no_url).is_err());
+
+        assert!(parse_flatpakrepo("<html>Not Found</html>").is_err());
+        assert!(parse_flatpakrepo(&FLATHUB.replace("a2V5IG1hdGVyaWFs", "not base64!")).is_err());
+    }
 }
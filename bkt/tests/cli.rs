@@ -134,6 +134,71 @@ fn shim_remove_requires_name() {
         .stderr(predicate::str::contains("NAME"));
 }
 
+#[test]
+fn shim_sync_dry_run_format_json_prints_plan() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("manifests/host-shims.json")
+        .write_str(r#"{"shims": [{"name": "podman"}, {"name": "docker", "host": "podman"}]}"#)
+        .unwrap();
+
+    let output = bkt_isolated(&temp)
+        .args(["shim", "sync", "--dry-run", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ops = plan["operations"].as_array().unwrap();
+    assert_eq!(ops.len(), 2);
+    assert!(ops.iter().all(|op| op["subsystem"] == "shim"));
+    assert!(ops.iter().all(|op| op["verb"] == "create"));
+    assert!(ops.iter().any(|op| op["target"] == "shim:docker"));
+}
+
+#[test]
+fn shim_sync_plans_removal_of_orphaned_shims() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("manifests/host-shims.json")
+        .write_str(r#"{"shims": [{"name": "podman"}]}"#)
+        .unwrap();
+    temp.child(".local/toolbox/shims/nmcli")
+        .write_str("#!/bin/bash\n# Managed by: bkt shim\nexec flatpak-spawn --host nmcli \"$@\"\n")
+        .unwrap();
+    temp.child(".local/toolbox/shims/mine")
+        .write_str("#!/bin/sh\necho hand-written\n")
+        .unwrap();
+
+    let plan_ops = |extra: &[&str]| {
+        let output = bkt_isolated(&temp)
+            .args(["shim", "sync", "--dry-run", "--format", "json"])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        plan["operations"].as_array().unwrap().clone()
+    };
+
+    let ops = plan_ops(&[]);
+    assert_eq!(ops.len(), 2);
+    assert_eq!(ops[0]["verb"], "create");
+    assert_eq!(ops[0]["target"], "shim:podman");
+    assert_eq!(ops[1]["verb"], "remove");
+    assert_eq!(ops[1]["target"], "shim:nmcli");
+
+    let ops = plan_ops(&["--keep-unmanaged"]);
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0]["verb"], "create");
+}
+
+#[test]
+fn shim_list_own_format_flag_shadows_global() {
+    bkt()
+        .args(["shim", "list", "--format", "json"])
+        .assert()
+        .success();
+}
+
 // ============================================================================
 // Flatpak command tests
 // ============================================================================
@@ -207,12 +272,12 @@ fn extension_list_succeeds() {
 }
 
 #[test]
-fn extension_add_requires_uuid() {
+fn extension_add_requires_query() {
     bkt()
         .args(["extension", "add"])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("UUID"));
+        .stderr(predicate::str::contains("<QUERY>"));
 }
 
 #[test]
@@ -412,6 +477,82 @@ fn shim_add_and_remove_integration() {
     temp.close().unwrap();
 }
 
+#[test]
+fn kargs_preset_apply_updates_manifest_and_containerfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("Containerfile")
+        .write_str(
+            "FROM scratch\n\n# === KERNEL_ARGUMENTS (managed by bkt) ===\n\
+             # No kernel arguments configured\n# === END KERNEL_ARGUMENTS ===\n",
+        )
+        .unwrap();
+
+    bkt_isolated(&temp)
+        .current_dir(temp.path())
+        .args(["admin", "kargs", "preset", "apply", "quiet-boot"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added karg: loglevel=3"));
+
+    let manifest =
+        std::fs::read_to_string(temp.path().join("manifests/system-config.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    assert_eq!(manifest["kargs"]["append"][0], "quiet");
+    assert_eq!(
+        manifest["kargs"]["presets"]["quiet-boot"]["append"][2],
+        "loglevel=3"
+    );
+    let containerfile = std::fs::read_to_string(temp.path().join("Containerfile")).unwrap();
+    assert!(containerfile.contains("--append=loglevel=3"));
+
+    bkt_isolated(&temp)
+        .current_dir(temp.path())
+        .args(["admin", "kargs", "preset", "apply", "verbose-boot"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "loglevel=7 vs loglevel=3 (from quiet-boot)",
+        ));
+
+    bkt_isolated(&temp)
+        .current_dir(temp.path())
+        .args(["admin", "kargs", "preset", "remove", "quiet-boot"])
+        .assert()
+        .success();
+    let containerfile = std::fs::read_to_string(temp.path().join("Containerfile")).unwrap();
+    assert!(containerfile.contains("# No kernel arguments configured"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn quiet_flag_suppresses_status_but_not_data() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    bkt_isolated(&temp)
+        .args(["--quiet", "shim", "add", "quiet-shim"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added shim").not());
+    bkt_isolated(&temp)
+        .args(["shim", "list", "-q"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("quiet-shim").and(predicate::str::contains("SHIMS").not()),
+        );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn quiet_and_verbose_conflict() {
+    bkt()
+        .args(["-q", "-v", "shim", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn shim_add_with_host_option() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -695,16 +836,83 @@ fn drift_explain_succeeds() {
 }
 
 #[test]
-fn drift_check_shows_not_implemented() {
-    // drift check currently shows a stub message since the Rust implementation
-    // is not yet complete (see RFC 0007). It succeeds with a warning.
+fn drift_rejects_unknown_subsystem() {
     bkt()
-        .args(["drift", "check"])
+        .args(["drift", "--subsystem", "nope"])
         .assert()
-        .success()
-        .stdout(predicate::str::contains(
-            "Drift detection is not yet implemented",
-        ));
+        .failure()
+        .stderr(predicate::str::contains("Unknown subsystem 'nope'"))
+        .stderr(predicate::str::contains("flatpak"));
+}
+
+#[test]
+fn drift_json_conflicts_with_fix() {
+    bkt()
+        .args(["drift", "check", "--json", "--fix", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+// ============================================================================
+// Diff command tests
+// ============================================================================
+
+#[test]
+fn diff_compares_against_manifests_at_ref() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("manifests/host-shims.json")
+        .write_str(r#"{"shims": [{"name": "podman"}]}"#)
+        .unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(temp.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "shims"]);
+    // Uncommitted edits don't count
+    temp.child("manifests/host-shims.json")
+        .write_str(r#"{"shims": []}"#)
+        .unwrap();
+
+    let output = bkt_isolated(&temp)
+        .args(["diff", "--ref", "HEAD", "--json", "-s", "shim,homebrew"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diff["ref"], "HEAD");
+    let subsystems = diff["subsystems"].as_array().unwrap();
+    let shim = subsystems.iter().find(|s| s["id"] == "shim").unwrap();
+    assert_eq!(shim["tracked"], true);
+    assert_eq!(shim["missing"], serde_json::json!(["podman"]));
+    let homebrew = subsystems.iter().find(|s| s["id"] == "homebrew").unwrap();
+    assert_eq!(homebrew["tracked"], false);
+    assert!(homebrew.get("extra").is_none());
+}
+
+#[test]
+fn diff_rejects_unknown_ref() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(temp.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    bkt_isolated(&temp)
+        .args(["diff", "--ref", "no-such-ref"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no-such-ref"));
 }
 
 // ============================================================================
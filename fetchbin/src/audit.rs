@@ -0,0 +1,249 @@
+use crate::manifest::{InstalledBinary, SourceSpec};
+use bkt_common::checksum::sha256_hex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What a bin dir link of an installed binary points at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum LinkState {
+    /// Points into the store dir of the recorded version.
+    Ok { target: PathBuf },
+    /// No link in the bin dir.
+    Missing,
+    /// Not a symlink; something replaced the link.
+    NotSymlink,
+    /// Points at a file that no longer exists.
+    Broken { target: PathBuf },
+    /// Points outside the recorded version's store dir, e.g. left over
+    /// from an update that didn't finish.
+    Stale { target: PathBuf },
+}
+
+/// One link of an installed binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkAudit {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: LinkState,
+}
+
+/// How the primary binary on disk compares to the recorded sha256.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum HashState {
+    Match,
+    Mismatch {
+        actual: String,
+    },
+    /// npm entries record the tarball's hash, not the generated wrapper's.
+    NotRecorded,
+    /// The link is broken or the file couldn't be read.
+    Unreadable {
+        error: String,
+    },
+}
+
+/// The result of checking one installed binary against the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BinaryAudit {
+    pub name: String,
+    pub links: Vec<LinkAudit>,
+    pub hash: HashState,
+}
+
+impl BinaryAudit {
+    /// Check the links of `installed` in `bin_dir`, and the primary
+    /// binary's hash. `store_dir` is where the recorded version lives.
+    pub fn check(
+        name: &str,
+        installed: &InstalledBinary,
+        bin_dir: &Path,
+        store_dir: &Path,
+    ) -> Self {
+        let links: Vec<LinkAudit> = installed
+            .linked_binaries()
+            .into_iter()
+            .map(|link| LinkAudit {
+                name: link.to_string(),
+                state: link_state(&bin_dir.join(link), store_dir),
+            })
+            .collect();
+
+        let hash = if matches!(installed.source, SourceSpec::Npm { .. }) {
+            HashState::NotRecorded
+        } else {
+            match fs::read(bin_dir.join(&installed.binary)) {
+                Ok(bytes) => {
+                    let actual = sha256_hex(&bytes);
+                    if actual.eq_ignore_ascii_case(&installed.sha256) {
+                        HashState::Match
+                    } else {
+                        HashState::Mismatch { actual }
+                    }
+                }
+                Err(err) => HashState::Unreadable {
+                    error: err.to_string(),
+                },
+            }
+        };
+
+        Self {
+            name: name.to_string(),
+            links,
+            hash,
+        }
+    }
+
+    /// Every link resolves into the store and the hash didn't change.
+    pub fn is_healthy(&self) -> bool {
+        self.problems().is_empty()
+    }
+
+    /// One line per problem found, empty when healthy.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .links
+            .iter()
+            .filter_map(|link| match &link.state {
+                LinkState::Ok { .. } => None,
+                LinkState::Missing => Some(format!("{}: link is missing", link.name)),
+                LinkState::NotSymlink => Some(format!("{}: replaced by a regular file", link.name)),
+                LinkState::Broken { target } => Some(format!(
+                    "{}: broken link to {}",
+                    link.name,
+                    target.display()
+                )),
+                LinkState::Stale { target } => Some(format!(
+                    "{}: points outside the installed version ({})",
+                    link.name,
+                    target.display()
+                )),
+            })
+            .collect();
+        match &self.hash {
+            HashState::Mismatch { actual } => {
+                problems.push(format!("sha256 mismatch (on disk: {actual})"));
+            }
+            // A broken link is already reported above
+            HashState::Unreadable { error } if problems.is_empty() => {
+                problems.push(format!("cannot read binary: {error}"));
+            }
+            _ => {}
+        }
+        problems
+    }
+}
+
+fn link_state(link: &Path, store_dir: &Path) -> LinkState {
+    let Ok(metadata) = link.symlink_metadata() else {
+        return LinkState::Missing;
+    };
+    if !metadata.file_type().is_symlink() {
+        return LinkState::NotSymlink;
+    }
+    let Ok(target) = fs::read_link(link) else {
+        return LinkState::Missing;
+    };
+    // Relative targets resolve against the bin dir
+    let target = match link.parent() {
+        Some(dir) => dir.join(target),
+        None => target,
+    };
+    if !target.exists() {
+        return LinkState::Broken { target };
+    }
+    if target.starts_with(store_dir) {
+        LinkState::Ok { target }
+    } else {
+        LinkState::Stale { target }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn installed(sha256: &str) -> InstalledBinary {
+        InstalledBinary {
+            source: SourceSpec::Github {
+                repo: "BurntSushi/ripgrep".to_string(),
+                version: "14.1.0".to_string(),
+                asset: "ripgrep.tar.gz".to_string(),
+                include_prerelease: false,
+            },
+            binary: "rg".to_string(),
+            version_req: None,
+            binaries: Vec::new(),
+            sha256: sha256.to_string(),
+            installed_at: "0".to_string(),
+            runtime: None,
+            pinned_version: None,
+            native_package: None,
+            libc: None,
+        }
+    }
+
+    #[test]
+    fn test_check_flags_hash_mismatch_and_stale_links() {
+        let temp = tempfile::tempdir().unwrap();
+        let bin_dir = temp.path().join("bin");
+        let store = temp.path().join("store/14.1.0");
+        let old_store = temp.path().join("store/14.0.0");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::create_dir_all(&store).unwrap();
+        fs::create_dir_all(&old_store).unwrap();
+        fs::write(store.join("rg"), b"rg").unwrap();
+        fs::write(old_store.join("rg"), b"old rg").unwrap();
+
+        symlink(store.join("rg"), bin_dir.join("rg")).unwrap();
+        let audit = BinaryAudit::check("rg", &installed(&sha256_hex(b"rg")), &bin_dir, &store);
+        assert!(audit.is_healthy(), "{:?}", audit.problems());
+
+        fs::write(store.join("rg"), b"tampered").unwrap();
+        let audit = BinaryAudit::check("rg", &installed(&sha256_hex(b"rg")), &bin_dir, &store);
+        assert_eq!(
+            audit.hash,
+            HashState::Mismatch {
+                actual: sha256_hex(b"tampered")
+            }
+        );
+
+        fs::remove_file(bin_dir.join("rg")).unwrap();
+        symlink(old_store.join("rg"), bin_dir.join("rg")).unwrap();
+        let audit = BinaryAudit::check("rg", &installed(&sha256_hex(b"old rg")), &bin_dir, &store);
+        assert!(!audit.is_healthy());
+        assert_eq!(
+            audit.links[0].state,
+            LinkState::Stale {
+                target: old_store.join("rg")
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_reports_broken_and_missing_links() {
+        let temp = tempfile::tempdir().unwrap();
+        let bin_dir = temp.path().join("bin");
+        let store = temp.path().join("store");
+        fs::create_dir_all(&bin_dir).unwrap();
+        symlink(store.join("rg"), bin_dir.join("rg")).unwrap();
+
+        let mut entry = installed("abc");
+        entry.binaries = vec!["rg".to_string(), "rg-extra".to_string()];
+        let audit = BinaryAudit::check("rg", &entry, &bin_dir, &store);
+
+        assert_eq!(
+            audit.links[0].state,
+            LinkState::Broken {
+                target: store.join("rg")
+            }
+        );
+        assert_eq!(audit.links[1].state, LinkState::Missing);
+        assert!(matches!(audit.hash, HashState::Unreadable { .. }));
+        assert_eq!(audit.problems().len(), 2);
+        assert!(!audit.is_healthy());
+    }
+}
@@ -0,0 +1,345 @@
+//! The C library an ELF binary needs.
+//!
+//! Release binaries built on a new distro fail on older ones with
+//! `GLIBC_2.38 not found`, but only once they run. Reading the binary's
+//! interpreter and its versioned glibc symbol references at install time
+//! catches that up front.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+const PT_INTERP: u32 = 3;
+const SHT_GNU_VERNEED: u32 = 0x6fff_fffe;
+
+/// What a binary needs from the system's C library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibcRequirement {
+    /// Statically linked or built against musl: runs on any Linux of the
+    /// right architecture.
+    Portable,
+    /// Dynamically linked against glibc, needing at least this version.
+    Glibc(GlibcVersion),
+}
+
+impl LibcRequirement {
+    /// Inspect the binary at `path`. `None` when it isn't an ELF executable
+    /// (scripts, npm entry points) or names no glibc version.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        let elf = Elf::parse(&data)?;
+        match elf.interpreter() {
+            None => Some(LibcRequirement::Portable),
+            Some(interp) if interp.contains("musl") => Some(LibcRequirement::Portable),
+            Some(_) => elf.max_glibc_version().map(LibcRequirement::Glibc),
+        }
+    }
+
+    /// Whether a system with `host` glibc can run it.
+    pub fn is_met_by(&self, host: &GlibcVersion) -> bool {
+        match self {
+            LibcRequirement::Portable => true,
+            LibcRequirement::Glibc(needed) => needed <= host,
+        }
+    }
+}
+
+impl fmt::Display for LibcRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibcRequirement::Portable => write!(f, "portable (static/musl)"),
+            LibcRequirement::Glibc(version) => write!(f, "glibc >= {version}"),
+        }
+    }
+}
+
+/// A glibc version such as `2.38`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GlibcVersion {
+    parts: Vec<u32>,
+}
+
+impl GlibcVersion {
+    /// The glibc of the running system, from `ldd --version`; `None` on
+    /// musl systems or when `ldd` isn't there.
+    pub fn host() -> Option<Self> {
+        let output = Command::new("ldd").arg("--version").output().ok()?;
+        parse_ldd_version(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+impl FromStr for GlibcVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value
+            .split('.')
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("invalid glibc version '{value}'"))?;
+        Ok(Self { parts })
+    }
+}
+
+impl TryFrom<String> for GlibcVersion {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GlibcVersion> for String {
+    fn from(version: GlibcVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl fmt::Display for GlibcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.parts.iter().map(u32::to_string).collect();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+impl PartialOrd for GlibcVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GlibcVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parts.cmp(&other.parts)
+    }
+}
+
+/// The version from `ldd --version` output, whose first line ends with it
+/// (`ldd (GNU libc) 2.39`, `ldd (Debian GLIBC 2.36-9+deb12u4) 2.36`).
+fn parse_ldd_version(output: &str) -> Option<GlibcVersion> {
+    let first = output.lines().next()?;
+    if !first.contains("GNU libc") && !first.contains("GLIBC") {
+        return None;
+    }
+    first.split_whitespace().last()?.parse().ok()
+}
+
+/// Just enough of an ELF file to find its interpreter and version needs.
+struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 0x34 || &data[..4] != b"\x7fELF" {
+            return None;
+        }
+        let is_64 = match data[4] {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let little_endian = match data[5] {
+            1 => true,
+            2 => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            is_64,
+            little_endian,
+        })
+    }
+
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(match self.little_endian {
+            true => u16::from_le_bytes(bytes),
+            false => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match self.little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        })
+    }
+
+    /// A file offset or size: 8 bytes in ELF64, 4 in ELF32.
+    fn word_at(&self, offset: usize) -> Option<usize> {
+        if !self.is_64 {
+            return self.u32_at(offset).map(|v| v as usize);
+        }
+        let bytes: [u8; 8] = self.data.get(offset..offset + 8)?.try_into().ok()?;
+        let value = match self.little_endian {
+            true => u64::from_le_bytes(bytes),
+            false => u64::from_be_bytes(bytes),
+        };
+        usize::try_from(value).ok()
+    }
+
+    fn c_str_at(&self, offset: usize) -> Option<&'a str> {
+        let rest = self.data.get(offset..)?;
+        let end = rest.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&rest[..end]).ok()
+    }
+
+    /// The `PT_INTERP` path (the dynamic loader), absent for static binaries.
+    fn interpreter(&self) -> Option<&'a str> {
+        let (phoff, entsize, count) = match self.is_64 {
+            true => (self.word_at(0x20)?, self.u16_at(0x36)?, self.u16_at(0x38)?),
+            false => (self.word_at(0x1c)?, self.u16_at(0x2a)?, self.u16_at(0x2c)?),
+        };
+        (0..count as usize).find_map(|i| {
+            let header = phoff + i * entsize as usize;
+            if self.u32_at(header)? != PT_INTERP {
+                return None;
+            }
+            let offset = match self.is_64 {
+                true => self.word_at(header + 0x08)?,
+                false => self.word_at(header + 0x04)?,
+            };
+            self.c_str_at(offset)
+        })
+    }
+
+    /// The highest `GLIBC_x.y` among the symbol versions the binary needs.
+    fn max_glibc_version(&self) -> Option<GlibcVersion> {
+        let (shoff, entsize, count) = match self.is_64 {
+            true => (self.word_at(0x28)?, self.u16_at(0x3a)?, self.u16_at(0x3c)?),
+            false => (self.word_at(0x20)?, self.u16_at(0x2e)?, self.u16_at(0x30)?),
+        };
+        let section = |i: usize| {
+            let header = shoff + i * entsize as usize;
+            let kind = self.u32_at(header + 4)?;
+            let (offset, link) = match self.is_64 {
+                true => (self.word_at(header + 0x18)?, self.u32_at(header + 0x28)?),
+                false => (self.word_at(header + 0x10)?, self.u32_at(header + 0x18)?),
+            };
+            Some((kind, offset, link as usize))
+        };
+
+        let mut max: Option<GlibcVersion> = None;
+        for i in 0..count as usize {
+            let Some((SHT_GNU_VERNEED, verneed, strtab)) = section(i) else {
+                continue;
+            };
+            let (_, strings, _) = section(strtab)?;
+            for name in self.version_needs(verneed, strings) {
+                let Some(version) = name.strip_prefix("GLIBC_") else {
+                    continue;
+                };
+                // GLIBC_PRIVATE and the like aren't versions
+                if let Ok(version) = version.parse::<GlibcVersion>() {
+                    if max.as_ref().is_none_or(|max| version > *max) {
+                        max = Some(version);
+                    }
+                }
+            }
+        }
+        max
+    }
+
+    /// Names in a `.gnu.version_r` section: `Verneed` entries, each with a
+    /// chain of `Vernaux` entries naming a version. Same layout in ELF32/64.
+    fn version_needs(&self, offset: usize, strings: usize) -> Vec<&'a str> {
+        let mut names = Vec::new();
+        let mut need = offset;
+        while let (Some(count), Some(aux), Some(next)) = (
+            self.u16_at(need + 2),
+            self.u32_at(need + 8),
+            self.u32_at(need + 12),
+        ) {
+            let mut entry = need + aux as usize;
+            for _ in 0..count {
+                let (Some(name), Some(next_aux)) =
+                    (self.u32_at(entry + 8), self.u32_at(entry + 12))
+                else {
+                    break;
+                };
+                if let Some(name) = self.c_str_at(strings + name as usize) {
+                    names.push(name);
+                }
+                entry += next_aux as usize;
+            }
+            if next == 0 {
+                break;
+            }
+            need += next as usize;
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glibc_versions_compare_numerically() {
+        let v = |s: &str| s.parse::<GlibcVersion>().unwrap();
+        assert!(v("2.38") > v("2.9"));
+        assert!(v("2.17") < v("2.17.1"));
+        assert!(LibcRequirement::Glibc(v("2.36")).is_met_by(&v("2.39")));
+        assert!(!LibcRequirement::Glibc(v("2.38")).is_met_by(&v("2.36")));
+        assert!(LibcRequirement::Portable.is_met_by(&v("2.17")));
+        assert!("PRIVATE".parse::<GlibcVersion>().is_err());
+    }
+
+    #[test]
+    fn parses_ldd_version_output() {
+        let fedora = "ldd (GNU libc) 2.39\nCopyright (C) 2024 Free Software Foundation, Inc.\n";
+        assert_eq!(parse_ldd_version(fedora), Some("2.39".parse().unwrap()));
+        let debian = "ldd (Debian GLIBC 2.36-9+deb12u4) 2.36\n";
+        assert_eq!(parse_ldd_version(debian), Some("2.36".parse().unwrap()));
+        assert_eq!(
+            parse_ldd_version("musl libc (x86_64)\nVersion 1.2.4\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn requirement_round_trips_through_json() {
+        let glibc = LibcRequirement::Glibc("2.38".parse().unwrap());
+        let json = serde_json::to_string(&glibc).unwrap();
+        assert_eq!(json, r#"{"glibc":"2.38"}"#);
+        assert_eq!(
+            serde_json::from_str::<LibcRequirement>(&json).unwrap(),
+            glibc
+        );
+        assert_eq!(
+            serde_json::to_string(&LibcRequirement::Portable).unwrap(),
+            r#""portable""#
+        );
+    }
+
+    #[test]
+    fn detects_requirements_of_real_binaries() {
+        let temp = tempfile::tempdir().unwrap();
+        let script = temp.path().join("tool");
+        fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(LibcRequirement::detect(&script), None);
+
+        // The test binary itself links glibc dynamically on the usual targets
+        let exe = std::env::current_exe().unwrap();
+        if cfg!(all(target_os = "linux", target_env = "gnu")) {
+            let Some(LibcRequirement::Glibc(needed)) = LibcRequirement::detect(&exe) else {
+                panic!("expected a glibc requirement for {}", exe.display());
+            };
+            assert!(needed >= "2.2".parse().unwrap());
+            if let Some(host) = GlibcVersion::host() {
+                assert!(needed <= host);
+            }
+        }
+    }
+}
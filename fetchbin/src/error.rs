@@ -10,9 +10,18 @@ impl From<bkt_common::error::CommonError> for FetchError {
                     "checksum mismatch: expected {expected}, got {actual}"
                 ))
             }
-            bkt_common::error::CommonError::Http(message) => FetchError::Network(message),
+            bkt_common::error::CommonError::Http(message)
+            | bkt_common::error::CommonError::Network(message) => FetchError::Network(message),
+            error @ bkt_common::error::CommonError::HttpStatus { .. } => {
+                FetchError::Network(error.to_string())
+            }
             bkt_common::error::CommonError::Manifest(message) => FetchError::Parse(message),
             bkt_common::error::CommonError::Json(err) => FetchError::Parse(err.to_string()),
+            error @ (bkt_common::error::CommonError::GpgKey(_)
+            | bkt_common::error::CommonError::FingerprintMismatch { .. }
+            | bkt_common::error::CommonError::InvalidChecksum(_)) => {
+                FetchError::Parse(error.to_string())
+            }
         }
     }
 }
@@ -20,9 +29,13 @@ impl From<bkt_common::error::CommonError> for FetchError {
 impl From<bkt_common::error::CommonError> for RuntimeError {
     fn from(error: bkt_common::error::CommonError) -> Self {
         match error {
-            bkt_common::error::CommonError::Http(message) => {
+            bkt_common::error::CommonError::Http(message)
+            | bkt_common::error::CommonError::Network(message) => {
                 RuntimeError::BinstallDownloadFailed(message)
             }
+            error @ bkt_common::error::CommonError::HttpStatus { .. } => {
+                RuntimeError::BinstallDownloadFailed(error.to_string())
+            }
             bkt_common::error::CommonError::Io(err) => RuntimeError::Io(err),
             bkt_common::error::CommonError::Archive(message) => {
                 RuntimeError::BinstallDownloadFailed(message)
@@ -36,6 +49,11 @@ impl From<bkt_common::error::CommonError> for RuntimeError {
             }
             bkt_common::error::CommonError::Manifest(message) => RuntimeError::Config(message),
             bkt_common::error::CommonError::Json(err) => RuntimeError::Config(err.to_string()),
+            error @ (bkt_common::error::CommonError::GpgKey(_)
+            | bkt_common::error::CommonError::FingerprintMismatch { .. }
+            | bkt_common::error::CommonError::InvalidChecksum(_)) => {
+                RuntimeError::Config(error.to_string())
+            }
         }
     }
 }
@@ -53,26 +71,54 @@ pub enum FetchError {
         pattern: String,
         available: Vec<String>,
     },
+    #[error("no asset found matching pattern '{pattern}'. Available: {}. To build from source instead, pass --fallback cargo", available.join(", "))]
+    AssetNotFoundNoFallback {
+        pattern: String,
+        available: Vec<String>,
+    },
     #[error("no download url for version {version}")]
     NoDownloadUrl { version: String },
     #[error("GitHub API error: {0}")]
     GitHubApi(String),
+    #[error("GitLab API error: {0}")]
+    GitLabApi(String),
     #[error("npm registry error: {0}")]
     NpmRegistry(String),
-    #[error("crates.io api error: {0}")]
-    CratesIoApi(String),
+    #[error("crates.io index error: {0}")]
+    CratesIndex(String),
     #[error("binary not found for package {package}. searched: {}", searched.join(", "))]
-    BinaryNotFound { package: String, searched: Vec<String> },
+    BinaryNotFound {
+        package: String,
+        searched: Vec<String>,
+    },
     #[error("package {package} requires install scripts")]
     RequiresScripts { package: String },
     #[error("multiple binaries found: {}", binaries.join(", "))]
     MultipleBinaries { binaries: Vec<String> },
     #[error("pnpm install failed: {0}")]
     PnpmInstallFailed(String),
+    #[error("package {package} declares bin {name} as {path}, but the package has no such file")]
+    MissingBinEntry {
+        package: String,
+        name: String,
+        path: String,
+    },
+    #[error("{binary} failed its smoke test: {detail}. Pass --no-verify to install it anyway")]
+    SmokeTestFailed { binary: String, detail: String },
     #[error("cargo-binstall failed: {0}")]
     BinstallFailed(String),
     #[error("unsupported archive format: {0}")]
     UnsupportedArchive(String),
+    #[error("checksum mismatch for {name}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("local path not found: {0}")]
+    LocalPathNotFound(String),
+    #[error("no executable found in {0}; pass --bin to choose one")]
+    NoExecutable(String),
     #[error("unimplemented source")]
     Unimplemented,
 }
@@ -117,6 +163,8 @@ pub enum ManifestError {
     Io(#[from] std::io::Error),
     #[error("manifest parse error: {0}")]
     Parse(#[from] serde_json::Error),
+    #[error("export format version {0} is newer than this fetchbin understands")]
+    UnsupportedExport(u32),
 }
 
 #[cfg(test)]
@@ -0,0 +1,393 @@
+//! The portable list of installed binaries behind `fetchbin export` and
+//! `fetchbin import`.
+//!
+//! An export records how each binary was asked for (source spec, version
+//! requirement, asset pattern, bin selection) rather than where it lives,
+//! so it can be replayed on another machine. The format is stable: fields
+//! may be added, but anything that changes the meaning of an existing one
+//! bumps [`EXPORT_VERSION`].
+
+use crate::error::{FetchError, ManifestError};
+use crate::manifest::{InstalledBinary, Manifest, SourceSpec};
+use crate::source::{filter_by_requirement, PackageSpec, ResolvedVersion, SourceConfig};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The export format version this build writes and understands.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Everything `fetchbin export` wrote, in name order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Export {
+    /// Format version, currently 1; files from a newer fetchbin are refused.
+    pub version: u32,
+    pub binaries: Vec<ExportedBinary>,
+}
+
+/// One installed binary, as `fetchbin import` will reinstall it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExportedBinary {
+    /// The manifest entry, named after its primary binary.
+    pub name: String,
+    /// Source spec without a version, as given to `fetchbin install`
+    /// (e.g. `github:sharkdp/fd`, `npm:turbo`).
+    pub spec: String,
+    /// The version that was installed; `import --frozen` installs exactly this.
+    pub version: String,
+    /// The requirement it was installed with (e.g. `^14`); a plain import
+    /// installs the latest version that satisfies it, or the latest of all
+    /// when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_req: Option<String>,
+    /// Release asset pattern, for github and gitlab sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+    /// The binary linked from the package.
+    pub bin: String,
+    /// Link every executable the package ships (`--all-bins`).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub all_bins: bool,
+    /// Consider pre-releases (`--include-prerelease`), for github sources.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub include_prerelease: bool,
+    /// Held at `version` by `fetchbin pin`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Export {
+    /// The export of every binary in `manifest`.
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        let mut binaries: Vec<ExportedBinary> = manifest
+            .binaries
+            .iter()
+            .map(|(name, installed)| ExportedBinary::from_installed(name, installed))
+            .collect();
+        binaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            version: EXPORT_VERSION,
+            binaries,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ManifestError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, ManifestError> {
+        let export: Self = serde_json::from_str(content)?;
+        if export.version > EXPORT_VERSION {
+            return Err(ManifestError::UnsupportedExport(export.version));
+        }
+        Ok(export)
+    }
+}
+
+impl ExportedBinary {
+    pub fn from_installed(name: &str, installed: &InstalledBinary) -> Self {
+        let (spec, asset) = match &installed.source {
+            SourceSpec::Npm { package, .. } => (format!("npm:{package}"), None),
+            SourceSpec::Cargo { crate_name, .. } => (format!("cargo:{crate_name}"), None),
+            SourceSpec::Github { repo, asset, .. } => (format!("github:{repo}"), Some(asset)),
+            SourceSpec::Gitlab { repo, asset, .. } => (format!("gitlab:{repo}"), Some(asset)),
+            SourceSpec::File { path, .. } => (format!("file:{path}"), None),
+        };
+        Self {
+            name: name.to_string(),
+            spec,
+            version: installed.version().to_string(),
+            version_req: installed.version_req.clone(),
+            // "platform" is what the manifest records when no pattern was given
+            asset: asset.filter(|asset| *asset != "platform").cloned(),
+            bin: installed.binary.clone(),
+            all_bins: !installed.binaries.is_empty(),
+            include_prerelease: matches!(
+                installed.source,
+                SourceSpec::Github {
+                    include_prerelease: true,
+                    ..
+                }
+            ),
+            pinned: installed.pinned_version.is_some(),
+        }
+    }
+
+    /// Whether `manifest` already has this entry exactly as exported, so
+    /// import has nothing to do for it.
+    pub fn is_installed(&self, manifest: &Manifest) -> bool {
+        manifest
+            .binaries
+            .get(&self.name)
+            .is_some_and(|installed| Self::from_installed(&self.name, installed) == *self)
+    }
+
+    /// The package spec to resolve this entry with.
+    pub fn package_spec(&self) -> Result<PackageSpec, FetchError> {
+        let mut spec = PackageSpec::from_str(&self.spec)?;
+        spec.version_req = match &spec.source {
+            // A local file only ever provides the version it was installed as
+            SourceConfig::File { .. } => Some(self.version.clone()),
+            _ => self.version_req.clone(),
+        };
+        if let Some(asset) = &self.asset {
+            match &mut spec.source {
+                SourceConfig::Github { asset_pattern, .. }
+                | SourceConfig::Gitlab { asset_pattern, .. } => {
+                    *asset_pattern = Some(asset.clone());
+                }
+                _ => {
+                    return Err(FetchError::Parse(format!(
+                        "{}: asset patterns only apply to github and gitlab sources",
+                        self.name
+                    )))
+                }
+            }
+        }
+        if self.include_prerelease {
+            match &mut spec.source {
+                SourceConfig::Github {
+                    include_prerelease, ..
+                } => *include_prerelease = true,
+                _ => {
+                    return Err(FetchError::Parse(format!(
+                        "{}: include_prerelease only applies to github sources",
+                        self.name
+                    )))
+                }
+            }
+        }
+        spec.binary_name = Some(self.bin.clone());
+        spec.all_bins = self.all_bins;
+        Ok(spec)
+    }
+
+    /// Pick the version to install from `versions` (newest first): the
+    /// recorded one when `frozen`, otherwise the newest that satisfies the
+    /// recorded requirement.
+    pub fn select(
+        &self,
+        versions: Vec<ResolvedVersion>,
+        frozen: bool,
+    ) -> Result<ResolvedVersion, FetchError> {
+        let wanted = self.version.trim_start_matches('v');
+        let found = if frozen {
+            versions
+                .into_iter()
+                .find(|resolved| resolved.version.trim_start_matches('v') == wanted)
+        } else {
+            filter_by_requirement(versions, self.version_req.as_deref())?
+                .into_iter()
+                .next()
+        };
+        found.ok_or_else(|| {
+            let wanted = if frozen {
+                self.version.as_str()
+            } else {
+                self.version_req.as_deref().unwrap_or("latest")
+            };
+            FetchError::Parse(format!("no version of {} matches {wanted}", self.spec))
+        })
+    }
+}
+
+/// Counts for the line `fetchbin import` closes with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub installed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl std::fmt::Display for ImportSummary {
+    /// e.g. "3 installed, 2 already installed, 1 failed"; zero counts are omitted.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            (self.installed, "installed"),
+            (self.skipped, "already installed"),
+            (self.failed, "failed"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect();
+
+        if parts.is_empty() {
+            write!(f, "nothing to import")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn github_binary() -> InstalledBinary {
+        InstalledBinary {
+            source: SourceSpec::Github {
+                repo: "sharkdp/fd".to_string(),
+                asset: "platform".to_string(),
+                version: "v10.2.0".to_string(),
+                include_prerelease: false,
+            },
+            binary: "fd".to_string(),
+            version_req: Some("^10".to_string()),
+            binaries: Vec::new(),
+            sha256: "abc123".to_string(),
+            installed_at: "1700000000".to_string(),
+            runtime: None,
+            pinned_version: None,
+            native_package: None,
+            libc: None,
+        }
+    }
+
+    fn resolved(version: &str) -> ResolvedVersion {
+        ResolvedVersion {
+            version: version.to_string(),
+            download_url: None,
+            checksum: None,
+            engines: None,
+        }
+    }
+
+    #[test]
+    fn export_records_how_binaries_were_installed() {
+        let mut manifest = Manifest::default();
+        manifest.binaries.insert("fd".to_string(), github_binary());
+        let mut biome = github_binary();
+        biome.source = SourceSpec::Npm {
+            package: "@biomejs/biome".to_string(),
+            version: "1.9.0".to_string(),
+        };
+        biome.binary = "biome".to_string();
+        biome.version_req = None;
+        biome.binaries = vec!["biome".to_string(), "biome-lsp".to_string()];
+        biome.pinned_version = Some("1.9.0".to_string());
+        manifest.binaries.insert("biome".to_string(), biome);
+
+        let export = Export::from_manifest(&manifest);
+        assert_eq!(
+            serde_json::to_value(&export).unwrap(),
+            serde_json::json!({
+                "version": 1,
+                "binaries": [
+                    {
+                        "name": "biome",
+                        "spec": "npm:@biomejs/biome",
+                        "version": "1.9.0",
+                        "bin": "biome",
+                        "all_bins": true,
+                        "pinned": true
+                    },
+                    {
+                        "name": "fd",
+                        "spec": "github:sharkdp/fd",
+                        "version": "v10.2.0",
+                        "version_req": "^10",
+                        "bin": "fd"
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn exported_entry_rebuilds_its_package_spec() {
+        let mut installed = github_binary();
+        installed.source = SourceSpec::Github {
+            repo: "sharkdp/fd".to_string(),
+            asset: "fd-*-x86_64-unknown-linux-musl.tar.gz".to_string(),
+            version: "v10.2.0".to_string(),
+            include_prerelease: true,
+        };
+        let spec = ExportedBinary::from_installed("fd", &installed)
+            .package_spec()
+            .unwrap();
+        assert_eq!(
+            spec.source,
+            SourceConfig::Github {
+                repo: "sharkdp/fd".to_string(),
+                asset_pattern: Some("fd-*-x86_64-unknown-linux-musl.tar.gz".to_string()),
+                include_prerelease: true,
+            }
+        );
+        assert_eq!(spec.version_req.as_deref(), Some("^10"));
+        assert_eq!(spec.binary_name.as_deref(), Some("fd"));
+        assert!(!spec.all_bins);
+    }
+
+    #[test]
+    fn file_entries_resolve_at_their_recorded_version() {
+        let mut installed = github_binary();
+        installed.source = SourceSpec::File {
+            path: "/opt/tools/fd.tar.gz".to_string(),
+            sha256: None,
+            version: "10.2.0".to_string(),
+        };
+        installed.version_req = None;
+        let spec = ExportedBinary::from_installed("fd", &installed)
+            .package_spec()
+            .unwrap();
+        assert_eq!(spec.version_req.as_deref(), Some("10.2.0"));
+    }
+
+    #[test]
+    fn select_honours_frozen_and_requirement() {
+        let entry = ExportedBinary::from_installed("fd", &github_binary());
+        let versions = || {
+            vec![
+                resolved("v11.0.0"),
+                resolved("v10.3.0"),
+                resolved("v10.2.0"),
+            ]
+        };
+
+        assert_eq!(entry.select(versions(), true).unwrap().version, "v10.2.0");
+        assert_eq!(entry.select(versions(), false).unwrap().version, "v10.3.0");
+
+        let err = entry.select(vec![resolved("v11.0.0")], false).unwrap_err();
+        assert!(err.to_string().contains("^10"), "{err}");
+        assert!(entry.select(vec![resolved("v10.3.0")], true).is_err());
+    }
+
+    #[test]
+    fn identical_entries_count_as_installed() {
+        let mut manifest = Manifest::default();
+        manifest.binaries.insert("fd".to_string(), github_binary());
+        let mut entry = ExportedBinary::from_installed("fd", &github_binary());
+        assert!(entry.is_installed(&manifest));
+
+        entry.version = "v10.3.0".to_string();
+        assert!(!entry.is_installed(&manifest));
+        entry.name = "rg".to_string();
+        assert!(!entry.is_installed(&manifest));
+    }
+
+    #[test]
+    fn newer_export_versions_are_refused() {
+        let err = Export::parse(r#"{ "version": 2, "binaries": [] }"#).unwrap_err();
+        assert!(matches!(err, ManifestError::UnsupportedExport(2)));
+        assert!(Export::parse(r#"{ "version": 1, "binaries": [] }"#).is_ok());
+    }
+
+    #[test]
+    fn summary_omits_zero_counts() {
+        let summary = ImportSummary {
+            installed: 2,
+            skipped: 0,
+            failed: 1,
+        };
+        assert_eq!(summary.to_string(), "2 installed, 1 failed");
+        assert_eq!(ImportSummary::default().to_string(), "nothing to import");
+    }
+}
@@ -1,13 +1,24 @@
+pub mod audit;
+pub mod elf;
 pub mod error;
+pub mod export;
 pub mod manifest;
 pub mod platform;
+pub mod prefetch;
 pub mod runtime;
 pub mod source;
+pub mod store;
+pub mod update;
 
+pub use audit::{BinaryAudit, HashState, LinkAudit, LinkState};
+pub use elf::{GlibcVersion, LibcRequirement};
 pub use error::{FetchError, ManifestError, RuntimeError};
-pub use manifest::{InstalledBinary, Manifest, RuntimeManifest};
+pub use export::{Export, ExportedBinary, ImportSummary};
+pub use manifest::{InstalledBinary, Manifest, NativePackage, RuntimeManifest, UpdateCandidate};
 pub use platform::Platform;
 pub use runtime::{PruneReport, RuntimePool, RuntimeUpdateReport, RuntimeVersion};
 pub use source::{
-    BinarySource, CargoSource, FetchedBinary, GithubSource, PackageSpec, ResolvedVersion,
+    BinarySource, CargoSource, FetchedBinary, FileSource, GithubSource, GitlabSource, PackageSpec,
+    ResolvedVersion,
 };
+pub use update::{UpdateEntry, UpdateOutcome, UpdateReport, UpdateSummary};
@@ -1,16 +1,22 @@
 use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use fetchbin::manifest::{RuntimeVersionSpec, SourceSpec};
-use fetchbin::source::SourceConfig;
+use fetchbin::prefetch::{self, Download, DownloadProgress};
+use fetchbin::source::{resolve_with_fallback, Fallback, SourceConfig};
+use fetchbin::store;
 use fetchbin::{
-    BinarySource, CargoSource, FetchError, GithubSource, InstalledBinary, Manifest, PackageSpec,
-    RuntimePool, RuntimeVersion,
+    BinaryAudit, BinarySource, CargoSource, Export, ExportedBinary, FetchError, FileSource,
+    GithubSource, GitlabSource, GlibcVersion, HashState, ImportSummary, InstalledBinary,
+    LibcRequirement, LinkState, Manifest, PackageSpec, RuntimePool, RuntimeVersion,
+    UpdateCandidate, UpdateEntry, UpdateOutcome, UpdateReport,
 };
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Parser, Debug)]
 #[command(name = "fetchbin")]
@@ -29,14 +35,118 @@ enum Commands {
         /// Select a specific binary from packages with multiple binaries
         #[arg(short, long)]
         bin: Option<String>,
+        /// Link every executable the package ships, not just the primary one
+        #[arg(long)]
+        all_bins: bool,
+        /// Reinstall even if the binary is pinned (clears the pin), and take
+        /// over links that belong to other packages
+        #[arg(long)]
+        force: bool,
+        /// For github sources: where to get the binary when no release
+        /// asset matches this platform
+        #[arg(long, value_enum)]
+        fallback: Option<FallbackKind>,
+        /// Crate to build with `--fallback cargo` (defaults to the repo name)
+        #[arg(long = "crate", requires = "fallback")]
+        crate_name: Option<String>,
+        /// For github sources: consider pre-releases and `-rc`/nightly tags,
+        /// now and on every update
+        #[arg(long)]
+        include_prerelease: bool,
+        /// Refuse a binary that needs a newer glibc than this system has,
+        /// instead of warning
+        #[arg(long)]
+        strict: bool,
+        /// For npm sources: skip running the installed binary with
+        /// `--version`/`--help` to check that it works
+        #[arg(long)]
+        no_verify: bool,
+    },
+    List {
+        /// Also show when each binary was installed and the C library it needs
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    Update {
+        /// Resolve available versions and report them without installing
+        #[arg(long)]
+        dry_run: bool,
+        /// Only consider these binaries (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Downloads to run at once
+        #[arg(short, long, default_value_t = prefetch::DEFAULT_JOBS)]
+        jobs: usize,
     },
-    List,
-    Update,
     Remove {
         name: String,
+        /// Also drop runtimes nothing uses anymore and empty store dirs
+        #[arg(long)]
+        purge: bool,
+    },
+    /// Delete store dirs no installed binary uses
+    Gc {
+        /// List what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Hold a binary at a version so `update` leaves it alone
+    Pin { name: String, version: String },
+    /// Release a pinned binary so `update` tracks the latest version again
+    Unpin { name: String },
+    /// Show where a binary links to and check it against the manifest
+    Which {
+        /// A manifest entry or any binary it links
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        name: Option<String>,
+        /// Check every installed binary; exits non-zero on any problem
+        #[arg(long)]
+        all: bool,
+    },
+    /// Write the installed set as a portable list for `import`
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Install everything listed in a file written by `export`
+    Import {
+        file: PathBuf,
+        /// Install the exact recorded versions instead of the latest that
+        /// satisfy each recorded requirement
+        #[arg(long)]
+        frozen: bool,
+    },
+    /// Generate shell completions
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
     },
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FallbackKind {
+    /// Build the crate with cargo-binstall
+    Cargo,
+}
+
+/// Options for `install` beyond the spec itself.
+#[derive(Default)]
+struct InstallOptions<'a> {
+    asset: Option<&'a str>,
+    bin: Option<&'a str>,
+    all_bins: bool,
+    force: bool,
+    fallback: Option<FallbackKind>,
+    crate_name: Option<&'a str>,
+    include_prerelease: bool,
+    strict: bool,
+    no_verify: bool,
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -48,175 +158,624 @@ fn main() {
 
 fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Install { spec, asset, bin } => {
-            cmd_install(&spec, asset.as_deref(), bin.as_deref())
+        Commands::Install {
+            spec,
+            asset,
+            bin,
+            all_bins,
+            force,
+            fallback,
+            crate_name,
+            include_prerelease,
+            strict,
+            no_verify,
+        } => cmd_install(
+            &spec,
+            InstallOptions {
+                asset: asset.as_deref(),
+                bin: bin.as_deref(),
+                all_bins,
+                force,
+                fallback,
+                crate_name: crate_name.as_deref(),
+                include_prerelease,
+                strict,
+                no_verify,
+            },
+        ),
+        Commands::List { verbose } => cmd_list(verbose),
+        Commands::Update {
+            dry_run,
+            only,
+            json,
+            jobs,
+        } => cmd_update(dry_run, &only, json, jobs),
+        Commands::Remove { name, purge } => cmd_remove(&name, purge),
+        Commands::Gc { dry_run } => cmd_gc(dry_run),
+        Commands::Pin { name, version } => cmd_pin(&name, &version),
+        Commands::Unpin { name } => cmd_unpin(&name),
+        Commands::Which { name, all } => match name {
+            Some(name) if !all => cmd_which(&name),
+            _ => cmd_which_all(),
+        },
+        Commands::Export { output } => cmd_export(output.as_deref()),
+        Commands::Import { file, frozen } => cmd_import(&file, frozen),
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "fetchbin",
+                &mut std::io::stdout(),
+            );
+            Ok(())
         }
-        Commands::List => cmd_list(),
-        Commands::Update => cmd_update(),
-        Commands::Remove { name } => cmd_remove(&name),
     }
 }
 
-fn cmd_install(spec: &str, asset: Option<&str>, bin: Option<&str>) -> Result<()> {
+fn cmd_install(spec: &str, options: InstallOptions<'_>) -> Result<()> {
+    let InstallOptions {
+        asset,
+        bin,
+        all_bins,
+        force,
+        fallback,
+        crate_name,
+        include_prerelease,
+        ..
+    } = options;
     let data_dir = fetchbin_data_dir();
-    let bin_dir = data_dir.join("bin");
-    let store_dir = data_dir.join("store");
     let manifest_path = manifest_path(&data_dir)?;
 
     let mut spec = PackageSpec::from_str(spec)?;
     if let Some(asset) = asset {
         match &mut spec.source {
-            SourceConfig::Github { asset_pattern, .. } => {
+            SourceConfig::Github { asset_pattern, .. }
+            | SourceConfig::Gitlab { asset_pattern, .. } => {
                 *asset_pattern = Some(asset.to_string());
             }
-            _ => bail!("--asset is only supported for github sources"),
+            _ => bail!("--asset is only supported for github and gitlab sources"),
+        }
+    }
+
+    if include_prerelease {
+        match &mut spec.source {
+            SourceConfig::Github {
+                include_prerelease, ..
+            } => *include_prerelease = true,
+            _ => bail!("--include-prerelease is only supported for github sources"),
         }
     }
 
     if let Some(bin) = bin {
         spec.binary_name = Some(bin.to_string());
     }
+    spec.all_bins = all_bins;
+
+    if let Some(FallbackKind::Cargo) = fallback {
+        let SourceConfig::Github { repo, .. } = &spec.source else {
+            bail!("--fallback is only supported for github sources");
+        };
+        let crate_name = crate_name
+            .unwrap_or_else(|| repo.rsplit('/').next().unwrap_or(repo))
+            .to_string();
+        spec.fallback = Some(Fallback::Cargo { crate_name });
+    }
+
+    // Record where a local install came from, not where it was run from
+    if let SourceConfig::File { path } = &mut spec.source {
+        *path = std::path::absolute(&*path)?.display().to_string();
+    }
+
+    if !force {
+        let manifest = Manifest::load(&manifest_path)?;
+        if let Some((name, version)) = find_pinned(&manifest, &spec) {
+            bail!(
+                "{name} is pinned at {version}; run `fetchbin unpin {name}` or pass --force to reinstall"
+            );
+        }
+    }
 
     println!("Installing {}...", spec.name);
 
     let mut runtime = RuntimePool::load(data_dir.clone())?;
 
-    let resolved = resolve_versions(&spec, &data_dir)?;
+    let (spec, resolved) = resolve_with_fallback(spec, |spec| resolve_versions(spec, &data_dir))?;
+    // Only a github spec gets here with a fallback, so cargo means it was used
+    if let (Some(_), SourceConfig::Cargo { crate_name }) = (fallback, &spec.source) {
+        println!("  ! No release asset for this platform; building {crate_name} with cargo");
+    }
     let latest = resolved
         .first()
         .cloned()
         .ok_or_else(|| FetchError::Parse("no versions resolved".to_string()))?;
     println!("  ✓ Resolved {}@{}", spec.name, latest.version);
 
-    let target_dir = store_dir_for_spec(&spec, &latest.version, &store_dir);
+    let mut manifest = Manifest::load(&manifest_path)?;
+    install_resolved(
+        &mut manifest,
+        &spec,
+        &latest,
+        &options,
+        &mut runtime,
+        &data_dir,
+    )?;
+    manifest.save(&manifest_path)?;
+
+    // Prune unused Node versions
+    let used_versions = collect_used_node_versions(&manifest);
+    let _ = runtime.prune(&used_versions);
+    runtime.save()?;
+
+    Ok(())
+}
+
+/// Fetch `version` of `spec`, link it, and record it in `manifest`.
+///
+/// Returns the manifest name of the new entry. The caller saves the manifest.
+fn install_resolved(
+    manifest: &mut Manifest,
+    spec: &PackageSpec,
+    version: &fetchbin::ResolvedVersion,
+    options: &InstallOptions<'_>,
+    runtime: &mut RuntimePool,
+    data_dir: &Path,
+) -> Result<String> {
+    let bin_dir = data_dir.join("bin");
+    let store_dir = data_dir.join("store");
+
+    let target_dir = store_dir_for_spec(spec, &version.version, &store_dir);
     if target_dir.exists() {
         fs::remove_dir_all(&target_dir)?;
     }
 
-    let fetched = fetch_version(&spec, &latest, &target_dir, &mut runtime, &data_dir)?;
+    let fetched = fetch_version(
+        spec,
+        version,
+        &target_dir,
+        runtime,
+        data_dir,
+        !options.no_verify,
+    )?;
     println!("  ✓ Downloaded and installed");
 
-    fs::create_dir_all(&bin_dir)?;
+    let libc = match check_libc(&fetched, options.strict) {
+        Ok(libc) => libc,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&target_dir);
+            return Err(err);
+        }
+    };
+
     let binary_name = binary_name_from_path(&fetched.binary_path)?;
-    let link_path = bin_dir.join(&binary_name);
-    if link_path.exists() {
-        fs::remove_file(&link_path)?;
+    let links = fetched_links(&fetched)?;
+    let link_names: Vec<String> = links.iter().map(|(name, _)| name.clone()).collect();
+
+    if let Err(err) = claim_links(
+        manifest,
+        &binary_name,
+        &link_names,
+        options.force,
+        &target_dir,
+        data_dir,
+    ) {
+        let _ = fs::remove_dir_all(&target_dir);
+        return Err(err);
+    }
+
+    if let Some(previous) = manifest.binaries.get(&binary_name) {
+        unlink_binaries(previous, &bin_dir, &link_names)?;
+    }
+
+    fs::create_dir_all(&bin_dir)?;
+    for (name, target) in &links {
+        let link_path = bin_dir.join(name);
+        if link_path.symlink_metadata().is_ok() {
+            fs::remove_file(&link_path)?;
+        }
+        create_symlink(target, &link_path)?;
+        println!("  ✓ Linked to {}", link_path.display());
     }
-    create_symlink(&fetched.binary_path, &link_path)?;
-    println!("  ✓ Linked to {}", link_path.display());
 
-    let mut manifest = Manifest::load(&manifest_path)?;
     manifest.binaries.insert(
         binary_name.clone(),
         InstalledBinary {
-            source: source_spec_from_package(&spec, &latest.version, asset),
-            binary: binary_name,
+            source: source_spec_from_package(spec, version, options.asset),
+            binary: binary_name.clone(),
+            // A file's requirement is just the version it was installed as
+            version_req: match spec.source {
+                SourceConfig::File { .. } => None,
+                _ => spec.version_req.clone(),
+            },
+            binaries: if spec.all_bins {
+                link_names
+            } else {
+                Vec::new()
+            },
             sha256: fetched.sha256,
             installed_at: current_timestamp(),
             runtime: runtime_spec_from_version(fetched.runtime_used.as_ref()),
+            pinned_version: None,
+            native_package: fetched.native_package,
+            libc,
         },
     );
-    manifest.save(&manifest_path)?;
-
-    // Prune unused Node versions
-    let used_versions = collect_used_node_versions(&manifest);
-    let _ = runtime.prune(&used_versions);
-    runtime.save()?;
 
-    Ok(())
+    Ok(binary_name)
 }
 
-fn cmd_list() -> Result<()> {
+fn cmd_list(verbose: bool) -> Result<()> {
     let data_dir = fetchbin_data_dir();
     let manifest_path = manifest_path(&data_dir)?;
     let manifest = Manifest::load(&manifest_path)?;
+    let host = if verbose { GlibcVersion::host() } else { None };
 
-    let mut entries: BTreeMap<String, (String, String)> = BTreeMap::new();
-    for (name, entry) in manifest.binaries.iter() {
-        let (version, source) = installed_version_source(entry);
-        entries.insert(name.clone(), (version, source));
+    let entries: BTreeMap<&String, &InstalledBinary> = manifest.binaries.iter().collect();
+    for (name, entry) in entries {
+        let (mut version, source) = installed_version_source(entry);
+        if let Some(req) = &entry.version_req {
+            version = format!("{version} ({req})");
+        }
+        let marker = if entry.pinned_version.is_some() {
+            "  (pinned)"
+        } else {
+            ""
+        };
+        println!("  {:<12} {:<8} {}{}", name, version, source, marker);
+        if verbose {
+            println!("      installed  {}", entry.installed_at);
+            println!(
+                "      libc       {}",
+                libc_description(entry, host.as_ref())
+            );
+        }
     }
 
-    for (name, (version, source)) in entries {
-        println!("  {:<12} {:<8} {}", name, version, source);
+    Ok(())
+}
+
+/// Detect the C library `fetched` needs, warning when this system's glibc
+/// is older than that; with `strict`, refusing instead.
+fn check_libc(fetched: &fetchbin::FetchedBinary, strict: bool) -> Result<Option<LibcRequirement>> {
+    let libc = LibcRequirement::detect(&fetched.binary_path);
+    if let (Some(requirement), Some(host)) = (&libc, GlibcVersion::host()) {
+        if !requirement.is_met_by(&host) {
+            let message = format!("needs {requirement}, but this system has glibc {host}");
+            if strict {
+                bail!("{} {message}", fetched.binary_path.display());
+            }
+            eprintln!("  ! Binary {message}; it will likely fail to start");
+        }
     }
+    Ok(libc)
+}
 
-    Ok(())
+/// The recorded C library requirement, flagged when `host` can't meet it.
+fn libc_description(installed: &InstalledBinary, host: Option<&GlibcVersion>) -> String {
+    match (&installed.libc, host) {
+        (None, _) => "not recorded".to_string(),
+        (Some(requirement), Some(host)) if !requirement.is_met_by(host) => {
+            format!("{requirement} ✗ this system has {host}")
+        }
+        (Some(requirement), _) => requirement.to_string(),
+    }
+}
+
+/// A binary with a newer version, resolved but not yet fetched.
+struct PendingUpdate {
+    index: usize,
+    installed: InstalledBinary,
+    spec: PackageSpec,
+    version: fetchbin::ResolvedVersion,
 }
 
-fn cmd_update() -> Result<()> {
+fn cmd_update(dry_run: bool, only: &[String], json: bool, jobs: usize) -> Result<()> {
     let data_dir = fetchbin_data_dir();
-    let bin_dir = data_dir.join("bin");
-    let store_dir = data_dir.join("store");
     let manifest_path = manifest_path(&data_dir)?;
 
     let mut manifest = Manifest::load(&manifest_path)?;
-    let mut runtime = RuntimePool::load(data_dir.clone())?;
-
-    let keys: Vec<String> = manifest.binaries.keys().cloned().collect();
-    let mut updated = 0;
+    if let Some(unknown) = only
+        .iter()
+        .find(|name| !manifest.binaries.contains_key(*name))
+    {
+        bail!("binary '{unknown}' not found");
+    }
 
-    for name in keys {
-        let installed = match manifest.binaries.get(&name).cloned() {
-            Some(value) => value,
-            None => continue,
+    // Resolve everything first so one bad source doesn't stop the rest
+    let mut report = UpdateReport {
+        dry_run,
+        binaries: Vec::new(),
+    };
+    let mut pending = Vec::new();
+    for candidate in manifest.update_candidates() {
+        let name = match &candidate {
+            UpdateCandidate::Check(name) | UpdateCandidate::Pinned { name, .. } => name,
         };
-
-        let spec = package_from_installed(&installed)?;
-        let update = check_update(&spec, &installed, &data_dir)?;
-        let Some(new_version) = update else {
+        if !only.is_empty() && !only.contains(name) {
+            continue;
+        }
+        let Some(installed) = manifest.binaries.get(name).cloned() else {
             continue;
         };
+        let (installed_version, source) = installed_version_source(&installed);
 
-        println!("Updating {}...", name);
-        println!("  ✓ Resolved {}@{}", spec.name, new_version.version);
+        let outcome = match candidate {
+            UpdateCandidate::Pinned { .. } => UpdateOutcome::Pinned,
+            UpdateCandidate::Check(_) => match package_from_installed(&installed)
+                .and_then(|spec| Ok((check_update(&spec, &installed, &data_dir)?, spec)))
+            {
+                Ok((None, _)) => UpdateOutcome::UpToDate,
+                Ok((Some(version), spec)) => {
+                    let outcome = UpdateOutcome::Available {
+                        version: version.version.clone(),
+                    };
+                    pending.push(PendingUpdate {
+                        index: report.binaries.len(),
+                        installed,
+                        spec,
+                        version,
+                    });
+                    outcome
+                }
+                Err(err) => UpdateOutcome::Failed {
+                    error: format!("{err:#}"),
+                },
+            },
+        };
+        report.binaries.push(UpdateEntry {
+            name: name.clone(),
+            installed: installed_version,
+            source,
+            outcome,
+        });
+    }
 
-        let target_dir = store_dir_for_spec(&spec, &new_version.version, &store_dir);
-        if target_dir.exists() {
-            fs::remove_dir_all(&target_dir)?;
+    if !dry_run && !pending.is_empty() {
+        // Download in parallel, then install one at a time: only this thread
+        // touches the manifest and the runtime pool
+        let (owners, downloads): (Vec<usize>, Vec<Download>) = pending
+            .iter()
+            .enumerate()
+            .filter_map(|(owner, update)| {
+                let name = &report.binaries[update.index].name;
+                Some((owner, prefetch_download(name, update)?))
+            })
+            .unzip();
+        let mut download_errors: Vec<Option<FetchError>> = pending.iter().map(|_| None).collect();
+        let bars = DownloadBars::new(&downloads, json);
+        for (owner, result) in owners
+            .into_iter()
+            .zip(prefetch::prefetch(&downloads, jobs, &bars))
+        {
+            download_errors[owner] = result.err();
         }
 
-        let fetched = fetch_version(&spec, &new_version, &target_dir, &mut runtime, &data_dir)?;
-        fs::create_dir_all(&bin_dir)?;
-        let link_path = bin_dir.join(&installed.binary);
-        if link_path.exists() {
-            fs::remove_file(&link_path)?;
+        let mut runtime = RuntimePool::load(data_dir.clone())?;
+        for (update, download_error) in pending.into_iter().zip(download_errors) {
+            let entry = &mut report.binaries[update.index];
+            if let Some(err) = download_error {
+                entry.outcome = UpdateOutcome::Failed {
+                    error: err.to_string(),
+                };
+                continue;
+            }
+            if !json {
+                println!("Updating {}...", entry.name);
+                println!(
+                    "  ✓ Resolved {}@{}",
+                    update.spec.name, update.version.version
+                );
+            }
+
+            match replace_installed(
+                &manifest,
+                &update.installed,
+                &update.spec,
+                &update.version,
+                &mut runtime,
+                &data_dir,
+            ) {
+                Ok(replaced) => {
+                    manifest.binaries.insert(entry.name.clone(), replaced);
+                    entry.outcome = UpdateOutcome::Updated {
+                        version: update.version.version,
+                    };
+                }
+                Err(err) => {
+                    entry.outcome = UpdateOutcome::Failed {
+                        error: format!("{err:#}"),
+                    };
+                }
+            }
         }
-        create_symlink(&fetched.binary_path, &link_path)?;
 
-        let previous_store = store_dir_for_installed(&installed, &store_dir);
-        if previous_store.exists() {
-            fs::remove_dir_all(&previous_store)?;
+        prefetch::clear();
+        manifest.save(&manifest_path)?;
+
+        // Prune unused Node versions
+        let used_versions = collect_used_node_versions(&manifest);
+        let _ = runtime.prune(&used_versions);
+        runtime.save()?;
+    }
+
+    let summary = report.summary();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_update_table(&report.binaries);
+        for (name, error) in report.failures() {
+            eprintln!("  ✗ {name}: {error}");
         }
+        println!("{summary}");
+    }
 
-        manifest.binaries.insert(
-            name.clone(),
-            InstalledBinary {
-                source: source_spec_from_installed(&installed, &new_version.version),
-                binary: installed.binary.clone(),
-                sha256: fetched.sha256,
-                installed_at: current_timestamp(),
-                runtime: runtime_spec_from_version(fetched.runtime_used.as_ref()),
-            },
+    if summary.failed > 0 {
+        let names: Vec<&str> = report.failures().map(|(name, _)| name).collect();
+        bail!("failed to update {}", names.join(", "));
+    }
+    Ok(())
+}
+
+/// The artifact download for the update of `name`, if its source has one
+/// to prefetch.
+fn prefetch_download(name: &str, update: &PendingUpdate) -> Option<Download> {
+    let headers = match &update.spec.source {
+        SourceConfig::Npm { .. } => Vec::new(),
+        SourceConfig::Github { .. } => GithubSource::new().asset_headers(),
+        SourceConfig::Gitlab { .. } => GitlabSource::new().headers().to_vec(),
+        // cargo-binstall and local files download nothing themselves
+        SourceConfig::Cargo { .. } | SourceConfig::File { .. } => return None,
+    };
+    Some(Download {
+        name: name.to_string(),
+        url: update.version.download_url.clone()?,
+        headers,
+    })
+}
+
+/// One progress bar per download: a byte count against `Content-Length`,
+/// or a spinner when the server doesn't send one.
+struct DownloadBars {
+    _multi: MultiProgress,
+    bars: Vec<ProgressBar>,
+}
+
+impl DownloadBars {
+    fn new(downloads: &[Download], hidden: bool) -> Self {
+        let multi = MultiProgress::with_draw_target(if hidden {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stderr()
+        });
+        let bars = downloads
+            .iter()
+            .map(|download| {
+                let bar = multi.add(ProgressBar::new_spinner());
+                bar.set_style(
+                    ProgressStyle::with_template("  {spinner} {prefix:<12} {bytes} {msg}")
+                        .expect("valid template"),
+                );
+                bar.set_prefix(download.name.clone());
+                bar.set_message("waiting");
+                bar
+            })
+            .collect();
+        Self {
+            _multi: multi,
+            bars,
+        }
+    }
+}
+
+impl DownloadProgress for DownloadBars {
+    fn progress(&self, index: usize, downloaded: u64, total: Option<u64>) {
+        let bar = &self.bars[index];
+        if downloaded == 0 {
+            bar.set_message("");
+            match total {
+                Some(total) => {
+                    bar.set_length(total);
+                    bar.set_style(
+                        ProgressStyle::with_template(
+                            "  {prefix:<12} {bar:30.cyan/blue} {bytes}/{total_bytes} {msg}",
+                        )
+                        .expect("valid template")
+                        .progress_chars("█▓░"),
+                    );
+                }
+                None => bar.enable_steady_tick(Duration::from_millis(80)),
+            }
+        }
+        bar.set_position(downloaded);
+    }
+
+    fn finished(&self, index: usize, result: &Result<(), FetchError>) {
+        let bar = &self.bars[index];
+        match result {
+            Ok(()) => bar.finish_with_message("✓"),
+            Err(err) => bar.abandon_with_message(format!("✗ {err}")),
+        }
+    }
+}
+
+fn print_update_table(entries: &[UpdateEntry]) {
+    println!(
+        "  {:<12} {:<10} {:<10} {}",
+        "NAME", "INSTALLED", "AVAILABLE", "SOURCE"
+    );
+    for entry in entries {
+        println!(
+            "  {:<12} {:<10} {:<10} {}",
+            entry.name,
+            entry.installed,
+            entry.available(),
+            entry.source
         );
-        updated += 1;
     }
+}
+
+fn cmd_pin(name: &str, version: &str) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest_path = manifest_path(&data_dir)?;
 
+    let mut manifest = Manifest::load(&manifest_path)?;
+    let installed = manifest
+        .binaries
+        .get(name)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("binary '{name}' not found"))?;
+
+    let mut runtime = None;
+    let mut pinned = if versions_match(installed.version(), version) {
+        installed
+    } else {
+        let spec = package_from_installed(&installed)?;
+        let target = resolve_versions(&spec, &data_dir)?
+            .into_iter()
+            .find(|resolved| versions_match(&resolved.version, version))
+            .ok_or_else(|| anyhow::anyhow!("version {version} of '{name}' not found"))?;
+
+        println!("Installing {}@{}...", spec.name, target.version);
+        let pool = runtime.insert(RuntimePool::load(data_dir.clone())?);
+        replace_installed(&manifest, &installed, &spec, &target, pool, &data_dir)?
+    };
+
+    let pinned_at = pinned.version().to_string();
+    pinned.pinned_version = Some(pinned_at.clone());
+    manifest.binaries.insert(name.to_string(), pinned);
     manifest.save(&manifest_path)?;
 
-    // Prune unused Node versions
-    let used_versions = collect_used_node_versions(&manifest);
-    let _ = runtime.prune(&used_versions);
-    runtime.save()?;
+    if let Some(mut runtime) = runtime {
+        // Prune unused Node versions
+        let used_versions = collect_used_node_versions(&manifest);
+        let _ = runtime.prune(&used_versions);
+        runtime.save()?;
+    }
+
+    println!("Pinned {} at {}", name, pinned_at);
+    Ok(())
+}
+
+fn cmd_unpin(name: &str) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest_path = manifest_path(&data_dir)?;
+
+    let mut manifest = Manifest::load(&manifest_path)?;
+    let installed = manifest
+        .binaries
+        .get_mut(name)
+        .ok_or_else(|| anyhow::anyhow!("binary '{name}' not found"))?;
 
-    if updated == 0 {
-        println!("All binaries are already up to date.");
+    if installed.pinned_version.take().is_none() {
+        println!("{} is not pinned", name);
+        return Ok(());
     }
 
+    manifest.save(&manifest_path)?;
+    println!("Unpinned {}", name);
     Ok(())
 }
 
-fn cmd_remove(name: &str) -> Result<()> {
+fn cmd_remove(name: &str, purge: bool) -> Result<()> {
     let data_dir = fetchbin_data_dir();
     let bin_dir = data_dir.join("bin");
     let store_dir = data_dir.join("store");
@@ -228,10 +787,7 @@ fn cmd_remove(name: &str) -> Result<()> {
         .remove(name)
         .ok_or_else(|| anyhow::anyhow!("binary '{name}' not found"))?;
 
-    let link_path = bin_dir.join(&installed.binary);
-    if link_path.exists() {
-        fs::remove_file(&link_path)?;
-    }
+    unlink_binaries(&installed, &bin_dir, &[])?;
 
     let store_path = store_dir_for_installed(&installed, &store_dir);
     if store_path.exists() {
@@ -240,9 +796,264 @@ fn cmd_remove(name: &str) -> Result<()> {
 
     manifest.save(&manifest_path)?;
     println!("Removed {}", name);
+
+    if purge {
+        store::remove_empty_parents(&store_path, &store_dir)?;
+
+        let mut runtime = RuntimePool::load(data_dir.clone())?;
+        let used_versions = collect_used_node_versions(&manifest);
+        let report = runtime.prune(&used_versions)?;
+        runtime.save()?;
+        for version in &report.removed {
+            println!("  ✓ Removed unused node {version}");
+        }
+    }
     Ok(())
 }
 
+fn cmd_gc(dry_run: bool) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let store_dir = data_dir.join("store");
+    let manifest = Manifest::load(&manifest_path(&data_dir)?)?;
+
+    let referenced: HashSet<PathBuf> = manifest
+        .binaries
+        .values()
+        .map(|installed| store_dir_for_installed(installed, &store_dir))
+        .collect();
+    let orphans = store::find_orphans(&store_dir, &referenced)
+        .with_context(|| format!("failed to scan {}", store_dir.display()))?;
+
+    if orphans.is_empty() {
+        println!("Nothing to collect");
+        return Ok(());
+    }
+
+    let mut reclaimed = 0;
+    for orphan in &orphans {
+        let path = orphan.path.strip_prefix(&store_dir).unwrap_or(&orphan.path);
+        if dry_run {
+            println!("  - {} ({})", path.display(), HumanBytes(orphan.bytes));
+        } else {
+            store::remove_orphan(orphan, &store_dir)
+                .with_context(|| format!("failed to remove {}", orphan.path.display()))?;
+            println!(
+                "  ✓ Removed {} ({})",
+                path.display(),
+                HumanBytes(orphan.bytes)
+            );
+        }
+        reclaimed += orphan.bytes;
+    }
+
+    if dry_run {
+        println!(
+            "Would remove {} store dirs, reclaiming {}",
+            orphans.len(),
+            HumanBytes(reclaimed)
+        );
+    } else {
+        println!(
+            "Removed {} store dirs, reclaimed {}",
+            orphans.len(),
+            HumanBytes(reclaimed)
+        );
+    }
+    Ok(())
+}
+
+fn cmd_export(output: Option<&Path>) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest = Manifest::load(&manifest_path(&data_dir)?)?;
+    let export = Export::from_manifest(&manifest);
+    let content = serde_json::to_string_pretty(&export)? + "\n";
+
+    match output {
+        Some(path) => {
+            fs::write(path, content)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "Exported {} binaries to {}",
+                export.binaries.len(),
+                path.display()
+            );
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+fn cmd_import(file: &Path, frozen: bool) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest_path = manifest_path(&data_dir)?;
+    let export =
+        Export::load(file).with_context(|| format!("failed to read {}", file.display()))?;
+
+    let mut manifest = Manifest::load(&manifest_path)?;
+    let mut runtime = RuntimePool::load(data_dir.clone())?;
+    let mut summary = ImportSummary::default();
+    let mut failures = Vec::new();
+
+    for entry in &export.binaries {
+        if entry.is_installed(&manifest) {
+            println!("  - {} {} already installed", entry.name, entry.version);
+            summary.skipped += 1;
+            continue;
+        }
+
+        println!("Installing {}...", entry.name);
+        match import_binary(&mut manifest, entry, frozen, &mut runtime, &data_dir) {
+            Ok(()) => {
+                // Record each install as it lands, so a later failure or
+                // crash doesn't lose it
+                manifest.save(&manifest_path)?;
+                summary.installed += 1;
+            }
+            Err(err) => {
+                failures.push((entry.name.as_str(), format!("{err:#}")));
+                summary.failed += 1;
+            }
+        }
+    }
+
+    if summary.installed > 0 {
+        // Prune unused Node versions
+        let used_versions = collect_used_node_versions(&manifest);
+        let _ = runtime.prune(&used_versions);
+        runtime.save()?;
+    }
+
+    for (name, error) in &failures {
+        eprintln!("  ✗ {name}: {error}");
+    }
+    println!("{summary}");
+
+    if !failures.is_empty() {
+        let names: Vec<&str> = failures.iter().map(|(name, _)| *name).collect();
+        bail!("failed to import {}", names.join(", "));
+    }
+    Ok(())
+}
+
+/// Install one entry of an export into `manifest`, pinning it again if it
+/// was pinned.
+fn import_binary(
+    manifest: &mut Manifest,
+    entry: &ExportedBinary,
+    frozen: bool,
+    runtime: &mut RuntimePool,
+    data_dir: &Path,
+) -> Result<()> {
+    let spec = entry.package_spec()?;
+    if let Some((name, version)) = find_pinned(manifest, &spec) {
+        bail!("{name} is pinned at {version}; run `fetchbin unpin {name}` to import it");
+    }
+
+    let version = entry.select(resolve_versions(&spec, data_dir)?, frozen)?;
+    println!("  ✓ Resolved {}@{}", spec.name, version.version);
+
+    let options = InstallOptions::default();
+    let name = install_resolved(manifest, &spec, &version, &options, runtime, data_dir)?;
+    if entry.pinned {
+        if let Some(installed) = manifest.binaries.get_mut(&name) {
+            installed.pinned_version = Some(version.version);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_which(name: &str) -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest = Manifest::load(&manifest_path(&data_dir)?)?;
+
+    // Accept any linked binary, not just the manifest key
+    let key = if manifest.binaries.contains_key(name) {
+        name
+    } else {
+        manifest
+            .owner_of(name, "")
+            .ok_or_else(|| anyhow::anyhow!("binary '{name}' not found"))?
+    };
+    let installed = &manifest.binaries[key];
+    let audit = audit_installed(key, installed, &data_dir);
+    let (version, source) = installed_version_source(installed);
+
+    println!("{key}");
+    for link in &audit.links {
+        let state = match &link.state {
+            LinkState::Ok { target } => format!("-> {}", target.display()),
+            LinkState::Missing => "✗ missing".to_string(),
+            LinkState::NotSymlink => "✗ not a symlink".to_string(),
+            LinkState::Broken { target } => format!("✗ broken -> {}", target.display()),
+            LinkState::Stale { target } => format!("✗ stale -> {}", target.display()),
+        };
+        println!("  link     {} {state}", link.name);
+    }
+    println!("  source   {}", source_description(installed, &source));
+    println!("  version  {version}");
+    println!("  sha256   {}", installed.sha256);
+    println!(
+        "  libc     {}",
+        libc_description(installed, GlibcVersion::host().as_ref())
+    );
+    let hash = match &audit.hash {
+        HashState::Match => "✓ matches".to_string(),
+        HashState::Mismatch { actual } => format!("✗ mismatch (on disk: {actual})"),
+        HashState::NotRecorded => "- not checked (npm records the tarball's hash)".to_string(),
+        HashState::Unreadable { error } => format!("✗ unreadable: {error}"),
+    };
+    println!("  on disk  {hash}");
+
+    if !audit.is_healthy() {
+        bail!("{key} does not match the manifest");
+    }
+    Ok(())
+}
+
+fn cmd_which_all() -> Result<()> {
+    let data_dir = fetchbin_data_dir();
+    let manifest = Manifest::load(&manifest_path(&data_dir)?)?;
+
+    let entries: BTreeMap<&String, &InstalledBinary> = manifest.binaries.iter().collect();
+    let mut failed = 0;
+    for (name, installed) in &entries {
+        let audit = audit_installed(name, installed, &data_dir);
+        let problems = audit.problems();
+        if problems.is_empty() {
+            println!("  ✓ {name}");
+            continue;
+        }
+        failed += 1;
+        println!("  ✗ {name}");
+        for problem in problems {
+            println!("      {problem}");
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {} binaries failed verification", entries.len());
+    }
+    println!("All {} binaries verified", entries.len());
+    Ok(())
+}
+
+fn audit_installed(name: &str, installed: &InstalledBinary, data_dir: &Path) -> BinaryAudit {
+    let store_dir = store_dir_for_installed(installed, &data_dir.join("store"));
+    BinaryAudit::check(name, installed, &data_dir.join("bin"), &store_dir)
+}
+
+/// The source kind plus what it was installed from, e.g. `github BurntSushi/ripgrep`.
+fn source_description(installed: &InstalledBinary, kind: &str) -> String {
+    match &installed.source {
+        SourceSpec::Npm { package, .. } => format!("{kind} {package}"),
+        SourceSpec::Cargo { crate_name, .. } => format!("{kind} {crate_name}"),
+        SourceSpec::Github { repo, asset, .. } | SourceSpec::Gitlab { repo, asset, .. } => {
+            format!("{kind} {repo} ({asset})")
+        }
+        SourceSpec::File { .. } => kind.to_string(),
+    }
+}
+
 fn fetchbin_data_dir() -> PathBuf {
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -253,11 +1064,16 @@ fn manifest_path(data_dir: &Path) -> Result<PathBuf> {
     Ok(Manifest::default_path().unwrap_or_else(|| data_dir.join("manifest.json")))
 }
 
-fn resolve_versions(spec: &PackageSpec, data_dir: &Path) -> Result<Vec<fetchbin::ResolvedVersion>> {
+fn resolve_versions(
+    spec: &PackageSpec,
+    data_dir: &Path,
+) -> Result<Vec<fetchbin::ResolvedVersion>, FetchError> {
     let resolved = match &spec.source {
         SourceConfig::Npm { .. } => fetchbin::source::npm::NpmSource::new().resolve(spec)?,
         SourceConfig::Cargo { .. } => CargoSource::new(data_dir.to_path_buf()).resolve(spec)?,
         SourceConfig::Github { .. } => GithubSource::new().resolve(spec)?,
+        SourceConfig::Gitlab { .. } => GitlabSource::new().resolve(spec)?,
+        SourceConfig::File { .. } => FileSource::new().resolve(spec)?,
     };
     Ok(resolved)
 }
@@ -268,17 +1084,22 @@ fn fetch_version(
     target_dir: &Path,
     runtime: &mut RuntimePool,
     data_dir: &Path,
+    verify: bool,
 ) -> Result<fetchbin::FetchedBinary> {
     let fetched = match &spec.source {
-        SourceConfig::Npm { .. } => {
-            fetchbin::source::npm::NpmSource::new().fetch(spec, version, target_dir, runtime)?
-        }
+        SourceConfig::Npm { .. } => fetchbin::source::npm::NpmSource::new()
+            .with_verify(verify)
+            .fetch(spec, version, target_dir, runtime)?,
         SourceConfig::Cargo { .. } => {
             CargoSource::new(data_dir.to_path_buf()).fetch(spec, version, target_dir, runtime)?
         }
         SourceConfig::Github { .. } => {
             GithubSource::new().fetch(spec, version, target_dir, runtime)?
         }
+        SourceConfig::Gitlab { .. } => {
+            GitlabSource::new().fetch(spec, version, target_dir, runtime)?
+        }
+        SourceConfig::File { .. } => FileSource::new().fetch(spec, version, target_dir, runtime)?,
     };
     Ok(fetched)
 }
@@ -296,10 +1117,184 @@ fn check_update(
             CargoSource::new(data_dir.to_path_buf()).check_update(installed)?
         }
         SourceConfig::Github { .. } => GithubSource::new().check_update(installed)?,
+        SourceConfig::Gitlab { .. } => GitlabSource::new().check_update(installed)?,
+        SourceConfig::File { .. } => FileSource::new().check_update(installed)?,
     };
     Ok(update)
 }
 
+/// Fetch `version` in place of `installed`, relink it, and drop the old store.
+///
+/// The returned entry keeps the binary name and pin of the one it replaces.
+/// New links that another entry in `manifest` already provides are skipped.
+fn replace_installed(
+    manifest: &Manifest,
+    installed: &InstalledBinary,
+    spec: &PackageSpec,
+    version: &fetchbin::ResolvedVersion,
+    runtime: &mut RuntimePool,
+    data_dir: &Path,
+) -> Result<InstalledBinary> {
+    let bin_dir = data_dir.join("bin");
+    let store_dir = data_dir.join("store");
+
+    let target_dir = store_dir_for_spec(spec, &version.version, &store_dir);
+    if target_dir.exists() {
+        fs::remove_dir_all(&target_dir)?;
+    }
+
+    let fetched = fetch_version(spec, version, &target_dir, runtime, data_dir, true)?;
+    let libc = check_libc(&fetched, false)?;
+    let mut links = fetched_links(&fetched)?;
+    links.retain(
+        |(name, _)| match manifest.owner_of(name, &installed.binary) {
+            Some(owner) => {
+                eprintln!("  ! Skipping {name}: already provided by {owner}");
+                false
+            }
+            None => true,
+        },
+    );
+    let link_names: Vec<String> = links.iter().map(|(name, _)| name.clone()).collect();
+
+    unlink_binaries(installed, &bin_dir, &link_names)?;
+    fs::create_dir_all(&bin_dir)?;
+    for (name, target) in &links {
+        let link_path = bin_dir.join(name);
+        if link_path.symlink_metadata().is_ok() {
+            fs::remove_file(&link_path)?;
+        }
+        create_symlink(target, &link_path)?;
+    }
+
+    let previous_store = store_dir_for_installed(installed, &store_dir);
+    if previous_store.exists() && previous_store != target_dir {
+        fs::remove_dir_all(&previous_store)?;
+    }
+
+    Ok(InstalledBinary {
+        source: source_spec_from_installed(installed, &version.version),
+        binary: installed.binary.clone(),
+        version_req: installed.version_req.clone(),
+        binaries: if installed.binaries.is_empty() {
+            Vec::new()
+        } else {
+            link_names
+        },
+        sha256: fetched.sha256,
+        installed_at: current_timestamp(),
+        runtime: runtime_spec_from_version(fetched.runtime_used.as_ref()),
+        pinned_version: installed.pinned_version.clone(),
+        native_package: fetched.native_package,
+        libc,
+    })
+}
+
+/// Link names and targets for everything a fetch produced, primary first.
+fn fetched_links(fetched: &fetchbin::FetchedBinary) -> Result<Vec<(String, PathBuf)>> {
+    let mut links = vec![(
+        binary_name_from_path(&fetched.binary_path)?,
+        fetched.binary_path.clone(),
+    )];
+    for path in &fetched.binary_paths {
+        let name = binary_name_from_path(path)?;
+        if links.iter().all(|(existing, _)| *existing != name) {
+            links.push((name, path.clone()));
+        }
+    }
+    Ok(links)
+}
+
+/// Make sure `links` are free for the entry `name` to use.
+///
+/// A link provided by another entry is an error naming that entry, unless
+/// `force` is set: then the link is taken from it, and an entry that loses
+/// its primary binary is removed along with its other links and store.
+fn claim_links(
+    manifest: &mut Manifest,
+    name: &str,
+    links: &[String],
+    force: bool,
+    target_dir: &Path,
+    data_dir: &Path,
+) -> Result<()> {
+    for link in links {
+        let Some(owner) = manifest.owner_of(link, name).map(str::to_string) else {
+            continue;
+        };
+        if !force {
+            bail!(
+                "{link} is already provided by {owner}; run `fetchbin remove {owner}` or pass --force to replace it"
+            );
+        }
+
+        println!("  ! Taking over {link} from {owner}");
+        let Some(entry) = manifest.binaries.get_mut(&owner) else {
+            continue;
+        };
+        if entry.binary != *link {
+            entry.binaries.retain(|binary| binary != link);
+            continue;
+        }
+
+        if let Some(removed) = manifest.binaries.remove(&owner) {
+            unlink_binaries(&removed, &data_dir.join("bin"), links)?;
+            let store_path = store_dir_for_installed(&removed, &data_dir.join("store"));
+            if store_path.exists() && store_path != target_dir {
+                fs::remove_dir_all(&store_path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove the bin dir links of `installed`, except those named in `keep`.
+fn unlink_binaries(installed: &InstalledBinary, bin_dir: &Path, keep: &[String]) -> Result<()> {
+    for name in installed.linked_binaries() {
+        if keep.iter().any(|kept| kept == name) {
+            continue;
+        }
+        let link_path = bin_dir.join(name);
+        if link_path.symlink_metadata().is_ok() {
+            fs::remove_file(&link_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Find a pinned manifest entry installed from the same source as `spec`.
+fn find_pinned<'a>(manifest: &'a Manifest, spec: &PackageSpec) -> Option<(&'a str, &'a str)> {
+    manifest.binaries.iter().find_map(|(name, installed)| {
+        let version = installed.pinned_version.as_deref()?;
+        let same_source = match (&spec.source, &installed.source) {
+            (SourceConfig::Npm { package }, SourceSpec::Npm { package: other, .. }) => {
+                package == other
+            }
+            (
+                SourceConfig::Cargo { crate_name },
+                SourceSpec::Cargo {
+                    crate_name: other, ..
+                },
+            ) => crate_name == other,
+            (SourceConfig::Github { repo, .. }, SourceSpec::Github { repo: other, .. })
+            | (SourceConfig::Gitlab { repo, .. }, SourceSpec::Gitlab { repo: other, .. })
+            | (SourceConfig::File { path: repo }, SourceSpec::File { path: other, .. }) => {
+                repo == other
+            }
+            _ => false,
+        };
+        let same_binary = spec
+            .binary_name
+            .as_deref()
+            .is_none_or(|binary| binary == installed.binary);
+        (same_source && same_binary).then_some((name.as_str(), version))
+    })
+}
+
+fn versions_match(left: &str, right: &str) -> bool {
+    left.trim_start_matches('v') == right.trim_start_matches('v')
+}
+
 fn store_dir_for_spec(spec: &PackageSpec, version: &str, store_root: &Path) -> PathBuf {
     match &spec.source {
         SourceConfig::Npm { package } => store_root
@@ -314,6 +1309,14 @@ fn store_dir_for_spec(spec: &PackageSpec, version: &str, store_root: &Path) -> P
             .join("github")
             .join(sanitize_component(repo))
             .join(version),
+        SourceConfig::Gitlab { repo, .. } => store_root
+            .join("gitlab")
+            .join(sanitize_component(repo))
+            .join(version),
+        SourceConfig::File { .. } => store_root
+            .join("file")
+            .join(sanitize_component(&spec.name))
+            .join(version),
     }
 }
 
@@ -334,6 +1337,16 @@ fn store_dir_for_installed(installed: &InstalledBinary, store_root: &Path) -> Pa
             .join("github")
             .join(sanitize_component(repo))
             .join(version),
+        SourceSpec::Gitlab { repo, version, .. } => store_root
+            .join("gitlab")
+            .join(sanitize_component(repo))
+            .join(version),
+        SourceSpec::File { path, version, .. } => store_root
+            .join("file")
+            .join(sanitize_component(&fetchbin::source::file::default_name(
+                path,
+            )))
+            .join(version),
     }
 }
 
@@ -348,7 +1361,12 @@ fn binary_name_from_path(path: &Path) -> Result<String> {
         .context("binary path missing file name")
 }
 
-fn source_spec_from_package(spec: &PackageSpec, version: &str, asset: Option<&str>) -> SourceSpec {
+fn source_spec_from_package(
+    spec: &PackageSpec,
+    resolved: &fetchbin::ResolvedVersion,
+    asset: Option<&str>,
+) -> SourceSpec {
+    let version = resolved.version.as_str();
     match &spec.source {
         SourceConfig::Npm { package } => SourceSpec::Npm {
             package: package.clone(),
@@ -361,6 +1379,7 @@ fn source_spec_from_package(spec: &PackageSpec, version: &str, asset: Option<&st
         SourceConfig::Github {
             repo,
             asset_pattern,
+            include_prerelease,
         } => SourceSpec::Github {
             repo: repo.clone(),
             asset: asset_pattern
@@ -369,6 +1388,24 @@ fn source_spec_from_package(spec: &PackageSpec, version: &str, asset: Option<&st
                 .unwrap_or("platform")
                 .to_string(),
             version: version.to_string(),
+            include_prerelease: *include_prerelease,
+        },
+        SourceConfig::Gitlab {
+            repo,
+            asset_pattern,
+        } => SourceSpec::Gitlab {
+            repo: repo.clone(),
+            asset: asset_pattern
+                .as_deref()
+                .or(asset)
+                .unwrap_or("platform")
+                .to_string(),
+            version: version.to_string(),
+        },
+        SourceConfig::File { path } => SourceSpec::File {
+            path: path.clone(),
+            sha256: resolved.checksum.clone(),
+            version: version.to_string(),
         },
     }
 }
@@ -383,11 +1420,27 @@ fn source_spec_from_installed(installed: &InstalledBinary, version: &str) -> Sou
             crate_name: crate_name.clone(),
             version: version.to_string(),
         },
-        SourceSpec::Github { repo, asset, .. } => SourceSpec::Github {
+        SourceSpec::Github {
+            repo,
+            asset,
+            include_prerelease,
+            ..
+        } => SourceSpec::Github {
+            repo: repo.clone(),
+            asset: asset.clone(),
+            version: version.to_string(),
+            include_prerelease: *include_prerelease,
+        },
+        SourceSpec::Gitlab { repo, asset, .. } => SourceSpec::Gitlab {
             repo: repo.clone(),
             asset: asset.clone(),
             version: version.to_string(),
         },
+        SourceSpec::File { path, sha256, .. } => SourceSpec::File {
+            path: path.clone(),
+            sha256: sha256.clone(),
+            version: version.to_string(),
+        },
     }
 }
 
@@ -395,21 +1448,30 @@ fn package_from_installed(installed: &InstalledBinary) -> Result<PackageSpec> {
     match &installed.source {
         SourceSpec::Npm { package, .. } => Ok(PackageSpec {
             name: package.clone(),
-            version_req: None,
+            version_req: installed.version_req.clone(),
             source: SourceConfig::Npm {
                 package: package.clone(),
             },
             binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
         }),
         SourceSpec::Cargo { crate_name, .. } => Ok(PackageSpec {
             name: crate_name.clone(),
-            version_req: None,
+            version_req: installed.version_req.clone(),
             source: SourceConfig::Cargo {
                 crate_name: crate_name.clone(),
             },
             binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
         }),
-        SourceSpec::Github { repo, asset, .. } => {
+        SourceSpec::Github {
+            repo,
+            asset,
+            include_prerelease,
+            ..
+        } => {
             let asset_pattern = if asset == "platform" {
                 None
             } else {
@@ -417,14 +1479,44 @@ fn package_from_installed(installed: &InstalledBinary) -> Result<PackageSpec> {
             };
             Ok(PackageSpec {
                 name: repo.clone(),
-                version_req: None,
+                version_req: installed.version_req.clone(),
                 source: SourceConfig::Github {
                     repo: repo.clone(),
                     asset_pattern,
+                    include_prerelease: *include_prerelease,
                 },
                 binary_name: Some(installed.binary.clone()),
+                all_bins: !installed.binaries.is_empty(),
+                fallback: None,
             })
         }
+        SourceSpec::Gitlab { repo, asset, .. } => {
+            let asset_pattern = if asset == "platform" {
+                None
+            } else {
+                Some(asset.clone())
+            };
+            Ok(PackageSpec {
+                name: repo.clone(),
+                version_req: installed.version_req.clone(),
+                source: SourceConfig::Gitlab {
+                    repo: repo.clone(),
+                    asset_pattern,
+                },
+                binary_name: Some(installed.binary.clone()),
+                all_bins: !installed.binaries.is_empty(),
+                fallback: None,
+            })
+        }
+        // A local file only ever provides the version it was installed as
+        SourceSpec::File { path, version, .. } => Ok(PackageSpec {
+            name: fetchbin::source::file::default_name(path),
+            version_req: Some(version.clone()),
+            source: SourceConfig::File { path: path.clone() },
+            binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
+        }),
     }
 }
 
@@ -442,6 +1534,8 @@ fn installed_version_source(installed: &InstalledBinary) -> (String, String) {
         SourceSpec::Npm { version, .. } => (version.clone(), "npm".to_string()),
         SourceSpec::Cargo { version, .. } => (version.clone(), "cargo".to_string()),
         SourceSpec::Github { version, .. } => (version.clone(), "github".to_string()),
+        SourceSpec::Gitlab { version, .. } => (version.clone(), "gitlab".to_string()),
+        SourceSpec::File { path, version, .. } => (version.clone(), format!("file:{path}")),
     }
 }
 
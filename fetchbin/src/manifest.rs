@@ -1,3 +1,4 @@
+use crate::elf::LibcRequirement;
 use crate::error::ManifestError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -27,7 +28,10 @@ impl Manifest {
             fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        // Write beside it and rename, so a crash never leaves half a manifest
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, path)?;
         Ok(())
     }
 
@@ -35,6 +39,45 @@ impl Manifest {
         let base = dirs::data_dir()?;
         Some(base.join("fetchbin").join("manifest.json"))
     }
+
+    /// The entry whose links include `link`, other than the entry named `except`.
+    pub fn owner_of(&self, link: &str, except: &str) -> Option<&str> {
+        let mut owners: Vec<&String> = self
+            .binaries
+            .iter()
+            .filter(|(name, installed)| {
+                name.as_str() != except && installed.linked_binaries().contains(&link)
+            })
+            .map(|(name, _)| name)
+            .collect();
+        owners.sort();
+        owners.first().map(|name| name.as_str())
+    }
+
+    /// Decide what `update` should do with each installed binary, sorted by name.
+    pub fn update_candidates(&self) -> Vec<UpdateCandidate> {
+        let mut names: Vec<&String> = self.binaries.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| match self.binaries[name].pinned_version.as_deref() {
+                Some(version) => UpdateCandidate::Pinned {
+                    name: name.clone(),
+                    version: version.to_string(),
+                },
+                None => UpdateCandidate::Check(name.clone()),
+            })
+            .collect()
+    }
+}
+
+/// How `update` treats a single installed binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateCandidate {
+    /// Check the source for a newer version.
+    Check(String),
+    /// Held at `version` by `fetchbin pin`; left alone.
+    Pinned { name: String, version: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -62,10 +105,57 @@ pub struct PnpmRuntimeManifest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstalledBinary {
     pub source: SourceSpec,
+    /// The primary binary; also the manifest key.
     pub binary: String,
+    /// The version requirement it was installed with (e.g. `^1`); `update`
+    /// only moves to versions that satisfy it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_req: Option<String>,
+    /// Every binary linked from the package, when installed with `--all-bins`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<String>,
     pub sha256: String,
     pub installed_at: String,
     pub runtime: Option<RuntimeVersionSpec>,
+    /// Version this binary is held at; `update` skips pinned binaries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
+    /// The platform-specific npm package the native executable came from,
+    /// for packages like esbuild that ship it as an optional dependency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_package: Option<NativePackage>,
+    /// The C library the primary binary needs, detected at install time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libc: Option<LibcRequirement>,
+}
+
+/// A platform-specific npm sub-package (e.g. `@esbuild/linux-x64`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NativePackage {
+    pub package: String,
+    pub version: String,
+}
+
+impl InstalledBinary {
+    /// The installed version, regardless of source.
+    pub fn version(&self) -> &str {
+        match &self.source {
+            SourceSpec::Npm { version, .. }
+            | SourceSpec::Cargo { version, .. }
+            | SourceSpec::Github { version, .. }
+            | SourceSpec::Gitlab { version, .. }
+            | SourceSpec::File { version, .. } => version,
+        }
+    }
+
+    /// Names of every link in the bin dir that belongs to this entry.
+    pub fn linked_binaries(&self) -> Vec<&str> {
+        if self.binaries.is_empty() {
+            vec![self.binary.as_str()]
+        } else {
+            self.binaries.iter().map(String::as_str).collect()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +173,22 @@ pub enum SourceSpec {
         repo: String,
         asset: String,
         version: String,
+        /// Updates consider pre-releases too.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        include_prerelease: bool,
+    },
+    Gitlab {
+        repo: String,
+        asset: String,
+        version: String,
+    },
+    /// Installed from a local path; `sha256` is that of the archive or
+    /// executable it was installed from (absent for a directory).
+    File {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+        version: String,
     },
 }
 
@@ -107,11 +213,16 @@ mod tests {
                     version: "2.3.4".to_string(),
                 },
                 binary: "turbo".to_string(),
+                version_req: None,
+                binaries: Vec::new(),
                 sha256: "abc123".to_string(),
                 installed_at: "2026-01-27T10:00:00Z".to_string(),
                 runtime: Some(RuntimeVersionSpec::Node {
                     version: "22.2.0".to_string(),
                 }),
+                pinned_version: None,
+                native_package: None,
+                libc: None,
             },
         );
 
@@ -120,4 +231,162 @@ mod tests {
         assert_eq!(restored.binaries.len(), 1);
         assert!(restored.binaries.contains_key("turbo"));
     }
+
+    fn npm_binary(name: &str, version: &str, pinned_version: Option<&str>) -> InstalledBinary {
+        InstalledBinary {
+            source: SourceSpec::Npm {
+                package: name.to_string(),
+                version: version.to_string(),
+            },
+            binary: name.to_string(),
+            version_req: None,
+            binaries: Vec::new(),
+            sha256: "abc123".to_string(),
+            installed_at: "2026-01-27T10:00:00Z".to_string(),
+            runtime: None,
+            pinned_version: pinned_version.map(str::to_string),
+            native_package: None,
+            libc: None,
+        }
+    }
+
+    #[test]
+    fn manifest_roundtrip_preserves_pin() {
+        let mut manifest = Manifest::default();
+        manifest.binaries.insert(
+            "turbo".to_string(),
+            npm_binary("turbo", "2.3.4", Some("2.3.4")),
+        );
+        manifest
+            .binaries
+            .insert("tsc".to_string(), npm_binary("tsc", "5.4.0", None));
+
+        let json = serde_json::to_string(&manifest).expect("serialize");
+        let restored: Manifest = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(
+            restored.binaries["turbo"].pinned_version.as_deref(),
+            Some("2.3.4")
+        );
+        assert_eq!(restored.binaries["tsc"].pinned_version, None);
+    }
+
+    #[test]
+    fn manifest_without_pin_field_loads_unpinned() {
+        let json = r#"{
+            "binaries": {
+                "turbo": {
+                    "source": { "type": "npm", "package": "turbo", "version": "2.3.4" },
+                    "binary": "turbo",
+                    "sha256": "abc123",
+                    "installed_at": "1700000000",
+                    "runtime": null
+                }
+            }
+        }"#;
+        let manifest: Manifest = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(manifest.binaries["turbo"].pinned_version, None);
+        assert!(!serde_json::to_string(&manifest)
+            .expect("serialize")
+            .contains("pinned_version"));
+    }
+
+    #[test]
+    fn manifest_without_version_req_loads_unconstrained() {
+        let json = r#"{
+            "binaries": {
+                "turbo": {
+                    "source": { "type": "npm", "package": "turbo", "version": "1.13.4" },
+                    "binary": "turbo",
+                    "sha256": "abc123",
+                    "installed_at": "1700000000",
+                    "runtime": null
+                }
+            }
+        }"#;
+        let mut manifest: Manifest = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(manifest.binaries["turbo"].version_req, None);
+        assert!(!serde_json::to_string(&manifest)
+            .expect("serialize")
+            .contains("version_req"));
+
+        manifest.binaries.get_mut("turbo").unwrap().version_req = Some("^1".to_string());
+        let json = serde_json::to_string(&manifest).expect("serialize");
+        let restored: Manifest = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(
+            restored.binaries["turbo"].version_req.as_deref(),
+            Some("^1")
+        );
+    }
+
+    #[test]
+    fn save_replaces_manifest_without_leaving_temp_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("manifest.json");
+        fs::write(&path, "{}").unwrap();
+
+        let mut manifest = Manifest::default();
+        manifest
+            .binaries
+            .insert("tsc".to_string(), npm_binary("tsc", "5.4.0", None));
+        manifest.save(&path).unwrap();
+
+        assert!(Manifest::load(&path).unwrap().binaries.contains_key("tsc"));
+        let names: Vec<_> = fs::read_dir(temp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["manifest.json"]);
+    }
+
+    #[test]
+    fn linked_binaries_fall_back_to_primary() {
+        let single = npm_binary("turbo", "2.3.4", None);
+        assert_eq!(single.linked_binaries(), vec!["turbo"]);
+
+        let mut multi = npm_binary("biome", "1.9.0", None);
+        multi.binaries = vec!["biome".to_string(), "biome-lsp".to_string()];
+        assert_eq!(multi.linked_binaries(), vec!["biome", "biome-lsp"]);
+        assert!(!serde_json::to_string(&single)
+            .expect("serialize")
+            .contains("binaries"));
+    }
+
+    #[test]
+    fn owner_of_finds_other_entries_only() {
+        let mut manifest = Manifest::default();
+        let mut fd = npm_binary("fd", "10.0.0", None);
+        fd.binaries = vec!["fd".to_string(), "fdfind".to_string()];
+        manifest.binaries.insert("fd".to_string(), fd);
+        manifest
+            .binaries
+            .insert("rg".to_string(), npm_binary("rg", "14.0.0", None));
+
+        assert_eq!(manifest.owner_of("fdfind", "other"), Some("fd"));
+        assert_eq!(manifest.owner_of("rg", "other"), Some("rg"));
+        assert_eq!(manifest.owner_of("fdfind", "fd"), None);
+        assert_eq!(manifest.owner_of("bat", "other"), None);
+    }
+
+    #[test]
+    fn update_candidates_skip_pinned_binaries() {
+        let mut manifest = Manifest::default();
+        manifest.binaries.insert(
+            "turbo".to_string(),
+            npm_binary("turbo", "2.3.4", Some("2.3.4")),
+        );
+        manifest
+            .binaries
+            .insert("tsc".to_string(), npm_binary("tsc", "5.4.0", None));
+
+        assert_eq!(
+            manifest.update_candidates(),
+            vec![
+                UpdateCandidate::Check("tsc".to_string()),
+                UpdateCandidate::Pinned {
+                    name: "turbo".to_string(),
+                    version: "2.3.4".to_string(),
+                },
+            ]
+        );
+    }
 }
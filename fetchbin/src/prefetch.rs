@@ -0,0 +1,188 @@
+//! Parallel artifact downloads ahead of installs.
+//!
+//! `fetchbin update` downloads every pending artifact up front, a few at a
+//! time, and the sources then take those bytes from here instead of
+//! downloading them again. Everything after the download (extraction,
+//! runtimes, the manifest) stays on the calling thread.
+
+use crate::error::FetchError;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Artifacts downloaded by [`prefetch`] and not yet claimed, by URL.
+static PREFETCHED: Mutex<BTreeMap<String, Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+/// Default number of downloads to run at once.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// An artifact to download ahead of its install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    /// The binary the artifact is for (for progress output)
+    pub name: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Progress callbacks for [`prefetch`], called from the worker threads.
+pub trait DownloadProgress: Sync {
+    /// `downloaded` bytes of `downloads[index]` have arrived, out of `total`
+    /// if the server said.
+    fn progress(&self, index: usize, downloaded: u64, total: Option<u64>);
+
+    /// `downloads[index]` finished, successfully or not.
+    fn finished(&self, index: usize, result: &Result<(), FetchError>);
+}
+
+/// Claim the prefetched bytes for `url`, if there are any.
+pub fn take(url: &str) -> Option<Vec<u8>> {
+    PREFETCHED.lock().unwrap().remove(url)
+}
+
+/// Download an artifact, using the prefetched bytes for `url` if there are any.
+pub fn download(url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, FetchError> {
+    if let Some(bytes) = take(url) {
+        return Ok(bytes);
+    }
+    bkt_common::http::download_with_headers(url, headers)
+        .map_err(|err| FetchError::Network(err.to_string()))
+}
+
+/// Download `downloads` with up to `jobs` worker threads, keeping the bytes
+/// for [`download`] to hand out.
+///
+/// One download failing doesn't stop the others. Results are returned in
+/// the same order as `downloads`.
+pub fn prefetch(
+    downloads: &[Download],
+    jobs: usize,
+    progress: &dyn DownloadProgress,
+) -> Vec<Result<(), FetchError>> {
+    let next = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<Result<(), FetchError>>>> =
+        Mutex::new(downloads.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.max(1).min(downloads.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(download) = downloads.get(index) else {
+                    break;
+                };
+
+                let headers: Vec<(&str, &str)> = download
+                    .headers
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str()))
+                    .collect();
+                let result = bkt_common::http::download_with_progress(
+                    &download.url,
+                    &headers,
+                    |downloaded, total| progress.progress(index, downloaded, total),
+                )
+                .map(|bytes| {
+                    PREFETCHED
+                        .lock()
+                        .unwrap()
+                        .insert(download.url.clone(), bytes);
+                })
+                .map_err(|err| FetchError::Network(err.to_string()));
+
+                progress.finished(index, &result);
+                slots.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    slots
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|slot| slot.expect("every download is run by a worker"))
+        .collect()
+}
+
+/// Drop any prefetched artifacts nothing claimed.
+pub fn clear() {
+    PREFETCHED.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[derive(Default)]
+    struct Recorder {
+        finished: Mutex<Vec<(usize, bool)>>,
+        totals: Mutex<BTreeMap<usize, Option<u64>>>,
+    }
+
+    impl DownloadProgress for Recorder {
+        fn progress(&self, index: usize, _downloaded: u64, total: Option<u64>) {
+            self.totals.lock().unwrap().insert(index, total);
+        }
+
+        fn finished(&self, index: usize, result: &Result<(), FetchError>) {
+            self.finished.lock().unwrap().push((index, result.is_ok()));
+        }
+    }
+
+    fn request(server: &Server, path: &str) -> Download {
+        Download {
+            name: path.trim_start_matches('/').to_string(),
+            url: format!("{}{path}", server.url()),
+            headers: vec![("User-Agent".to_string(), "fetchbin".to_string())],
+        }
+    }
+
+    #[test]
+    fn prefetch_downloads_in_parallel_and_keeps_going_after_a_failure() {
+        let mut server = Server::new();
+        let a = server
+            .mock("GET", "/a.tgz")
+            .with_body("first artifact")
+            .create();
+        let b = server.mock("GET", "/b.tgz").with_status(404).create();
+        let c = server
+            .mock("GET", "/c.tgz")
+            .with_body("third artifact")
+            .create();
+
+        let downloads = vec![
+            request(&server, "/a.tgz"),
+            request(&server, "/b.tgz"),
+            request(&server, "/c.tgz"),
+        ];
+        let recorder = Recorder::default();
+        let results = prefetch(&downloads, 2, &recorder);
+
+        a.assert();
+        b.assert();
+        c.assert();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        let mut finished = recorder.finished.into_inner().unwrap();
+        finished.sort();
+        assert_eq!(finished, vec![(0, true), (1, false), (2, true)]);
+        assert_eq!(recorder.totals.lock().unwrap()[&0], Some(14));
+
+        // Prefetched bytes are handed out once, without another request
+        assert_eq!(download(&downloads[2].url, &[]).unwrap(), b"third artifact");
+        let again = server
+            .mock("GET", "/c.tgz")
+            .with_body("downloaded again")
+            .create();
+        assert_eq!(
+            download(&downloads[2].url, &[]).unwrap(),
+            b"downloaded again"
+        );
+        again.assert();
+
+        clear();
+        assert!(!PREFETCHED.lock().unwrap().contains_key(&downloads[0].url));
+    }
+}
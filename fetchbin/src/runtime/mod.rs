@@ -5,7 +5,9 @@ use crate::runtime::node::{download_node, resolve_node_runtime, NodeVersionIndex
 use crate::runtime::pnpm::{
     download_pnpm, fetch_latest_pnpm_version, resolve_pnpm_runtime, PnpmRuntime,
 };
-use crate::source::github::checksum::{parse_checksum_file, sha256_hex};
+use bkt_common::checksum::find_checksum;
+use bkt_common::error::CommonError;
+use bkt_common::http::{DownloadOptions, Downloader};
 use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -138,24 +140,47 @@ impl RuntimePool {
             .find(|asset| asset.name == asset_name)
             .ok_or_else(|| RuntimeError::BinstallAssetNotFound(asset_name.to_string()))?;
 
-        let bytes = bkt_common::http::download_with_headers(
-            &asset.browser_download_url,
-            &headers,
-        )
-        .map_err(|err| RuntimeError::BinstallDownloadFailed(err.to_string()))?;
-
         let checksum_asset = release
             .assets
             .iter()
             .find(|asset| asset.name == "checksums.txt")
             .ok_or_else(|| RuntimeError::BinstallAssetNotFound("checksums.txt".to_string()))?;
-        let checksum_bytes = bkt_common::http::download_with_headers(
+        let checksum_text = bkt_common::http::download_text_with_headers(
             &checksum_asset.browser_download_url,
             &headers,
         )
         .map_err(|err| RuntimeError::BinstallDownloadFailed(err.to_string()))?;
-        let checksum_text = String::from_utf8_lossy(&checksum_bytes);
-        verify_binstall_checksum(&checksum_text, &asset.name, &bytes)?;
+        let expected = find_checksum(&checksum_text, &asset.name).ok_or_else(|| {
+            RuntimeError::ShasumParse(format!("checksum entry not found for {}", asset.name))
+        })?;
+
+        let archive = self
+            .data_dir
+            .join("toolchains")
+            .join("cargo-binstall")
+            .join(&asset.name);
+        let downloaded = Downloader::new()
+            .user_agent("fetchbin")
+            .download(
+                &asset.browser_download_url,
+                &archive,
+                &DownloadOptions {
+                    checksum: Some(&expected),
+                    ..Default::default()
+                },
+            )
+            .map_err(|err| match err {
+                CommonError::ChecksumMismatch { expected, actual } => {
+                    RuntimeError::ChecksumMismatch {
+                        filename: asset.name.clone(),
+                        expected,
+                        actual,
+                    }
+                }
+                other => RuntimeError::BinstallDownloadFailed(other.to_string()),
+            })?;
+        let bytes = fs::read(&downloaded.path)?;
+        fs::remove_file(&downloaded.path)?;
 
         if dest.exists() {
             fs::remove_dir_all(&dest)?;
@@ -319,40 +344,6 @@ pub(crate) fn binstall_asset_name(platform: &Platform) -> Result<&'static str, R
     }
 }
 
-fn verify_binstall_checksum(
-    checksum_text: &str,
-    asset_name: &str,
-    bytes: &[u8],
-) -> Result<(), RuntimeError> {
-    let checksums = parse_checksum_file(checksum_text);
-    let expected = checksums
-        .get(asset_name)
-        .or_else(|| {
-            let name = Path::new(asset_name)
-                .file_name()
-                .and_then(|name| name.to_str())?;
-            checksums.get(name)
-        })
-        .cloned();
-
-    let Some(expected) = expected else {
-        return Err(RuntimeError::ShasumParse(format!(
-            "checksum entry not found for {asset_name}"
-        )));
-    };
-
-    let actual = sha256_hex(bytes);
-    if expected.to_lowercase() != actual {
-        return Err(RuntimeError::ChecksumMismatch {
-            filename: asset_name.to_string(),
-            expected,
-            actual,
-        });
-    }
-
-    Ok(())
-}
-
 fn resolve_binstall_binary(dest: &Path) -> Option<PathBuf> {
     find_binstall_binary(dest)
 }
@@ -480,24 +471,4 @@ mod tests {
             "cargo-binstall-aarch64-unknown-linux-gnu.tgz"
         );
     }
-
-    #[test]
-    fn test_verify_binstall_checksum() {
-        let bytes = b"hello";
-        let hash = sha256_hex(bytes);
-        let checksum_text = format!("{hash}  cargo-binstall.tgz\n");
-
-        verify_binstall_checksum(&checksum_text, "cargo-binstall.tgz", bytes)
-            .expect("checksum ok");
-    }
-
-    #[test]
-    fn test_verify_binstall_checksum_mismatch() {
-        let bytes = b"hello";
-        let checksum_text = "deadbeef  cargo-binstall.tgz\n";
-
-        let err = verify_binstall_checksum(&checksum_text, "cargo-binstall.tgz", bytes)
-            .expect_err("checksum error");
-        assert!(matches!(err, RuntimeError::ChecksumMismatch { .. }));
-    }
 }
@@ -0,0 +1,304 @@
+//! Installing a downloaded release asset, whatever shape it ships in.
+//!
+//! Release assets are tarballs (gzip, xz or zstd), zip archives, or bare
+//! executables. The format comes from the asset name, or from the leading
+//! bytes when the name doesn't say.
+
+use crate::error::FetchError;
+use crate::source::find_executables;
+use bkt_common::archive::{
+    extract_tar_gz, extract_tar_xz, extract_tar_zst, extract_zip, sniff_archive_type, write_raw,
+    ArchiveType,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name fragments that mark where a bare executable's name stops and its
+/// platform (or version) suffix starts, e.g. `jq-linux-amd64`.
+const PLATFORM_TOKENS: &[&str] = &[
+    "linux", "darwin", "macos", "osx", "apple", "windows", "win", "win64", "amd64", "x86", "x64",
+    "i386", "i686", "386", "arm64", "aarch64", "arm", "armv7", "armhf", "musl", "gnu", "static",
+    "unknown",
+];
+
+/// Install `bytes`, downloaded as `asset_name`, into `target_dir` and return
+/// the binary to link.
+///
+/// `bin` is the `--bin` the user asked for, which must exist in an archive.
+/// Without one, archives are searched for `default_name` and then for the
+/// largest executable, and a bare executable is named after the asset.
+pub(crate) fn install_asset(
+    asset_name: &str,
+    bytes: &[u8],
+    target_dir: &Path,
+    bin: Option<&str>,
+    default_name: &str,
+) -> Result<PathBuf, FetchError> {
+    fs::create_dir_all(target_dir)?;
+
+    match sniff_archive_type(asset_name, bytes) {
+        ArchiveType::TarGz => extract_tar_gz(bytes, target_dir, 0)?,
+        ArchiveType::TarXz => extract_tar_xz(bytes, target_dir, 0)?,
+        ArchiveType::TarZst => extract_tar_zst(bytes, target_dir, 0)?,
+        ArchiveType::Zip => extract_zip(bytes, target_dir)?,
+        ArchiveType::Raw => {
+            let name = bin
+                .map(str::to_string)
+                .unwrap_or_else(|| raw_binary_name(asset_name));
+            return Ok(write_raw(bytes, target_dir, &name)?);
+        }
+    }
+
+    pick_executable(target_dir, bin, default_name, asset_name)
+}
+
+/// The binary to link from an extracted archive.
+///
+/// A file named `bin` (when given) or `default_name` wins, with or without
+/// an exec bit since zip archives don't always keep one. Otherwise the
+/// largest executable is taken, which skips helper scripts shipped next to
+/// the real binary. An explicit `bin` that isn't there is an error.
+pub(crate) fn pick_executable(
+    dir: &Path,
+    bin: Option<&str>,
+    default_name: &str,
+    package: &str,
+) -> Result<PathBuf, FetchError> {
+    let wanted = bin.unwrap_or(default_name);
+    if let Some(found) = find_named(dir, wanted)? {
+        return Ok(found);
+    }
+
+    let executables = find_executables(dir)?;
+    if bin.is_some() {
+        return Err(FetchError::BinaryNotFound {
+            package: package.to_string(),
+            searched: executables.iter().map(|path| file_name(path)).collect(),
+        });
+    }
+
+    let mut largest: Option<(u64, PathBuf)> = None;
+    for path in executables {
+        let size = fs::metadata(&path)?.len();
+        if largest.as_ref().is_none_or(|(best, _)| size > *best) {
+            largest = Some((size, path));
+        }
+    }
+    largest
+        .map(|(_, path)| path)
+        .ok_or_else(|| FetchError::NoExecutable(package.to_string()))
+}
+
+/// The command name for a bare executable asset: the asset name up to its
+/// platform or version suffix (`yq_linux_amd64` -> `yq`).
+pub(crate) fn raw_binary_name(asset_name: &str) -> String {
+    let name = asset_name
+        .strip_suffix(".exe")
+        .or_else(|| asset_name.strip_suffix(".bin"))
+        .unwrap_or(asset_name);
+
+    let suffix_start = name
+        .match_indices(['-', '_', '.'])
+        .map(|(index, _)| index)
+        .find(|&index| {
+            let token = name[index + 1..]
+                .split(['-', '_', '.'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+            is_platform_token(&token)
+        });
+
+    match suffix_start {
+        Some(index) if index > 0 => name[..index].to_string(),
+        _ => name.to_string(),
+    }
+}
+
+fn is_platform_token(token: &str) -> bool {
+    let version = token.strip_prefix('v').unwrap_or(token);
+    PLATFORM_TOKENS.contains(&token) || version.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// The first file (in path order) called `name` or `name.exe` under `dir`.
+pub(crate) fn find_named(dir: &Path, name: &str) -> Result<Option<PathBuf>, FetchError> {
+    let exe = format!("{name}.exe");
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if let Some(found) = find_named(&path, name)? {
+                return Ok(Some(found));
+            }
+        } else if entry.file_name() == name || entry.file_name() == exe.as_str() {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const ENTRIES: &[(&str, &[u8], u32)] = &[
+        ("lazygit_0.44.1/LICENSE", b"MIT", 0o644),
+        ("lazygit_0.44.1/completions.sh", b"#!/bin/sh\n", 0o755),
+        ("lazygit_0.44.1/lazygit", b"\x7fELF-lazygit-binary", 0o755),
+    ];
+
+    fn tar(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data, mode) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(*mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn tar_gz(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar(entries)).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn tar_xz(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        lzma_rs::xz_compress(&mut tar(entries).as_slice(), &mut compressed).unwrap();
+        compressed
+    }
+
+    fn tar_zst(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        zstd::encode_all(tar(entries).as_slice(), 0).unwrap()
+    }
+
+    fn zip(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, data, mode) in entries {
+            let options = zip::write::FileOptions::default().unix_permissions(*mode);
+            writer.start_file(*path, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn installs_binary_from_each_archive_format() {
+        let cases = [
+            ("lazygit_0.44.1_Linux_x86_64.tar.gz", tar_gz(ENTRIES)),
+            ("lazygit_0.44.1_Linux_x86_64.tar.xz", tar_xz(ENTRIES)),
+            ("lazygit_0.44.1_Linux_x86_64.tar.zst", tar_zst(ENTRIES)),
+            ("lazygit_0.44.1_Linux_x86_64.zip", zip(ENTRIES)),
+        ];
+
+        for (asset, bytes) in cases {
+            let temp = tempfile::tempdir().unwrap();
+            let path = install_asset(asset, &bytes, temp.path(), None, "lazygit").unwrap();
+            assert_eq!(
+                path,
+                temp.path().join("lazygit_0.44.1/lazygit"),
+                "from {asset}"
+            );
+        }
+    }
+
+    #[test]
+    fn detects_archives_without_an_extension() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = install_asset(
+            "lazygit-linux-amd64",
+            &tar_zst(ENTRIES),
+            temp.path(),
+            None,
+            "lazygit",
+        )
+        .unwrap();
+        assert_eq!(path, temp.path().join("lazygit_0.44.1/lazygit"));
+    }
+
+    #[test]
+    fn falls_back_to_largest_executable() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = install_asset("lg.zip", &zip(ENTRIES), temp.path(), None, "lg").unwrap();
+        assert_eq!(path, temp.path().join("lazygit_0.44.1/lazygit"));
+    }
+
+    #[test]
+    fn explicit_bin_must_exist() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = install_asset(
+            "lazygit.tar.gz",
+            &tar_gz(ENTRIES),
+            temp.path(),
+            Some("completions.sh"),
+            "lazygit",
+        )
+        .unwrap();
+        assert_eq!(path, temp.path().join("lazygit_0.44.1/completions.sh"));
+
+        let err = install_asset(
+            "lazygit.tar.gz",
+            &tar_gz(ENTRIES),
+            temp.path(),
+            Some("lg"),
+            "lazygit",
+        )
+        .unwrap_err();
+        assert!(matches!(err, FetchError::BinaryNotFound { .. }));
+    }
+
+    #[test]
+    fn archive_without_executables_is_an_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = install_asset(
+            "docs.tar.gz",
+            &tar_gz(&[("docs/README.md", b"# docs", 0o644)]),
+            temp.path(),
+            None,
+            "docs",
+        )
+        .unwrap_err();
+        assert!(matches!(err, FetchError::NoExecutable(_)));
+    }
+
+    #[test]
+    fn installs_bare_executable_under_asset_or_bin_name() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = install_asset("jq-linux-amd64", b"\x7fELF-jq", temp.path(), None, "jq").unwrap();
+        assert_eq!(path, temp.path().join("jq"));
+
+        let path = install_asset(
+            "jq-linux-amd64",
+            b"\x7fELF-jq",
+            temp.path(),
+            Some("jq17"),
+            "jq",
+        )
+        .unwrap();
+        assert_eq!(path, temp.path().join("jq17"));
+    }
+
+    #[test]
+    fn raw_binary_name_strips_platform_suffix() {
+        assert_eq!(raw_binary_name("jq-linux-amd64"), "jq");
+        assert_eq!(raw_binary_name("yq_linux_amd64"), "yq");
+        assert_eq!(
+            raw_binary_name("docker-compose-linux-x86_64"),
+            "docker-compose"
+        );
+        assert_eq!(raw_binary_name("kubectl"), "kubectl");
+        assert_eq!(raw_binary_name("shfmt_v3.10.0_linux_amd64"), "shfmt");
+        assert_eq!(raw_binary_name("tool.exe"), "tool");
+    }
+}
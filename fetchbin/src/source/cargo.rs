@@ -2,26 +2,76 @@ use crate::error::FetchError;
 use crate::manifest::{InstalledBinary, SourceSpec};
 use crate::runtime::RuntimePool;
 use crate::source::github::checksum::sha256_hex;
-use crate::source::{BinarySource, FetchedBinary, PackageSpec, ResolvedVersion, SourceConfig};
+use crate::source::{
+    filter_by_requirement, find_executables, normalize_version_req, BinarySource, FetchedBinary,
+    PackageSpec, ResolvedVersion, SourceConfig,
+};
+use bkt_common::error::CommonError;
+use bkt_common::http::Revalidated;
 use semver::{Version, VersionReq};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// The crates.io sparse index, one newline-delimited JSON file per crate.
+const SPARSE_INDEX: &str = "https://index.crates.io";
+
 pub struct CargoSource {
     data_dir: PathBuf,
+    index_base: String,
 }
 
 impl CargoSource {
     pub fn new(data_dir: PathBuf) -> Self {
-        Self { data_dir }
+        Self::with_index_base(data_dir, SPARSE_INDEX)
+    }
+
+    pub fn with_index_base(data_dir: PathBuf, base: impl Into<String>) -> Self {
+        Self {
+            data_dir,
+            index_base: base.into(),
+        }
     }
 
-    fn fetch_metadata(&self, crate_name: &str) -> Result<CratesIoResponse, FetchError> {
-        let url = format!("https://crates.io/api/v1/crates/{crate_name}");
-        bkt_common::http::download_json::<CratesIoResponse>(&url, &[])
-            .map_err(|err| FetchError::CratesIoApi(err.to_string()))
+    /// The crate's index file, revalidated against the cached copy's ETag
+    /// so an unchanged crate costs a 304 and no download.
+    fn fetch_index(&self, crate_name: &str) -> Result<String, FetchError> {
+        let path = index_path(crate_name);
+        let url = format!("{}/{}", self.index_base.trim_end_matches('/'), path);
+        let cached = self.data_dir.join("cache").join("crates-index").join(&path);
+        let etag_path = cached.with_extension("etag");
+
+        let etag = fs::read_to_string(&etag_path)
+            .ok()
+            .filter(|_| cached.exists());
+        let response = bkt_common::http::download_if_modified(&url, etag.as_deref()).map_err(
+            |err| match err {
+                CommonError::HttpStatus { status: 404, .. } => {
+                    FetchError::CratesIndex(format!("crate {crate_name} not found"))
+                }
+                err => FetchError::CratesIndex(err.to_string()),
+            },
+        )?;
+
+        match response {
+            Revalidated::NotModified => Ok(fs::read_to_string(&cached)?),
+            Revalidated::Modified { body, etag } => {
+                let body = String::from_utf8(body)
+                    .map_err(|err| FetchError::CratesIndex(format!("{url}: {err}")))?;
+                if let Some(parent) = cached.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&cached, &body)?;
+                match etag {
+                    Some(etag) => fs::write(&etag_path, etag)?,
+                    None => {
+                        let _ = fs::remove_file(&etag_path);
+                    }
+                }
+                Ok(body)
+            }
+        }
     }
 }
 
@@ -34,25 +84,34 @@ impl Default for CargoSource {
     }
 }
 
+/// One published version, as a line of a sparse index file.
 #[derive(Debug, Deserialize)]
-struct CratesIoResponse {
-    #[serde(rename = "crate")]
-    krate: CrateInfo,
-    versions: Vec<CrateVersion>,
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct CrateInfo {
-    #[allow(dead_code)]
-    name: String,
-    max_version: String,
+/// Where a crate's file lives in the sparse index: `1/a`, `2/ab`,
+/// `3/a/abc`, and `ri/pg/ripgrep` for longer names.
+fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct CrateVersion {
-    num: String,
-    #[serde(default)]
-    yanked: bool,
+fn parse_index(body: &str) -> Result<Vec<IndexEntry>, FetchError> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .map_err(|err| FetchError::Parse(format!("invalid crates index entry: {err}")))
+        })
+        .collect()
 }
 
 impl BinarySource for CargoSource {
@@ -70,8 +129,8 @@ impl BinarySource for CargoSource {
             }
         };
 
-        let metadata = self.fetch_metadata(crate_name)?;
-        resolve_versions(&metadata, spec.version_req.as_deref())
+        let entries = parse_index(&self.fetch_index(crate_name)?)?;
+        resolve_versions(&entries, spec.normalized_version_req().as_deref())
     }
 
     fn fetch(
@@ -116,11 +175,23 @@ impl BinarySource for CargoSource {
             return Err(FetchError::BinstallFailed(stderr.to_string()));
         }
 
+        let installed_bins = if spec.all_bins {
+            find_executables(&target_dir.join("bin"))?
+        } else {
+            Vec::new()
+        };
+
         let binary_name = spec
             .binary_name
             .clone()
             .unwrap_or_else(|| crate_name.to_string());
-        let binary_path = target_dir.join("bin").join(&binary_name);
+        let mut binary_path = target_dir.join("bin").join(&binary_name);
+        if !binary_path.exists() && spec.binary_name.is_none() {
+            // Crates like ripgrep don't ship a binary named after the crate
+            if let Some(first) = installed_bins.first() {
+                binary_path = first.clone();
+            }
+        }
         if !binary_path.exists() {
             return Err(FetchError::BinaryNotFound {
                 package: crate_name.to_string(),
@@ -130,12 +201,19 @@ impl BinarySource for CargoSource {
 
         set_executable(&binary_path)?;
         let sha256 = sha256_hex(&fs::read(&binary_path)?);
+        let binary_paths = if installed_bins.is_empty() {
+            vec![binary_path.clone()]
+        } else {
+            installed_bins
+        };
 
         Ok(FetchedBinary {
             binary_path,
+            binary_paths,
             version: version.version.clone(),
             sha256,
             runtime_used: None,
+            native_package: None,
         })
     }
 
@@ -157,18 +235,20 @@ impl BinarySource for CargoSource {
 
         let spec = PackageSpec {
             name: crate_name.clone(),
-            version_req: None,
+            version_req: _installed.version_req.as_deref().map(normalize_version_req),
             source: SourceConfig::Cargo {
                 crate_name: crate_name.clone(),
             },
             binary_name: Some(_installed.binary.clone()),
+            all_bins: !_installed.binaries.is_empty(),
+            fallback: None,
         };
 
-        let latest = self
-            .resolve(&spec)?
-            .into_iter()
-            .next()
-            .ok_or_else(|| FetchError::Parse("no versions returned".to_string()))?;
+        let latest =
+            filter_by_requirement(self.resolve(&spec)?, _installed.version_req.as_deref())?
+                .into_iter()
+                .next()
+                .ok_or_else(|| FetchError::Parse("no versions returned".to_string()))?;
 
         if versions_match(&latest.version, current_version) {
             Ok(None)
@@ -178,14 +258,19 @@ impl BinarySource for CargoSource {
     }
 }
 
+/// The highest non-yanked version satisfying `version_req`.
+///
+/// Without a requirement (or with `latest`) that's the highest stable
+/// version, falling back to prereleases only for crates that have nothing
+/// else, as crates.io's `max_version` does.
 fn resolve_versions(
-    metadata: &CratesIoResponse,
+    entries: &[IndexEntry],
     version_req: Option<&str>,
 ) -> Result<Vec<ResolvedVersion>, FetchError> {
-    let available: Vec<&CrateVersion> = metadata
-        .versions
+    let available: Vec<Version> = entries
         .iter()
-        .filter(|version| !version.yanked)
+        .filter(|entry| !entry.yanked)
+        .filter_map(|entry| Version::parse(&entry.vers).ok())
         .collect();
 
     if available.is_empty() {
@@ -194,85 +279,37 @@ fn resolve_versions(
         ));
     }
 
-    let resolved = if let Some(req) = version_req {
-        if req == "latest" {
-            latest_version(metadata, &available)
-        } else if let Some(version) = find_version(&available, req) {
-            vec![resolved_from_version(version)]
-        } else if let Ok(req) = VersionReq::parse(req) {
-            let mut matching = matching_versions(&available, &req);
-            if matching.is_empty() {
+    let best = match version_req {
+        None | Some("latest") => available
+            .iter()
+            .filter(|version| version.pre.is_empty())
+            .max()
+            .or_else(|| available.iter().max()),
+        Some(req) => {
+            let parsed = VersionReq::parse(req).map_err(|_| {
+                FetchError::Parse(format!("unsupported cargo version requirement: {req}"))
+            })?;
+            let best = available
+                .iter()
+                .filter(|version| parsed.matches(version))
+                .max();
+            if best.is_none() {
                 return Err(FetchError::Parse(format!("no versions matching {req}")));
             }
-            matching
-                .drain(..)
-                .map(|(_, version)| resolved_from_version(version))
-                .collect()
-        } else {
-            return Err(FetchError::Parse(format!(
-                "unsupported cargo version requirement: {req}"
-            )));
+            best
         }
-    } else {
-        latest_version(metadata, &available)
     };
 
-    Ok(resolved)
-}
-
-fn latest_version(
-    metadata: &CratesIoResponse,
-    available: &[&CrateVersion],
-) -> Vec<ResolvedVersion> {
-    let max_version = metadata.krate.max_version.as_str();
-    if let Some(version) = available
-        .iter()
-        .find(|version| versions_match(&version.num, max_version))
-    {
-        return vec![resolved_from_version(version)];
-    }
-
-    let mut parsed: Vec<(Version, &CrateVersion)> = available
-        .iter()
-        .filter_map(|version| Version::parse(&version.num).ok().map(|v| (v, *version)))
-        .collect();
-    parsed.sort_by(|(left, _), (right, _)| right.cmp(left));
-    parsed
-        .first()
-        .map(|(_, version)| vec![resolved_from_version(version)])
-        .unwrap_or_default()
-}
-
-fn resolved_from_version(version: &CrateVersion) -> ResolvedVersion {
-    ResolvedVersion {
-        version: version.num.clone(),
-        download_url: None,
-        checksum: None,
-        engines: None,
-    }
-}
-
-fn find_version<'a>(versions: &'a [&CrateVersion], requested: &str) -> Option<&'a CrateVersion> {
-    versions
-        .iter()
-        .find(|version| versions_match(&version.num, requested))
-        .copied()
-}
-
-fn matching_versions<'a>(
-    versions: &'a [&CrateVersion],
-    req: &VersionReq,
-) -> Vec<(Version, &'a CrateVersion)> {
-    let mut matches = Vec::new();
-    for version in versions {
-        if let Ok(parsed) = Version::parse(&version.num) {
-            if req.matches(&parsed) {
-                matches.push((parsed, *version));
-            }
-        }
-    }
-    matches.sort_by(|(left, _), (right, _)| right.cmp(left));
-    matches
+    Ok(best
+        .map(|version| {
+            vec![ResolvedVersion {
+                version: version.to_string(),
+                download_url: None,
+                checksum: None,
+                engines: None,
+            }]
+        })
+        .unwrap_or_default())
 }
 
 fn versions_match(left: &str, right: &str) -> bool {
@@ -299,37 +336,118 @@ fn set_executable(path: &Path) -> Result<(), FetchError> {
 mod tests {
     use super::*;
 
+    const RIPGREP_INDEX: &str = include_str!("../../tests/fixtures/crates-index/ripgrep");
+    const PRERELEASE_INDEX: &str = include_str!("../../tests/fixtures/crates-index/prerelease");
+
+    fn resolved(index: &str, req: Option<&str>) -> Result<Vec<String>, FetchError> {
+        let entries = parse_index(index)?;
+        Ok(resolve_versions(&entries, req)?
+            .into_iter()
+            .map(|resolved| resolved.version)
+            .collect())
+    }
+
     #[test]
-    fn test_parse_crates_io_response() {
-        let json = r#"{
-            "crate": { "name": "ripgrep", "max_version": "14.1.0" },
-            "versions": [
-                { "num": "14.1.0", "yanked": false },
-                { "num": "14.0.0", "yanked": false }
+    fn test_index_path_follows_sparse_layout() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("xz"), "2/xz");
+        assert_eq!(index_path("bat"), "3/b/bat");
+        assert_eq!(index_path("ripgrep"), "ri/pg/ripgrep");
+        assert_eq!(index_path("Inflector"), "in/fl/inflector");
+    }
+
+    #[test]
+    fn test_parse_index_file() {
+        let entries = parse_index(RIPGREP_INDEX).expect("parse");
+        let versions: Vec<&str> = entries.iter().map(|e| e.vers.as_str()).collect();
+        assert_eq!(
+            versions,
+            vec![
+                "13.0.0",
+                "14.0.0",
+                "14.1.0",
+                "14.1.1",
+                "15.0.0-beta.1",
+                "15.0.0"
             ]
-        }"#;
+        );
+        assert!(entries[3].yanked);
+        assert!(!entries[2].yanked);
+    }
 
-        let parsed: CratesIoResponse = serde_json::from_str(json).expect("parse");
-        assert_eq!(parsed.krate.name, "ripgrep");
-        assert_eq!(parsed.krate.max_version, "14.1.0");
-        assert_eq!(parsed.versions.len(), 2);
-        assert!(!parsed.versions[0].yanked);
+    #[test]
+    fn test_resolve_skips_yanked_and_prerelease_versions() {
+        // 15.0.0 is yanked, 14.1.1 is yanked; 15.0.0-beta.1 is a prerelease
+        assert_eq!(resolved(RIPGREP_INDEX, None).unwrap(), vec!["14.1.0"]);
+        assert_eq!(
+            resolved(RIPGREP_INDEX, Some("latest")).unwrap(),
+            vec!["14.1.0"]
+        );
+        assert_eq!(
+            resolved(RIPGREP_INDEX, Some("^13")).unwrap(),
+            vec!["13.0.0"]
+        );
+        assert_eq!(
+            resolved(RIPGREP_INDEX, Some(&normalize_version_req("14.0"))).unwrap(),
+            vec!["14.1.0"]
+        );
+        assert_eq!(
+            resolved(RIPGREP_INDEX, Some("=15.0.0-beta.1")).unwrap(),
+            vec!["15.0.0-beta.1"]
+        );
+        assert!(resolved(RIPGREP_INDEX, Some("^16")).is_err());
     }
 
     #[test]
-    fn test_resolve_crate_versions() {
-        let json = r#"{
-            "crate": { "name": "bat", "max_version": "1.3.0" },
-            "versions": [
-                { "num": "1.3.0", "yanked": false },
-                { "num": "1.2.0", "yanked": false },
-                { "num": "2.0.0", "yanked": true }
-            ]
-        }"#;
+    fn test_resolve_falls_back_to_prereleases() {
+        assert_eq!(
+            resolved(PRERELEASE_INDEX, None).unwrap(),
+            vec!["0.2.0-alpha.2"]
+        );
+        assert!(resolved("{\"vers\":\"1.0.0\",\"yanked\":true}\n", None).is_err());
+    }
 
-        let parsed: CratesIoResponse = serde_json::from_str(json).expect("parse");
-        let resolved = resolve_versions(&parsed, Some("^1.0")).expect("resolve");
-        let versions: Vec<&str> = resolved.iter().map(|r| r.version.as_str()).collect();
-        assert_eq!(versions, vec!["1.3.0", "1.2.0"]);
+    #[test]
+    fn test_index_is_cached_and_revalidated() {
+        let mut server = mockito::Server::new();
+        let fresh = server
+            .mock("GET", "/ri/pg/ripgrep")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"abc\"")
+            .with_body(RIPGREP_INDEX)
+            .expect(1)
+            .create();
+        let revalidated = server
+            .mock("GET", "/ri/pg/ripgrep")
+            .match_header("if-none-match", "\"abc\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+
+        let data_dir = tempfile::tempdir().unwrap();
+        let source = CargoSource::with_index_base(data_dir.path().to_path_buf(), server.url());
+        assert_eq!(source.fetch_index("ripgrep").unwrap(), RIPGREP_INDEX);
+        assert_eq!(source.fetch_index("ripgrep").unwrap(), RIPGREP_INDEX);
+
+        fresh.assert();
+        revalidated.assert();
+        assert!(data_dir
+            .path()
+            .join("cache/crates-index/ri/pg/ripgrep")
+            .exists());
+    }
+
+    #[test]
+    fn test_missing_crate_is_reported() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/no/su/nosuchcrate")
+            .with_status(404)
+            .create();
+        let data_dir = tempfile::tempdir().unwrap();
+        let source = CargoSource::with_index_base(data_dir.path().to_path_buf(), server.url());
+
+        let err = source.fetch_index("nosuchcrate").unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
     }
 }
@@ -0,0 +1,342 @@
+use crate::error::FetchError;
+use crate::manifest::InstalledBinary;
+use crate::runtime::RuntimePool;
+use crate::source::asset::find_named;
+use crate::source::{
+    archive_binary_paths, find_executables, BinarySource, FetchedBinary, PackageSpec,
+    ResolvedVersion, SourceConfig,
+};
+use bkt_common::archive::{
+    detect_archive_type, extract_tar_gz, extract_tar_xz, extract_tar_zst, extract_zip,
+    set_executable, write_raw, ArchiveType,
+};
+use bkt_common::checksum::sha256_hex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Installs from a local archive, executable, or directory, for machines
+/// without network access. The version comes from the spec, and there are
+/// never updates.
+pub struct FileSource;
+
+impl FileSource {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FileSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinarySource for FileSource {
+    fn source_type(&self) -> &'static str {
+        "file"
+    }
+
+    fn resolve(&self, spec: &PackageSpec) -> Result<Vec<ResolvedVersion>, FetchError> {
+        let path = local_path(spec)?;
+        let version = spec
+            .version_req
+            .as_deref()
+            .map(|version| version.trim().trim_start_matches('='))
+            .filter(|version| !version.is_empty())
+            .ok_or_else(|| {
+                FetchError::Parse(format!(
+                    "file sources need a version, e.g. file:{}@1.0.0",
+                    path.display()
+                ))
+            })?;
+
+        Ok(vec![ResolvedVersion {
+            version: version.to_string(),
+            download_url: Some(path.display().to_string()),
+            checksum: source_sha256(&path)?,
+            engines: None,
+        }])
+    }
+
+    fn fetch(
+        &self,
+        spec: &PackageSpec,
+        version: &ResolvedVersion,
+        target_dir: &Path,
+        _runtime: &mut RuntimePool,
+    ) -> Result<FetchedBinary, FetchError> {
+        let path = local_path(spec)?;
+        fs::create_dir_all(target_dir)?;
+
+        let binary_path = if path.is_dir() {
+            copy_dir(&path, target_dir)?;
+            select_executable(spec, &path, target_dir)?
+        } else {
+            let name = file_name(&path);
+            let bytes = fs::read(&path)?;
+            match detect_archive_type(&name) {
+                ArchiveType::TarGz => {
+                    extract_tar_gz(&bytes, target_dir, 0)?;
+                    select_executable(spec, &path, target_dir)?
+                }
+                ArchiveType::TarXz => {
+                    extract_tar_xz(&bytes, target_dir, 0)?;
+                    select_executable(spec, &path, target_dir)?
+                }
+                ArchiveType::TarZst => {
+                    extract_tar_zst(&bytes, target_dir, 0)?;
+                    select_executable(spec, &path, target_dir)?
+                }
+                ArchiveType::Zip => {
+                    extract_zip(&bytes, target_dir)?;
+                    select_executable(spec, &path, target_dir)?
+                }
+                ArchiveType::Raw => {
+                    let binary_name = spec.binary_name.as_deref().unwrap_or(&name);
+                    write_raw(&bytes, target_dir, binary_name)?
+                }
+            }
+        };
+
+        set_executable(&binary_path)?;
+
+        let sha256 = sha256_hex(&fs::read(&binary_path)?);
+        let binary_paths = archive_binary_paths(spec, target_dir, &binary_path)?;
+
+        Ok(FetchedBinary {
+            binary_path,
+            binary_paths,
+            version: version.version.clone(),
+            sha256,
+            runtime_used: None,
+            native_package: None,
+        })
+    }
+
+    fn check_update(
+        &self,
+        _installed: &InstalledBinary,
+    ) -> Result<Option<ResolvedVersion>, FetchError> {
+        Ok(None)
+    }
+}
+
+/// The spec's local path, which must exist.
+fn local_path(spec: &PackageSpec) -> Result<PathBuf, FetchError> {
+    let SourceConfig::File { path } = &spec.source else {
+        return Err(FetchError::Parse(
+            "FileSource used with non-file spec".to_string(),
+        ));
+    };
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(FetchError::LocalPathNotFound(path.display().to_string()));
+    }
+    Ok(path)
+}
+
+/// sha256 of what a `file:` spec points at: the archive or executable
+/// itself, or nothing for a directory.
+fn source_sha256(path: &Path) -> Result<Option<String>, FetchError> {
+    if path.is_dir() {
+        return Ok(None);
+    }
+    Ok(Some(sha256_hex(&fs::read(path)?)))
+}
+
+/// Default name for a `file:` spec: the file name without archive suffix.
+pub fn default_name(path: &str) -> String {
+    let name = file_name(Path::new(path.trim_end_matches('/')));
+    for suffix in [
+        ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.zst", ".tzst", ".zip",
+    ] {
+        if let Some(stem) = name.strip_suffix(suffix) {
+            return stem.to_string();
+        }
+    }
+    name
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// The binary to link from the staged files: `--bin` if given, otherwise
+/// the only executable there is.
+fn select_executable(
+    spec: &PackageSpec,
+    source: &Path,
+    staged: &Path,
+) -> Result<PathBuf, FetchError> {
+    let executables = find_executables(staged)?;
+
+    if let Some(binary_name) = spec.binary_name.as_deref() {
+        return find_named(staged, binary_name)?.ok_or_else(|| FetchError::BinaryNotFound {
+            package: source.display().to_string(),
+            searched: executables.iter().map(|path| file_name(path)).collect(),
+        });
+    }
+
+    match executables.as_slice() {
+        [] => Err(FetchError::NoExecutable(source.display().to_string())),
+        [only] => Ok(only.clone()),
+        _ => Err(FetchError::MultipleBinaries {
+            binaries: executables.iter().map(|path| file_name(path)).collect(),
+        }),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), FetchError> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn runtime(dir: &Path) -> RuntimePool {
+        RuntimePool::load(dir.to_path_buf()).expect("runtime pool")
+    }
+
+    fn tar_gz(entries: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, data, mode) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(*mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn parses_file_spec_with_version() {
+        let spec =
+            PackageSpec::from_str("file:/srv/stage/ripgrep-14.1.0.tar.gz@14.1.0").expect("parse");
+        assert_eq!(spec.name, "ripgrep-14.1.0");
+        assert_eq!(spec.version_req.as_deref(), Some("14.1.0"));
+        assert_eq!(
+            spec.source,
+            SourceConfig::File {
+                path: "/srv/stage/ripgrep-14.1.0.tar.gz".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_requires_existing_path_and_version() {
+        let temp = tempfile::tempdir().unwrap();
+        let archive = temp.path().join("tool.tar.gz");
+        fs::write(&archive, b"").unwrap();
+
+        let missing = PackageSpec::from_str("file:/does/not/exist@1.0").unwrap();
+        assert!(matches!(
+            FileSource.resolve(&missing),
+            Err(FetchError::LocalPathNotFound(_))
+        ));
+
+        let unversioned = PackageSpec::from_str(&format!("file:{}", archive.display())).unwrap();
+        assert!(FileSource.resolve(&unversioned).is_err());
+
+        let spec = PackageSpec::from_str(&format!("file:{}@1.2.3", archive.display())).unwrap();
+        assert_eq!(FileSource.resolve(&spec).unwrap()[0].version, "1.2.3");
+    }
+
+    #[test]
+    fn fetch_extracts_named_binary_from_archive() {
+        let temp = tempfile::tempdir().unwrap();
+        let archive = temp.path().join("ripgrep-14.1.0.tar.gz");
+        fs::write(
+            &archive,
+            tar_gz(&[
+                ("ripgrep-14.1.0/rg", b"\x7fELF-rg", 0o755),
+                ("ripgrep-14.1.0/README.md", b"# rg", 0o644),
+            ]),
+        )
+        .unwrap();
+
+        let mut spec =
+            PackageSpec::from_str(&format!("file:{}@14.1.0", archive.display())).unwrap();
+        spec.binary_name = Some("rg".to_string());
+        let version = FileSource.resolve(&spec).unwrap().remove(0);
+        let target = temp.path().join("store");
+
+        let fetched = FileSource
+            .fetch(&spec, &version, &target, &mut runtime(temp.path()))
+            .unwrap();
+        assert_eq!(fetched.binary_path, target.join("ripgrep-14.1.0/rg"));
+        assert_eq!(fetched.version, "14.1.0");
+        assert_eq!(fetched.sha256, sha256_hex(b"\x7fELF-rg"));
+    }
+
+    #[test]
+    fn fetch_errors_when_archive_has_no_executable() {
+        let temp = tempfile::tempdir().unwrap();
+        let archive = temp.path().join("docs.tar.gz");
+        fs::write(&archive, tar_gz(&[("docs/README.md", b"# docs", 0o644)])).unwrap();
+
+        let spec = PackageSpec::from_str(&format!("file:{}@1.0", archive.display())).unwrap();
+        let version = FileSource.resolve(&spec).unwrap().remove(0);
+        let err = FileSource
+            .fetch(
+                &spec,
+                &version,
+                &temp.path().join("store"),
+                &mut runtime(temp.path()),
+            )
+            .unwrap_err();
+        assert!(matches!(err, FetchError::NoExecutable(_)));
+    }
+
+    #[test]
+    fn fetch_copies_raw_executable_and_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let raw = temp.path().join("just");
+        fs::write(&raw, b"\x7fELF-just").unwrap();
+
+        let spec = PackageSpec::from_str(&format!("file:{}@1.36.0", raw.display())).unwrap();
+        let version = FileSource.resolve(&spec).unwrap().remove(0);
+        let fetched = FileSource
+            .fetch(
+                &spec,
+                &version,
+                &temp.path().join("store-raw"),
+                &mut runtime(temp.path()),
+            )
+            .unwrap();
+        assert_eq!(fetched.binary_path, temp.path().join("store-raw/just"));
+
+        let dir = temp.path().join("staged");
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin/fd"), b"\x7fELF-fd").unwrap();
+        fs::write(dir.join("LICENSE"), b"MIT").unwrap();
+
+        let spec = PackageSpec::from_str(&format!("file:{}@10.0.0", dir.display())).unwrap();
+        let version = FileSource.resolve(&spec).unwrap().remove(0);
+        let fetched = FileSource
+            .fetch(
+                &spec,
+                &version,
+                &temp.path().join("store-dir"),
+                &mut runtime(temp.path()),
+            )
+            .unwrap();
+        assert_eq!(fetched.binary_path, temp.path().join("store-dir/bin/fd"));
+        assert_eq!(source_sha256(&dir).unwrap(), None);
+    }
+}
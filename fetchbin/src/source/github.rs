@@ -7,30 +7,62 @@ use crate::error::FetchError;
 use crate::manifest::{InstalledBinary, SourceSpec};
 use crate::platform::Platform;
 use crate::runtime::RuntimePool;
-use crate::source::{BinarySource, FetchedBinary, PackageSpec, ResolvedVersion, SourceConfig};
-use api::{Asset, Release};
-use bkt_common::archive::{
-    detect_archive_type, extract_tar_gz_binary, extract_zip_binary, set_executable, write_raw,
-    ArchiveType,
+use crate::source::asset::install_asset;
+use crate::source::{
+    archive_binary_paths, filter_by_requirement, BinarySource, FetchedBinary, PackageSpec,
+    ResolvedVersion, SourceConfig,
 };
-use bkt_common::checksum::sha256_hex;
-use checksum::{find_checksum_asset, parse_checksum_file};
+use api::{Asset, Release};
+use bkt_common::archive::set_executable;
+use bkt_common::checksum::{find_checksum, sha256_hex, verify};
+use bkt_common::error::CommonError;
+use bkt_common::http::{DownloadOptions, Downloader};
+use checksum::find_checksum_asset;
 use glob::Pattern;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Environment variables checked, in order, for a GitHub token.
+const TOKEN_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
 
 pub struct GithubSource {
     headers: Vec<(String, String)>,
+    authenticated: bool,
+    downloader: Downloader,
 }
 
 impl GithubSource {
     pub fn new() -> Self {
+        Self::with_token(token_from_env())
+    }
+
+    /// A source using `token` for every API request and asset download.
+    pub fn with_token(token: Option<String>) -> Self {
         let mut headers = vec![("User-Agent".to_string(), "fetchbin".to_string())];
-        if let Ok(token) = env::var("GITHUB_TOKEN") {
-            headers.push(("Authorization".to_string(), format!("token {token}")));
+        if let Some(token) = &token {
+            headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+        }
+        Self {
+            headers,
+            authenticated: token.is_some(),
+            downloader: Downloader::new().user_agent("fetchbin").github_token(token),
         }
-        Self { headers }
+    }
+
+    /// Headers sent with every request, including any auth token.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Headers for downloading an asset from the URL [`Self::asset_url`]
+    /// gives.
+    pub fn asset_headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.headers.clone();
+        if self.authenticated {
+            headers.push(("Accept".to_string(), "application/octet-stream".to_string()));
+        }
+        headers
     }
 
     fn header_refs(&self) -> Vec<(&str, &str)> {
@@ -43,7 +75,16 @@ impl GithubSource {
     fn fetch_releases(&self, repo: &str) -> Result<Vec<Release>, FetchError> {
         let url = format!("https://api.github.com/repos/{repo}/releases");
         bkt_common::http::download_json::<Vec<Release>>(&url, &self.header_refs())
-            .map_err(|err| FetchError::GitHubApi(err.to_string()))
+            .map_err(|err| api_error(repo, err, self.authenticated))
+    }
+
+    /// Where to download `asset` from: with a token, the API endpoint,
+    /// since `browser_download_url` doesn't accept one for private repos.
+    fn asset_url<'a>(&self, asset: &'a Asset) -> Result<&'a str, FetchError> {
+        if self.authenticated && !asset.url.trim().is_empty() {
+            return Ok(&asset.url);
+        }
+        asset_url(asset)
     }
 
     fn find_asset<'a>(
@@ -51,70 +92,135 @@ impl GithubSource {
         release: &'a Release,
         pattern: Option<&str>,
     ) -> Result<&'a Asset, FetchError> {
-        let available: Vec<String> = release
-            .assets
+        select_asset(&release.assets, |asset| &asset.name, pattern)
+    }
+
+    fn download_checksums(&self, asset: &Asset) -> Result<String, FetchError> {
+        let url = self.asset_url(asset)?;
+        let headers = self.asset_headers();
+        let headers: Vec<(&str, &str)> = headers
             .iter()
-            .map(|asset| asset.name.clone())
+            .map(|(key, value)| (key.as_str(), value.as_str()))
             .collect();
+        let bytes = crate::prefetch::download(url, &headers)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
 
-        let mut candidates: Vec<&Asset> = if let Some(pattern) = pattern {
-            let pattern_lower = pattern.to_lowercase();
-            let use_glob = pattern_lower.contains('*')
-                || pattern_lower.contains('?')
-                || pattern_lower.contains('[');
-
-            if use_glob {
-                let pattern = Pattern::new(&pattern_lower)
-                    .map_err(|err| FetchError::Parse(err.to_string()))?;
-                release
-                    .assets
-                    .iter()
-                    .filter(|asset| pattern.matches(&asset.name.to_lowercase()))
-                    .collect()
-            } else {
-                release
-                    .assets
-                    .iter()
-                    .filter(|asset| asset.name.to_lowercase().contains(&pattern_lower))
-                    .collect()
-            }
-        } else {
-            let platform = Platform::current();
-            release
-                .assets
-                .iter()
-                .filter(|asset| platform.matches_asset(&asset.name))
-                .collect()
+    /// Download a release asset, checking it against `checksum` when known.
+    ///
+    /// Bytes already fetched by `fetchbin update` are used as they are;
+    /// otherwise the asset is downloaded to the cache (resuming an
+    /// interrupted download of the same release) and read back.
+    fn download_asset(
+        &self,
+        asset: &Asset,
+        dest: &Path,
+        checksum: Option<&str>,
+    ) -> Result<Vec<u8>, FetchError> {
+        let url = self.asset_url(asset)?;
+        let mismatch = |err| match err {
+            CommonError::ChecksumMismatch { expected, actual } => FetchError::ChecksumMismatch {
+                name: asset.name.clone(),
+                expected,
+                actual,
+            },
+            other => other.into(),
         };
 
-        if candidates.is_empty() {
-            let pattern_label = if let Some(pattern) = pattern {
-                pattern.to_string()
-            } else {
-                let platform = Platform::current();
-                platform.asset_patterns().join(",")
-            };
-
-            return Err(FetchError::AssetNotFound {
-                pattern: pattern_label,
-                available,
-            });
+        if let Some(bytes) = crate::prefetch::take(url) {
+            if let Some(expected) = checksum {
+                verify(&bytes, expected).map_err(mismatch)?;
+            }
+            return Ok(bytes);
         }
 
-        candidates.sort_by_key(|asset| asset_rank(&asset.name));
-        Ok(candidates[0])
+        let accept = [("Accept", "application/octet-stream")];
+        let options = DownloadOptions {
+            checksum,
+            headers: if self.authenticated { &accept } else { &[] },
+            ..Default::default()
+        };
+        let result = self
+            .downloader
+            .download(url, dest, &options)
+            .map_err(mismatch)?;
+        let bytes = fs::read(&result.path)?;
+        fs::remove_file(&result.path)?;
+        Ok(bytes)
     }
+}
 
-    fn download_asset(&self, asset: &Asset) -> Result<Vec<u8>, FetchError> {
-        if asset.browser_download_url.trim().is_empty() {
-            return Err(FetchError::NoDownloadUrl {
-                version: asset.name.clone(),
-            });
+/// The first non-empty token in [`TOKEN_VARS`].
+fn token_from_env() -> Option<String> {
+    TOKEN_VARS
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|token| !token.trim().is_empty())
+}
+
+/// Explain a failed API request for `repo`: GitHub answers 404 for
+/// private repos the caller can't see, so that needs spelling out.
+fn api_error(repo: &str, err: CommonError, authenticated: bool) -> FetchError {
+    let vars = TOKEN_VARS.join(" or ");
+    let message = match err {
+        CommonError::HttpStatus { status: 401, .. } => {
+            format!("GitHub rejected the token in {vars} (401): it is invalid or expired")
+        }
+        CommonError::HttpStatus { status: 403, .. } if authenticated => format!(
+            "GitHub refused access to {repo} (403): the token lacks access, or its rate limit was hit"
+        ),
+        CommonError::HttpStatus { status: 403, .. } => format!(
+            "GitHub refused access to {repo} (403): probably the anonymous rate limit; set {vars}"
+        ),
+        CommonError::HttpStatus { status: 404, .. } if authenticated => {
+            format!("repo {repo} not found, or the token lacks access to it")
         }
+        CommonError::HttpStatus { status: 404, .. } => {
+            format!("repo {repo} not found; if it is private, set {vars}")
+        }
+        other => other.to_string(),
+    };
+    FetchError::GitHubApi(message)
+}
+
+/// Whether a release is a pre-release: marked as one, or tagged like one
+/// (`v2.0.0-rc.1`, `nightly`) by projects that don't mark them.
+pub(crate) fn is_prerelease(release: &Release) -> bool {
+    let tag = release.tag_name.to_lowercase();
+    release.prerelease
+        || tag.contains("nightly")
+        || semver::Version::parse(normalize_version(&tag))
+            .is_ok_and(|version| !version.pre.is_empty())
+}
+
+/// The releases to consider, newest first: never drafts, and pre-releases
+/// only when asked for.
+fn candidate_releases(
+    releases: Vec<Release>,
+    include_prerelease: bool,
+) -> impl Iterator<Item = Release> {
+    releases
+        .into_iter()
+        .filter(move |release| !release.draft && (include_prerelease || !is_prerelease(release)))
+}
 
-        bkt_common::http::download_with_headers(&asset.browser_download_url, &self.header_refs())
-            .map_err(|err| FetchError::Network(err.to_string()))
+fn asset_url(asset: &Asset) -> Result<&str, FetchError> {
+    if asset.browser_download_url.trim().is_empty() {
+        return Err(FetchError::NoDownloadUrl {
+            version: asset.name.clone(),
+        });
     }
+    Ok(&asset.browser_download_url)
+}
+
+/// Where release assets are downloaded before they're installed.
+fn download_dir(repo: &str, tag: &str) -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(env::temp_dir)
+        .join("fetchbin")
+        .join("downloads")
+        .join(repo.replace('/', "-"))
+        .join(tag)
 }
 
 impl Default for GithubSource {
@@ -129,11 +235,12 @@ impl BinarySource for GithubSource {
     }
 
     fn resolve(&self, spec: &PackageSpec) -> Result<Vec<ResolvedVersion>, FetchError> {
-        let (repo, asset_pattern) = match &spec.source {
+        let (repo, asset_pattern, include_prerelease) = match &spec.source {
             SourceConfig::Github {
                 repo,
                 asset_pattern,
-            } => (repo.as_str(), asset_pattern.as_deref()),
+                include_prerelease,
+            } => (repo.as_str(), asset_pattern.as_deref(), *include_prerelease),
             _ => {
                 return Err(FetchError::Parse(
                     "GithubSource used with non-github spec".to_string(),
@@ -144,14 +251,11 @@ impl BinarySource for GithubSource {
         let releases = self.fetch_releases(repo)?;
         let mut resolved = Vec::new();
 
-        for release in releases
-            .into_iter()
-            .filter(|release| !release.prerelease && !release.draft)
-        {
+        for release in candidate_releases(releases, include_prerelease) {
             match self.find_asset(&release, asset_pattern) {
                 Ok(asset) => resolved.push(ResolvedVersion {
                     version: release.tag_name.clone(),
-                    download_url: Some(asset.browser_download_url.clone()),
+                    download_url: Some(self.asset_url(asset)?.to_string()),
                     checksum: None,
                     engines: None,
                 }),
@@ -183,6 +287,7 @@ impl BinarySource for GithubSource {
             SourceConfig::Github {
                 repo,
                 asset_pattern,
+                ..
             } => (repo.as_str(), asset_pattern.as_deref()),
             _ => {
                 return Err(FetchError::Parse(
@@ -203,64 +308,46 @@ impl BinarySource for GithubSource {
             return Err(FetchError::UnsupportedArchive(asset.name.clone()));
         }
 
-        let asset_bytes = self.download_asset(asset)?;
-
-        if let Some(checksum_asset) = find_checksum_asset(release, asset) {
-            let checksum_bytes = self.download_asset(checksum_asset)?;
-            let checksum_text = String::from_utf8_lossy(&checksum_bytes);
-            let checksums = parse_checksum_file(&checksum_text);
-            let expected = checksums
-                .get(&asset.name)
-                .or_else(|| {
-                    let name = Path::new(&asset.name)
-                        .file_name()
-                        .and_then(|name| name.to_str())?;
-                    checksums.get(name)
-                })
-                .cloned();
-
-            let Some(expected) = expected else {
-                return Err(FetchError::Parse(format!(
-                    "checksum entry not found for {}",
-                    asset.name
-                )));
-            };
-
-            let actual = sha256_hex(&asset_bytes);
-            if expected.to_lowercase() != actual {
-                return Err(FetchError::Parse(format!(
-                    "checksum mismatch for {}: expected {}, got {}",
-                    asset.name, expected, actual
-                )));
+        let expected = match find_checksum_asset(release, asset) {
+            Some(checksum_asset) => {
+                let checksums = self.download_checksums(checksum_asset)?;
+                let Some(expected) = find_checksum(&checksums, &asset.name) else {
+                    return Err(FetchError::Parse(format!(
+                        "checksum entry not found for {}",
+                        asset.name
+                    )));
+                };
+                Some(expected)
             }
-        } else {
-            eprintln!("warning: no checksum found for {}", asset.name);
-        }
-
-        fs::create_dir_all(target_dir)?;
-        let binary_name = spec
-            .binary_name
-            .clone()
-            .unwrap_or_else(|| repo_name(repo).to_string());
-
-        let binary_path = match detect_archive_type(&asset.name) {
-            ArchiveType::TarGz => extract_tar_gz_binary(&asset_bytes, target_dir, &binary_name)?,
-            ArchiveType::TarXz => {
-                return Err(FetchError::UnsupportedArchive(asset.name.clone()));
+            None => {
+                eprintln!("warning: no checksum found for {}", asset.name);
+                None
             }
-            ArchiveType::Zip => extract_zip_binary(&asset_bytes, target_dir, &binary_name)?,
-            ArchiveType::Raw => write_raw(&asset_bytes, target_dir, &binary_name)?,
         };
 
+        let dest = download_dir(repo, &release.tag_name).join(&asset.name);
+        let asset_bytes = self.download_asset(asset, &dest, expected.as_deref())?;
+
+        let binary_path = install_asset(
+            &asset.name,
+            &asset_bytes,
+            target_dir,
+            spec.binary_name.as_deref(),
+            repo_name(repo),
+        )?;
+
         set_executable(&binary_path)?;
 
         let sha256 = sha256_hex(&fs::read(&binary_path)?);
+        let binary_paths = archive_binary_paths(spec, target_dir, &binary_path)?;
 
         Ok(FetchedBinary {
             binary_path,
+            binary_paths,
             version: version.version.clone(),
             sha256,
             runtime_used: None,
+            native_package: None,
         })
     }
 
@@ -268,8 +355,13 @@ impl BinarySource for GithubSource {
         &self,
         installed: &InstalledBinary,
     ) -> Result<Option<ResolvedVersion>, FetchError> {
-        let (repo, current_version) = match &installed.source {
-            SourceSpec::Github { repo, version, .. } => (repo, version),
+        let (repo, current_version, include_prerelease) = match &installed.source {
+            SourceSpec::Github {
+                repo,
+                version,
+                include_prerelease,
+                ..
+            } => (repo, version, *include_prerelease),
             _ => {
                 return Err(FetchError::Parse(
                     "GithubSource used with non-github install".to_string(),
@@ -279,15 +371,20 @@ impl BinarySource for GithubSource {
 
         let spec = PackageSpec {
             name: repo_name(repo).to_string(),
-            version_req: None,
+            version_req: installed.version_req.clone(),
             source: SourceConfig::Github {
                 repo: repo.clone(),
                 asset_pattern: None,
+                include_prerelease,
             },
             binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
         };
 
-        let releases = self.resolve(&spec)?;
+        // Releases aren't filtered by `resolve`, so apply the requirement here
+        let releases =
+            filter_by_requirement(self.resolve(&spec)?, installed.version_req.as_deref())?;
         let Some(latest) = releases.first() else {
             return Ok(None);
         };
@@ -300,6 +397,66 @@ impl BinarySource for GithubSource {
     }
 }
 
+/// Pick the best release asset for the current platform.
+///
+/// With a `pattern`, assets are matched case-insensitively as a glob (when the
+/// pattern contains glob metacharacters) or as a substring. Without one, the
+/// current platform's asset patterns are used. Ties prefer `.tar.gz`, then `.zip`.
+pub(crate) fn select_asset<'a, T>(
+    assets: &'a [T],
+    name_of: impl Fn(&T) -> &str,
+    pattern: Option<&str>,
+) -> Result<&'a T, FetchError> {
+    let available: Vec<String> = assets
+        .iter()
+        .map(|asset| name_of(asset).to_string())
+        .collect();
+
+    let mut candidates: Vec<&T> = if let Some(pattern) = pattern {
+        let pattern_lower = pattern.to_lowercase();
+        let use_glob = pattern_lower.contains('*')
+            || pattern_lower.contains('?')
+            || pattern_lower.contains('[');
+
+        if use_glob {
+            let pattern =
+                Pattern::new(&pattern_lower).map_err(|err| FetchError::Parse(err.to_string()))?;
+            assets
+                .iter()
+                .filter(|asset| pattern.matches(&name_of(asset).to_lowercase()))
+                .collect()
+        } else {
+            assets
+                .iter()
+                .filter(|asset| name_of(asset).to_lowercase().contains(&pattern_lower))
+                .collect()
+        }
+    } else {
+        let platform = Platform::current();
+        assets
+            .iter()
+            .filter(|asset| platform.matches_asset(name_of(asset)))
+            .collect()
+    };
+
+    if candidates.is_empty() {
+        let pattern_label = if let Some(pattern) = pattern {
+            pattern.to_string()
+        } else {
+            let platform = Platform::current();
+            platform.asset_patterns().join(",")
+        };
+
+        return Err(FetchError::AssetNotFound {
+            pattern: pattern_label,
+            available,
+        });
+    }
+
+    candidates.sort_by_key(|asset| asset_rank(name_of(asset)));
+    Ok(candidates[0])
+}
+
 fn asset_rank(name: &str) -> u8 {
     let lower = name.to_lowercase();
     if lower.ends_with(".tar.gz") {
@@ -311,11 +468,11 @@ fn asset_rank(name: &str) -> u8 {
     }
 }
 
-fn repo_name(repo: &str) -> &str {
+pub(crate) fn repo_name(repo: &str) -> &str {
     repo.rsplit('/').next().unwrap_or(repo)
 }
 
-fn versions_match(left: &str, right: &str) -> bool {
+pub(crate) fn versions_match(left: &str, right: &str) -> bool {
     normalize_version(left) == normalize_version(right)
 }
 
@@ -323,17 +480,92 @@ fn normalize_version(value: &str) -> &str {
     value.strip_prefix('v').unwrap_or(value)
 }
 
-fn is_unsupported_archive(name: &str) -> bool {
+pub(crate) fn is_unsupported_archive(name: &str) -> bool {
     let lower = name.to_lowercase();
     // Check for supported formats first (.tgz is equivalent to .tar.gz)
-    if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") || lower.ends_with(".zip") {
+    if [
+        ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.zst", ".tzst", ".zip",
+    ]
+    .iter()
+    .any(|suffix| lower.ends_with(suffix))
+    {
         return false;
     }
     // These are unsupported archive formats
     lower.ends_with(".tar")
         || lower.ends_with(".tar.bz2")
-        || lower.ends_with(".tar.xz")
         || lower.ends_with(".gz")
         || lower.ends_with(".bz2")
         || lower.ends_with(".xz")
+        || lower.ends_with(".zst")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASES: &str = include_str!("../../tests/fixtures/github/releases.json");
+
+    fn tags(include_prerelease: bool) -> Vec<String> {
+        let releases: Vec<Release> = serde_json::from_str(RELEASES).unwrap();
+        candidate_releases(releases, include_prerelease)
+            .map(|release| release.tag_name)
+            .collect()
+    }
+
+    #[test]
+    fn prereleases_and_drafts_are_skipped_by_default() {
+        assert_eq!(tags(false), ["v1.4.2"]);
+    }
+
+    #[test]
+    fn include_prerelease_keeps_them_but_never_drafts() {
+        assert_eq!(
+            tags(true),
+            ["nightly", "v2.0.0-rc.1", "v1.5.0-beta", "v1.4.2"]
+        );
+    }
+
+    #[test]
+    fn private_repo_errors_say_what_to_do() {
+        let message = |status, authenticated| {
+            let err = CommonError::HttpStatus {
+                url: "https://api.github.com/repos/me/private/releases".to_string(),
+                status,
+            };
+            api_error("me/private", err, authenticated).to_string()
+        };
+
+        assert!(message(404, false).contains("set GITHUB_TOKEN or GH_TOKEN"));
+        assert!(message(404, true).contains("token lacks access"));
+        assert!(message(401, true).contains("invalid or expired"));
+        assert!(message(403, false).contains("rate limit"));
+    }
+
+    #[test]
+    fn token_downloads_go_through_the_api() {
+        let asset = Asset {
+            url: "https://api.github.com/repos/me/private/releases/assets/1".to_string(),
+            name: "tool.tar.gz".to_string(),
+            browser_download_url: "https://github.com/me/private/releases/download/v1/tool.tar.gz"
+                .to_string(),
+            size: 0,
+        };
+
+        let anonymous = GithubSource::with_token(None);
+        assert_eq!(
+            anonymous.asset_url(&asset).unwrap(),
+            asset.browser_download_url
+        );
+        assert!(!anonymous
+            .asset_headers()
+            .iter()
+            .any(|(key, _)| key == "Accept"));
+
+        let authed = GithubSource::with_token(Some("secret".to_string()));
+        assert_eq!(authed.asset_url(&asset).unwrap(), asset.url);
+        let headers = authed.asset_headers();
+        assert!(headers.contains(&("Authorization".to_string(), "Bearer secret".to_string())));
+        assert!(headers.contains(&("Accept".to_string(), "application/octet-stream".to_string())));
+    }
 }
@@ -16,6 +16,11 @@ pub struct Release {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Asset {
+    /// API endpoint for the asset; with `Accept: application/octet-stream`
+    /// it serves the bytes, and unlike `browser_download_url` it takes a
+    /// token, so it works for private repos.
+    #[serde(default)]
+    pub url: String,
     pub name: String,
     #[serde(default)]
     pub browser_download_url: String,
@@ -2,16 +2,24 @@ use crate::source::github::api::{Asset, Release};
 
 pub use bkt_common::checksum::{parse_checksum_file, sha256_hex};
 
-pub fn find_checksum_asset<'a>(release: &'a Release, asset: &Asset) -> Option<&'a Asset> {
-    let preferred = format!("{}.sha256", asset.name);
-    if let Some(asset) = release.assets.iter().find(|item| item.name == preferred) {
-        return Some(asset);
-    }
+/// Per-asset checksum files (`<asset><suffix>`), strongest first.
+pub(crate) const CHECKSUM_SUFFIXES: &[&str] = &[".sha512", ".sha256"];
+
+/// Release-wide checksum files, in order of preference.
+pub(crate) const CHECKSUM_FILES: &[&str] = &[
+    "checksums.txt",
+    "SHASUMS512.txt",
+    "SHA512SUMS",
+    "SHASUMS256.txt",
+    "SHA256SUMS",
+];
 
-    let fallback = ["checksums.txt", "SHASUMS256.txt", "SHA256SUMS"];
-    fallback
+pub fn find_checksum_asset<'a>(release: &'a Release, asset: &Asset) -> Option<&'a Asset> {
+    let by_name = |name: &str| release.assets.iter().find(|item| item.name == name);
+    CHECKSUM_SUFFIXES
         .iter()
-        .find_map(|name| release.assets.iter().find(|item| item.name == *name))
+        .find_map(|suffix| by_name(&format!("{}{suffix}", asset.name)))
+        .or_else(|| CHECKSUM_FILES.iter().find_map(|name| by_name(name)))
 }
 
 #[cfg(test)]
@@ -60,4 +68,33 @@ SHA256 (tool.zip) = 6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617af
             Some(&"6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d".to_string())
         );
     }
+
+    #[test]
+    fn test_find_checksum_asset_prefers_strongest_per_asset_file() {
+        let release: Release = serde_json::from_value(serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [
+                {"name": "tool.tar.gz"},
+                {"name": "SHA256SUMS"},
+                {"name": "tool.tar.gz.sha256"},
+                {"name": "tool.tar.gz.sha512"},
+            ],
+        }))
+        .unwrap();
+        let asset = &release.assets[0];
+        assert_eq!(
+            find_checksum_asset(&release, asset).map(|a| a.name.as_str()),
+            Some("tool.tar.gz.sha512")
+        );
+
+        let release: Release = serde_json::from_value(serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [{"name": "tool.tar.gz"}, {"name": "SHA512SUMS"}],
+        }))
+        .unwrap();
+        assert_eq!(
+            find_checksum_asset(&release, &release.assets[0]).map(|a| a.name.as_str()),
+            Some("SHA512SUMS")
+        );
+    }
 }
@@ -0,0 +1,315 @@
+#[path = "gitlab/api.rs"]
+mod api;
+
+use crate::error::FetchError;
+use crate::manifest::{InstalledBinary, SourceSpec};
+use crate::runtime::RuntimePool;
+use crate::source::asset::install_asset;
+use crate::source::github::checksum::{CHECKSUM_FILES, CHECKSUM_SUFFIXES};
+use crate::source::github::{is_unsupported_archive, repo_name, select_asset, versions_match};
+use crate::source::{
+    archive_binary_paths, filter_by_requirement, BinarySource, FetchedBinary, PackageSpec,
+    ResolvedVersion, SourceConfig,
+};
+use api::{Link, Release};
+use bkt_common::archive::set_executable;
+use bkt_common::checksum::{parse_checksum_file, sha256_hex, verify};
+use bkt_common::error::CommonError;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_HOST: &str = "gitlab.com";
+
+pub struct GitlabSource {
+    headers: Vec<(String, String)>,
+}
+
+impl GitlabSource {
+    pub fn new() -> Self {
+        let mut headers = vec![("User-Agent".to_string(), "fetchbin".to_string())];
+        if let Ok(token) = env::var("GITLAB_TOKEN") {
+            headers.push(("PRIVATE-TOKEN".to_string(), token));
+        }
+        Self { headers }
+    }
+
+    /// Headers sent with every request, including any auth token.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    fn header_refs(&self) -> Vec<(&str, &str)> {
+        self.headers
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    fn fetch_releases(&self, repo: &str) -> Result<Vec<Release>, FetchError> {
+        let url = releases_url(repo);
+        bkt_common::http::download_json::<Vec<Release>>(&url, &self.header_refs())
+            .map_err(|err| FetchError::GitLabApi(err.to_string()))
+    }
+
+    fn find_asset<'a>(
+        &self,
+        release: &'a Release,
+        pattern: Option<&str>,
+    ) -> Result<&'a Link, FetchError> {
+        select_asset(&release.assets.links, |link| &link.name, pattern)
+    }
+
+    fn download_asset(&self, link: &Link) -> Result<Vec<u8>, FetchError> {
+        let url = link.download_url();
+        if url.trim().is_empty() {
+            return Err(FetchError::NoDownloadUrl {
+                version: link.name.clone(),
+            });
+        }
+
+        crate::prefetch::download(url, &self.header_refs())
+    }
+}
+
+impl Default for GitlabSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinarySource for GitlabSource {
+    fn source_type(&self) -> &'static str {
+        "gitlab"
+    }
+
+    fn resolve(&self, spec: &PackageSpec) -> Result<Vec<ResolvedVersion>, FetchError> {
+        let (repo, asset_pattern) = match &spec.source {
+            SourceConfig::Gitlab {
+                repo,
+                asset_pattern,
+            } => (repo.as_str(), asset_pattern.as_deref()),
+            _ => {
+                return Err(FetchError::Parse(
+                    "GitlabSource used with non-gitlab spec".to_string(),
+                ))
+            }
+        };
+
+        let releases = self.fetch_releases(repo)?;
+        let mut resolved = Vec::new();
+
+        for release in releases
+            .into_iter()
+            .filter(|release| !release.upcoming_release)
+        {
+            match self.find_asset(&release, asset_pattern) {
+                Ok(link) => resolved.push(ResolvedVersion {
+                    version: release.tag_name.clone(),
+                    download_url: Some(link.download_url().to_string()),
+                    checksum: None,
+                    engines: None,
+                }),
+                Err(FetchError::AssetNotFound { .. }) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if resolved.is_empty() {
+            return Err(FetchError::AssetNotFound {
+                pattern: asset_pattern
+                    .map(|pattern| pattern.to_string())
+                    .unwrap_or_else(|| "platform".to_string()),
+                available: Vec::new(),
+            });
+        }
+
+        Ok(resolved)
+    }
+
+    fn fetch(
+        &self,
+        spec: &PackageSpec,
+        version: &ResolvedVersion,
+        target_dir: &Path,
+        _runtime: &mut RuntimePool,
+    ) -> Result<FetchedBinary, FetchError> {
+        let (repo, asset_pattern) = match &spec.source {
+            SourceConfig::Gitlab {
+                repo,
+                asset_pattern,
+            } => (repo.as_str(), asset_pattern.as_deref()),
+            _ => {
+                return Err(FetchError::Parse(
+                    "GitlabSource used with non-gitlab spec".to_string(),
+                ))
+            }
+        };
+
+        let releases = self.fetch_releases(repo)?;
+        let release = releases
+            .iter()
+            .find(|release| versions_match(&release.tag_name, &version.version))
+            .ok_or_else(|| FetchError::Parse(format!("version {} not found", version.version)))?;
+
+        let link = self.find_asset(release, asset_pattern)?;
+
+        if is_unsupported_archive(&link.name) {
+            return Err(FetchError::UnsupportedArchive(link.name.clone()));
+        }
+
+        let asset_bytes = self.download_asset(link)?;
+
+        if let Some(checksum_link) = find_checksum_link(release, link) {
+            let checksum_bytes = self.download_asset(checksum_link)?;
+            let checksum_text = String::from_utf8_lossy(&checksum_bytes);
+            let checksums = parse_checksum_file(&checksum_text);
+
+            let Some(expected) = checksums.get(&link.name) else {
+                return Err(FetchError::Parse(format!(
+                    "checksum entry not found for {}",
+                    link.name
+                )));
+            };
+
+            verify(&asset_bytes, expected).map_err(|err| match err {
+                CommonError::ChecksumMismatch { expected, actual } => {
+                    FetchError::ChecksumMismatch {
+                        name: link.name.clone(),
+                        expected,
+                        actual,
+                    }
+                }
+                other => other.into(),
+            })?;
+        } else {
+            eprintln!("warning: no checksum found for {}", link.name);
+        }
+
+        let binary_path = install_asset(
+            &link.name,
+            &asset_bytes,
+            target_dir,
+            spec.binary_name.as_deref(),
+            repo_name(repo),
+        )?;
+
+        set_executable(&binary_path)?;
+
+        let sha256 = sha256_hex(&fs::read(&binary_path)?);
+        let binary_paths = archive_binary_paths(spec, target_dir, &binary_path)?;
+
+        Ok(FetchedBinary {
+            binary_path,
+            binary_paths,
+            version: version.version.clone(),
+            sha256,
+            runtime_used: None,
+            native_package: None,
+        })
+    }
+
+    fn check_update(
+        &self,
+        installed: &InstalledBinary,
+    ) -> Result<Option<ResolvedVersion>, FetchError> {
+        let (repo, asset, current_version) = match &installed.source {
+            SourceSpec::Gitlab {
+                repo,
+                asset,
+                version,
+            } => (repo, asset, version),
+            _ => {
+                return Err(FetchError::Parse(
+                    "GitlabSource used with non-gitlab install".to_string(),
+                ))
+            }
+        };
+
+        let spec = PackageSpec {
+            name: repo_name(repo).to_string(),
+            version_req: installed.version_req.clone(),
+            source: SourceConfig::Gitlab {
+                repo: repo.clone(),
+                asset_pattern: (asset != "platform").then(|| asset.clone()),
+            },
+            binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
+        };
+
+        // Releases aren't filtered by `resolve`, so apply the requirement here
+        let releases =
+            filter_by_requirement(self.resolve(&spec)?, installed.version_req.as_deref())?;
+        let Some(latest) = releases.first() else {
+            return Ok(None);
+        };
+
+        if versions_match(&latest.version, current_version) {
+            Ok(None)
+        } else {
+            Ok(Some(latest.clone()))
+        }
+    }
+}
+
+/// Split a repo spec into `(host, project_path)`.
+///
+/// A leading path segment containing a `.` is treated as the host of a
+/// self-hosted instance (`gitlab.example.com/group/project`); otherwise the
+/// project lives on gitlab.com. Project paths may include nested subgroups.
+pub fn split_host(repo: &str) -> (&str, &str) {
+    match repo.split_once('/') {
+        Some((host, path)) if host.contains('.') => (host, path),
+        _ => (DEFAULT_HOST, repo),
+    }
+}
+
+fn releases_url(repo: &str) -> String {
+    let (host, path) = split_host(repo);
+    let encoded = path.replace('/', "%2F");
+    format!("https://{host}/api/v4/projects/{encoded}/releases")
+}
+
+fn find_checksum_link<'a>(release: &'a Release, link: &Link) -> Option<&'a Link> {
+    let links = &release.assets.links;
+    let by_name = |name: &str| links.iter().find(|item| item.name == name);
+    CHECKSUM_SUFFIXES
+        .iter()
+        .find_map(|suffix| by_name(&format!("{}{suffix}", link.name)))
+        .or_else(|| CHECKSUM_FILES.iter().find_map(|name| by_name(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_host_defaults_to_gitlab_com() {
+        assert_eq!(split_host("group/project"), ("gitlab.com", "group/project"));
+        assert_eq!(
+            split_host("group/sub/project"),
+            ("gitlab.com", "group/sub/project")
+        );
+    }
+
+    #[test]
+    fn split_host_accepts_self_hosted_prefix() {
+        assert_eq!(
+            split_host("gitlab.example.com/group/project"),
+            ("gitlab.example.com", "group/project")
+        );
+    }
+
+    #[test]
+    fn releases_url_encodes_project_path() {
+        assert_eq!(
+            releases_url("gitlab-org/cli"),
+            "https://gitlab.com/api/v4/projects/gitlab-org%2Fcli/releases"
+        );
+        assert_eq!(
+            releases_url("git.example.org/team/tools/thing"),
+            "https://git.example.org/api/v4/projects/team%2Ftools%2Fthing/releases"
+        );
+    }
+}
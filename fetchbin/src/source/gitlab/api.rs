@@ -0,0 +1,91 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub upcoming_release: bool,
+    #[serde(default)]
+    pub assets: Assets,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Assets {
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Link {
+    pub name: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub direct_asset_url: Option<String>,
+}
+
+impl Link {
+    /// Prefer the permanent direct asset URL when GitLab provides one.
+    pub fn download_url(&self) -> &str {
+        self.direct_asset_url
+            .as_deref()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or(&self.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_release_response() {
+        let json = r#"
+        [
+            {
+                "tag_name": "v1.2.0",
+                "name": "Release 1.2.0",
+                "upcoming_release": false,
+                "assets": {
+                    "count": 2,
+                    "sources": [],
+                    "links": [
+                        {
+                            "name": "tool-linux-amd64.tar.gz",
+                            "url": "https://gitlab.com/group/tool/-/package_files/1/download",
+                            "direct_asset_url": "https://gitlab.com/group/tool/-/releases/v1.2.0/downloads/tool-linux-amd64.tar.gz",
+                            "link_type": "package"
+                        },
+                        {
+                            "name": "checksums.txt",
+                            "url": "https://example.com/checksums.txt"
+                        }
+                    ]
+                }
+            },
+            {
+                "tag_name": "v1.1.0",
+                "assets": { "links": [] }
+            }
+        ]
+        "#;
+
+        let releases: Vec<Release> = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].tag_name, "v1.2.0");
+        assert!(!releases[0].upcoming_release);
+        assert_eq!(releases[0].assets.links.len(), 2);
+        assert_eq!(
+            releases[0].assets.links[0].download_url(),
+            "https://gitlab.com/group/tool/-/releases/v1.2.0/downloads/tool-linux-amd64.tar.gz"
+        );
+        assert_eq!(
+            releases[0].assets.links[1].download_url(),
+            "https://example.com/checksums.txt"
+        );
+        assert!(releases[1].assets.links.is_empty());
+    }
+}
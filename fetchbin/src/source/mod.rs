@@ -1,17 +1,23 @@
 use crate::error::FetchError;
-use crate::manifest::InstalledBinary;
+use crate::manifest::{InstalledBinary, NativePackage};
 use crate::runtime::{RuntimePool, RuntimeVersion};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+mod asset;
 pub mod cargo;
+pub mod file;
 #[path = "github.rs"]
 pub mod github;
+#[path = "gitlab.rs"]
+pub mod gitlab;
 pub mod npm;
 
 pub use cargo::CargoSource;
+pub use file::FileSource;
 pub use github::GithubSource;
+pub use gitlab::GitlabSource;
 
 pub trait BinarySource: Send + Sync {
     fn source_type(&self) -> &'static str;
@@ -38,28 +44,119 @@ pub struct PackageSpec {
     pub version_req: Option<String>,
     pub source: SourceConfig,
     pub binary_name: Option<String>,
+    /// Link every executable the package ships, not just `binary_name`.
+    #[serde(default)]
+    pub all_bins: bool,
+    /// Where to get the binary when a release has no asset for this platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Fallback>,
+}
+
+/// Another source to install from when a GitHub release has no asset for
+/// this platform.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Fallback {
+    /// Install the crate with cargo-binstall, which builds it from source
+    /// when there's nothing prebuilt.
+    Cargo { crate_name: String },
+}
+
+/// Resolve `spec`, switching to its fallback if the release assets don't
+/// cover this platform.
+///
+/// Returns the spec that was actually resolved, so the install (and the
+/// manifest entry, and later updates) use the fallback source.
+pub fn resolve_with_fallback(
+    spec: PackageSpec,
+    resolve: impl Fn(&PackageSpec) -> Result<Vec<ResolvedVersion>, FetchError>,
+) -> Result<(PackageSpec, Vec<ResolvedVersion>), FetchError> {
+    match resolve(&spec) {
+        Err(FetchError::AssetNotFound { pattern, available }) => match spec.fallback_spec() {
+            Some(fallback) => {
+                let resolved = resolve(&fallback)?;
+                Ok((fallback, resolved))
+            }
+            None if matches!(spec.source, SourceConfig::Github { .. }) => {
+                Err(FetchError::AssetNotFoundNoFallback { pattern, available })
+            }
+            None => Err(FetchError::AssetNotFound { pattern, available }),
+        },
+        result => Ok((spec, result?)),
+    }
 }
 
 impl PackageSpec {
+    /// The spec to install instead when this one's release assets don't
+    /// cover the platform. Only GitHub specs fall back.
+    pub fn fallback_spec(&self) -> Option<PackageSpec> {
+        let SourceConfig::Github { .. } = &self.source else {
+            return None;
+        };
+        match self.fallback.as_ref()? {
+            Fallback::Cargo { crate_name } => Some(PackageSpec {
+                name: crate_name.clone(),
+                // Release tags are often `v1.2.3`; crate versions never are
+                version_req: self
+                    .version_req
+                    .as_ref()
+                    .map(|req| req.trim_start_matches('v').to_string()),
+                source: SourceConfig::Cargo {
+                    crate_name: crate_name.clone(),
+                },
+                binary_name: self.binary_name.clone(),
+                all_bins: self.all_bins,
+                fallback: None,
+            }),
+        }
+    }
+
     /// Normalize version requirement. Bare versions like "2.0" become "^2.0".
     pub fn normalized_version_req(&self) -> Option<String> {
-        self.version_req.as_ref().map(|req| {
-            let trimmed = req.trim();
-            if trimmed.starts_with('^')
-                || trimmed.starts_with('~')
-                || trimmed.starts_with('>')
-                || trimmed.starts_with('<')
-                || trimmed.starts_with('=')
-                || trimmed == "latest"
-            {
-                trimmed.to_string()
-            } else {
-                format!("^{trimmed}")
-            }
-        })
+        self.version_req.as_deref().map(normalize_version_req)
+    }
+}
+
+/// Normalize a version requirement. Bare versions like "2.0" become "^2.0".
+pub fn normalize_version_req(req: &str) -> String {
+    let trimmed = req.trim();
+    if trimmed.starts_with('^')
+        || trimmed.starts_with('~')
+        || trimmed.starts_with('>')
+        || trimmed.starts_with('<')
+        || trimmed.starts_with('=')
+        || trimmed == "latest"
+    {
+        trimmed.to_string()
+    } else {
+        format!("^{trimmed}")
     }
 }
 
+/// Keep the versions that satisfy an installed binary's `version_req`,
+/// preserving order. Release tags may carry a `v` prefix; tags that aren't
+/// semver never satisfy a requirement.
+pub(crate) fn filter_by_requirement(
+    versions: Vec<ResolvedVersion>,
+    version_req: Option<&str>,
+) -> Result<Vec<ResolvedVersion>, FetchError> {
+    let req = match version_req.map(normalize_version_req) {
+        None => return Ok(versions),
+        Some(req) if req == "latest" => return Ok(versions),
+        Some(req) => req,
+    };
+    // `^v1.2` is as likely as `v1.2` for tag-versioned sources
+    let parsed = semver::VersionReq::parse(&req.replacen('v', "", 1))
+        .map_err(|_| FetchError::Parse(format!("unsupported version requirement: {req}")))?;
+    Ok(versions
+        .into_iter()
+        .filter(|resolved| {
+            semver::Version::parse(resolved.version.trim_start_matches('v'))
+                .is_ok_and(|version| parsed.matches(&version))
+        })
+        .collect())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum SourceConfig {
@@ -72,6 +169,18 @@ pub enum SourceConfig {
     Github {
         repo: String,
         asset_pattern: Option<String>,
+        /// Consider pre-releases (and `-rc`/nightly tags) when resolving.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        include_prerelease: bool,
+    },
+    /// GitLab releases; `repo` may be prefixed with a self-hosted instance host.
+    Gitlab {
+        repo: String,
+        asset_pattern: Option<String>,
+    },
+    /// A local archive, executable, or directory (offline installs).
+    File {
+        path: String,
     },
 }
 
@@ -79,6 +188,29 @@ impl FromStr for PackageSpec {
     type Err = FetchError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // Paths may contain ':', and '@' only ever precedes the version
+        if let Some(rest) = value.strip_prefix("file:") {
+            let (path, version_req) = match rest.rsplit_once('@') {
+                Some((left, right)) => (left, Some(right.to_string())),
+                None => (rest, None),
+            };
+            if path.is_empty() {
+                return Err(FetchError::Parse(
+                    "missing path for file source".to_string(),
+                ));
+            }
+            return Ok(Self {
+                name: file::default_name(path),
+                version_req,
+                source: SourceConfig::File {
+                    path: path.to_string(),
+                },
+                binary_name: None,
+                all_bins: false,
+                fallback: None,
+            });
+        }
+
         let (source_part, version_req) = match value.split_once('@') {
             Some((left, right)) => (left, Some(right.to_string())),
             None => (value, None),
@@ -98,6 +230,11 @@ impl FromStr for PackageSpec {
             "github" => SourceConfig::Github {
                 repo: name.to_string(),
                 asset_pattern: None,
+                include_prerelease: false,
+            },
+            "gitlab" => SourceConfig::Gitlab {
+                repo: name.to_string(),
+                asset_pattern: None,
             },
             other => return Err(FetchError::Parse(format!("unknown source type: {other}"))),
         };
@@ -107,6 +244,8 @@ impl FromStr for PackageSpec {
             version_req,
             source,
             binary_name: None,
+            all_bins: false,
+            fallback: None,
         })
     }
 }
@@ -127,9 +266,75 @@ pub struct EngineRequirements {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FetchedBinary {
     pub binary_path: PathBuf,
+    /// Every executable to link, including `binary_path`.
+    pub binary_paths: Vec<PathBuf>,
     pub version: String,
     pub sha256: String,
     pub runtime_used: Option<RuntimeVersion>,
+    /// The npm sub-package the executable was taken from, if any.
+    pub native_package: Option<NativePackage>,
+}
+
+/// Executables under `dir`: files with an exec bit, or ELF binaries (zip
+/// archives don't carry permissions). Sorted, first file wins per name.
+pub(crate) fn find_executables(dir: &Path) -> Result<Vec<PathBuf>, FetchError> {
+    fn walk(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), FetchError> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                walk(&path, found)?;
+            } else if file_type.is_file() && is_executable(&path)? {
+                found.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut found = Vec::new();
+    walk(dir, &mut found)?;
+    found.sort();
+
+    let mut seen = std::collections::HashSet::new();
+    found.retain(|path| seen.insert(path.file_name().map(|name| name.to_os_string())));
+    Ok(found)
+}
+
+/// Binaries to link from an extracted archive: just `binary_path`, or with
+/// `--all-bins` every executable in `target_dir` (marked executable, since zip
+/// archives lose their permissions).
+pub(crate) fn archive_binary_paths(
+    spec: &PackageSpec,
+    target_dir: &Path,
+    binary_path: &Path,
+) -> Result<Vec<PathBuf>, FetchError> {
+    if !spec.all_bins {
+        return Ok(vec![binary_path.to_path_buf()]);
+    }
+
+    let mut paths = find_executables(target_dir)?;
+    for path in &paths {
+        bkt_common::archive::set_executable(path)?;
+    }
+    paths.retain(|path| path.file_name() != binary_path.file_name());
+    paths.insert(0, binary_path.to_path_buf());
+    Ok(paths)
+}
+
+fn is_executable(path: &Path) -> Result<bool, FetchError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if std::fs::metadata(path)?.permissions().mode() & 0o111 != 0 {
+            return Ok(true);
+        }
+    }
+
+    let mut magic = [0u8; 4];
+    let mut file = std::fs::File::open(path)?;
+    let read = std::io::Read::read(&mut file, &mut magic)?;
+    Ok(read == 4 && magic == *b"\x7fELF")
 }
 
 #[cfg(test)]
@@ -149,6 +354,123 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_gitlab_spec_with_host() {
+        let spec =
+            PackageSpec::from_str("gitlab:gitlab.example.com/group/tool@1.2").expect("parse");
+        assert_eq!(spec.name, "gitlab.example.com/group/tool");
+        assert_eq!(spec.version_req.as_deref(), Some("1.2"));
+        assert_eq!(
+            spec.source,
+            SourceConfig::Gitlab {
+                repo: "gitlab.example.com/group/tool".to_string(),
+                asset_pattern: None,
+            }
+        );
+    }
+
+    fn github_spec(fallback: Option<Fallback>) -> PackageSpec {
+        PackageSpec {
+            name: "BurntSushi/ripgrep".to_string(),
+            version_req: Some("v14.1.0".to_string()),
+            source: SourceConfig::Github {
+                repo: "BurntSushi/ripgrep".to_string(),
+                asset_pattern: None,
+                include_prerelease: false,
+            },
+            binary_name: Some("rg".to_string()),
+            all_bins: false,
+            fallback,
+        }
+    }
+
+    fn no_asset() -> FetchError {
+        FetchError::AssetNotFound {
+            pattern: "aarch64-linux".to_string(),
+            available: vec!["ripgrep-x86_64-linux.tar.gz".to_string()],
+        }
+    }
+
+    fn resolved(version: &str) -> Vec<ResolvedVersion> {
+        vec![ResolvedVersion {
+            version: version.to_string(),
+            download_url: None,
+            checksum: None,
+            engines: None,
+        }]
+    }
+
+    #[test]
+    fn fallback_roundtrips_through_json() {
+        let spec = github_spec(Some(Fallback::Cargo {
+            crate_name: "ripgrep".to_string(),
+        }));
+        let json = serde_json::to_string(&spec).expect("serialize");
+        assert!(json.contains(r#""fallback":{"type":"cargo","crate_name":"ripgrep"}"#));
+        let restored: PackageSpec = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(restored, spec);
+
+        let plain = serde_json::to_string(&github_spec(None)).expect("serialize");
+        assert!(!plain.contains("fallback"));
+    }
+
+    #[test]
+    fn resolve_with_fallback_switches_to_cargo_when_no_asset_matches() {
+        let spec = github_spec(Some(Fallback::Cargo {
+            crate_name: "ripgrep".to_string(),
+        }));
+        let (effective, versions) = resolve_with_fallback(spec, |spec| match &spec.source {
+            SourceConfig::Github { .. } => Err(no_asset()),
+            SourceConfig::Cargo { crate_name } => {
+                assert_eq!(crate_name, "ripgrep");
+                assert_eq!(spec.version_req.as_deref(), Some("14.1.0"));
+                Ok(resolved("14.1.0"))
+            }
+            _ => unreachable!(),
+        })
+        .expect("fallback resolves");
+
+        assert_eq!(
+            effective.source,
+            SourceConfig::Cargo {
+                crate_name: "ripgrep".to_string()
+            }
+        );
+        assert_eq!(effective.binary_name.as_deref(), Some("rg"));
+        assert_eq!(versions[0].version, "14.1.0");
+    }
+
+    #[test]
+    fn resolve_with_fallback_keeps_github_when_assets_match() {
+        let spec = github_spec(Some(Fallback::Cargo {
+            crate_name: "ripgrep".to_string(),
+        }));
+        let (effective, _) = resolve_with_fallback(spec.clone(), |spec| {
+            assert!(matches!(spec.source, SourceConfig::Github { .. }));
+            Ok(resolved("v14.1.0"))
+        })
+        .expect("resolves");
+        assert_eq!(effective, spec);
+    }
+
+    #[test]
+    fn resolve_without_fallback_suggests_one() {
+        let err = resolve_with_fallback(github_spec(None), |_| Err(no_asset())).unwrap_err();
+        assert!(matches!(err, FetchError::AssetNotFoundNoFallback { .. }));
+        assert!(err.to_string().contains("--fallback cargo"));
+
+        // Other failures aren't a reason to build from source
+        let spec = github_spec(Some(Fallback::Cargo {
+            crate_name: "ripgrep".to_string(),
+        }));
+        let err = resolve_with_fallback(spec, |spec| match spec.source {
+            SourceConfig::Github { .. } => Err(FetchError::GitHubApi("rate limited".to_string())),
+            _ => unreachable!(),
+        })
+        .unwrap_err();
+        assert!(matches!(err, FetchError::GitHubApi(_)));
+    }
+
     #[test]
     fn normalized_version_req_handles_bare_versions() {
         let spec = PackageSpec {
@@ -158,6 +480,8 @@ mod tests {
                 package: "tool".to_string(),
             },
             binary_name: None,
+            all_bins: false,
+            fallback: None,
         };
 
         assert_eq!(spec.normalized_version_req(), Some("^2.0".to_string()));
@@ -172,11 +496,69 @@ mod tests {
                 package: "tool".to_string(),
             },
             binary_name: None,
+            all_bins: false,
+            fallback: None,
         };
 
         assert_eq!(spec.normalized_version_req(), Some(">=1.2".to_string()));
     }
 
+    #[test]
+    fn filter_by_requirement_keeps_matching_versions() {
+        let versions = || {
+            ["v2.0.0", "v1.10.0", "1.9.1", "nightly", "0.9.0"]
+                .into_iter()
+                .map(|version| ResolvedVersion {
+                    version: version.to_string(),
+                    download_url: None,
+                    checksum: None,
+                    engines: None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let kept = |req| -> Vec<String> {
+            filter_by_requirement(versions(), req)
+                .unwrap()
+                .into_iter()
+                .map(|resolved| resolved.version)
+                .collect()
+        };
+
+        assert_eq!(kept(Some("1")), ["v1.10.0", "1.9.1"]);
+        assert_eq!(kept(Some("v1.9")), ["v1.10.0", "1.9.1"]);
+        assert_eq!(kept(Some("~1.9")), ["1.9.1"]);
+        assert_eq!(kept(Some("latest")).len(), 5);
+        assert_eq!(kept(None).len(), 5);
+        assert!(filter_by_requirement(versions(), Some("not a version")).is_err());
+    }
+
+    #[test]
+    fn find_executables_picks_exec_bit_and_elf_files() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("tool-1.0/completions")).unwrap();
+        std::fs::write(dir.join("tool-1.0/tool"), b"\x7fELF\x02\x01").unwrap();
+        std::fs::write(dir.join("tool-1.0/helper.sh"), b"#!/bin/sh\n").unwrap();
+        std::fs::write(dir.join("tool-1.0/README.md"), b"# tool\n").unwrap();
+        std::fs::write(dir.join("tool-1.0/completions/tool.bash"), b"complete\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let helper = dir.join("tool-1.0/helper.sh");
+            std::fs::set_permissions(&helper, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let names: Vec<String> = find_executables(dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        #[cfg(unix)]
+        assert_eq!(names, vec!["helper.sh", "tool"]);
+        #[cfg(not(unix))]
+        assert_eq!(names, vec!["tool"]);
+    }
+
     #[test]
     fn normalized_version_req_keeps_latest() {
         let spec = PackageSpec {
@@ -186,6 +568,8 @@ mod tests {
                 package: "tool".to_string(),
             },
             binary_name: None,
+            all_bins: false,
+            fallback: None,
         };
 
         assert_eq!(spec.normalized_version_req(), Some("latest".to_string()));
@@ -1,20 +1,27 @@
 use crate::error::FetchError;
-use crate::manifest::{InstalledBinary, SourceSpec};
+use crate::manifest::{InstalledBinary, NativePackage, SourceSpec};
+use crate::platform::{Arch, Os, Platform};
 use crate::runtime::{RuntimePool, RuntimeVersion};
 use crate::source::{
-    BinarySource, EngineRequirements, FetchedBinary, PackageSpec, ResolvedVersion, SourceConfig,
+    filter_by_requirement, normalize_version_req, BinarySource, EngineRequirements, FetchedBinary,
+    PackageSpec, ResolvedVersion, SourceConfig,
 };
+use base64::Engine;
 use semver::{Version, VersionReq};
 use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use std::collections::HashMap;
 use std::env;
 use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
 pub struct NpmSource {
     registry_base: String,
+    verify: bool,
 }
 
 impl NpmSource {
@@ -25,9 +32,17 @@ impl NpmSource {
     pub fn with_registry_base(base: impl Into<String>) -> Self {
         Self {
             registry_base: base.into(),
+            verify: true,
         }
     }
 
+    /// Whether to smoke-test the installed binary (`--version`, then
+    /// `--help`) before reporting success. On by default.
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
     fn registry_url(&self, package: &str) -> String {
         let encoded = encode_package_name(package);
         format!("{}/{}", self.registry_base.trim_end_matches('/'), encoded)
@@ -38,8 +53,187 @@ impl NpmSource {
         bkt_common::http::download_json::<NpmPackageMetadata>(&url, &[])
             .map_err(|err| FetchError::NpmRegistry(err.to_string()))
     }
+
+    /// The optional dependency of `metadata` that carries the native
+    /// executable for `platform`, with its resolved version metadata.
+    ///
+    /// Well-known packages are matched by their naming convention; anything
+    /// else by the `os`/`cpu` fields of optional dependencies whose name
+    /// mentions the platform's OS.
+    fn native_package(
+        &self,
+        package: &str,
+        metadata: &NpmVersionMetadata,
+        platform: &Platform,
+    ) -> Result<Option<(String, NpmVersionMetadata)>, FetchError> {
+        let optional = &metadata.optional_dependencies;
+        if optional.is_empty() {
+            return Ok(None);
+        }
+
+        for name in known_native_packages(package, platform) {
+            if let Some(req) = optional.get(&name) {
+                return Ok(self
+                    .sub_package_version(&name, req)?
+                    .map(|meta| (name, meta)));
+            }
+        }
+
+        let Some(os) = npm_os(platform) else {
+            return Ok(None);
+        };
+        let os_aliases: &[&str] = match os {
+            "win32" => &["win32", "windows"],
+            "darwin" => &["darwin", "macos"],
+            _ => &[os],
+        };
+        let mut candidates: Vec<&String> = optional
+            .keys()
+            .filter(|name| os_aliases.iter().any(|alias| name.contains(alias)))
+            .collect();
+        candidates.sort();
+
+        for name in candidates {
+            if let Some(meta) = self.sub_package_version(name, &optional[name])? {
+                if matches_platform(&meta, platform) {
+                    return Ok(Some((name.clone(), meta)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Metadata for the version of `package` an optional dependency asks for.
+    fn sub_package_version(
+        &self,
+        package: &str,
+        requirement: &str,
+    ) -> Result<Option<NpmVersionMetadata>, FetchError> {
+        let mut metadata = self.fetch_metadata(package)?;
+        let version = match find_version(&metadata.versions, requirement) {
+            Some(meta) => Some(meta.version.clone()),
+            None => VersionReq::parse(requirement).ok().and_then(|req| {
+                matching_versions(&metadata.versions, &req)
+                    .first()
+                    .map(|(_, meta)| meta.version.clone())
+            }),
+        };
+        Ok(version.and_then(|version| metadata.versions.remove(&version)))
+    }
+
+    /// Download, verify, and `pnpm add` the package into its store directory,
+    /// then write wrappers for its bins. Returns the tarball's sha256.
+    fn install_js(
+        &self,
+        install: &JsInstall<'_>,
+        node_path: &Path,
+        pnpm_path: &Path,
+    ) -> Result<String, FetchError> {
+        let JsInstall {
+            package,
+            version,
+            version_meta,
+            bins,
+            binary_name,
+            wrapped,
+            store_dir,
+            target_dir,
+        } = *install;
+
+        let tarball_url =
+            version_meta
+                .dist
+                .tarball
+                .as_deref()
+                .ok_or_else(|| FetchError::NoDownloadUrl {
+                    version: version.version.clone(),
+                })?;
+        let tarball = crate::prefetch::download(tarball_url, &[])?;
+
+        let tarball_name = format!("{}-{}.tgz", package_name(package), version.version);
+        match version
+            .checksum
+            .clone()
+            .or_else(|| version_meta.dist.checksum())
+        {
+            Some(expected) => verify_tarball(&tarball_name, &expected, &tarball)?,
+            None => eprintln!("warning: no checksum found for {tarball_name}"),
+        }
+
+        // Install from the verified tarball so pnpm can't fetch different bytes
+        fs::write(store_dir.join(&tarball_name), &tarball)?;
+
+        let mut command = Command::new(pnpm_path);
+        command
+            .current_dir(store_dir)
+            .arg("add")
+            .arg("--ignore-scripts")
+            .arg(format!("./{tarball_name}"));
+
+        if let Some(node_bin_dir) = node_path.parent() {
+            let current = env::var_os("PATH").unwrap_or_else(|| OsString::new());
+            let mut paths: Vec<PathBuf> = env::split_paths(&current).collect();
+            paths.insert(0, node_bin_dir.to_path_buf());
+            if let Ok(joined) = env::join_paths(paths) {
+                command.env("PATH", joined);
+            }
+        }
+
+        let output = command
+            .output()
+            .map_err(|err| FetchError::PnpmInstallFailed(format!("failed to run pnpm: {err}")))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(FetchError::PnpmInstallFailed(stderr.to_string()));
+        }
+
+        fs::create_dir_all(target_dir)?;
+        for name in wrapped {
+            let bin_rel = bins.get(*name).ok_or_else(|| FetchError::BinaryNotFound {
+                package: package.to_string(),
+                searched: sorted_names(bins),
+            })?;
+            let js_binary_path = resolve_js_binary(store_dir, package, name, bin_rel)?;
+            fix_shebang(&js_binary_path, node_path)?;
+            set_executable(&js_binary_path)?;
+
+            let target_path = npm_wrapper_path(target_dir, name);
+            if target_path.exists() {
+                fs::remove_file(&target_path)?;
+            }
+            create_npm_wrapper(&target_path, node_path, &js_binary_path)?;
+        }
+
+        if self.verify {
+            smoke_test(
+                &npm_wrapper_path(target_dir, binary_name),
+                SMOKE_TEST_TIMEOUT,
+            )?;
+        }
+
+        // The wrapper is generated locally; record the tarball that was verified
+        Ok(crate::source::github::checksum::sha256_hex(&tarball))
+    }
+}
+
+/// Everything [`NpmSource::install_js`] needs to know about one install.
+#[derive(Clone, Copy)]
+struct JsInstall<'a> {
+    package: &'a str,
+    version: &'a ResolvedVersion,
+    version_meta: &'a NpmVersionMetadata,
+    bins: &'a HashMap<String, String>,
+    binary_name: &'a str,
+    wrapped: &'a [&'a String],
+    store_dir: &'a Path,
+    target_dir: &'a Path,
 }
 
+/// How long the post-install `--version`/`--help` check may run.
+const SMOKE_TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
 impl Default for NpmSource {
     fn default() -> Self {
         Self::new()
@@ -66,12 +260,35 @@ struct NpmVersionMetadata {
     dist: NpmDist,
     bin: Option<BinField>,
     engines: Option<EnginesField>,
+    /// Platform-specific packages, of which npm installs only the matching one.
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: HashMap<String, String>,
+    /// Platforms this package installs on (`os`, `cpu`, `libc` in package.json).
+    #[serde(default)]
+    os: Vec<String>,
+    #[serde(default)]
+    cpu: Vec<String>,
+    #[serde(default)]
+    libc: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct NpmDist {
     #[serde(default)]
     tarball: Option<String>,
+    /// Subresource Integrity string (e.g. `sha512-<base64>`)
+    #[serde(default)]
+    integrity: Option<String>,
+    /// Legacy hex sha1 of the tarball
+    #[serde(default)]
+    shasum: Option<String>,
+}
+
+impl NpmDist {
+    /// The strongest checksum the registry published for the tarball.
+    fn checksum(&self) -> Option<String> {
+        self.integrity.clone().or_else(|| self.shasum.clone())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -162,6 +379,38 @@ impl BinarySource for NpmSource {
             .get(&version.version)
             .ok_or_else(|| FetchError::Parse(format!("version {} not found", version.version)))?;
 
+        let bins = collect_bins(package, version_meta.bin.as_ref())?;
+        let binary_name = match spec.binary_name.as_deref() {
+            None if spec.all_bins => primary_binary_name(package, &bins)?,
+            requested => select_binary_name(package, &bins, requested)?,
+        };
+        let mut wrapped: Vec<&String> = if spec.all_bins {
+            bins.keys().collect()
+        } else {
+            vec![&binary_name]
+        };
+        wrapped.sort();
+
+        // Packages like esbuild ship a JS shim plus the real executable in a
+        // per-platform optional dependency; install that directly when we can.
+        if let Some((native, native_meta)) =
+            self.native_package(package, version_meta, &Platform::current())?
+        {
+            if let Some(fetched) = fetch_native(
+                &native,
+                &native_meta,
+                &version.version,
+                &binary_name,
+                &wrapped,
+                target_dir,
+            )? {
+                return Ok(fetched);
+            }
+            eprintln!(
+                "warning: {native} has no {binary_name} executable; installing the JS package"
+            );
+        }
+
         let node_requirement = version_meta
             .engines
             .as_ref()
@@ -184,77 +433,36 @@ impl BinarySource for NpmSource {
         }
         fs::create_dir_all(&store_dir)?;
 
-        let mut command = Command::new(&pnpm_runtime.pnpm_path);
-        command
-            .current_dir(&store_dir)
-            .arg("add")
-            .arg("--ignore-scripts")
-            .arg(format!("{package}@{}", version.version));
-
-        if let Some(node_bin_dir) = node_runtime.node_path.parent() {
-            let current = env::var_os("PATH").unwrap_or_else(|| OsString::new());
-            let mut paths: Vec<PathBuf> = env::split_paths(&current).collect();
-            paths.insert(0, node_bin_dir.to_path_buf());
-            if let Ok(joined) = env::join_paths(paths) {
-                command.env("PATH", joined);
-            }
-        }
-
-        let output = command
-            .output()
-            .map_err(|err| FetchError::PnpmInstallFailed(format!("failed to run pnpm: {err}")))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(FetchError::PnpmInstallFailed(stderr.to_string()));
-        }
-
-        let bins = collect_bins(package, version_meta.bin.as_ref())?;
-        let binary_name = select_binary_name(package, &bins, spec.binary_name.as_deref())?;
-        let bin_rel = bins
-            .get(&binary_name)
-            .ok_or_else(|| FetchError::BinaryNotFound {
-                package: package.to_string(),
-                searched: bins.keys().cloned().collect(),
-            })?;
-
-        let bin_dir = store_dir.join("node_modules").join(".bin");
-        let link_path = bin_dir.join(&binary_name);
-        let package_bin = store_dir
-            .join("node_modules")
-            .join(package)
-            .join(bin_rel.trim_start_matches("./"));
-
-        let js_binary_path = if package_bin.exists() {
-            package_bin
-        } else if link_path.exists() {
-            link_path
-        } else {
-            return Err(FetchError::BinaryNotFound {
-                package: package.to_string(),
-                searched: vec![
-                    link_path.display().to_string(),
-                    package_bin.display().to_string(),
-                ],
-            });
+        let install = JsInstall {
+            package,
+            version,
+            version_meta,
+            bins: &bins,
+            binary_name: &binary_name,
+            wrapped: &wrapped,
+            store_dir: &store_dir,
+            target_dir,
         };
-
-        set_executable(&js_binary_path)?;
-
-        fs::create_dir_all(target_dir)?;
-        let target_path = npm_wrapper_path(target_dir, &binary_name);
-        if target_path.exists() {
-            fs::remove_file(&target_path)?;
+        let result = self.install_js(&install, &node_runtime.node_path, &pnpm_runtime.pnpm_path);
+        if result.is_err() {
+            // Don't leave a half-installed package behind for the next run
+            let _ = fs::remove_dir_all(&store_dir);
+            for name in &wrapped {
+                let _ = fs::remove_file(npm_wrapper_path(target_dir, name));
+            }
         }
-        create_npm_wrapper(&target_path, &node_runtime.node_path, &js_binary_path)?;
-
-        let sha256 = crate::source::github::checksum::sha256_hex(&fs::read(&target_path)?);
+        let sha256 = result?;
 
         Ok(FetchedBinary {
-            binary_path: target_path,
+            binary_path: npm_wrapper_path(target_dir, &binary_name),
+            binary_paths: wrapped
+                .iter()
+                .map(|name| npm_wrapper_path(target_dir, name))
+                .collect(),
             version: version.version.clone(),
             sha256,
             runtime_used: Some(RuntimeVersion::Node(node_runtime.version.clone())),
+            native_package: None,
         })
     }
 
@@ -273,27 +481,204 @@ impl BinarySource for NpmSource {
 
         let spec = PackageSpec {
             name: package_name(package).to_string(),
-            version_req: None,
+            version_req: installed.version_req.as_deref().map(normalize_version_req),
             source: SourceConfig::Npm {
                 package: package.clone(),
             },
             binary_name: Some(installed.binary.clone()),
+            all_bins: !installed.binaries.is_empty(),
+            fallback: None,
         };
 
-        let latest = self
-            .resolve(&spec)?
+        let latest = filter_by_requirement(self.resolve(&spec)?, installed.version_req.as_deref())?
             .into_iter()
             .next()
             .ok_or_else(|| FetchError::Parse("no versions returned".to_string()))?;
 
-        if versions_match(&latest.version, current_version) {
-            Ok(None)
-        } else {
-            Ok(Some(latest))
+        if !versions_match(&latest.version, current_version) {
+            return Ok(Some(latest));
+        }
+
+        // The executable came from a sub-package, which can be republished
+        // under the same top-level version
+        if let Some(native) = &installed.native_package {
+            let metadata = self.fetch_metadata(package)?;
+            let current = metadata
+                .versions
+                .get(&latest.version)
+                .is_some_and(|meta| native_is_current(meta, native));
+            if !current {
+                return Ok(Some(latest));
+            }
         }
+
+        Ok(None)
+    }
+}
+
+/// npm's name for the platform's OS (`process.platform`).
+fn npm_os(platform: &Platform) -> Option<&'static str> {
+    match platform.os {
+        Os::Linux => Some("linux"),
+        Os::MacOs => Some("darwin"),
+        Os::Windows => Some("win32"),
+        Os::Unknown(_) => None,
+    }
+}
+
+/// npm's name for the platform's CPU (`process.arch`).
+fn npm_cpu(platform: &Platform) -> Option<&'static str> {
+    match platform.arch {
+        Arch::X86_64 => Some("x64"),
+        Arch::Aarch64 => Some("arm64"),
+        Arch::Armv7 => Some("arm"),
+        Arch::Unknown(_) => None,
     }
 }
 
+/// Sub-packages that well-known packages ship their executable in, in order
+/// of preference.
+fn known_native_packages(package: &str, platform: &Platform) -> Vec<String> {
+    let (Some(os), Some(cpu)) = (npm_os(platform), npm_cpu(platform)) else {
+        return Vec::new();
+    };
+
+    match package {
+        "esbuild" => vec![format!("@esbuild/{os}-{cpu}")],
+        "turbo" => {
+            let os = if os == "win32" { "windows" } else { os };
+            let cpu = if cpu == "x64" { "64" } else { cpu };
+            vec![format!("turbo-{os}-{cpu}")]
+        }
+        "@biomejs/biome" => vec![
+            format!("@biomejs/cli-{os}-{cpu}"),
+            format!("@biomejs/cli-{os}-{cpu}-musl"),
+        ],
+        "@swc/core" | "@swc/cli" => match os {
+            "linux" => vec![
+                format!("@swc/core-{os}-{cpu}-gnu"),
+                format!("@swc/core-{os}-{cpu}-musl"),
+            ],
+            "win32" => vec![format!("@swc/core-{os}-{cpu}-msvc")],
+            _ => vec![format!("@swc/core-{os}-{cpu}")],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a sub-package's `os`/`cpu`/`libc` fields select `platform`.
+///
+/// Packages that don't declare both `os` and `cpu` aren't platform packages.
+fn matches_platform(metadata: &NpmVersionMetadata, platform: &Platform) -> bool {
+    let (Some(os), Some(cpu)) = (npm_os(platform), npm_cpu(platform)) else {
+        return false;
+    };
+    let glibc = metadata.libc.is_empty() || metadata.libc.iter().any(|libc| libc == "glibc");
+
+    metadata.os.iter().any(|value| value == os)
+        && metadata.cpu.iter().any(|value| value == cpu)
+        && (os != "linux" || glibc)
+}
+
+/// Whether `latest` still depends on the sub-package version that's installed.
+fn native_is_current(latest: &NpmVersionMetadata, native: &NativePackage) -> bool {
+    latest
+        .optional_dependencies
+        .get(&native.package)
+        .is_some_and(|req| versions_match(req, &native.version))
+}
+
+/// Download and unpack a platform sub-package into `target_dir`, returning the
+/// executables named in `wanted` it contains.
+///
+/// Returns `None`, leaving nothing behind, if it has no `binary_name` executable.
+fn fetch_native(
+    package: &str,
+    metadata: &NpmVersionMetadata,
+    version: &str,
+    binary_name: &str,
+    wanted: &[&String],
+    target_dir: &Path,
+) -> Result<Option<FetchedBinary>, FetchError> {
+    let tarball_url =
+        metadata
+            .dist
+            .tarball
+            .as_deref()
+            .ok_or_else(|| FetchError::NoDownloadUrl {
+                version: metadata.version.clone(),
+            })?;
+    let tarball = crate::prefetch::download(tarball_url, &[])?;
+
+    let tarball_name = format!("{}-{}.tgz", package_name(package), metadata.version);
+    match metadata.dist.checksum() {
+        Some(expected) => verify_tarball(&tarball_name, &expected, &tarball)?,
+        None => eprintln!("warning: no checksum found for {tarball_name}"),
+    }
+
+    let native_dir = target_dir.join("native");
+    if native_dir.exists() {
+        fs::remove_dir_all(&native_dir)?;
+    }
+    bkt_common::archive::extract_tar_gz(&tarball, &native_dir, 0)
+        .map_err(|err| FetchError::Parse(err.to_string()))?;
+
+    let Some(binary_path) = find_native_executable(&native_dir, binary_name)? else {
+        fs::remove_dir_all(&native_dir)?;
+        return Ok(None);
+    };
+
+    let mut binary_paths = Vec::new();
+    for name in wanted {
+        if let Some(path) = find_native_executable(&native_dir, name)? {
+            set_executable(&path)?;
+            binary_paths.push(path);
+        }
+    }
+
+    Ok(Some(FetchedBinary {
+        binary_path,
+        binary_paths,
+        version: version.to_string(),
+        sha256: crate::source::github::checksum::sha256_hex(&tarball),
+        runtime_used: None,
+        native_package: Some(NativePackage {
+            package: package.to_string(),
+            version: metadata.version.clone(),
+        }),
+    }))
+}
+
+/// The file called `name` (or `name.exe`) in an unpacked package, nearest the
+/// package root first.
+fn find_native_executable(dir: &Path, name: &str) -> Result<Option<PathBuf>, FetchError> {
+    let exe = format!("{name}.exe");
+    let mut pending = vec![dir.to_path_buf()];
+    while !pending.is_empty() {
+        let mut found = Vec::new();
+        let mut next = Vec::new();
+        for dir in pending {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    next.push(entry.path());
+                } else if file_type.is_file()
+                    && (entry.file_name() == name || entry.file_name() == exe.as_str())
+                {
+                    found.push(entry.path());
+                }
+            }
+        }
+        found.sort();
+        if let Some(path) = found.into_iter().next() {
+            return Ok(Some(path));
+        }
+        pending = next;
+    }
+    Ok(None)
+}
+
 fn encode_package_name(package: &str) -> String {
     package.replace('@', "%40").replace('/', "%2F")
 }
@@ -306,13 +691,76 @@ fn resolved_from_metadata(metadata: &NpmVersionMetadata) -> ResolvedVersion {
     ResolvedVersion {
         version: metadata.version.clone(),
         download_url: metadata.dist.tarball.clone(),
-        checksum: None,
+        checksum: metadata.dist.checksum(),
         engines: metadata.engines.as_ref().map(|engines| EngineRequirements {
             node: engines.node.clone(),
         }),
     }
 }
 
+/// Verify tarball bytes against an npm checksum.
+///
+/// Accepts an SRI string (`sha512-<base64>`, possibly several space-separated
+/// entries, of which the strongest supported one is used) or a legacy hex
+/// sha1 `shasum`.
+fn verify_tarball(name: &str, expected: &str, bytes: &[u8]) -> Result<(), FetchError> {
+    let expected = expected.trim();
+    let (expected, actual) = match strongest_sri(expected) {
+        Some((algorithm, digest)) => {
+            let actual = match algorithm {
+                "sha512" => sri_digest::<Sha512>(bytes),
+                "sha384" => sri_digest::<Sha384>(bytes),
+                "sha256" => sri_digest::<Sha256>(bytes),
+                _ => sri_digest::<Sha1>(bytes),
+            };
+            (
+                format!("{algorithm}-{digest}"),
+                format!("{algorithm}-{actual}"),
+            )
+        }
+        None if expected.len() == 40 && expected.chars().all(|c| c.is_ascii_hexdigit()) => {
+            let actual = format!("{:x}", Sha1::digest(bytes));
+            (expected.to_lowercase(), actual)
+        }
+        None => {
+            return Err(FetchError::Parse(format!(
+                "unsupported checksum for {name}: {expected}"
+            )))
+        }
+    };
+
+    if expected != actual {
+        return Err(FetchError::ChecksumMismatch {
+            name: name.to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
+/// Pick the strongest supported `algorithm-digest` entry from an SRI string.
+fn strongest_sri(integrity: &str) -> Option<(&str, &str)> {
+    const PREFERENCE: [&str; 4] = ["sha512", "sha384", "sha256", "sha1"];
+
+    integrity
+        .split_whitespace()
+        .filter_map(|entry| entry.split_once('-'))
+        .filter_map(|(algorithm, digest)| {
+            // Drop SRI options (`sha512-<digest>?opt`)
+            let digest = digest.split('?').next().unwrap_or(digest);
+            let rank = PREFERENCE.iter().position(|known| *known == algorithm)?;
+            Some((rank, algorithm, digest))
+        })
+        .min_by_key(|(rank, _, _)| *rank)
+        .map(|(_, algorithm, digest)| (algorithm, digest))
+}
+
+fn sri_digest<D: Digest>(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(D::digest(bytes))
+}
+
 fn find_version<'a>(
     versions: &'a HashMap<String, NpmVersionMetadata>,
     requested: &str,
@@ -368,6 +816,142 @@ fn collect_bins(
     Ok(bins)
 }
 
+/// The primary binary for `--all-bins`: the one named after the package if
+/// there is one, otherwise the first by name.
+fn primary_binary_name(
+    package: &str,
+    bins: &HashMap<String, String>,
+) -> Result<String, FetchError> {
+    let name = package_name(package);
+    if bins.contains_key(name) {
+        return Ok(name.to_string());
+    }
+
+    bins.keys()
+        .min()
+        .cloned()
+        .ok_or_else(|| FetchError::BinaryNotFound {
+            package: package.to_string(),
+            searched: Vec::new(),
+        })
+}
+
+/// Locate the installed JS entry point for `name`: the file the package's
+/// `bin` map declares, which must exist in the extracted tarball.
+fn resolve_js_binary(
+    store_dir: &Path,
+    package: &str,
+    name: &str,
+    bin_rel: &str,
+) -> Result<PathBuf, FetchError> {
+    let package_bin = store_dir
+        .join("node_modules")
+        .join(package)
+        .join(bin_rel.trim_start_matches("./"));
+
+    if package_bin.is_file() {
+        Ok(package_bin)
+    } else {
+        Err(FetchError::MissingBinEntry {
+            package: package.to_string(),
+            name: name.to_string(),
+            path: bin_rel.to_string(),
+        })
+    }
+}
+
+/// Point a bin script's shebang at the pooled Node runtime, so running it
+/// directly (not just through the wrapper) uses the same interpreter.
+///
+/// Scripts without a shebang get one; a shebang for some other interpreter,
+/// or a file that isn't text, is left alone. The file is replaced rather
+/// than rewritten in place because pnpm hard-links it from its shared store.
+fn fix_shebang(path: &Path, node_path: &Path) -> Result<(), FetchError> {
+    let contents = fs::read(path)?;
+    let Ok(text) = std::str::from_utf8(&contents) else {
+        return Ok(());
+    };
+
+    let node = node_path.display().to_string();
+    let body = match text.strip_prefix("#!") {
+        Some(rest) => {
+            let (line, body) = rest.split_once('\n').unwrap_or((rest, ""));
+            if line.trim() == node || !line.contains("node") {
+                return Ok(());
+            }
+            body
+        }
+        None => text,
+    };
+
+    let tmp = path.with_extension("fetchbin-tmp");
+    fs::write(&tmp, format!("#!{node}\n{body}"))?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Run `binary --version`, falling back to `--help`, and fail unless one of
+/// them exits successfully within `timeout`.
+fn smoke_test(binary: &Path, timeout: Duration) -> Result<(), FetchError> {
+    let mut failures = Vec::new();
+    for flag in ["--version", "--help"] {
+        match run_with_timeout(binary, flag, timeout) {
+            Ok(()) => return Ok(()),
+            Err(detail) => failures.push(format!("{flag}: {detail}")),
+        }
+    }
+
+    Err(FetchError::SmokeTestFailed {
+        binary: binary
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| binary.display().to_string()),
+        detail: failures.join("; "),
+    })
+}
+
+fn run_with_timeout(binary: &Path, flag: &str, timeout: Duration) -> Result<(), String> {
+    let mut child = Command::new(binary)
+        .arg(flag)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run: {err}"))?;
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|err| err.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    };
+
+    if status.success() {
+        return Ok(());
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        use std::io::Read;
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    match stderr.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) => Err(format!("{status}: {}", line.trim())),
+        None => Err(status.to_string()),
+    }
+}
+
+fn sorted_names(bins: &HashMap<String, String>) -> Vec<String> {
+    let mut names: Vec<String> = bins.keys().cloned().collect();
+    names.sort();
+    names
+}
+
 fn select_binary_name(
     package: &str,
     bins: &HashMap<String, String>,
@@ -380,13 +964,13 @@ fn select_binary_name(
 
         return Err(FetchError::BinaryNotFound {
             package: package.to_string(),
-            searched: bins.keys().cloned().collect(),
+            searched: sorted_names(bins),
         });
     }
 
     if bins.len() > 1 {
         return Err(FetchError::MultipleBinaries {
-            binaries: bins.keys().cloned().collect(),
+            binaries: sorted_names(bins),
         });
     }
 
@@ -559,6 +1143,330 @@ mod tests {
         assert_eq!(result.unwrap(), "only-one");
     }
 
+    #[test]
+    fn test_primary_binary_name_prefers_package_name() {
+        let mut bins = HashMap::new();
+        bins.insert("biome-lsp".to_string(), "./lsp.js".to_string());
+        bins.insert("biome".to_string(), "./bin/biome".to_string());
+        assert_eq!(
+            primary_binary_name("@biomejs/biome", &bins).unwrap(),
+            "biome"
+        );
+
+        bins.remove("biome");
+        bins.insert("a-tool".to_string(), "./a.js".to_string());
+        assert_eq!(
+            primary_binary_name("@biomejs/biome", &bins).unwrap(),
+            "a-tool"
+        );
+    }
+
+    const FIXTURE_TARBALL: &[u8] = include_bytes!("../../tests/fixtures/npm/hello-bin-1.0.0.tgz");
+    const FIXTURE_INTEGRITY: &str =
+        "sha512-yIempOJ1buEYck9inTw9cZrC/z9X1QdMfoeqLFLEU71trz7hJI9Qp8QxjhN+DBWnmVOmLH42BK5FpCdVMq7Lsg==";
+    const FIXTURE_SHASUM: &str = "182f2fa46d4959ab82370c02fa99dd8556f12ed5";
+
+    #[test]
+    fn test_verify_tarball_sri_integrity() {
+        verify_tarball("hello-bin-1.0.0.tgz", FIXTURE_INTEGRITY, FIXTURE_TARBALL)
+            .expect("integrity ok");
+    }
+
+    #[test]
+    fn test_verify_tarball_legacy_shasum() {
+        verify_tarball("hello-bin-1.0.0.tgz", FIXTURE_SHASUM, FIXTURE_TARBALL).expect("shasum ok");
+        verify_tarball(
+            "hello-bin-1.0.0.tgz",
+            &FIXTURE_SHASUM.to_uppercase(),
+            FIXTURE_TARBALL,
+        )
+        .expect("uppercase shasum ok");
+    }
+
+    #[test]
+    fn test_verify_tarball_prefers_strongest_sri_entry() {
+        let integrity = format!("sha1-AAAA {FIXTURE_INTEGRITY}");
+        verify_tarball("hello-bin-1.0.0.tgz", &integrity, FIXTURE_TARBALL)
+            .expect("sha512 entry wins");
+    }
+
+    #[test]
+    fn test_verify_tarball_mismatch() {
+        let mut tampered = FIXTURE_TARBALL.to_vec();
+        tampered[0] ^= 0xff;
+
+        let err = verify_tarball("hello-bin-1.0.0.tgz", FIXTURE_INTEGRITY, &tampered)
+            .expect_err("integrity mismatch");
+        match err {
+            FetchError::ChecksumMismatch {
+                name,
+                expected,
+                actual,
+            } => {
+                assert_eq!(name, "hello-bin-1.0.0.tgz");
+                assert_eq!(expected, FIXTURE_INTEGRITY);
+                assert!(actual.starts_with("sha512-"));
+                assert_ne!(actual, expected);
+            }
+            other => panic!("expected ChecksumMismatch, got {other:?}"),
+        }
+
+        let err = verify_tarball("hello-bin-1.0.0.tgz", FIXTURE_SHASUM, &tampered)
+            .expect_err("shasum mismatch");
+        assert!(matches!(err, FetchError::ChecksumMismatch { .. }));
+    }
+
+    #[test]
+    fn test_verify_tarball_rejects_unknown_format() {
+        let err = verify_tarball("pkg.tgz", "md5-abc", FIXTURE_TARBALL).expect_err("unsupported");
+        assert!(matches!(err, FetchError::Parse(_)));
+    }
+
+    #[test]
+    fn test_resolve_carries_integrity() {
+        let mut server = Server::new();
+        let body = r#"{
+            "name": "hello-bin",
+            "dist-tags": { "latest": "1.0.0" },
+            "versions": {
+                "0.9.0": {
+                    "version": "0.9.0",
+                    "dist": { "tarball": "https://example.com/0.9.0.tgz", "shasum": "182f2fa46d4959ab82370c02fa99dd8556f12ed5" }
+                },
+                "1.0.0": {
+                    "version": "1.0.0",
+                    "dist": {
+                        "tarball": "https://example.com/1.0.0.tgz",
+                        "shasum": "182f2fa46d4959ab82370c02fa99dd8556f12ed5",
+                        "integrity": "sha512-yIempOJ1buEYck9inTw9cZrC/z9X1QdMfoeqLFLEU71trz7hJI9Qp8QxjhN+DBWnmVOmLH42BK5FpCdVMq7Lsg=="
+                    }
+                }
+            }
+        }"#;
+
+        server
+            .mock("GET", "/hello-bin")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let source = NpmSource::with_registry_base(server.url());
+        let spec = |version_req: Option<&str>| PackageSpec {
+            name: "hello-bin".to_string(),
+            version_req: version_req.map(str::to_string),
+            source: SourceConfig::Npm {
+                package: "hello-bin".to_string(),
+            },
+            binary_name: None,
+            all_bins: false,
+            fallback: None,
+        };
+
+        let latest = source.resolve(&spec(None)).expect("resolve latest");
+        assert_eq!(latest[0].checksum.as_deref(), Some(FIXTURE_INTEGRITY));
+
+        let legacy = source.resolve(&spec(Some("0.9.0"))).expect("resolve 0.9.0");
+        assert_eq!(legacy[0].checksum.as_deref(), Some(FIXTURE_SHASUM));
+    }
+
+    const LINUX_X64: Platform = Platform {
+        os: Os::Linux,
+        arch: Arch::X86_64,
+    };
+
+    fn version_meta(json: &str) -> NpmVersionMetadata {
+        serde_json::from_str(json).expect("parse version metadata")
+    }
+
+    #[test]
+    fn test_known_native_packages() {
+        assert_eq!(
+            known_native_packages("esbuild", &LINUX_X64),
+            vec!["@esbuild/linux-x64"]
+        );
+        assert_eq!(
+            known_native_packages("turbo", &LINUX_X64),
+            vec!["turbo-linux-64"]
+        );
+        let windows = Platform {
+            os: Os::Windows,
+            arch: Arch::X86_64,
+        };
+        assert_eq!(
+            known_native_packages("turbo", &windows),
+            vec!["turbo-windows-64"]
+        );
+        let mac = Platform {
+            os: Os::MacOs,
+            arch: Arch::Aarch64,
+        };
+        assert_eq!(
+            known_native_packages("@biomejs/biome", &mac),
+            vec![
+                "@biomejs/cli-darwin-arm64",
+                "@biomejs/cli-darwin-arm64-musl"
+            ]
+        );
+        assert_eq!(
+            known_native_packages("@swc/core", &LINUX_X64)[0],
+            "@swc/core-linux-x64-gnu"
+        );
+        assert!(known_native_packages("left-pad", &LINUX_X64).is_empty());
+    }
+
+    #[test]
+    fn test_native_package_uses_known_convention() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/%40esbuild%2Flinux-x64")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "name": "@esbuild/linux-x64",
+                    "dist-tags": { "latest": "0.24.0" },
+                    "versions": {
+                        "0.24.0": { "version": "0.24.0", "dist": { "tarball": "https://example.com/linux-x64-0.24.0.tgz" } }
+                    }
+                }"#,
+            )
+            .create();
+
+        let esbuild = version_meta(
+            r#"{
+                "version": "0.24.0",
+                "dist": { "tarball": "https://example.com/esbuild-0.24.0.tgz" },
+                "bin": { "esbuild": "bin/esbuild" },
+                "optionalDependencies": {
+                    "@esbuild/darwin-arm64": "0.24.0",
+                    "@esbuild/linux-x64": "0.24.0"
+                }
+            }"#,
+        );
+
+        let source = NpmSource::with_registry_base(server.url());
+        let (name, meta) = source
+            .native_package("esbuild", &esbuild, &LINUX_X64)
+            .expect("lookup")
+            .expect("native package");
+        assert_eq!(name, "@esbuild/linux-x64");
+        assert_eq!(meta.version, "0.24.0");
+    }
+
+    #[test]
+    fn test_native_package_falls_back_to_os_cpu_fields() {
+        let mut server = Server::new();
+        let sub_package = |name: &str, os: &str, cpu: &str, libc: &str| {
+            format!(
+                r#"{{
+                    "name": "{name}",
+                    "dist-tags": {{ "latest": "1.0.0" }},
+                    "versions": {{
+                        "1.0.0": {{
+                            "version": "1.0.0",
+                            "dist": {{ "tarball": "https://example.com/{os}-{cpu}.tgz" }},
+                            "os": ["{os}"], "cpu": ["{cpu}"], "libc": [{libc}]
+                        }}
+                    }}
+                }}"#
+            )
+        };
+        for (path, body) in [
+            (
+                "/%40acme%2Ftool-linux-arm64",
+                sub_package("@acme/tool-linux-arm64", "linux", "arm64", ""),
+            ),
+            (
+                "/%40acme%2Ftool-linux-x64",
+                sub_package("@acme/tool-linux-x64", "linux", "x64", r#""glibc""#),
+            ),
+            (
+                "/%40acme%2Ftool-linux-x64-musl",
+                sub_package("@acme/tool-linux-x64-musl", "linux", "x64", r#""musl""#),
+            ),
+        ] {
+            server
+                .mock("GET", path)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create();
+        }
+
+        // Optional dependencies that don't name the OS are never fetched
+        let tool = version_meta(
+            r#"{
+                "version": "1.0.0",
+                "dist": { "tarball": "https://example.com/tool-1.0.0.tgz" },
+                "optionalDependencies": {
+                    "@acme/tool-darwin-x64": "1.0.0",
+                    "@acme/tool-linux-arm64": "1.0.0",
+                    "@acme/tool-linux-x64": "^1.0.0",
+                    "@acme/tool-linux-x64-musl": "1.0.0",
+                    "fsevents": "2.3.3"
+                }
+            }"#,
+        );
+
+        let source = NpmSource::with_registry_base(server.url());
+        let (name, meta) = source
+            .native_package("@acme/tool", &tool, &LINUX_X64)
+            .expect("lookup")
+            .expect("native package");
+        assert_eq!(name, "@acme/tool-linux-x64");
+        assert_eq!(meta.version, "1.0.0");
+
+        let no_optional = version_meta(
+            r#"{ "version": "1.0.0", "dist": { "tarball": "https://example.com/x.tgz" } }"#,
+        );
+        assert!(source
+            .native_package("@acme/tool", &no_optional, &LINUX_X64)
+            .expect("lookup")
+            .is_none());
+    }
+
+    #[test]
+    fn test_native_is_current_compares_sub_package_version() {
+        let latest = version_meta(
+            r#"{
+                "version": "2.3.4",
+                "dist": {},
+                "optionalDependencies": { "turbo-linux-64": "2.3.4" }
+            }"#,
+        );
+        let native = |version: &str| NativePackage {
+            package: "turbo-linux-64".to_string(),
+            version: version.to_string(),
+        };
+
+        assert!(native_is_current(&latest, &native("2.3.4")));
+        assert!(!native_is_current(&latest, &native("2.3.3")));
+        assert!(!native_is_current(
+            &latest,
+            &NativePackage {
+                package: "turbo-linux-arm64".to_string(),
+                version: "2.3.4".to_string(),
+            }
+        ));
+    }
+
+    #[test]
+    fn test_find_native_executable_prefers_shallowest() {
+        let temp = tempdir().expect("tempdir");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("package/bin/nested")).unwrap();
+        fs::write(dir.join("package/bin/nested/esbuild"), b"nested").unwrap();
+        fs::write(dir.join("package/bin/esbuild"), b"\x7fELF").unwrap();
+        fs::write(dir.join("package/README.md"), b"# esbuild").unwrap();
+
+        assert_eq!(
+            find_native_executable(dir, "esbuild").unwrap(),
+            Some(dir.join("package/bin/esbuild"))
+        );
+        assert_eq!(find_native_executable(dir, "biome").unwrap(), None);
+    }
+
     #[test]
     fn test_scoped_package_url() {
         assert_eq!(encode_package_name("@scope/pkg"), "%40scope%2Fpkg");
@@ -591,6 +1499,8 @@ mod tests {
                 package: "left-pad".to_string(),
             },
             binary_name: None,
+            all_bins: false,
+            fallback: None,
         };
 
         let resolved = source.resolve(&spec).expect("resolve");
@@ -649,4 +1559,103 @@ mod tests {
         let perms = fs::metadata(&wrapper_path).expect("metadata").permissions();
         assert_eq!(perms.mode() & 0o777, 0o755);
     }
+
+    #[test]
+    fn test_resolve_js_binary_requires_declared_file() {
+        let temp = tempdir().expect("tempdir");
+        let package_dir = temp.path().join("node_modules").join("cowsay");
+        fs::create_dir_all(package_dir.join("cli")).expect("mkdir");
+        fs::write(package_dir.join("cli/index.js"), "").expect("write");
+
+        let found = resolve_js_binary(temp.path(), "cowsay", "cowsay", "./cli/index.js");
+        assert_eq!(found.unwrap(), package_dir.join("cli/index.js"));
+
+        match resolve_js_binary(temp.path(), "cowsay", "cowthink", "./cli/think.js") {
+            Err(FetchError::MissingBinEntry { name, path, .. }) => {
+                assert_eq!(name, "cowthink");
+                assert_eq!(path, "./cli/think.js");
+            }
+            other => panic!("expected MissingBinEntry, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_fix_shebang() {
+        let temp = tempdir().expect("tempdir");
+        let node = Path::new("/opt/fetchbin/node/bin/node");
+        let script = temp.path().join("cli.js");
+
+        fs::write(&script, "#!/usr/bin/env node\nconsole.log(1)\n").expect("write");
+        fix_shebang(&script, node).expect("fix");
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            "#!/opt/fetchbin/node/bin/node\nconsole.log(1)\n"
+        );
+
+        // Already pointing at the runtime: unchanged
+        fix_shebang(&script, node).expect("fix");
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            "#!/opt/fetchbin/node/bin/node\nconsole.log(1)\n"
+        );
+
+        fs::write(&script, "console.log(1)\n").expect("write");
+        fix_shebang(&script, node).expect("fix");
+        assert_eq!(
+            fs::read_to_string(&script).unwrap(),
+            "#!/opt/fetchbin/node/bin/node\nconsole.log(1)\n"
+        );
+
+        // Another interpreter's script is left alone
+        fs::write(&script, "#!/bin/sh\necho 1\n").expect("write");
+        fix_shebang(&script, node).expect("fix");
+        assert_eq!(fs::read_to_string(&script).unwrap(), "#!/bin/sh\necho 1\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_smoke_test_falls_back_to_help() {
+        let temp = tempdir().expect("tempdir");
+        let tool = temp.path().join("tool");
+        fs::write(
+            &tool,
+            "#!/bin/sh\n[ \"$1\" = --help ] && exit 0\necho \"unknown flag $1\" >&2\nexit 2\n",
+        )
+        .expect("write");
+        set_executable(&tool).expect("chmod");
+
+        smoke_test(&tool, Duration::from_secs(5)).expect("--help passes");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_smoke_test_reports_failure_and_timeout() {
+        let temp = tempdir().expect("tempdir");
+        let broken = temp.path().join("broken");
+        fs::write(
+            &broken,
+            "#!/bin/sh\necho \"Cannot find module\" >&2\nexit 1\n",
+        )
+        .expect("write");
+        set_executable(&broken).expect("chmod");
+
+        match smoke_test(&broken, Duration::from_secs(5)) {
+            Err(FetchError::SmokeTestFailed { binary, detail }) => {
+                assert_eq!(binary, "broken");
+                assert!(detail.contains("Cannot find module"), "{detail}");
+            }
+            other => panic!("expected SmokeTestFailed, got {other:?}"),
+        }
+
+        let hangs = temp.path().join("hangs");
+        fs::write(&hangs, "#!/bin/sh\nexec sleep 10\n").expect("write");
+        set_executable(&hangs).expect("chmod");
+
+        match smoke_test(&hangs, Duration::from_millis(200)) {
+            Err(FetchError::SmokeTestFailed { detail, .. }) => {
+                assert!(detail.contains("timed out"), "{detail}");
+            }
+            other => panic!("expected SmokeTestFailed, got {other:?}"),
+        }
+    }
 }
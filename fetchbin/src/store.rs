@@ -0,0 +1,176 @@
+//! Housekeeping for the store, where installs unpack into
+//! `<store>/<source>/<name>/<version>`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A store entry no manifest entry points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Version dirs under `store_root` that aren't in `referenced`, such as
+/// those left by an install that crashed before recording itself, plus
+/// `<source>/<name>` dirs with no versions left.
+pub fn find_orphans(store_root: &Path, referenced: &HashSet<PathBuf>) -> io::Result<Vec<Orphan>> {
+    let mut orphans = Vec::new();
+    for source in subdirs(store_root)? {
+        for name in subdirs(&source)? {
+            let mut versions = 0;
+            for entry in sorted_entries(&name)? {
+                versions += 1;
+                if !referenced.contains(&entry) {
+                    let bytes = disk_usage(&entry)?;
+                    orphans.push(Orphan { path: entry, bytes });
+                }
+            }
+            if versions == 0 {
+                orphans.push(Orphan {
+                    path: name,
+                    bytes: 0,
+                });
+            }
+        }
+    }
+    Ok(orphans)
+}
+
+/// Delete an orphan found by [`find_orphans`], and any parents it leaves
+/// empty.
+pub fn remove_orphan(orphan: &Orphan, store_root: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(&orphan.path)?.is_dir() {
+        fs::remove_dir_all(&orphan.path)?;
+    } else {
+        fs::remove_file(&orphan.path)?;
+    }
+    remove_empty_parents(&orphan.path, store_root)
+}
+
+/// Remove the parents of `path` that are empty, stopping at `root` (which
+/// is kept) or the first one that isn't.
+pub fn remove_empty_parents(path: &Path, root: &Path) -> io::Result<()> {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) {
+            break;
+        }
+        match fs::remove_dir(current) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            // Not empty (ENOTEMPTY has no stable ErrorKind everywhere)
+            Err(_) if current.read_dir()?.next().is_some() => break,
+            Err(err) => return Err(err),
+        }
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+/// Bytes used by the files under `path`, without following symlinks.
+pub fn disk_usage(path: &Path) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += disk_usage(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+fn subdirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(sorted_entries(dir)?
+        .into_iter()
+        .filter(|path| path.is_dir() && !path.is_symlink())
+        .collect())
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, bytes: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; bytes]).unwrap();
+    }
+
+    #[test]
+    fn finds_unreferenced_versions_and_empty_names() {
+        let temp = TempDir::new().unwrap();
+        let store = temp.path().join("store");
+        let kept = store.join("github/BurntSushi-ripgrep/14.1.0");
+        let stale = store.join("github/BurntSushi-ripgrep/14.0.0");
+        let crashed = store.join("npm/prettier/3.2.0");
+        write(&kept.join("rg"), 10);
+        write(&stale.join("rg"), 7);
+        write(&crashed.join("node_modules/prettier/index.js"), 5);
+        fs::create_dir_all(store.join("cargo/empty")).unwrap();
+
+        let referenced = HashSet::from([kept.clone()]);
+        let orphans = find_orphans(&store, &referenced).unwrap();
+        assert_eq!(
+            orphans,
+            [
+                Orphan {
+                    path: store.join("cargo/empty"),
+                    bytes: 0
+                },
+                Orphan {
+                    path: stale,
+                    bytes: 7
+                },
+                Orphan {
+                    path: crashed,
+                    bytes: 5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn removing_orphans_cleans_empty_parents_but_keeps_the_root() {
+        let temp = TempDir::new().unwrap();
+        let store = temp.path().join("store");
+        let kept = store.join("github/BurntSushi-ripgrep/14.1.0");
+        write(&kept.join("rg"), 1);
+        write(&store.join("github/BurntSushi-ripgrep/14.0.0/rg"), 1);
+        write(&store.join("npm/prettier/3.2.0/index.js"), 1);
+
+        let referenced = HashSet::from([kept.clone()]);
+        for orphan in find_orphans(&store, &referenced).unwrap() {
+            remove_orphan(&orphan, &store).unwrap();
+        }
+
+        assert!(kept.exists());
+        assert!(!store.join("github/BurntSushi-ripgrep/14.0.0").exists());
+        assert!(!store.join("npm").exists());
+        assert!(store.exists());
+        assert!(find_orphans(&store, &referenced).unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_store_has_no_orphans() {
+        let temp = TempDir::new().unwrap();
+        let orphans = find_orphans(&temp.path().join("store"), &HashSet::new()).unwrap();
+        assert!(orphans.is_empty());
+    }
+}
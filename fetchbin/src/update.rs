@@ -0,0 +1,178 @@
+use serde::Serialize;
+
+/// What `update` found, or did, for one installed binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum UpdateOutcome {
+    UpToDate,
+    /// Held by `fetchbin pin`; not checked.
+    Pinned,
+    /// A newer version exists (dry run, or not yet fetched).
+    Available {
+        version: String,
+    },
+    Updated {
+        version: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// One row of an update report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateEntry {
+    pub name: String,
+    pub installed: String,
+    pub source: String,
+    #[serde(flatten)]
+    pub outcome: UpdateOutcome,
+}
+
+impl UpdateEntry {
+    /// Text for the "available" column.
+    pub fn available(&self) -> String {
+        match &self.outcome {
+            UpdateOutcome::UpToDate => "up to date".to_string(),
+            UpdateOutcome::Pinned => "pinned".to_string(),
+            UpdateOutcome::Available { version } | UpdateOutcome::Updated { version } => {
+                version.clone()
+            }
+            UpdateOutcome::Failed { .. } => "error".to_string(),
+        }
+    }
+}
+
+/// Per-outcome counts for the closing summary line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UpdateSummary {
+    pub updated: usize,
+    pub available: usize,
+    pub up_to_date: usize,
+    pub pinned: usize,
+    pub failed: usize,
+}
+
+/// The result of `fetchbin update`, one entry per binary considered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UpdateReport {
+    pub dry_run: bool,
+    pub binaries: Vec<UpdateEntry>,
+}
+
+impl UpdateReport {
+    pub fn summary(&self) -> UpdateSummary {
+        let mut summary = UpdateSummary::default();
+        for entry in &self.binaries {
+            match entry.outcome {
+                UpdateOutcome::UpToDate => summary.up_to_date += 1,
+                UpdateOutcome::Pinned => summary.pinned += 1,
+                UpdateOutcome::Available { .. } => summary.available += 1,
+                UpdateOutcome::Updated { .. } => summary.updated += 1,
+                UpdateOutcome::Failed { .. } => summary.failed += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.binaries
+            .iter()
+            .filter_map(|entry| match &entry.outcome {
+                UpdateOutcome::Failed { error } => Some((entry.name.as_str(), error.as_str())),
+                _ => None,
+            })
+    }
+}
+
+impl std::fmt::Display for UpdateSummary {
+    /// e.g. "3 updated, 2 up-to-date, 1 failed"; zero counts are omitted.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            (self.updated, "updated"),
+            (self.available, "available"),
+            (self.up_to_date, "up-to-date"),
+            (self.pinned, "pinned"),
+            (self.failed, "failed"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect();
+
+        if parts.is_empty() {
+            write!(f, "nothing to update")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, outcome: UpdateOutcome) -> UpdateEntry {
+        UpdateEntry {
+            name: name.to_string(),
+            installed: "1.0.0".to_string(),
+            source: "npm".to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn summary_counts_each_outcome() {
+        let report = UpdateReport {
+            dry_run: false,
+            binaries: vec![
+                entry(
+                    "a",
+                    UpdateOutcome::Updated {
+                        version: "2.0.0".to_string(),
+                    },
+                ),
+                entry("b", UpdateOutcome::UpToDate),
+                entry("c", UpdateOutcome::UpToDate),
+                entry(
+                    "d",
+                    UpdateOutcome::Failed {
+                        error: "boom".to_string(),
+                    },
+                ),
+            ],
+        };
+
+        assert_eq!(
+            report.summary().to_string(),
+            "1 updated, 2 up-to-date, 1 failed"
+        );
+        assert_eq!(report.failures().collect::<Vec<_>>(), vec![("d", "boom")]);
+        assert_eq!(
+            UpdateReport::default().summary().to_string(),
+            "nothing to update"
+        );
+    }
+
+    #[test]
+    fn entries_serialize_with_flat_status() {
+        let value = serde_json::to_value(entry(
+            "turbo",
+            UpdateOutcome::Available {
+                version: "2.3.4".to_string(),
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "name": "turbo",
+                "installed": "1.0.0",
+                "source": "npm",
+                "status": "available",
+                "version": "2.3.4",
+            })
+        );
+    }
+}
@@ -11,8 +11,11 @@ fn test_resolve_lazygit_releases() {
         source: SourceConfig::Github {
             repo: "jesseduffield/lazygit".to_string(),
             asset_pattern: None,
+            include_prerelease: false,
         },
         binary_name: None,
+        all_bins: false,
+        fallback: None,
     };
 
     let releases = source.resolve(&spec).expect("resolve releases");
@@ -0,0 +1,103 @@
+//! Fingerprinting a repo's ASCII-armored signing key.
+//!
+//! The fingerprint is a SHA-256 over the decoded key material, so armor
+//! headers, line wrapping and the CRC line can change without it changing,
+//! while a rotated or swapped key always does.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+const BEGIN: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+const END: &str = "-----END PGP PUBLIC KEY BLOCK-----";
+
+/// SHA-256 (hex) of the key material in every public key block of `armored`.
+pub fn fingerprint(armored: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut blocks = 0;
+    let mut lines = armored.lines().map(str::trim);
+
+    while lines.any(|line| line == BEGIN) {
+        // Armor headers (`Version: ...`) run up to the first blank line
+        let mut body = String::new();
+        let mut in_headers = true;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line == END {
+                closed = true;
+                break;
+            }
+            if in_headers {
+                if line.is_empty() {
+                    in_headers = false;
+                } else if !line.contains(':') {
+                    // No headers at all: this is already body
+                    in_headers = false;
+                    body.push_str(line);
+                }
+                continue;
+            }
+            if !line.starts_with('=') {
+                body.push_str(line);
+            }
+        }
+        if !closed {
+            bail!("unterminated PGP public key block");
+        }
+
+        let material = base64::engine::general_purpose::STANDARD
+            .decode(&body)
+            .context("decoding PGP key block")?;
+        if material.is_empty() {
+            bail!("empty PGP public key block");
+        }
+        hasher.update(&material);
+        blocks += 1;
+    }
+
+    if blocks == 0 {
+        bail!("no PGP public key block found");
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn armor(headers: &str, body: &str, crc: &str) -> String {
+        format!("{BEGIN}\n{headers}\n{body}\n={crc}\n{END}\n")
+    }
+
+    #[test]
+    fn fingerprint_ignores_armor_headers_and_crc() {
+        let plain = armor("", "bWF0ZXJp\nYWw=", "AAAA");
+        let with_headers = armor(
+            "Version: GnuPG v2\nComment: repo key\n",
+            "bWF0ZXJpYWw=",
+            "BBBB",
+        );
+
+        let expected = format!("{:x}", Sha256::digest(b"material"));
+        assert_eq!(fingerprint(&plain).unwrap(), expected);
+        assert_eq!(fingerprint(&with_headers).unwrap(), expected);
+        assert_ne!(
+            fingerprint(&armor("", "b3RoZXI=", "AAAA")).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn fingerprint_covers_every_block() {
+        let first = armor("", "bWF0ZXJpYWw=", "AAAA");
+        let both = format!("{first}{}", armor("", "b3RoZXI=", "AAAA"));
+        assert_ne!(fingerprint(&both).unwrap(), fingerprint(&first).unwrap());
+    }
+
+    #[test]
+    fn fingerprint_rejects_non_keys() {
+        assert!(fingerprint("<html>404</html>").is_err());
+        assert!(fingerprint(&format!("{BEGIN}\n\nbWF0ZXJpYWw=\n")).is_err());
+        assert!(fingerprint(&armor("", "not base64!", "AAAA")).is_err());
+    }
+}
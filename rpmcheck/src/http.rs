@@ -0,0 +1,37 @@
+//! The HTTP layer repo checks go through.
+//!
+//! Checks only ever GET whole files, so the trait is that and nothing more;
+//! tests swap in a map of canned responses.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+/// Fetches repo metadata files by URL.
+pub trait Fetch: Sync {
+    /// GET `url` and return the body, failing on a non-success status.
+    fn get(&self, url: &str) -> Result<Vec<u8>>;
+
+    /// GET `url` as UTF-8 text.
+    fn get_text(&self, url: &str) -> Result<String> {
+        String::from_utf8(self.get(url)?).with_context(|| format!("{url} is not UTF-8"))
+    }
+}
+
+impl Fetch for reqwest::blocking::Client {
+    fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let bytes = reqwest::blocking::Client::get(self, url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.bytes())?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// A blocking HTTP client with a per-request `timeout`.
+pub fn client(timeout: Duration) -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("building HTTP client")
+}
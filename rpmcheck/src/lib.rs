@@ -1,3 +1,8 @@
+pub mod gpgkey;
+pub mod http;
+pub mod lockfile;
+pub mod repodata;
+
 use serde::Deserialize;
 
 // ---------------------------------------------------------------------------
@@ -7,6 +12,12 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 pub struct Manifest {
     pub repos: Vec<RepoEntry>,
+    /// `$basearch` for every repo, instead of the host's architecture.
+    #[serde(default)]
+    pub basearch: Option<String>,
+    /// `$releasever` for every repo.
+    #[serde(default)]
+    pub releasever: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -14,54 +25,197 @@ pub struct RepoEntry {
     pub name: String,
     pub baseurl: String,
     pub packages: Vec<String>,
+    /// `$basearch` for this repo, overriding the manifest-level value.
+    #[serde(default)]
+    pub basearch: Option<String>,
+    /// The repo's ASCII-armored signing key; its fingerprint is tracked so
+    /// a key rotation shows up as a change.
+    #[serde(default)]
+    pub gpg_key_url: Option<String>,
+}
+
+/// Where to fetch a repo's metadata and signing key from, with DNF
+/// variables expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoUrls {
+    pub baseurl: String,
+    pub gpg_key: Option<String>,
+}
+
+impl Manifest {
+    /// The `$basearch` to check `repo` for: `arch_flag` (`--arch`), then the
+    /// repo's and the manifest's `basearch`, then the host architecture.
+    pub fn basearch_for(&self, repo: &RepoEntry, arch_flag: Option<&str>) -> String {
+        arch_flag
+            .or(repo.basearch.as_deref())
+            .or(self.basearch.as_deref())
+            .unwrap_or_else(|| host_basearch())
+            .to_string()
+    }
+
+    /// `repo`'s baseurl with DNF variables expanded.
+    pub fn repo_url(&self, repo: &RepoEntry, arch_flag: Option<&str>) -> String {
+        expand_repo_url(
+            &repo.baseurl,
+            &self.basearch_for(repo, arch_flag),
+            self.releasever.as_deref(),
+        )
+    }
+
+    /// `repo`'s baseurl and key URL with DNF variables expanded.
+    pub fn repo_urls(&self, repo: &RepoEntry, arch_flag: Option<&str>) -> RepoUrls {
+        let basearch = self.basearch_for(repo, arch_flag);
+        let expand = |url: &str| expand_repo_url(url, &basearch, self.releasever.as_deref());
+        RepoUrls {
+            baseurl: expand(&repo.baseurl),
+            gpg_key: repo.gpg_key_url.as_deref().map(expand),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // URL expansion
 // ---------------------------------------------------------------------------
 
-/// Expand DNF-style variables in a URL (e.g. `$basearch` or `${basearch}`).
-pub fn expand_repo_url(url: &str) -> String {
-    let basearch = match std::env::consts::ARCH {
+/// The build host's architecture, as DNF names it.
+pub fn host_basearch() -> &'static str {
+    match std::env::consts::ARCH {
         "x86_64" => "x86_64",
         "aarch64" => "aarch64",
         "arm" => "armhfp",
         "powerpc64" => "ppc64le",
         "s390x" => "s390x",
         other => other,
-    };
-    url.replace("${basearch}", basearch)
-        .replace("$basearch", basearch)
+    }
+}
+
+/// Expand DNF-style variables in a URL (e.g. `$basearch` or `${basearch}`).
+///
+/// `$releasever` is left as-is when no `releasever` is given.
+pub fn expand_repo_url(url: &str, basearch: &str, releasever: Option<&str>) -> String {
+    let url = url
+        .replace("${basearch}", basearch)
+        .replace("$basearch", basearch);
+    match releasever {
+        Some(releasever) => url
+            .replace("${releasever}", releasever)
+            .replace("$releasever", releasever),
+        None => url,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn manifest(json: &str) -> Manifest {
+        serde_json::from_str(json).expect("parse manifest")
+    }
+
+    const REPOS: &str = r#"
+        "repos": [
+            { "name": "pinned", "baseurl": "https://example.com/$basearch",
+              "packages": [], "basearch": "ppc64le" },
+            { "name": "plain", "baseurl": "https://example.com/${basearch}", "packages": [] }
+        ]"#;
+
     #[test]
     fn expand_bare_basearch() {
         let url = "https://example.com/rpm/stable/$basearch";
-        let expanded = expand_repo_url(url);
-        assert!(
-            !expanded.contains('$'),
-            "unexpanded variable in: {expanded}"
+        assert_eq!(
+            expand_repo_url(url, "aarch64", None),
+            "https://example.com/rpm/stable/aarch64"
         );
-        assert!(expanded.ends_with(std::env::consts::ARCH) || expanded.ends_with("x86_64"));
     }
 
     #[test]
     fn expand_braced_basearch() {
         let url = "https://example.com/rpm/stable/${basearch}";
-        let expanded = expand_repo_url(url);
-        assert!(
-            !expanded.contains('$'),
-            "unexpanded variable in: {expanded}"
+        assert_eq!(
+            expand_repo_url(url, "x86_64", None),
+            "https://example.com/rpm/stable/x86_64"
+        );
+    }
+
+    #[test]
+    fn expand_releasever() {
+        let url = "https://example.com/fedora/$releasever/${basearch}/";
+        assert_eq!(
+            expand_repo_url(url, "x86_64", Some("43")),
+            "https://example.com/fedora/43/x86_64/"
+        );
+        assert_eq!(
+            expand_repo_url("https://example.com/${releasever}", "x86_64", Some("43")),
+            "https://example.com/43"
+        );
+        // Without a releasever the variable is left for the fetch to fail on
+        assert_eq!(
+            expand_repo_url(url, "x86_64", None),
+            "https://example.com/fedora/$releasever/x86_64/"
         );
     }
 
     #[test]
     fn no_variables_unchanged() {
         let url = "https://example.com/rpm/stable/x86_64";
-        assert_eq!(expand_repo_url(url), url);
+        assert_eq!(expand_repo_url(url, "aarch64", Some("43")), url);
+    }
+
+    #[test]
+    fn basearch_precedence_flag_then_manifest_then_host() {
+        let with_arch = manifest(&format!(r#"{{ "basearch": "x86_64", {REPOS} }}"#));
+        let without = manifest(&format!("{{ {REPOS} }}"));
+        let (pinned, plain) = (&with_arch.repos[0], &with_arch.repos[1]);
+
+        // --arch wins over everything
+        assert_eq!(with_arch.basearch_for(pinned, Some("aarch64")), "aarch64");
+        assert_eq!(with_arch.basearch_for(plain, Some("aarch64")), "aarch64");
+        // Then the repo's own basearch, then the manifest's
+        assert_eq!(with_arch.basearch_for(pinned, None), "ppc64le");
+        assert_eq!(with_arch.basearch_for(plain, None), "x86_64");
+        // Then the host
+        assert_eq!(
+            without.basearch_for(&without.repos[1], None),
+            host_basearch()
+        );
+    }
+
+    #[test]
+    fn repo_url_uses_manifest_releasever() {
+        let manifest = manifest(
+            r#"{
+                "basearch": "x86_64",
+                "releasever": "42",
+                "repos": [{ "name": "r", "packages": [],
+                            "baseurl": "https://example.com/f$releasever/$basearch" }]
+            }"#,
+        );
+        assert_eq!(
+            manifest.repo_url(&manifest.repos[0], Some("aarch64")),
+            "https://example.com/f42/aarch64"
+        );
+    }
+
+    #[test]
+    fn repo_urls_expand_the_key_url_too() {
+        let manifest = manifest(
+            r#"{
+                "releasever": "42",
+                "repos": [
+                    { "name": "keyed", "packages": [], "basearch": "x86_64",
+                      "baseurl": "https://example.com/f$releasever/$basearch",
+                      "gpg_key_url": "https://example.com/keys/f$releasever.asc" },
+                    { "name": "unkeyed", "packages": [], "baseurl": "https://example.com" }
+                ]
+            }"#,
+        );
+        assert_eq!(
+            manifest.repo_urls(&manifest.repos[0], None),
+            RepoUrls {
+                baseurl: "https://example.com/f42/x86_64".to_string(),
+                gpg_key: Some("https://example.com/keys/f42.asc".to_string()),
+            }
+        );
+        assert_eq!(manifest.repo_urls(&manifest.repos[1], None).gpg_key, None);
     }
 }
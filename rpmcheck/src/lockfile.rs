@@ -0,0 +1,495 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// ---------------------------------------------------------------------------
+// Lockfile types
+// ---------------------------------------------------------------------------
+
+/// Per-package snapshot of tracked repo state, written by `--lock`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub packages: BTreeMap<String, LockedPackage>,
+    /// Signing key fingerprint per repo, for repos with a `gpg_key_url`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub epoch: String,
+    pub version: String,
+    pub release: String,
+    pub repo: String,
+    /// Where the repo serves the package from (its primary.xml
+    /// `<location>`), so a package moving shows up in diffs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+}
+
+impl LockedPackage {
+    /// `version-release`, with an `epoch:` prefix when non-zero.
+    pub fn evr(&self) -> String {
+        if self.epoch.is_empty() || self.epoch == "0" {
+            format!("{}-{}", self.version, self.release)
+        } else {
+            format!("{}:{}-{}", self.epoch, self.version, self.release)
+        }
+    }
+
+    fn cmp_evr(&self, other: &Self) -> Ordering {
+        let epoch = |e: &str| e.parse::<u64>().unwrap_or(0);
+        epoch(&self.epoch)
+            .cmp(&epoch(&other.epoch))
+            .then_with(|| rpmvercmp(&self.version, &other.version))
+            .then_with(|| rpmvercmp(&self.release, &other.release))
+    }
+}
+
+impl Lockfile {
+    pub fn load(path: &str) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("reading lockfile {path}"))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing lockfile {path}"))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        std::fs::write(path, content).with_context(|| format!("writing lockfile {path}"))
+    }
+
+    /// Record a package seen in `repo`, keeping the newest version per name.
+    pub fn record(&mut self, name: &str, package: LockedPackage) {
+        match self.packages.get(name) {
+            Some(existing) if existing.cmp_evr(&package) != Ordering::Less => {}
+            _ => {
+                self.packages.insert(name.to_string(), package);
+            }
+        }
+    }
+
+    /// SHA-256 over the locked versions and any key fingerprints; this is
+    /// the hash `--baseline` compares.
+    ///
+    /// Without keys it's the same hash as before keys were tracked.
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (name, pkg) in &self.packages {
+            hasher.update(format!(
+                "{}\t{}\t{}\t{}\n",
+                name, pkg.epoch, pkg.version, pkg.release
+            ));
+        }
+        for (repo, fingerprint) in &self.keys {
+            hasher.update(format!("key\t{repo}\t{fingerprint}\n"));
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Drop packages locked from any of `repos` (e.g. repos that failed to
+    /// fetch, so their packages don't show up as removed).
+    pub fn without_repos(&self, repos: &BTreeSet<String>) -> Self {
+        Self {
+            packages: self
+                .packages
+                .iter()
+                .filter(|(_, pkg)| !repos.contains(&pkg.repo))
+                .map(|(name, pkg)| (name.clone(), pkg.clone()))
+                .collect(),
+            keys: self
+                .keys
+                .iter()
+                .filter(|(repo, _)| !repos.contains(*repo))
+                .map(|(repo, key)| (repo.clone(), key.clone()))
+                .collect(),
+        }
+    }
+
+    /// Repos whose signing key differs between `self` and `current`.
+    ///
+    /// A repo only counts once both sides have a fingerprint, so starting
+    /// (or stopping) tracking a key isn't reported as a rotation.
+    pub fn key_changes(&self, current: &Lockfile) -> Vec<KeyChange> {
+        self.keys
+            .iter()
+            .filter_map(|(repo, old)| {
+                let new = current.keys.get(repo)?;
+                (new != old).then(|| KeyChange {
+                    repo: repo.clone(),
+                    key_changed: true,
+                    old: old.clone(),
+                    new: new.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Changes needed to go from `self` (the lockfile) to `current`, by name.
+    pub fn diff(&self, current: &Lockfile) -> Vec<PackageChange> {
+        let names: BTreeSet<&String> = self
+            .packages
+            .keys()
+            .chain(current.packages.keys())
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let from = self.packages.get(name);
+                let to = current.packages.get(name);
+                let change = match (from, to) {
+                    (None, Some(_)) => ChangeKind::Added,
+                    (Some(_), None) => ChangeKind::Removed,
+                    (Some(old), Some(new)) => match old.cmp_evr(new) {
+                        Ordering::Less => ChangeKind::Upgraded,
+                        Ordering::Greater => ChangeKind::Downgraded,
+                        Ordering::Equal => match (&old.href, &new.href) {
+                            (Some(from), Some(to)) if from != to => ChangeKind::Moved,
+                            _ => return None,
+                        },
+                    },
+                    (None, None) => return None,
+                };
+                Some(PackageChange {
+                    name: name.clone(),
+                    change,
+                    from: from.cloned(),
+                    to: to.cloned(),
+                })
+            })
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Diff output
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    /// Same version, served from a different location.
+    Moved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageChange {
+    pub name: String,
+    pub change: ChangeKind,
+    pub from: Option<LockedPackage>,
+    pub to: Option<LockedPackage>,
+}
+
+impl std::fmt::Display for PackageChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.from, &self.to) {
+            (None, Some(to)) => write!(f, "+ {} {} ({})", self.name, to.evr(), to.repo),
+            (Some(from), None) => write!(f, "- {} {} ({})", self.name, from.evr(), from.repo),
+            (Some(from), Some(to)) if self.change == ChangeKind::Moved => write!(
+                f,
+                "~ {} {} moved {} -> {} ({})",
+                self.name,
+                to.evr(),
+                from.href.as_deref().unwrap_or_default(),
+                to.href.as_deref().unwrap_or_default(),
+                to.repo
+            ),
+            (Some(from), Some(to)) => {
+                write!(
+                    f,
+                    "~ {} {} -> {} ({})",
+                    self.name,
+                    from.evr(),
+                    to.evr(),
+                    to.repo
+                )
+            }
+            (None, None) => write!(f, "  {}", self.name),
+        }
+    }
+}
+
+/// A repo's signing key changing between two lockfiles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyChange {
+    pub repo: String,
+    /// Always `true`; spelled out so JSON consumers can match on it.
+    pub key_changed: bool,
+    pub old: String,
+    pub new: String,
+}
+
+impl std::fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "! {} signing key {} -> {}",
+            self.repo, self.old, self.new
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RPM version comparison
+// ---------------------------------------------------------------------------
+
+/// Compare two version or release strings the way `rpmvercmp` does.
+///
+/// Strings are split into alternating numeric and alphabetic segments;
+/// numeric segments compare as numbers and beat alphabetic ones, `~` sorts
+/// before anything (pre-releases) and `^` sorts after the end of a string.
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let significant = |c: &u8| c.is_ascii_alphanumeric() || *c == b'~' || *c == b'^';
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+
+    loop {
+        a = &a[a.iter().position(significant).unwrap_or(a.len())..];
+        b = &b[b.iter().position(significant).unwrap_or(b.len())..];
+
+        match (a.first(), b.first()) {
+            (Some(b'~'), Some(b'~')) | (Some(b'^'), Some(b'^')) => {
+                a = &a[1..];
+                b = &b[1..];
+                continue;
+            }
+            (Some(b'~'), _) => return Ordering::Less,
+            (_, Some(b'~')) => return Ordering::Greater,
+            (Some(b'^'), None) => return Ordering::Greater,
+            (None, Some(b'^')) => return Ordering::Less,
+            (Some(b'^'), _) => return Ordering::Less,
+            (_, Some(b'^')) => return Ordering::Greater,
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(_), Some(_)) => {}
+        }
+
+        let numeric = a[0].is_ascii_digit();
+        let in_segment = |c: &u8| {
+            if numeric {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        let a_len = a.iter().position(|c| !in_segment(c)).unwrap_or(a.len());
+        let b_len = b.iter().position(|c| !in_segment(c)).unwrap_or(b.len());
+        let (a_seg, b_seg) = (&a[..a_len], &b[..b_len]);
+
+        // Segments of different types: numeric is newer
+        if b_seg.is_empty() {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let ordering = if numeric {
+            let trim = |s: &[u8]| -> usize { s.iter().position(|c| *c != b'0').unwrap_or(s.len()) };
+            let (a_num, b_num) = (&a_seg[trim(a_seg)..], &b_seg[trim(b_seg)..]);
+            a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num))
+        } else {
+            a_seg.cmp(b_seg)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        a = &a[a_len..];
+        b = &b[b_len..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(version: &str, release: &str, repo: &str) -> LockedPackage {
+        LockedPackage {
+            epoch: "0".to_string(),
+            version: version.to_string(),
+            release: release.to_string(),
+            repo: repo.to_string(),
+            href: None,
+        }
+    }
+
+    fn lock(entries: &[(&str, LockedPackage)]) -> Lockfile {
+        Lockfile {
+            packages: entries
+                .iter()
+                .map(|(name, p)| (name.to_string(), p.clone()))
+                .collect(),
+            keys: BTreeMap::new(),
+        }
+    }
+
+    fn at(href: &str, package: LockedPackage) -> LockedPackage {
+        LockedPackage {
+            href: Some(href.to_string()),
+            ..package
+        }
+    }
+
+    #[test]
+    fn rpmvercmp_matches_rpm_ordering() {
+        use Ordering::*;
+        let cases = [
+            ("1.0", "1.0", Equal),
+            ("1.0", "2.0", Less),
+            ("2.0.1", "2.0", Greater),
+            ("1.10", "1.9", Greater),
+            ("1.010", "1.10", Equal),
+            ("1.0a", "1.0", Greater),
+            ("1.a", "1.1", Less),
+            ("1.0~rc1", "1.0", Less),
+            ("1.0~rc1", "1.0~rc2", Less),
+            ("1.0^git1", "1.0", Greater),
+            ("1.0^git1", "1.0.1", Less),
+            ("1_0", "1.0", Equal),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(rpmvercmp(a, b), expected, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn record_keeps_newest_version() {
+        let mut lock = Lockfile::default();
+        lock.record("code", pkg("1.90.0", "1", "vscode"));
+        lock.record("code", pkg("1.100.0", "1", "vscode"));
+        lock.record("code", pkg("1.95.0", "1", "vscode"));
+        assert_eq!(lock.packages["code"].version, "1.100.0");
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_version_changes() {
+        let old = lock(&[
+            ("code", pkg("1.90.0", "1", "vscode")),
+            ("edge", pkg("120.0", "1", "edge")),
+            ("gone", pkg("1.0", "1", "misc")),
+            ("same", pkg("2.0", "3", "misc")),
+        ]);
+        let new = lock(&[
+            ("code", pkg("1.91.0", "1", "vscode")),
+            ("edge", pkg("119.0", "1", "edge")),
+            ("fresh", pkg("0.1", "1", "misc")),
+            ("same", pkg("2.0", "3", "misc")),
+        ]);
+
+        let changes: Vec<(String, ChangeKind)> = old
+            .diff(&new)
+            .into_iter()
+            .map(|c| (c.name, c.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("code".to_string(), ChangeKind::Upgraded),
+                ("edge".to_string(), ChangeKind::Downgraded),
+                ("fresh".to_string(), ChangeKind::Added),
+                ("gone".to_string(), ChangeKind::Removed),
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn without_repos_hides_failed_repo_packages() {
+        let old = lock(&[
+            ("code", pkg("1.90.0", "1", "vscode")),
+            ("edge", pkg("120.0", "1", "edge")),
+        ]);
+        let current = lock(&[("code", pkg("1.90.0", "1", "vscode"))]);
+        let failed = BTreeSet::from(["edge".to_string()]);
+        assert!(old.without_repos(&failed).diff(&current).is_empty());
+    }
+
+    #[test]
+    fn hash_matches_legacy_single_version_hash() {
+        let lock = lock(&[("code", pkg("1.90.0", "1", "vscode"))]);
+        let mut hasher = Sha256::new();
+        hasher.update("code\t0\t1.90.0\t1\n");
+        assert_eq!(lock.hash(), format!("{:x}", hasher.finalize()));
+    }
+
+    #[test]
+    fn diff_reports_moved_packages() {
+        let old = lock(&[
+            (
+                "code",
+                at("Packages/code.rpm", pkg("1.90.0", "1", "vscode")),
+            ),
+            ("edge", pkg("120.0", "1", "edge")),
+        ]);
+        let new = lock(&[
+            (
+                "code",
+                at(
+                    "https://cdn.example.com/code.rpm",
+                    pkg("1.90.0", "1", "vscode"),
+                ),
+            ),
+            ("edge", at("edge.rpm", pkg("120.0", "1", "edge"))),
+        ]);
+
+        let changes = old.diff(&new);
+        assert_eq!(changes.len(), 1, "a newly recorded href isn't a move");
+        assert_eq!(changes[0].change, ChangeKind::Moved);
+        assert_eq!(
+            changes[0].to_string(),
+            "~ code 1.90.0-1 moved Packages/code.rpm -> https://cdn.example.com/code.rpm (vscode)"
+        );
+    }
+
+    #[test]
+    fn keys_change_the_hash_and_are_reported_when_rotated() {
+        let plain = lock(&[("code", pkg("1.90.0", "1", "vscode"))]);
+        let mut keyed = plain.clone();
+        keyed.keys.insert("vscode".to_string(), "aaaa".to_string());
+        let mut rotated = keyed.clone();
+        rotated
+            .keys
+            .insert("vscode".to_string(), "bbbb".to_string());
+
+        assert_ne!(keyed.hash(), plain.hash());
+        assert_ne!(rotated.hash(), keyed.hash());
+
+        assert!(plain.key_changes(&keyed).is_empty());
+        assert!(keyed.key_changes(&plain).is_empty());
+        assert_eq!(
+            keyed.key_changes(&rotated),
+            [KeyChange {
+                repo: "vscode".to_string(),
+                key_changed: true,
+                old: "aaaa".to_string(),
+                new: "bbbb".to_string(),
+            }]
+        );
+
+        let failed = BTreeSet::from(["vscode".to_string()]);
+        assert!(keyed.without_repos(&failed).keys.is_empty());
+    }
+
+    #[test]
+    fn lockfile_roundtrip() {
+        let lock = lock(&[("code", pkg("1.90.0", "1", "vscode"))]);
+        let json = serde_json::to_string(&lock).unwrap();
+        let restored: Lockfile = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, lock);
+        assert_eq!(restored.hash(), lock.hash());
+    }
+}
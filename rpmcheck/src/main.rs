@@ -1,40 +1,74 @@
-use std::collections::{BTreeMap, HashSet};
-use std::io::Read;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
-use flate2::read::GzDecoder;
-use quick_xml::events::Event;
-use quick_xml::Reader;
-use rpmcheck::{expand_repo_url, Manifest, RepoEntry};
-use serde::Serialize;
-use sha2::{Digest, Sha256};
+use rpmcheck::http;
+use rpmcheck::lockfile::Lockfile;
+use rpmcheck::repodata::{cache_arg_name, repo_hash, PackageVersion, RepoChecker};
+use rpmcheck::{Manifest, RepoUrls};
 
 // ---------------------------------------------------------------------------
-// Package version (from primary.xml)
+// CLI
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Serialize, Ord, PartialOrd, Eq, PartialEq)]
-struct PackageVersion {
-    name: String,
-    epoch: String,
-    version: String,
-    release: String,
-}
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
-// ---------------------------------------------------------------------------
-// CLI
-// ---------------------------------------------------------------------------
+/// Exit code when some repos failed but `--allow-partial` was given.
+const EXIT_PARTIAL: i32 = 3;
 
 fn print_usage() {
     eprintln!("Usage: rpmcheck <manifest.json> [--baseline <hash>] [--json] [--per-repo]");
+    eprintln!("                [--lock <path>] [--diff <lockfile>]");
+    eprintln!("                [--concurrency <n>] [--timeout <secs>] [--allow-partial]");
+    eprintln!("                [--arch <basearch>]");
     eprintln!();
     eprintln!("Check external RPM repos for package version changes.");
     eprintln!("Outputs a SHA-256 hash of tracked package versions.");
     eprintln!();
+    eprintln!("Options:");
+    eprintln!("  -l, --lock <path>      Write per-package versions to a JSON lockfile");
+    eprintln!("  -d, --diff <lockfile>  Print packages added/removed/changed/moved and signing");
+    eprintln!("                         keys rotated since the lockfile");
+    eprintln!(
+        "  -c, --concurrency <n>  Repos to check in parallel (default: {DEFAULT_CONCURRENCY})"
+    );
+    eprintln!(
+        "  -t, --timeout <secs>   Per-request HTTP timeout (default: {DEFAULT_TIMEOUT_SECS})"
+    );
+    eprintln!("  --allow-partial        Output results for reachable repos when others fail");
+    eprintln!("  -a, --arch <basearch>  Expand $basearch as this instead of the manifest's");
+    eprintln!("                         basearch or the host architecture");
+    eprintln!();
     eprintln!("Exit codes:");
-    eprintln!("  0  Success (or unchanged when --baseline given)");
-    eprintln!("  1  Versions changed from baseline");
+    eprintln!("  0  Success (or unchanged when --baseline/--diff given)");
+    eprintln!("  1  Versions or keys changed from baseline or lockfile");
     eprintln!("  2  Error");
+    eprintln!("  3  Some repos failed (only with --allow-partial)");
+}
+
+/// Options controlling how repos are fetched and how failures are reported.
+struct RunOptions<'a> {
+    baseline: Option<&'a str>,
+    lock: Option<&'a str>,
+    diff: Option<&'a str>,
+    json: bool,
+    per_repo: bool,
+    concurrency: usize,
+    timeout: Duration,
+    allow_partial: bool,
+    arch: Option<&'a str>,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> T {
+    match value.and_then(|v| v.parse().ok()) {
+        Some(n) => n,
+        None => {
+            eprintln!("{flag} requires a numeric value");
+            print_usage();
+            std::process::exit(2);
+        }
+    }
 }
 
 fn main() {
@@ -42,8 +76,14 @@ fn main() {
 
     let mut manifest_path = None;
     let mut baseline = None;
+    let mut lock = None;
+    let mut diff = None;
     let mut json = false;
     let mut per_repo = false;
+    let mut concurrency = DEFAULT_CONCURRENCY;
+    let mut timeout_secs = DEFAULT_TIMEOUT_SECS;
+    let mut allow_partial = false;
+    let mut arch = None;
     let mut i = 1;
 
     while i < args.len() {
@@ -52,8 +92,29 @@ fn main() {
                 i += 1;
                 baseline = args.get(i).cloned();
             }
+            "--lock" | "-l" => {
+                i += 1;
+                lock = args.get(i).cloned();
+            }
+            "--diff" | "-d" => {
+                i += 1;
+                diff = args.get(i).cloned();
+            }
             "--json" | "-j" => json = true,
             "--per-repo" => per_repo = true,
+            "--concurrency" | "-c" => {
+                i += 1;
+                concurrency = parse_number("--concurrency", args.get(i));
+            }
+            "--timeout" | "-t" => {
+                i += 1;
+                timeout_secs = parse_number("--timeout", args.get(i));
+            }
+            "--allow-partial" => allow_partial = true,
+            "--arch" | "-a" => {
+                i += 1;
+                arch = args.get(i).cloned();
+            }
             "-h" | "--help" => {
                 print_usage();
                 std::process::exit(0);
@@ -78,7 +139,19 @@ fn main() {
         }
     };
 
-    if let Err(e) = run(&manifest_path, baseline.as_deref(), json, per_repo) {
+    let options = RunOptions {
+        baseline: baseline.as_deref(),
+        lock: lock.as_deref(),
+        diff: diff.as_deref(),
+        json,
+        per_repo,
+        concurrency: concurrency.max(1),
+        timeout: Duration::from_secs(timeout_secs),
+        allow_partial,
+        arch: arch.as_deref(),
+    };
+
+    if let Err(e) = run(&manifest_path, &options) {
         eprintln!("error: {e:#}");
         std::process::exit(2);
     }
@@ -88,27 +161,50 @@ fn main() {
 // Core logic
 // ---------------------------------------------------------------------------
 
-fn run(manifest_path: &str, baseline: Option<&str>, json: bool, per_repo: bool) -> Result<()> {
+fn run(manifest_path: &str, options: &RunOptions<'_>) -> Result<()> {
     let manifest: Manifest = serde_json::from_str(
         &std::fs::read_to_string(manifest_path)
             .with_context(|| format!("reading {manifest_path}"))?,
     )
     .context("parsing manifest JSON")?;
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .context("building HTTP client")?;
+    // Read the lockfile up front so a bad path fails before any network work
+    let previous_lock = options.diff.map(Lockfile::load).transpose()?;
+
+    let checker = RepoChecker::new(http::client(options.timeout)?);
+
+    let urls: Vec<RepoUrls> = manifest
+        .repos
+        .iter()
+        .map(|repo| manifest.repo_urls(repo, options.arch))
+        .collect();
+    let outcomes = checker.check_all(&manifest.repos, &urls, options.concurrency);
 
     let mut all: BTreeMap<String, Vec<PackageVersion>> = BTreeMap::new();
+    let mut lock = Lockfile::default();
     let mut repo_hashes: BTreeMap<String, String> = BTreeMap::new();
+    let mut failures: BTreeMap<String, String> = BTreeMap::new();
 
-    for repo in &manifest.repos {
-        eprintln!("repo: {} ({})", repo.name, repo.baseurl);
-        let tracked: HashSet<&str> = repo.packages.iter().map(|s| s.as_str()).collect();
+    for ((repo, url), outcome) in manifest.repos.iter().zip(&urls).zip(outcomes) {
+        eprintln!("repo: {} ({})", repo.name, url.baseurl);
+        for line in &outcome.log {
+            eprintln!("  {line}");
+        }
 
-        let versions = check_repo(&client, repo, &tracked)
-            .with_context(|| format!("checking repo '{}'", repo.name))?;
+        let check = match outcome.result {
+            Ok(check) => check,
+            Err(e) => {
+                eprintln!("  failed (see summary below)");
+                failures.insert(repo.name.clone(), format!("{e:#}"));
+                continue;
+            }
+        };
+
+        let versions = check.versions;
+        if let Some(fingerprint) = check.key_fingerprint {
+            eprintln!("  signing key {fingerprint}");
+            lock.keys.insert(repo.name.clone(), fingerprint);
+        }
 
         // Warn about tracked packages not found in repo
         let found_names: HashSet<&str> = versions.iter().map(|p| p.name.as_str()).collect();
@@ -118,22 +214,27 @@ fn run(manifest_path: &str, baseline: Option<&str>, json: bool, per_repo: bool)
             }
         }
 
-        let mut sorted_versions = versions.clone();
-        sorted_versions.sort();
-
-        let mut repo_hasher = Sha256::new();
-        for pv in &sorted_versions {
-            repo_hasher.update(format!(
-                "{}\t{}\t{}\t{}\n",
-                pv.name, pv.epoch, pv.version, pv.release
-            ));
-        }
-        let repo_hash = format!("{:x}", repo_hasher.finalize());
-        repo_hashes.insert(repo.name.clone(), repo_hash);
+        repo_hashes.insert(repo.name.clone(), repo_hash(&versions));
 
         for pv in &versions {
             eprintln!("  {} {}-{}", pv.name, pv.version, pv.release);
             all.entry(pv.name.clone()).or_default().push(pv.clone());
+            lock.record(&pv.name, pv.locked(&repo.name));
+        }
+    }
+
+    if !failures.is_empty() {
+        eprintln!();
+        eprintln!(
+            "{} of {} repo(s) failed:",
+            failures.len(),
+            manifest.repos.len()
+        );
+        for (name, error) in &failures {
+            eprintln!("  {name}: {error}");
+        }
+        if !options.allow_partial {
+            bail!("{} repo(s) could not be checked", failures.len());
         }
     }
 
@@ -142,37 +243,62 @@ fn run(manifest_path: &str, baseline: Option<&str>, json: bool, per_repo: bool)
         v.sort();
     }
 
-    // Hash tracked package versions
-    let mut hasher = Sha256::new();
-    for (name, versions) in &all {
-        for pv in versions {
-            hasher.update(format!(
-                "{}\t{}\t{}\t{}\n",
-                name, pv.epoch, pv.version, pv.release
-            ));
-        }
-    }
-    let hash = format!("{:x}", hasher.finalize());
+    // Hash the locked (newest per package) versions
+    let hash = lock.hash();
+    let baseline = options.baseline;
     let changed = baseline.map(|b| b != hash);
 
+    if let Some(path) = options.lock {
+        lock.save(path)?;
+        eprintln!("wrote lockfile {path}");
+    }
+
+    // Packages from failed repos are unknown, not removed
+    let failed_repos: BTreeSet<String> = failures.keys().cloned().collect();
+    let previous_lock = previous_lock.map(|previous| previous.without_repos(&failed_repos));
+    let changes = previous_lock.as_ref().map(|previous| previous.diff(&lock));
+    let key_changes = previous_lock
+        .as_ref()
+        .map(|previous| previous.key_changes(&lock));
+
     // Output
-    if json {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "hash": hash,
-                "changed": changed.unwrap_or(false),
-                "packages": all,
-                "repo_hashes": repo_hashes,
-            }))?
-        );
+    if options.json {
+        let mut report = serde_json::json!({
+            "hash": hash,
+            "changed": changed.unwrap_or(false),
+            "packages": all,
+            "repo_hashes": repo_hashes,
+            "failed_repos": failures,
+        });
+        if let Some(changes) = &changes {
+            report["changes"] = serde_json::to_value(changes)?;
+        }
+        if let Some(key_changes) = &key_changes {
+            report["key_changed"] = (!key_changes.is_empty()).into();
+            report["key_changes"] = serde_json::to_value(key_changes)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        if per_repo {
+        if options.per_repo {
             for (repo_name, repo_hash) in &repo_hashes {
                 println!("{}={}", cache_arg_name(repo_name), repo_hash);
             }
         }
         println!("{hash}");
+        if let Some(changes) = &changes {
+            if changes.is_empty() {
+                eprintln!("no package changes since {}", options.diff.unwrap());
+            } else {
+                eprintln!("{} package change(s):", changes.len());
+                for change in changes {
+                    eprintln!("  {change}");
+                }
+            }
+        }
+        for change in key_changes.iter().flatten() {
+            eprintln!("warning: signing key rotated for repo '{}'", change.repo);
+            eprintln!("  {change}");
+        }
         match changed {
             Some(true) => {
                 eprintln!("changed (was: {})", baseline.unwrap());
@@ -183,195 +309,15 @@ fn run(manifest_path: &str, baseline: Option<&str>, json: bool, per_repo: bool)
         }
     }
 
-    Ok(())
-}
-
-// ---------------------------------------------------------------------------
-// Repo checking
-// ---------------------------------------------------------------------------
-
-fn check_repo(
-    client: &reqwest::blocking::Client,
-    repo: &RepoEntry,
-    tracked: &HashSet<&str>,
-) -> Result<Vec<PackageVersion>> {
-    let baseurl = expand_repo_url(&repo.baseurl);
-
-    // 1. Fetch repomd.xml to discover primary.xml.gz location
-    let repomd_url = format!("{}/repodata/repomd.xml", baseurl.trim_end_matches('/'));
-    let repomd_body = client
-        .get(&repomd_url)
-        .send()
-        .and_then(|r| r.error_for_status())
-        .and_then(|r| r.text())
-        .with_context(|| format!("fetching {repomd_url}"))?;
-
-    let primary_href =
-        find_primary_href(&repomd_body).context("finding primary.xml.gz in repomd.xml")?;
-
-    // 2. Fetch and decompress primary.xml.gz
-    let primary_url = format!("{}/{}", baseurl.trim_end_matches('/'), primary_href);
-    eprintln!("  fetching {primary_url}");
-
-    let compressed = client
-        .get(&primary_url)
-        .send()
-        .and_then(|r| r.error_for_status())
-        .and_then(|r| r.bytes())
-        .with_context(|| format!("fetching {primary_url}"))?;
-
-    let mut xml = String::new();
-    GzDecoder::new(&compressed[..])
-        .read_to_string(&mut xml)
-        .context("decompressing primary.xml.gz")?;
-
-    // 3. Parse for tracked packages
-    parse_packages(&xml, tracked).context("parsing primary.xml")
-}
-
-// ---------------------------------------------------------------------------
-// repomd.xml parser — find the <location href="..."> for type="primary"
-// ---------------------------------------------------------------------------
-
-fn find_primary_href(repomd_xml: &str) -> Result<String> {
-    let mut reader = Reader::from_str(repomd_xml);
-    reader.config_mut().trim_text(true);
-
-    let mut in_primary = false;
-
-    loop {
-        match reader.read_event()? {
-            Event::Start(ref e) | Event::Empty(ref e) => {
-                let local = tag_local(e.name());
-
-                if local == "data" {
-                    for attr in e.attributes() {
-                        let attr = attr?;
-                        if attr.key.as_ref() == b"type" && attr.value.as_ref() == b"primary" {
-                            in_primary = true;
-                        }
-                    }
-                }
-
-                if in_primary && local == "location" {
-                    for attr in e.attributes() {
-                        let attr = attr?;
-                        if attr.key.as_ref() == b"href" {
-                            return Ok(std::str::from_utf8(&attr.value)?.to_string());
-                        }
-                    }
-                }
-            }
-            Event::End(ref e) => {
-                if tag_local(e.name()) == "data" {
-                    in_primary = false;
-                }
-            }
-            Event::Eof => break,
-            _ => {}
-        }
+    if changes.as_ref().is_some_and(|c| !c.is_empty())
+        || key_changes.as_ref().is_some_and(|c| !c.is_empty())
+    {
+        std::process::exit(1);
     }
 
-    bail!("no <data type=\"primary\"> found in repomd.xml")
-}
-
-// ---------------------------------------------------------------------------
-// primary.xml parser — extract (name, epoch, version, release) for tracked pkgs
-// ---------------------------------------------------------------------------
-
-fn parse_packages(xml: &str, tracked: &HashSet<&str>) -> Result<Vec<PackageVersion>> {
-    let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
-
-    let mut results = Vec::new();
-    let mut in_package = false;
-    let mut current_name = String::new();
-    let mut reading_name = false;
-
-    loop {
-        match reader.read_event()? {
-            Event::Start(ref e) => {
-                let local = tag_local(e.name());
-
-                if local == "package" {
-                    in_package = true;
-                    current_name.clear();
-                } else if in_package && local == "name" {
-                    reading_name = true;
-                }
-            }
-            Event::Empty(ref e) => {
-                let local = tag_local(e.name());
-
-                if in_package && local == "version" && tracked.contains(current_name.as_str()) {
-                    let mut epoch = String::from("0");
-                    let mut ver = String::new();
-                    let mut rel = String::new();
-
-                    for attr in e.attributes() {
-                        let attr = attr?;
-                        match std::str::from_utf8(attr.key.as_ref())? {
-                            "epoch" => epoch = std::str::from_utf8(&attr.value)?.to_string(),
-                            "ver" => ver = std::str::from_utf8(&attr.value)?.to_string(),
-                            "rel" => rel = std::str::from_utf8(&attr.value)?.to_string(),
-                            _ => {}
-                        }
-                    }
-
-                    results.push(PackageVersion {
-                        name: current_name.clone(),
-                        epoch,
-                        version: ver,
-                        release: rel,
-                    });
-                }
-            }
-            Event::Text(ref e) => {
-                if reading_name {
-                    current_name = e.unescape()?.to_string();
-                    reading_name = false;
-                }
-            }
-            Event::End(ref e) => {
-                if tag_local(e.name()) == "package" {
-                    in_package = false;
-                }
-            }
-            Event::Eof => break,
-            _ => {}
-        }
+    if !failures.is_empty() {
+        std::process::exit(EXIT_PARTIAL);
     }
 
-    Ok(results)
-}
-
-// ---------------------------------------------------------------------------
-// Helpers
-// ---------------------------------------------------------------------------
-
-/// Extract the local tag name as a String from a quick-xml element.
-fn tag_local(name: quick_xml::name::QName<'_>) -> String {
-    let raw = std::str::from_utf8(name.as_ref()).unwrap_or("");
-    local_name(raw).to_string()
-}
-
-/// Strip namespace prefix: "repo:data" → "data", "data" → "data"
-fn local_name(tag: &str) -> &str {
-    tag.rsplit_once(':').map_or(tag, |(_, local)| local)
-}
-
-/// Convert a repo name to a Dockerfile ARG name for cache busting.
-/// e.g. "microsoft-edge" -> "CACHE_EPOCH_MICROSOFT_EDGE"
-fn cache_arg_name(repo_name: &str) -> String {
-    let sanitized: String = repo_name
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() {
-                c.to_ascii_uppercase()
-            } else {
-                '_'
-            }
-        })
-        .collect();
-    format!("CACHE_EPOCH_{sanitized}")
+    Ok(())
 }
@@ -0,0 +1,783 @@
+//! Fetching repo metadata and extracting tracked package versions.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::http::{self, Fetch};
+use crate::lockfile::{LockedPackage, Lockfile};
+use crate::{gpgkey, Manifest, RepoEntry, RepoUrls};
+
+// ---------------------------------------------------------------------------
+// Package version (from primary.xml)
+// ---------------------------------------------------------------------------
+
+/// A tracked package's EVR as published in a repo's primary.xml.
+#[derive(Debug, Clone, Serialize, Ord, PartialOrd, Eq, PartialEq)]
+pub struct PackageVersion {
+    pub name: String,
+    pub epoch: String,
+    pub version: String,
+    pub release: String,
+    /// The package's `<location href>`, prefixed with its `xml:base` when
+    /// the repo serves packages from another host.
+    pub href: String,
+}
+
+impl PackageVersion {
+    /// This version as a lockfile entry from `repo`.
+    pub fn locked(&self, repo: &str) -> LockedPackage {
+        LockedPackage {
+            epoch: self.epoch.clone(),
+            version: self.version.clone(),
+            release: self.release.clone(),
+            repo: repo.to_string(),
+            href: Some(self.href.clone()),
+        }
+    }
+}
+
+/// Hash a repo's tracked package versions, independent of the order the
+/// repo lists them in.
+///
+/// This is the per-repo value behind the Containerfile's
+/// `CACHE_EPOCH_<REPO>` build args.
+pub fn repo_hash(versions: &[PackageVersion]) -> String {
+    let mut sorted = versions.to_vec();
+    sorted.sort();
+
+    let mut hasher = Sha256::new();
+    for pv in &sorted {
+        hasher.update(format!(
+            "{}\t{}\t{}\t{}\n",
+            pv.name, pv.epoch, pv.version, pv.release
+        ));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash the newest version of each package across all repos.
+///
+/// This is the overall hash rpmcheck prints, the same as the `--lock`
+/// lockfile's.
+pub fn hash_versions(versions: &BTreeMap<String, Vec<PackageVersion>>) -> String {
+    let mut lock = Lockfile::default();
+    for (name, found) in versions {
+        for pv in found {
+            lock.record(name, pv.locked(""));
+        }
+    }
+    lock.hash()
+}
+
+/// What a repo check found.
+#[derive(Debug)]
+pub struct RepoCheck {
+    pub versions: Vec<PackageVersion>,
+    /// The signing key's fingerprint, when the repo has a `gpg_key_url`.
+    pub key_fingerprint: Option<String>,
+}
+
+/// Result of checking a single repo, with log lines buffered so output stays
+/// in manifest order regardless of which fetch finishes first.
+pub struct RepoOutcome {
+    pub log: Vec<String>,
+    pub result: Result<RepoCheck>,
+}
+
+/// Checks repos for their tracked packages' versions over a [`Fetch`]
+/// client, usually a `reqwest::blocking::Client`.
+pub struct RepoChecker<F> {
+    client: F,
+}
+
+impl<F: Fetch> RepoChecker<F> {
+    pub fn new(client: F) -> Self {
+        Self { client }
+    }
+
+    /// The versions of `tracked` packages published at the repo's expanded
+    /// `urls` (see [`Manifest::repo_urls`]), and its key's fingerprint.
+    pub fn check(
+        &self,
+        repo: &RepoEntry,
+        urls: &RepoUrls,
+        tracked: &HashSet<&str>,
+    ) -> Result<RepoCheck> {
+        self.check_logged(repo, urls, tracked, &mut Vec::new())
+    }
+
+    /// Like [`check`](Self::check), logging fetched URLs into `log`.
+    pub fn check_logged(
+        &self,
+        repo: &RepoEntry,
+        urls: &RepoUrls,
+        tracked: &HashSet<&str>,
+        log: &mut Vec<String>,
+    ) -> Result<RepoCheck> {
+        let versions = self.check_versions(repo, &urls.baseurl, tracked, log)?;
+
+        // A key that can't be fetched fails the repo rather than dropping
+        // out of the hash, which would look like a change of its own
+        let key_fingerprint = match &urls.gpg_key {
+            Some(key_url) => {
+                log.push(format!("fetching {key_url}"));
+                let armored = self
+                    .client
+                    .get_text(key_url)
+                    .with_context(|| format!("fetching {key_url}"))?;
+                Some(
+                    gpgkey::fingerprint(&armored)
+                        .with_context(|| format!("reading signing key {key_url}"))?,
+                )
+            }
+            None => None,
+        };
+
+        Ok(RepoCheck {
+            versions,
+            key_fingerprint,
+        })
+    }
+
+    fn check_versions(
+        &self,
+        repo: &RepoEntry,
+        baseurl: &str,
+        tracked: &HashSet<&str>,
+        log: &mut Vec<String>,
+    ) -> Result<Vec<PackageVersion>> {
+        // 1. Fetch repomd.xml to discover the primary.xml location
+        let repomd_url = format!("{}/repodata/repomd.xml", baseurl.trim_end_matches('/'));
+        let repomd_body = self
+            .client
+            .get_text(&repomd_url)
+            .with_context(|| format!("fetching {repomd_url}"))?;
+
+        let primary = find_primary_href(&repomd_body).with_context(|| {
+            format!("finding primary.xml in repomd.xml for repo '{}'", repo.name)
+        })?;
+
+        // 2. Fetch and decompress primary.xml
+        let primary_url = format!("{}/{}", baseurl.trim_end_matches('/'), primary.href);
+        log.push(format!("fetching {primary_url}"));
+
+        let compressed = self
+            .client
+            .get(&primary_url)
+            .with_context(|| format!("fetching {primary_url}"))?;
+
+        let xml = primary
+            .compression
+            .decompress(&compressed)
+            .with_context(|| format!("decompressing {}", primary.href))?;
+
+        // 3. Parse for tracked packages
+        parse_packages(&xml, tracked).context("parsing primary.xml")
+    }
+
+    /// Check all repos using up to `concurrency` worker threads.
+    ///
+    /// `urls` are the repos' expanded URLs. Outcomes are returned in the
+    /// same order as `repos`.
+    pub fn check_all(
+        &self,
+        repos: &[RepoEntry],
+        urls: &[RepoUrls],
+        concurrency: usize,
+    ) -> Vec<RepoOutcome> {
+        let next = AtomicUsize::new(0);
+        let slots: Mutex<Vec<Option<RepoOutcome>>> =
+            Mutex::new(repos.iter().map(|_| None).collect());
+
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency.min(repos.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(repo) = repos.get(index) else {
+                        break;
+                    };
+
+                    let tracked: HashSet<&str> = repo.packages.iter().map(|s| s.as_str()).collect();
+                    let mut log = Vec::new();
+                    let result = self
+                        .check_logged(repo, &urls[index], &tracked, &mut log)
+                        .with_context(|| format!("checking repo '{}'", repo.name));
+
+                    slots.lock().unwrap()[index] = Some(RepoOutcome { log, result });
+                });
+            }
+        });
+
+        slots
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.expect("every repo is checked by a worker"))
+            .collect()
+    }
+}
+
+/// Check every repo in `manifest` for its tracked package versions.
+///
+/// Returns `(repo name, versions or error)` in manifest order; one repo
+/// failing doesn't stop the others.
+pub fn repo_versions(
+    manifest: &Manifest,
+    arch: Option<&str>,
+    concurrency: usize,
+    timeout: Duration,
+) -> Result<Vec<(String, Result<Vec<PackageVersion>>)>> {
+    let checker = RepoChecker::new(http::client(timeout)?);
+    let urls: Vec<RepoUrls> = manifest
+        .repos
+        .iter()
+        .map(|repo| manifest.repo_urls(repo, arch))
+        .collect();
+
+    let outcomes = checker.check_all(&manifest.repos, &urls, concurrency);
+    Ok(manifest
+        .repos
+        .iter()
+        .zip(outcomes)
+        .map(|(repo, outcome)| {
+            let versions = outcome.result.map(|check| check.versions);
+            (repo.name.clone(), versions)
+        })
+        .collect())
+}
+
+/// Check every repo in `manifest` and hash its tracked package versions.
+///
+/// Returns `(repo name, hash or error)` in manifest order; one repo failing
+/// doesn't stop the others.
+pub fn repo_hashes(
+    manifest: &Manifest,
+    arch: Option<&str>,
+    concurrency: usize,
+    timeout: Duration,
+) -> Result<Vec<(String, Result<String>)>> {
+    Ok(repo_versions(manifest, arch, concurrency, timeout)?
+        .into_iter()
+        .map(|(name, result)| (name, result.map(|v| repo_hash(&v))))
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// repomd.xml parser — find the <location href="..."> for type="primary"
+// ---------------------------------------------------------------------------
+
+/// Compression of a metadata file, derived from its href extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    fn from_href(href: &str) -> Result<Self> {
+        match href.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("gz") => Ok(Self::Gzip),
+            Some("xz") => Ok(Self::Xz),
+            Some("zst" | "zstd") => Ok(Self::Zstd),
+            Some("xml") => Ok(Self::None),
+            _ => bail!("unsupported compression for {href} (expected .gz, .xz or .zst)"),
+        }
+    }
+
+    fn decompress(self, bytes: &[u8]) -> Result<String> {
+        let mut xml = String::new();
+        match self {
+            Self::None => {
+                xml = std::str::from_utf8(bytes)?.to_string();
+            }
+            Self::Gzip => {
+                GzDecoder::new(bytes).read_to_string(&mut xml)?;
+            }
+            Self::Xz => {
+                let mut out = Vec::new();
+                lzma_rs::xz_decompress(&mut std::io::BufReader::new(bytes), &mut out)
+                    .map_err(|e| anyhow::anyhow!("xz: {e}"))?;
+                xml = String::from_utf8(out)?;
+            }
+            Self::Zstd => {
+                zstd::stream::read::Decoder::new(bytes)?.read_to_string(&mut xml)?;
+            }
+        }
+        Ok(xml)
+    }
+}
+
+/// Where primary.xml lives, relative to the repo baseurl.
+#[derive(Debug, PartialEq, Eq)]
+struct PrimaryLocation {
+    href: String,
+    compression: Compression,
+}
+
+fn find_primary_href(repomd_xml: &str) -> Result<PrimaryLocation> {
+    let mut reader = Reader::from_str(repomd_xml);
+    reader.config_mut().trim_text(true);
+
+    let mut current_type: Option<String> = None;
+    let mut data_types = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let local = tag_local(e.name());
+
+                if local == "data" {
+                    for attr in e.attributes() {
+                        let attr = attr?;
+                        if attr.key.as_ref() == b"type" {
+                            let data_type = std::str::from_utf8(&attr.value)?.to_string();
+                            data_types.push(data_type.clone());
+                            current_type = Some(data_type);
+                        }
+                    }
+                }
+
+                if current_type.as_deref() == Some("primary") && local == "location" {
+                    for attr in e.attributes() {
+                        let attr = attr?;
+                        if attr.key.as_ref() == b"href" {
+                            let href = std::str::from_utf8(&attr.value)?.to_string();
+                            let compression = Compression::from_href(&href)?;
+                            return Ok(PrimaryLocation { href, compression });
+                        }
+                    }
+                }
+            }
+            Event::End(ref e) if tag_local(e.name()) == "data" => {
+                current_type = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if data_types.iter().any(|t| t == "primary_db") {
+        bail!(
+            "repo only publishes sqlite metadata (primary_db), which is not supported; \
+             available metadata: {}",
+            data_types.join(", ")
+        );
+    }
+    if data_types.is_empty() {
+        bail!("no <data type=\"primary\"> found in repomd.xml");
+    }
+    bail!(
+        "no <data type=\"primary\"> found in repomd.xml; available metadata: {}",
+        data_types.join(", ")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// primary.xml parser — extract (name, epoch, version, release, href) for
+// tracked pkgs
+// ---------------------------------------------------------------------------
+
+fn parse_packages(xml: &str, tracked: &HashSet<&str>) -> Result<Vec<PackageVersion>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut results = Vec::new();
+    let mut in_package = false;
+    let mut current_name = String::new();
+    let mut reading_name = false;
+    // <version> comes before <location>, so the package is completed there
+    let mut pending: Option<PackageVersion> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) => {
+                let local = tag_local(e.name());
+
+                if local == "package" {
+                    in_package = true;
+                    current_name.clear();
+                    pending = None;
+                } else if in_package && local == "name" {
+                    reading_name = true;
+                }
+            }
+            Event::Empty(ref e) => {
+                let local = tag_local(e.name());
+
+                if in_package && local == "version" && tracked.contains(current_name.as_str()) {
+                    let mut epoch = String::from("0");
+                    let mut ver = String::new();
+                    let mut rel = String::new();
+
+                    for attr in e.attributes() {
+                        let attr = attr?;
+                        match std::str::from_utf8(attr.key.as_ref())? {
+                            "epoch" => epoch = std::str::from_utf8(&attr.value)?.to_string(),
+                            "ver" => ver = std::str::from_utf8(&attr.value)?.to_string(),
+                            "rel" => rel = std::str::from_utf8(&attr.value)?.to_string(),
+                            _ => {}
+                        }
+                    }
+
+                    pending = Some(PackageVersion {
+                        name: current_name.clone(),
+                        epoch,
+                        version: ver,
+                        release: rel,
+                        href: String::new(),
+                    });
+                } else if in_package && local == "location" {
+                    if let Some(pv) = pending.as_mut() {
+                        let mut base = None;
+                        for attr in e.attributes() {
+                            let attr = attr?;
+                            let value = std::str::from_utf8(&attr.value)?;
+                            match std::str::from_utf8(attr.key.as_ref())? {
+                                "href" => pv.href = value.to_string(),
+                                "xml:base" => base = Some(value.to_string()),
+                                _ => {}
+                            }
+                        }
+                        if let Some(base) = base {
+                            pv.href = format!("{}/{}", base.trim_end_matches('/'), pv.href);
+                        }
+                    }
+                }
+            }
+            Event::Text(ref e) if reading_name => {
+                current_name = e.unescape()?.to_string();
+                reading_name = false;
+            }
+            Event::End(ref e) if tag_local(e.name()) == "package" => {
+                in_package = false;
+                results.extend(pending.take());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(results)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Extract the local tag name as a String from a quick-xml element.
+fn tag_local(name: quick_xml::name::QName<'_>) -> String {
+    let raw = std::str::from_utf8(name.as_ref()).unwrap_or("");
+    local_name(raw).to_string()
+}
+
+/// Strip namespace prefix: "repo:data" → "data", "data" → "data"
+fn local_name(tag: &str) -> &str {
+    tag.rsplit_once(':').map_or(tag, |(_, local)| local)
+}
+
+/// Convert a repo name to a Dockerfile ARG name for cache busting.
+/// e.g. "microsoft-edge" -> "CACHE_EPOCH_MICROSOFT_EDGE"
+pub fn cache_arg_name(repo_name: &str) -> String {
+    let sanitized: String = repo_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("CACHE_EPOCH_{sanitized}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    /// Canned responses by URL; anything else is a 404.
+    struct Fixtures(HashMap<String, Vec<u8>>);
+
+    impl Fetch for Fixtures {
+        fn get(&self, url: &str) -> Result<Vec<u8>> {
+            match self.0.get(url) {
+                Some(body) => Ok(body.clone()),
+                None => bail!("404 Not Found for {url}"),
+            }
+        }
+    }
+
+    fn primary_xml(packages: &[(&str, &str, &str)]) -> String {
+        let packages: String = packages
+            .iter()
+            .map(|(name, ver, rel)| {
+                format!(
+                    "<package type=\"rpm\"><name>{name}</name><arch>x86_64</arch>\
+                     <version epoch=\"0\" ver=\"{ver}\" rel=\"{rel}\"/>\
+                     <location href=\"Packages/{name}-{ver}-{rel}.rpm\"/></package>\n"
+                )
+            })
+            .collect();
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <metadata xmlns=\"http://linux.duke.edu/metadata/common\" \
+             packages=\"{}\">\n{packages}</metadata>\n",
+            packages.len()
+        )
+    }
+
+    fn repo_entry(packages: &[&str]) -> RepoEntry {
+        RepoEntry {
+            name: "vscode".to_string(),
+            baseurl: "https://example.com/yumrepos/vscode".to_string(),
+            packages: packages.iter().map(|p| p.to_string()).collect(),
+            basearch: None,
+            gpg_key_url: None,
+        }
+    }
+
+    fn urls(baseurl: &str, gpg_key: Option<&str>) -> RepoUrls {
+        RepoUrls {
+            baseurl: baseurl.to_string(),
+            gpg_key: gpg_key.map(str::to_string),
+        }
+    }
+
+    fn gzip(xml: &str) -> Vec<u8> {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(xml.as_bytes()).unwrap();
+        gz.finish().unwrap()
+    }
+
+    /// Fixtures for a repo at `base` publishing `packages`.
+    fn repo_fixtures(base: &str, packages: &[(&str, &str, &str)]) -> HashMap<String, Vec<u8>> {
+        HashMap::from([
+            (
+                format!("{base}/repodata/repomd.xml"),
+                repomd(&[("primary", "repodata/abc-primary.xml.gz")]).into_bytes(),
+            ),
+            (
+                format!("{base}/repodata/abc-primary.xml.gz"),
+                gzip(&primary_xml(packages)),
+            ),
+        ])
+    }
+
+    fn repomd(entries: &[(&str, &str)]) -> String {
+        let data: String = entries
+            .iter()
+            .map(|(data_type, href)| {
+                format!(
+                    "  <data type=\"{data_type}\">\n    \
+                     <checksum type=\"sha256\">00</checksum>\n    \
+                     <location href=\"{href}\"/>\n  </data>\n"
+                )
+            })
+            .collect();
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <repomd xmlns=\"http://linux.duke.edu/metadata/repo\" \
+             xmlns:rpm=\"http://linux.duke.edu/metadata/rpm\">\n\
+             <revision>1700000000</revision>\n{data}</repomd>\n"
+        )
+    }
+
+    #[test]
+    fn find_primary_detects_compression() {
+        let cases = [
+            ("repodata/abc-primary.xml.gz", Compression::Gzip),
+            ("repodata/abc-primary.xml.xz", Compression::Xz),
+            ("repodata/abc-primary.xml.zst", Compression::Zstd),
+            ("repodata/primary.xml", Compression::None),
+        ];
+        for (href, compression) in cases {
+            let xml = repomd(&[
+                ("filelists", "repodata/abc-filelists.xml.gz"),
+                ("primary", href),
+                ("primary_db", "repodata/abc-primary.sqlite.bz2"),
+            ]);
+            assert_eq!(
+                find_primary_href(&xml).unwrap(),
+                PrimaryLocation {
+                    href: href.to_string(),
+                    compression,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn find_primary_reports_sqlite_only_repo() {
+        let xml = repomd(&[
+            ("primary_db", "repodata/abc-primary.sqlite.xz"),
+            ("filelists_db", "repodata/abc-filelists.sqlite.xz"),
+        ]);
+        let err = find_primary_href(&xml).unwrap_err().to_string();
+        assert!(err.contains("sqlite"), "{err}");
+        assert!(err.contains("primary_db, filelists_db"), "{err}");
+    }
+
+    #[test]
+    fn find_primary_rejects_unknown_compression() {
+        let xml = repomd(&[("primary", "repodata/abc-primary.xml.bz2")]);
+        let err = find_primary_href(&xml).unwrap_err().to_string();
+        assert!(err.contains("unsupported compression"), "{err}");
+    }
+
+    #[test]
+    fn decompress_each_format() {
+        let xml = "<metadata packages=\"0\"/>";
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(xml.as_bytes()).unwrap();
+        let gz = gz.finish().unwrap();
+
+        let mut xz = Vec::new();
+        lzma_rs::xz_compress(&mut xml.as_bytes(), &mut xz).unwrap();
+
+        let zst = zstd::stream::encode_all(xml.as_bytes(), 0).unwrap();
+
+        assert_eq!(Compression::Gzip.decompress(&gz).unwrap(), xml);
+        assert_eq!(Compression::Xz.decompress(&xz).unwrap(), xml);
+        assert_eq!(Compression::Zstd.decompress(&zst).unwrap(), xml);
+        assert_eq!(Compression::None.decompress(xml.as_bytes()).unwrap(), xml);
+        assert!(Compression::Zstd.decompress(&gz).is_err());
+    }
+
+    #[test]
+    fn repo_hash_ignores_listing_order() {
+        let pv = |name: &str, version: &str| PackageVersion {
+            name: name.to_string(),
+            epoch: "0".to_string(),
+            version: version.to_string(),
+            release: "1".to_string(),
+            href: format!("Packages/{name}.rpm"),
+        };
+        let a = [pv("code", "1.90.0"), pv("code-insiders", "1.91.0")];
+        let b = [pv("code-insiders", "1.91.0"), pv("code", "1.90.0")];
+
+        assert_eq!(repo_hash(&a), repo_hash(&b));
+        assert_eq!(repo_hash(&a).len(), 64);
+        assert_ne!(
+            repo_hash(&a),
+            repo_hash(&[pv("code", "1.90.1"), pv("code-insiders", "1.91.0")])
+        );
+    }
+
+    #[test]
+    fn checker_reads_tracked_versions_from_fixtures() {
+        let base = "https://example.com/yumrepos/vscode";
+        let checker = RepoChecker::new(Fixtures(repo_fixtures(
+            base,
+            &[
+                ("code", "1.90.0", "1"),
+                ("code", "1.91.0", "2"),
+                ("code-insiders", "1.92.0", "1"),
+                ("unrelated", "9.9", "1"),
+            ],
+        )));
+
+        let repo = repo_entry(&["code", "missing"]);
+        let tracked: HashSet<&str> = repo.packages.iter().map(|s| s.as_str()).collect();
+        let mut log = Vec::new();
+        let check = checker
+            .check_logged(&repo, &urls(&format!("{base}/"), None), &tracked, &mut log)
+            .unwrap();
+
+        let found: Vec<(&str, &str, &str)> = check
+            .versions
+            .iter()
+            .map(|pv| (pv.name.as_str(), pv.version.as_str(), pv.href.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("code", "1.90.0", "Packages/code-1.90.0-1.rpm"),
+                ("code", "1.91.0", "Packages/code-1.91.0-2.rpm")
+            ]
+        );
+        assert_eq!(check.key_fingerprint, None);
+        assert_eq!(
+            log,
+            [format!("fetching {base}/repodata/abc-primary.xml.gz")]
+        );
+
+        let by_name = BTreeMap::from([("code".to_string(), check.versions)]);
+        let mut lock = Lockfile::default();
+        lock.record(
+            "code",
+            LockedPackage {
+                epoch: "0".to_string(),
+                version: "1.91.0".to_string(),
+                release: "2".to_string(),
+                repo: "vscode".to_string(),
+                href: None,
+            },
+        );
+        assert_eq!(hash_versions(&by_name), lock.hash());
+    }
+
+    #[test]
+    fn location_base_is_part_of_the_href() {
+        let xml = "<metadata><package type=\"rpm\"><name>code</name>\
+                   <version epoch=\"1\" ver=\"1.90.0\" rel=\"1\"/>\
+                   <location xml:base=\"https://cdn.example.com/rpms/\" href=\"code.rpm\"/>\
+                   </package></metadata>";
+        let versions = parse_packages(xml, &HashSet::from(["code"])).unwrap();
+        assert_eq!(versions[0].epoch, "1");
+        assert_eq!(versions[0].href, "https://cdn.example.com/rpms/code.rpm");
+    }
+
+    #[test]
+    fn checker_fingerprints_the_signing_key() {
+        let base = "https://example.com/yumrepos/vscode";
+        let key_url = "https://example.com/keys/microsoft.asc";
+        let key = "-----BEGIN PGP PUBLIC KEY BLOCK-----\n\nbWF0ZXJpYWw=\n=AAAA\n\
+                   -----END PGP PUBLIC KEY BLOCK-----\n";
+        let mut fixtures = repo_fixtures(base, &[("code", "1.90.0", "1")]);
+        fixtures.insert(key_url.to_string(), key.as_bytes().to_vec());
+        let checker = RepoChecker::new(Fixtures(fixtures));
+
+        let repo = repo_entry(&["code"]);
+        let tracked = HashSet::from(["code"]);
+        let check = checker
+            .check(&repo, &urls(base, Some(key_url)), &tracked)
+            .unwrap();
+        assert_eq!(
+            check.key_fingerprint,
+            Some(gpgkey::fingerprint(key).unwrap())
+        );
+
+        let missing = urls(base, Some("https://example.com/keys/gone.asc"));
+        let err = format!(
+            "{:#}",
+            checker.check(&repo, &missing, &tracked).unwrap_err()
+        );
+        assert!(err.contains("gone.asc"), "{err}");
+    }
+
+    #[test]
+    fn checker_reports_unreachable_repo() {
+        let checker = RepoChecker::new(Fixtures(HashMap::new()));
+        let repo = repo_entry(&["code"]);
+        let outcomes =
+            checker.check_all(std::slice::from_ref(&repo), &[urls(&repo.baseurl, None)], 2);
+        let err = format!("{:#}", outcomes[0].result.as_ref().unwrap_err());
+        assert!(err.contains("checking repo 'vscode'"), "{err}");
+        assert!(err.contains("repomd.xml"), "{err}");
+    }
+}
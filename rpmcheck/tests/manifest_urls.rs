@@ -1,4 +1,4 @@
-use rpmcheck::{expand_repo_url, Manifest};
+use rpmcheck::Manifest;
 
 #[test]
 fn manifest_repo_urls_are_reachable() {
@@ -21,7 +21,7 @@ fn manifest_repo_urls_are_reachable() {
         .expect("building HTTP client");
 
     for repo in &manifest.repos {
-        let url = expand_repo_url(&repo.baseurl);
+        let url = manifest.repo_url(repo, None);
         let repomd_url = format!("{}/repodata/repomd.xml", url.trim_end_matches('/'));
 
         eprintln!("  {}: HEAD {repomd_url}", repo.name);